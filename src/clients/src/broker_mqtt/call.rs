@@ -16,7 +16,8 @@ use crate::poll::ClientPool;
 use common_base::error::common::CommonError;
 use prost::Message as _;
 use protocol::broker_server::generate::mqtt::{
-    CommonReply, DeleteSessionRequest, SendLastWillMessageRequest, UpdateCacheRequest
+    CommonReply, DeleteSessionRequest, ListGroupOffsetReply, ListGroupOffsetRequest,
+    ResetGroupOffsetReply, ResetGroupOffsetRequest, SendLastWillMessageRequest, UpdateCacheRequest,
 };
 use std::sync::Arc;
 
@@ -97,3 +98,53 @@ pub async fn send_last_will_message(
         }
     }
 }
+
+pub async fn broker_mqtt_list_group_offset(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: ListGroupOffsetRequest,
+) -> Result<ListGroupOffsetReply, CommonError> {
+    let request_data = ListGroupOffsetRequest::encode_to_vec(&request);
+    match retry_call(
+        MQTTBrokerService::Mqtt,
+        MQTTBrokerInterface::ListGroupOffset,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match ListGroupOffsetReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn broker_mqtt_reset_group_offset(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: ResetGroupOffsetRequest,
+) -> Result<ResetGroupOffsetReply, CommonError> {
+    let request_data = ResetGroupOffsetRequest::encode_to_vec(&request);
+    match retry_call(
+        MQTTBrokerService::Mqtt,
+        MQTTBrokerInterface::ResetGroupOffset,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match ResetGroupOffsetReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
@@ -16,6 +16,7 @@ use common_base::error::common::CommonError;
 use prost::Message;
 use protocol::broker_server::generate::mqtt::{
     mqtt_broker_service_client::MqttBrokerServiceClient, CommonReply, DeleteSessionRequest,
+    ListGroupOffsetReply, ListGroupOffsetRequest, ResetGroupOffsetReply, ResetGroupOffsetRequest,
     SendLastWillMessageRequest, UpdateCacheRequest,
 };
 use tonic::transport::Channel;
@@ -70,3 +71,37 @@ pub(crate) async fn inner_send_last_will_message(
         }
     }
 }
+
+pub(crate) async fn inner_list_group_offset(
+    mut client: MqttBrokerServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match ListGroupOffsetRequest::decode(request.as_ref()) {
+        Ok(request) => match client.list_group_offset(request).await {
+            Ok(result) => {
+                return Ok(ListGroupOffsetReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_reset_group_offset(
+    mut client: MqttBrokerServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match ResetGroupOffsetRequest::decode(request.as_ref()) {
+        Ok(request) => match client.reset_group_offset(request).await {
+            Ok(result) => {
+                return Ok(ResetGroupOffsetReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
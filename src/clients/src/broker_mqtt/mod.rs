@@ -13,7 +13,10 @@
 
 use crate::{poll::ClientPool, retry_sleep_time, retry_times};
 use common_base::error::common::CommonError;
-use inner::{inner_delete_session, inner_send_last_will_message, inner_update_cache};
+use inner::{
+    inner_delete_session, inner_list_group_offset, inner_reset_group_offset,
+    inner_send_last_will_message, inner_update_cache,
+};
 use log::error;
 use mobc::Manager;
 use protocol::broker_server::generate::mqtt::mqtt_broker_service_client::MqttBrokerServiceClient;
@@ -31,6 +34,8 @@ pub enum MQTTBrokerInterface {
     DeleteSession,
     UpdateCache,
     SendLastWillMessage,
+    ListGroupOffset,
+    ResetGroupOffset,
 }
 
 pub mod call;
@@ -108,6 +113,12 @@ pub(crate) async fn mqtt_interface_call(
                 MQTTBrokerInterface::SendLastWillMessage => {
                     inner_send_last_will_message(client, request).await
                 }
+                MQTTBrokerInterface::ListGroupOffset => {
+                    inner_list_group_offset(client, request).await
+                }
+                MQTTBrokerInterface::ResetGroupOffset => {
+                    inner_reset_group_offset(client, request).await
+                }
             };
             match result {
                 Ok(data) => return Ok(data),
@@ -0,0 +1,693 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::connection::JournalConnectionPool;
+use crate::{
+    placement::{
+        journal::call::get_shard,
+        kv::call::placement_compare_and_swap,
+        placement::call::{delete_idempotent_data, exists_idempotent_data, set_idempotent_data},
+    },
+    poll::ClientPool,
+};
+use common_base::error::{common::CommonError, journal_server::JournalServerError};
+use dashmap::DashMap;
+use futures::future::join_all;
+use log::warn;
+use protocol::{
+    journal_server::{
+        codec::StorageEnginePacket,
+        generate::protocol::{
+            end_txn::{EndTxnReq, EndTxnReqBody},
+            fetch::{FetchReq, FetchReqBody, FetchShard, IsolationLevel, ShardFetchResult},
+            header::{ApiKey, ApiType, ApiVersion, ErrorCode, Header, RequestCommon},
+            metadata::{MetadataReq, MetadataReqBody, MetadataRespBody},
+            produce::{Acks, ProduceReq, ProduceReqBody, ShardData},
+            record::Record,
+        },
+    },
+    placement_center::generate::{
+        journal::GetShardRequest,
+        kv::{CompareAndSwapReply, CompareAndSwapRequest},
+        placement::{
+            DeleteIdempotentDataRequest, ExistsIdempotentDataRequest, SetIdempotentDataRequest,
+        },
+    },
+};
+use std::{sync::Arc, time::Duration};
+use tokio::time::sleep;
+
+#[derive(Clone, Debug)]
+pub struct JournalClientConfig {
+    pub cluster_name: String,
+    pub client_id: String,
+    // Placement center addresses used to look up shard leaders.
+    pub placement_center: Vec<String>,
+    // Journal server addresses asked for metadata when the placement center does not
+    // know the leader of a shard.
+    pub bootstrap_addrs: Vec<String>,
+    // Node id sent with fetches when the client is used by a follower replica.
+    pub replica_id: u64,
+    pub max_connections_per_node: usize,
+    pub request_timeout_ms: u64,
+    // Acks::All waits until the records are on every in-sync replica.
+    pub acks: Acks,
+    pub max_retry_times: usize,
+    pub retry_backoff_ms: u64,
+    // ReadCommitted only returns the records of committed transactions.
+    pub isolation_level: IsolationLevel,
+}
+
+// Identifies the records of an idempotent producer, so that the server writes the records
+// of a retried request only once. A non-zero transactional id makes the records part of
+// the ongoing transaction of the producer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProducerIdentity {
+    pub producer_id: u64,
+    pub producer_epoch: u32,
+    pub transactional_id: u32,
+}
+
+impl Default for JournalClientConfig {
+    fn default() -> Self {
+        return JournalClientConfig {
+            cluster_name: "".to_string(),
+            client_id: "".to_string(),
+            placement_center: Vec::new(),
+            bootstrap_addrs: Vec::new(),
+            replica_id: 0,
+            max_connections_per_node: 2,
+            request_timeout_ms: 30000,
+            acks: Acks::Leader,
+            max_retry_times: 5,
+            retry_backoff_ms: 100,
+            isolation_level: IsolationLevel::ReadUncommitted,
+        };
+    }
+}
+
+// Client of the journal servers of a cluster. Requests of a shard are sent to its leader,
+// which is looked up once and cached until a request fails because the leader moved.
+pub struct JournalClient {
+    config: JournalClientConfig,
+    client_poll: Arc<ClientPool>,
+    connections: JournalConnectionPool,
+    leaders: DashMap<String, String>,
+}
+
+impl JournalClient {
+    pub fn new(config: JournalClientConfig, client_poll: Arc<ClientPool>) -> Self {
+        let connections = JournalConnectionPool::new(config.max_connections_per_node);
+        return JournalClient {
+            config,
+            client_poll,
+            connections,
+            leaders: DashMap::with_capacity(64),
+        };
+    }
+
+    // Appends the records to the shard and returns their offsets.
+    pub async fn produce(
+        &self,
+        shard_name: &str,
+        records: Vec<Record>,
+    ) -> Result<Vec<u64>, CommonError> {
+        return self
+            .produce_idempotent(shard_name, records, &ProducerIdentity::default(), 0)
+            .await;
+    }
+
+    // Appends the records of an idempotent producer, base_sequence being the sequence
+    // number of the first record on the shard. Retries of the request are only written
+    // once, they return the offsets of the records written the first time.
+    pub async fn produce_idempotent(
+        &self,
+        shard_name: &str,
+        records: Vec<Record>,
+        producer: &ProducerIdentity,
+        base_sequence: u32,
+    ) -> Result<Vec<u64>, CommonError> {
+        let body = ProduceReqBody {
+            transactional_id: producer.transactional_id,
+            acks: self.config.acks.into(),
+            timeout_ms: self.config.request_timeout_ms as u32,
+            shards: vec![ShardData {
+                shard_name: shard_name.to_string(),
+                records,
+                base_sequence,
+            }],
+            producer_id: producer.producer_id,
+            producer_epoch: producer.producer_epoch,
+        };
+        let mut times = 0;
+        loop {
+            times = times + 1;
+            let result = self.produce_once(shard_name, body.clone()).await;
+            match result {
+                Ok(offsets) => return Ok(offsets),
+                Err(e) => {
+                    if !self.retry_after(shard_name, &e, times).await {
+                        return Err(e);
+                    }
+                }
+            }
+        }
+    }
+
+    // Reads records of the shard from offset. When nothing is available the request is
+    // held by the server for up to max_wait_ms.
+    pub async fn fetch(
+        &self,
+        shard_name: &str,
+        offset: u64,
+        max_record_num: u32,
+        max_wait_ms: u32,
+    ) -> Result<ShardFetchResult, CommonError> {
+        let body = FetchReqBody {
+            max_wait_ms,
+            shards: vec![FetchShard {
+                shard_name: shard_name.to_string(),
+                offset,
+                max_record_num,
+                ..Default::default()
+            }],
+            replica_id: self.config.replica_id,
+            isolation_level: self.config.isolation_level.into(),
+            ..Default::default()
+        };
+        let mut times = 0;
+        loop {
+            times = times + 1;
+            let result = self.fetch_once(shard_name, body.clone()).await;
+            match result {
+                Ok(shard) => return Ok(shard),
+                Err(e) => {
+                    if !self.retry_after(shard_name, &e, times).await {
+                        return Err(e);
+                    }
+                }
+            }
+        }
+    }
+
+    // Commits or aborts the transaction of the producer on the shards, returns once the
+    // markers are on every in-sync replica.
+    pub async fn end_txn(
+        &self,
+        producer: &ProducerIdentity,
+        commit: bool,
+        shards: Vec<String>,
+    ) -> Result<(), CommonError> {
+        let mut results = Vec::new();
+        for shard_name in shards.iter() {
+            results.push(self.end_shard_txn(shard_name, producer, commit));
+        }
+        for result in join_all(results).await {
+            result?;
+        }
+        return Ok(());
+    }
+
+    // Gives out the producer id of producer_name and a newer epoch on every call, both are
+    // kept in the placement center. A producer restarting under the same name keeps its
+    // id, and its new epoch fences the previous instance on the shards.
+    pub async fn init_producer(
+        &self,
+        producer_name: &str,
+    ) -> Result<ProducerIdentity, CommonError> {
+        if self.config.placement_center.is_empty() {
+            return Err(CommonError::CommmonError(
+                "producer ids are given out by the placement center, none is configured"
+                    .to_string(),
+            ));
+        }
+        let key = format!(
+            "/journal/{}/producer/{}",
+            self.config.cluster_name, producer_name
+        );
+        let mut current = None;
+        let mut expected_revision = 0;
+        for _ in 0..=self.config.max_retry_times {
+            let (producer_id, producer_epoch) = match current {
+                Some((producer_id, producer_epoch)) => (producer_id, producer_epoch + 1),
+                None => (self.next_producer_id().await?, 0),
+            };
+            let value = format!("{}:{}", producer_id, producer_epoch);
+            let reply = self
+                .compare_and_swap(&key, value, expected_revision)
+                .await?;
+            if reply.succeeded {
+                return Ok(ProducerIdentity {
+                    producer_id,
+                    producer_epoch,
+                    transactional_id: 0,
+                });
+            }
+            // Another instance of the producer initialised in between.
+            match reply.current {
+                Some(entry) => {
+                    current = Some(parse_producer(&entry.value)?);
+                    expected_revision = entry.mod_revision;
+                }
+                None => {
+                    current = None;
+                    expected_revision = 0;
+                }
+            }
+        }
+        return Err(CommonError::CommmonError(format!(
+            "producer {} was initialised concurrently too often",
+            producer_name
+        )));
+    }
+
+    // Producer ids start from 1, 0 is the id of records without a producer.
+    async fn next_producer_id(&self) -> Result<u64, CommonError> {
+        let key = format!("/journal/{}/producer_id", self.config.cluster_name);
+        let mut last_id = 0;
+        let mut expected_revision = 0;
+        for _ in 0..=self.config.max_retry_times {
+            let reply = self
+                .compare_and_swap(&key, (last_id + 1).to_string(), expected_revision)
+                .await?;
+            if reply.succeeded {
+                return Ok(last_id + 1);
+            }
+            match reply.current {
+                Some(entry) => {
+                    last_id = match entry.value.parse::<u64>() {
+                        Ok(id) => id,
+                        Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+                    };
+                    expected_revision = entry.mod_revision;
+                }
+                None => {
+                    last_id = 0;
+                    expected_revision = 0;
+                }
+            }
+        }
+        return Err(CommonError::CommmonError(
+            "producer ids were given out concurrently too often".to_string(),
+        ));
+    }
+
+    async fn compare_and_swap(
+        &self,
+        key: &str,
+        value: String,
+        expected_revision: u64,
+    ) -> Result<CompareAndSwapReply, CommonError> {
+        let req = CompareAndSwapRequest {
+            key: key.to_string(),
+            value,
+            expected_revision,
+            lease_id: 0,
+        };
+        return placement_compare_and_swap(
+            self.client_poll.clone(),
+            self.config.placement_center.clone(),
+            req,
+        )
+        .await;
+    }
+
+    // The commit decision of a transaction is kept in the placement center between writing
+    // the first and the last commit marker, so that a producer restarting in between can
+    // finish the commit on the remaining shards instead of aborting them.
+    pub async fn record_txn_commit(&self, producer: &ProducerIdentity) -> Result<(), CommonError> {
+        if self.config.placement_center.is_empty() {
+            return Ok(());
+        }
+        let req = SetIdempotentDataRequest {
+            cluster_name: self.config.cluster_name.clone(),
+            producer_id: txn_producer_key(producer.producer_id),
+            seq_num: producer.transactional_id as u64,
+        };
+        set_idempotent_data(
+            self.client_poll.clone(),
+            self.config.placement_center.clone(),
+            req,
+        )
+        .await?;
+        return Ok(());
+    }
+
+    pub async fn txn_commit_recorded(
+        &self,
+        producer: &ProducerIdentity,
+    ) -> Result<bool, CommonError> {
+        if self.config.placement_center.is_empty() {
+            return Ok(false);
+        }
+        let req = ExistsIdempotentDataRequest {
+            cluster_name: self.config.cluster_name.clone(),
+            producer_id: txn_producer_key(producer.producer_id),
+            seq_num: producer.transactional_id as u64,
+        };
+        let reply = exists_idempotent_data(
+            self.client_poll.clone(),
+            self.config.placement_center.clone(),
+            req,
+        )
+        .await?;
+        return Ok(reply.exists);
+    }
+
+    pub async fn forget_txn_commit(&self, producer: &ProducerIdentity) -> Result<(), CommonError> {
+        if self.config.placement_center.is_empty() {
+            return Ok(());
+        }
+        let req = DeleteIdempotentDataRequest {
+            cluster_name: self.config.cluster_name.clone(),
+            producer_id: txn_producer_key(producer.producer_id),
+            seq_num: producer.transactional_id as u64,
+        };
+        delete_idempotent_data(
+            self.client_poll.clone(),
+            self.config.placement_center.clone(),
+            req,
+        )
+        .await?;
+        return Ok(());
+    }
+
+    pub async fn metadata(
+        &self,
+        addr: &str,
+        shards: Vec<String>,
+    ) -> Result<MetadataRespBody, CommonError> {
+        let req = MetadataReq {
+            header: Some(self.build_header(ApiKey::Metadata)),
+            body: Some(MetadataReqBody { shards }),
+        };
+        match self
+            .call(addr, StorageEnginePacket::MetadataReq(req))
+            .await?
+        {
+            StorageEnginePacket::MetadataResp(resp) => {
+                return Ok(resp.body.unwrap_or_default());
+            }
+            packet => return Err(unexpected_response(addr, &packet)),
+        }
+    }
+
+    // Returns the address of the leader of the shard, from the cache if it is known.
+    pub async fn shard_leader(&self, shard_name: &str) -> Result<String, CommonError> {
+        if let Some(addr) = self.leaders.get(shard_name) {
+            return Ok(addr.clone());
+        }
+
+        if !self.config.placement_center.is_empty() {
+            let req = GetShardRequest {
+                cluster_name: self.config.cluster_name.clone(),
+                shard_name: shard_name.to_string(),
+                stale_read: false,
+            };
+            match get_shard(
+                self.client_poll.clone(),
+                self.config.placement_center.clone(),
+                req,
+            )
+            .await
+            {
+                Ok(reply) => {
+                    if !reply.leader_addr.is_empty() {
+                        self.leaders
+                            .insert(shard_name.to_string(), reply.leader_addr.clone());
+                        return Ok(reply.leader_addr);
+                    }
+                }
+                Err(e) => {
+                    warn!("get leader of shard {} failed, {}", shard_name, e);
+                }
+            }
+        }
+
+        for addr in self.config.bootstrap_addrs.iter() {
+            let body = match self.metadata(addr, vec![shard_name.to_string()]).await {
+                Ok(body) => body,
+                Err(e) => {
+                    warn!("get metadata from journal server {} failed, {}", addr, e);
+                    continue;
+                }
+            };
+            for shard in body.shards {
+                if shard.shard_name == shard_name && !shard.leader_addr.is_empty() {
+                    self.leaders
+                        .insert(shard_name.to_string(), shard.leader_addr.clone());
+                    return Ok(shard.leader_addr);
+                }
+            }
+            // A shard that does not exist yet is created on its first produce, the node
+            // answering the metadata request can serve it.
+            self.leaders.insert(shard_name.to_string(), addr.clone());
+            return Ok(addr.clone());
+        }
+        return Err(JournalServerError::NoShardLeader(shard_name.to_string()).into());
+    }
+
+    pub fn set_leader(&self, shard_name: &str, addr: &str) {
+        self.leaders
+            .insert(shard_name.to_string(), addr.to_string());
+    }
+
+    pub fn invalidate_leader(&self, shard_name: &str) {
+        self.leaders.remove(shard_name);
+    }
+
+    async fn produce_once(
+        &self,
+        shard_name: &str,
+        body: ProduceReqBody,
+    ) -> Result<Vec<u64>, CommonError> {
+        let addr = self.shard_leader(shard_name).await?;
+        let req = ProduceReq {
+            header: Some(self.build_header(ApiKey::Produce)),
+            body: Some(body),
+        };
+        match self
+            .call(&addr, StorageEnginePacket::ProduceReq(req))
+            .await?
+        {
+            StorageEnginePacket::ProduceResp(resp) => {
+                let body = resp.body.unwrap_or_default();
+                for shard in body.shards {
+                    if shard.shard_name != shard_name {
+                        continue;
+                    }
+                    check_error_code(&addr, shard_name, shard.error_code, shard.error_message)?;
+                    return Ok(shard.offsets);
+                }
+                return Err(unexpected_shard(&addr, shard_name));
+            }
+            packet => return Err(unexpected_response(&addr, &packet)),
+        }
+    }
+
+    async fn end_shard_txn(
+        &self,
+        shard_name: &str,
+        producer: &ProducerIdentity,
+        commit: bool,
+    ) -> Result<(), CommonError> {
+        let body = EndTxnReqBody {
+            producer_id: producer.producer_id,
+            producer_epoch: producer.producer_epoch,
+            transactional_id: producer.transactional_id,
+            commit,
+            shards: vec![shard_name.to_string()],
+            timeout_ms: self.config.request_timeout_ms as u32,
+        };
+        let mut times = 0;
+        loop {
+            times = times + 1;
+            let result = self.end_txn_once(shard_name, body.clone()).await;
+            match result {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    if !self.retry_after(shard_name, &e, times).await {
+                        return Err(e);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn end_txn_once(&self, shard_name: &str, body: EndTxnReqBody) -> Result<(), CommonError> {
+        let addr = self.shard_leader(shard_name).await?;
+        let req = EndTxnReq {
+            header: Some(self.build_header(ApiKey::EndTxn)),
+            body: Some(body),
+        };
+        match self
+            .call(&addr, StorageEnginePacket::EndTxnReq(req))
+            .await?
+        {
+            StorageEnginePacket::EndTxnResp(resp) => {
+                let body = resp.body.unwrap_or_default();
+                for shard in body.shards {
+                    if shard.shard_name != shard_name {
+                        continue;
+                    }
+                    return check_error_code(
+                        &addr,
+                        shard_name,
+                        shard.error_code,
+                        shard.error_message,
+                    );
+                }
+                return Err(unexpected_shard(&addr, shard_name));
+            }
+            packet => return Err(unexpected_response(&addr, &packet)),
+        }
+    }
+
+    async fn fetch_once(
+        &self,
+        shard_name: &str,
+        body: FetchReqBody,
+    ) -> Result<ShardFetchResult, CommonError> {
+        let addr = self.shard_leader(shard_name).await?;
+        let req = FetchReq {
+            header: Some(self.build_header(ApiKey::Consume)),
+            body: Some(body),
+        };
+        match self.call(&addr, StorageEnginePacket::FetchReq(req)).await? {
+            StorageEnginePacket::FetchResp(resp) => {
+                let body = resp.body.unwrap_or_default();
+                for shard in body.shards {
+                    if shard.shard_name != shard_name {
+                        continue;
+                    }
+                    check_error_code(
+                        &addr,
+                        shard_name,
+                        shard.error_code,
+                        shard.error_message.clone(),
+                    )?;
+                    return Ok(shard);
+                }
+                return Err(unexpected_shard(&addr, shard_name));
+            }
+            packet => return Err(unexpected_response(&addr, &packet)),
+        }
+    }
+
+    async fn call(
+        &self,
+        addr: &str,
+        packet: StorageEnginePacket,
+    ) -> Result<StorageEnginePacket, CommonError> {
+        let conn = self.connections.get(addr).await?;
+        // The server holds a long-poll fetch for up to its max wait, which is on top of
+        // the request timeout.
+        let mut wait = Duration::from_millis(self.config.request_timeout_ms);
+        if let StorageEnginePacket::FetchReq(req) = &packet {
+            if let Some(body) = &req.body {
+                wait = wait + Duration::from_millis(body.max_wait_ms as u64);
+            }
+        }
+        return conn.call(packet, wait).await;
+    }
+
+    // Decides whether a failed request is retried. Requests failing because the leader
+    // moved or the connection broke are retried against a newly looked up leader.
+    async fn retry_after(&self, shard_name: &str, e: &CommonError, times: usize) -> bool {
+        let retriable = match e {
+            CommonError::JournalServerError(JournalServerError::NotLeader(_, _)) => true,
+            CommonError::JournalServerError(JournalServerError::ConnectionClosed(_)) => true,
+            CommonError::IoError(_) => true,
+            _ => false,
+        };
+        if !retriable || times > self.config.max_retry_times {
+            return false;
+        }
+        warn!(
+            "request to shard {} failed, retry {} times, {}",
+            shard_name, times, e
+        );
+        self.invalidate_leader(shard_name);
+        sleep(Duration::from_millis(
+            self.config.retry_backoff_ms * times as u64,
+        ))
+        .await;
+        return true;
+    }
+
+    fn build_header(&self, api_key: ApiKey) -> Header {
+        return Header {
+            api_key: api_key.into(),
+            api_type: ApiType::Request.into(),
+            api_version: ApiVersion::V0.into(),
+            request: Some(RequestCommon {
+                correlation_id: 0,
+                client_id: self.config.client_id.clone(),
+            }),
+            response: None,
+        };
+    }
+}
+
+fn check_error_code(
+    addr: &str,
+    shard_name: &str,
+    error_code: i32,
+    error_message: String,
+) -> Result<(), CommonError> {
+    let err = match ErrorCode::try_from(error_code) {
+        Ok(ErrorCode::Success) => return Ok(()),
+        Ok(ErrorCode::NotLeader) => {
+            JournalServerError::NotLeader(addr.to_string(), shard_name.to_string())
+        }
+        Ok(ErrorCode::ShardNotFound) => JournalServerError::ShardNotFound(shard_name.to_string()),
+        Ok(ErrorCode::OffsetOutOfRange) => {
+            JournalServerError::OffsetOutOfRange(shard_name.to_string(), error_message)
+        }
+        _ => JournalServerError::ServerError(error_code, error_message),
+    };
+    return Err(err.into());
+}
+
+// The producer id and epoch are kept as "{id}:{epoch}".
+fn parse_producer(value: &str) -> Result<(u64, u32), CommonError> {
+    if let Some((producer_id, producer_epoch)) = value.split_once(':') {
+        if let (Ok(producer_id), Ok(producer_epoch)) =
+            (producer_id.parse::<u64>(), producer_epoch.parse::<u32>())
+        {
+            return Ok((producer_id, producer_epoch));
+        }
+    }
+    return Err(CommonError::CommmonError(format!(
+        "invalid producer id and epoch {}",
+        value
+    )));
+}
+
+fn txn_producer_key(producer_id: u64) -> String {
+    return format!("journal-txn-{}", producer_id);
+}
+
+fn unexpected_response(addr: &str, packet: &StorageEnginePacket) -> CommonError {
+    return CommonError::CommmonError(format!(
+        "journal server {} returned an unexpected response {:?}",
+        addr, packet
+    ));
+}
+
+fn unexpected_shard(addr: &str, shard_name: &str) -> CommonError {
+    return CommonError::CommmonError(format!(
+        "journal server {} returned no result for shard {}",
+        addr, shard_name
+    ));
+}
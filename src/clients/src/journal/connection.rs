@@ -0,0 +1,229 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::error::{common::CommonError, journal_server::JournalServerError};
+use dashmap::DashMap;
+use futures::{SinkExt, StreamExt};
+use log::error;
+use protocol::journal_server::{
+    codec::{StorageEngineCodec, StorageEnginePacket},
+    generate::protocol::header::Header,
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::{
+    net::TcpStream,
+    sync::{mpsc, oneshot},
+    time::timeout,
+};
+use tokio_util::codec::{FramedRead, FramedWrite};
+
+const WRITE_QUEUE_SIZE: usize = 1024;
+
+// A TCP connection to a journal server. Requests are written as soon as they are sent
+// and responses are matched to them by correlation id, so any number of requests can be
+// in flight on the connection at the same time.
+pub struct JournalConnection {
+    addr: String,
+    sender: mpsc::Sender<StorageEnginePacket>,
+    inflight: Arc<DashMap<u32, oneshot::Sender<StorageEnginePacket>>>,
+    correlation_id: AtomicU32,
+    closed: Arc<AtomicBool>,
+}
+
+impl JournalConnection {
+    pub async fn connect(addr: &str) -> Result<Self, CommonError> {
+        let stream = TcpStream::connect(addr).await?;
+        let (read_half, write_half) = stream.into_split();
+        let (sender, mut receiver) = mpsc::channel::<StorageEnginePacket>(WRITE_QUEUE_SIZE);
+        let inflight: Arc<DashMap<u32, oneshot::Sender<StorageEnginePacket>>> =
+            Arc::new(DashMap::with_capacity(64));
+        let closed = Arc::new(AtomicBool::new(false));
+
+        let write_closed = closed.clone();
+        let write_addr = addr.to_string();
+        tokio::spawn(async move {
+            let mut write_frame = FramedWrite::new(write_half, StorageEngineCodec::new());
+            while let Some(packet) = receiver.recv().await {
+                if let Err(e) = write_frame.send(packet).await {
+                    error!("write to journal server {} failed, {}", write_addr, e);
+                    break;
+                }
+            }
+            write_closed.store(true, Ordering::SeqCst);
+        });
+
+        let read_closed = closed.clone();
+        let read_inflight = inflight.clone();
+        let read_addr = addr.to_string();
+        tokio::spawn(async move {
+            let mut read_frame = FramedRead::new(read_half, StorageEngineCodec::new());
+            while let Some(data) = read_frame.next().await {
+                match data {
+                    Ok(packet) => {
+                        let id = response_correlation_id(&packet);
+                        if let Some((_, sender)) = read_inflight.remove(&id) {
+                            let _ = sender.send(packet);
+                        }
+                    }
+                    Err(e) => {
+                        error!("read from journal server {} failed, {}", read_addr, e);
+                        break;
+                    }
+                }
+            }
+            read_closed.store(true, Ordering::SeqCst);
+            // Dropping the senders fails every request still waiting on the connection.
+            read_inflight.clear();
+        });
+
+        return Ok(JournalConnection {
+            addr: addr.to_string(),
+            sender,
+            inflight,
+            correlation_id: AtomicU32::new(1),
+            closed,
+        });
+    }
+
+    pub fn addr(&self) -> &str {
+        return &self.addr;
+    }
+
+    pub fn is_closed(&self) -> bool {
+        return self.closed.load(Ordering::SeqCst);
+    }
+
+    // Sends the request and waits for its response. The correlation id of the request
+    // header is assigned by the connection.
+    pub async fn call(
+        &self,
+        mut packet: StorageEnginePacket,
+        wait: Duration,
+    ) -> Result<StorageEnginePacket, CommonError> {
+        if self.is_closed() {
+            return Err(JournalServerError::ConnectionClosed(self.addr.clone()).into());
+        }
+        let id = self.correlation_id.fetch_add(1, Ordering::SeqCst);
+        set_request_correlation_id(&mut packet, id);
+
+        let (sender, receiver) = oneshot::channel();
+        self.inflight.insert(id, sender);
+        // The reader may have failed the waiting requests just before the insert.
+        if self.is_closed() {
+            self.inflight.remove(&id);
+            return Err(JournalServerError::ConnectionClosed(self.addr.clone()).into());
+        }
+        if self.sender.send(packet).await.is_err() {
+            self.inflight.remove(&id);
+            self.closed.store(true, Ordering::SeqCst);
+            return Err(JournalServerError::ConnectionClosed(self.addr.clone()).into());
+        }
+
+        match timeout(wait, receiver).await {
+            Ok(Ok(packet)) => return Ok(packet),
+            Ok(Err(_)) => {
+                return Err(JournalServerError::ConnectionClosed(self.addr.clone()).into());
+            }
+            Err(_) => {
+                self.inflight.remove(&id);
+                return Err(JournalServerError::RequestTimeout(self.addr.clone()).into());
+            }
+        }
+    }
+}
+
+// Keeps up to max_connections_per_node connections to every journal server and spreads
+// the requests over them. Broken connections are replaced the next time they are picked.
+pub struct JournalConnectionPool {
+    max_connections_per_node: usize,
+    connections: DashMap<String, Vec<Option<Arc<JournalConnection>>>>,
+    next: AtomicUsize,
+}
+
+impl JournalConnectionPool {
+    pub fn new(max_connections_per_node: usize) -> Self {
+        return JournalConnectionPool {
+            max_connections_per_node: max_connections_per_node.max(1),
+            connections: DashMap::with_capacity(8),
+            next: AtomicUsize::new(0),
+        };
+    }
+
+    pub async fn get(&self, addr: &str) -> Result<Arc<JournalConnection>, CommonError> {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.max_connections_per_node;
+        if let Some(connections) = self.connections.get(addr) {
+            if let Some(Some(conn)) = connections.get(index) {
+                if !conn.is_closed() {
+                    return Ok(conn.clone());
+                }
+            }
+        }
+
+        // The map is not locked while connecting, two callers racing for the same slot
+        // both connect and the later one wins.
+        let conn = Arc::new(JournalConnection::connect(addr).await?);
+        let mut connections = self
+            .connections
+            .entry(addr.to_string())
+            .or_insert_with(|| vec![None; self.max_connections_per_node]);
+        connections[index] = Some(conn.clone());
+        return Ok(conn);
+    }
+
+    pub fn remove(&self, addr: &str) {
+        self.connections.remove(addr);
+    }
+}
+
+fn set_request_correlation_id(packet: &mut StorageEnginePacket, id: u32) {
+    let header = match packet {
+        StorageEnginePacket::ProduceReq(data) => &mut data.header,
+        StorageEnginePacket::FetchReq(data) => &mut data.header,
+        StorageEnginePacket::MetadataReq(data) => &mut data.header,
+        StorageEnginePacket::ProduceResp(data) => &mut data.header,
+        StorageEnginePacket::FetchResp(data) => &mut data.header,
+        StorageEnginePacket::MetadataResp(data) => &mut data.header,
+        StorageEnginePacket::EndTxnReq(data) => &mut data.header,
+        StorageEnginePacket::EndTxnResp(data) => &mut data.header,
+    };
+    let header = header.get_or_insert_with(Header::default);
+    header
+        .request
+        .get_or_insert_with(Default::default)
+        .correlation_id = id;
+}
+
+fn response_correlation_id(packet: &StorageEnginePacket) -> u32 {
+    let header = match packet {
+        StorageEnginePacket::ProduceReq(data) => &data.header,
+        StorageEnginePacket::FetchReq(data) => &data.header,
+        StorageEnginePacket::MetadataReq(data) => &data.header,
+        StorageEnginePacket::ProduceResp(data) => &data.header,
+        StorageEnginePacket::FetchResp(data) => &data.header,
+        StorageEnginePacket::MetadataResp(data) => &data.header,
+        StorageEnginePacket::EndTxnReq(data) => &data.header,
+        StorageEnginePacket::EndTxnResp(data) => &data.header,
+    };
+    if let Some(header) = header {
+        if let Some(response) = &header.response {
+            return response.correlation_id;
+        }
+    }
+    return 0;
+}
@@ -0,0 +1,88 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::client::JournalClient;
+use common_base::error::common::CommonError;
+use protocol::journal_server::generate::protocol::record::Record;
+use std::sync::Arc;
+
+#[derive(Clone, Debug)]
+pub struct ConsumerConfig {
+    pub max_record_num: u32,
+    // How long the server holds a fetch when there is nothing new to read.
+    pub max_wait_ms: u32,
+}
+
+impl Default for ConsumerConfig {
+    fn default() -> Self {
+        return ConsumerConfig {
+            max_record_num: 100,
+            max_wait_ms: 500,
+        };
+    }
+}
+
+// Reads a shard in order from a position that advances past every record returned.
+pub struct JournalConsumer {
+    client: Arc<JournalClient>,
+    config: ConsumerConfig,
+    shard_name: String,
+    offset: u64,
+}
+
+impl JournalConsumer {
+    pub fn new(
+        client: Arc<JournalClient>,
+        config: ConsumerConfig,
+        shard_name: String,
+        offset: u64,
+    ) -> Self {
+        return JournalConsumer {
+            client,
+            config,
+            shard_name,
+            offset,
+        };
+    }
+
+    pub fn position(&self) -> u64 {
+        return self.offset;
+    }
+
+    pub fn seek(&mut self, offset: u64) {
+        self.offset = offset;
+    }
+
+    // Returns the next records of the shard, or an empty list if none arrived within the
+    // max wait.
+    pub async fn poll(&mut self) -> Result<Vec<Record>, CommonError> {
+        let result = self
+            .client
+            .fetch(
+                &self.shard_name,
+                self.offset,
+                self.config.max_record_num,
+                self.config.max_wait_ms,
+            )
+            .await?;
+        if let Some(record) = result.records.last() {
+            self.offset = record.offset + 1;
+        }
+        // A read committed fetch can skip control records and aborted records without
+        // returning any of them.
+        if result.next_fetch_offset > self.offset {
+            self.offset = result.next_fetch_offset;
+        }
+        return Ok(result.records);
+    }
+}
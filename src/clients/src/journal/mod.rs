@@ -11,4 +11,308 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod client;
+pub mod connection;
+pub mod consumer;
+pub mod producer;
+pub mod transaction;
 
+#[cfg(test)]
+mod tests {
+    use super::{
+        client::{JournalClient, JournalClientConfig},
+        consumer::{ConsumerConfig, JournalConsumer},
+        producer::{JournalProducer, ProducerConfig},
+        transaction::TransactionalProducer,
+    };
+    use crate::poll::ClientPool;
+    use futures::{SinkExt, StreamExt};
+    use protocol::journal_server::{
+        codec::{StorageEngineCodec, StorageEnginePacket},
+        generate::protocol::{
+            end_txn::{EndTxnResp, EndTxnRespBody, ShardEndTxnResult},
+            fetch::{FetchResp, FetchRespBody, ShardFetchResult},
+            header::{ApiKey, ApiType, ErrorCode, Header, ResponseCommon},
+            metadata::{MetadataResp, MetadataRespBody, ShardMetadata},
+            produce::{ProduceResp, ProduceRespBody, ShardProduceResult},
+            record::{ControlType, Record},
+        },
+    };
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+    use tokio::net::TcpListener;
+    use tokio_util::codec::Framed;
+
+    // Shards shared by the mock servers, with the address of the node that leads them.
+    #[derive(Default)]
+    struct MockCluster {
+        leader: Mutex<String>,
+        shards: Mutex<HashMap<String, Vec<Record>>>,
+        produce_requests: Mutex<Vec<String>>,
+    }
+
+    async fn start_mock_server(cluster: Arc<MockCluster>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let server_addr = addr.clone();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let cluster = cluster.clone();
+                let addr = server_addr.clone();
+                tokio::spawn(async move {
+                    let mut frame = Framed::new(stream, StorageEngineCodec::new());
+                    while let Some(Ok(packet)) = frame.next().await {
+                        let resp = mock_handle(&cluster, &addr, packet);
+                        frame.send(resp).await.unwrap();
+                    }
+                });
+            }
+        });
+        return addr;
+    }
+
+    fn mock_handle(
+        cluster: &MockCluster,
+        addr: &str,
+        packet: StorageEnginePacket,
+    ) -> StorageEnginePacket {
+        let leader = cluster.leader.lock().unwrap().clone();
+        match packet {
+            StorageEnginePacket::ProduceReq(req) => {
+                let id = req.header.unwrap().request.unwrap().correlation_id;
+                let mut shards = Vec::new();
+                for data in req.body.unwrap().shards {
+                    let mut result = ShardProduceResult {
+                        shard_name: data.shard_name.clone(),
+                        ..Default::default()
+                    };
+                    cluster
+                        .produce_requests
+                        .lock()
+                        .unwrap()
+                        .push(addr.to_string());
+                    if leader != addr {
+                        result.error_code = ErrorCode::NotLeader.into();
+                    } else {
+                        let mut all = cluster.shards.lock().unwrap();
+                        let records = all.entry(data.shard_name).or_default();
+                        for mut record in data.records {
+                            record.offset = records.len() as u64;
+                            result.offsets.push(record.offset);
+                            records.push(record);
+                        }
+                    }
+                    shards.push(result);
+                }
+                return StorageEnginePacket::ProduceResp(ProduceResp {
+                    header: Some(resp_header(ApiKey::Produce, id)),
+                    body: Some(ProduceRespBody { shards }),
+                });
+            }
+            StorageEnginePacket::FetchReq(req) => {
+                let id = req.header.unwrap().request.unwrap().correlation_id;
+                let all = cluster.shards.lock().unwrap();
+                let mut shards = Vec::new();
+                for fetch in req.body.unwrap().shards {
+                    let records = all.get(&fetch.shard_name).cloned().unwrap_or_default();
+                    shards.push(ShardFetchResult {
+                        shard_name: fetch.shard_name,
+                        next_offset: records.len() as u64,
+                        records: records.into_iter().skip(fetch.offset as usize).collect(),
+                        ..Default::default()
+                    });
+                }
+                return StorageEnginePacket::FetchResp(FetchResp {
+                    header: Some(resp_header(ApiKey::Consume, id)),
+                    body: Some(FetchRespBody { shards }),
+                });
+            }
+            StorageEnginePacket::EndTxnReq(req) => {
+                let id = req.header.unwrap().request.unwrap().correlation_id;
+                let body = req.body.unwrap();
+                let control = if body.commit {
+                    ControlType::Commit
+                } else {
+                    ControlType::Abort
+                };
+                let mut all = cluster.shards.lock().unwrap();
+                let mut shards = Vec::new();
+                for shard_name in body.shards {
+                    let records = all.entry(shard_name.clone()).or_default();
+                    let offset = records.len() as u64;
+                    records.push(Record {
+                        offset,
+                        producer_id: body.producer_id,
+                        producer_epoch: body.producer_epoch,
+                        transactional: true,
+                        control: control.into(),
+                        ..Default::default()
+                    });
+                    shards.push(ShardEndTxnResult {
+                        shard_name,
+                        offset,
+                        ..Default::default()
+                    });
+                }
+                return StorageEnginePacket::EndTxnResp(EndTxnResp {
+                    header: Some(resp_header(ApiKey::EndTxn, id)),
+                    body: Some(EndTxnRespBody { shards }),
+                });
+            }
+            StorageEnginePacket::MetadataReq(req) => {
+                let id = req.header.unwrap().request.unwrap().correlation_id;
+                let shards = req
+                    .body
+                    .unwrap()
+                    .shards
+                    .into_iter()
+                    .map(|shard_name| ShardMetadata {
+                        shard_name,
+                        leader_addr: leader.clone(),
+                        ..Default::default()
+                    })
+                    .collect();
+                return StorageEnginePacket::MetadataResp(MetadataResp {
+                    header: Some(resp_header(ApiKey::Metadata, id)),
+                    body: Some(MetadataRespBody {
+                        nodes: Vec::new(),
+                        shards,
+                    }),
+                });
+            }
+            packet => panic!("unexpected request {:?}", packet),
+        }
+    }
+
+    fn resp_header(api_key: ApiKey, correlation_id: u32) -> Header {
+        return Header {
+            api_key: api_key.into(),
+            api_type: ApiType::Response.into(),
+            response: Some(ResponseCommon {
+                correlation_id,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+    }
+
+    fn build_client(bootstrap_addrs: Vec<String>) -> Arc<JournalClient> {
+        let config = JournalClientConfig {
+            bootstrap_addrs,
+            retry_backoff_ms: 1,
+            ..Default::default()
+        };
+        return Arc::new(JournalClient::new(config, Arc::new(ClientPool::new(1))));
+    }
+
+    fn build_record(value: &str) -> Record {
+        return Record {
+            value: value.as_bytes().to_vec(),
+            ..Default::default()
+        };
+    }
+
+    #[tokio::test]
+    async fn produce_retries_on_leader_change() {
+        let cluster = Arc::new(MockCluster::default());
+        let addr1 = start_mock_server(cluster.clone()).await;
+        let addr2 = start_mock_server(cluster.clone()).await;
+        *cluster.leader.lock().unwrap() = addr1.clone();
+
+        let client = build_client(vec![addr1.clone()]);
+        let offsets = client
+            .produce("s1", vec![build_record("a"), build_record("b")])
+            .await
+            .unwrap();
+        assert_eq!(offsets, vec![0, 1]);
+        assert_eq!(client.shard_leader("s1").await.unwrap(), addr1);
+
+        // The cached leader answers NotLeader, the client looks the leader up again.
+        *cluster.leader.lock().unwrap() = addr2.clone();
+        let offsets = client.produce("s1", vec![build_record("c")]).await.unwrap();
+        assert_eq!(offsets, vec![2]);
+        assert_eq!(client.shard_leader("s1").await.unwrap(), addr2);
+        assert_eq!(
+            *cluster.produce_requests.lock().unwrap(),
+            vec![addr1.clone(), addr1, addr2]
+        );
+    }
+
+    #[tokio::test]
+    async fn producer_batches_and_consumer_polls() {
+        let cluster = Arc::new(MockCluster::default());
+        let addr = start_mock_server(cluster.clone()).await;
+        *cluster.leader.lock().unwrap() = addr.clone();
+
+        let client = build_client(vec![addr]);
+        let producer = Arc::new(JournalProducer::new(
+            client.clone(),
+            ProducerConfig {
+                linger_ms: 50,
+                ..Default::default()
+            },
+        ));
+        let mut tasks = Vec::new();
+        for i in 0..10 {
+            let producer = producer.clone();
+            tasks.push(tokio::spawn(async move {
+                return producer
+                    .send("s1", build_record(&format!("v{}", i)))
+                    .await
+                    .unwrap();
+            }));
+        }
+        let mut offsets = Vec::new();
+        for task in tasks {
+            offsets.push(task.await.unwrap());
+        }
+        offsets.sort();
+        assert_eq!(offsets, (0..10).collect::<Vec<u64>>());
+        // All the records lingered into a single batch.
+        assert_eq!(cluster.produce_requests.lock().unwrap().len(), 1);
+
+        let mut consumer = JournalConsumer::new(
+            client.clone(),
+            ConsumerConfig::default(),
+            "s1".to_string(),
+            4,
+        );
+        let records = consumer.poll().await.unwrap();
+        assert_eq!(records.len(), 6);
+        assert_eq!(consumer.position(), 10);
+        assert!(consumer.poll().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn transactional_producer_commits_on_every_shard() {
+        let cluster = Arc::new(MockCluster::default());
+        let addr = start_mock_server(cluster.clone()).await;
+        *cluster.leader.lock().unwrap() = addr.clone();
+
+        let client = build_client(vec![addr]);
+        let mut producer = TransactionalProducer::new(client, 7, 0);
+        assert!(producer.send("s1", vec![build_record("a")]).await.is_err());
+
+        producer.begin().unwrap();
+        producer
+            .send("s1", vec![build_record("a"), build_record("b")])
+            .await
+            .unwrap();
+        producer.send("s2", vec![build_record("c")]).await.unwrap();
+        producer.send("s1", vec![build_record("d")]).await.unwrap();
+        producer.commit().await.unwrap();
+        assert!(!producer.in_transaction());
+
+        let all = cluster.shards.lock().unwrap();
+        let s1 = all.get("s1").unwrap();
+        assert_eq!(s1.len(), 4);
+        assert_eq!(s1[3].control, i32::from(ControlType::Commit));
+        assert_eq!(s1[3].producer_id, 7);
+        let s2 = all.get("s2").unwrap();
+        assert_eq!(s2.len(), 2);
+        assert_eq!(s2[1].control, i32::from(ControlType::Commit));
+    }
+}
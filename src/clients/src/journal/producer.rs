@@ -0,0 +1,183 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::client::JournalClient;
+use common_base::error::common::CommonError;
+use futures::future::join_all;
+use protocol::journal_server::generate::protocol::record::Record;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+    time::{sleep_until, Instant},
+};
+
+#[derive(Clone, Debug)]
+pub struct ProducerConfig {
+    // A batch is sent once it holds this many records or bytes of record values,
+    // otherwise when it has waited for linger_ms.
+    pub batch_record_num: usize,
+    pub batch_bytes: usize,
+    pub linger_ms: u64,
+    pub queue_size: usize,
+}
+
+impl Default for ProducerConfig {
+    fn default() -> Self {
+        return ProducerConfig {
+            batch_record_num: 500,
+            batch_bytes: 1024 * 1024,
+            linger_ms: 5,
+            queue_size: 10000,
+        };
+    }
+}
+
+struct ProduceItem {
+    shard_name: String,
+    record: Record,
+    result: oneshot::Sender<Result<u64, CommonError>>,
+}
+
+struct ProduceBatch {
+    records: Vec<Record>,
+    results: Vec<oneshot::Sender<Result<u64, CommonError>>>,
+    bytes: usize,
+    deadline: Instant,
+}
+
+// Collects the records sent by any number of tasks into per shard batches, which are
+// written by a background task. Batches of a shard are written one at a time, so records
+// sent from one task keep their order.
+pub struct JournalProducer {
+    sender: mpsc::Sender<ProduceItem>,
+    handle: JoinHandle<()>,
+}
+
+impl JournalProducer {
+    pub fn new(client: Arc<JournalClient>, config: ProducerConfig) -> Self {
+        let (sender, receiver) = mpsc::channel(config.queue_size);
+        let handle = tokio::spawn(run_batch_loop(client, config, receiver));
+        return JournalProducer { sender, handle };
+    }
+
+    // Sends the record and waits until the batch it is part of is written, returns the
+    // offset of the record.
+    pub async fn send(&self, shard_name: &str, record: Record) -> Result<u64, CommonError> {
+        let (result, receiver) = oneshot::channel();
+        let item = ProduceItem {
+            shard_name: shard_name.to_string(),
+            record,
+            result,
+        };
+        if self.sender.send(item).await.is_err() {
+            return Err(producer_closed());
+        }
+        match receiver.await {
+            Ok(result) => return result,
+            Err(_) => return Err(producer_closed()),
+        }
+    }
+
+    // Writes the records still waiting in batches and stops the background task.
+    pub async fn close(self) {
+        drop(self.sender);
+        let _ = self.handle.await;
+    }
+}
+
+async fn run_batch_loop(
+    client: Arc<JournalClient>,
+    config: ProducerConfig,
+    mut receiver: mpsc::Receiver<ProduceItem>,
+) {
+    let linger = Duration::from_millis(config.linger_ms);
+    let mut batches: HashMap<String, ProduceBatch> = HashMap::new();
+    let mut closed = false;
+    while !closed || !batches.is_empty() {
+        let deadline = batches.values().map(|batch| batch.deadline).min();
+        let wait_deadline = async {
+            match deadline {
+                Some(deadline) => sleep_until(deadline).await,
+                None => futures::future::pending::<()>().await,
+            }
+        };
+
+        if !closed {
+            tokio::select! {
+                item = receiver.recv() => {
+                    match item {
+                        Some(item) => add_to_batch(&mut batches, item, linger),
+                        None => closed = true,
+                    }
+                }
+                _ = wait_deadline => {}
+            }
+        }
+
+        // Everything that is full or has lingered long enough is sent. After the channel
+        // is closed all that is left is sent.
+        let now = Instant::now();
+        let ready: Vec<String> = batches
+            .iter()
+            .filter(|(_, batch)| {
+                closed
+                    || batch.deadline <= now
+                    || batch.records.len() >= config.batch_record_num
+                    || batch.bytes >= config.batch_bytes
+            })
+            .map(|(shard_name, _)| shard_name.clone())
+            .collect();
+        let mut flushes = Vec::new();
+        for shard_name in ready {
+            if let Some(batch) = batches.remove(&shard_name) {
+                flushes.push(flush_batch(client.clone(), shard_name, batch));
+            }
+        }
+        join_all(flushes).await;
+    }
+}
+
+fn add_to_batch(batches: &mut HashMap<String, ProduceBatch>, item: ProduceItem, linger: Duration) {
+    let batch = batches
+        .entry(item.shard_name)
+        .or_insert_with(|| ProduceBatch {
+            records: Vec::new(),
+            results: Vec::new(),
+            bytes: 0,
+            deadline: Instant::now() + linger,
+        });
+    batch.bytes = batch.bytes + item.record.value.len();
+    batch.records.push(item.record);
+    batch.results.push(item.result);
+}
+
+async fn flush_batch(client: Arc<JournalClient>, shard_name: String, batch: ProduceBatch) {
+    match client.produce(&shard_name, batch.records).await {
+        Ok(offsets) => {
+            for (result, offset) in batch.results.into_iter().zip(offsets.into_iter()) {
+                let _ = result.send(Ok(offset));
+            }
+        }
+        Err(e) => {
+            let message = e.to_string();
+            for result in batch.results {
+                let _ = result.send(Err(CommonError::CommmonError(message.clone())));
+            }
+        }
+    }
+}
+
+fn producer_closed() -> CommonError {
+    return CommonError::CommmonError("journal producer has been closed".to_string());
+}
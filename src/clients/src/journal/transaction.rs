@@ -0,0 +1,232 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::client::{JournalClient, ProducerIdentity};
+use common_base::error::common::CommonError;
+use protocol::journal_server::generate::protocol::record::Record;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+// Writes records of any number of shards that become visible to read committed consumers
+// all at once on commit, or not at all on abort. The producer coordinates the transaction
+// itself, it ends it by writing a commit or abort marker on every shard it wrote to.
+//
+// Every send carries the sequence number of its first record on the shard, so a retried
+// send is only written once. A transaction left open for longer than the transaction
+// timeout of the journal servers is aborted by them, which fences the producer.
+pub struct TransactionalProducer {
+    client: Arc<JournalClient>,
+    identity: ProducerIdentity,
+    // Set when the id of the producer was given out by the placement center, which then
+    // also gives out its newer epochs.
+    producer_name: Option<String>,
+    last_transactional_id: u32,
+    sequences: HashMap<String, u32>,
+    shards: HashSet<String>,
+    // Set when a send of the ongoing transaction failed, the transaction can then only be
+    // aborted.
+    abort_only: bool,
+}
+
+impl TransactionalProducer {
+    pub fn new(client: Arc<JournalClient>, producer_id: u64, producer_epoch: u32) -> Self {
+        return TransactionalProducer {
+            client,
+            identity: ProducerIdentity {
+                producer_id,
+                producer_epoch,
+                transactional_id: 0,
+            },
+            producer_name: None,
+            last_transactional_id: 0,
+            sequences: HashMap::new(),
+            shards: HashSet::new(),
+            abort_only: false,
+        };
+    }
+
+    // Builds the producer with the id of producer_name and a new epoch from the placement
+    // center, which fences the previous instance of the producer.
+    pub async fn init(
+        client: Arc<JournalClient>,
+        producer_name: &str,
+    ) -> Result<Self, CommonError> {
+        let identity = client.init_producer(producer_name).await?;
+        let mut producer =
+            TransactionalProducer::new(client, identity.producer_id, identity.producer_epoch);
+        producer.producer_name = Some(producer_name.to_string());
+        return Ok(producer);
+    }
+
+    pub fn identity(&self) -> ProducerIdentity {
+        return self.identity;
+    }
+
+    pub fn in_transaction(&self) -> bool {
+        return self.identity.transactional_id > 0;
+    }
+
+    pub fn begin(&mut self) -> Result<(), CommonError> {
+        if self.in_transaction() {
+            return Err(CommonError::CommmonError(format!(
+                "producer {} already has transaction {} ongoing",
+                self.identity.producer_id, self.identity.transactional_id
+            )));
+        }
+        self.identity.transactional_id = self.next_transactional_id();
+        self.shards.clear();
+        self.abort_only = false;
+        return Ok(());
+    }
+
+    // Writes the records as part of the ongoing transaction and returns their offsets.
+    pub async fn send(
+        &mut self,
+        shard_name: &str,
+        records: Vec<Record>,
+    ) -> Result<Vec<u64>, CommonError> {
+        self.check_sendable()?;
+        let num = records.len() as u32;
+        let base_sequence = self.sequences.get(shard_name).copied().unwrap_or(0);
+        self.shards.insert(shard_name.to_string());
+        match self
+            .client
+            .produce_idempotent(shard_name, records, &self.identity, base_sequence)
+            .await
+        {
+            Ok(offsets) => {
+                self.sequences
+                    .insert(shard_name.to_string(), base_sequence.wrapping_add(num));
+                return Ok(offsets);
+            }
+            Err(e) => {
+                // Whether the records were written is unknown.
+                self.abort_only = true;
+                return Err(e);
+            }
+        }
+    }
+
+    pub async fn commit(&mut self) -> Result<(), CommonError> {
+        self.check_sendable()?;
+        let shards: Vec<String> = self.shards.iter().cloned().collect();
+        if !shards.is_empty() {
+            self.client.record_txn_commit(&self.identity).await?;
+            self.client.end_txn(&self.identity, true, shards).await?;
+            self.client.forget_txn_commit(&self.identity).await?;
+        }
+        self.finish();
+        return Ok(());
+    }
+
+    pub async fn abort(&mut self) -> Result<(), CommonError> {
+        if !self.in_transaction() {
+            return Ok(());
+        }
+        let shards: Vec<String> = self.shards.iter().cloned().collect();
+        if !shards.is_empty() {
+            self.client.end_txn(&self.identity, false, shards).await?;
+        }
+        if self.abort_only {
+            // The sequences of the shards are unknown after a failed send, the server
+            // accepts them from zero again under a newer epoch.
+            self.identity.producer_epoch = self.next_epoch().await?;
+            self.sequences.clear();
+        }
+        self.finish();
+        return Ok(());
+    }
+
+    // Finishes a transaction a previous instance of the producer left open on the shards.
+    // It is committed if the commit decision had been recorded, otherwise aborted. The
+    // producer moves to a newer epoch, which fences the previous instance.
+    pub async fn recover(
+        &mut self,
+        transactional_id: u32,
+        shards: Vec<String>,
+    ) -> Result<bool, CommonError> {
+        if self.in_transaction() {
+            return Err(CommonError::CommmonError(format!(
+                "producer {} has transaction {} ongoing",
+                self.identity.producer_id, self.identity.transactional_id
+            )));
+        }
+        let previous = ProducerIdentity {
+            transactional_id,
+            ..self.identity
+        };
+        let commit = self.client.txn_commit_recorded(&previous).await?;
+        let recovering = ProducerIdentity {
+            producer_epoch: self.next_epoch().await?,
+            ..previous
+        };
+        self.client.end_txn(&recovering, commit, shards).await?;
+        if commit {
+            self.client.forget_txn_commit(&previous).await?;
+        }
+        self.identity.producer_epoch = recovering.producer_epoch;
+        self.identity.transactional_id = transactional_id;
+        self.sequences.clear();
+        self.finish();
+        return Ok(commit);
+    }
+
+    fn check_sendable(&self) -> Result<(), CommonError> {
+        if !self.in_transaction() {
+            return Err(CommonError::CommmonError(format!(
+                "producer {} has no ongoing transaction",
+                self.identity.producer_id
+            )));
+        }
+        if self.abort_only {
+            return Err(CommonError::CommmonError(format!(
+                "transaction {} of producer {} failed and can only be aborted",
+                self.identity.transactional_id, self.identity.producer_id
+            )));
+        }
+        return Ok(());
+    }
+
+    async fn next_epoch(&self) -> Result<u32, CommonError> {
+        let producer_name = match &self.producer_name {
+            Some(producer_name) => producer_name,
+            None => return Ok(self.identity.producer_epoch + 1),
+        };
+        let identity = self.client.init_producer(producer_name).await?;
+        if identity.producer_id != self.identity.producer_id {
+            return Err(CommonError::CommmonError(format!(
+                "producer {} was given id {} instead of {}",
+                producer_name, identity.producer_id, self.identity.producer_id
+            )));
+        }
+        return Ok(identity.producer_epoch);
+    }
+
+    // Transactional ids are never zero, zero marks records outside of a transaction.
+    fn next_transactional_id(&self) -> u32 {
+        let id = self.last_transactional_id.wrapping_add(1);
+        if id == 0 {
+            return 1;
+        }
+        return id;
+    }
+
+    fn finish(&mut self) {
+        self.last_transactional_id = self.identity.transactional_id;
+        self.identity.transactional_id = 0;
+        self.shards.clear();
+        self.abort_only = false;
+    }
+}
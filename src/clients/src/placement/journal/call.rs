@@ -18,7 +18,8 @@ use crate::poll::ClientPool;
 use common_base::error::common::CommonError;
 use prost::Message;
 use protocol::placement_center::generate::journal::{
-    CreateSegmentRequest, DeleteSegmentRequest, DeleteShardRequest,
+    CreateSegmentRequest, DeleteSegmentRequest, DeleteShardRequest, GetShardReply, GetShardRequest,
+    ListShardReply, ListShardRequest, ReportShardStatusRequest,
 };
 use protocol::placement_center::generate::{common::CommonReply, journal::CreateShardRequest};
 use std::sync::Arc;
@@ -73,6 +74,56 @@ pub async fn delete_shard(
     }
 }
 
+pub async fn get_shard(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: GetShardRequest,
+) -> Result<GetShardReply, CommonError> {
+    let request_data = GetShardRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Journal,
+        PlacementCenterInterface::GetShard,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match GetShardReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn list_shard(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: ListShardRequest,
+) -> Result<ListShardReply, CommonError> {
+    let request_data = ListShardRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Journal,
+        PlacementCenterInterface::ListShard,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match ListShardReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
 pub async fn create_segment(
     client_poll: Arc<ClientPool>,
     addrs: Vec<String>,
@@ -122,3 +173,28 @@ pub async fn delete_segment(
         }
     }
 }
+
+pub async fn report_shard_status(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: ReportShardStatusRequest,
+) -> Result<CommonReply, CommonError> {
+    let request_data = ReportShardStatusRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Journal,
+        PlacementCenterInterface::ReportShardStatus,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match CommonReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
@@ -18,7 +18,8 @@ use protocol::placement_center::generate::{
     common::CommonReply,
     journal::{
         engine_service_client::EngineServiceClient, CreateSegmentRequest, CreateShardRequest,
-        DeleteSegmentRequest, DeleteShardRequest,
+        DeleteSegmentRequest, DeleteShardRequest, GetShardReply, GetShardRequest, ListShardReply,
+        ListShardRequest, ReportShardStatusRequest,
     },
 };
 use tonic::transport::Channel;
@@ -57,6 +58,40 @@ pub(crate) async fn inner_delete_shard(
     }
 }
 
+pub(crate) async fn inner_get_shard(
+    mut client: EngineServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match GetShardRequest::decode(request.as_ref()) {
+        Ok(request) => match client.get_shard(request).await {
+            Ok(result) => {
+                return Ok(GetShardReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_list_shard(
+    mut client: EngineServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match ListShardRequest::decode(request.as_ref()) {
+        Ok(request) => match client.list_shard(request).await {
+            Ok(result) => {
+                return Ok(ListShardReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
 pub(crate) async fn inner_create_segment(
     mut client: EngineServiceClient<Channel>,
     request: Vec<u8>,
@@ -90,3 +125,20 @@ pub(crate) async fn inner_delete_segment(
         }
     }
 }
+
+pub(crate) async fn inner_report_shard_status(
+    mut client: EngineServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match ReportShardStatusRequest::decode(request.as_ref()) {
+        Ok(request) => match client.report_shard_status(request).await {
+            Ok(result) => {
+                return Ok(CommonReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
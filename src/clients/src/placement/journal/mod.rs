@@ -22,6 +22,7 @@ use tonic::transport::Channel;
 
 use self::inner::{
     inner_create_segment, inner_create_shard, inner_delete_segment, inner_delete_shard,
+    inner_get_shard, inner_list_shard, inner_report_shard_status,
 };
 use super::PlacementCenterInterface;
 
@@ -43,12 +44,21 @@ pub async fn journal_interface_call(
                 PlacementCenterInterface::DeleteShard => {
                     inner_delete_shard(client, request.clone()).await
                 }
+                PlacementCenterInterface::GetShard => {
+                    inner_get_shard(client, request.clone()).await
+                }
+                PlacementCenterInterface::ListShard => {
+                    inner_list_shard(client, request.clone()).await
+                }
                 PlacementCenterInterface::CreateSegment => {
                     inner_create_segment(client, request.clone()).await
                 }
                 PlacementCenterInterface::DeleteSegment => {
                     inner_delete_segment(client, request.clone()).await
                 }
+                PlacementCenterInterface::ReportShardStatus => {
+                    inner_report_shard_status(client, request.clone()).await
+                }
                 _ => {
                     return Err(CommonError::CommmonError(format!(
                         "journal service does not support service interfaces [{:?}]",
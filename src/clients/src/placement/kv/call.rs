@@ -21,9 +21,20 @@ use common_base::error::common::CommonError;
 use prost::Message as _;
 use protocol::placement_center::generate::{
     common::CommonReply,
-    kv::{DeleteRequest, ExistsReply, ExistsRequest, GetReply, GetRequest, SetRequest},
+    kv::{
+        CompareAndSwapReply, CompareAndSwapRequest, DeleteRequest, ExistsReply, ExistsRequest,
+        GetReply, GetRequest, LeaseGrantReply, LeaseGrantRequest, LeaseKeepAliveReply,
+        LeaseKeepAliveRequest, LeaseRevokeRequest, ListReply, ListRequest, SetRequest,
+        StreamCommitOffsetRequest, StreamCreateShardRequest, StreamDeleteShardRequest,
+        StreamGetShardReply, StreamGetShardRequest, StreamListGroupReply, StreamListGroupRequest,
+        StreamOffsetByTimestampReply, StreamOffsetByTimestampRequest, StreamReadByKeyRequest,
+        StreamReadByOffsetReply, StreamReadByOffsetRequest, StreamReadByTimestampRequest,
+        StreamReadReply, StreamReadRequest, StreamResetGroupOffsetRequest, StreamWriteReply,
+        StreamWriteRequest, WatchReply, WatchRequest,
+    },
 };
 use std::sync::Arc;
+use tonic::codec::Streaming;
 
 pub async fn placement_set(
     client_poll: Arc<ClientPool>,
@@ -125,6 +136,455 @@ pub async fn placement_exists(
     }
 }
 
+pub async fn placement_stream_create_shard(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: StreamCreateShardRequest,
+) -> Result<CommonReply, CommonError> {
+    let request_data = StreamCreateShardRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Kv,
+        PlacementCenterInterface::StreamCreateShard,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match CommonReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn placement_stream_delete_shard(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: StreamDeleteShardRequest,
+) -> Result<CommonReply, CommonError> {
+    let request_data = StreamDeleteShardRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Kv,
+        PlacementCenterInterface::StreamDeleteShard,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match CommonReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn placement_stream_write(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: StreamWriteRequest,
+) -> Result<StreamWriteReply, CommonError> {
+    let request_data = StreamWriteRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Kv,
+        PlacementCenterInterface::StreamWrite,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match StreamWriteReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn placement_stream_read(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: StreamReadRequest,
+) -> Result<StreamReadReply, CommonError> {
+    let request_data = StreamReadRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Kv,
+        PlacementCenterInterface::StreamRead,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match StreamReadReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn placement_stream_commit_offset(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: StreamCommitOffsetRequest,
+) -> Result<CommonReply, CommonError> {
+    let request_data = StreamCommitOffsetRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Kv,
+        PlacementCenterInterface::StreamCommitOffset,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match CommonReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn placement_stream_read_by_offset(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: StreamReadByOffsetRequest,
+) -> Result<StreamReadByOffsetReply, CommonError> {
+    let request_data = StreamReadByOffsetRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Kv,
+        PlacementCenterInterface::StreamReadByOffset,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match StreamReadByOffsetReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn placement_stream_read_by_timestamp(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: StreamReadByTimestampRequest,
+) -> Result<StreamReadReply, CommonError> {
+    let request_data = StreamReadByTimestampRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Kv,
+        PlacementCenterInterface::StreamReadByTimestamp,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match StreamReadReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn placement_stream_read_by_key(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: StreamReadByKeyRequest,
+) -> Result<StreamReadByOffsetReply, CommonError> {
+    let request_data = StreamReadByKeyRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Kv,
+        PlacementCenterInterface::StreamReadByKey,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match StreamReadByOffsetReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn placement_stream_get_shard(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: StreamGetShardRequest,
+) -> Result<StreamGetShardReply, CommonError> {
+    let request_data = StreamGetShardRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Kv,
+        PlacementCenterInterface::StreamGetShard,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match StreamGetShardReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn placement_stream_list_group(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: StreamListGroupRequest,
+) -> Result<StreamListGroupReply, CommonError> {
+    let request_data = StreamListGroupRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Kv,
+        PlacementCenterInterface::StreamListGroup,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match StreamListGroupReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn placement_stream_offset_by_timestamp(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: StreamOffsetByTimestampRequest,
+) -> Result<StreamOffsetByTimestampReply, CommonError> {
+    let request_data = StreamOffsetByTimestampRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Kv,
+        PlacementCenterInterface::StreamOffsetByTimestamp,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match StreamOffsetByTimestampReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn placement_stream_reset_group_offset(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: StreamResetGroupOffsetRequest,
+) -> Result<CommonReply, CommonError> {
+    let request_data = StreamResetGroupOffsetRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Kv,
+        PlacementCenterInterface::StreamResetGroupOffset,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match CommonReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn placement_list(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: ListRequest,
+) -> Result<ListReply, CommonError> {
+    let request_data = ListRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Kv,
+        PlacementCenterInterface::List,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match ListReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn placement_compare_and_swap(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: CompareAndSwapRequest,
+) -> Result<CompareAndSwapReply, CommonError> {
+    let request_data = CompareAndSwapRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Kv,
+        PlacementCenterInterface::CompareAndSwap,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match CompareAndSwapReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn placement_lease_grant(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: LeaseGrantRequest,
+) -> Result<LeaseGrantReply, CommonError> {
+    let request_data = LeaseGrantRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Kv,
+        PlacementCenterInterface::LeaseGrant,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match LeaseGrantReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn placement_lease_revoke(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: LeaseRevokeRequest,
+) -> Result<CommonReply, CommonError> {
+    let request_data = LeaseRevokeRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Kv,
+        PlacementCenterInterface::LeaseRevoke,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match CommonReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn placement_lease_keep_alive(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: LeaseKeepAliveRequest,
+) -> Result<LeaseKeepAliveReply, CommonError> {
+    let request_data = LeaseKeepAliveRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Kv,
+        PlacementCenterInterface::LeaseKeepAlive,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match LeaseKeepAliveReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+// Opens the watch on the first node that accepts it. Every node serves the watches from
+// the data it applied, so the watch does not need the leader.
+pub async fn placement_watch(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: WatchRequest,
+) -> Result<Streaming<WatchReply>, CommonError> {
+    let mut last_error = CommonError::CommmonError("no placement center address".to_string());
+    for addr in addrs {
+        let mut client = match client_poll.placement_center_kv_services_client(addr).await {
+            Ok(client) => client,
+            Err(e) => {
+                last_error = e;
+                continue;
+            }
+        };
+        match client.watch(request.clone()).await {
+            Ok(reply) => return Ok(reply.into_inner()),
+            Err(e) => last_error = CommonError::GrpcServerStatus(e),
+        }
+    }
+    return Err(last_error);
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -139,7 +599,11 @@ mod tests {
         let addrs = vec!["127.0.0.1:1228".to_string()];
         let key = "test-sub-name".to_string();
         let value = "test-group-name".to_string();
-        let request = SetRequest { key:key.clone(), value };
+        let request = SetRequest {
+            key: key.clone(),
+            value,
+            ..Default::default()
+        };
         match placement_set(client_poll.clone(), addrs.clone(), request).await {
             Ok(da) => {
                 println!("{:?}", da);
@@ -151,7 +615,10 @@ mod tests {
             }
         }
 
-        let get_req = GetRequest { key };
+        let get_req = GetRequest {
+            key,
+            stale_read: false,
+        };
         match placement_get(client_poll, addrs, get_req).await {
             Ok(da) => {
                 println!("{:?}", da);
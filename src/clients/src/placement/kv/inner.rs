@@ -17,8 +17,15 @@ use prost::Message;
 use protocol::placement_center::generate::{
     common::CommonReply,
     kv::{
-        kv_service_client::KvServiceClient, DeleteRequest, ExistsReply, ExistsRequest, GetReply,
-        GetRequest, SetRequest,
+        kv_service_client::KvServiceClient, CompareAndSwapReply, CompareAndSwapRequest,
+        DeleteRequest, ExistsReply, ExistsRequest, GetReply, GetRequest, LeaseGrantReply,
+        LeaseGrantRequest, LeaseKeepAliveReply, LeaseKeepAliveRequest, LeaseRevokeRequest,
+        ListReply, ListRequest, SetRequest, StreamCommitOffsetRequest, StreamCreateShardRequest,
+        StreamDeleteShardRequest, StreamGetShardReply, StreamGetShardRequest, StreamListGroupReply,
+        StreamListGroupRequest, StreamOffsetByTimestampReply, StreamOffsetByTimestampRequest,
+        StreamReadByKeyRequest, StreamReadByOffsetReply, StreamReadByOffsetRequest,
+        StreamReadByTimestampRequest, StreamReadReply, StreamReadRequest,
+        StreamResetGroupOffsetRequest, StreamWriteReply, StreamWriteRequest,
     },
 };
 use tonic::transport::Channel;
@@ -90,3 +97,292 @@ pub(crate) async fn inner_exists(
         }
     }
 }
+
+pub(crate) async fn inner_stream_create_shard(
+    mut client: KvServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match StreamCreateShardRequest::decode(request.as_ref()) {
+        Ok(request) => match client.stream_create_shard(request).await {
+            Ok(result) => {
+                return Ok(CommonReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_stream_delete_shard(
+    mut client: KvServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match StreamDeleteShardRequest::decode(request.as_ref()) {
+        Ok(request) => match client.stream_delete_shard(request).await {
+            Ok(result) => {
+                return Ok(CommonReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_stream_write(
+    mut client: KvServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match StreamWriteRequest::decode(request.as_ref()) {
+        Ok(request) => match client.stream_write(request).await {
+            Ok(result) => {
+                return Ok(StreamWriteReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_stream_read(
+    mut client: KvServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match StreamReadRequest::decode(request.as_ref()) {
+        Ok(request) => match client.stream_read(request).await {
+            Ok(result) => {
+                return Ok(StreamReadReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_stream_commit_offset(
+    mut client: KvServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match StreamCommitOffsetRequest::decode(request.as_ref()) {
+        Ok(request) => match client.stream_commit_offset(request).await {
+            Ok(result) => {
+                return Ok(CommonReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_stream_read_by_offset(
+    mut client: KvServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match StreamReadByOffsetRequest::decode(request.as_ref()) {
+        Ok(request) => match client.stream_read_by_offset(request).await {
+            Ok(result) => {
+                return Ok(StreamReadByOffsetReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_stream_read_by_timestamp(
+    mut client: KvServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match StreamReadByTimestampRequest::decode(request.as_ref()) {
+        Ok(request) => match client.stream_read_by_timestamp(request).await {
+            Ok(result) => {
+                return Ok(StreamReadReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_stream_read_by_key(
+    mut client: KvServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match StreamReadByKeyRequest::decode(request.as_ref()) {
+        Ok(request) => match client.stream_read_by_key(request).await {
+            Ok(result) => {
+                return Ok(StreamReadByOffsetReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_stream_get_shard(
+    mut client: KvServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match StreamGetShardRequest::decode(request.as_ref()) {
+        Ok(request) => match client.stream_get_shard(request).await {
+            Ok(result) => {
+                return Ok(StreamGetShardReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_stream_list_group(
+    mut client: KvServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match StreamListGroupRequest::decode(request.as_ref()) {
+        Ok(request) => match client.stream_list_group(request).await {
+            Ok(result) => {
+                return Ok(StreamListGroupReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_stream_offset_by_timestamp(
+    mut client: KvServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match StreamOffsetByTimestampRequest::decode(request.as_ref()) {
+        Ok(request) => match client.stream_offset_by_timestamp(request).await {
+            Ok(result) => {
+                return Ok(StreamOffsetByTimestampReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_stream_reset_group_offset(
+    mut client: KvServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match StreamResetGroupOffsetRequest::decode(request.as_ref()) {
+        Ok(request) => match client.stream_reset_group_offset(request).await {
+            Ok(result) => {
+                return Ok(CommonReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_list(
+    mut client: KvServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match ListRequest::decode(request.as_ref()) {
+        Ok(request) => match client.list(request).await {
+            Ok(result) => {
+                return Ok(ListReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_compare_and_swap(
+    mut client: KvServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match CompareAndSwapRequest::decode(request.as_ref()) {
+        Ok(request) => match client.compare_and_swap(request).await {
+            Ok(result) => {
+                return Ok(CompareAndSwapReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_lease_grant(
+    mut client: KvServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match LeaseGrantRequest::decode(request.as_ref()) {
+        Ok(request) => match client.lease_grant(request).await {
+            Ok(result) => {
+                return Ok(LeaseGrantReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_lease_revoke(
+    mut client: KvServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match LeaseRevokeRequest::decode(request.as_ref()) {
+        Ok(request) => match client.lease_revoke(request).await {
+            Ok(result) => {
+                return Ok(CommonReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_lease_keep_alive(
+    mut client: KvServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match LeaseKeepAliveRequest::decode(request.as_ref()) {
+        Ok(request) => match client.lease_keep_alive(request).await {
+            Ok(result) => {
+                return Ok(LeaseKeepAliveReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
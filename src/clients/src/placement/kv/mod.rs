@@ -13,7 +13,14 @@
 
 
 use crate::poll::ClientPool;
-use self::inner::{inner_delete, inner_exists, inner_get, inner_set};
+use self::inner::{
+    inner_compare_and_swap, inner_delete, inner_exists, inner_get, inner_lease_grant,
+    inner_lease_keep_alive, inner_lease_revoke, inner_list, inner_set, inner_stream_commit_offset,
+    inner_stream_create_shard, inner_stream_delete_shard, inner_stream_get_shard,
+    inner_stream_list_group, inner_stream_offset_by_timestamp, inner_stream_read,
+    inner_stream_read_by_key, inner_stream_read_by_offset, inner_stream_read_by_timestamp,
+    inner_stream_reset_group_offset, inner_stream_write,
+};
 use super::PlacementCenterInterface;
 use common_base::error::common::CommonError;
 use mobc::Manager;
@@ -51,6 +58,55 @@ pub(crate) async fn kv_interface_call(
                 PlacementCenterInterface::Delete => inner_delete(client, request.clone()).await,
                 PlacementCenterInterface::Get => inner_get(client, request.clone()).await,
                 PlacementCenterInterface::Exists => inner_exists(client, request.clone()).await,
+                PlacementCenterInterface::StreamCreateShard => {
+                    inner_stream_create_shard(client, request.clone()).await
+                }
+                PlacementCenterInterface::StreamDeleteShard => {
+                    inner_stream_delete_shard(client, request.clone()).await
+                }
+                PlacementCenterInterface::StreamWrite => {
+                    inner_stream_write(client, request.clone()).await
+                }
+                PlacementCenterInterface::StreamRead => {
+                    inner_stream_read(client, request.clone()).await
+                }
+                PlacementCenterInterface::StreamCommitOffset => {
+                    inner_stream_commit_offset(client, request.clone()).await
+                }
+                PlacementCenterInterface::StreamReadByOffset => {
+                    inner_stream_read_by_offset(client, request.clone()).await
+                }
+                PlacementCenterInterface::StreamReadByTimestamp => {
+                    inner_stream_read_by_timestamp(client, request.clone()).await
+                }
+                PlacementCenterInterface::StreamReadByKey => {
+                    inner_stream_read_by_key(client, request.clone()).await
+                }
+                PlacementCenterInterface::StreamGetShard => {
+                    inner_stream_get_shard(client, request.clone()).await
+                }
+                PlacementCenterInterface::StreamListGroup => {
+                    inner_stream_list_group(client, request.clone()).await
+                }
+                PlacementCenterInterface::StreamOffsetByTimestamp => {
+                    inner_stream_offset_by_timestamp(client, request.clone()).await
+                }
+                PlacementCenterInterface::StreamResetGroupOffset => {
+                    inner_stream_reset_group_offset(client, request.clone()).await
+                }
+                PlacementCenterInterface::List => inner_list(client, request.clone()).await,
+                PlacementCenterInterface::CompareAndSwap => {
+                    inner_compare_and_swap(client, request.clone()).await
+                }
+                PlacementCenterInterface::LeaseGrant => {
+                    inner_lease_grant(client, request.clone()).await
+                }
+                PlacementCenterInterface::LeaseRevoke => {
+                    inner_lease_revoke(client, request.clone()).await
+                }
+                PlacementCenterInterface::LeaseKeepAlive => {
+                    inner_lease_keep_alive(client, request.clone()).await
+                }
                 _ => return Err(CommonError::CommmonError(format!(
                     "kv service does not support service interfaces [{:?}]",
                     interface
@@ -0,0 +1,205 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::placement::call::{
+    acquire_lock, campaign, get_election_leader, get_lock, release_lock, resign,
+};
+use crate::poll::ClientPool;
+use common_base::error::common::CommonError;
+use protocol::placement_center::generate::placement::{
+    AcquireLockRequest, CampaignRequest, GetElectionLeaderRequest, GetLockRequest, LockHolder,
+    ReleaseLockRequest, ResignRequest,
+};
+use std::sync::Arc;
+
+// A lock held by a broker or journal node. It is released by placement center when the node
+// stops sending heartbeats for ttl seconds, so every write made under the lock should carry
+// the fencing token and be rejected when a larger one was seen.
+pub struct DistributedLock {
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    lock_name: String,
+    holder: LockHolder,
+    ttl: u64,
+    fencing_token: Option<u64>,
+}
+
+impl DistributedLock {
+    pub fn new(
+        client_poll: Arc<ClientPool>,
+        addrs: Vec<String>,
+        lock_name: String,
+        holder: LockHolder,
+        ttl: u64,
+    ) -> Self {
+        return DistributedLock {
+            client_poll,
+            addrs,
+            lock_name,
+            holder,
+            ttl,
+            fencing_token: None,
+        };
+    }
+
+    // Waits up to timeout_ms for the lock and returns its fencing token, None when another
+    // holder kept it.
+    pub async fn acquire(&mut self, timeout_ms: u64) -> Result<Option<u64>, CommonError> {
+        let request = AcquireLockRequest {
+            lock_name: self.lock_name.clone(),
+            holder: Some(self.holder.clone()),
+            ttl: self.ttl,
+            timeout_ms,
+            acquire_time: 0,
+        };
+        let reply = acquire_lock(self.client_poll.clone(), self.addrs.clone(), request).await?;
+        if !reply.acquired {
+            self.fencing_token = None;
+            return Ok(None);
+        }
+        self.fencing_token = Some(reply.fencing_token);
+        return Ok(self.fencing_token);
+    }
+
+    pub async fn release(&mut self) -> Result<(), CommonError> {
+        let fencing_token = match self.fencing_token.take() {
+            Some(token) => token,
+            None => return Ok(()),
+        };
+        let request = ReleaseLockRequest {
+            lock_name: self.lock_name.clone(),
+            holder: Some(self.holder.clone()),
+            fencing_token,
+        };
+        release_lock(self.client_poll.clone(), self.addrs.clone(), request).await?;
+        return Ok(());
+    }
+
+    pub fn fencing_token(&self) -> Option<u64> {
+        return self.fencing_token;
+    }
+
+    // The current holder of the lock and its fencing token
+    pub async fn holder(&self) -> Result<Option<(LockHolder, u64)>, CommonError> {
+        let request = GetLockRequest {
+            lock_name: self.lock_name.clone(),
+        };
+        let reply = get_lock(self.client_poll.clone(), self.addrs.clone(), request).await?;
+        if !reply.held {
+            return Ok(None);
+        }
+        return Ok(Some((
+            reply.holder.unwrap_or_default(),
+            reply.fencing_token,
+        )));
+    }
+}
+
+// The leadership of an election among the nodes of a cluster. The term grows with every new
+// leader and fences the writes of the previous ones.
+pub struct LeaderElection {
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    election_name: String,
+    candidate: LockHolder,
+    value: String,
+    ttl: u64,
+    term: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ElectionLeader {
+    pub leader: LockHolder,
+    pub value: String,
+    pub term: u64,
+}
+
+impl LeaderElection {
+    pub fn new(
+        client_poll: Arc<ClientPool>,
+        addrs: Vec<String>,
+        election_name: String,
+        candidate: LockHolder,
+        value: String,
+        ttl: u64,
+    ) -> Self {
+        return LeaderElection {
+            client_poll,
+            addrs,
+            election_name,
+            candidate,
+            value,
+            ttl,
+            term: None,
+        };
+    }
+
+    // Waits up to timeout_ms to become the leader, and returns the leader of the election
+    // either way.
+    pub async fn campaign(&mut self, timeout_ms: u64) -> Result<ElectionLeader, CommonError> {
+        let request = CampaignRequest {
+            election_name: self.election_name.clone(),
+            candidate: Some(self.candidate.clone()),
+            value: self.value.clone(),
+            ttl: self.ttl,
+            timeout_ms,
+            acquire_time: 0,
+        };
+        let reply = campaign(self.client_poll.clone(), self.addrs.clone(), request).await?;
+        self.term = if reply.elected {
+            Some(reply.term)
+        } else {
+            None
+        };
+        return Ok(ElectionLeader {
+            leader: reply.leader.unwrap_or_default(),
+            value: reply.value,
+            term: reply.term,
+        });
+    }
+
+    pub async fn resign(&mut self) -> Result<(), CommonError> {
+        let term = match self.term.take() {
+            Some(term) => term,
+            None => return Ok(()),
+        };
+        let request = ResignRequest {
+            election_name: self.election_name.clone(),
+            leader: Some(self.candidate.clone()),
+            term,
+        };
+        resign(self.client_poll.clone(), self.addrs.clone(), request).await?;
+        return Ok(());
+    }
+
+    // The term of this node while it leads
+    pub fn term(&self) -> Option<u64> {
+        return self.term;
+    }
+
+    pub async fn leader(&self) -> Result<Option<ElectionLeader>, CommonError> {
+        let request = GetElectionLeaderRequest {
+            election_name: self.election_name.clone(),
+        };
+        let reply =
+            get_election_leader(self.client_poll.clone(), self.addrs.clone(), request).await?;
+        if !reply.has_leader {
+            return Ok(None);
+        }
+        return Ok(Some(ElectionLeader {
+            leader: reply.leader.unwrap_or_default(),
+            value: reply.value,
+            term: reply.term,
+        }));
+    }
+}
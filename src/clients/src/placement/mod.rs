@@ -36,19 +36,55 @@ pub enum PlacementCenterInterface {
     Get,
     Delete,
     Exists,
+    StreamCreateShard,
+    StreamDeleteShard,
+    StreamWrite,
+    StreamRead,
+    StreamCommitOffset,
+    StreamReadByOffset,
+    StreamReadByTimestamp,
+    StreamReadByKey,
+    StreamGetShard,
+    StreamListGroup,
+    StreamOffsetByTimestamp,
+    StreamResetGroupOffset,
+    List,
+    CompareAndSwap,
+    LeaseGrant,
+    LeaseRevoke,
+    LeaseKeepAlive,
 
     // placement inner interface
     RegisterNode,
     UnRegisterNode,
     Heartbeat,
+    ReportMonitor,
     SendRaftMessage,
     SendRaftConfChange,
+    SendRaftSnapshot,
+    ListRaftMember,
+    AddRaftMember,
+    PromoteRaftLearner,
+    RemoveRaftMember,
+    TransferRaftLeader,
+    AcquireLock,
+    ReleaseLock,
+    GetLock,
+    Campaign,
+    Resign,
+    GetElectionLeader,
+    CreateBackup,
+    ReadBackup,
+    ImportMetadata,
 
     // journal service interface
     CreateShard,
     DeleteShard,
+    GetShard,
+    ListShard,
     CreateSegment,
     DeleteSegment,
+    ReportShardStatus,
 
     // mqtt service interface
     GetShareSub,
@@ -77,9 +113,28 @@ pub enum PlacementCenterInterface {
 
 pub mod journal;
 pub mod kv;
+pub mod lock;
 pub mod mqtt;
 pub mod placement;
 
+// gRPC metadata a placement center node that is not the leader sets on the error of a request
+// only the leader can serve, with the address of the leader.
+pub const LEADER_ADDR_METADATA_KEY: &str = "placement-leader-addr";
+
+// The leader a rejected request should be sent to.
+fn leader_hint(err: &CommonError) -> Option<String> {
+    if let CommonError::GrpcServerStatus(status) = err {
+        if let Some(value) = status.metadata().get(LEADER_ADDR_METADATA_KEY) {
+            if let Ok(addr) = value.to_str() {
+                if !addr.is_empty() {
+                    return Some(addr.to_string());
+                }
+            }
+        }
+    }
+    return None;
+}
+
 async fn retry_call(
     service: PlacementCenterService,
     interface: PlacementCenterInterface,
@@ -88,9 +143,13 @@ async fn retry_call(
     request: Vec<u8>,
 ) -> Result<Vec<u8>, CommonError> {
     let mut times = 1;
+    let mut leader_addr: Option<String> = None;
     loop {
         let index = times % addrs.len();
-        let addr = addrs.get(index).unwrap().clone();
+        let addr = match leader_addr.take() {
+            Some(addr) => addr,
+            None => addrs.get(index).unwrap().clone(),
+        };
         let result = match service {
             PlacementCenterService::Journal => {
                 journal_interface_call(
@@ -143,8 +202,36 @@ async fn retry_call(
                     return Err(e);
                 }
                 times = times + 1;
+                // The node is not the leader, the request is sent to the leader right away.
+                if let Some(leader) = leader_hint(&e) {
+                    if leader != addr {
+                        leader_addr = Some(leader);
+                        continue;
+                    }
+                }
             }
         }
         sleep(Duration::from_secs(retry_sleep_time(times) as u64)).await;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{leader_hint, LEADER_ADDR_METADATA_KEY};
+    use common_base::error::common::CommonError;
+    use tonic::{metadata::MetadataMap, Code, Status};
+
+    #[test]
+    fn leader_hint_from_status() {
+        let mut metadata = MetadataMap::new();
+        metadata.insert(LEADER_ADDR_METADATA_KEY, "127.0.0.1:2228".parse().unwrap());
+        let status = Status::with_metadata(Code::FailedPrecondition, "not leader", metadata);
+        let err = CommonError::GrpcServerStatus(status);
+        assert_eq!(leader_hint(&err), Some("127.0.0.1:2228".to_string()));
+
+        let err = CommonError::GrpcServerStatus(Status::unavailable("no leader"));
+        assert_eq!(leader_hint(&err), None);
+        let err = CommonError::CommmonError("x".to_string());
+        assert_eq!(leader_hint(&err), None);
+    }
+}
@@ -448,6 +448,7 @@ mod tests {
         let request = GetShareSubLeaderRequest {
             group_name,
             cluster_name,
+            stale_read: false,
         };
         match placement_get_share_sub_leader(client_poll, addrs, request).await {
             Ok(da) => {
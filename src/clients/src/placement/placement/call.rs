@@ -20,11 +20,17 @@ use prost::Message;
 use protocol::placement_center::generate::{
     common::CommonReply,
     placement::{
-        DeleteIdempotentDataRequest, DeleteResourceConfigRequest, ExistsIdempotentDataReply,
-        ExistsIdempotentDataRequest, GetResourceConfigReply, GetResourceConfigRequest,
-        HeartbeatRequest, RegisterNodeRequest, SendRaftConfChangeReply, SendRaftConfChangeRequest,
-        SendRaftMessageReply, SendRaftMessageRequest, SetIdempotentDataRequest,
-        SetResourceConfigRequest, UnRegisterNodeRequest,
+        AcquireLockReply, AcquireLockRequest, AddRaftMemberRequest, CampaignReply, CampaignRequest,
+        CreateBackupReply, CreateBackupRequest, DeleteIdempotentDataRequest,
+        DeleteResourceConfigRequest, ExistsIdempotentDataReply, ExistsIdempotentDataRequest,
+        GetElectionLeaderReply, GetElectionLeaderRequest, GetLockReply, GetLockRequest,
+        GetResourceConfigReply, GetResourceConfigRequest, HeartbeatRequest, ImportMetadataRequest,
+        ListRaftMemberReply, ListRaftMemberRequest, PromoteRaftLearnerRequest, ReadBackupReply,
+        ReadBackupRequest, RegisterNodeRequest, ReleaseLockRequest, RemoveRaftMemberRequest,
+        ReportMonitorRequest, ResignRequest, SendRaftConfChangeReply, SendRaftConfChangeRequest,
+        SendRaftMessageReply, SendRaftMessageRequest, SendRaftSnapshotReply,
+        SendRaftSnapshotRequest, SetIdempotentDataRequest, SetResourceConfigRequest,
+        TransferRaftLeaderRequest, UnRegisterNodeRequest,
     },
 };
 use std::sync::Arc;
@@ -104,6 +110,31 @@ pub async fn heartbeat(
     }
 }
 
+pub async fn report_monitor(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: ReportMonitorRequest,
+) -> Result<CommonReply, CommonError> {
+    let request_data = ReportMonitorRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Placement,
+        PlacementCenterInterface::ReportMonitor,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match CommonReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
 pub async fn send_raft_message(
     client_poll: Arc<ClientPool>,
     addrs: Vec<String>,
@@ -303,3 +334,378 @@ pub async fn exists_idempotent_data(
         }
     }
 }
+
+pub async fn send_raft_snapshot(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: SendRaftSnapshotRequest,
+) -> Result<SendRaftSnapshotReply, CommonError> {
+    let request_data = SendRaftSnapshotRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Placement,
+        PlacementCenterInterface::SendRaftSnapshot,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match SendRaftSnapshotReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn list_raft_member(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: ListRaftMemberRequest,
+) -> Result<ListRaftMemberReply, CommonError> {
+    let request_data = ListRaftMemberRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Placement,
+        PlacementCenterInterface::ListRaftMember,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match ListRaftMemberReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn add_raft_member(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: AddRaftMemberRequest,
+) -> Result<CommonReply, CommonError> {
+    let request_data = AddRaftMemberRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Placement,
+        PlacementCenterInterface::AddRaftMember,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match CommonReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn promote_raft_learner(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: PromoteRaftLearnerRequest,
+) -> Result<CommonReply, CommonError> {
+    let request_data = PromoteRaftLearnerRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Placement,
+        PlacementCenterInterface::PromoteRaftLearner,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match CommonReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn remove_raft_member(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: RemoveRaftMemberRequest,
+) -> Result<CommonReply, CommonError> {
+    let request_data = RemoveRaftMemberRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Placement,
+        PlacementCenterInterface::RemoveRaftMember,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match CommonReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn transfer_raft_leader(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: TransferRaftLeaderRequest,
+) -> Result<CommonReply, CommonError> {
+    let request_data = TransferRaftLeaderRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Placement,
+        PlacementCenterInterface::TransferRaftLeader,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match CommonReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn acquire_lock(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: AcquireLockRequest,
+) -> Result<AcquireLockReply, CommonError> {
+    let request_data = AcquireLockRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Placement,
+        PlacementCenterInterface::AcquireLock,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match AcquireLockReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn release_lock(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: ReleaseLockRequest,
+) -> Result<CommonReply, CommonError> {
+    let request_data = ReleaseLockRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Placement,
+        PlacementCenterInterface::ReleaseLock,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match CommonReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn get_lock(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: GetLockRequest,
+) -> Result<GetLockReply, CommonError> {
+    let request_data = GetLockRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Placement,
+        PlacementCenterInterface::GetLock,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match GetLockReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn campaign(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: CampaignRequest,
+) -> Result<CampaignReply, CommonError> {
+    let request_data = CampaignRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Placement,
+        PlacementCenterInterface::Campaign,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match CampaignReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn resign(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: ResignRequest,
+) -> Result<CommonReply, CommonError> {
+    let request_data = ResignRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Placement,
+        PlacementCenterInterface::Resign,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match CommonReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn get_election_leader(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: GetElectionLeaderRequest,
+) -> Result<GetElectionLeaderReply, CommonError> {
+    let request_data = GetElectionLeaderRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Placement,
+        PlacementCenterInterface::GetElectionLeader,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match GetElectionLeaderReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn create_backup(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: CreateBackupRequest,
+) -> Result<CreateBackupReply, CommonError> {
+    let request_data = CreateBackupRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Placement,
+        PlacementCenterInterface::CreateBackup,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match CreateBackupReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn read_backup(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: ReadBackupRequest,
+) -> Result<ReadBackupReply, CommonError> {
+    let request_data = ReadBackupRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Placement,
+        PlacementCenterInterface::ReadBackup,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match ReadBackupReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
+
+pub async fn import_metadata(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    request: ImportMetadataRequest,
+) -> Result<CommonReply, CommonError> {
+    let request_data = ImportMetadataRequest::encode_to_vec(&request);
+    match retry_call(
+        PlacementCenterService::Placement,
+        PlacementCenterInterface::ImportMetadata,
+        client_poll,
+        addrs,
+        request_data,
+    )
+    .await
+    {
+        Ok(data) => match CommonReply::decode(data.as_ref()) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        Err(e) => {
+            return Err(e);
+        }
+    }
+}
@@ -16,12 +16,18 @@ use prost::Message;
 use protocol::placement_center::generate::{
     common::CommonReply,
     placement::{
-        placement_center_service_client::PlacementCenterServiceClient, DeleteIdempotentDataRequest,
+        placement_center_service_client::PlacementCenterServiceClient, AcquireLockReply,
+        AcquireLockRequest, AddRaftMemberRequest, CampaignReply, CampaignRequest,
+        CreateBackupReply, CreateBackupRequest, DeleteIdempotentDataRequest,
         DeleteResourceConfigRequest, ExistsIdempotentDataReply, ExistsIdempotentDataRequest,
-        GetResourceConfigReply, GetResourceConfigRequest, HeartbeatRequest, RegisterNodeRequest,
-        SendRaftConfChangeReply, SendRaftConfChangeRequest, SendRaftMessageReply,
-        SendRaftMessageRequest, SetIdempotentDataRequest, SetResourceConfigRequest,
-        UnRegisterNodeRequest,
+        GetElectionLeaderReply, GetElectionLeaderRequest, GetLockReply, GetLockRequest,
+        GetResourceConfigReply, GetResourceConfigRequest, HeartbeatRequest, ImportMetadataRequest,
+        ListRaftMemberReply, ListRaftMemberRequest, PromoteRaftLearnerRequest, ReadBackupReply,
+        ReadBackupRequest, RegisterNodeRequest, ReleaseLockRequest, RemoveRaftMemberRequest,
+        ReportMonitorRequest, ResignRequest, SendRaftConfChangeReply, SendRaftConfChangeRequest,
+        SendRaftMessageReply, SendRaftMessageRequest, SendRaftSnapshotReply,
+        SendRaftSnapshotRequest, SetIdempotentDataRequest, SetResourceConfigRequest,
+        TransferRaftLeaderRequest, UnRegisterNodeRequest,
     },
 };
 use tonic::transport::Channel;
@@ -77,6 +83,23 @@ pub(crate) async fn inner_heartbeat(
     }
 }
 
+pub(crate) async fn inner_report_monitor(
+    mut client: PlacementCenterServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match ReportMonitorRequest::decode(request.as_ref()) {
+        Ok(request) => match client.report_monitor(request).await {
+            Ok(result) => {
+                return Ok(CommonReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
 pub(crate) async fn inner_send_raft_message(
     mut client: PlacementCenterServiceClient<Channel>,
     request: Vec<u8>,
@@ -214,3 +237,258 @@ pub(crate) async fn inner_delete_idempotent(
         }
     }
 }
+
+pub(crate) async fn inner_send_raft_snapshot(
+    mut client: PlacementCenterServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match SendRaftSnapshotRequest::decode(request.as_ref()) {
+        Ok(request) => match client.send_raft_snapshot(request).await {
+            Ok(result) => {
+                return Ok(SendRaftSnapshotReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_list_raft_member(
+    mut client: PlacementCenterServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match ListRaftMemberRequest::decode(request.as_ref()) {
+        Ok(request) => match client.list_raft_member(request).await {
+            Ok(result) => {
+                return Ok(ListRaftMemberReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_add_raft_member(
+    mut client: PlacementCenterServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match AddRaftMemberRequest::decode(request.as_ref()) {
+        Ok(request) => match client.add_raft_member(request).await {
+            Ok(result) => {
+                return Ok(CommonReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_promote_raft_learner(
+    mut client: PlacementCenterServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match PromoteRaftLearnerRequest::decode(request.as_ref()) {
+        Ok(request) => match client.promote_raft_learner(request).await {
+            Ok(result) => {
+                return Ok(CommonReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_remove_raft_member(
+    mut client: PlacementCenterServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match RemoveRaftMemberRequest::decode(request.as_ref()) {
+        Ok(request) => match client.remove_raft_member(request).await {
+            Ok(result) => {
+                return Ok(CommonReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_transfer_raft_leader(
+    mut client: PlacementCenterServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match TransferRaftLeaderRequest::decode(request.as_ref()) {
+        Ok(request) => match client.transfer_raft_leader(request).await {
+            Ok(result) => {
+                return Ok(CommonReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_acquire_lock(
+    mut client: PlacementCenterServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match AcquireLockRequest::decode(request.as_ref()) {
+        Ok(request) => match client.acquire_lock(request).await {
+            Ok(result) => {
+                return Ok(AcquireLockReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_release_lock(
+    mut client: PlacementCenterServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match ReleaseLockRequest::decode(request.as_ref()) {
+        Ok(request) => match client.release_lock(request).await {
+            Ok(result) => {
+                return Ok(CommonReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_get_lock(
+    mut client: PlacementCenterServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match GetLockRequest::decode(request.as_ref()) {
+        Ok(request) => match client.get_lock(request).await {
+            Ok(result) => {
+                return Ok(GetLockReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_campaign(
+    mut client: PlacementCenterServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match CampaignRequest::decode(request.as_ref()) {
+        Ok(request) => match client.campaign(request).await {
+            Ok(result) => {
+                return Ok(CampaignReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_resign(
+    mut client: PlacementCenterServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match ResignRequest::decode(request.as_ref()) {
+        Ok(request) => match client.resign(request).await {
+            Ok(result) => {
+                return Ok(CommonReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_get_election_leader(
+    mut client: PlacementCenterServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match GetElectionLeaderRequest::decode(request.as_ref()) {
+        Ok(request) => match client.get_election_leader(request).await {
+            Ok(result) => {
+                return Ok(GetElectionLeaderReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_create_backup(
+    mut client: PlacementCenterServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match CreateBackupRequest::decode(request.as_ref()) {
+        Ok(request) => match client.create_backup(request).await {
+            Ok(result) => {
+                return Ok(CreateBackupReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_read_backup(
+    mut client: PlacementCenterServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match ReadBackupRequest::decode(request.as_ref()) {
+        Ok(request) => match client.read_backup(request).await {
+            Ok(result) => {
+                return Ok(ReadBackupReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
+
+pub(crate) async fn inner_import_metadata(
+    mut client: PlacementCenterServiceClient<Channel>,
+    request: Vec<u8>,
+) -> Result<Vec<u8>, CommonError> {
+    match ImportMetadataRequest::decode(request.as_ref()) {
+        Ok(request) => match client.import_metadata(request).await {
+            Ok(result) => {
+                return Ok(CommonReply::encode_to_vec(&result.into_inner()));
+            }
+            Err(e) => return Err(CommonError::GrpcServerStatus(e)),
+        },
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
@@ -13,8 +13,12 @@
 
 use common_base::error::common::CommonError;
 use inner::{
+    inner_acquire_lock, inner_add_raft_member, inner_campaign, inner_create_backup,
     inner_delete_idempotent, inner_delete_resource_config, inner_exist_idempotent,
-    inner_get_resource_config, inner_set_idempotent, inner_set_resource_config,
+    inner_get_election_leader, inner_get_lock, inner_get_resource_config, inner_import_metadata,
+    inner_list_raft_member, inner_promote_raft_learner, inner_read_backup, inner_release_lock,
+    inner_remove_raft_member, inner_resign, inner_send_raft_snapshot, inner_set_idempotent,
+    inner_set_resource_config, inner_transfer_raft_leader,
 };
 use mobc::Manager;
 use protocol::placement_center::generate::placement::placement_center_service_client::PlacementCenterServiceClient;
@@ -24,8 +28,8 @@ use tonic::transport::Channel;
 use crate::poll::ClientPool;
 
 use self::inner::{
-    inner_heartbeat, inner_register_node, inner_send_raft_conf_change, inner_send_raft_message,
-    inner_unregister_node,
+    inner_heartbeat, inner_register_node, inner_report_monitor, inner_send_raft_conf_change,
+    inner_send_raft_message, inner_unregister_node,
 };
 
 use super::PlacementCenterInterface;
@@ -51,6 +55,9 @@ pub(crate) async fn placement_interface_call(
                 PlacementCenterInterface::Heartbeat => {
                     inner_heartbeat(client, request.clone()).await
                 }
+                PlacementCenterInterface::ReportMonitor => {
+                    inner_report_monitor(client, request.clone()).await
+                }
                 PlacementCenterInterface::SendRaftMessage => {
                     inner_send_raft_message(client, request.clone()).await
                 }
@@ -75,6 +82,45 @@ pub(crate) async fn placement_interface_call(
                 PlacementCenterInterface::DeleteIdempotentData => {
                     inner_delete_idempotent(client, request.clone()).await
                 }
+                PlacementCenterInterface::SendRaftSnapshot => {
+                    inner_send_raft_snapshot(client, request.clone()).await
+                }
+                PlacementCenterInterface::ListRaftMember => {
+                    inner_list_raft_member(client, request.clone()).await
+                }
+                PlacementCenterInterface::AddRaftMember => {
+                    inner_add_raft_member(client, request.clone()).await
+                }
+                PlacementCenterInterface::PromoteRaftLearner => {
+                    inner_promote_raft_learner(client, request.clone()).await
+                }
+                PlacementCenterInterface::RemoveRaftMember => {
+                    inner_remove_raft_member(client, request.clone()).await
+                }
+                PlacementCenterInterface::TransferRaftLeader => {
+                    inner_transfer_raft_leader(client, request.clone()).await
+                }
+                PlacementCenterInterface::AcquireLock => {
+                    inner_acquire_lock(client, request.clone()).await
+                }
+                PlacementCenterInterface::ReleaseLock => {
+                    inner_release_lock(client, request.clone()).await
+                }
+                PlacementCenterInterface::GetLock => inner_get_lock(client, request.clone()).await,
+                PlacementCenterInterface::Campaign => inner_campaign(client, request.clone()).await,
+                PlacementCenterInterface::Resign => inner_resign(client, request.clone()).await,
+                PlacementCenterInterface::GetElectionLeader => {
+                    inner_get_election_leader(client, request.clone()).await
+                }
+                PlacementCenterInterface::CreateBackup => {
+                    inner_create_backup(client, request.clone()).await
+                }
+                PlacementCenterInterface::ReadBackup => {
+                    inner_read_backup(client, request.clone()).await
+                }
+                PlacementCenterInterface::ImportMetadata => {
+                    inner_import_metadata(client, request.clone()).await
+                }
                 _ => {
                     return Err(CommonError::CommmonError(format!(
                         "placement service does not support service interfaces [{:?}]",
@@ -0,0 +1,233 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap::{command, Parser, Subcommand};
+use clients::placement::placement::call::{
+    add_raft_member, create_backup, import_metadata, list_raft_member, promote_raft_learner,
+    read_backup, remove_raft_member, transfer_raft_leader,
+};
+use clients::poll::ClientPool;
+use common_base::config::placement_center::init_placement_center_conf_by_path;
+use common_base::error::common::CommonError;
+use placement_center::{read_backup_file, restore_metadata};
+use protocol::placement_center::generate::placement::{
+    AddRaftMemberRequest, CreateBackupRequest, ImportMetadataRequest, ListRaftMemberRequest,
+    MetadataEntry, PromoteRaftLearnerRequest, ReadBackupRequest, RemoveRaftMemberRequest,
+    TransferRaftLeaderRequest,
+};
+use std::fs::File;
+use std::io::Write;
+use std::process::exit;
+use std::sync::Arc;
+
+// Bytes of a backup downloaded per request
+const BACKUP_CHUNK_BYTES: u64 = 1024 * 1024;
+
+// Keys imported per raft entry
+const IMPORT_BATCH_NUM: usize = 500;
+
+#[derive(Parser, Debug)]
+#[command(author="robustmq", version="0.0.1", about=" RobustMQ: Manages the members and the metadata of a placement center cluster.", long_about = None)]
+#[command(next_line_help = true)]
+struct ArgsParams {
+    /// Address of any placement center node, requests are forwarded to the leader
+    #[arg(short, long, default_value_t=String::from("127.0.0.1:1228"))]
+    server: String,
+
+    #[command(subcommand)]
+    action: AdminAction,
+}
+
+#[derive(Subcommand, Debug)]
+enum AdminAction {
+    /// Lists the voters and learners of the cluster
+    List,
+    /// Adds a node, start it with the current members as its nodes before adding it
+    Add {
+        #[arg(long)]
+        node_id: u64,
+        #[arg(long)]
+        node_addr: String,
+        /// Adds the node as a learner, which can be promoted once it caught up
+        #[arg(long, default_value_t = false)]
+        learner: bool,
+    },
+    /// Promotes a learner that caught up with the leader to a voter
+    Promote {
+        #[arg(long)]
+        node_id: u64,
+    },
+    /// Removes a node, the leader has to transfer its leadership before it is removed
+    Remove {
+        #[arg(long)]
+        node_id: u64,
+    },
+    /// Transfers the leadership to a voter
+    TransferLeader {
+        #[arg(long)]
+        node_id: u64,
+    },
+    /// Backs up the metadata of the node given by --server while it is running
+    Backup {
+        /// File the backup is written to
+        #[arg(long)]
+        output: String,
+        /// Only backs up the metadata of this cluster
+        #[arg(long)]
+        cluster_name: Option<String>,
+    },
+    /// Restores a backup into the empty data directory of a node, run it on every node of
+    /// the new cluster before their first start
+    Restore {
+        /// Configuration file of the node to restore
+        #[arg(long)]
+        conf: String,
+        #[arg(long)]
+        input: String,
+        /// Only restores the metadata of this cluster
+        #[arg(long)]
+        cluster_name: Option<String>,
+    },
+    /// Imports the metadata of one cluster from a backup into a running cluster
+    Import {
+        #[arg(long)]
+        input: String,
+        #[arg(long)]
+        cluster_name: String,
+    },
+}
+
+#[tokio::main]
+async fn main() {
+    let args = ArgsParams::parse();
+    let client_poll = Arc::new(ClientPool::new(1));
+    if let Err(e) = run(client_poll, vec![args.server], args.action).await {
+        eprintln!("{}", e);
+        exit(1);
+    }
+}
+
+async fn run(
+    client_poll: Arc<ClientPool>,
+    addrs: Vec<String>,
+    action: AdminAction,
+) -> Result<(), CommonError> {
+    match action {
+        AdminAction::List => {
+            let reply = list_raft_member(client_poll, addrs, ListRaftMemberRequest {}).await?;
+            println!(
+                "leader: {}, commit index: {}",
+                reply.leader_id, reply.commit_index
+            );
+            for member in reply.members {
+                let role = if member.learner { "learner" } else { "voter" };
+                println!(
+                    "{}\t{}\t{}\tmatched: {}",
+                    member.node_id, member.node_addr, role, member.matched
+                );
+            }
+        }
+        AdminAction::Add {
+            node_id,
+            node_addr,
+            learner,
+        } => {
+            let request = AddRaftMemberRequest {
+                node_id,
+                node_addr,
+                learner,
+            };
+            add_raft_member(client_poll, addrs, request).await?;
+            println!("node {} was added", node_id);
+        }
+        AdminAction::Promote { node_id } => {
+            let request = PromoteRaftLearnerRequest { node_id };
+            promote_raft_learner(client_poll, addrs, request).await?;
+            println!("node {} was promoted to voter", node_id);
+        }
+        AdminAction::Remove { node_id } => {
+            let request = RemoveRaftMemberRequest { node_id };
+            remove_raft_member(client_poll, addrs, request).await?;
+            println!("node {} was removed", node_id);
+        }
+        AdminAction::TransferLeader { node_id } => {
+            let request = TransferRaftLeaderRequest { node_id };
+            transfer_raft_leader(client_poll, addrs, request).await?;
+            println!("leadership is being transferred to node {}", node_id);
+        }
+        AdminAction::Backup {
+            output,
+            cluster_name,
+        } => {
+            let request = CreateBackupRequest {
+                cluster_name: cluster_name.unwrap_or_default(),
+            };
+            let reply = create_backup(client_poll.clone(), addrs.clone(), request).await?;
+            let mut file = File::create(&output)?;
+            let mut offset = 0;
+            while offset < reply.len {
+                let request = ReadBackupRequest {
+                    backup_name: reply.backup_name.clone(),
+                    offset,
+                    max_bytes: BACKUP_CHUNK_BYTES,
+                };
+                let chunk = read_backup(client_poll.clone(), addrs.clone(), request).await?;
+                if chunk.data.is_empty() {
+                    return Err(CommonError::CommmonError(format!(
+                        "backup {} ended at {} bytes, {} bytes were expected",
+                        reply.backup_name, offset, reply.len
+                    )));
+                }
+                file.write_all(&chunk.data)?;
+                offset = offset + chunk.data.len() as u64;
+            }
+            file.sync_all()?;
+            println!(
+                "{} keys at index {} were backed up to {}",
+                reply.key_num, reply.applied_index, output
+            );
+        }
+        AdminAction::Restore {
+            conf,
+            input,
+            cluster_name,
+        } => {
+            let config = init_placement_center_conf_by_path(&conf);
+            let num = restore_metadata(config, &input, cluster_name.as_ref())?;
+            println!("{} keys were restored into {}", num, config.data_path);
+        }
+        AdminAction::Import {
+            input,
+            cluster_name,
+        } => {
+            let (_, entries) = read_backup_file(&input, Some(&cluster_name))?;
+            let num = entries.len();
+            for batch in entries.chunks(IMPORT_BATCH_NUM) {
+                let request = ImportMetadataRequest {
+                    cluster_name: cluster_name.clone(),
+                    entries: batch
+                        .iter()
+                        .map(|entry| MetadataEntry {
+                            key: entry.key.clone(),
+                            value: entry.value.clone().into_bytes(),
+                        })
+                        .collect(),
+                };
+                import_metadata(client_poll.clone(), addrs.clone(), request).await?;
+            }
+            println!("{} keys of cluster [{}] were imported", num, cluster_name);
+        }
+    }
+    return Ok(());
+}
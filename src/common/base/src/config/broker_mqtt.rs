@@ -21,11 +21,15 @@ use super::default_mqtt::{
     default_auth, default_grpc_port, default_http_port, default_log, default_network,
     default_network_quic_port, default_network_tcp_port, default_network_tcps_port,
     default_network_websocket_port, default_network_websockets_port, default_storage,
-    default_system, default_tcp_thread,
+    default_system, default_tcp_thread, default_topic_compression,
+    default_topic_compression_algorithm, default_topic_compression_min_size,
+    default_topic_retention, default_topic_retention_check_interval_sec,
+    default_topic_retention_cleanup_policy, default_topic_retention_sec,
 };
 use crate::tools::create_fold;
 use crate::tools::read_file;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::OnceLock;
 
 #[derive(Debug, Deserialize, Serialize, Clone, Default)]
@@ -50,6 +54,10 @@ pub struct BrokerMQTTConfig {
     pub auth: Auth,
     #[serde(default = "default_log")]
     pub log: Log,
+    #[serde(default = "default_topic_retention")]
+    pub topic_retention: TopicRetention,
+    #[serde(default = "default_topic_compression")]
+    pub topic_compression: TopicCompression,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, Default)]
@@ -100,6 +108,36 @@ pub struct System {
     pub default_password: String,
 }
 
+// Retention applied to the shard of every topic, a limit of 0 means it is not enabled.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct TopicRetention {
+    #[serde(default = "default_topic_retention_sec")]
+    pub retention_sec: u64,
+    #[serde(default)]
+    pub retention_bytes: u64,
+    #[serde(default)]
+    pub retention_record_num: u64,
+    // delete or compact
+    #[serde(default = "default_topic_retention_cleanup_policy")]
+    pub cleanup_policy: String,
+    #[serde(default = "default_topic_retention_check_interval_sec")]
+    pub check_interval_sec: u64,
+}
+
+// Compression of the messages stored for each topic.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct TopicCompression {
+    // none, lz4, zstd or snappy
+    #[serde(default = "default_topic_compression_algorithm")]
+    pub algorithm: String,
+    // Payloads smaller than this size, in bytes, are not compressed.
+    #[serde(default = "default_topic_compression_min_size")]
+    pub min_size: u64,
+    // Per topic algorithm, keyed by topic name, overriding the default algorithm.
+    #[serde(default)]
+    pub topics: HashMap<String, String>,
+}
+
 static BROKER_MQTT_CONF: OnceLock<BrokerMQTTConfig> = OnceLock::new();
 
 pub fn init_broker_mqtt_conf_by_path(config_path: &String) -> &'static BrokerMQTTConfig {
@@ -199,6 +237,7 @@ mod tests {
         assert_eq!(config.storage.storage_type, "memory".to_string());
         assert_eq!(config.storage.journal_addr, "".to_string());
         assert_eq!(config.storage.mysql_addr, "".to_string());
+        assert_eq!(config.storage.sqlite_path, "".to_string());
 
         assert_eq!(config.log.log_path, "/tmp/robust-default".to_string());
         assert_eq!(config.log.log_config, "");
@@ -206,6 +245,16 @@ mod tests {
         assert_eq!(config.auth.storage_type, "memory".to_string());
         assert_eq!(config.auth.journal_addr, "".to_string());
         assert_eq!(config.auth.mysql_addr, "".to_string());
+
+        assert_eq!(config.topic_retention.retention_sec, 604800);
+        assert_eq!(config.topic_retention.retention_bytes, 0);
+        assert_eq!(config.topic_retention.retention_record_num, 0);
+        assert_eq!(config.topic_retention.cleanup_policy, "delete".to_string());
+        assert_eq!(config.topic_retention.check_interval_sec, 60);
+
+        assert_eq!(config.topic_compression.algorithm, "none".to_string());
+        assert_eq!(config.topic_compression.min_size, 1024);
+        assert!(config.topic_compression.topics.is_empty());
     }
 
     #[test]
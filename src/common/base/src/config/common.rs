@@ -20,6 +20,8 @@ pub struct Storage {
     pub journal_addr: String,
     #[serde(default)]
     pub mysql_addr: String,
+    #[serde(default)]
+    pub sqlite_path: String,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
@@ -0,0 +1,103 @@
+/*
+ * Copyright (c) 2023 RobustMQ Team
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::journal_server::{LocalTier, Replication, S3Tier, Storage, TieredStorage};
+
+pub fn default_storage() -> Storage {
+    Storage {
+        fsync_policy: default_storage_fsync_policy(),
+        fsync_interval_ms: default_storage_fsync_interval_ms(),
+        segment_max_bytes: default_storage_segment_max_bytes(),
+        compaction_interval_ms: default_storage_compaction_interval_ms(),
+        tombstone_retention_ms: default_storage_tombstone_retention_ms(),
+        transaction_timeout_ms: default_storage_transaction_timeout_ms(),
+    }
+}
+
+pub fn default_storage_fsync_policy() -> String {
+    "interval".to_string()
+}
+
+pub fn default_storage_fsync_interval_ms() -> u64 {
+    1000
+}
+
+pub fn default_storage_segment_max_bytes() -> u64 {
+    1024 * 1024 * 1024
+}
+
+pub fn default_storage_compaction_interval_ms() -> u64 {
+    60000
+}
+
+pub fn default_storage_tombstone_retention_ms() -> u64 {
+    24 * 3600 * 1000
+}
+
+pub fn default_storage_transaction_timeout_ms() -> u64 {
+    60000
+}
+
+pub fn default_replication() -> Replication {
+    Replication {
+        replica_lag_time_max_ms: default_replication_replica_lag_time_max_ms(),
+        fetch_max_wait_ms: default_replication_fetch_max_wait_ms(),
+        assignment_refresh_ms: default_replication_assignment_refresh_ms(),
+    }
+}
+
+pub fn default_replication_replica_lag_time_max_ms() -> u64 {
+    10000
+}
+
+pub fn default_replication_fetch_max_wait_ms() -> u32 {
+    500
+}
+
+pub fn default_replication_assignment_refresh_ms() -> u64 {
+    3000
+}
+
+pub fn default_tiered_storage() -> TieredStorage {
+    TieredStorage {
+        enable: false,
+        backend: default_tiered_storage_backend(),
+        offload_interval_ms: default_tiered_storage_offload_interval_ms(),
+        local_retention_ms: default_tiered_storage_local_retention_ms(),
+        local_retention_bytes: 0,
+        local: LocalTier::default(),
+        s3: S3Tier {
+            path_style: default_tiered_storage_s3_path_style(),
+            ..Default::default()
+        },
+    }
+}
+
+pub fn default_tiered_storage_backend() -> String {
+    "local".to_string()
+}
+
+pub fn default_tiered_storage_offload_interval_ms() -> u64 {
+    30000
+}
+
+pub fn default_tiered_storage_local_retention_ms() -> u64 {
+    3600000
+}
+
+pub fn default_tiered_storage_s3_path_style() -> bool {
+    true
+}
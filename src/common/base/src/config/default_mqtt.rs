@@ -12,9 +12,10 @@
 // limitations under the License.
 
 use super::{
-    broker_mqtt::{Network, System, TcpThread},
+    broker_mqtt::{Network, System, TcpThread, TopicCompression, TopicRetention},
     common::{Auth, Log, Storage},
 };
+use std::collections::HashMap;
 
 pub fn default_grpc_port() -> u32 {
     9981
@@ -77,6 +78,7 @@ pub fn default_storage() -> Storage {
         storage_type: "memory".to_string(),
         journal_addr: "".to_string(),
         mysql_addr: "".to_string(),
+        sqlite_path: "".to_string(),
     }
 }
 
@@ -94,3 +96,41 @@ pub fn default_auth() -> Auth {
         mysql_addr: "".to_string(),
     }
 }
+
+pub fn default_topic_retention() -> TopicRetention {
+    TopicRetention {
+        retention_sec: default_topic_retention_sec(),
+        retention_bytes: 0,
+        retention_record_num: 0,
+        cleanup_policy: default_topic_retention_cleanup_policy(),
+        check_interval_sec: default_topic_retention_check_interval_sec(),
+    }
+}
+
+pub fn default_topic_retention_sec() -> u64 {
+    7 * 24 * 3600
+}
+
+pub fn default_topic_retention_cleanup_policy() -> String {
+    "delete".to_string()
+}
+
+pub fn default_topic_retention_check_interval_sec() -> u64 {
+    60
+}
+
+pub fn default_topic_compression() -> TopicCompression {
+    TopicCompression {
+        algorithm: default_topic_compression_algorithm(),
+        min_size: default_topic_compression_min_size(),
+        topics: HashMap::new(),
+    }
+}
+
+pub fn default_topic_compression_algorithm() -> String {
+    "none".to_string()
+}
+
+pub fn default_topic_compression_min_size() -> u64 {
+    1024
+}
@@ -12,7 +12,7 @@
 // limitations under the License.
 
 use super::{
-    placement_center::Rocksdb,
+    placement_center::{JournalController, Raft, Rocksdb, StreamStorage},
     common::Log,
 };
 use toml::Table;
@@ -77,4 +77,69 @@ pub fn default_heartbeat_timeout_ms() -> u64 {
 
 pub fn default_heartbeat_check_time_ms() -> u64 {
     1000
-}
\ No newline at end of file
+}
+
+pub fn default_max_shard_record_num() -> u64 {
+    100000
+}
+
+pub fn default_max_shard_size() -> u64 {
+    100 * 1024 * 1024
+}
+
+pub fn default_retention_check_interval_ms() -> u64 {
+    10000
+}
+
+pub fn default_stream_storage() -> StreamStorage {
+    StreamStorage {
+        max_shard_record_num: default_max_shard_record_num(),
+        max_shard_size: default_max_shard_size(),
+        retention_check_interval_ms: default_retention_check_interval_ms(),
+    }
+}
+
+pub fn default_segment_max_bytes() -> u64 {
+    1024 * 1024 * 1024
+}
+
+pub fn default_segment_max_time_ms() -> u64 {
+    60 * 60 * 1000
+}
+
+pub fn default_segment_check_interval_ms() -> u64 {
+    1000
+}
+
+pub fn default_preferred_election_interval_ms() -> u64 {
+    60 * 1000
+}
+
+pub fn default_journal_controller() -> JournalController {
+    JournalController {
+        segment_max_bytes: default_segment_max_bytes(),
+        segment_max_time_ms: default_segment_max_time_ms(),
+        segment_check_interval_ms: default_segment_check_interval_ms(),
+        preferred_election_interval_ms: default_preferred_election_interval_ms(),
+    }
+}
+
+pub fn default_snapshot_interval_entries() -> u64 {
+    1000
+}
+
+pub fn default_snapshot_log_tail_entries() -> u64 {
+    500
+}
+
+pub fn default_snapshot_chunk_bytes() -> u64 {
+    4 * 1024 * 1024
+}
+
+pub fn default_raft() -> Raft {
+    Raft {
+        snapshot_interval_entries: default_snapshot_interval_entries(),
+        snapshot_log_tail_entries: default_snapshot_log_tail_entries(),
+        snapshot_chunk_bytes: default_snapshot_chunk_bytes(),
+    }
+}
@@ -20,6 +20,16 @@ use std::sync::OnceLock;
 use toml::Table;
 
 use super::common::Log;
+use super::default_journal_server::{
+    default_replication, default_replication_assignment_refresh_ms,
+    default_replication_fetch_max_wait_ms, default_replication_replica_lag_time_max_ms,
+    default_storage, default_storage_compaction_interval_ms, default_storage_fsync_interval_ms,
+    default_storage_fsync_policy, default_storage_segment_max_bytes,
+    default_storage_tombstone_retention_ms, default_storage_transaction_timeout_ms,
+    default_tiered_storage, default_tiered_storage_backend,
+    default_tiered_storage_local_retention_ms, default_tiered_storage_offload_interval_ms,
+    default_tiered_storage_s3_path_style,
+};
 
 #[derive(Debug, Deserialize, Clone, Default)]
 pub struct JournalServerConfig {
@@ -29,11 +39,21 @@ pub struct JournalServerConfig {
     pub prometheus_port: u16,
     pub runtime_work_threads: usize,
     pub data_path: Vec<String>,
+    // Optional rack or zone label, the placement center spreads the replicas of a segment
+    // over nodes with different labels.
+    #[serde(default)]
+    pub rack: String,
     pub placement_center: Vec<String>,
     pub nodes: Table,
     pub rocksdb: Rocksdb,
     pub network: Network,
     pub log: Log,
+    #[serde(default = "default_storage")]
+    pub storage: Storage,
+    #[serde(default = "default_replication")]
+    pub replication: Replication,
+    #[serde(default = "default_tiered_storage")]
+    pub tiered_storage: TieredStorage,
 }
 
 #[derive(Debug, Deserialize, Clone, Default)]
@@ -51,6 +71,87 @@ pub struct Rocksdb {
     pub max_open_files: Option<i32>,
 }
 
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct Storage {
+    // every_write, interval or os
+    #[serde(default = "default_storage_fsync_policy")]
+    pub fsync_policy: String,
+    #[serde(default = "default_storage_fsync_interval_ms")]
+    pub fsync_interval_ms: u64,
+    // The active segment file of a shard is rolled once it reaches this size. Only the
+    // rolled segments can be offloaded to the remote tier.
+    #[serde(default = "default_storage_segment_max_bytes")]
+    pub segment_max_bytes: u64,
+    // How often the sealed segments of the shards with the compact cleanup policy are
+    // compacted.
+    #[serde(default = "default_storage_compaction_interval_ms")]
+    pub compaction_interval_ms: u64,
+    // How long a compacted shard keeps the tombstone of a deleted key, used when the
+    // shard does not set its own.
+    #[serde(default = "default_storage_tombstone_retention_ms")]
+    pub tombstone_retention_ms: u64,
+    // The leader of a shard aborts a transaction that is not ended within this time, 0
+    // never aborts.
+    #[serde(default = "default_storage_transaction_timeout_ms")]
+    pub transaction_timeout_ms: u64,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct Replication {
+    // A follower that has not caught up with the leader for this long leaves the ISR.
+    #[serde(default = "default_replication_replica_lag_time_max_ms")]
+    pub replica_lag_time_max_ms: u64,
+    #[serde(default = "default_replication_fetch_max_wait_ms")]
+    pub fetch_max_wait_ms: u32,
+    // How often the replica assignments of this node are read from the placement center.
+    #[serde(default = "default_replication_assignment_refresh_ms")]
+    pub assignment_refresh_ms: u64,
+}
+
+// Offloads the sealed segments of the shards and their indexes to a remote tier.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct TieredStorage {
+    #[serde(default)]
+    pub enable: bool,
+    // local or s3
+    #[serde(default = "default_tiered_storage_backend")]
+    pub backend: String,
+    #[serde(default = "default_tiered_storage_offload_interval_ms")]
+    pub offload_interval_ms: u64,
+    // Offloaded segments are removed from the local disk once their newest record is
+    // older than this, 0 removes them right after the upload.
+    #[serde(default = "default_tiered_storage_local_retention_ms")]
+    pub local_retention_ms: u64,
+    // Offloaded segments are also removed, oldest first, while a shard holds more bytes
+    // than this on the local disk. 0 disables the limit.
+    #[serde(default)]
+    pub local_retention_bytes: u64,
+    #[serde(default)]
+    pub local: LocalTier,
+    #[serde(default)]
+    pub s3: S3Tier,
+}
+
+// A directory used as the remote tier, usually an NFS mount.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct LocalTier {
+    pub path: String,
+}
+
+// A bucket of an S3 compatible object storage, such as AWS S3 or MinIO.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct S3Tier {
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    pub access_key: String,
+    pub secret_key: String,
+    // Addresses the bucket as endpoint/bucket instead of bucket.endpoint, which MinIO
+    // needs by default.
+    #[serde(default = "default_tiered_storage_s3_path_style")]
+    pub path_style: bool,
+}
+
 static STORAGE_ENGINE_CONFIG: OnceLock<JournalServerConfig> = OnceLock::new();
 
 pub fn init_journal_server_conf_by_path(config_path: &String) -> &'static JournalServerConfig {
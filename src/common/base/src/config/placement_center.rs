@@ -16,9 +16,13 @@
 
 use super::default_placement_center::{
     default_addr, default_cluster_name, default_data_path, default_grpc_port,
-    default_heartbeat_check_time_ms, default_heartbeat_timeout_ms, default_http_port, default_log,
-    default_max_open_files, default_node_id, default_nodes, default_rocksdb,
-    default_runtime_work_threads,
+    default_heartbeat_check_time_ms, default_heartbeat_timeout_ms, default_http_port,
+    default_journal_controller, default_log, default_max_open_files, default_max_shard_record_num,
+    default_max_shard_size, default_node_id, default_nodes, default_preferred_election_interval_ms,
+    default_raft, default_retention_check_interval_ms, default_rocksdb,
+    default_runtime_work_threads, default_segment_check_interval_ms, default_segment_max_bytes,
+    default_segment_max_time_ms, default_snapshot_chunk_bytes, default_snapshot_interval_entries,
+    default_snapshot_log_tail_entries, default_stream_storage,
 };
 use crate::tools::{create_fold, read_file};
 use serde::{Deserialize, Serialize};
@@ -53,6 +57,12 @@ pub struct PlacementCenterConfig {
     pub heartbeat_timeout_ms: u64,
     #[serde(default = "default_heartbeat_check_time_ms")]
     pub heartbeat_check_time_ms: u64,
+    #[serde(default = "default_stream_storage")]
+    pub stream_storage: StreamStorage,
+    #[serde(default = "default_journal_controller")]
+    pub journal_controller: JournalController,
+    #[serde(default = "default_raft")]
+    pub raft: Raft,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
@@ -61,6 +71,46 @@ pub struct Rocksdb {
     pub max_open_files: Option<i32>,
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+pub struct StreamStorage {
+    #[serde(default = "default_max_shard_record_num")]
+    pub max_shard_record_num: u64,
+    #[serde(default = "default_max_shard_size")]
+    pub max_shard_size: u64,
+    // How often the leader removes the records beyond the retention config of the shards.
+    #[serde(default = "default_retention_check_interval_ms")]
+    pub retention_check_interval_ms: u64,
+}
+
+// Lifecycle of the segments of the journal shards.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+pub struct JournalController {
+    // The segment being written is rolled once it holds this many bytes, or once it has
+    // been written for segment_max_time_ms.
+    #[serde(default = "default_segment_max_bytes")]
+    pub segment_max_bytes: u64,
+    #[serde(default = "default_segment_max_time_ms")]
+    pub segment_max_time_ms: u64,
+    #[serde(default = "default_segment_check_interval_ms")]
+    pub segment_check_interval_ms: u64,
+    #[serde(default = "default_preferred_election_interval_ms")]
+    pub preferred_election_interval_ms: u64,
+}
+
+// Snapshots of the raft state machine of the placement center.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+pub struct Raft {
+    // A snapshot is taken every snapshot_interval_entries applied entries, the raft log
+    // below it is then removed except for its last snapshot_log_tail_entries entries.
+    #[serde(default = "default_snapshot_interval_entries")]
+    pub snapshot_interval_entries: u64,
+    #[serde(default = "default_snapshot_log_tail_entries")]
+    pub snapshot_log_tail_entries: u64,
+    // Size of the chunks a snapshot is sent to a follower in.
+    #[serde(default = "default_snapshot_chunk_bytes")]
+    pub snapshot_chunk_bytes: u64,
+}
+
 static PLACEMENT_CENTER_CONF: OnceLock<PlacementCenterConfig> = OnceLock::new();
 
 pub fn init_placement_center_conf_by_path(config_path: &String) -> &'static PlacementCenterConfig {
@@ -127,7 +177,10 @@ pub fn placement_center_conf() -> &'static PlacementCenterConfig {
 
 #[cfg(test)]
 mod tests {
-    use super::{placement_center_conf, Log, PlacementCenterConfig, Rocksdb};
+    use super::{
+        placement_center_conf, JournalController, Log, PlacementCenterConfig, Raft, Rocksdb,
+        StreamStorage,
+    };
     use crate::config::placement_center::init_placement_center_conf_by_path;
     use toml::Table;
 
@@ -167,5 +220,30 @@ mod tests {
         );
         assert_eq!(config.heartbeat_timeout_ms, 30000);
         assert_eq!(config.heartbeat_check_time_ms, 1000);
+        assert_eq!(
+            config.stream_storage,
+            StreamStorage {
+                max_shard_record_num: 100000,
+                max_shard_size: 104857600,
+                retention_check_interval_ms: 10000,
+            }
+        );
+        assert_eq!(
+            config.journal_controller,
+            JournalController {
+                segment_max_bytes: 1073741824,
+                segment_max_time_ms: 3600000,
+                segment_check_interval_ms: 1000,
+                preferred_election_interval_ms: 60000,
+            }
+        );
+        assert_eq!(
+            config.raft,
+            Raft {
+                snapshot_interval_entries: 1000,
+                snapshot_log_tail_entries: 500,
+                snapshot_chunk_bytes: 4194304,
+            }
+        );
     }
 }
@@ -1,4 +1,55 @@
 use thiserror::Error;
 
 #[derive(Error, Debug)]
-pub enum JournalServerError {}
+pub enum JournalServerError {
+    #[error("Segment file {0} is not a valid segment file")]
+    InvalidSegmentFile(String),
+
+    #[error("Record offset {0} does not match the next offset {1} of the segment")]
+    SegmentOffsetMismatch(u64, u64),
+
+    #[error("Record could not be decoded, {0}")]
+    RecordDecodeError(String),
+
+    #[error("Index entry {0} could not be decoded")]
+    IndexDecodeError(String),
+
+    #[error("Node {0} is not the leader of shard {1}")]
+    NotLeader(String, String),
+
+    #[error("No leader could be found for shard {0}")]
+    NoShardLeader(String),
+
+    #[error("Shard {0} does not exist")]
+    ShardNotFound(String),
+
+    #[error("Offset out of range for shard {0}, {1}")]
+    OffsetOutOfRange(String, String),
+
+    #[error("Connection to {0} has been closed")]
+    ConnectionClosed(String),
+
+    #[error("Request to {0} timed out")]
+    RequestTimeout(String),
+
+    #[error("Request failed with error code {0}, {1}")]
+    ServerError(i32, String),
+
+    #[error("Remote storage backend {0} is not supported")]
+    UnknownRemoteStorageBackend(String),
+
+    #[error("Object {0} does not exist in the remote storage")]
+    RemoteObjectNotFound(String),
+
+    #[error("Remote storage request failed, {0}")]
+    RemoteStorageError(String),
+
+    #[error("Sequence {3} of producer {1} on shard {0} does not follow sequence {2}")]
+    OutOfOrderSequence(String, u64, u32, u32),
+
+    #[error("Producer {1} with epoch {2} on shard {0} has been fenced by a newer epoch")]
+    ProducerFenced(String, u64, u32),
+
+    #[error("Invalid transaction of producer {1} on shard {0}, {2}")]
+    InvalidTransaction(String, u64, String),
+}
@@ -4,4 +4,46 @@ use thiserror::Error;
 pub enum PlacementCenterError {
     #[error("Description The interface {0} submitted logs to the commit log")]
     RaftLogCommitTimeout(String),
+
+    #[error("The interface {0} was rejected, {1}")]
+    RaftRequestRejected(String, String),
+
+    #[error("Node {0} is not the leader, the leader is [{1}]")]
+    NotLeader(u64, String),
+
+    #[error("Shard [{0}] does not exist")]
+    ShardDoesNotExist(String),
+
+    #[error("Shard [{0}] exceeds the stream storage limit, {1} is limited to {2}")]
+    ShardExceedsStreamStorageLimit(String, String, u64),
+
+    #[error("Shard [{0}] needs {1} replicas, but only {2} journal nodes can hold them")]
+    NotEnoughJournalNodes(String, u32, usize),
+
+    #[error("Segment {1} of shard [{0}] has no replicas")]
+    SegmentReplicasNotSet(String, u64),
+
+    #[error("Segment {1} of shard [{0}] does not exist")]
+    SegmentDoesNotExist(String, u64),
+
+    #[error("Segment {1} of shard [{0}] is {2}, expected {3}")]
+    SegmentStatusMismatch(String, u64, String, String),
+
+    #[error("Segment {1} of shard [{0}] has no replica at index {2}")]
+    SegmentReplicaNotFound(String, u64, u32),
+
+    #[error("Lease {0} does not exist")]
+    KvLeaseNotFound(u64),
+
+    #[error("Revision {0} has been compacted, the oldest revision kept is {1}")]
+    KvRevisionCompacted(u64, u64),
+
+    #[error("The revision of key [{0}] is not {1}")]
+    KvRevisionMismatch(String, u64),
+
+    #[error("Key [{0}] is reserved for the lease records")]
+    KvKeyReserved(String),
+
+    #[error("Node {1} of cluster [{0}] is not registered and cannot hold a lock")]
+    LockHolderNotRegistered(String, u64),
 }
@@ -31,3 +31,8 @@ pub fn success_response<T: Serialize>(data: T) -> String {
 pub fn error_response() -> String {
     return "".to_string();
 }
+
+pub fn error_response_with_msg(msg: String) -> String {
+    let resp = Response { code: 1, data: msg };
+    return serde_json::to_string(&resp).unwrap();
+}
@@ -0,0 +1,89 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use bytes::Bytes;
+use criterion::{criterion_group, criterion_main, Criterion};
+use metadata_struct::mqtt::message::MQTTMessage;
+use protocol::mqtt::common::QoS;
+use std::hint::black_box;
+use storage_adapter::record::{Header, Record};
+
+fn build_message(payload_size: usize) -> MQTTMessage {
+    return MQTTMessage {
+        client_id: "bench-client".to_string(),
+        qos: QoS::AtLeastOnce,
+        pkid: 1,
+        topic: Bytes::from("/bench/topic"),
+        payload: Bytes::from(vec![7u8; payload_size]),
+        user_properties: vec![("k1".to_string(), "v1".to_string())],
+        create_time: 1700000000,
+        ..Default::default()
+    };
+}
+
+fn message_codec(c: &mut Criterion) {
+    for payload_size in [64, 1024, 16 * 1024] {
+        let message = build_message(payload_size);
+        let json_data = serde_json::to_vec(&message).unwrap();
+        let binary_data = message.encode();
+
+        c.bench_function(&format!("json_encode_{}", payload_size), |b| {
+            b.iter(|| serde_json::to_vec(black_box(&message)).unwrap())
+        });
+        c.bench_function(&format!("binary_encode_{}", payload_size), |b| {
+            b.iter(|| black_box(&message).encode())
+        });
+        c.bench_function(&format!("json_decode_{}", payload_size), |b| {
+            b.iter(|| serde_json::from_slice::<MQTTMessage>(black_box(&json_data)).unwrap())
+        });
+        c.bench_function(&format!("binary_decode_{}", payload_size), |b| {
+            b.iter(|| MQTTMessage::decode(black_box(&binary_data)).unwrap())
+        });
+    }
+}
+
+fn build_record(payload_size: usize) -> Record {
+    return Record::build_a(
+        Some("bench-key".to_string()),
+        vec![7u8; payload_size],
+        Some(vec![Header {
+            name: "k1".to_string(),
+            value: "v1".to_string(),
+        }]),
+        Some(1700000000),
+    );
+}
+
+fn record_codec(c: &mut Criterion) {
+    for payload_size in [64, 1024, 16 * 1024] {
+        let record = build_record(payload_size);
+        let json_data = serde_json::to_vec(&record).unwrap();
+        let binary_data = record.encode();
+
+        c.bench_function(&format!("record_json_encode_{}", payload_size), |b| {
+            b.iter(|| serde_json::to_vec(black_box(&record)).unwrap())
+        });
+        c.bench_function(&format!("record_binary_encode_{}", payload_size), |b| {
+            b.iter(|| black_box(&record).encode())
+        });
+        c.bench_function(&format!("record_json_decode_{}", payload_size), |b| {
+            b.iter(|| serde_json::from_slice::<Record>(black_box(&json_data)).unwrap())
+        });
+        c.bench_function(&format!("record_binary_decode_{}", payload_size), |b| {
+            b.iter(|| Record::decode(black_box(&binary_data)).unwrap())
+        });
+    }
+}
+
+criterion_group!(benches, message_codec, record_codec);
+criterion_main!(benches);
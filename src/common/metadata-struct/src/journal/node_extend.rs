@@ -0,0 +1,25 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+use serde::{Deserialize, Serialize};
+
+// Registered as the extend info of a journal server node, used by the placement center
+// to place segment replicas.
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct JournalNodeExtend {
+    pub data_fold: Vec<String>,
+    // Optional rack or zone of the node, replicas of a segment are spread over racks.
+    #[serde(default)]
+    pub rack: String,
+}
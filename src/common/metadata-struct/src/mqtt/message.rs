@@ -13,9 +13,11 @@
 
 use bytes::Bytes;
 use common_base::{error::common::CommonError, tools::now_second};
-use protocol::mqtt::common::{Publish, PublishProperties, QoS};
+use prost::Message as _;
+use protocol::broker_server::generate::message::{MqttMessage, UserProperty};
+use protocol::mqtt::common::{qos, Publish, PublishProperties, QoS};
 use serde::{Deserialize, Serialize};
-use storage_adapter::record::Record;
+use storage_adapter::record::{codec_decode, codec_encode, Record, CODEC_VERSION_V1};
 
 #[derive(Clone, Serialize, Deserialize, Default)]
 pub struct MQTTMessage {
@@ -69,28 +71,154 @@ impl MQTTMessage {
         publish_properties: &Option<PublishProperties>,
     ) -> Option<Record> {
         let msg = MQTTMessage::build_message(client_id, publish, publish_properties);
-        match serde_json::to_vec(&msg) {
-            Ok(data) => {
-                return Some(Record::build_b(data));
-            }
-
-            Err(_) => {
-                return None;
-            }
-        }
+        return Some(Record::build_b(msg.encode()));
     }
 
     pub fn decode_record(record: Record) -> Result<MQTTMessage, CommonError> {
-        let data: MQTTMessage = match serde_json::from_slice(record.data.as_slice()) {
-            Ok(da) => da,
-            Err(e) => {
-                return Err(CommonError::CommmonError(e.to_string()));
+        return MQTTMessage::decode(record.data.as_slice());
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let message = MqttMessage {
+            client_id: self.client_id.clone(),
+            dup: self.dup,
+            qos: u8::from(self.qos) as u32,
+            pkid: self.pkid as u32,
+            retain: self.retain,
+            topic: self.topic.to_vec(),
+            payload: self.payload.to_vec(),
+            format_indicator: self.format_indicator.map(|v| v as u32),
+            expiry_interval: self.expiry_interval,
+            response_topic: self.response_topic.clone(),
+            correlation_data: self.correlation_data.as_ref().map(|v| v.to_vec()),
+            user_properties: self
+                .user_properties
+                .iter()
+                .map(|(key, value)| UserProperty {
+                    key: key.clone(),
+                    value: value.clone(),
+                })
+                .collect(),
+            subscription_identifiers: self
+                .subscription_identifiers
+                .iter()
+                .map(|v| *v as u64)
+                .collect(),
+            content_type: self.content_type.clone(),
+            create_time: self.create_time,
+        };
+        return codec_encode(CODEC_VERSION_V1, MqttMessage::encode_to_vec(&message));
+    }
+
+    // Messages written before the binary codec was introduced are JSON encoded.
+    pub fn decode(data: &[u8]) -> Result<MQTTMessage, CommonError> {
+        let (version, body) = match codec_decode(data) {
+            Some(da) => da,
+            None => match serde_json::from_slice::<MQTTMessage>(data) {
+                Ok(da) => return Ok(da),
+                Err(e) => {
+                    return Err(CommonError::CommmonError(e.to_string()));
+                }
+            },
+        };
+
+        if version != CODEC_VERSION_V1 {
+            return Err(CommonError::CommmonError(format!(
+                "Unsupported MQTT message codec version {}",
+                version
+            )));
+        }
+
+        let message = MqttMessage::decode(body)?;
+        let qos = match qos(message.qos as u8) {
+            Some(qos) => qos,
+            None => {
+                return Err(CommonError::CommmonError(format!(
+                    "Invalid QoS {} in MQTT message",
+                    message.qos
+                )));
             }
         };
-        return Ok(data);
+        return Ok(MQTTMessage {
+            client_id: message.client_id,
+            dup: message.dup,
+            qos,
+            pkid: message.pkid as u16,
+            retain: message.retain,
+            topic: Bytes::from(message.topic),
+            payload: Bytes::from(message.payload),
+            format_indicator: message.format_indicator.map(|v| v as u8),
+            expiry_interval: message.expiry_interval,
+            response_topic: message.response_topic,
+            correlation_data: message.correlation_data.map(Bytes::from),
+            user_properties: message
+                .user_properties
+                .into_iter()
+                .map(|p| (p.key, p.value))
+                .collect(),
+            subscription_identifiers: message
+                .subscription_identifiers
+                .into_iter()
+                .map(|v| v as usize)
+                .collect(),
+            content_type: message.content_type,
+            create_time: message.create_time,
+        });
     }
+}
 
-    pub fn encode(&self) -> Vec<u8> {
-        return serde_json::to_vec(&self).unwrap();
+#[cfg(test)]
+mod tests {
+    use super::MQTTMessage;
+    use bytes::Bytes;
+    use protocol::mqtt::common::QoS;
+
+    fn build_test_message() -> MQTTMessage {
+        return MQTTMessage {
+            client_id: "c1".to_string(),
+            qos: QoS::ExactlyOnce,
+            pkid: 10,
+            topic: Bytes::from("t1"),
+            payload: Bytes::from(vec![0u8, 1, 2, 255]),
+            format_indicator: Some(1),
+            expiry_interval: Some(30),
+            correlation_data: Some(Bytes::from("cd")),
+            user_properties: vec![("k1".to_string(), "v1".to_string())],
+            subscription_identifiers: vec![1, 2],
+            create_time: 1700000000,
+            ..Default::default()
+        };
+    }
+
+    #[test]
+    fn message_codec() {
+        let message = build_test_message();
+        let data = message.encode();
+        let res = MQTTMessage::decode(&data).unwrap();
+        assert_eq!(res.client_id, message.client_id);
+        assert_eq!(res.qos, message.qos);
+        assert_eq!(res.pkid, message.pkid);
+        assert_eq!(res.topic, message.topic);
+        assert_eq!(res.payload, message.payload);
+        assert_eq!(res.format_indicator, message.format_indicator);
+        assert_eq!(res.expiry_interval, message.expiry_interval);
+        assert_eq!(res.response_topic, None);
+        assert_eq!(res.correlation_data, message.correlation_data);
+        assert_eq!(res.user_properties, message.user_properties);
+        assert_eq!(
+            res.subscription_identifiers,
+            message.subscription_identifiers
+        );
+        assert_eq!(res.create_time, message.create_time);
+    }
+
+    #[test]
+    fn message_codec_json_compatible() {
+        let message = build_test_message();
+        let data = serde_json::to_vec(&message).unwrap();
+        let res = MQTTMessage::decode(&data).unwrap();
+        assert_eq!(res.client_id, message.client_id);
+        assert_eq!(res.payload, message.payload);
+        assert!(data.len() > message.encode().len());
     }
 }
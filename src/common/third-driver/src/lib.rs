@@ -12,4 +12,5 @@
 // limitations under the License.
 
 
-pub mod mysql;
\ No newline at end of file
+pub mod mysql;
+pub mod sqlite;
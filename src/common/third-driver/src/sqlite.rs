@@ -0,0 +1,36 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::error::common::CommonError;
+use rusqlite::Connection;
+
+// Opens the database file in WAL mode. Every committed transaction is synced to disk,
+// so the data survives a power loss of the node.
+pub fn build_sqlite_conn(path: &str) -> Result<Connection, CommonError> {
+    let conn = match Connection::open(path) {
+        Ok(conn) => conn,
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    };
+    match conn.execute_batch(
+        "PRAGMA journal_mode = WAL;
+        PRAGMA synchronous = FULL;
+        PRAGMA busy_timeout = 5000;",
+    ) {
+        Ok(()) => return Ok(conn),
+        Err(e) => {
+            return Err(CommonError::CommmonError(e.to_string()));
+        }
+    }
+}
@@ -11,4 +11,28 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use common_base::{
+    config::journal_server::TieredStorage,
+    error::{common::CommonError, journal_server::JournalServerError},
+};
+use local::LocalStorage;
+use s3::S3Storage;
+use std::sync::Arc;
+use storage::RemoteStorage;
 
+pub mod local;
+pub mod s3;
+pub mod sign;
+pub mod storage;
+
+pub fn build_remote_storage(
+    config: &TieredStorage,
+) -> Result<Arc<dyn RemoteStorage>, CommonError> {
+    match config.backend.as_str() {
+        "local" => return Ok(Arc::new(LocalStorage::new(&config.local.path)?)),
+        "s3" => return Ok(Arc::new(S3Storage::new(config.s3.clone())?)),
+        backend => {
+            return Err(JournalServerError::UnknownRemoteStorageBackend(backend.to_string()).into());
+        }
+    }
+}
@@ -0,0 +1,248 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::storage::RemoteStorage;
+use axum::async_trait;
+use bytes::Bytes;
+use common_base::error::{common::CommonError, journal_server::JournalServerError};
+use std::{
+    io::{ErrorKind, SeekFrom},
+    path::{Path, PathBuf},
+};
+use tokio::{
+    fs,
+    io::{AsyncReadExt, AsyncSeekExt},
+};
+
+// Objects are written to a temporary file first and renamed, so that a reader never
+// sees a partially written object.
+const TEMP_FILE_SUFFIX: &str = ".tmp";
+
+// Keeps the objects as files under a directory, which is usually an NFS mount shared by
+// the journal nodes. The key is the path of the file relative to the directory.
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: &str) -> Result<Self, CommonError> {
+        if root.is_empty() {
+            return Err(CommonError::ParameterCannotBeNull(
+                "tiered_storage.local.path".to_string(),
+            ));
+        }
+        std::fs::create_dir_all(root)?;
+        return Ok(LocalStorage {
+            root: PathBuf::from(root),
+        });
+    }
+
+    fn object_path(&self, key: &str) -> Result<PathBuf, CommonError> {
+        if key.is_empty()
+            || key
+                .split('/')
+                .any(|part| part.is_empty() || part == "." || part == "..")
+        {
+            return Err(JournalServerError::RemoteStorageError(format!(
+                "invalid object key {}",
+                key
+            ))
+            .into());
+        }
+        return Ok(self.root.join(key));
+    }
+
+    // Returns the path of the object and of the temporary file it is written to.
+    async fn prepare_write(&self, key: &str) -> Result<(PathBuf, PathBuf), CommonError> {
+        let path = self.object_path(key)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        let temp_path = PathBuf::from(format!("{}{}", path.display(), TEMP_FILE_SUFFIX));
+        return Ok((path, temp_path));
+    }
+
+    async fn commit_write(&self, temp_path: &Path, path: &Path) -> Result<(), CommonError> {
+        fs::File::open(temp_path).await?.sync_all().await?;
+        fs::rename(temp_path, path).await?;
+        return Ok(());
+    }
+}
+
+#[async_trait]
+impl RemoteStorage for LocalStorage {
+    async fn put(&self, key: &str, data: Bytes) -> Result<(), CommonError> {
+        let (path, temp_path) = self.prepare_write(key).await?;
+        fs::write(&temp_path, &data).await?;
+        return self.commit_write(&temp_path, &path).await;
+    }
+
+    async fn put_file(&self, key: &str, src: &Path) -> Result<(), CommonError> {
+        let (path, temp_path) = self.prepare_write(key).await?;
+        fs::copy(src, &temp_path).await?;
+        return self.commit_write(&temp_path, &path).await;
+    }
+
+    async fn get(&self, key: &str) -> Result<Bytes, CommonError> {
+        match fs::read(self.object_path(key)?).await {
+            Ok(data) => return Ok(Bytes::from(data)),
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                return Err(JournalServerError::RemoteObjectNotFound(key.to_string()).into());
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    async fn get_range(&self, key: &str, start: u64, len: u64) -> Result<Bytes, CommonError> {
+        let mut file = match fs::File::open(self.object_path(key)?).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                return Err(JournalServerError::RemoteObjectNotFound(key.to_string()).into());
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let size = file.metadata().await?.len();
+        if start >= size {
+            return Ok(Bytes::new());
+        }
+        file.seek(SeekFrom::Start(start)).await?;
+        let mut data = vec![0u8; len.min(size - start) as usize];
+        file.read_exact(&mut data).await?;
+        return Ok(Bytes::from(data));
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), CommonError> {
+        match fs::remove_file(self.object_path(key)?).await {
+            Ok(()) => return Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, CommonError> {
+        let mut keys = Vec::new();
+        let mut dirs = vec![self.root.clone()];
+        while let Some(dir) = dirs.pop() {
+            let mut entries = match fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if entry.file_type().await?.is_dir() {
+                    dirs.push(path);
+                    continue;
+                }
+                let key = match path.strip_prefix(&self.root) {
+                    Ok(relative) => relative
+                        .components()
+                        .map(|part| part.as_os_str().to_string_lossy().to_string())
+                        .collect::<Vec<String>>()
+                        .join("/"),
+                    Err(_) => continue,
+                };
+                if key.starts_with(prefix) && !key.ends_with(TEMP_FILE_SUFFIX) {
+                    keys.push(key);
+                }
+            }
+        }
+        keys.sort();
+        return Ok(keys);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LocalStorage;
+    use crate::storage::RemoteStorage;
+    use bytes::Bytes;
+    use common_base::error::{common::CommonError, journal_server::JournalServerError};
+    use std::fs;
+
+    fn build_storage(name: &str) -> LocalStorage {
+        let dir = std::env::temp_dir().join(format!("robustmq-remote-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        return LocalStorage::new(&dir.display().to_string()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn put_get_list_delete() {
+        let storage = build_storage("local-objects");
+        storage
+            .put("c1/s1/00000000000000000000.log", Bytes::from("0123456789"))
+            .await
+            .unwrap();
+        storage
+            .put("c1/s1/00000000000000000010.log", Bytes::from("abc"))
+            .await
+            .unwrap();
+        storage
+            .put("c1/s2/00000000000000000000.log", Bytes::from("xyz"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            storage.get("c1/s1/00000000000000000000.log").await.unwrap(),
+            Bytes::from("0123456789")
+        );
+        assert_eq!(
+            storage
+                .get_range("c1/s1/00000000000000000000.log", 2, 3)
+                .await
+                .unwrap(),
+            Bytes::from("234")
+        );
+        assert_eq!(
+            storage
+                .get_range("c1/s1/00000000000000000000.log", 8, 10)
+                .await
+                .unwrap(),
+            Bytes::from("89")
+        );
+        assert_eq!(
+            storage.list("c1/s1/").await.unwrap(),
+            vec![
+                "c1/s1/00000000000000000000.log".to_string(),
+                "c1/s1/00000000000000000010.log".to_string()
+            ]
+        );
+
+        storage
+            .delete("c1/s1/00000000000000000000.log")
+            .await
+            .unwrap();
+        storage
+            .delete("c1/s1/00000000000000000000.log")
+            .await
+            .unwrap();
+        assert_eq!(storage.list("c1/s1/").await.unwrap().len(), 1);
+        match storage.get("c1/s1/00000000000000000000.log").await {
+            Err(CommonError::JournalServerError(JournalServerError::RemoteObjectNotFound(_))) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(storage.get("../outside").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn put_file() {
+        let storage = build_storage("local-put-file");
+        let src = std::env::temp_dir().join("robustmq-remote-local-put-file.src");
+        fs::write(&src, b"segment").unwrap();
+        storage.put_file("c1/s1/0.log", &src).await.unwrap();
+        assert_eq!(
+            storage.get("c1/s1/0.log").await.unwrap(),
+            Bytes::from("segment")
+        );
+    }
+}
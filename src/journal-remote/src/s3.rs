@@ -0,0 +1,443 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    sign::{
+        amz_date, authorization, canonical_query, sha256_hex, uri_encode, SignKey, SignRequest,
+        UNSIGNED_PAYLOAD,
+    },
+    storage::RemoteStorage,
+};
+use axum::async_trait;
+use bytes::Bytes;
+use common_base::{
+    config::journal_server::S3Tier,
+    error::{common::CommonError, journal_server::JournalServerError},
+    tools::now_second,
+};
+use reqwest::{header::CONTENT_LENGTH, Body, Client, Method, Response, StatusCode, Url};
+use std::path::Path;
+use tokio_util::io::ReaderStream;
+
+const DEFAULT_REGION: &str = "us-east-1";
+
+// Talks to an S3 compatible object storage through its REST API, requests are signed
+// with Signature Version 4.
+pub struct S3Storage {
+    client: Client,
+    config: S3Tier,
+    endpoint: Url,
+}
+
+impl S3Storage {
+    pub fn new(config: S3Tier) -> Result<Self, CommonError> {
+        if config.endpoint.is_empty() {
+            return Err(CommonError::ParameterCannotBeNull(
+                "tiered_storage.s3.endpoint".to_string(),
+            ));
+        }
+        if config.bucket.is_empty() {
+            return Err(CommonError::ParameterCannotBeNull(
+                "tiered_storage.s3.bucket".to_string(),
+            ));
+        }
+        let endpoint = match Url::parse(&config.endpoint) {
+            Ok(url) => url,
+            Err(e) => {
+                return Err(JournalServerError::RemoteStorageError(format!(
+                    "invalid endpoint {}, {}",
+                    config.endpoint, e
+                ))
+                .into());
+            }
+        };
+        return Ok(S3Storage {
+            client: Client::new(),
+            config,
+            endpoint,
+        });
+    }
+
+    // Returns the url of the object, and its path as it is signed. An empty key addresses
+    // the bucket.
+    fn object_url(
+        &self,
+        key: &str,
+        query: &[(String, String)],
+    ) -> Result<(Url, String), CommonError> {
+        let host = match self.endpoint.host_str() {
+            Some(host) => host,
+            None => {
+                return Err(JournalServerError::RemoteStorageError(format!(
+                    "endpoint {} has no host",
+                    self.config.endpoint
+                ))
+                .into());
+            }
+        };
+        let port = match self.endpoint.port() {
+            Some(port) => format!(":{}", port),
+            None => "".to_string(),
+        };
+        let (authority, path) = if self.config.path_style {
+            let path = if key.is_empty() {
+                format!("/{}", uri_encode(&self.config.bucket, true))
+            } else {
+                format!(
+                    "/{}/{}",
+                    uri_encode(&self.config.bucket, true),
+                    uri_encode(key, false)
+                )
+            };
+            (format!("{}{}", host, port), path)
+        } else {
+            (
+                format!("{}.{}{}", self.config.bucket, host, port),
+                format!("/{}", uri_encode(key, false)),
+            )
+        };
+        let mut url = format!("{}://{}{}", self.endpoint.scheme(), authority, path);
+        if !query.is_empty() {
+            url = format!("{}?{}", url, canonical_query(query));
+        }
+        match Url::parse(&url) {
+            Ok(url) => return Ok((url, path)),
+            Err(e) => {
+                return Err(JournalServerError::RemoteStorageError(format!(
+                    "invalid object url {}, {}",
+                    url, e
+                ))
+                .into());
+            }
+        }
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(String, String)],
+        extra_headers: &[(String, String)],
+        payload_hash: &str,
+        body: Option<Body>,
+    ) -> Result<Response, CommonError> {
+        let (url, canonical_uri) = self.object_url(key, query)?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => "".to_string(),
+        };
+        let date = amz_date(now_second());
+        let mut headers = vec![
+            ("host".to_string(), host),
+            ("x-amz-content-sha256".to_string(), payload_hash.to_string()),
+            ("x-amz-date".to_string(), date.clone()),
+        ];
+        headers.extend_from_slice(extra_headers);
+        let region = if self.config.region.is_empty() {
+            DEFAULT_REGION
+        } else {
+            &self.config.region
+        };
+        let auth = authorization(
+            &SignRequest {
+                method: method.as_str(),
+                canonical_uri: &canonical_uri,
+                query,
+                headers: &headers,
+                payload_hash,
+            },
+            &SignKey {
+                access_key: &self.config.access_key,
+                secret_key: &self.config.secret_key,
+                region,
+            },
+            &date,
+        );
+
+        let mut builder = self
+            .client
+            .request(method.clone(), url)
+            .header("authorization", auth);
+        // The host header is set by the client from the url.
+        for (name, value) in headers.iter().filter(|(name, _)| name != "host") {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        if let Some(body) = body {
+            builder = builder.body(body);
+        }
+        match builder.send().await {
+            Ok(response) => return Ok(response),
+            Err(e) => {
+                return Err(JournalServerError::RemoteStorageError(format!(
+                    "{} {} failed, {}",
+                    method, key, e
+                ))
+                .into());
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl RemoteStorage for S3Storage {
+    async fn put(&self, key: &str, data: Bytes) -> Result<(), CommonError> {
+        let payload_hash = sha256_hex(&data);
+        let response = self
+            .send(
+                Method::PUT,
+                key,
+                &[],
+                &[],
+                &payload_hash,
+                Some(Body::from(data)),
+            )
+            .await?;
+        check_status(&Method::PUT, key, response).await?;
+        return Ok(());
+    }
+
+    async fn put_file(&self, key: &str, path: &Path) -> Result<(), CommonError> {
+        let file = tokio::fs::File::open(path).await?;
+        // S3 rejects uploads of unknown length.
+        let size = file.metadata().await?.len();
+        let headers = vec![(CONTENT_LENGTH.to_string(), size.to_string())];
+        let body = Body::wrap_stream(ReaderStream::new(file));
+        let response = self
+            .send(
+                Method::PUT,
+                key,
+                &[],
+                &headers,
+                UNSIGNED_PAYLOAD,
+                Some(body),
+            )
+            .await?;
+        check_status(&Method::PUT, key, response).await?;
+        return Ok(());
+    }
+
+    async fn get(&self, key: &str) -> Result<Bytes, CommonError> {
+        let response = self
+            .send(Method::GET, key, &[], &[], &sha256_hex(b""), None)
+            .await?;
+        let response = check_status(&Method::GET, key, response).await?;
+        return read_body(&Method::GET, key, response).await;
+    }
+
+    async fn get_range(&self, key: &str, start: u64, len: u64) -> Result<Bytes, CommonError> {
+        if len == 0 {
+            return Ok(Bytes::new());
+        }
+        let headers = vec![(
+            "range".to_string(),
+            format!("bytes={}-{}", start, start + len - 1),
+        )];
+        let response = self
+            .send(Method::GET, key, &[], &headers, &sha256_hex(b""), None)
+            .await?;
+        // The range starts after the end of the object.
+        if response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+            return Ok(Bytes::new());
+        }
+        let response = check_status(&Method::GET, key, response).await?;
+        return read_body(&Method::GET, key, response).await;
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), CommonError> {
+        let response = self
+            .send(Method::DELETE, key, &[], &[], &sha256_hex(b""), None)
+            .await?;
+        match check_status(&Method::DELETE, key, response).await {
+            Ok(_) => return Ok(()),
+            Err(CommonError::JournalServerError(JournalServerError::RemoteObjectNotFound(_))) => {
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, CommonError> {
+        let mut keys = Vec::new();
+        let mut continuation_token: Option<String> = None;
+        loop {
+            let mut query = vec![
+                ("list-type".to_string(), "2".to_string()),
+                ("prefix".to_string(), prefix.to_string()),
+            ];
+            if let Some(token) = continuation_token.take() {
+                query.push(("continuation-token".to_string(), token));
+            }
+            let response = self
+                .send(Method::GET, "", &query, &[], &sha256_hex(b""), None)
+                .await?;
+            let response = check_status(&Method::GET, prefix, response).await?;
+            let body = read_body(&Method::GET, prefix, response).await?;
+            let body = String::from_utf8_lossy(&body);
+
+            keys.extend(xml_values(&body, "Key"));
+            let truncated = xml_values(&body, "IsTruncated")
+                .first()
+                .map(|value| value == "true")
+                .unwrap_or(false);
+            continuation_token = xml_values(&body, "NextContinuationToken").into_iter().next();
+            if !truncated || continuation_token.is_none() {
+                break;
+            }
+        }
+        keys.sort();
+        return Ok(keys);
+    }
+}
+
+async fn check_status(
+    method: &Method,
+    key: &str,
+    response: Response,
+) -> Result<Response, CommonError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    if status == StatusCode::NOT_FOUND {
+        return Err(JournalServerError::RemoteObjectNotFound(key.to_string()).into());
+    }
+    let body = response.text().await.unwrap_or_default();
+    return Err(JournalServerError::RemoteStorageError(format!(
+        "{} {} returned {}, {}",
+        method, key, status, body
+    ))
+    .into());
+}
+
+async fn read_body(method: &Method, key: &str, response: Response) -> Result<Bytes, CommonError> {
+    match response.bytes().await {
+        Ok(data) => return Ok(data),
+        Err(e) => {
+            return Err(JournalServerError::RemoteStorageError(format!(
+                "failed to read the response of {} {}, {}",
+                method, key, e
+            ))
+            .into());
+        }
+    }
+}
+
+// Returns the text of every element with the tag, the responses of S3 hold no nested
+// elements of the same name.
+fn xml_values(body: &str, tag: &str) -> Vec<String> {
+    let start_tag = format!("<{}>", tag);
+    let end_tag = format!("</{}>", tag);
+    let mut values = Vec::new();
+    let mut rest = body;
+    while let Some(start) = rest.find(&start_tag) {
+        rest = &rest[start + start_tag.len()..];
+        let end = match rest.find(&end_tag) {
+            Some(end) => end,
+            None => break,
+        };
+        values.push(xml_unescape(&rest[..end]));
+        rest = &rest[end + end_tag.len()..];
+    }
+    return values;
+}
+
+fn xml_unescape(value: &str) -> String {
+    return value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{xml_values, S3Storage};
+    use crate::storage::RemoteStorage;
+    use bytes::Bytes;
+    use common_base::config::journal_server::S3Tier;
+    use std::fs;
+
+    #[test]
+    fn parse_list_objects_reply() {
+        let body = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <Name>robustmq</Name><Prefix>c1/s1/</Prefix><KeyCount>2</KeyCount>
+  <IsTruncated>true</IsTruncated>
+  <Contents><Key>c1/s1/a&amp;b.log</Key><Size>10</Size></Contents>
+  <Contents><Key>c1/s1/c.log</Key><Size>3</Size></Contents>
+  <NextContinuationToken>1ueGcxLPRx1Tr/XYExHnhbYLgveDs2J/wm36Hy4vbOwM=</NextContinuationToken>
+</ListBucketResult>"#;
+        assert_eq!(
+            xml_values(body, "Key"),
+            vec!["c1/s1/a&b.log".to_string(), "c1/s1/c.log".to_string()]
+        );
+        assert_eq!(xml_values(body, "IsTruncated"), vec!["true".to_string()]);
+        assert_eq!(
+            xml_values(body, "NextContinuationToken"),
+            vec!["1ueGcxLPRx1Tr/XYExHnhbYLgveDs2J/wm36Hy4vbOwM=".to_string()]
+        );
+    }
+
+    // Needs a MinIO server with a robustmq bucket, e.g.
+    // docker run -p 9000:9000 minio/minio server /data
+    // mc mb local/robustmq
+    #[tokio::test]
+    #[ignore]
+    async fn minio_objects() {
+        let storage = S3Storage::new(S3Tier {
+            endpoint: "http://127.0.0.1:9000".to_string(),
+            region: "us-east-1".to_string(),
+            bucket: "robustmq".to_string(),
+            access_key: "minioadmin".to_string(),
+            secret_key: "minioadmin".to_string(),
+            path_style: true,
+        })
+        .unwrap();
+
+        let prefix = "test/minio-objects/";
+        for key in storage.list(prefix).await.unwrap() {
+            storage.delete(&key).await.unwrap();
+        }
+        let key = format!("{}00000000000000000000.log", prefix);
+        storage
+            .put(&key, Bytes::from("0123456789"))
+            .await
+            .unwrap();
+        assert_eq!(storage.get(&key).await.unwrap(), Bytes::from("0123456789"));
+        assert_eq!(
+            storage.get_range(&key, 2, 3).await.unwrap(),
+            Bytes::from("234")
+        );
+        assert_eq!(
+            storage.get_range(&key, 8, 10).await.unwrap(),
+            Bytes::from("89")
+        );
+        assert!(storage.get_range(&key, 20, 10).await.unwrap().is_empty());
+
+        let src = std::env::temp_dir().join("robustmq-remote-minio-objects.src");
+        fs::write(&src, b"segment").unwrap();
+        let file_key = format!("{}00000000000000000010.log", prefix);
+        storage.put_file(&file_key, &src).await.unwrap();
+        assert_eq!(
+            storage.get(&file_key).await.unwrap(),
+            Bytes::from("segment")
+        );
+        assert_eq!(storage.list(prefix).await.unwrap(), vec![key.clone(), file_key]);
+
+        storage.delete(&key).await.unwrap();
+        storage.delete(&key).await.unwrap();
+        assert!(storage.get(&key).await.is_err());
+    }
+}
@@ -0,0 +1,39 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use axum::async_trait;
+use bytes::Bytes;
+use common_base::error::common::CommonError;
+use std::path::Path;
+
+// An object storage used as the remote tier of the journal. Objects are addressed by
+// keys made of path segments separated by a slash, and are never modified once written.
+#[async_trait]
+pub trait RemoteStorage: Send + Sync {
+    async fn put(&self, key: &str, data: Bytes) -> Result<(), CommonError>;
+
+    // Uploads the content of a local file without loading it into memory.
+    async fn put_file(&self, key: &str, path: &Path) -> Result<(), CommonError>;
+
+    // Fails with RemoteObjectNotFound if the object does not exist.
+    async fn get(&self, key: &str) -> Result<Bytes, CommonError>;
+
+    // Reads up to len bytes from start on, less are returned at the end of the object.
+    async fn get_range(&self, key: &str, start: u64, len: u64) -> Result<Bytes, CommonError>;
+
+    // Deleting an object that does not exist succeeds.
+    async fn delete(&self, key: &str) -> Result<(), CommonError>;
+
+    // Returns the keys starting with prefix, in ascending order.
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, CommonError>;
+}
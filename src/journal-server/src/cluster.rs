@@ -11,18 +11,31 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::shard::manager::ShardManager;
 use clients::{
-    placement::placement::call::{heartbeat, register_node, un_register_node},
+    placement::{
+        journal::call::report_shard_status,
+        placement::call::{heartbeat, register_node, report_monitor, un_register_node},
+    },
     poll::ClientPool,
 };
 use common_base::{config::journal_server::JournalServerConfig, tools::get_local_ip};
 use log::{debug, error, info};
+use metadata_struct::journal::node_extend::JournalNodeExtend;
 use protocol::placement_center::generate::{
     common::ClusterType,
-    placement::{HeartbeatRequest, RegisterNodeRequest, UnRegisterNodeRequest},
+    journal::{ReportShardStatusRequest, ShardStatus},
+    placement::{
+        FoldMonitor, HeartbeatRequest, RegisterNodeRequest, ReportMonitorRequest,
+        UnRegisterNodeRequest,
+    },
 };
 use std::{sync::Arc, time::Duration};
-use tokio::time;
+use tokio::{select, sync::broadcast, time};
+
+const REPORT_HEARTBEAT_INTERVAL_MS: u64 = 1000;
+const REPORT_MONITOR_INTERVAL_MS: u64 = 10000;
+const REPORT_SHARD_STATUS_INTERVAL_MS: u64 = 3000;
 
 pub async fn register_storage_engine_node(
     client_poll: Arc<ClientPool>,
@@ -33,7 +46,18 @@ pub async fn register_storage_engine_node(
     req.cluster_name = config.cluster_name;
     req.node_id = config.node_id;
     req.node_ip = get_local_ip();
-    req.extend_info = "".to_string();
+    // Clients connect to this address to produce and fetch.
+    req.node_inner_addr = format!("{}:{}", req.node_ip, config.grpc_port);
+    let extend = JournalNodeExtend {
+        data_fold: config.data_path.clone(),
+        rack: config.rack.clone(),
+    };
+    req.extend_info = match serde_json::to_string(&extend) {
+        Ok(data) => data,
+        Err(e) => {
+            panic!("{}", e.to_string());
+        }
+    };
     match register_node(client_poll.clone(), config.placement_center, req.clone()).await {
         Ok(_) => {
             info!("Node {} has been successfully registered", config.node_id);
@@ -63,30 +87,154 @@ pub async fn unregister_storage_engine_node(
     }
 }
 
-pub async fn report_heartbeat(client_poll: Arc<ClientPool>, config: JournalServerConfig) {
+pub async fn report_heartbeat(
+    client_poll: Arc<ClientPool>,
+    config: JournalServerConfig,
+    stop_send: broadcast::Sender<bool>,
+) {
+    let mut stop_rx = stop_send.subscribe();
+    loop {
+        select! {
+            val = stop_rx.recv() => {
+                if let Ok(flag) = val {
+                    if flag {
+                        info!("{}", "Heartbeat report thread stopped successfully.");
+                        break;
+                    }
+                }
+            }
+            _ = time::sleep(Duration::from_millis(REPORT_HEARTBEAT_INTERVAL_MS)) => {
+                let mut req = HeartbeatRequest::default();
+                req.cluster_name = config.cluster_name.clone();
+                req.cluster_type = ClusterType::JournalServer.into();
+                req.node_id = config.node_id;
+                match heartbeat(client_poll.clone(), config.placement_center.clone(), req).await {
+                    Ok(_) => {
+                        debug!(
+                            "Node {} successfully reports the heartbeat communication",
+                            config.node_id
+                        );
+                    }
+                    Err(e) => {
+                        error!("{}", e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Reports the usage of the data folders, which the placement center uses to place new
+// segment replicas.
+pub async fn report_monitor_thread(
+    client_poll: Arc<ClientPool>,
+    config: JournalServerConfig,
+    shard_manager: Arc<ShardManager>,
+    stop_send: broadcast::Sender<bool>,
+) {
+    let mut stop_rx = stop_send.subscribe();
     loop {
-        let mut req = HeartbeatRequest::default();
-        req.cluster_name = config.cluster_name.clone();
-        req.cluster_type = ClusterType::JournalServer.into();
-        req.node_id = config.node_id;
-        match heartbeat(
-            client_poll.clone(),
-            config.placement_center.clone(),
-            req.clone(),
-        )
-        .await
-        {
-            Ok(_) => {
-                debug!(
-                    "Node {} successfully reports the heartbeat communication",
-                    config.node_id
-                );
-                break;
+        select! {
+            val = stop_rx.recv() => {
+                if let Ok(flag) = val {
+                    if flag {
+                        info!("{}", "Monitor report thread stopped successfully.");
+                        break;
+                    }
+                }
             }
-            Err(e) => {
-                error!("{}", e);
+            _ = time::sleep(Duration::from_millis(REPORT_MONITOR_INTERVAL_MS)) => {
+                report_fold_usage(&client_poll, &config, &shard_manager).await;
             }
         }
-        time::sleep(Duration::from_millis(1000)).await;
+    }
+}
+
+async fn report_fold_usage(
+    client_poll: &Arc<ClientPool>,
+    config: &JournalServerConfig,
+    shard_manager: &Arc<ShardManager>,
+) {
+    let folds = match shard_manager.fold_usage() {
+        Ok(folds) => folds,
+        Err(e) => {
+            error!("Failed to read the usage of the data folders, {}", e);
+            return;
+        }
+    };
+    let req = ReportMonitorRequest {
+        cluster_name: config.cluster_name.clone(),
+        node_id: config.node_id,
+        folds: folds
+            .into_iter()
+            .map(|usage| FoldMonitor {
+                fold: usage.fold,
+                used_bytes: usage.used_bytes,
+                segment_num: usage.segment_num,
+            })
+            .collect(),
+        ..Default::default()
+    };
+    if let Err(e) = report_monitor(client_poll.clone(), config.placement_center.clone(), req).await
+    {
+        error!("Failed to report the node monitor, {}", e);
+    }
+}
+
+// Reports the offsets of the shards held by this node. The placement center rolls the
+// segments and picks their leaders from them.
+pub async fn report_shard_status_thread(
+    client_poll: Arc<ClientPool>,
+    config: JournalServerConfig,
+    shard_manager: Arc<ShardManager>,
+    stop_send: broadcast::Sender<bool>,
+) {
+    let mut stop_rx = stop_send.subscribe();
+    loop {
+        select! {
+            val = stop_rx.recv() => {
+                if let Ok(flag) = val {
+                    if flag {
+                        info!("{}", "Shard status report thread stopped successfully.");
+                        break;
+                    }
+                }
+            }
+            _ = time::sleep(Duration::from_millis(REPORT_SHARD_STATUS_INTERVAL_MS)) => {
+                report_shard_progress(&client_poll, &config, &shard_manager).await;
+            }
+        }
+    }
+}
+
+async fn report_shard_progress(
+    client_poll: &Arc<ClientPool>,
+    config: &JournalServerConfig,
+    shard_manager: &Arc<ShardManager>,
+) {
+    let shards = match shard_manager.shard_status() {
+        Ok(shards) => shards,
+        Err(e) => {
+            error!("Failed to read the status of the shards, {}", e);
+            return;
+        }
+    };
+    let req = ReportShardStatusRequest {
+        cluster_name: config.cluster_name.clone(),
+        node_id: config.node_id,
+        shards: shards
+            .into_iter()
+            .map(|status| ShardStatus {
+                shard_name: status.shard_name,
+                next_offset: status.next_offset,
+                high_watermark: status.high_watermark,
+                log_bytes: status.log_bytes,
+            })
+            .collect(),
+    };
+    if let Err(e) =
+        report_shard_status(client_poll.clone(), config.placement_center.clone(), req).await
+    {
+        error!("Failed to report the status of the shards, {}", e);
     }
 }
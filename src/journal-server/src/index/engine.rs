@@ -0,0 +1,104 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::error::{common::CommonError, journal_server::JournalServerError};
+use rocksdb::{Options, WriteBatch, DB};
+
+// The local RocksDB holding the indexes of all segments stored on this node.
+pub struct IndexEngine {
+    db: DB,
+}
+
+impl IndexEngine {
+    pub fn new(path: &str, max_open_files: Option<i32>) -> Result<Self, CommonError> {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        if let Some(num) = max_open_files {
+            opts.set_max_open_files(num);
+        }
+        let db = DB::open(&opts, path)?;
+        return Ok(IndexEngine { db });
+    }
+
+    pub fn write(&self, batch: WriteBatch) -> Result<(), CommonError> {
+        self.db.write(batch)?;
+        return Ok(());
+    }
+
+    pub fn get(&self, key: &str) -> Result<Option<Vec<u8>>, CommonError> {
+        return Ok(self.db.get(key)?);
+    }
+
+    // Returns the last entry under prefix whose key is not greater than key.
+    pub fn floor(&self, prefix: &str, key: &str) -> Result<Option<Vec<u8>>, CommonError> {
+        let mut iter = self.db.raw_iterator();
+        iter.seek_for_prev(key);
+        if iter.valid() {
+            if let (Some(raw_key), Some(value)) = (iter.key(), iter.value()) {
+                if raw_key.starts_with(prefix.as_bytes()) {
+                    return Ok(Some(value.to_vec()));
+                }
+            }
+        }
+        iter.status()?;
+        return Ok(None);
+    }
+
+    // Returns the entries under prefix from start_key on, in key order.
+    pub fn list_from(
+        &self,
+        prefix: &str,
+        start_key: &str,
+    ) -> Result<Vec<(String, Vec<u8>)>, CommonError> {
+        let mut iter = self.db.raw_iterator();
+        iter.seek(start_key);
+        let mut results = Vec::new();
+        while iter.valid() {
+            let (raw_key, value) = match (iter.key(), iter.value()) {
+                (Some(raw_key), Some(value)) => (raw_key, value),
+                _ => break,
+            };
+            if !raw_key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            results.push((String::from_utf8(raw_key.to_vec())?, value.to_vec()));
+            iter.next();
+        }
+        iter.status()?;
+        return Ok(results);
+    }
+
+    pub fn delete_prefix(&self, batch: &mut WriteBatch, prefix: &str) -> Result<(), CommonError> {
+        for (key, _) in self.list_from(prefix, prefix)? {
+            batch.delete(key);
+        }
+        return Ok(());
+    }
+}
+
+pub fn encode_u64_pair(first: u64, second: u64) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(16);
+    buf.extend_from_slice(&first.to_be_bytes());
+    buf.extend_from_slice(&second.to_be_bytes());
+    return buf;
+}
+
+pub fn decode_u64_list(data: &[u8], num: usize) -> Result<Vec<u64>, CommonError> {
+    if data.len() != num * 8 {
+        return Err(JournalServerError::IndexDecodeError(format!("{:?}", data)).into());
+    }
+    return Ok(data
+        .chunks(8)
+        .map(|raw| u64::from_be_bytes(raw.try_into().unwrap()))
+        .collect());
+}
@@ -11,4 +11,86 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use super::engine::{decode_u64_list, IndexEngine};
+use common_base::error::common::CommonError;
+use rocksdb::WriteBatch;
+use std::sync::Arc;
 
+// Record keys are arbitrary bytes, they are hex encoded so that they cannot contain the
+// separator of the index keys.
+fn encode_record_key(key: &[u8]) -> String {
+    return key.iter().map(|b| format!("{:02x}", b)).collect();
+}
+
+pub fn key_record_key_index_prefix(shard_name: &str, key: &[u8]) -> String {
+    return format!("/key/{}/{}/", shard_name, encode_record_key(key));
+}
+
+pub fn key_record_key_index(shard_name: &str, key: &[u8], offset: u64) -> String {
+    return format!(
+        "{}{:020}",
+        key_record_key_index_prefix(shard_name, key),
+        offset
+    );
+}
+
+pub fn key_key_offset_prefix(shard_name: &str) -> String {
+    return format!("/key_offset/{}/", shard_name);
+}
+
+pub fn key_key_offset(shard_name: &str, offset: u64) -> String {
+    return format!("{}{:020}", key_key_offset_prefix(shard_name), offset);
+}
+
+// Index of the offsets of every record key in a shard. Each version of a key is kept
+// together with an offset -> key entry, so that the entries past a truncation point can
+// be removed without scanning all keys.
+pub struct KeyIndex {
+    engine: Arc<IndexEngine>,
+}
+
+impl KeyIndex {
+    pub fn new(engine: Arc<IndexEngine>) -> Self {
+        return KeyIndex { engine };
+    }
+
+    pub fn save(&self, batch: &mut WriteBatch, shard_name: &str, key: &[u8], offset: u64) {
+        batch.put(
+            key_record_key_index(shard_name, key, offset),
+            offset.to_be_bytes(),
+        );
+        batch.put(key_key_offset(shard_name, offset), key);
+    }
+
+    // Returns the offset of the latest record with the key.
+    pub fn latest(&self, shard_name: &str, key: &[u8]) -> Result<Option<u64>, CommonError> {
+        let prefix = key_record_key_index_prefix(shard_name, key);
+        let last_key = key_record_key_index(shard_name, key, u64::MAX);
+        if let Some(data) = self.engine.floor(&prefix, &last_key)? {
+            return Ok(Some(decode_u64_list(&data, 1)?[0]));
+        }
+        return Ok(None);
+    }
+
+    // Removes the entries of the records in [start_offset, end_offset).
+    pub fn remove_range(
+        &self,
+        batch: &mut WriteBatch,
+        shard_name: &str,
+        start_offset: u64,
+        end_offset: u64,
+    ) -> Result<(), CommonError> {
+        let prefix = key_key_offset_prefix(shard_name);
+        let start_key = key_key_offset(shard_name, start_offset);
+        let end_key = key_key_offset(shard_name, end_offset);
+        for (index_key, key) in self.engine.list_from(&prefix, &start_key)? {
+            if index_key >= end_key {
+                break;
+            }
+            let offset = index_key[prefix.len()..].parse::<u64>().unwrap_or_default();
+            batch.delete(key_record_key_index(shard_name, &key, offset));
+            batch.delete(index_key);
+        }
+        return Ok(());
+    }
+}
@@ -12,6 +12,457 @@
 // limitations under the License.
 
 
+use crate::{
+    record::record::Record,
+    shard::{
+        producer_state::{AbortedTxn, ProducerState},
+        segment::{SegmentFile, FRAME_HEADER_LEN, SEGMENT_HEADER_LEN},
+    },
+};
+use common_base::error::{common::CommonError, journal_server::JournalServerError};
+use engine::{decode_u64_list, encode_u64_pair, IndexEngine};
+use key_index::KeyIndex;
+use offset_index::OffsetIndex;
+use rocksdb::WriteBatch;
+use std::sync::Arc;
+use time_index::TimeIndex;
+
+pub mod engine;
 pub mod key_index;
 pub mod offset_index;
-pub mod time_index;
\ No newline at end of file
+pub mod time_index;
+
+// A record is added to the offset and time indexes once this many bytes have been
+// written since the last indexed record.
+pub const INDEX_INTERVAL_BYTES: u64 = 4096;
+
+const REBUILD_BATCH_RECORD_NUM: usize = 1000;
+
+pub fn key_segment_index_meta(shard_name: &str, segment_no: u64) -> String {
+    return format!("/segment/{}/{:020}", shard_name, segment_no);
+}
+
+pub fn key_high_watermark(shard_name: &str) -> String {
+    return format!("/high_watermark/{}", shard_name);
+}
+
+pub fn key_producer_snapshot(shard_name: &str) -> String {
+    return format!("/producer_snapshot/{}", shard_name);
+}
+
+pub fn key_aborted_txn_prefix(shard_name: &str) -> String {
+    return format!("/aborted_txn/{}/", shard_name);
+}
+
+pub fn key_aborted_txn(shard_name: &str, last_offset: u64) -> String {
+    return format!("{}{:020}", key_aborted_txn_prefix(shard_name), last_offset);
+}
+
+// How far a segment has been indexed. It is written in the same batch as the index
+// entries, so a segment whose records are not all covered by its indexes is detected
+// when the shard is opened.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SegmentIndexMeta {
+    pub next_offset: u64,
+    pub max_timestamp: u64,
+    pub last_index_position: u64,
+}
+
+impl SegmentIndexMeta {
+    fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(24);
+        buf.extend_from_slice(&self.next_offset.to_be_bytes());
+        buf.extend_from_slice(&self.max_timestamp.to_be_bytes());
+        buf.extend_from_slice(&self.last_index_position.to_be_bytes());
+        return buf;
+    }
+
+    fn decode(data: &[u8]) -> Result<Self, CommonError> {
+        let raw = decode_u64_list(data, 3)?;
+        return Ok(SegmentIndexMeta {
+            next_offset: raw[0],
+            max_timestamp: raw[1],
+            last_index_position: raw[2],
+        });
+    }
+}
+
+// The offset and time index entries of a segment, exported along with the segment when
+// it is copied to the remote tier.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SegmentIndexEntries {
+    // (offset, position)
+    pub offset_entries: Vec<(u64, u64)>,
+    // (max timestamp, offset, position)
+    pub time_entries: Vec<(u64, u64, u64)>,
+}
+
+// The offset, time and key indexes of one shard.
+pub struct ShardIndex {
+    shard_name: String,
+    engine: Arc<IndexEngine>,
+    offset_index: OffsetIndex,
+    time_index: TimeIndex,
+    key_index: KeyIndex,
+}
+
+impl ShardIndex {
+    pub fn new(engine: Arc<IndexEngine>, shard_name: String) -> Self {
+        return ShardIndex {
+            shard_name,
+            offset_index: OffsetIndex::new(engine.clone()),
+            time_index: TimeIndex::new(engine.clone()),
+            key_index: KeyIndex::new(engine.clone()),
+            engine,
+        };
+    }
+
+    pub fn get_meta(&self, segment_no: u64) -> Result<Option<SegmentIndexMeta>, CommonError> {
+        let key = key_segment_index_meta(&self.shard_name, segment_no);
+        if let Some(data) = self.engine.get(&key)? {
+            return Ok(Some(SegmentIndexMeta::decode(&data)?));
+        }
+        return Ok(None);
+    }
+
+    // Indexes records appended to the segment, positions are the file positions of the
+    // records as returned by the append.
+    pub fn append(
+        &self,
+        segment_no: u64,
+        meta: &mut SegmentIndexMeta,
+        records: &[Record],
+        positions: &[u64],
+    ) -> Result<(), CommonError> {
+        let mut batch = WriteBatch::default();
+        for (record, position) in records.iter().zip(positions.iter()) {
+            meta.max_timestamp = meta.max_timestamp.max(record.timestamp);
+            if *position == SEGMENT_HEADER_LEN
+                || *position >= meta.last_index_position + INDEX_INTERVAL_BYTES
+            {
+                self.offset_index.save(
+                    &mut batch,
+                    &self.shard_name,
+                    segment_no,
+                    record.offset,
+                    *position,
+                );
+                self.time_index.save(
+                    &mut batch,
+                    &self.shard_name,
+                    segment_no,
+                    meta.max_timestamp,
+                    record.offset,
+                    *position,
+                );
+                meta.last_index_position = *position;
+            }
+            if !record.key.is_empty() {
+                self.key_index
+                    .save(&mut batch, &self.shard_name, &record.key, record.offset);
+            }
+            meta.next_offset = record.offset + 1;
+        }
+        batch.put(
+            key_segment_index_meta(&self.shard_name, segment_no),
+            meta.encode(),
+        );
+        return self.engine.write(batch);
+    }
+
+    // Drops the indexes of the segment and builds them again from the segment file.
+    // end_offset bounds the key index entries that belong to the segment.
+    pub fn rebuild(
+        &self,
+        segment: &SegmentFile,
+        end_offset: u64,
+    ) -> Result<SegmentIndexMeta, CommonError> {
+        let mut batch = WriteBatch::default();
+        self.offset_index
+            .delete_segment(&mut batch, &self.shard_name, segment.segment_no)?;
+        self.time_index
+            .delete_segment(&mut batch, &self.shard_name, segment.segment_no)?;
+        self.key_index.remove_range(
+            &mut batch,
+            &self.shard_name,
+            segment.start_offset,
+            end_offset,
+        )?;
+        self.engine.write(batch)?;
+
+        let mut meta = SegmentIndexMeta {
+            next_offset: segment.start_offset,
+            ..Default::default()
+        };
+        let mut position = SEGMENT_HEADER_LEN;
+        loop {
+            let data = segment.read_at(position, REBUILD_BATCH_RECORD_NUM, 0)?;
+            if data.is_empty() {
+                break;
+            }
+            let (positions, records): (Vec<u64>, Vec<Record>) = data.into_iter().unzip();
+            position = positions[positions.len() - 1]
+                + FRAME_HEADER_LEN
+                + records[records.len() - 1].encode_len() as u64;
+            self.append(segment.segment_no, &mut meta, &records, &positions)?;
+        }
+        // The records at the end of a compacted segment may have been removed, and an
+        // empty segment still records that it has been indexed.
+        meta.next_offset = meta.next_offset.max(segment.next_offset);
+        let mut batch = WriteBatch::default();
+        batch.put(
+            key_segment_index_meta(&self.shard_name, segment.segment_no),
+            meta.encode(),
+        );
+        self.engine.write(batch)?;
+        return Ok(meta);
+    }
+
+    // Removes the index entries of the records cut off when the tail of the segment was
+    // truncated.
+    pub fn truncate(
+        &self,
+        segment: &SegmentFile,
+        meta: &mut SegmentIndexMeta,
+    ) -> Result<(), CommonError> {
+        let mut batch = WriteBatch::default();
+        self.offset_index.truncate(
+            &mut batch,
+            &self.shard_name,
+            segment.segment_no,
+            segment.next_offset,
+        )?;
+        self.time_index.truncate(
+            &mut batch,
+            &self.shard_name,
+            segment.segment_no,
+            segment.next_offset,
+        )?;
+        self.key_index
+            .remove_range(&mut batch, &self.shard_name, segment.next_offset, u64::MAX)?;
+        self.engine.write(batch)?;
+
+        meta.next_offset = segment.next_offset;
+        meta.last_index_position = match self.offset_index.floor(
+            &self.shard_name,
+            segment.segment_no,
+            segment.next_offset,
+        )? {
+            Some((_, position)) => position,
+            None => 0,
+        };
+        let mut batch = WriteBatch::default();
+        batch.put(
+            key_segment_index_meta(&self.shard_name, segment.segment_no),
+            meta.encode(),
+        );
+        return self.engine.write(batch);
+    }
+
+    // Removes the offset and time indexes of a segment whose file has been removed, the
+    // key index entries are removed with the truncation of the segment before it.
+    pub fn delete_segment(&self, segment_no: u64) -> Result<(), CommonError> {
+        let mut batch = WriteBatch::default();
+        self.offset_index
+            .delete_segment(&mut batch, &self.shard_name, segment_no)?;
+        self.time_index
+            .delete_segment(&mut batch, &self.shard_name, segment_no)?;
+        batch.delete(key_segment_index_meta(&self.shard_name, segment_no));
+        return self.engine.write(batch);
+    }
+
+    pub fn get_high_watermark(&self) -> Result<Option<u64>, CommonError> {
+        if let Some(data) = self.engine.get(&key_high_watermark(&self.shard_name))? {
+            return Ok(Some(decode_u64_list(&data, 1)?[0]));
+        }
+        return Ok(None);
+    }
+
+    pub fn save_high_watermark(&self, high_watermark: u64) -> Result<(), CommonError> {
+        let mut batch = WriteBatch::default();
+        batch.put(
+            key_high_watermark(&self.shard_name),
+            high_watermark.to_be_bytes(),
+        );
+        return self.engine.write(batch);
+    }
+
+    // Returns the producer state saved by save_producer_snapshot and the offset up to
+    // which the log had been applied to it.
+    pub fn get_producer_snapshot(&self) -> Result<Option<(u64, ProducerState)>, CommonError> {
+        if let Some(data) = self.engine.get(&key_producer_snapshot(&self.shard_name))? {
+            if data.len() < 8 {
+                return Err(JournalServerError::IndexDecodeError(format!("{:?}", data)).into());
+            }
+            let next_offset = decode_u64_list(&data[0..8], 1)?[0];
+            return Ok(Some((next_offset, ProducerState::decode(&data[8..])?)));
+        }
+        return Ok(None);
+    }
+
+    pub fn save_producer_snapshot(
+        &self,
+        next_offset: u64,
+        state: &ProducerState,
+    ) -> Result<(), CommonError> {
+        let mut data = next_offset.to_be_bytes().to_vec();
+        data.extend_from_slice(&state.encode());
+        let mut batch = WriteBatch::default();
+        batch.put(key_producer_snapshot(&self.shard_name), data);
+        return self.engine.write(batch);
+    }
+
+    pub fn delete_producer_snapshot(&self) -> Result<(), CommonError> {
+        let mut batch = WriteBatch::default();
+        batch.delete(key_producer_snapshot(&self.shard_name));
+        return self.engine.write(batch);
+    }
+
+    pub fn save_aborted_txn(&self, txn: &AbortedTxn) -> Result<(), CommonError> {
+        let mut data = encode_u64_pair(txn.producer_id, txn.first_offset);
+        data.extend_from_slice(&txn.last_offset.to_be_bytes());
+        let mut batch = WriteBatch::default();
+        batch.put(key_aborted_txn(&self.shard_name, txn.last_offset), data);
+        return self.engine.write(batch);
+    }
+
+    // Returns the aborted transactions of the shard ordered by their last offset.
+    pub fn list_aborted_txns(&self) -> Result<Vec<AbortedTxn>, CommonError> {
+        let prefix = key_aborted_txn_prefix(&self.shard_name);
+        let mut results = Vec::new();
+        for (_, data) in self.engine.list_from(&prefix, &prefix)? {
+            let raw = decode_u64_list(&data, 3)?;
+            results.push(AbortedTxn {
+                producer_id: raw[0],
+                first_offset: raw[1],
+                last_offset: raw[2],
+            });
+        }
+        return Ok(results);
+    }
+
+    // Removes the aborted transactions whose marker is at or after offset.
+    pub fn truncate_aborted_txns(&self, offset: u64) -> Result<(), CommonError> {
+        let prefix = key_aborted_txn_prefix(&self.shard_name);
+        let mut batch = WriteBatch::default();
+        for (key, _) in self
+            .engine
+            .list_from(&prefix, &key_aborted_txn(&self.shard_name, offset))?
+        {
+            batch.delete(key);
+        }
+        return self.engine.write(batch);
+    }
+
+    // Returns the position to scan from for the record at offset.
+    pub fn offset_position(&self, segment_no: u64, offset: u64) -> Result<u64, CommonError> {
+        match self
+            .offset_index
+            .floor(&self.shard_name, segment_no, offset)?
+        {
+            Some((_, position)) => return Ok(position),
+            None => return Ok(SEGMENT_HEADER_LEN),
+        }
+    }
+
+    // Returns the position to scan from for the first record whose timestamp is not less
+    // than timestamp.
+    pub fn timestamp_position(&self, segment_no: u64, timestamp: u64) -> Result<u64, CommonError> {
+        match self
+            .time_index
+            .floor(&self.shard_name, segment_no, timestamp)?
+        {
+            Some((_, position)) => return Ok(position),
+            None => return Ok(SEGMENT_HEADER_LEN),
+        }
+    }
+
+    pub fn export(&self, segment_no: u64) -> Result<SegmentIndexEntries, CommonError> {
+        return Ok(SegmentIndexEntries {
+            offset_entries: self.offset_index.list(&self.shard_name, segment_no)?,
+            time_entries: self.time_index.list(&self.shard_name, segment_no)?,
+        });
+    }
+
+    pub fn latest_offset_by_key(&self, key: &[u8]) -> Result<Option<u64>, CommonError> {
+        return self.key_index.latest(&self.shard_name, key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{engine::IndexEngine, ShardIndex, INDEX_INTERVAL_BYTES};
+    use crate::{
+        record::record::Record,
+        shard::segment::{FsyncPolicy, SegmentFile, SEGMENT_HEADER_LEN},
+    };
+    use bytes::Bytes;
+    use std::{fs, sync::Arc};
+
+    fn build_records(start_offset: u64, num: u64) -> Vec<Record> {
+        let mut records = Vec::new();
+        for i in start_offset..start_offset + num {
+            let mut record = Record::build(
+                Bytes::from(format!("k{}", i % 10)),
+                Bytes::from(vec![0u8; 1000]),
+                Vec::new(),
+            );
+            record.offset = i;
+            record.timestamp = 1000 + i;
+            records.push(record);
+        }
+        return records;
+    }
+
+    #[test]
+    fn shard_index_append_rebuild_truncate() {
+        let dir = std::env::temp_dir().join("robustmq-journal-shard-index");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let engine =
+            Arc::new(IndexEngine::new(&dir.join("index").display().to_string(), None).unwrap());
+        let index = ShardIndex::new(engine, "s1".to_string());
+
+        let mut segment = SegmentFile::create(&dir, 0, 0, FsyncPolicy::Os).unwrap();
+        let records = build_records(0, 50);
+        let positions = segment.append(&records).unwrap();
+        let mut meta = Default::default();
+        index.append(0, &mut meta, &records, &positions).unwrap();
+        assert_eq!(meta.next_offset, 50);
+        assert_eq!(meta.max_timestamp, 1049);
+        assert_eq!(index.get_meta(0).unwrap().unwrap(), meta);
+
+        // The index is sparse, the floor position is before the record but close to it.
+        let position = index.offset_position(0, 30).unwrap();
+        assert!(position <= positions[30]);
+        assert!(positions[30] - position < INDEX_INTERVAL_BYTES + 1100);
+        assert_eq!(index.offset_position(0, 0).unwrap(), SEGMENT_HEADER_LEN);
+
+        let position = index.timestamp_position(0, 1030).unwrap();
+        assert!(position <= positions[30]);
+        assert_eq!(
+            index.timestamp_position(0, 1000).unwrap(),
+            SEGMENT_HEADER_LEN
+        );
+
+        assert_eq!(index.latest_offset_by_key(b"k3").unwrap(), Some(43));
+        assert_eq!(index.latest_offset_by_key(b"k11").unwrap(), None);
+
+        let entries = index.export(0).unwrap();
+        assert_eq!(entries.offset_entries[0], (0, SEGMENT_HEADER_LEN));
+        assert!(entries.offset_entries.len() > 1);
+        assert_eq!(entries.time_entries.len(), entries.offset_entries.len());
+        assert_eq!(entries.time_entries[0], (1000, 0, SEGMENT_HEADER_LEN));
+
+        let rebuilt = index.rebuild(&segment, u64::MAX).unwrap();
+        assert_eq!(rebuilt, meta);
+        assert_eq!(index.latest_offset_by_key(b"k3").unwrap(), Some(43));
+
+        segment.next_offset = 35;
+        index.truncate(&segment, &mut meta).unwrap();
+        assert_eq!(meta.next_offset, 35);
+        assert!(meta.last_index_position <= positions[34]);
+        assert_eq!(index.latest_offset_by_key(b"k3").unwrap(), Some(33));
+        assert!(index.offset_position(0, 49).unwrap() <= positions[34]);
+    }
+}
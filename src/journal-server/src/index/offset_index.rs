@@ -11,4 +11,99 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use super::engine::{decode_u64_list, encode_u64_pair, IndexEngine};
+use common_base::error::common::CommonError;
+use rocksdb::WriteBatch;
+use std::sync::Arc;
 
+pub fn key_offset_index_prefix(shard_name: &str, segment_no: u64) -> String {
+    return format!("/offset/{}/{:020}/", shard_name, segment_no);
+}
+
+pub fn key_offset_index(shard_name: &str, segment_no: u64, offset: u64) -> String {
+    return format!(
+        "{}{:020}",
+        key_offset_index_prefix(shard_name, segment_no),
+        offset
+    );
+}
+
+// Sparse offset -> file position index of a segment, only one record every few KB is
+// indexed and the rest is found by scanning forward from the nearest indexed record.
+pub struct OffsetIndex {
+    engine: Arc<IndexEngine>,
+}
+
+impl OffsetIndex {
+    pub fn new(engine: Arc<IndexEngine>) -> Self {
+        return OffsetIndex { engine };
+    }
+
+    pub fn save(
+        &self,
+        batch: &mut WriteBatch,
+        shard_name: &str,
+        segment_no: u64,
+        offset: u64,
+        position: u64,
+    ) {
+        batch.put(
+            key_offset_index(shard_name, segment_no, offset),
+            encode_u64_pair(offset, position),
+        );
+    }
+
+    // Returns the (offset, position) of the last indexed record at or before offset.
+    pub fn floor(
+        &self,
+        shard_name: &str,
+        segment_no: u64,
+        offset: u64,
+    ) -> Result<Option<(u64, u64)>, CommonError> {
+        let prefix = key_offset_index_prefix(shard_name, segment_no);
+        let key = key_offset_index(shard_name, segment_no, offset);
+        if let Some(data) = self.engine.floor(&prefix, &key)? {
+            let raw = decode_u64_list(&data, 2)?;
+            return Ok(Some((raw[0], raw[1])));
+        }
+        return Ok(None);
+    }
+
+    // Returns every (offset, position) entry of the segment in offset order.
+    pub fn list(&self, shard_name: &str, segment_no: u64) -> Result<Vec<(u64, u64)>, CommonError> {
+        let prefix = key_offset_index_prefix(shard_name, segment_no);
+        let mut results = Vec::new();
+        for (_, data) in self.engine.list_from(&prefix, &prefix)? {
+            let raw = decode_u64_list(&data, 2)?;
+            results.push((raw[0], raw[1]));
+        }
+        return Ok(results);
+    }
+
+    // Removes the entries of the records from next_offset on.
+    pub fn truncate(
+        &self,
+        batch: &mut WriteBatch,
+        shard_name: &str,
+        segment_no: u64,
+        next_offset: u64,
+    ) -> Result<(), CommonError> {
+        let prefix = key_offset_index_prefix(shard_name, segment_no);
+        let start_key = key_offset_index(shard_name, segment_no, next_offset);
+        for (key, _) in self.engine.list_from(&prefix, &start_key)? {
+            batch.delete(key);
+        }
+        return Ok(());
+    }
+
+    pub fn delete_segment(
+        &self,
+        batch: &mut WriteBatch,
+        shard_name: &str,
+        segment_no: u64,
+    ) -> Result<(), CommonError> {
+        return self
+            .engine
+            .delete_prefix(batch, &key_offset_index_prefix(shard_name, segment_no));
+    }
+}
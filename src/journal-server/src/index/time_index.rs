@@ -11,4 +11,117 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use super::engine::{decode_u64_list, encode_u64_pair, IndexEngine};
+use common_base::error::{common::CommonError, journal_server::JournalServerError};
+use rocksdb::WriteBatch;
+use std::sync::Arc;
 
+pub fn key_time_index_prefix(shard_name: &str, segment_no: u64) -> String {
+    return format!("/time/{}/{:020}/", shard_name, segment_no);
+}
+
+pub fn key_time_index(shard_name: &str, segment_no: u64, timestamp: u64) -> String {
+    return format!(
+        "{}{:020}",
+        key_time_index_prefix(shard_name, segment_no),
+        timestamp
+    );
+}
+
+// Sparse timestamp -> offset index of a segment. An entry (timestamp, offset, position)
+// means that no record up to and including offset has a larger timestamp, so a search
+// for a timestamp can start at the last entry below it even if producer timestamps are
+// not in order.
+pub struct TimeIndex {
+    engine: Arc<IndexEngine>,
+}
+
+impl TimeIndex {
+    pub fn new(engine: Arc<IndexEngine>) -> Self {
+        return TimeIndex { engine };
+    }
+
+    pub fn save(
+        &self,
+        batch: &mut WriteBatch,
+        shard_name: &str,
+        segment_no: u64,
+        max_timestamp: u64,
+        offset: u64,
+        position: u64,
+    ) {
+        batch.put(
+            key_time_index(shard_name, segment_no, max_timestamp),
+            encode_u64_pair(offset, position),
+        );
+    }
+
+    // Returns the (offset, position) to start from when searching for the first record
+    // whose timestamp is not less than timestamp.
+    pub fn floor(
+        &self,
+        shard_name: &str,
+        segment_no: u64,
+        timestamp: u64,
+    ) -> Result<Option<(u64, u64)>, CommonError> {
+        if timestamp == 0 {
+            return Ok(None);
+        }
+        let prefix = key_time_index_prefix(shard_name, segment_no);
+        let key = key_time_index(shard_name, segment_no, timestamp - 1);
+        if let Some(data) = self.engine.floor(&prefix, &key)? {
+            let raw = decode_u64_list(&data, 2)?;
+            return Ok(Some((raw[0], raw[1])));
+        }
+        return Ok(None);
+    }
+
+    // Returns every (timestamp, offset, position) entry of the segment in timestamp
+    // order.
+    pub fn list(
+        &self,
+        shard_name: &str,
+        segment_no: u64,
+    ) -> Result<Vec<(u64, u64, u64)>, CommonError> {
+        let prefix = key_time_index_prefix(shard_name, segment_no);
+        let mut results = Vec::new();
+        for (key, data) in self.engine.list_from(&prefix, &prefix)? {
+            let timestamp = match key[prefix.len()..].parse::<u64>() {
+                Ok(timestamp) => timestamp,
+                Err(_) => return Err(JournalServerError::IndexDecodeError(key).into()),
+            };
+            let raw = decode_u64_list(&data, 2)?;
+            results.push((timestamp, raw[0], raw[1]));
+        }
+        return Ok(results);
+    }
+
+    // Removes the entries pointing at records from next_offset on.
+    pub fn truncate(
+        &self,
+        batch: &mut WriteBatch,
+        shard_name: &str,
+        segment_no: u64,
+        next_offset: u64,
+    ) -> Result<(), CommonError> {
+        let prefix = key_time_index_prefix(shard_name, segment_no);
+        for (key, data) in self.engine.list_from(&prefix, &prefix)? {
+            let raw = decode_u64_list(&data, 2)?;
+            if raw[0] >= next_offset {
+                batch.delete(key);
+            }
+        }
+        return Ok(());
+    }
+
+    pub fn delete_segment(
+        &self,
+        batch: &mut WriteBatch,
+        shard_name: &str,
+        segment_no: u64,
+    ) -> Result<(), CommonError> {
+        return self
+            .engine
+            .delete_prefix(batch, &key_time_index_prefix(shard_name, segment_no));
+    }
+}
@@ -11,26 +11,46 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use clients::poll::ClientPool;
-use cluster::{register_storage_engine_node, report_heartbeat, unregister_storage_engine_node};
+use clients::{
+    journal::client::{JournalClient, JournalClientConfig},
+    poll::ClientPool,
+};
+use cluster::{
+    register_storage_engine_node, report_heartbeat, report_monitor_thread,
+    report_shard_status_thread, unregister_storage_engine_node,
+};
 use common_base::{
     config::journal_server::{journal_server_conf, JournalServerConfig},
     metrics::register_prometheus_export,
     runtime::create_runtime,
 };
+use index::engine::IndexEngine;
+use journal_remote::build_remote_storage;
 use log::info;
+use replica::{
+    follower::start_follower_thread, manager::ReplicaManager, start_assignment_thread,
+    start_isr_check_thread, start_transaction_timeout_thread,
+};
 use server::start_tcp_server;
-use std::sync::Arc;
+use shard::{
+    compaction::{start_compaction_thread, Compactor},
+    manager::{start_fsync_thread, ShardManager},
+    segment::FsyncPolicy,
+};
+use std::{sync::Arc, time::Duration};
+use tiered::{manager::RemoteLogManager, start_offload_thread};
 use tokio::{runtime::Runtime, signal, sync::broadcast};
 
 mod cluster;
-mod index;
+pub mod index;
 mod network;
 mod raft;
-mod record;
+pub mod record;
+pub mod replica;
 mod server;
-mod shard;
+pub mod shard;
 mod storage;
+pub mod tiered;
 
 pub struct JournalServer {
     config: JournalServerConfig,
@@ -38,6 +58,12 @@ pub struct JournalServer {
     server_runtime: Runtime,
     daemon_runtime: Runtime,
     client_poll: Arc<ClientPool>,
+    shard_manager: Arc<ShardManager>,
+    replica_manager: Arc<ReplicaManager>,
+    // Set when tiered storage is enabled.
+    remote_log: Option<Arc<RemoteLogManager>>,
+    // Used by the follower replicas to fetch from the shard leaders.
+    replica_client: Arc<JournalClient>,
 }
 
 impl JournalServer {
@@ -48,6 +74,54 @@ impl JournalServer {
         let daemon_runtime = create_runtime("daemon-runtime", config.runtime_work_threads);
 
         let client_poll: Arc<ClientPool> = Arc::new(ClientPool::new(3));
+        let fsync_policy = FsyncPolicy::build(
+            &config.storage.fsync_policy,
+            config.storage.fsync_interval_ms,
+        );
+        let index_path = format!("{}/{}", config.data_path[0], "_index_rocksdb");
+        let index_engine = match IndexEngine::new(&index_path, config.rocksdb.max_open_files) {
+            Ok(engine) => Arc::new(engine),
+            Err(e) => {
+                panic!("Failed to open the index RocksDB {}, {}", index_path, e);
+            }
+        };
+        let shard_manager = Arc::new(ShardManager::new(
+            config.data_path.clone(),
+            fsync_policy,
+            config.storage.segment_max_bytes,
+            index_engine,
+        ));
+        let replica_manager = Arc::new(ReplicaManager::new(
+            config.node_id,
+            Duration::from_millis(config.replication.replica_lag_time_max_ms),
+            shard_manager.clone(),
+        ));
+        let remote_log = if config.tiered_storage.enable {
+            match build_remote_storage(&config.tiered_storage) {
+                Ok(storage) => Some(Arc::new(RemoteLogManager::new(
+                    config.cluster_name.clone(),
+                    config.tiered_storage.clone(),
+                    storage,
+                    shard_manager.clone(),
+                    replica_manager.clone(),
+                ))),
+                Err(e) => {
+                    panic!("Failed to build the remote storage of the journal, {}", e);
+                }
+            }
+        } else {
+            None
+        };
+        let replica_client = Arc::new(JournalClient::new(
+            JournalClientConfig {
+                cluster_name: config.cluster_name.clone(),
+                client_id: format!("replica-{}", config.node_id),
+                placement_center: config.placement_center.clone(),
+                replica_id: config.node_id,
+                ..Default::default()
+            },
+            client_poll.clone(),
+        ));
 
         return JournalServer {
             config,
@@ -55,10 +129,16 @@ impl JournalServer {
             server_runtime,
             daemon_runtime,
             client_poll,
+            shard_manager,
+            replica_manager,
+            remote_log,
+            replica_client,
         };
     }
 
     pub fn start(&self) {
+        self.load_shards();
+
         self.register_node();
 
         self.start_prometheus_export();
@@ -71,8 +151,11 @@ impl JournalServer {
     }
 
     fn start_tcp_server(&self) {
-        self.server_runtime.spawn(async {
-            start_tcp_server().await;
+        let shard_manager = self.shard_manager.clone();
+        let replica_manager = self.replica_manager.clone();
+        let remote_log = self.remote_log.clone();
+        self.server_runtime.spawn(async move {
+            start_tcp_server(shard_manager, replica_manager, remote_log).await;
         });
     }
 
@@ -86,8 +169,112 @@ impl JournalServer {
     fn start_daemon_thread(&self) {
         let config = self.config.clone();
         let client_poll = self.client_poll.clone();
+        let stop_send = self.stop_send.clone();
+        self.daemon_runtime
+            .spawn(async move { report_heartbeat(client_poll, config, stop_send).await });
+
+        let shard_manager = self.shard_manager.clone();
+        let stop_send = self.stop_send.clone();
+        self.daemon_runtime
+            .spawn(async move { start_fsync_thread(shard_manager, stop_send).await });
+
+        if let Some(remote_log) = self.remote_log.clone() {
+            let stop_send = self.stop_send.clone();
+            self.daemon_runtime
+                .spawn(async move { start_offload_thread(remote_log, stop_send).await });
+        }
+
+        let client_poll = self.client_poll.clone();
+        let config = self.config.clone();
+        let shard_manager = self.shard_manager.clone();
+        let stop_send = self.stop_send.clone();
+        self.daemon_runtime.spawn(async move {
+            report_monitor_thread(client_poll, config, shard_manager, stop_send).await
+        });
+
+        let client_poll = self.client_poll.clone();
+        let config = self.config.clone();
+        let shard_manager = self.shard_manager.clone();
+        let stop_send = self.stop_send.clone();
+        self.daemon_runtime.spawn(async move {
+            report_shard_status_thread(client_poll, config, shard_manager, stop_send).await
+        });
+
+        let replica_manager = self.replica_manager.clone();
+        let shard_manager = self.shard_manager.clone();
+        let client_poll = self.client_poll.clone();
+        let config = self.config.clone();
+        let stop_send = self.stop_send.clone();
+        self.daemon_runtime.spawn(async move {
+            start_assignment_thread(
+                replica_manager,
+                shard_manager,
+                client_poll,
+                config,
+                stop_send,
+            )
+            .await
+        });
+
+        let compactor = Arc::new(Compactor::new(
+            self.shard_manager.clone(),
+            self.config.storage.tombstone_retention_ms,
+        ));
+        let interval_ms = self.config.storage.compaction_interval_ms;
+        let stop_send = self.stop_send.clone();
         self.daemon_runtime
-            .spawn(async move { report_heartbeat(client_poll, config).await });
+            .spawn(async move { start_compaction_thread(compactor, interval_ms, stop_send).await });
+
+        let replica_manager = self.replica_manager.clone();
+        let lag_time_max_ms = self.config.replication.replica_lag_time_max_ms;
+        let stop_send = self.stop_send.clone();
+        self.daemon_runtime.spawn(async move {
+            start_isr_check_thread(replica_manager, lag_time_max_ms, stop_send).await
+        });
+
+        let transaction_timeout_ms = self.config.storage.transaction_timeout_ms;
+        if transaction_timeout_ms > 0 {
+            let replica_manager = self.replica_manager.clone();
+            let shard_manager = self.shard_manager.clone();
+            let stop_send = self.stop_send.clone();
+            self.daemon_runtime.spawn(async move {
+                start_transaction_timeout_thread(
+                    replica_manager,
+                    shard_manager,
+                    transaction_timeout_ms,
+                    stop_send,
+                )
+                .await
+            });
+        }
+
+        let replica_manager = self.replica_manager.clone();
+        let shard_manager = self.shard_manager.clone();
+        let replica_client = self.replica_client.clone();
+        let fetch_max_wait_ms = self.config.replication.fetch_max_wait_ms;
+        let stop_send = self.stop_send.clone();
+        self.daemon_runtime.spawn(async move {
+            start_follower_thread(
+                replica_manager,
+                shard_manager,
+                replica_client,
+                fetch_max_wait_ms,
+                stop_send,
+            )
+            .await
+        });
+    }
+
+    fn load_shards(&self) {
+        match self.shard_manager.load() {
+            Ok(()) => {}
+            Err(e) => {
+                panic!(
+                    "Failed to load shards from the data path, {}",
+                    e.to_string()
+                );
+            }
+        }
     }
 
     fn waiting_stop(&self) {
@@ -11,29 +11,42 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::{response::build_produce_resp, services::Services};
-
+use super::{
+    response::{build_end_txn_resp, build_fetch_resp, build_metadata_resp, build_produce_resp},
+    services::Services,
+};
 use log::error;
-use protocol::journal_server::codec::StorageEnginePacket;
+use protocol::journal_server::{codec::StorageEnginePacket, generate::protocol::header::Header};
+use std::sync::Arc;
 
 pub struct Command {
     packet: StorageEnginePacket,
-    services: Services,
+    services: Arc<Services>,
 }
 
 impl Command {
-    pub fn new(packet: StorageEnginePacket) -> Self {
-        let services = Services::new();
+    pub fn new(packet: StorageEnginePacket, services: Arc<Services>) -> Self {
         return Command { packet, services };
     }
 
-    pub fn apply(&self) -> StorageEnginePacket {
+    // Handles a request packet, returns None for packets that have no response.
+    pub async fn apply(&self) -> Option<StorageEnginePacket> {
         match self.packet.clone() {
             StorageEnginePacket::ProduceReq(data) => {
-                self.services.produce(data);
+                let body = self.services.produce(data.body.unwrap_or_default()).await;
+                return Some(build_produce_resp(correlation_id(&data.header), body));
             }
             StorageEnginePacket::FetchReq(data) => {
-                self.services.fetch(data);
+                let body = self.services.fetch(data.body.unwrap_or_default()).await;
+                return Some(build_fetch_resp(correlation_id(&data.header), body));
+            }
+            StorageEnginePacket::MetadataReq(data) => {
+                let body = self.services.metadata(data.body.unwrap_or_default());
+                return Some(build_metadata_resp(correlation_id(&data.header), body));
+            }
+            StorageEnginePacket::EndTxnReq(data) => {
+                let body = self.services.end_txn(data.body.unwrap_or_default()).await;
+                return Some(build_end_txn_resp(correlation_id(&data.header), body));
             }
             _ => {
                 error!(
@@ -42,6 +55,15 @@ impl Command {
                 );
             }
         }
-        return build_produce_resp();
+        return None;
+    }
+}
+
+fn correlation_id(header: &Option<Header>) -> u32 {
+    if let Some(header) = header {
+        if let Some(request) = &header.request {
+            return request.correlation_id;
+        }
     }
+    return 0;
 }
@@ -14,30 +14,56 @@
 use protocol::journal_server::{
     codec::StorageEnginePacket,
     generate::protocol::{
-        header::{ApiKey, ApiType, ApiVersion, Header, RequestCommon},
+        end_txn::{EndTxnResp, EndTxnRespBody},
+        fetch::{FetchResp, FetchRespBody},
+        header::{ApiKey, ApiType, ApiVersion, ErrorCode, Header, ResponseCommon},
+        metadata::{MetadataResp, MetadataRespBody},
         produce::{ProduceResp, ProduceRespBody},
     },
 };
 
-pub fn build_produce_resp() -> StorageEnginePacket {
-    let header = build_req_header(33, "client-id-fsfsdff".to_string());
-    let body = ProduceRespBody {};
-    let req = ProduceResp {
-        header: Some(header),
+pub fn build_produce_resp(correlation_id: u32, body: ProduceRespBody) -> StorageEnginePacket {
+    let resp = ProduceResp {
+        header: Some(build_resp_header(ApiKey::Produce, correlation_id)),
         body: Some(body),
     };
-    return StorageEnginePacket::ProduceResp(req);
+    return StorageEnginePacket::ProduceResp(resp);
 }
 
-fn build_req_header(correlation_id: u32, client_id: String) -> Header {
+pub fn build_fetch_resp(correlation_id: u32, body: FetchRespBody) -> StorageEnginePacket {
+    let resp = FetchResp {
+        header: Some(build_resp_header(ApiKey::Consume, correlation_id)),
+        body: Some(body),
+    };
+    return StorageEnginePacket::FetchResp(resp);
+}
+
+pub fn build_metadata_resp(correlation_id: u32, body: MetadataRespBody) -> StorageEnginePacket {
+    let resp = MetadataResp {
+        header: Some(build_resp_header(ApiKey::Metadata, correlation_id)),
+        body: Some(body),
+    };
+    return StorageEnginePacket::MetadataResp(resp);
+}
+
+pub fn build_end_txn_resp(correlation_id: u32, body: EndTxnRespBody) -> StorageEnginePacket {
+    let resp = EndTxnResp {
+        header: Some(build_resp_header(ApiKey::EndTxn, correlation_id)),
+        body: Some(body),
+    };
+    return StorageEnginePacket::EndTxnResp(resp);
+}
+
+fn build_resp_header(api_key: ApiKey, correlation_id: u32) -> Header {
     return Header {
-        api_key: ApiKey::Produce.into(),
+        api_key: api_key.into(),
         api_type: ApiType::Response.into(),
         api_version: ApiVersion::V0.into(),
-        request: Some(RequestCommon {
-            correlation_id: correlation_id,
-            client_id: client_id,
+        request: None,
+        response: Some(ResponseCommon {
+            correlation_id,
+            error_code: ErrorCode::Success.into(),
+            error_message: "".to_string(),
         }),
-        response: None,
     };
 }
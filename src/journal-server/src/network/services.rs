@@ -11,16 +11,956 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use protocol::journal_server::generate::protocol::{fetch::FetchReq, produce::ProduceReq};
+use crate::{
+    record::{
+        header::Header,
+        record::{Record, RecordControl},
+    },
+    replica::manager::ReplicaManager,
+    shard::{
+        manager::{lock_shard, ShardManager},
+        producer_state::ProducerAppend,
+    },
+    tiered::manager::RemoteLogManager,
+};
+use bytes::Bytes;
+use common_base::{
+    config::journal_server::journal_server_conf,
+    error::{common::CommonError, journal_server::JournalServerError},
+};
+use log::error;
+use protocol::journal_server::generate::protocol::{
+    end_txn::{EndTxnReqBody, EndTxnRespBody, ShardEndTxnResult},
+    fetch::{FetchReqBody, FetchRespBody, FetchShard, IsolationLevel, ShardFetchResult},
+    header::ErrorCode,
+    metadata::{MetadataReqBody, MetadataRespBody, NodeInfo, ShardMetadata},
+    produce::{Acks, ProduceReqBody, ProduceRespBody, ShardData, ShardProduceResult},
+    record::{ControlType, Record as ProtocolRecord, RecordHeader},
+};
+use std::{sync::Arc, time::Duration};
+use tokio::time::{timeout_at, Instant};
 
-pub struct Services {}
+const DEFAULT_FETCH_RECORD_NUM: usize = 100;
+const DEFAULT_PRODUCE_TIMEOUT_MS: u64 = 30000;
+
+pub struct Services {
+    shard_manager: Arc<ShardManager>,
+    replica_manager: Arc<ReplicaManager>,
+    // Set when tiered storage is enabled.
+    remote_log: Option<Arc<RemoteLogManager>>,
+}
 
 impl Services {
-    pub fn new() -> Self {
-        return Services {};
+    pub fn new(
+        shard_manager: Arc<ShardManager>,
+        replica_manager: Arc<ReplicaManager>,
+        remote_log: Option<Arc<RemoteLogManager>>,
+    ) -> Self {
+        return Services {
+            shard_manager,
+            replica_manager,
+            remote_log,
+        };
+    }
+
+    // Appends the records on the leader. With Acks::All the response waits until the
+    // records are on every in-sync replica, or fails with ReplicationTimeout after
+    // timeout_ms. Records of an idempotent producer are only written once however often
+    // the request is retried, and records with a transactional id are part of the
+    // ongoing transaction of the producer until it is ended by end_txn.
+    pub async fn produce(&self, body: ProduceReqBody) -> ProduceRespBody {
+        let mut shards = Vec::new();
+        for shard in body.shards {
+            let producer = ProducerAppend {
+                producer_id: body.producer_id,
+                producer_epoch: body.producer_epoch,
+                base_sequence: shard.base_sequence,
+                transactional: body.transactional_id > 0,
+            };
+            shards.push(self.produce_shard(shard, &producer));
+        }
+
+        if body.acks == Acks::All as i32 {
+            let deadline = request_deadline(body.timeout_ms);
+            for result in shards.iter_mut() {
+                let last_offset = match result.offsets.last() {
+                    Some(offset) if result.error_code == 0 => *offset,
+                    _ => continue,
+                };
+                if !self
+                    .replica_manager
+                    .wait_high_watermark(&result.shard_name, last_offset + 1, deadline)
+                    .await
+                {
+                    result.error_code = ErrorCode::ReplicationTimeout.into();
+                    result.error_message = replication_timeout(&result.shard_name);
+                }
+            }
+        }
+        return ProduceRespBody { shards };
     }
 
-    pub fn produce(&self, _: ProduceReq) {}
+    // Writes the commit or abort marker of the transaction of the producer to each shard,
+    // and waits until the markers are on every in-sync replica. The transaction becomes
+    // visible to consumers reading committed records once its marker is replicated.
+    pub async fn end_txn(&self, body: EndTxnReqBody) -> EndTxnRespBody {
+        let deadline = request_deadline(body.timeout_ms);
+        let mut shards = Vec::new();
+        for shard_name in body.shards.iter() {
+            let mut result = ShardEndTxnResult {
+                shard_name: shard_name.clone(),
+                ..Default::default()
+            };
+            if body.producer_id == 0 {
+                result.error_code = ErrorCode::InvalidRequest.into();
+                result.error_message = "a transaction needs a producer id".to_string();
+                shards.push(result);
+                continue;
+            }
+            if !self.replica_manager.is_leader(shard_name) {
+                result.error_code = ErrorCode::NotLeader.into();
+                result.error_message = format!("node is not the leader of shard {}", shard_name);
+                shards.push(result);
+                continue;
+            }
+            if self.shard_manager.get_shard(shard_name).is_none() {
+                result.error_code = ErrorCode::ShardNotFound.into();
+                result.error_message = format!("shard {} does not exist", shard_name);
+                shards.push(result);
+                continue;
+            }
+
+            match self.shard_manager.end_transaction(
+                shard_name,
+                body.producer_id,
+                body.producer_epoch,
+                body.commit,
+            ) {
+                Ok(Some(offset)) => {
+                    result.offset = offset;
+                    if let Err(e) = self.replica_manager.leader_appended(shard_name) {
+                        error!(
+                            "Failed to advance the high watermark of shard {}, {}",
+                            shard_name, e
+                        );
+                    }
+                    if !self
+                        .replica_manager
+                        .wait_high_watermark(shard_name, offset + 1, deadline)
+                        .await
+                    {
+                        result.error_code = ErrorCode::ReplicationTimeout.into();
+                        result.error_message = replication_timeout(shard_name);
+                    }
+                }
+                // The producer wrote nothing to the shard in the transaction, or the
+                // marker was written by an earlier attempt.
+                Ok(None) => {}
+                Err(e) => {
+                    result.error_code = error_code(&e).into();
+                    result.error_message = e.to_string();
+                }
+            }
+            shards.push(result);
+        }
+        return EndTxnRespBody { shards };
+    }
+
+    // Reads the requested shards, holding the request for up to max_wait_ms until at
+    // least one record is available. Fetches from follower replicas also report how far
+    // the follower has replicated.
+    pub async fn fetch(&self, body: FetchReqBody) -> FetchRespBody {
+        if body.replica_id > 0 {
+            for shard in body.shards.iter() {
+                if let Err(e) = self.replica_manager.update_follower_offset(
+                    &shard.shard_name,
+                    body.replica_id,
+                    shard.offset,
+                ) {
+                    error!(
+                        "Failed to update the offset of replica {} of shard {}, {}",
+                        body.replica_id, shard.shard_name, e
+                    );
+                }
+            }
+        }
+
+        let deadline = Instant::now() + Duration::from_millis(body.max_wait_ms as u64);
+        loop {
+            // Register for the notification before reading so that an append between the
+            // read and the wait is not missed.
+            let notified = self.shard_manager.wait_append();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let shards = self.fetch_once(&body).await;
+            // Records that were all filtered out still move the fetch forward.
+            let has_data = shards
+                .iter()
+                .zip(body.shards.iter())
+                .any(|(result, shard)| {
+                    !result.records.is_empty()
+                        || result.error_code != 0
+                        || result.next_fetch_offset > shard.offset
+                });
+            if has_data || Instant::now() >= deadline {
+                return FetchRespBody { shards };
+            }
+            if timeout_at(deadline, notified).await.is_err() {
+                return FetchRespBody {
+                    shards: self.fetch_once(&body).await,
+                };
+            }
+        }
+    }
+
+    pub fn metadata(&self, body: MetadataReqBody) -> MetadataRespBody {
+        let conf = journal_server_conf();
+        let mut nodes = Vec::new();
+        for (node_id, addr) in conf.nodes.iter() {
+            if let (Ok(node_id), Some(addr)) = (node_id.parse::<u64>(), addr.as_str()) {
+                nodes.push(NodeInfo {
+                    node_id,
+                    addr: addr.to_string(),
+                });
+            }
+        }
+        let leader_addr = match conf.nodes.get(&conf.node_id.to_string()) {
+            Some(addr) => addr.as_str().unwrap_or_default().to_string(),
+            None => "".to_string(),
+        };
+
+        let shard_names = if body.shards.is_empty() {
+            self.shard_manager.shard_names()
+        } else {
+            body.shards
+        };
+        let mut shards = Vec::new();
+        for shard_name in shard_names {
+            let mut metadata = ShardMetadata {
+                shard_name: shard_name.clone(),
+                ..Default::default()
+            };
+            match self.shard_manager.get_shard(&shard_name) {
+                Some(shard) => match lock_shard(&shard) {
+                    Ok(log) => {
+                        // A shard without a replica assignment is served by the node
+                        // holding it.
+                        match self.replica_manager.assignment(&shard_name) {
+                            Some(assignment) => {
+                                metadata.leader_id = assignment.leader_id;
+                                metadata.leader_addr = assignment.leader_addr;
+                            }
+                            None => {
+                                metadata.leader_id = conf.node_id;
+                                metadata.leader_addr = leader_addr.clone();
+                            }
+                        }
+                        metadata.start_offset = match self.remote_start_offset(&shard_name) {
+                            Some(remote_start_offset) => {
+                                remote_start_offset.min(log.start_offset())
+                            }
+                            None => log.start_offset(),
+                        };
+                        metadata.next_offset = log.next_offset();
+                    }
+                    Err(_) => {
+                        metadata.error_code = ErrorCode::UnknownError.into();
+                    }
+                },
+                None => {
+                    metadata.error_code = ErrorCode::ShardNotFound.into();
+                }
+            }
+            shards.push(metadata);
+        }
+        return MetadataRespBody { nodes, shards };
+    }
+
+    fn produce_shard(&self, shard: ShardData, producer: &ProducerAppend) -> ShardProduceResult {
+        let mut result = ShardProduceResult {
+            shard_name: shard.shard_name.clone(),
+            ..Default::default()
+        };
+        if shard.shard_name.is_empty() || shard.records.is_empty() {
+            result.error_code = ErrorCode::InvalidRequest.into();
+            result.error_message = "shard name and records cannot be empty".to_string();
+            return result;
+        }
+        if producer.transactional && producer.producer_id == 0 {
+            result.error_code = ErrorCode::InvalidRequest.into();
+            result.error_message = "transactional records need a producer id".to_string();
+            return result;
+        }
+        if !self.replica_manager.is_leader(&shard.shard_name) {
+            result.error_code = ErrorCode::NotLeader.into();
+            result.error_message = format!("node is not the leader of shard {}", shard.shard_name);
+            return result;
+        }
+
+        // The values are compressed by the leader, the followers copy them as they are.
+        let config = self.shard_manager.shard_config(&shard.shard_name);
+        let mut records = Vec::with_capacity(shard.records.len());
+        for record in shard.records {
+            let mut record = produced_record(record);
+            if let Err(e) = record.compress(&config.compression, config.compression_min_size) {
+                result.error_code = ErrorCode::UnknownError.into();
+                result.error_message = e.to_string();
+                return result;
+            }
+            records.push(record);
+        }
+        let appended = if producer.producer_id > 0 {
+            self.shard_manager
+                .append_idempotent(&shard.shard_name, producer, records)
+        } else {
+            self.shard_manager.append(&shard.shard_name, records)
+        };
+        match appended {
+            Ok(offsets) => {
+                result.offsets = offsets;
+                if let Err(e) = self.replica_manager.leader_appended(&shard.shard_name) {
+                    error!(
+                        "Failed to advance the high watermark of shard {}, {}",
+                        shard.shard_name, e
+                    );
+                }
+            }
+            Err(e) => {
+                result.error_code = error_code(&e).into();
+                result.error_message = e.to_string();
+            }
+        }
+        return result;
+    }
+
+    async fn fetch_once(&self, body: &FetchReqBody) -> Vec<ShardFetchResult> {
+        let mut results = Vec::new();
+        let mut remaining_bytes = if body.max_bytes > 0 {
+            Some(body.max_bytes)
+        } else {
+            None
+        };
+        for shard in body.shards.iter() {
+            let result = self.fetch_shard(shard, body, remaining_bytes).await;
+            if let Some(remaining) = remaining_bytes {
+                let size: u64 = result.records.iter().map(|r| r.value.len() as u64).sum();
+                remaining_bytes = Some(remaining.saturating_sub(size));
+            }
+            results.push(result);
+        }
+        return results;
+    }
+
+    // remaining_bytes is what is left of the max bytes of the whole request, if it is set.
+    // Consumers only read the records below the high watermark, or below the last stable
+    // offset when they read committed records, followers read up to the end of the log.
+    // Consumers do not get the transaction markers, nor the records of aborted
+    // transactions when they read committed records. Consumers get the values
+    // decompressed, followers get them as they are stored.
+    async fn fetch_shard(
+        &self,
+        shard: &FetchShard,
+        body: &FetchReqBody,
+        remaining_bytes: Option<u64>,
+    ) -> ShardFetchResult {
+        let replica_id = body.replica_id;
+        let read_committed = body.isolation_level == IsolationLevel::ReadCommitted as i32;
+        let mut result = ShardFetchResult {
+            shard_name: shard.shard_name.clone(),
+            ..Default::default()
+        };
+        if !self.replica_manager.is_leader(&shard.shard_name) {
+            result.error_code = ErrorCode::NotLeader.into();
+            result.error_message = format!("node is not the leader of shard {}", shard.shard_name);
+            return result;
+        }
+        let shard_log = match self.shard_manager.get_shard(&shard.shard_name) {
+            Some(shard_log) => shard_log,
+            None => {
+                result.error_code = ErrorCode::ShardNotFound.into();
+                result.error_message = format!("shard {} does not exist", shard.shard_name);
+                return result;
+            }
+        };
+        let local_start_offset = {
+            let log = match lock_shard(&shard_log) {
+                Ok(log) => log,
+                Err(e) => {
+                    result.error_code = ErrorCode::UnknownError.into();
+                    result.error_message = e.to_string();
+                    return result;
+                }
+            };
+            result.next_offset = log.next_offset();
+            result.high_watermark = log.high_watermark();
+            result.last_stable_offset = log.last_stable_offset();
+            log.start_offset()
+        };
+        result.start_offset = match self.remote_start_offset(&shard.shard_name) {
+            Some(remote_start_offset) => remote_start_offset.min(local_start_offset),
+            None => local_start_offset,
+        };
+        if shard.offset < result.start_offset || shard.offset > result.next_offset {
+            result.error_code = ErrorCode::OffsetOutOfRange.into();
+            result.error_message = format!(
+                "offset {} is out of range [{}, {}]",
+                shard.offset, result.start_offset, result.next_offset
+            );
+            return result;
+        }
+
+        result.next_fetch_offset = shard.offset;
+        let end_offset = if replica_id > 0 {
+            result.next_offset
+        } else if read_committed {
+            result.last_stable_offset
+        } else {
+            result.high_watermark
+        };
+        let max_record = if shard.max_record_num == 0 {
+            DEFAULT_FETCH_RECORD_NUM
+        } else {
+            shard.max_record_num as usize
+        };
+        let max_record = max_record.min(end_offset.saturating_sub(shard.offset) as usize);
+        if max_record == 0 {
+            return result;
+        }
+        let max_bytes = match (shard.max_bytes, remaining_bytes) {
+            (_, Some(0)) => return result,
+            (0, Some(remaining)) => remaining,
+            (max, Some(remaining)) => max.min(remaining),
+            (max, None) => max,
+        };
 
-    pub fn fetch(&self, _: FetchReq) {}
+        // The local segments may have been removed since the start offset was read, the
+        // records below the local log are read from the remote tier without holding the
+        // shard lock.
+        let local_records = match lock_shard(&shard_log) {
+            Ok(log) if shard.offset >= log.start_offset() => {
+                Some(log.read(shard.offset, max_record, max_bytes))
+            }
+            Ok(_) => None,
+            Err(e) => Some(Err(e)),
+        };
+        let records = match local_records {
+            Some(records) => records,
+            None => {
+                let remote_log = self.remote_log.as_ref();
+                let segment = remote_log.and_then(|remote_log| {
+                    remote_log.find_segment(&shard.shard_name, shard.offset)
+                });
+                match (remote_log, segment) {
+                    (Some(remote_log), Some(segment)) => {
+                        remote_log
+                            .read(
+                                &shard.shard_name,
+                                &segment,
+                                shard.offset,
+                                max_record,
+                                max_bytes,
+                            )
+                            .await
+                    }
+                    _ => {
+                        result.error_code = ErrorCode::OffsetOutOfRange.into();
+                        result.error_message = format!(
+                            "offset {} is neither in the local log nor on the remote tier",
+                            shard.offset
+                        );
+                        return result;
+                    }
+                }
+            }
+        };
+        match records {
+            Ok(records) => {
+                if let Some(record) = records.last() {
+                    result.next_fetch_offset = record.offset + 1;
+                }
+                let records = if replica_id > 0 {
+                    records
+                } else {
+                    let records = self.consumer_records(&shard.shard_name, records, read_committed);
+                    match decompress_records(records) {
+                        Ok(records) => records,
+                        Err(e) => {
+                            result.error_code = ErrorCode::UnknownError.into();
+                            result.error_message = e.to_string();
+                            return result;
+                        }
+                    }
+                };
+                result.records = records.into_iter().map(record_to_protocol).collect();
+            }
+            Err(e) => {
+                result.error_code = ErrorCode::UnknownError.into();
+                result.error_message = e.to_string();
+            }
+        }
+        return result;
+    }
+
+    fn consumer_records(
+        &self,
+        shard_name: &String,
+        records: Vec<Record>,
+        read_committed: bool,
+    ) -> Vec<Record> {
+        let aborted = match (read_committed, records.first(), records.last()) {
+            (true, Some(first), Some(last)) => {
+                match self.shard_manager.aborted_transactions(
+                    shard_name,
+                    first.offset,
+                    last.offset + 1,
+                ) {
+                    Ok(aborted) => aborted,
+                    Err(e) => {
+                        error!(
+                            "Failed to read the aborted transactions of shard {}, {}",
+                            shard_name, e
+                        );
+                        Vec::new()
+                    }
+                }
+            }
+            _ => Vec::new(),
+        };
+        return records
+            .into_iter()
+            .filter(|record| {
+                !record.is_control()
+                    && !aborted.iter().any(|txn| {
+                        txn.producer_id == record.producer_id
+                            && record.offset >= txn.first_offset
+                            && record.offset <= txn.last_offset
+                    })
+            })
+            .collect();
+    }
+
+    fn remote_start_offset(&self, shard_name: &str) -> Option<u64> {
+        if let Some(remote_log) = &self.remote_log {
+            return remote_log.start_offset(shard_name);
+        }
+        return None;
+    }
+}
+
+fn request_deadline(timeout_ms: u32) -> Instant {
+    let timeout_ms = if timeout_ms == 0 {
+        DEFAULT_PRODUCE_TIMEOUT_MS
+    } else {
+        timeout_ms as u64
+    };
+    return Instant::now() + Duration::from_millis(timeout_ms);
+}
+
+fn replication_timeout(shard_name: &str) -> String {
+    return format!(
+        "records of shard {} were not replicated to the ISR within the timeout",
+        shard_name
+    );
+}
+
+fn error_code(e: &CommonError) -> ErrorCode {
+    match e {
+        CommonError::JournalServerError(JournalServerError::OutOfOrderSequence(..)) => {
+            return ErrorCode::OutOfOrderSequence;
+        }
+        CommonError::JournalServerError(JournalServerError::ProducerFenced(..)) => {
+            return ErrorCode::ProducerFenced;
+        }
+        CommonError::JournalServerError(JournalServerError::InvalidTransaction(..)) => {
+            return ErrorCode::InvalidTransaction;
+        }
+        _ => return ErrorCode::UnknownError,
+    }
+}
+
+// Builds a record sent by a producer, the producer fields are set by the server.
+fn produced_record(record: ProtocolRecord) -> Record {
+    let headers = record
+        .headers
+        .into_iter()
+        .map(|h| Header::new(h.key, Bytes::from(h.value)))
+        .collect();
+    let mut result = Record::build(Bytes::from(record.key), Bytes::from(record.value), headers);
+    result.timestamp = record.timestamp;
+    return result;
+}
+
+fn decompress_records(records: Vec<Record>) -> Result<Vec<Record>, CommonError> {
+    let mut results = Vec::with_capacity(records.len());
+    for mut record in records {
+        record.decompress()?;
+        results.push(record);
+    }
+    return Ok(results);
+}
+
+// Builds a record fetched from the leader as the leader wrote it.
+pub(crate) fn protocol_to_record(record: ProtocolRecord) -> Record {
+    let producer_id = record.producer_id;
+    let producer_epoch = record.producer_epoch;
+    let sequence = record.sequence;
+    let is_transactional = record.transactional;
+    let is_compressed = record.compressed;
+    let control = match ControlType::try_from(record.control) {
+        Ok(ControlType::Commit) => RecordControl::Commit,
+        Ok(ControlType::Abort) => RecordControl::Abort,
+        _ => RecordControl::None,
+    };
+    let offset = record.offset;
+    let mut result = produced_record(record);
+    result.offset = offset;
+    result.producer_id = producer_id;
+    result.producer_epoch = producer_epoch;
+    result.sequence = sequence;
+    result.is_transactional = is_transactional;
+    result.control = control;
+    result.is_compressed = is_compressed;
+    return result;
+}
+
+fn record_to_protocol(record: Record) -> ProtocolRecord {
+    let control = match record.control {
+        RecordControl::None => ControlType::None,
+        RecordControl::Commit => ControlType::Commit,
+        RecordControl::Abort => ControlType::Abort,
+    };
+    return ProtocolRecord {
+        offset: record.offset,
+        timestamp: record.timestamp,
+        key: record.key.to_vec(),
+        value: record.value.to_vec(),
+        headers: record
+            .headers
+            .into_iter()
+            .map(|h| RecordHeader {
+                key: h.key,
+                value: h.value.to_vec(),
+            })
+            .collect(),
+        producer_id: record.producer_id,
+        producer_epoch: record.producer_epoch,
+        sequence: record.sequence,
+        transactional: record.is_transactional,
+        control: control.into(),
+        compressed: record.is_compressed,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Services;
+    use crate::{
+        index::engine::IndexEngine,
+        replica::manager::{ReplicaManager, ShardAssignment},
+        shard::{
+            manager::{ShardConfig, ShardManager},
+            segment::FsyncPolicy,
+        },
+    };
+    use protocol::journal_server::generate::protocol::{
+        end_txn::EndTxnReqBody,
+        fetch::{FetchReqBody, FetchShard, IsolationLevel},
+        header::ErrorCode,
+        produce::{Acks, ProduceReqBody, ShardData},
+        record::Record,
+    };
+    use std::{fs, sync::Arc, time::Duration};
+    use storage_adapter::compression::CompressionType;
+    use tokio::time::{sleep, Instant};
+
+    fn build_services(name: &str) -> Arc<Services> {
+        let dir = std::env::temp_dir().join(format!("robustmq-journal-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let engine =
+            IndexEngine::new(&dir.join("_index_rocksdb").display().to_string(), None).unwrap();
+        let shard_manager = ShardManager::new(
+            vec![dir.display().to_string()],
+            FsyncPolicy::Os,
+            0,
+            Arc::new(engine),
+        );
+        let shard_manager = Arc::new(shard_manager);
+        let replica_manager = Arc::new(ReplicaManager::new(
+            1,
+            Duration::from_secs(60),
+            shard_manager.clone(),
+        ));
+        return Arc::new(Services::new(shard_manager, replica_manager, None));
+    }
+
+    fn produce_body(shard_name: &str, num: u64) -> ProduceReqBody {
+        let records = (0..num)
+            .map(|i| Record {
+                value: format!("v{}", i).into_bytes(),
+                ..Default::default()
+            })
+            .collect();
+        return ProduceReqBody {
+            shards: vec![ShardData {
+                shard_name: shard_name.to_string(),
+                records,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+    }
+
+    fn fetch_body(shard_name: &str, offset: u64, max_wait_ms: u32) -> FetchReqBody {
+        return FetchReqBody {
+            max_wait_ms,
+            shards: vec![FetchShard {
+                shard_name: shard_name.to_string(),
+                offset,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+    }
+
+    #[tokio::test]
+    async fn produce_fetch() {
+        let services = build_services("services-produce-fetch");
+        let resp = services.produce(produce_body("s1", 3)).await;
+        assert_eq!(resp.shards[0].offsets, vec![0, 1, 2]);
+
+        let resp = services.fetch(fetch_body("s1", 1, 0)).await;
+        let shard = &resp.shards[0];
+        assert_eq!(shard.error_code, ErrorCode::Success as i32);
+        assert_eq!(shard.next_offset, 3);
+        let offsets: Vec<u64> = shard.records.iter().map(|r| r.offset).collect();
+        assert_eq!(offsets, vec![1, 2]);
+        assert_eq!(shard.records[0].value, b"v1".to_vec());
+
+        let resp = services.fetch(fetch_body("s1", 4, 0)).await;
+        assert_eq!(
+            resp.shards[0].error_code,
+            ErrorCode::OffsetOutOfRange as i32
+        );
+        let resp = services.fetch(fetch_body("s2", 0, 0)).await;
+        assert_eq!(resp.shards[0].error_code, ErrorCode::ShardNotFound as i32);
+    }
+
+    #[tokio::test]
+    async fn produce_fetch_compressed() {
+        let services = build_services("services-compressed");
+        services.shard_manager.set_shard_config(
+            "s1",
+            ShardConfig {
+                compression: CompressionType::Lz4,
+                compression_min_size: 16,
+                ..Default::default()
+            },
+        );
+        let value = "robustmq".repeat(64).into_bytes();
+        let mut body = produce_body("s1", 1);
+        body.shards[0].records.push(Record {
+            value: value.clone(),
+            ..Default::default()
+        });
+        let resp = services.produce(body).await;
+        assert_eq!(resp.shards[0].offsets, vec![0, 1]);
+
+        // Only the large value is stored compressed, consumers get it decompressed.
+        let resp = services.fetch(fetch_body("s1", 0, 0)).await;
+        let records = &resp.shards[0].records;
+        assert!(!records[1].compressed);
+        assert_eq!(records[0].value, b"v0".to_vec());
+        assert_eq!(records[1].value, value);
+
+        // Followers copy the compressed value.
+        let mut follower_fetch = fetch_body("s1", 0, 0);
+        follower_fetch.replica_id = 2;
+        let resp = services.fetch(follower_fetch).await;
+        let records = &resp.shards[0].records;
+        assert!(!records[0].compressed);
+        assert!(records[1].compressed);
+        assert!(records[1].value.len() < value.len());
+    }
+
+    #[tokio::test]
+    async fn fetch_long_poll() {
+        let services = build_services("services-long-poll");
+        services.produce(produce_body("s1", 1)).await;
+
+        // Nothing new arrives, the fetch returns empty after the wait.
+        let start = Instant::now();
+        let resp = services.fetch(fetch_body("s1", 1, 100)).await;
+        assert!(resp.shards[0].records.is_empty());
+        assert!(start.elapsed() >= Duration::from_millis(100));
+
+        // A produce during the wait completes the fetch.
+        let producer = services.clone();
+        tokio::spawn(async move {
+            sleep(Duration::from_millis(50)).await;
+            producer.produce(produce_body("s1", 1)).await;
+        });
+        let start = Instant::now();
+        let resp = services.fetch(fetch_body("s1", 1, 5000)).await;
+        assert_eq!(resp.shards[0].records.len(), 1);
+        assert!(start.elapsed() < Duration::from_millis(5000));
+    }
+
+    #[tokio::test]
+    async fn produce_acks_all_waits_for_isr() {
+        let services = build_services("services-acks-all");
+        services.produce(produce_body("s1", 1)).await;
+        services.replica_manager.set_assignment(
+            "s1",
+            ShardAssignment {
+                leader_id: 1,
+                leader_addr: "127.0.0.1:2228".to_string(),
+                replicas: vec![1, 2],
+                next_replicas: Vec::new(),
+            },
+        );
+
+        // The follower has not fetched, the records stay above the high watermark.
+        let mut body = produce_body("s1", 2);
+        body.acks = Acks::All.into();
+        body.timeout_ms = 100;
+        let resp = services.produce(body.clone()).await;
+        assert_eq!(
+            resp.shards[0].error_code,
+            ErrorCode::ReplicationTimeout as i32
+        );
+        let resp = services.fetch(fetch_body("s1", 1, 0)).await;
+        assert!(resp.shards[0].records.is_empty());
+        assert_eq!(resp.shards[0].high_watermark, 1);
+
+        // The follower reads up to the end of the log, and its next fetch moves the high
+        // watermark, which completes the produce waiting for it.
+        let mut follower_fetch = fetch_body("s1", 1, 0);
+        follower_fetch.replica_id = 2;
+        let resp = services.fetch(follower_fetch).await;
+        assert_eq!(resp.shards[0].records.len(), 2);
+
+        let follower = services.clone();
+        tokio::spawn(async move {
+            sleep(Duration::from_millis(50)).await;
+            let mut follower_fetch = fetch_body("s1", 5, 0);
+            follower_fetch.replica_id = 2;
+            follower.fetch(follower_fetch).await;
+        });
+        body.timeout_ms = 5000;
+        let resp = services.produce(body).await;
+        assert_eq!(resp.shards[0].error_code, ErrorCode::Success as i32);
+        assert_eq!(resp.shards[0].offsets, vec![3, 4]);
+        let resp = services.fetch(fetch_body("s1", 1, 0)).await;
+        assert_eq!(resp.shards[0].records.len(), 4);
+
+        services.replica_manager.set_assignment(
+            "s1",
+            ShardAssignment {
+                leader_id: 2,
+                leader_addr: "127.0.0.1:3228".to_string(),
+                replicas: vec![1, 2],
+                next_replicas: Vec::new(),
+            },
+        );
+        let resp = services.produce(produce_body("s1", 1)).await;
+        assert_eq!(resp.shards[0].error_code, ErrorCode::NotLeader as i32);
+    }
+
+    #[tokio::test]
+    async fn produce_idempotent_retry() {
+        let services = build_services("services-idempotent");
+        let mut body = produce_body("s1", 3);
+        body.producer_id = 7;
+        let resp = services.produce(body.clone()).await;
+        assert_eq!(resp.shards[0].offsets, vec![0, 1, 2]);
+
+        // The retry is answered with the offsets of the records written the first time.
+        let resp = services.produce(body.clone()).await;
+        assert_eq!(resp.shards[0].error_code, ErrorCode::Success as i32);
+        assert_eq!(resp.shards[0].offsets, vec![0, 1, 2]);
+
+        body.shards[0].base_sequence = 5;
+        let resp = services.produce(body.clone()).await;
+        assert_eq!(
+            resp.shards[0].error_code,
+            ErrorCode::OutOfOrderSequence as i32
+        );
+        body.shards[0].base_sequence = 3;
+        let resp = services.produce(body.clone()).await;
+        assert_eq!(resp.shards[0].offsets, vec![3, 4, 5]);
+
+        // A new epoch fences the producer instances of the older one.
+        body.producer_epoch = 1;
+        body.shards[0].base_sequence = 0;
+        let resp = services.produce(body.clone()).await;
+        assert_eq!(resp.shards[0].offsets, vec![6, 7, 8]);
+        body.producer_epoch = 0;
+        body.shards[0].base_sequence = 6;
+        let resp = services.produce(body).await;
+        assert_eq!(resp.shards[0].error_code, ErrorCode::ProducerFenced as i32);
+
+        let resp = services.fetch(fetch_body("s1", 0, 0)).await;
+        assert_eq!(resp.shards[0].records.len(), 9);
+        assert_eq!(resp.shards[0].records[8].producer_id, 7);
+        assert_eq!(resp.shards[0].records[8].sequence, 2);
+    }
+
+    #[tokio::test]
+    async fn transaction_read_committed() {
+        let services = build_services("services-txn");
+        let mut body = produce_body("s1", 2);
+        body.producer_id = 7;
+        body.transactional_id = 1;
+        services.produce(body.clone()).await;
+        services.produce(produce_body("s1", 1)).await;
+
+        let mut committed_fetch = fetch_body("s1", 0, 0);
+        committed_fetch.isolation_level = IsolationLevel::ReadCommitted.into();
+        let resp = services.fetch(committed_fetch.clone()).await;
+        assert!(resp.shards[0].records.is_empty());
+        assert_eq!(resp.shards[0].last_stable_offset, 0);
+        let resp = services.fetch(fetch_body("s1", 0, 0)).await;
+        assert_eq!(resp.shards[0].records.len(), 3);
+
+        let mut end = EndTxnReqBody {
+            producer_id: 7,
+            transactional_id: 1,
+            commit: false,
+            shards: vec!["s1".to_string()],
+            ..Default::default()
+        };
+        let resp = services.end_txn(end.clone()).await;
+        assert_eq!(resp.shards[0].error_code, ErrorCode::Success as i32);
+        assert_eq!(resp.shards[0].offset, 3);
+        // Ending it again writes nothing.
+        let resp = services.end_txn(end.clone()).await;
+        assert_eq!(resp.shards[0].error_code, ErrorCode::Success as i32);
+        assert_eq!(resp.shards[0].offset, 0);
+
+        // The aborted records and the marker are skipped.
+        let resp = services.fetch(committed_fetch.clone()).await;
+        let shard = &resp.shards[0];
+        assert_eq!(shard.last_stable_offset, 4);
+        assert_eq!(shard.next_fetch_offset, 4);
+        let offsets: Vec<u64> = shard.records.iter().map(|r| r.offset).collect();
+        assert_eq!(offsets, vec![2]);
+
+        body.transactional_id = 2;
+        body.shards[0].base_sequence = 2;
+        let resp = services.produce(body).await;
+        assert_eq!(resp.shards[0].offsets, vec![4, 5]);
+        end.transactional_id = 2;
+        end.commit = true;
+        let resp = services.end_txn(end).await;
+        assert_eq!(resp.shards[0].offset, 6);
+
+        committed_fetch.shards[0].offset = 4;
+        let resp = services.fetch(committed_fetch).await;
+        let shard = &resp.shards[0];
+        assert_eq!(shard.next_fetch_offset, 7);
+        let offsets: Vec<u64> = shard.records.iter().map(|r| r.offset).collect();
+        assert_eq!(offsets, vec![4, 5]);
+    }
 }
@@ -14,13 +14,13 @@
 
 use bytes::Bytes;
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Header {
     pub key: String,
     pub value: Bytes,
 }
 
 impl Header {
-    #[allow(dead_code)]
     pub fn new(key: String, value: Bytes) -> Self {
         Header { key, value }
     }
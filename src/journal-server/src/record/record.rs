@@ -12,14 +12,37 @@
 // limitations under the License.
 
 
-use bytes::Bytes;
+use bytes::{Buf, BufMut, Bytes};
+use common_base::error::{common::CommonError, journal_server::JournalServerError};
+use storage_adapter::compression::{compress, decompress, CompressionType};
 
 use super::header::Header;
 
-#[allow(dead_code)]
+const ATTRIBUTE_COMPRESSED: u8 = 0x01;
+const ATTRIBUTE_PRODUCER: u8 = 0x02;
+const ATTRIBUTE_TRANSACTIONAL: u8 = 0x04;
+const ATTRIBUTE_CONTROL_COMMIT: u8 = 0x08;
+const ATTRIBUTE_CONTROL_ABORT: u8 = 0x10;
+
+// A control record ends the transaction of its producer on the shard. It is written by
+// the server and carries no key or value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RecordControl {
+    #[default]
+    None,
+    Commit,
+    Abort,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct Record {
     pub offset: u64,
+    // The producer fields are only set for records written by an idempotent producer.
+    pub producer_id: u64,
+    pub producer_epoch: u32,
     pub sequence: u32,
+    pub is_transactional: bool,
+    pub control: RecordControl,
     pub timestamp: u64,
     pub size: u32,
     pub is_compressed: bool,
@@ -30,3 +53,313 @@ pub struct Record {
     pub value_size: u32,
     pub value: Bytes,
 }
+
+impl Record {
+    pub fn build(key: Bytes, value: Bytes, headers: Vec<Header>) -> Self {
+        return Record {
+            has_header: !headers.is_empty(),
+            headers,
+            key_size: key.len() as u32,
+            key,
+            value_size: value.len() as u32,
+            value,
+            ..Default::default()
+        };
+    }
+
+    // Builds the control record ending the transaction of the producer.
+    pub fn build_control(producer_id: u64, producer_epoch: u32, control: RecordControl) -> Self {
+        return Record {
+            producer_id,
+            producer_epoch,
+            is_transactional: true,
+            control,
+            ..Default::default()
+        };
+    }
+
+    pub fn is_control(&self) -> bool {
+        return self.control != RecordControl::None;
+    }
+
+    // Compresses the value when it is at least min_size bytes and gets smaller. A compressed
+    // value starts with the id of its compression type.
+    pub fn compress(
+        &mut self,
+        compression: &CompressionType,
+        min_size: u64,
+    ) -> Result<(), CommonError> {
+        if self.is_compressed
+            || *compression == CompressionType::None
+            || (self.value.len() as u64) < min_size
+        {
+            return Ok(());
+        }
+        let data = compress(compression, &self.value)?;
+        if data.len() + 1 >= self.value.len() {
+            return Ok(());
+        }
+        let mut value = Vec::with_capacity(data.len() + 1);
+        value.put_u8(compression_id(compression));
+        value.put_slice(&data);
+        self.value_size = value.len() as u32;
+        self.value = Bytes::from(value);
+        self.is_compressed = true;
+        return Ok(());
+    }
+
+    pub fn decompress(&mut self) -> Result<(), CommonError> {
+        if !self.is_compressed {
+            return Ok(());
+        }
+        let mut buf = self.value.as_ref();
+        let compression = compression_type(read_u8(&mut buf)?)?;
+        let value = decompress(&compression, buf)?;
+        self.value_size = value.len() as u32;
+        self.value = Bytes::from(value);
+        self.is_compressed = false;
+        return Ok(());
+    }
+
+    // | offset(u64) | timestamp(u64) | attributes(u8) |
+    // | producer id(u64) | producer epoch(u32) | sequence(u32) | (only with a producer)
+    // | header num(u32) |
+    // | header key len(u32) | header key | header value len(u32) | header value | ...
+    // | key len(u32) | key | value len(u32) | value |
+    // A compressed value is | compression id(u8) | compressed value |.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.encode_len());
+        buf.put_u64(self.offset);
+        buf.put_u64(self.timestamp);
+        buf.put_u8(self.attributes());
+        if self.producer_id > 0 {
+            buf.put_u64(self.producer_id);
+            buf.put_u32(self.producer_epoch);
+            buf.put_u32(self.sequence);
+        }
+        buf.put_u32(self.headers.len() as u32);
+        for header in self.headers.iter() {
+            buf.put_u32(header.key.len() as u32);
+            buf.put_slice(header.key.as_bytes());
+            buf.put_u32(header.value.len() as u32);
+            buf.put_slice(&header.value);
+        }
+        buf.put_u32(self.key.len() as u32);
+        buf.put_slice(&self.key);
+        buf.put_u32(self.value.len() as u32);
+        buf.put_slice(&self.value);
+        return buf;
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Record, CommonError> {
+        let mut buf = data;
+        let offset = read_u64(&mut buf)?;
+        let timestamp = read_u64(&mut buf)?;
+        let attributes = read_u8(&mut buf)?;
+        let (producer_id, producer_epoch, sequence) = if attributes & ATTRIBUTE_PRODUCER != 0 {
+            (
+                read_u64(&mut buf)?,
+                read_u32(&mut buf)?,
+                read_u32(&mut buf)?,
+            )
+        } else {
+            (0, 0, 0)
+        };
+        let control = if attributes & ATTRIBUTE_CONTROL_COMMIT != 0 {
+            RecordControl::Commit
+        } else if attributes & ATTRIBUTE_CONTROL_ABORT != 0 {
+            RecordControl::Abort
+        } else {
+            RecordControl::None
+        };
+        let header_num = read_u32(&mut buf)?;
+        let mut headers = Vec::new();
+        for _ in 0..header_num {
+            let key = read_bytes(&mut buf)?;
+            let value = read_bytes(&mut buf)?;
+            let key = match String::from_utf8(key.to_vec()) {
+                Ok(key) => key,
+                Err(e) => {
+                    return Err(JournalServerError::RecordDecodeError(e.to_string()).into());
+                }
+            };
+            headers.push(Header::new(key, value));
+        }
+        let key = read_bytes(&mut buf)?;
+        let value = read_bytes(&mut buf)?;
+        return Ok(Record {
+            offset,
+            producer_id,
+            producer_epoch,
+            sequence,
+            is_transactional: attributes & ATTRIBUTE_TRANSACTIONAL != 0,
+            control,
+            timestamp,
+            size: data.len() as u32,
+            is_compressed: attributes & ATTRIBUTE_COMPRESSED != 0,
+            has_header: !headers.is_empty(),
+            headers,
+            key_size: key.len() as u32,
+            key,
+            value_size: value.len() as u32,
+            value,
+        });
+    }
+
+    pub fn encode_len(&self) -> usize {
+        let header_len: usize = self
+            .headers
+            .iter()
+            .map(|h| 8 + h.key.len() + h.value.len())
+            .sum();
+        let producer_len = if self.producer_id > 0 { 16 } else { 0 };
+        let body_len = 4 + header_len + 4 + self.key.len() + 4 + self.value.len();
+        return 8 + 8 + 1 + producer_len + body_len;
+    }
+
+    fn attributes(&self) -> u8 {
+        let mut attributes = 0;
+        if self.is_compressed {
+            attributes |= ATTRIBUTE_COMPRESSED;
+        }
+        if self.producer_id > 0 {
+            attributes |= ATTRIBUTE_PRODUCER;
+        }
+        if self.is_transactional {
+            attributes |= ATTRIBUTE_TRANSACTIONAL;
+        }
+        match self.control {
+            RecordControl::Commit => attributes |= ATTRIBUTE_CONTROL_COMMIT,
+            RecordControl::Abort => attributes |= ATTRIBUTE_CONTROL_ABORT,
+            RecordControl::None => {}
+        }
+        return attributes;
+    }
+}
+
+fn compression_id(compression: &CompressionType) -> u8 {
+    match compression {
+        CompressionType::None => return 0,
+        CompressionType::Lz4 => return 1,
+        CompressionType::Zstd => return 2,
+        CompressionType::Snappy => return 3,
+    }
+}
+
+fn compression_type(id: u8) -> Result<CompressionType, CommonError> {
+    match id {
+        0 => return Ok(CompressionType::None),
+        1 => return Ok(CompressionType::Lz4),
+        2 => return Ok(CompressionType::Zstd),
+        3 => return Ok(CompressionType::Snappy),
+        _ => {
+            return Err(JournalServerError::RecordDecodeError(format!(
+                "unknown compression id {}",
+                id
+            ))
+            .into())
+        }
+    }
+}
+
+fn check_remaining(buf: &[u8], len: usize) -> Result<(), CommonError> {
+    if buf.remaining() < len {
+        return Err(JournalServerError::RecordDecodeError(format!(
+            "{} bytes are required but only {} bytes remain",
+            len,
+            buf.remaining()
+        ))
+        .into());
+    }
+    return Ok(());
+}
+
+fn read_u8(buf: &mut &[u8]) -> Result<u8, CommonError> {
+    check_remaining(buf, 1)?;
+    return Ok(buf.get_u8());
+}
+
+fn read_u32(buf: &mut &[u8]) -> Result<u32, CommonError> {
+    check_remaining(buf, 4)?;
+    return Ok(buf.get_u32());
+}
+
+fn read_u64(buf: &mut &[u8]) -> Result<u64, CommonError> {
+    check_remaining(buf, 8)?;
+    return Ok(buf.get_u64());
+}
+
+fn read_bytes(buf: &mut &[u8]) -> Result<Bytes, CommonError> {
+    let len = read_u32(buf)? as usize;
+    check_remaining(buf, len)?;
+    return Ok(buf.copy_to_bytes(len));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Record, RecordControl};
+    use crate::record::header::Header;
+    use bytes::Bytes;
+    use storage_adapter::compression::CompressionType;
+
+    #[test]
+    fn record_encode_decode() {
+        let mut record = Record::build(
+            Bytes::from("k1"),
+            Bytes::from("v1"),
+            vec![Header::new("h1".to_string(), Bytes::from("hv1"))],
+        );
+        record.offset = 10;
+        record.timestamp = 1000;
+        record.is_compressed = true;
+
+        let data = record.encode();
+        assert_eq!(data.len(), record.encode_len());
+        let res = Record::decode(&data).unwrap();
+        assert_eq!(res.offset, 10);
+        assert_eq!(res.timestamp, 1000);
+        assert!(res.is_compressed);
+        assert!(res.has_header);
+        assert_eq!(res.headers, record.headers);
+        assert_eq!(res.key, record.key);
+        assert_eq!(res.value, record.value);
+
+        assert!(Record::decode(&data[0..data.len() - 1]).is_err());
+        assert_eq!(res.producer_id, 0);
+
+        let mut record = Record::build_control(7, 2, RecordControl::Abort);
+        record.sequence = 9;
+        let data = record.encode();
+        assert_eq!(data.len(), record.encode_len());
+        let res = Record::decode(&data).unwrap();
+        assert_eq!(res.producer_id, 7);
+        assert_eq!(res.producer_epoch, 2);
+        assert_eq!(res.sequence, 9);
+        assert!(res.is_transactional);
+        assert_eq!(res.control, RecordControl::Abort);
+        assert!(res.key.is_empty());
+    }
+    #[test]
+    fn record_compress() {
+        let value = Bytes::from("robustmq".repeat(64));
+        let mut record = Record::build(Bytes::from("k1"), value.clone(), Vec::new());
+        record.compress(&CompressionType::Zstd, 1024).unwrap();
+        assert!(!record.is_compressed);
+
+        record.compress(&CompressionType::Zstd, 16).unwrap();
+        assert!(record.is_compressed);
+        assert!(record.value.len() < value.len());
+        assert_eq!(record.value_size as usize, record.value.len());
+
+        let mut res = Record::decode(&record.encode()).unwrap();
+        assert!(res.is_compressed);
+        res.decompress().unwrap();
+        assert!(!res.is_compressed);
+        assert_eq!(res.value, value);
+        assert_eq!(res.value_size as usize, value.len());
+
+        let mut record = Record::build(Bytes::from("k1"), Bytes::from("v1"), Vec::new());
+        record.compress(&CompressionType::Lz4, 0).unwrap();
+        assert!(!record.is_compressed);
+    }
+}
@@ -0,0 +1,201 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::manager::{ReplicaManager, ShardAssignment};
+use crate::{network::services::protocol_to_record, shard::manager::ShardManager};
+use clients::journal::client::JournalClient;
+use common_base::error::{common::CommonError, journal_server::JournalServerError};
+use log::{error, info, warn};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::{select, sync::broadcast, task::JoinHandle, time::sleep};
+
+const FOLLOWER_FETCH_RECORD_NUM: u32 = 1000;
+const FOLLOWER_CHECK_INTERVAL_MS: u64 = 1000;
+const FOLLOWER_RETRY_INTERVAL_MS: u64 = 1000;
+
+// Keeps one fetch task running for every shard this node follows. A task is replaced
+// when the leader of its shard changes and stopped when the node no longer follows it.
+pub async fn start_follower_thread(
+    replica_manager: Arc<ReplicaManager>,
+    shard_manager: Arc<ShardManager>,
+    client: Arc<JournalClient>,
+    fetch_max_wait_ms: u32,
+    stop_send: broadcast::Sender<bool>,
+) {
+    let mut fetchers: HashMap<String, (ShardAssignment, JoinHandle<()>)> = HashMap::new();
+    let mut stop_rx = stop_send.subscribe();
+    loop {
+        select! {
+            val = stop_rx.recv() => {
+                if let Ok(flag) = val {
+                    if flag {
+                        for (_, (_, handle)) in fetchers.drain() {
+                            handle.abort();
+                        }
+                        info!("{}", "Replica follower thread stopped successfully.");
+                        break;
+                    }
+                }
+            }
+            _ = sleep(Duration::from_millis(FOLLOWER_CHECK_INTERVAL_MS)) => {
+                let follower_shards: HashMap<String, ShardAssignment> =
+                    replica_manager.follower_shards().into_iter().collect();
+
+                let stopped: Vec<String> = fetchers
+                    .iter()
+                    .filter(|(shard_name, (assignment, handle))| {
+                        handle.is_finished() || follower_shards.get(*shard_name) != Some(assignment)
+                    })
+                    .map(|(shard_name, _)| shard_name.clone())
+                    .collect();
+                for shard_name in stopped {
+                    if let Some((_, handle)) = fetchers.remove(&shard_name) {
+                        handle.abort();
+                    }
+                }
+
+                for (shard_name, assignment) in follower_shards {
+                    if fetchers.contains_key(&shard_name) {
+                        continue;
+                    }
+                    info!(
+                        "Start fetching shard {} from leader {}",
+                        shard_name, assignment.leader_addr
+                    );
+                    let handle = tokio::spawn(run_shard_fetcher(
+                        shard_name.clone(),
+                        assignment.clone(),
+                        shard_manager.clone(),
+                        client.clone(),
+                        fetch_max_wait_ms,
+                    ));
+                    fetchers.insert(shard_name, (assignment, handle));
+                }
+            }
+        }
+    }
+}
+
+async fn run_shard_fetcher(
+    shard_name: String,
+    assignment: ShardAssignment,
+    shard_manager: Arc<ShardManager>,
+    client: Arc<JournalClient>,
+    fetch_max_wait_ms: u32,
+) {
+    // Records above the high watermark may not have reached the new leader, they are
+    // dropped and fetched again.
+    if let Err(e) = truncate_to_high_watermark(&shard_name, &shard_manager) {
+        error!(
+            "Failed to truncate shard {} to its high watermark, {}",
+            shard_name, e
+        );
+        return;
+    }
+    client.set_leader(&shard_name, &assignment.leader_addr);
+
+    loop {
+        match fetch_from_leader(&shard_name, &shard_manager, &client, fetch_max_wait_ms).await {
+            Ok(()) => {}
+            Err(CommonError::JournalServerError(JournalServerError::OffsetOutOfRange(_, _))) => {
+                if let Err(e) =
+                    truncate_to_leader(&shard_name, &assignment, &shard_manager, &client).await
+                {
+                    warn!(
+                        "Failed to truncate shard {} to the leader, {}",
+                        shard_name, e
+                    );
+                    sleep(Duration::from_millis(FOLLOWER_RETRY_INTERVAL_MS)).await;
+                }
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to fetch shard {} from leader {}, {}",
+                    shard_name, assignment.leader_addr, e
+                );
+                sleep(Duration::from_millis(FOLLOWER_RETRY_INTERVAL_MS)).await;
+            }
+        }
+    }
+}
+
+fn truncate_to_high_watermark(
+    shard_name: &String,
+    shard_manager: &Arc<ShardManager>,
+) -> Result<(), CommonError> {
+    if shard_manager.get_shard(shard_name).is_none() {
+        shard_manager.create_shard(shard_name)?;
+    }
+    let high_watermark = shard_manager.high_watermark(shard_name)?;
+    return shard_manager.truncate(shard_name, high_watermark);
+}
+
+// Fetches the records after the end of the local log and appends them with the offsets
+// assigned by the leader. Offsets can be skipped where the leader compacted the log.
+async fn fetch_from_leader(
+    shard_name: &String,
+    shard_manager: &Arc<ShardManager>,
+    client: &Arc<JournalClient>,
+    fetch_max_wait_ms: u32,
+) -> Result<(), CommonError> {
+    let next_offset = shard_manager.next_offset(shard_name)?;
+    let result = client
+        .fetch(
+            shard_name,
+            next_offset,
+            FOLLOWER_FETCH_RECORD_NUM,
+            fetch_max_wait_ms,
+        )
+        .await?;
+
+    if let Some(record) = result.records.first() {
+        if record.offset < next_offset {
+            return Err(CommonError::CommmonError(format!(
+                "leader returned offset {} of shard {} for a fetch from offset {}",
+                record.offset, shard_name, next_offset
+            )));
+        }
+        let records = result.records.into_iter().map(protocol_to_record).collect();
+        shard_manager.append_replicated(shard_name, records)?;
+    }
+
+    let next_offset = shard_manager.next_offset(shard_name)?;
+    return shard_manager.set_high_watermark(shard_name, result.high_watermark.min(next_offset));
+}
+
+// The leader does not have the offset the follower fetches from, so the follower holds
+// records the leader lost when it took over. They are dropped.
+async fn truncate_to_leader(
+    shard_name: &String,
+    assignment: &ShardAssignment,
+    shard_manager: &Arc<ShardManager>,
+    client: &Arc<JournalClient>,
+) -> Result<(), CommonError> {
+    let body = client
+        .metadata(&assignment.leader_addr, vec![shard_name.clone()])
+        .await?;
+    for shard in body.shards {
+        if shard.shard_name != *shard_name || shard.error_code != 0 {
+            continue;
+        }
+        if shard.next_offset < shard_manager.next_offset(shard_name)? {
+            info!(
+                "Truncating shard {} to offset {} of leader {}",
+                shard_name, shard.next_offset, assignment.leader_addr
+            );
+            shard_manager.truncate(shard_name, shard.next_offset)?;
+        }
+        return Ok(());
+    }
+    return Err(JournalServerError::ShardNotFound(shard_name.clone()).into());
+}
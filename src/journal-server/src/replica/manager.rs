@@ -0,0 +1,417 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::shard::manager::ShardManager;
+use common_base::error::common::CommonError;
+use dashmap::DashMap;
+use log::info;
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::time::{timeout_at, Instant};
+
+// Where the replicas of a shard live, as assigned by the placement center.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShardAssignment {
+    pub leader_id: u64,
+    pub leader_addr: String,
+    pub replicas: Vec<u64>,
+    // Replicas of the next segment of the shard. They copy the shard ahead of the segment
+    // roll, so that any of them can lead the next segment, but they stay out of the ISR.
+    pub next_replicas: Vec<u64>,
+}
+
+// Replication progress of a shard led by this node.
+struct LeaderState {
+    // In-sync replicas, always including the leader itself.
+    isr: BTreeSet<u64>,
+    // The offset each follower fetches from, every record below it is on the follower.
+    follower_offsets: HashMap<u64, u64>,
+    // When each follower last fetched from the end of the leader log.
+    caught_up_times: HashMap<u64, Instant>,
+}
+
+// Tracks which shards this node leads or follows, and for the shards it leads which
+// followers are in sync. The high watermark of a led shard is the lowest offset reached
+// by every in-sync replica.
+pub struct ReplicaManager {
+    node_id: u64,
+    replica_lag_time_max: Duration,
+    shard_manager: Arc<ShardManager>,
+    assignments: DashMap<String, ShardAssignment>,
+    leader_states: DashMap<String, LeaderState>,
+}
+
+impl ReplicaManager {
+    pub fn new(
+        node_id: u64,
+        replica_lag_time_max: Duration,
+        shard_manager: Arc<ShardManager>,
+    ) -> Self {
+        return ReplicaManager {
+            node_id,
+            replica_lag_time_max,
+            shard_manager,
+            assignments: DashMap::with_capacity(8),
+            leader_states: DashMap::with_capacity(8),
+        };
+    }
+
+    pub fn node_id(&self) -> u64 {
+        return self.node_id;
+    }
+
+    pub fn set_assignment(&self, shard_name: &str, assignment: ShardAssignment) {
+        if let Some(current) = self.assignments.get(shard_name) {
+            if *current == assignment {
+                return;
+            }
+        }
+        info!(
+            "Replica assignment of shard {} changed to {:?}",
+            shard_name, assignment
+        );
+        // Leadership starts over with every assigned replica in sync, those that do not
+        // catch up leave the ISR after the max lag time.
+        self.leader_states.remove(shard_name);
+        if assignment.leader_id == self.node_id {
+            let now = Instant::now();
+            let mut isr: BTreeSet<u64> = assignment.replicas.iter().copied().collect();
+            isr.insert(self.node_id);
+            let caught_up_times = isr.iter().map(|node_id| (*node_id, now)).collect();
+            self.leader_states.insert(
+                shard_name.to_string(),
+                LeaderState {
+                    isr,
+                    follower_offsets: HashMap::new(),
+                    caught_up_times,
+                },
+            );
+        }
+        self.assignments.insert(shard_name.to_string(), assignment);
+    }
+
+    pub fn assignment(&self, shard_name: &str) -> Option<ShardAssignment> {
+        if let Some(assignment) = self.assignments.get(shard_name) {
+            return Some(assignment.clone());
+        }
+        return None;
+    }
+
+    pub fn assigned_shards(&self) -> Vec<String> {
+        return self
+            .assignments
+            .iter()
+            .map(|raw| raw.key().clone())
+            .collect();
+    }
+
+    // A shard without an assignment is served by the node that holds it, as on a single
+    // node deployment.
+    pub fn is_leader(&self, shard_name: &str) -> bool {
+        match self.assignments.get(shard_name) {
+            Some(assignment) => return assignment.leader_id == self.node_id,
+            None => return true,
+        }
+    }
+
+    // The shards this node holds a replica of and that are led by another node.
+    pub fn follower_shards(&self) -> Vec<(String, ShardAssignment)> {
+        return self
+            .assignments
+            .iter()
+            .filter(|raw| {
+                let assignment = raw.value();
+                assignment.leader_id != 0
+                    && assignment.leader_id != self.node_id
+                    && (assignment.replicas.contains(&self.node_id)
+                        || assignment.next_replicas.contains(&self.node_id))
+            })
+            .map(|raw| (raw.key().clone(), raw.value().clone()))
+            .collect();
+    }
+
+    pub fn leader_shards(&self) -> Vec<String> {
+        return self
+            .leader_states
+            .iter()
+            .map(|raw| raw.key().clone())
+            .collect();
+    }
+
+    pub fn isr(&self, shard_name: &str) -> Vec<u64> {
+        if let Some(state) = self.leader_states.get(shard_name) {
+            return state.isr.iter().copied().collect();
+        }
+        return vec![self.node_id];
+    }
+
+    // Called for every fetch of a follower, offset is where the follower fetches from.
+    // A follower that reaches the end of the log is caught up, and one that is out of
+    // the ISR joins it again once it holds every record below the high watermark.
+    pub fn update_follower_offset(
+        &self,
+        shard_name: &str,
+        replica_id: u64,
+        offset: u64,
+    ) -> Result<(), CommonError> {
+        let shard_name = shard_name.to_string();
+        let next_offset = self.shard_manager.next_offset(&shard_name)?;
+        let high_watermark = self.shard_manager.high_watermark(&shard_name)?;
+        let assigned = match self.assignments.get(&shard_name) {
+            Some(assignment) => assignment.replicas.contains(&replica_id),
+            None => false,
+        };
+        match self.leader_states.get_mut(&shard_name) {
+            Some(mut state) => {
+                state.follower_offsets.insert(replica_id, offset);
+                if offset >= next_offset {
+                    state.caught_up_times.insert(replica_id, Instant::now());
+                }
+                if assigned && !state.isr.contains(&replica_id) && offset >= high_watermark {
+                    info!(
+                        "Replica {} of shard {} caught up at offset {}, it joins the ISR",
+                        replica_id, shard_name, offset
+                    );
+                    state.isr.insert(replica_id);
+                    state.caught_up_times.insert(replica_id, Instant::now());
+                }
+            }
+            None => return Ok(()),
+        }
+        return self.maybe_advance_high_watermark(&shard_name);
+    }
+
+    pub fn leader_appended(&self, shard_name: &str) -> Result<(), CommonError> {
+        return self.maybe_advance_high_watermark(shard_name);
+    }
+
+    pub fn maybe_advance_high_watermark(&self, shard_name: &str) -> Result<(), CommonError> {
+        let shard_name = shard_name.to_string();
+        let next_offset = self.shard_manager.next_offset(&shard_name)?;
+        let high_watermark = match self.leader_states.get(&shard_name) {
+            Some(state) => state
+                .isr
+                .iter()
+                .map(|node_id| {
+                    if *node_id == self.node_id {
+                        return next_offset;
+                    }
+                    return state
+                        .follower_offsets
+                        .get(node_id)
+                        .copied()
+                        .unwrap_or_default();
+                })
+                .min()
+                .unwrap_or(next_offset),
+            None => {
+                if !self.is_leader(&shard_name) {
+                    return Ok(());
+                }
+                next_offset
+            }
+        };
+        return self
+            .shard_manager
+            .set_high_watermark(&shard_name, high_watermark);
+    }
+
+    // Removes the followers that have not caught up within the max lag time from the
+    // ISR, which lets the high watermark move on without them.
+    pub fn shrink_isr(&self, shard_name: &str) -> Result<(), CommonError> {
+        let mut shrunk = false;
+        if let Some(mut state) = self.leader_states.get_mut(shard_name) {
+            let now = Instant::now();
+            let lagging: Vec<u64> = state
+                .isr
+                .iter()
+                .filter(|node_id| **node_id != self.node_id)
+                .filter(|node_id| match state.caught_up_times.get(node_id) {
+                    Some(time) => now.duration_since(*time) > self.replica_lag_time_max,
+                    None => true,
+                })
+                .copied()
+                .collect();
+            for node_id in lagging {
+                info!(
+                    "Replica {} of shard {} has not caught up for {:?}, it leaves the ISR",
+                    node_id, shard_name, self.replica_lag_time_max
+                );
+                state.isr.remove(&node_id);
+                shrunk = true;
+            }
+        }
+        if shrunk {
+            return self.maybe_advance_high_watermark(shard_name);
+        }
+        return Ok(());
+    }
+
+    // Waits until the high watermark of the shard reaches offset, returns false if it did
+    // not before the deadline.
+    pub async fn wait_high_watermark(
+        &self,
+        shard_name: &str,
+        offset: u64,
+        deadline: Instant,
+    ) -> bool {
+        let shard_name = shard_name.to_string();
+        loop {
+            let notified = self.shard_manager.wait_append();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            match self.shard_manager.high_watermark(&shard_name) {
+                Ok(high_watermark) => {
+                    if high_watermark >= offset {
+                        return true;
+                    }
+                }
+                Err(_) => return false,
+            }
+            if timeout_at(deadline, notified).await.is_err() {
+                return false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ReplicaManager, ShardAssignment};
+    use crate::{
+        index::engine::IndexEngine,
+        record::record::Record,
+        shard::{manager::ShardManager, segment::FsyncPolicy},
+    };
+    use bytes::Bytes;
+    use std::{fs, sync::Arc, time::Duration};
+    use tokio::time::{sleep, Instant};
+
+    fn build_replica_manager(name: &str, lag_time_max: Duration) -> Arc<ReplicaManager> {
+        let dir = std::env::temp_dir().join(format!("robustmq-journal-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let engine =
+            IndexEngine::new(&dir.join("_index_rocksdb").display().to_string(), None).unwrap();
+        let shard_manager = ShardManager::new(
+            vec![dir.display().to_string()],
+            FsyncPolicy::Os,
+            0,
+            Arc::new(engine),
+        );
+        return Arc::new(ReplicaManager::new(
+            1,
+            lag_time_max,
+            Arc::new(shard_manager),
+        ));
+    }
+
+    fn append(replica_manager: &ReplicaManager, shard_name: &str, num: u64) {
+        let records = (0..num)
+            .map(|i| Record::build(Bytes::new(), Bytes::from(format!("v{}", i)), Vec::new()))
+            .collect();
+        replica_manager
+            .shard_manager
+            .append(&shard_name.to_string(), records)
+            .unwrap();
+        replica_manager.leader_appended(shard_name).unwrap();
+    }
+
+    fn high_watermark(replica_manager: &ReplicaManager, shard_name: &str) -> u64 {
+        return replica_manager
+            .shard_manager
+            .high_watermark(&shard_name.to_string())
+            .unwrap();
+    }
+
+    #[test]
+    fn high_watermark_follows_isr() {
+        let replica_manager = build_replica_manager("replica-isr", Duration::from_secs(60));
+        // Without an assignment the node leads the shard alone.
+        append(&replica_manager, "s1", 3);
+        assert!(replica_manager.is_leader("s1"));
+        assert_eq!(high_watermark(&replica_manager, "s1"), 3);
+
+        replica_manager.set_assignment(
+            "s1",
+            ShardAssignment {
+                leader_id: 1,
+                leader_addr: "127.0.0.1:2228".to_string(),
+                replicas: vec![1, 2, 3],
+                next_replicas: Vec::new(),
+            },
+        );
+        assert_eq!(replica_manager.isr("s1"), vec![1, 2, 3]);
+        append(&replica_manager, "s1", 2);
+        assert_eq!(high_watermark(&replica_manager, "s1"), 3);
+
+        replica_manager.update_follower_offset("s1", 2, 5).unwrap();
+        assert_eq!(high_watermark(&replica_manager, "s1"), 3);
+        replica_manager.update_follower_offset("s1", 3, 4).unwrap();
+        assert_eq!(high_watermark(&replica_manager, "s1"), 4);
+        replica_manager.update_follower_offset("s1", 3, 5).unwrap();
+        assert_eq!(high_watermark(&replica_manager, "s1"), 5);
+
+        replica_manager.set_assignment(
+            "s1",
+            ShardAssignment {
+                leader_id: 2,
+                leader_addr: "127.0.0.1:3228".to_string(),
+                replicas: vec![1, 2, 3],
+                next_replicas: Vec::new(),
+            },
+        );
+        assert!(!replica_manager.is_leader("s1"));
+        assert_eq!(replica_manager.follower_shards().len(), 1);
+        assert!(replica_manager.leader_shards().is_empty());
+    }
+
+    #[tokio::test]
+    async fn lagging_follower_leaves_isr() {
+        let replica_manager = build_replica_manager("replica-shrink", Duration::from_millis(50));
+        replica_manager.set_assignment(
+            "s1",
+            ShardAssignment {
+                leader_id: 1,
+                leader_addr: "127.0.0.1:2228".to_string(),
+                replicas: vec![1, 2],
+                next_replicas: Vec::new(),
+            },
+        );
+        append(&replica_manager, "s1", 2);
+        assert_eq!(high_watermark(&replica_manager, "s1"), 0);
+
+        let waiter = replica_manager.clone();
+        let task = tokio::spawn(async move {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            return waiter.wait_high_watermark("s1", 2, deadline).await;
+        });
+
+        sleep(Duration::from_millis(100)).await;
+        replica_manager.shrink_isr("s1").unwrap();
+        assert_eq!(replica_manager.isr("s1"), vec![1]);
+        assert!(task.await.unwrap());
+        assert_eq!(high_watermark(&replica_manager, "s1"), 2);
+
+        // The follower catches up and joins the ISR again.
+        replica_manager.update_follower_offset("s1", 2, 2).unwrap();
+        assert_eq!(replica_manager.isr("s1"), vec![1, 2]);
+        let deadline = Instant::now() + Duration::from_millis(50);
+        append(&replica_manager, "s1", 1);
+        assert!(!replica_manager.wait_high_watermark("s1", 3, deadline).await);
+    }
+}
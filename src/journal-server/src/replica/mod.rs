@@ -0,0 +1,174 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::shard::manager::{CleanupPolicy, ShardConfig, ShardManager};
+use clients::{placement::journal::call::list_shard, poll::ClientPool};
+use common_base::{config::journal_server::JournalServerConfig, error::common::CommonError};
+use log::{error, info};
+use manager::{ReplicaManager, ShardAssignment};
+use protocol::placement_center::generate::journal::ListShardRequest;
+use std::{collections::HashSet, sync::Arc, time::Duration};
+use storage_adapter::compression::CompressionType;
+use tokio::{select, sync::broadcast, time::sleep};
+
+pub mod follower;
+pub mod manager;
+
+// Reads the shards with a replica on this node from the placement center periodically,
+// along with the settings of each shard.
+pub async fn start_assignment_thread(
+    replica_manager: Arc<ReplicaManager>,
+    shard_manager: Arc<ShardManager>,
+    client_poll: Arc<ClientPool>,
+    config: JournalServerConfig,
+    stop_send: broadcast::Sender<bool>,
+) {
+    let mut stop_rx = stop_send.subscribe();
+    loop {
+        select! {
+            val = stop_rx.recv() => {
+                if let Ok(flag) = val {
+                    if flag {
+                        info!("{}", "Replica assignment thread stopped successfully.");
+                        break;
+                    }
+                }
+            }
+            _ = sleep(Duration::from_millis(config.replication.assignment_refresh_ms)) => {
+                if let Err(e) = refresh_assignments(&replica_manager, &shard_manager, &client_poll, &config).await {
+                    error!("Failed to refresh replica assignments, error message: {}", e.to_string());
+                }
+            }
+        }
+    }
+}
+
+async fn refresh_assignments(
+    replica_manager: &Arc<ReplicaManager>,
+    shard_manager: &Arc<ShardManager>,
+    client_poll: &Arc<ClientPool>,
+    config: &JournalServerConfig,
+) -> Result<(), CommonError> {
+    let request = ListShardRequest {
+        cluster_name: config.cluster_name.clone(),
+        node_id: config.node_id,
+        stale_read: false,
+    };
+    let reply = list_shard(
+        client_poll.clone(),
+        config.placement_center.clone(),
+        request,
+    )
+    .await?;
+
+    let mut shard_names = HashSet::new();
+    for shard in reply.shards {
+        shard_names.insert(shard.shard_name.clone());
+        shard_manager.set_shard_config(
+            &shard.shard_name,
+            ShardConfig {
+                cleanup_policy: CleanupPolicy::build(&shard.cleanup_policy),
+                tombstone_retention_ms: shard.tombstone_retention_ms,
+                compression: CompressionType::from_name(&shard.compression),
+                compression_min_size: shard.compression_min_size,
+            },
+        );
+        replica_manager.set_assignment(
+            &shard.shard_name,
+            ShardAssignment {
+                leader_id: shard.leader_id,
+                leader_addr: shard.leader_addr,
+                replicas: shard.replica_nodes,
+                next_replicas: shard.next_replica_nodes,
+            },
+        );
+    }
+    // A shard that no longer has a replica on this node is neither led nor followed.
+    for shard_name in replica_manager.assigned_shards() {
+        if !shard_names.contains(&shard_name) {
+            replica_manager.set_assignment(&shard_name, ShardAssignment::default());
+        }
+    }
+    return Ok(());
+}
+
+// Removes the followers that fell behind from the ISR of the shards this node leads.
+pub async fn start_isr_check_thread(
+    replica_manager: Arc<ReplicaManager>,
+    replica_lag_time_max_ms: u64,
+    stop_send: broadcast::Sender<bool>,
+) {
+    let interval_ms = (replica_lag_time_max_ms / 2).max(1);
+    let mut stop_rx = stop_send.subscribe();
+    loop {
+        select! {
+            val = stop_rx.recv() => {
+                if let Ok(flag) = val {
+                    if flag {
+                        info!("{}", "ISR check thread stopped successfully.");
+                        break;
+                    }
+                }
+            }
+            _ = sleep(Duration::from_millis(interval_ms)) => {
+                for shard_name in replica_manager.leader_shards() {
+                    if let Err(e) = replica_manager.shrink_isr(&shard_name) {
+                        error!("Failed to check the ISR of shard {}, error message: {}", shard_name, e.to_string());
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Aborts the transactions left open for transaction_timeout_ms on the shards this node
+// leads, so that a producer that went away does not hold back the last stable offset.
+pub async fn start_transaction_timeout_thread(
+    replica_manager: Arc<ReplicaManager>,
+    shard_manager: Arc<ShardManager>,
+    transaction_timeout_ms: u64,
+    stop_send: broadcast::Sender<bool>,
+) {
+    let interval_ms = (transaction_timeout_ms / 2).max(1);
+    let mut stop_rx = stop_send.subscribe();
+    loop {
+        select! {
+            val = stop_rx.recv() => {
+                if let Ok(flag) = val {
+                    if flag {
+                        info!("{}", "Transaction timeout thread stopped successfully.");
+                        break;
+                    }
+                }
+            }
+            _ = sleep(Duration::from_millis(interval_ms)) => {
+                for shard_name in replica_manager.leader_shards() {
+                    match shard_manager.abort_expired_transactions(&shard_name, transaction_timeout_ms) {
+                        Ok(offsets) => {
+                            if offsets.is_empty() {
+                                continue;
+                            }
+                            info!("Aborted {} timed out transactions of shard {}", offsets.len(), shard_name);
+                            if let Err(e) = replica_manager.leader_appended(&shard_name) {
+                                error!("Failed to advance the high watermark of shard {}, error message: {}", shard_name, e.to_string());
+                            }
+                        }
+                        Err(e) => {
+                            error!("Failed to abort the timed out transactions of shard {}, error message: {}", shard_name, e.to_string());
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
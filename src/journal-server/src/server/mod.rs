@@ -12,15 +12,25 @@
 // limitations under the License.
 
 
+use crate::{
+    network::services::Services, replica::manager::ReplicaManager, shard::manager::ShardManager,
+    tiered::manager::RemoteLogManager,
+};
 use common_base::config::journal_server::journal_server_conf;
+use std::sync::Arc;
 
 use self::tcp::tcp_server::TcpServer;
 
 pub mod quic;
 pub mod tcp;
 
-pub async fn start_tcp_server() {
+pub async fn start_tcp_server(
+    shard_manager: Arc<ShardManager>,
+    replica_manager: Arc<ReplicaManager>,
+    remote_log: Option<Arc<RemoteLogManager>>,
+) {
     let conf = journal_server_conf();
+    let services = Arc::new(Services::new(shard_manager, replica_manager, remote_log));
     let tcp = TcpServer::new(
         conf.network.accept_thread_num,
         conf.network.max_connection_num,
@@ -30,6 +40,7 @@ pub async fn start_tcp_server() {
         conf.network.response_thread_num,
         60,
         10,
+        services,
     );
     tcp.start(conf.grpc_port).await;
 }
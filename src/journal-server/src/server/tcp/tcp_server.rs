@@ -15,7 +15,10 @@ use super::{
     connection::{Connection, ConnectionManager},
     packet::ResponsePackage,
 };
-use crate::{network::command::Command, server::tcp::packet::RequestPackage};
+use crate::{
+    network::{command::Command, services::Services},
+    server::tcp::packet::RequestPackage,
+};
 
 use futures::StreamExt;
 use log::error;
@@ -33,6 +36,7 @@ pub struct TcpServer {
     request_queue_sx: Sender<RequestPackage>,
     response_queue_sx: Sender<ResponsePackage>,
     codec: StorageEngineCodec,
+    services: Arc<Services>,
 }
 
 impl TcpServer {
@@ -45,6 +49,7 @@ impl TcpServer {
         response_process_num: usize,
         max_try_mut_times: u64,
         try_mut_sleep_time_ms: u64,
+        services: Arc<Services>,
     ) -> Self {
         let (request_queue_sx, _) = broadcast::channel(request_queue_size);
         let (response_queue_sx, _) = broadcast::channel(response_queue_size);
@@ -64,6 +69,7 @@ impl TcpServer {
             request_queue_sx,
             response_queue_sx,
             codec,
+            services,
         }
     }
 
@@ -145,21 +151,32 @@ impl TcpServer {
     async fn handler_process(&self) -> Result<(), Error> {
         let mut request_queue_rx = self.request_queue_sx.subscribe();
         let response_queue_sx = self.response_queue_sx.clone();
+        let services = self.services.clone();
         tokio::spawn(async move {
             while let Ok(resquest_package) = request_queue_rx.recv().await {
-                //Business logic processing
-                let command = Command::new(resquest_package.packet);
-                let resp = command.apply();
-
-                // Writes the result of the business logic processing to the return queue
-                let response_package = ResponsePackage::new(resquest_package.connection_id, resp);
-                match response_queue_sx.send(response_package) {
-                    Ok(_) => {}
-                    Err(err) => error!(
-                        "Failed to write data to the response queue, error message: {:?}",
-                        err
-                    ),
-                }
+                let response_queue_sx = response_queue_sx.clone();
+                let services = services.clone();
+                // A fetch can wait for new records, so every request is handled in its own
+                // task to not hold up the requests behind it.
+                tokio::spawn(async move {
+                    //Business logic processing
+                    let command = Command::new(resquest_package.packet, services);
+                    let resp = match command.apply().await {
+                        Some(resp) => resp,
+                        None => return,
+                    };
+
+                    // Writes the result of the business logic processing to the return queue
+                    let response_package =
+                        ResponsePackage::new(resquest_package.connection_id, resp);
+                    match response_queue_sx.send(response_package) {
+                        Ok(_) => {}
+                        Err(err) => error!(
+                            "Failed to write data to the response queue, error message: {:?}",
+                            err
+                        ),
+                    }
+                });
             }
         });
         return Ok(());
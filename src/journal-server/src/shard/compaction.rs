@@ -0,0 +1,366 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{
+    manager::{CleanupPolicy, ShardManager},
+    segment::{
+        compacting_file_path, FsyncPolicy, SegmentFile, FRAME_HEADER_LEN, SEGMENT_HEADER_LEN,
+    },
+    shard_log::SealedSegment,
+};
+use crate::{index::ShardIndex, record::record::Record};
+use common_base::{error::common::CommonError, tools::now_mills};
+use log::{error, info};
+use std::{fs, sync::Arc, time::Duration};
+use tokio::{select, sync::broadcast, time::sleep};
+
+const COMPACTION_BATCH_RECORD_NUM: usize = 1000;
+
+// Rewrites the sealed segments of the shards with the compact cleanup policy so that only
+// the latest record of every key is kept. The offsets of the kept records do not change.
+pub struct Compactor {
+    shard_manager: Arc<ShardManager>,
+    // Used for the shards that do not set their own tombstone retention.
+    tombstone_retention_ms: u64,
+}
+
+impl Compactor {
+    pub fn new(shard_manager: Arc<ShardManager>, tombstone_retention_ms: u64) -> Self {
+        return Compactor {
+            shard_manager,
+            tombstone_retention_ms,
+        };
+    }
+
+    pub fn compact(&self) {
+        for shard_name in self.shard_manager.shard_names() {
+            let config = self.shard_manager.shard_config(&shard_name);
+            if config.cleanup_policy != CleanupPolicy::Compact {
+                continue;
+            }
+            let tombstone_retention_ms = if config.tombstone_retention_ms > 0 {
+                config.tombstone_retention_ms
+            } else {
+                self.tombstone_retention_ms
+            };
+            if let Err(e) = self.compact_shard(&shard_name, tombstone_retention_ms) {
+                error!(
+                    "Failed to compact shard {}, error message: {}",
+                    shard_name,
+                    e.to_string()
+                );
+            }
+        }
+    }
+
+    // Compacts the sealed segments of the shard, oldest first, and returns the number of
+    // removed records.
+    pub fn compact_shard(
+        &self,
+        shard_name: &String,
+        tombstone_retention_ms: u64,
+    ) -> Result<u64, CommonError> {
+        let index = ShardIndex::new(self.shard_manager.index_engine(), shard_name.clone());
+        let mut removed_num = 0;
+        for sealed in self.shard_manager.sealed_segments(shard_name)? {
+            removed_num +=
+                self.compact_segment(shard_name, &index, &sealed, tombstone_retention_ms)?;
+        }
+        return Ok(removed_num);
+    }
+
+    // The segment is read and its compacted copy written without holding the shard lock,
+    // which is only taken to swap the files.
+    fn compact_segment(
+        &self,
+        shard_name: &String,
+        index: &ShardIndex,
+        sealed: &SealedSegment,
+        tombstone_retention_ms: u64,
+    ) -> Result<u64, CommonError> {
+        let stable_offset = self.shard_manager.last_stable_offset(shard_name)?;
+        let now = now_mills() as u64;
+        let mut segment = SegmentFile::open(&sealed.path, sealed.segment_no, FsyncPolicy::Os)?;
+        segment.next_offset = sealed.next_offset;
+        segment.size = sealed.size;
+
+        let compacted_path = compacting_file_path(&sealed.path);
+        if compacted_path.exists() {
+            fs::remove_file(&compacted_path)?;
+        }
+        let mut compacted = SegmentFile::create_at(
+            &compacted_path,
+            sealed.segment_no,
+            sealed.start_offset,
+            FsyncPolicy::Os,
+        )?;
+
+        let mut removed_num = 0;
+        let mut position = SEGMENT_HEADER_LEN;
+        loop {
+            let data = segment.read_at(position, COMPACTION_BATCH_RECORD_NUM, 0)?;
+            if data.is_empty() {
+                break;
+            }
+            let mut kept = Vec::new();
+            for (record_position, record) in data {
+                position = record_position + FRAME_HEADER_LEN + record.encode_len() as u64;
+                if is_removable(index, &record, stable_offset, now, tombstone_retention_ms)? {
+                    removed_num += 1;
+                } else {
+                    kept.push(record);
+                }
+            }
+            if !kept.is_empty() {
+                compacted.append(&kept)?;
+            }
+        }
+
+        if removed_num == 0 {
+            drop(compacted);
+            fs::remove_file(&compacted_path)?;
+            return Ok(0);
+        }
+        compacted.sync()?;
+        drop(compacted);
+
+        if !self
+            .shard_manager
+            .replace_segment(shard_name, sealed, &compacted_path)?
+        {
+            return Ok(0);
+        }
+        info!(
+            "Compacted segment {} of shard {}, {} records removed",
+            sealed.segment_no, shard_name, removed_num
+        );
+        return Ok(removed_num);
+    }
+}
+
+// A record is removed once a later record with the same key is below the last stable
+// offset, so the later one can no longer be truncated away or aborted. The latest record
+// of a key is only removed when it is a tombstone, an empty value, older than the
+// tombstone retention. Records without a key, such as transaction markers, are always
+// kept.
+fn is_removable(
+    index: &ShardIndex,
+    record: &Record,
+    stable_offset: u64,
+    now: u64,
+    tombstone_retention_ms: u64,
+) -> Result<bool, CommonError> {
+    if record.key.is_empty() {
+        return Ok(false);
+    }
+    match index.latest_offset_by_key(&record.key)? {
+        Some(latest) if latest > record.offset => return Ok(latest < stable_offset),
+        Some(latest) if latest == record.offset => {
+            return Ok(record.value.is_empty()
+                && now.saturating_sub(record.timestamp) >= tombstone_retention_ms);
+        }
+        _ => return Ok(false),
+    }
+}
+
+// Compacts the shards with the compact cleanup policy every interval_ms.
+pub async fn start_compaction_thread(
+    compactor: Arc<Compactor>,
+    interval_ms: u64,
+    stop_send: broadcast::Sender<bool>,
+) {
+    let mut stop_rx = stop_send.subscribe();
+    loop {
+        select! {
+            val = stop_rx.recv() => {
+                if let Ok(flag) = val {
+                    if flag {
+                        info!("{}", "Shard compaction thread stopped successfully.");
+                        break;
+                    }
+                }
+            }
+            _ = sleep(Duration::from_millis(interval_ms)) => {
+                compactor.compact();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Compactor;
+    use crate::{
+        index::engine::IndexEngine,
+        record::record::Record,
+        shard::{
+            manager::{CleanupPolicy, ShardConfig, ShardManager},
+            segment::FsyncPolicy,
+        },
+    };
+    use bytes::Bytes;
+    use common_base::tools::now_mills;
+    use std::{fs, sync::Arc};
+
+    fn build_record(key: &str, value: &str, timestamp: u64) -> Record {
+        let mut record = Record::build(
+            Bytes::from(key.to_string()),
+            Bytes::from(value.to_string()),
+            Vec::new(),
+        );
+        record.timestamp = timestamp;
+        return record;
+    }
+
+    fn offsets(records: &[Record]) -> Vec<u64> {
+        return records.iter().map(|record| record.offset).collect();
+    }
+
+    #[test]
+    fn compact_keeps_latest_record_of_every_key() {
+        let dir = std::env::temp_dir().join("robustmq-journal-compaction");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let engine =
+            Arc::new(IndexEngine::new(&dir.join("_index").display().to_string(), None).unwrap());
+        let shard_manager = Arc::new(ShardManager::new(
+            vec![dir.display().to_string()],
+            FsyncPolicy::EveryWrite,
+            0,
+            engine.clone(),
+        ));
+        let shard_name = "s1".to_string();
+        let now = now_mills() as u64;
+
+        // Segment 0: offsets 0-4, segment 1: offsets 5-8, segment 2 is active.
+        shard_manager
+            .append(
+                &shard_name,
+                vec![
+                    build_record("a", "a0", now),
+                    build_record("b", "b0", now),
+                    build_record("", "n0", now),
+                    build_record("c", "c0", now),
+                    build_record("a", "a1", now),
+                ],
+            )
+            .unwrap();
+        let shard = shard_manager.get_shard(&shard_name).unwrap();
+        shard.lock().unwrap().roll().unwrap();
+        shard_manager
+            .append(
+                &shard_name,
+                vec![
+                    build_record("c", "", now),
+                    build_record("b", "b1", now),
+                    build_record("d", "", now),
+                    build_record("a", "a2", now),
+                ],
+            )
+            .unwrap();
+        shard.lock().unwrap().roll().unwrap();
+        shard_manager
+            .append(&shard_name, vec![build_record("b", "b2", now)])
+            .unwrap();
+
+        let compactor = Compactor::new(shard_manager.clone(), 3600 * 1000);
+        // Nothing is compacted until the shard uses the compact policy, and the later
+        // records must be below the high watermark.
+        compactor.compact();
+        assert_eq!(
+            shard_manager.read(&shard_name, 0, 100, 0).unwrap().len(),
+            10
+        );
+        shard_manager.set_shard_config(
+            &shard_name,
+            ShardConfig {
+                cleanup_policy: CleanupPolicy::Compact,
+                tombstone_retention_ms: 0,
+                ..Default::default()
+            },
+        );
+        shard_manager.set_high_watermark(&shard_name, 9).unwrap();
+        assert_eq!(
+            compactor.compact_shard(&shard_name, 3600 * 1000).unwrap(),
+            3
+        );
+
+        // b2 at offset 9 is above the high watermark, so b0 and b1 are kept. The tombstones
+        // are kept while they are within the retention.
+        let records = shard_manager.read(&shard_name, 0, 100, 0).unwrap();
+        assert_eq!(offsets(&records), vec![1, 2, 5, 6, 7, 8, 9]);
+        assert!(shard_manager
+            .read_by_offset(&shard_name, 0)
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            shard_manager
+                .read_by_offset(&shard_name, 2)
+                .unwrap()
+                .unwrap()
+                .value,
+            Bytes::from("n0")
+        );
+        assert_eq!(
+            offsets(&shard_manager.read(&shard_name, 3, 2, 0).unwrap()),
+            vec![5, 6]
+        );
+        assert_eq!(
+            shard_manager
+                .read_by_key(&shard_name, b"a")
+                .unwrap()
+                .unwrap()
+                .offset,
+            8
+        );
+        assert_eq!(shard_manager.next_offset(&shard_name).unwrap(), 10);
+
+        // Once b2 is below the high watermark and the tombstones expired, only the latest
+        // record of a and b and the record without a key are left.
+        shard_manager.set_high_watermark(&shard_name, 10).unwrap();
+        assert_eq!(compactor.compact_shard(&shard_name, 0).unwrap(), 4);
+        let records = shard_manager.read(&shard_name, 0, 100, 0).unwrap();
+        assert_eq!(offsets(&records), vec![2, 8, 9]);
+        assert!(shard_manager
+            .read_by_key(&shard_name, b"c")
+            .unwrap()
+            .is_none());
+        assert!(shard_manager
+            .read_by_key(&shard_name, b"d")
+            .unwrap()
+            .is_none());
+        assert_eq!(compactor.compact_shard(&shard_name, 0).unwrap(), 0);
+
+        // The compacted segments and their indexes are used after a restart.
+        drop(shard);
+        let shard_manager = ShardManager::new(
+            vec![dir.display().to_string()],
+            FsyncPolicy::EveryWrite,
+            0,
+            engine,
+        );
+        shard_manager.load().unwrap();
+        let records = shard_manager.read(&shard_name, 0, 100, 0).unwrap();
+        assert_eq!(offsets(&records), vec![2, 8, 9]);
+        assert_eq!(shard_manager.start_offset(&shard_name).unwrap(), 0);
+        assert_eq!(shard_manager.next_offset(&shard_name).unwrap(), 10);
+        assert_eq!(
+            shard_manager
+                .read_by_key(&shard_name, b"b")
+                .unwrap()
+                .unwrap()
+                .offset,
+            9
+        );
+    }
+}
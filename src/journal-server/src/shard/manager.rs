@@ -0,0 +1,593 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{
+    producer_state::{AbortedTxn, ProducerAppend},
+    segment::{parse_segment_file_name, FsyncPolicy},
+    shard_log::{SealedSegment, ShardLog},
+};
+use crate::{
+    index::{engine::IndexEngine, SegmentIndexEntries},
+    record::record::{Record, RecordControl},
+};
+use common_base::error::common::CommonError;
+use dashmap::{mapref::entry::Entry, DashMap};
+use log::{error, info};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+use storage_adapter::compression::CompressionType;
+use tokio::{
+    select,
+    sync::{broadcast, futures::Notified, Notify},
+    time::sleep,
+};
+
+// Disk usage of a data folder, reported to the placement center for replica placement.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FoldUsage {
+    pub fold: String,
+    pub used_bytes: u64,
+    pub segment_num: u64,
+}
+
+// Progress of a shard on this node, reported to the placement center which rolls the
+// segments and elects their leaders from it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShardStatus {
+    pub shard_name: String,
+    pub next_offset: u64,
+    pub high_watermark: u64,
+    pub log_bytes: u64,
+}
+
+// How the old records of a shard are cleaned up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CleanupPolicy {
+    // Whole segments are removed, by the tiered storage retention.
+    #[default]
+    Delete,
+    // The sealed segments are compacted to the latest record of every key.
+    Compact,
+}
+
+impl CleanupPolicy {
+    pub fn build(policy: &str) -> Self {
+        match policy {
+            "compact" => CleanupPolicy::Compact,
+            _ => CleanupPolicy::Delete,
+        }
+    }
+}
+
+// Settings of a shard given when it was created in the placement center.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShardConfig {
+    pub cleanup_policy: CleanupPolicy,
+    // How long the tombstone of a deleted key is kept by compaction, 0 uses the default
+    // of the node.
+    pub tombstone_retention_ms: u64,
+    // The leader compresses the values of at least compression_min_size bytes.
+    pub compression: CompressionType,
+    pub compression_min_size: u64,
+}
+
+// Holds the logs of all shards stored on this node, spread over the data folders.
+pub struct ShardManager {
+    data_path: Vec<String>,
+    fsync_policy: FsyncPolicy,
+    // The active segment of a shard is rolled once it reaches this size, 0 never rolls.
+    segment_max_bytes: u64,
+    index_engine: Arc<IndexEngine>,
+    shards: DashMap<String, Arc<Mutex<ShardLog>>>,
+    shard_folds: DashMap<String, PathBuf>,
+    shard_configs: DashMap<String, ShardConfig>,
+    // Wakes up the fetches waiting for new records.
+    append_notify: Notify,
+}
+
+impl ShardManager {
+    pub fn new(
+        data_path: Vec<String>,
+        fsync_policy: FsyncPolicy,
+        segment_max_bytes: u64,
+        index_engine: Arc<IndexEngine>,
+    ) -> Self {
+        return ShardManager {
+            data_path,
+            fsync_policy,
+            segment_max_bytes,
+            index_engine,
+            shards: DashMap::with_capacity(8),
+            shard_folds: DashMap::with_capacity(8),
+            shard_configs: DashMap::with_capacity(8),
+            append_notify: Notify::new(),
+        };
+    }
+
+    // Opens every shard found in the data folders, recovering the active segment of each.
+    pub fn load(&self) -> Result<(), CommonError> {
+        for fold in self.data_path.iter() {
+            let fold = Path::new(fold);
+            if !fold.exists() {
+                continue;
+            }
+            for entry in fs::read_dir(fold)? {
+                let entry = entry?;
+                if !entry.file_type()?.is_dir() {
+                    continue;
+                }
+                let shard_name = entry.file_name().to_string_lossy().to_string();
+                // Folders starting with _ hold node data such as the index RocksDB.
+                if shard_name.starts_with('_') {
+                    continue;
+                }
+                let shard_log = ShardLog::open(
+                    &entry.path(),
+                    shard_name.clone(),
+                    self.fsync_policy,
+                    self.index_engine.clone(),
+                )?;
+                info!(
+                    "Shard {} is loaded from {}, next offset is {}",
+                    shard_name,
+                    entry.path().display(),
+                    shard_log.next_offset()
+                );
+                self.shard_folds
+                    .insert(shard_name.clone(), fold.to_path_buf());
+                self.shards
+                    .insert(shard_name, Arc::new(Mutex::new(shard_log)));
+            }
+        }
+        return Ok(());
+    }
+
+    pub fn get_shard(&self, shard_name: &String) -> Option<Arc<Mutex<ShardLog>>> {
+        if let Some(shard) = self.shards.get(shard_name) {
+            return Some(shard.clone());
+        }
+        return None;
+    }
+
+    pub fn shard_names(&self) -> Vec<String> {
+        return self.shards.iter().map(|raw| raw.key().clone()).collect();
+    }
+
+    pub fn set_shard_config(&self, shard_name: &str, config: ShardConfig) {
+        self.shard_configs.insert(shard_name.to_string(), config);
+    }
+
+    pub fn shard_config(&self, shard_name: &String) -> ShardConfig {
+        if let Some(config) = self.shard_configs.get(shard_name) {
+            return config.clone();
+        }
+        return ShardConfig::default();
+    }
+
+    pub fn shard_status(&self) -> Result<Vec<ShardStatus>, CommonError> {
+        let mut results = Vec::new();
+        for shard in self.shards.iter() {
+            let log = lock_shard(shard.value())?;
+            results.push(ShardStatus {
+                shard_name: shard.key().clone(),
+                next_offset: log.next_offset(),
+                high_watermark: log.high_watermark(),
+                log_bytes: log.size(),
+            });
+        }
+        return Ok(results);
+    }
+
+    // Creates the shard in the data folder holding the fewest shards.
+    pub fn create_shard(&self, shard_name: &String) -> Result<Arc<Mutex<ShardLog>>, CommonError> {
+        let fold = self.select_fold();
+        match self.shards.entry(shard_name.clone()) {
+            Entry::Occupied(shard) => return Ok(shard.get().clone()),
+            Entry::Vacant(entry) => {
+                let dir = fold.join(shard_name);
+                let shard_log = ShardLog::open(
+                    &dir,
+                    shard_name.clone(),
+                    self.fsync_policy,
+                    self.index_engine.clone(),
+                )?;
+                let shard = Arc::new(Mutex::new(shard_log));
+                entry.insert(shard.clone());
+                self.shard_folds.insert(shard_name.clone(), fold);
+                return Ok(shard);
+            }
+        }
+    }
+
+    pub fn append(
+        &self,
+        shard_name: &String,
+        records: Vec<Record>,
+    ) -> Result<Vec<u64>, CommonError> {
+        return self.append_with(shard_name, |log| log.append(records));
+    }
+
+    // Appends the records of an idempotent producer, a retry of records that were already
+    // written returns their offsets.
+    pub fn append_idempotent(
+        &self,
+        shard_name: &String,
+        producer: &ProducerAppend,
+        records: Vec<Record>,
+    ) -> Result<Vec<u64>, CommonError> {
+        return self.append_with(shard_name, |log| log.append_idempotent(producer, records));
+    }
+
+    // Appends the commit or abort marker of the transaction of the producer, returns the
+    // offset of the marker or None when the producer has no transaction on the shard.
+    pub fn end_transaction(
+        &self,
+        shard_name: &String,
+        producer_id: u64,
+        producer_epoch: u32,
+        commit: bool,
+    ) -> Result<Option<u64>, CommonError> {
+        let control = if commit {
+            RecordControl::Commit
+        } else {
+            RecordControl::Abort
+        };
+        let offsets = self.append_with(shard_name, |log| {
+            match log.end_transaction(producer_id, producer_epoch, control)? {
+                Some(offset) => Ok(vec![offset]),
+                None => Ok(Vec::new()),
+            }
+        })?;
+        return Ok(offsets.first().copied());
+    }
+
+    // Aborts the transactions ongoing on the shard for timeout_ms or more, returns the
+    // offsets of the abort markers.
+    pub fn abort_expired_transactions(
+        &self,
+        shard_name: &String,
+        timeout_ms: u64,
+    ) -> Result<Vec<u64>, CommonError> {
+        if self.get_shard(shard_name).is_none() {
+            return Ok(Vec::new());
+        }
+        return self.append_with(shard_name, |log| log.abort_expired_transactions(timeout_ms));
+    }
+
+    fn append_with<F>(&self, shard_name: &String, append: F) -> Result<Vec<u64>, CommonError>
+    where
+        F: FnOnce(&mut ShardLog) -> Result<Vec<u64>, CommonError>,
+    {
+        let shard = match self.get_shard(shard_name) {
+            Some(shard) => shard,
+            None => self.create_shard(shard_name)?,
+        };
+        let offsets = {
+            let mut log = lock_shard(&shard)?;
+            if self.segment_max_bytes > 0 && log.active_segment().size >= self.segment_max_bytes {
+                log.roll()?;
+            }
+            append(&mut log)?
+        };
+        self.append_notify.notify_waiters();
+        return Ok(offsets);
+    }
+
+    // Appends records fetched from the leader, keeping the offsets the leader assigned.
+    pub fn append_replicated(
+        &self,
+        shard_name: &String,
+        records: Vec<Record>,
+    ) -> Result<(), CommonError> {
+        let shard = match self.get_shard(shard_name) {
+            Some(shard) => shard,
+            None => self.create_shard(shard_name)?,
+        };
+        {
+            let mut log = lock_shard(&shard)?;
+            if self.segment_max_bytes > 0 && log.active_segment().size >= self.segment_max_bytes {
+                log.roll()?;
+            }
+            log.append_replicated(records)?;
+        }
+        self.append_notify.notify_waiters();
+        return Ok(());
+    }
+
+    // Resolves after the next successful append to any shard, or after the high
+    // watermark of any shard moved.
+    pub fn wait_append(&self) -> Notified<'_> {
+        return self.append_notify.notified();
+    }
+
+    pub fn next_offset(&self, shard_name: &String) -> Result<u64, CommonError> {
+        if let Some(shard) = self.get_shard(shard_name) {
+            return Ok(lock_shard(&shard)?.next_offset());
+        }
+        return Ok(0);
+    }
+
+    pub fn high_watermark(&self, shard_name: &String) -> Result<u64, CommonError> {
+        if let Some(shard) = self.get_shard(shard_name) {
+            return Ok(lock_shard(&shard)?.high_watermark());
+        }
+        return Ok(0);
+    }
+
+    pub fn last_stable_offset(&self, shard_name: &String) -> Result<u64, CommonError> {
+        if let Some(shard) = self.get_shard(shard_name) {
+            return Ok(lock_shard(&shard)?.last_stable_offset());
+        }
+        return Ok(0);
+    }
+
+    pub fn aborted_transactions(
+        &self,
+        shard_name: &String,
+        start_offset: u64,
+        end_offset: u64,
+    ) -> Result<Vec<AbortedTxn>, CommonError> {
+        if let Some(shard) = self.get_shard(shard_name) {
+            return Ok(lock_shard(&shard)?.aborted_transactions(start_offset, end_offset));
+        }
+        return Ok(Vec::new());
+    }
+
+    pub fn set_high_watermark(
+        &self,
+        shard_name: &String,
+        high_watermark: u64,
+    ) -> Result<(), CommonError> {
+        if let Some(shard) = self.get_shard(shard_name) {
+            if lock_shard(&shard)?.set_high_watermark(high_watermark)? {
+                self.append_notify.notify_waiters();
+            }
+        }
+        return Ok(());
+    }
+
+    pub fn truncate(&self, shard_name: &String, offset: u64) -> Result<(), CommonError> {
+        if let Some(shard) = self.get_shard(shard_name) {
+            return lock_shard(&shard)?.truncate_to(offset);
+        }
+        return Ok(());
+    }
+
+    pub fn read(
+        &self,
+        shard_name: &String,
+        offset: u64,
+        max_record: usize,
+        max_bytes: u64,
+    ) -> Result<Vec<Record>, CommonError> {
+        if let Some(shard) = self.get_shard(shard_name) {
+            return lock_shard(&shard)?.read(offset, max_record, max_bytes);
+        }
+        return Ok(Vec::new());
+    }
+
+    pub fn read_by_offset(
+        &self,
+        shard_name: &String,
+        offset: u64,
+    ) -> Result<Option<Record>, CommonError> {
+        if let Some(shard) = self.get_shard(shard_name) {
+            return lock_shard(&shard)?.read_by_offset(offset);
+        }
+        return Ok(None);
+    }
+
+    pub fn read_by_timestamp(
+        &self,
+        shard_name: &String,
+        start_timestamp: u64,
+        end_timestamp: u64,
+        max_record: usize,
+        max_bytes: u64,
+    ) -> Result<Vec<Record>, CommonError> {
+        if let Some(shard) = self.get_shard(shard_name) {
+            return lock_shard(&shard)?.read_by_timestamp(
+                start_timestamp,
+                end_timestamp,
+                max_record,
+                max_bytes,
+            );
+        }
+        return Ok(Vec::new());
+    }
+
+    pub fn read_by_key(
+        &self,
+        shard_name: &String,
+        key: &[u8],
+    ) -> Result<Option<Record>, CommonError> {
+        if let Some(shard) = self.get_shard(shard_name) {
+            return lock_shard(&shard)?.read_by_key(key);
+        }
+        return Ok(None);
+    }
+
+    pub fn offset_by_timestamp(
+        &self,
+        shard_name: &String,
+        timestamp: u64,
+    ) -> Result<Option<u64>, CommonError> {
+        if let Some(shard) = self.get_shard(shard_name) {
+            return lock_shard(&shard)?.offset_by_timestamp(timestamp);
+        }
+        return Ok(None);
+    }
+
+    pub fn sealed_segments(&self, shard_name: &String) -> Result<Vec<SealedSegment>, CommonError> {
+        if let Some(shard) = self.get_shard(shard_name) {
+            return Ok(lock_shard(&shard)?.sealed_segments());
+        }
+        return Ok(Vec::new());
+    }
+
+    // Returns the offset and time index entries of a segment of the shard.
+    pub fn segment_index(
+        &self,
+        shard_name: &String,
+        segment_no: u64,
+    ) -> Result<SegmentIndexEntries, CommonError> {
+        if let Some(shard) = self.get_shard(shard_name) {
+            return lock_shard(&shard)?.segment_index(segment_no);
+        }
+        return Ok(SegmentIndexEntries::default());
+    }
+
+    pub fn remove_oldest_segment(
+        &self,
+        shard_name: &String,
+        start_offset: u64,
+    ) -> Result<bool, CommonError> {
+        if let Some(shard) = self.get_shard(shard_name) {
+            return lock_shard(&shard)?.remove_oldest_segment(start_offset);
+        }
+        return Ok(false);
+    }
+
+    pub fn replace_segment(
+        &self,
+        shard_name: &String,
+        sealed: &SealedSegment,
+        compacted_path: &Path,
+    ) -> Result<bool, CommonError> {
+        if let Some(shard) = self.get_shard(shard_name) {
+            return lock_shard(&shard)?.replace_segment(sealed, compacted_path);
+        }
+        fs::remove_file(compacted_path)?;
+        return Ok(false);
+    }
+
+    pub fn start_offset(&self, shard_name: &String) -> Result<u64, CommonError> {
+        if let Some(shard) = self.get_shard(shard_name) {
+            return Ok(lock_shard(&shard)?.start_offset());
+        }
+        return Ok(0);
+    }
+
+    // Bytes of the segment files of the shard on this node.
+    pub fn local_size(&self, shard_name: &String) -> Result<u64, CommonError> {
+        if let Some(shard) = self.get_shard(shard_name) {
+            return Ok(lock_shard(&shard)?.size());
+        }
+        return Ok(0);
+    }
+
+    pub fn sync_all(&self) -> Result<(), CommonError> {
+        for shard in self.shards.iter() {
+            lock_shard(shard.value())?.sync()?;
+        }
+        return Ok(());
+    }
+
+    pub fn fsync_policy(&self) -> FsyncPolicy {
+        return self.fsync_policy;
+    }
+
+    pub fn index_engine(&self) -> Arc<IndexEngine> {
+        return self.index_engine.clone();
+    }
+
+    // Counts the segment files and their bytes in every data folder.
+    pub fn fold_usage(&self) -> Result<Vec<FoldUsage>, CommonError> {
+        let mut results = Vec::new();
+        for fold in self.data_path.iter() {
+            let mut usage = FoldUsage {
+                fold: fold.clone(),
+                ..Default::default()
+            };
+            let path = Path::new(fold);
+            if path.exists() {
+                for shard_dir in fs::read_dir(path)? {
+                    let shard_dir = shard_dir?;
+                    if !shard_dir.file_type()?.is_dir()
+                        || shard_dir.file_name().to_string_lossy().starts_with('_')
+                    {
+                        continue;
+                    }
+                    for entry in fs::read_dir(shard_dir.path())? {
+                        let entry = entry?;
+                        if parse_segment_file_name(&entry.file_name().to_string_lossy()).is_some() {
+                            usage.used_bytes += entry.metadata()?.len();
+                            usage.segment_num += 1;
+                        }
+                    }
+                }
+            }
+            results.push(usage);
+        }
+        return Ok(results);
+    }
+
+    fn select_fold(&self) -> PathBuf {
+        let mut selected = PathBuf::from(&self.data_path[0]);
+        let mut min_num = usize::MAX;
+        for fold in self.data_path.iter() {
+            let fold = PathBuf::from(fold);
+            let num = self
+                .shard_folds
+                .iter()
+                .filter(|raw| *raw.value() == fold)
+                .count();
+            if num < min_num {
+                min_num = num;
+                selected = fold;
+            }
+        }
+        return selected;
+    }
+}
+
+pub fn lock_shard(shard: &Arc<Mutex<ShardLog>>) -> Result<MutexGuard<ShardLog>, CommonError> {
+    match shard.lock() {
+        Ok(log) => return Ok(log),
+        Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+    }
+}
+
+// Syncs the dirty segments of all shards periodically when the fsync policy is interval,
+// so that data is flushed even if no further writes arrive.
+pub async fn start_fsync_thread(
+    shard_manager: Arc<ShardManager>,
+    stop_send: broadcast::Sender<bool>,
+) {
+    let interval_ms = match shard_manager.fsync_policy() {
+        FsyncPolicy::Interval(ms) => ms,
+        _ => return,
+    };
+    let mut stop_rx = stop_send.subscribe();
+    loop {
+        select! {
+            val = stop_rx.recv() => {
+                if let Ok(flag) = val {
+                    if flag {
+                        info!("{}", "Segment fsync thread stopped successfully.");
+                        break;
+                    }
+                }
+            }
+            _ = sleep(Duration::from_millis(interval_ms)) => {
+                if let Err(e) = shard_manager.sync_all() {
+                    error!("Failed to sync segment files, error message: {}", e.to_string());
+                }
+            }
+        }
+    }
+}
@@ -12,4 +12,8 @@
 // limitations under the License.
 
 
-pub mod segment;
\ No newline at end of file
+pub mod compaction;
+pub mod manager;
+pub mod producer_state;
+pub mod segment;
+pub mod shard_log;
@@ -0,0 +1,489 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::record::record::{Record, RecordControl};
+use bytes::{Buf, BufMut};
+use common_base::{
+    error::{common::CommonError, journal_server::JournalServerError},
+    tools::now_mills,
+};
+use std::collections::{HashMap, VecDeque};
+
+// Number of the latest batches of a producer whose offsets are kept, a retried batch is
+// only recognised as a duplicate while it is one of them.
+const PRODUCER_BATCH_CACHE_NUM: usize = 5;
+
+// Who appends the records of a produce request, as given in the request.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProducerAppend {
+    pub producer_id: u64,
+    pub producer_epoch: u32,
+    pub base_sequence: u32,
+    pub transactional: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppendCheck {
+    Append,
+    // The records were written before, with these offsets.
+    Duplicate(Vec<u64>),
+}
+
+// A transaction ended by an abort marker. Its records are in [first_offset, last_offset],
+// the last offset being the offset of the marker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AbortedTxn {
+    pub producer_id: u64,
+    pub first_offset: u64,
+    pub last_offset: u64,
+}
+
+// Records with consecutive sequence numbers written at consecutive offsets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ProducerBatch {
+    base_sequence: u32,
+    last_sequence: u32,
+    first_offset: u64,
+    last_offset: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct ProducerEntry {
+    epoch: u32,
+    batches: VecDeque<ProducerBatch>,
+    // Offset of the first record of the ongoing transaction.
+    txn_first_offset: Option<u64>,
+    // When this node saw the ongoing transaction start, it is not part of the snapshot and
+    // starts again when the state is restored.
+    txn_start_ms: u64,
+}
+
+impl ProducerEntry {
+    fn duplicate_offsets(&self, base_sequence: u32, num: u32) -> Option<Vec<u64>> {
+        let last_sequence = base_sequence.wrapping_add(num - 1);
+        for batch in self.batches.iter() {
+            let start = base_sequence.wrapping_sub(batch.base_sequence);
+            let end = last_sequence.wrapping_sub(batch.base_sequence);
+            if start <= end && end <= batch.last_sequence.wrapping_sub(batch.base_sequence) {
+                let first_offset = batch.first_offset + start as u64;
+                return Some((first_offset..first_offset + num as u64).collect());
+            }
+        }
+        return None;
+    }
+
+    fn next_sequence(&self) -> u32 {
+        match self.batches.back() {
+            Some(batch) => return batch.last_sequence.wrapping_add(1),
+            None => return 0,
+        }
+    }
+}
+
+// The sequence numbers and transactions of the idempotent producers writing to a shard.
+// It is derived from the records of the log, so leaders and followers hold the same state.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProducerState {
+    producers: HashMap<u64, ProducerEntry>,
+    aborted: Vec<AbortedTxn>,
+}
+
+impl ProducerState {
+    pub fn new() -> Self {
+        return ProducerState::default();
+    }
+
+    // Decides whether the num records of the producer are appended, are a retry of
+    // records already written, or are rejected.
+    pub fn check_append(
+        &self,
+        shard_name: &str,
+        producer: &ProducerAppend,
+        num: u32,
+    ) -> Result<AppendCheck, CommonError> {
+        let entry = match self.producers.get(&producer.producer_id) {
+            Some(entry) => entry,
+            // The state of the producer is unknown, it may have been written before the
+            // start of the log.
+            None => return Ok(AppendCheck::Append),
+        };
+        if producer.producer_epoch < entry.epoch {
+            return Err(fenced(
+                shard_name,
+                producer.producer_id,
+                producer.producer_epoch,
+            ));
+        }
+        if producer.producer_epoch > entry.epoch {
+            if entry.txn_first_offset.is_some() {
+                return Err(invalid_transaction(
+                    shard_name,
+                    producer.producer_id,
+                    format!(
+                        "the transaction of epoch {} has to be ended first",
+                        entry.epoch
+                    ),
+                ));
+            }
+            if producer.base_sequence != 0 {
+                return Err(out_of_order(
+                    shard_name,
+                    producer.producer_id,
+                    0,
+                    producer.base_sequence,
+                ));
+            }
+            return Ok(AppendCheck::Append);
+        }
+
+        if let Some(offsets) = entry.duplicate_offsets(producer.base_sequence, num) {
+            return Ok(AppendCheck::Duplicate(offsets));
+        }
+        if entry.txn_first_offset.is_some() && !producer.transactional {
+            return Err(invalid_transaction(
+                shard_name,
+                producer.producer_id,
+                "records outside of the ongoing transaction".to_string(),
+            ));
+        }
+        let expected = entry.next_sequence();
+        if producer.base_sequence != expected {
+            return Err(out_of_order(
+                shard_name,
+                producer.producer_id,
+                expected,
+                producer.base_sequence,
+            ));
+        }
+        return Ok(AppendCheck::Append);
+    }
+
+    // Returns whether the producer has a transaction on the shard that a marker of the
+    // epoch can end. A newer epoch ends the transaction of an older one.
+    pub fn check_end_txn(
+        &self,
+        shard_name: &str,
+        producer_id: u64,
+        producer_epoch: u32,
+    ) -> Result<bool, CommonError> {
+        let entry = match self.producers.get(&producer_id) {
+            Some(entry) => entry,
+            None => return Ok(false),
+        };
+        if producer_epoch < entry.epoch {
+            return Err(fenced(shard_name, producer_id, producer_epoch));
+        }
+        return Ok(entry.txn_first_offset.is_some());
+    }
+
+    // Updates the state with a record written to the log. Returns the transaction the
+    // record aborted, if it is an abort marker.
+    pub fn apply(&mut self, record: &Record) -> Option<AbortedTxn> {
+        if record.producer_id == 0 {
+            return None;
+        }
+        let entry = self.producers.entry(record.producer_id).or_default();
+        if record.producer_epoch > entry.epoch {
+            entry.epoch = record.producer_epoch;
+            entry.batches.clear();
+        }
+
+        if record.is_control() {
+            let first_offset = entry.txn_first_offset.take()?;
+            if record.control == RecordControl::Abort {
+                let aborted = AbortedTxn {
+                    producer_id: record.producer_id,
+                    first_offset,
+                    last_offset: record.offset,
+                };
+                self.aborted.push(aborted);
+                return Some(aborted);
+            }
+            return None;
+        }
+
+        if record.is_transactional && entry.txn_first_offset.is_none() {
+            entry.txn_first_offset = Some(record.offset);
+            entry.txn_start_ms = now_mills() as u64;
+        }
+        if let Some(batch) = entry.batches.back_mut() {
+            if batch.last_offset + 1 == record.offset
+                && batch.last_sequence.wrapping_add(1) == record.sequence
+            {
+                batch.last_sequence = record.sequence;
+                batch.last_offset = record.offset;
+                return None;
+            }
+        }
+        entry.batches.push_back(ProducerBatch {
+            base_sequence: record.sequence,
+            last_sequence: record.sequence,
+            first_offset: record.offset,
+            last_offset: record.offset,
+        });
+        if entry.batches.len() > PRODUCER_BATCH_CACHE_NUM {
+            entry.batches.pop_front();
+        }
+        return None;
+    }
+
+    // Offset of the first record of the oldest ongoing transaction, records from it on
+    // may still be aborted.
+    pub fn first_unstable_offset(&self) -> Option<u64> {
+        return self
+            .producers
+            .values()
+            .filter_map(|entry| entry.txn_first_offset)
+            .min();
+    }
+
+    // Returns the producer id and epoch of the transactions ongoing for timeout_ms or more.
+    pub fn expired_transactions(&self, now_ms: u64, timeout_ms: u64) -> Vec<(u64, u32)> {
+        return self
+            .producers
+            .iter()
+            .filter(|(_, entry)| {
+                entry.txn_first_offset.is_some()
+                    && now_ms.saturating_sub(entry.txn_start_ms) >= timeout_ms
+            })
+            .map(|(producer_id, entry)| (*producer_id, entry.epoch))
+            .collect();
+    }
+
+    pub fn set_aborted(&mut self, aborted: Vec<AbortedTxn>) {
+        self.aborted = aborted;
+    }
+
+    // Returns the aborted transactions with records in [start_offset, end_offset).
+    pub fn aborted_transactions(&self, start_offset: u64, end_offset: u64) -> Vec<AbortedTxn> {
+        return self
+            .aborted
+            .iter()
+            .filter(|txn| txn.last_offset >= start_offset && txn.first_offset < end_offset)
+            .copied()
+            .collect();
+    }
+
+    // | producer num(u32) | producer id(u64) | epoch(u32) | txn first offset(u64) |
+    // | batch num(u32) | base sequence(u32) | last sequence(u32) | first offset(u64) |
+    // | last offset(u64) | ...
+    // The aborted transactions are not part of it, they are stored one by one.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.put_u32(self.producers.len() as u32);
+        for (producer_id, entry) in self.producers.iter() {
+            buf.put_u64(*producer_id);
+            buf.put_u32(entry.epoch);
+            buf.put_u64(entry.txn_first_offset.unwrap_or(u64::MAX));
+            buf.put_u32(entry.batches.len() as u32);
+            for batch in entry.batches.iter() {
+                buf.put_u32(batch.base_sequence);
+                buf.put_u32(batch.last_sequence);
+                buf.put_u64(batch.first_offset);
+                buf.put_u64(batch.last_offset);
+            }
+        }
+        return buf;
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, CommonError> {
+        let mut buf = data;
+        let mut producers = HashMap::new();
+        let now_ms = now_mills() as u64;
+        let producer_num = read_u32(&mut buf, data)?;
+        for _ in 0..producer_num {
+            check_remaining(buf, 24, data)?;
+            let producer_id = buf.get_u64();
+            let epoch = buf.get_u32();
+            let txn_first_offset = match buf.get_u64() {
+                u64::MAX => None,
+                offset => Some(offset),
+            };
+            let txn_start_ms = match txn_first_offset {
+                Some(_) => now_ms,
+                None => 0,
+            };
+            let batch_num = buf.get_u32();
+            let mut batches = VecDeque::new();
+            for _ in 0..batch_num {
+                check_remaining(buf, 24, data)?;
+                batches.push_back(ProducerBatch {
+                    base_sequence: buf.get_u32(),
+                    last_sequence: buf.get_u32(),
+                    first_offset: buf.get_u64(),
+                    last_offset: buf.get_u64(),
+                });
+            }
+            producers.insert(
+                producer_id,
+                ProducerEntry {
+                    epoch,
+                    batches,
+                    txn_first_offset,
+                    txn_start_ms,
+                },
+            );
+        }
+        return Ok(ProducerState {
+            producers,
+            aborted: Vec::new(),
+        });
+    }
+}
+
+fn check_remaining(buf: &[u8], len: usize, data: &[u8]) -> Result<(), CommonError> {
+    if buf.remaining() < len {
+        return Err(JournalServerError::IndexDecodeError(format!("{:?}", data)).into());
+    }
+    return Ok(());
+}
+
+fn read_u32(buf: &mut &[u8], data: &[u8]) -> Result<u32, CommonError> {
+    check_remaining(buf, 4, data)?;
+    return Ok(buf.get_u32());
+}
+
+fn fenced(shard_name: &str, producer_id: u64, producer_epoch: u32) -> CommonError {
+    return JournalServerError::ProducerFenced(shard_name.to_string(), producer_id, producer_epoch)
+        .into();
+}
+
+fn out_of_order(shard_name: &str, producer_id: u64, expected: u32, received: u32) -> CommonError {
+    return JournalServerError::OutOfOrderSequence(
+        shard_name.to_string(),
+        producer_id,
+        expected,
+        received,
+    )
+    .into();
+}
+
+fn invalid_transaction(shard_name: &str, producer_id: u64, message: String) -> CommonError {
+    return JournalServerError::InvalidTransaction(shard_name.to_string(), producer_id, message)
+        .into();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AbortedTxn, AppendCheck, ProducerAppend, ProducerState};
+    use crate::record::record::{Record, RecordControl};
+    use common_base::error::{common::CommonError, journal_server::JournalServerError};
+
+    fn build_record(offset: u64, epoch: u32, sequence: u32, transactional: bool) -> Record {
+        return Record {
+            offset,
+            producer_id: 1,
+            producer_epoch: epoch,
+            sequence,
+            is_transactional: transactional,
+            ..Default::default()
+        };
+    }
+
+    fn producer(epoch: u32, base_sequence: u32, transactional: bool) -> ProducerAppend {
+        return ProducerAppend {
+            producer_id: 1,
+            producer_epoch: epoch,
+            base_sequence,
+            transactional,
+        };
+    }
+
+    #[test]
+    fn producer_state_sequence_and_fencing() {
+        let mut state = ProducerState::new();
+        assert_eq!(
+            state.check_append("s1", &producer(0, 0, false), 3).unwrap(),
+            AppendCheck::Append
+        );
+        for i in 0..3 {
+            state.apply(&build_record(10 + i, 0, i as u32, false));
+        }
+
+        // A retry of written records returns their offsets.
+        assert_eq!(
+            state.check_append("s1", &producer(0, 0, false), 3).unwrap(),
+            AppendCheck::Duplicate(vec![10, 11, 12])
+        );
+        assert_eq!(
+            state.check_append("s1", &producer(0, 1, false), 2).unwrap(),
+            AppendCheck::Duplicate(vec![11, 12])
+        );
+        assert_eq!(
+            state.check_append("s1", &producer(0, 3, false), 1).unwrap(),
+            AppendCheck::Append
+        );
+        assert!(matches!(
+            state.check_append("s1", &producer(0, 5, false), 1),
+            Err(CommonError::JournalServerError(
+                JournalServerError::OutOfOrderSequence(_, 1, 3, 5)
+            ))
+        ));
+
+        // A new epoch starts its sequence again and fences the older one.
+        assert!(state.check_append("s1", &producer(1, 3, false), 1).is_err());
+        state.apply(&build_record(13, 1, 0, false));
+        assert!(matches!(
+            state.check_append("s1", &producer(0, 3, false), 1),
+            Err(CommonError::JournalServerError(
+                JournalServerError::ProducerFenced(_, 1, 0)
+            ))
+        ));
+        assert_eq!(
+            state.check_append("s1", &producer(1, 1, false), 1).unwrap(),
+            AppendCheck::Append
+        );
+
+        let decoded = ProducerState::decode(&state.encode()).unwrap();
+        assert_eq!(decoded, state);
+    }
+
+    #[test]
+    fn producer_state_transactions() {
+        let mut state = ProducerState::new();
+        state.apply(&build_record(5, 0, 0, true));
+        state.apply(&build_record(6, 0, 1, true));
+        assert_eq!(state.first_unstable_offset(), Some(5));
+        assert!(state.expired_transactions(0, 1000).is_empty());
+        assert_eq!(state.expired_transactions(u64::MAX, 1000), vec![(1, 0)]);
+        assert!(state.check_append("s1", &producer(0, 2, false), 1).is_err());
+        assert!(state.check_end_txn("s1", 1, 0).unwrap());
+
+        let marker = Record {
+            offset: 7,
+            ..Record::build_control(1, 0, RecordControl::Abort)
+        };
+        let aborted = AbortedTxn {
+            producer_id: 1,
+            first_offset: 5,
+            last_offset: 7,
+        };
+        assert_eq!(state.apply(&marker), Some(aborted));
+        assert_eq!(state.first_unstable_offset(), None);
+        assert!(!state.check_end_txn("s1", 1, 0).unwrap());
+        assert_eq!(state.aborted_transactions(0, 6), vec![aborted]);
+        assert!(state.aborted_transactions(8, 20).is_empty());
+
+        // The next transaction commits, the marker of a newer epoch fences the producer.
+        state.apply(&build_record(8, 0, 2, true));
+        assert!(state.check_append("s1", &producer(1, 0, true), 1).is_err());
+        let marker = Record {
+            offset: 9,
+            ..Record::build_control(1, 1, RecordControl::Commit)
+        };
+        assert_eq!(state.apply(&marker), None);
+        assert_eq!(state.first_unstable_offset(), None);
+        assert!(state.check_end_txn("s1", 1, 0).is_err());
+        assert_eq!(state.aborted_transactions(0, 20).len(), 1);
+    }
+}
@@ -11,4 +11,548 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::record::record::Record;
+use bytes::{Buf, BufMut};
+use common_base::error::{common::CommonError, journal_server::JournalServerError};
+use log::warn;
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufReader, ErrorKind, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
+// | magic(u32) | version(u32) | start offset(u64) |
+const SEGMENT_MAGIC: u32 = 0x524A_5347;
+const SEGMENT_VERSION: u32 = 1;
+pub const SEGMENT_HEADER_LEN: u64 = 16;
+
+// Every record is framed as | length(u32) | crc32(u32) | record body |
+pub const FRAME_HEADER_LEN: u64 = 8;
+
+pub const SEGMENT_FILE_SUFFIX: &str = "log";
+
+// A compacted copy of a segment is written next to it with this suffix appended, and
+// renamed over the segment once it is complete.
+pub const COMPACTING_FILE_SUFFIX: &str = "compacting";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsyncPolicy {
+    // Sync the file after every append.
+    EveryWrite,
+    // Sync the file when the given milliseconds have passed since the last sync.
+    Interval(u64),
+    // Leave flushing to the operating system.
+    Os,
+}
+
+impl FsyncPolicy {
+    pub fn build(policy: &str, interval_ms: u64) -> Self {
+        match policy {
+            "every_write" => FsyncPolicy::EveryWrite,
+            "os" => FsyncPolicy::Os,
+            _ => FsyncPolicy::Interval(interval_ms),
+        }
+    }
+}
+
+pub fn segment_file_name(segment_no: u64) -> String {
+    return format!("{:020}.{}", segment_no, SEGMENT_FILE_SUFFIX);
+}
+
+pub fn parse_segment_file_name(file_name: &str) -> Option<u64> {
+    if let Some(no) = file_name.strip_suffix(&format!(".{}", SEGMENT_FILE_SUFFIX)) {
+        return no.parse::<u64>().ok();
+    }
+    return None;
+}
+
+pub fn compacting_file_path(segment_path: &Path) -> PathBuf {
+    let mut path = segment_path.as_os_str().to_os_string();
+    path.push(format!(".{}", COMPACTING_FILE_SUFFIX));
+    return PathBuf::from(path);
+}
+
+// An append-only file holding the records of a shard from start_offset on. Offsets grow
+// by one with every record, except in a compacted segment where the records replaced by
+// a later record with the same key have been removed.
+pub struct SegmentFile {
+    pub segment_no: u64,
+    pub path: PathBuf,
+    pub start_offset: u64,
+    pub next_offset: u64,
+    pub size: u64,
+    file: File,
+    fsync_policy: FsyncPolicy,
+    last_sync: Instant,
+    dirty: bool,
+}
+
+impl SegmentFile {
+    pub fn create(
+        dir: &Path,
+        segment_no: u64,
+        start_offset: u64,
+        fsync_policy: FsyncPolicy,
+    ) -> Result<Self, CommonError> {
+        return SegmentFile::create_at(
+            &dir.join(segment_file_name(segment_no)),
+            segment_no,
+            start_offset,
+            fsync_policy,
+        );
+    }
+
+    pub fn create_at(
+        path: &Path,
+        segment_no: u64,
+        start_offset: u64,
+        fsync_policy: FsyncPolicy,
+    ) -> Result<Self, CommonError> {
+        let path = path.to_path_buf();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create_new(true)
+            .open(&path)?;
+        let mut header = Vec::with_capacity(SEGMENT_HEADER_LEN as usize);
+        header.put_u32(SEGMENT_MAGIC);
+        header.put_u32(SEGMENT_VERSION);
+        header.put_u64(start_offset);
+        file.write_all(&header)?;
+        file.sync_all()?;
+
+        return Ok(SegmentFile {
+            segment_no,
+            path,
+            start_offset,
+            next_offset: start_offset,
+            size: SEGMENT_HEADER_LEN,
+            file,
+            fsync_policy,
+            last_sync: Instant::now(),
+            dirty: false,
+        });
+    }
+
+    // Opens an existing segment without scanning its records, next_offset is only
+    // known after recover() or when it is set from the following segment.
+    pub fn open(
+        path: &Path,
+        segment_no: u64,
+        fsync_policy: FsyncPolicy,
+    ) -> Result<Self, CommonError> {
+        let file = OpenOptions::new().read(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        let mut header = [0u8; SEGMENT_HEADER_LEN as usize];
+        let mut reader = file.try_clone()?;
+        reader.seek(SeekFrom::Start(0))?;
+        if size < SEGMENT_HEADER_LEN || reader.read_exact(&mut header).is_err() {
+            return Err(JournalServerError::InvalidSegmentFile(path.display().to_string()).into());
+        }
+        let mut buf = &header[..];
+        let magic = buf.get_u32();
+        let version = buf.get_u32();
+        let start_offset = buf.get_u64();
+        if magic != SEGMENT_MAGIC || version != SEGMENT_VERSION {
+            return Err(JournalServerError::InvalidSegmentFile(path.display().to_string()).into());
+        }
+
+        return Ok(SegmentFile {
+            segment_no,
+            path: path.to_path_buf(),
+            start_offset,
+            next_offset: start_offset,
+            size,
+            file,
+            fsync_policy,
+            last_sync: Instant::now(),
+            dirty: false,
+        });
+    }
+
+    // Scans every frame of the segment. A write torn by a crash or power loss leaves an
+    // incomplete or corrupted frame at the tail, which is truncated.
+    pub fn recover(&mut self) -> Result<(), CommonError> {
+        let file_len = self.file.metadata()?.len();
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        reader.seek(SeekFrom::Start(SEGMENT_HEADER_LEN))?;
+        let mut position = SEGMENT_HEADER_LEN;
+        let mut next_offset = self.start_offset;
+        while let Some((record, frame_len)) = read_frame(&mut reader, file_len - position)? {
+            if record.offset < next_offset {
+                break;
+            }
+            next_offset = record.offset + 1;
+            position += frame_len;
+        }
+
+        if position < file_len {
+            warn!(
+                "Segment file {} has {} invalid bytes at the tail, truncate it to {} bytes",
+                self.path.display(),
+                file_len - position,
+                position
+            );
+            self.file.set_len(position)?;
+            self.file.sync_all()?;
+        }
+        self.size = position;
+        self.next_offset = next_offset;
+        return Ok(());
+    }
+
+    // Appends records whose offsets grow from next_offset on, and returns the file
+    // position of every record.
+    pub fn append(&mut self, records: &[Record]) -> Result<Vec<u64>, CommonError> {
+        let mut buf = Vec::new();
+        let mut positions = Vec::new();
+        let mut position = self.size;
+        let mut next_offset = self.next_offset;
+        for record in records {
+            if record.offset < next_offset {
+                return Err(
+                    JournalServerError::SegmentOffsetMismatch(record.offset, next_offset).into(),
+                );
+            }
+            let body = record.encode();
+            buf.put_u32(body.len() as u32);
+            buf.put_u32(crc32fast::hash(&body));
+            buf.put_slice(&body);
+            positions.push(position);
+            position += FRAME_HEADER_LEN + body.len() as u64;
+            next_offset = record.offset + 1;
+        }
+
+        if let Err(e) = self.file.write_all(&buf) {
+            // Drop the partially written frames so that the next append starts at a frame boundary.
+            let _ = self.file.set_len(self.size);
+            return Err(e.into());
+        }
+        self.size = position;
+        self.next_offset = next_offset;
+        self.dirty = true;
+
+        match self.fsync_policy {
+            FsyncPolicy::EveryWrite => self.sync()?,
+            FsyncPolicy::Interval(ms) => {
+                if self.last_sync.elapsed() >= Duration::from_millis(ms) {
+                    self.sync()?;
+                }
+            }
+            FsyncPolicy::Os => {}
+        }
+        return Ok(positions);
+    }
+
+    // Cuts the segment off at the frame at position, which holds the record next_offset.
+    pub fn truncate(&mut self, position: u64, next_offset: u64) -> Result<(), CommonError> {
+        self.file.set_len(position)?;
+        self.file.sync_all()?;
+        self.size = position;
+        self.next_offset = next_offset;
+        self.dirty = false;
+        return Ok(());
+    }
+
+    pub fn sync(&mut self) -> Result<(), CommonError> {
+        if self.dirty {
+            self.file.sync_data()?;
+            self.dirty = false;
+        }
+        self.last_sync = Instant::now();
+        return Ok(());
+    }
+
+    // Reads records from the frame at position, returning each record with its position.
+    // The first record is always returned even if it is larger than max_bytes.
+    pub fn read_at(
+        &self,
+        position: u64,
+        max_record: usize,
+        max_bytes: u64,
+    ) -> Result<Vec<(u64, Record)>, CommonError> {
+        let mut results = Vec::new();
+        if position < SEGMENT_HEADER_LEN || position >= self.size {
+            return Ok(results);
+        }
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        reader.seek(SeekFrom::Start(position))?;
+        let mut position = position;
+        let mut total_bytes = 0;
+        while position < self.size && results.len() < max_record {
+            let (record, frame_len) = match read_frame(&mut reader, self.size - position)? {
+                Some(frame) => frame,
+                None => {
+                    return Err(JournalServerError::InvalidSegmentFile(format!(
+                        "{}, the frame at position {} is corrupted",
+                        self.path.display(),
+                        position
+                    ))
+                    .into());
+                }
+            };
+            total_bytes += record.value.len() as u64;
+            if max_bytes > 0 && total_bytes > max_bytes && !results.is_empty() {
+                break;
+            }
+            results.push((position, record));
+            position += frame_len;
+        }
+        return Ok(results);
+    }
+
+    // Returns the file position of the first record at or after offset by scanning the
+    // frames.
+    pub fn position_of(&self, offset: u64) -> Result<Option<u64>, CommonError> {
+        return self.position_from(SEGMENT_HEADER_LEN, offset);
+    }
+
+    // Returns the file position of the first record at or after offset by scanning the
+    // frames from position on, which must be the position of a record before it.
+    pub fn position_from(&self, position: u64, offset: u64) -> Result<Option<u64>, CommonError> {
+        if offset < self.start_offset || offset >= self.next_offset {
+            return Ok(None);
+        }
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        reader.seek(SeekFrom::Start(position))?;
+        let mut position = position;
+        while position < self.size {
+            let mut frame_header = [0u8; FRAME_HEADER_LEN as usize];
+            reader.read_exact(&mut frame_header)?;
+            let len = (&frame_header[..]).get_u32() as u64;
+            let mut offset_buf = [0u8; 8];
+            reader.read_exact(&mut offset_buf)?;
+            if u64::from_be_bytes(offset_buf) >= offset {
+                return Ok(Some(position));
+            }
+            reader.seek_relative(len as i64 - 8)?;
+            position += FRAME_HEADER_LEN + len;
+        }
+        return Ok(None);
+    }
+
+    pub fn read_by_offset(&self, offset: u64) -> Result<Option<Record>, CommonError> {
+        if let Some(position) = self.position_of(offset)? {
+            if let Some((_, record)) = self.read_at(position, 1, 0)?.into_iter().next() {
+                if record.offset == offset {
+                    return Ok(Some(record));
+                }
+            }
+        }
+        return Ok(None);
+    }
+}
+
+// Reads the next frame, returns None if the frame is incomplete or does not pass the
+// crc check. remaining is the number of bytes left in the file from the frame on.
+fn read_frame(
+    reader: &mut BufReader<File>,
+    remaining: u64,
+) -> Result<Option<(Record, u64)>, CommonError> {
+    if remaining < FRAME_HEADER_LEN {
+        return Ok(None);
+    }
+    let mut frame_header = [0u8; FRAME_HEADER_LEN as usize];
+    if let Err(e) = reader.read_exact(&mut frame_header) {
+        if e.kind() == ErrorKind::UnexpectedEof {
+            return Ok(None);
+        }
+        return Err(e.into());
+    }
+    let mut buf = &frame_header[..];
+    let len = buf.get_u32() as u64;
+    let crc = buf.get_u32();
+    if len > remaining - FRAME_HEADER_LEN {
+        return Ok(None);
+    }
+
+    let mut body = vec![0u8; len as usize];
+    if let Err(e) = reader.read_exact(&mut body) {
+        if e.kind() == ErrorKind::UnexpectedEof {
+            return Ok(None);
+        }
+        return Err(e.into());
+    }
+    if crc32fast::hash(&body) != crc {
+        return Ok(None);
+    }
+    match Record::decode(&body) {
+        Ok(record) => return Ok(Some((record, FRAME_HEADER_LEN + len))),
+        Err(_) => return Ok(None),
+    }
+}
+
+// Decodes the frames of a byte range of a segment file that starts at a frame boundary
+// at position, returning each record with its position. An incomplete frame at the end
+// of the range is left for the next read.
+pub fn decode_frames(data: &[u8], position: u64) -> Result<Vec<(u64, Record)>, CommonError> {
+    let mut results = Vec::new();
+    let mut buf = data;
+    let mut position = position;
+    while buf.len() as u64 >= FRAME_HEADER_LEN {
+        let mut frame_header = &buf[..FRAME_HEADER_LEN as usize];
+        let len = frame_header.get_u32() as usize;
+        let crc = frame_header.get_u32();
+        let frame_len = FRAME_HEADER_LEN as usize + len;
+        if buf.len() < frame_len {
+            break;
+        }
+        let body = &buf[FRAME_HEADER_LEN as usize..frame_len];
+        if crc32fast::hash(body) != crc {
+            return Err(JournalServerError::InvalidSegmentFile(format!(
+                "the frame at position {} is corrupted",
+                position
+            ))
+            .into());
+        }
+        results.push((position, Record::decode(body)?));
+        buf = &buf[frame_len..];
+        position += frame_len as u64;
+    }
+    return Ok(results);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        compacting_file_path, decode_frames, parse_segment_file_name, segment_file_name,
+        FsyncPolicy, SegmentFile, SEGMENT_HEADER_LEN,
+    };
+    use crate::record::record::Record;
+    use bytes::Bytes;
+    use std::{fs, io::Write, path::PathBuf};
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("robustmq-journal-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        return dir;
+    }
+
+    fn build_records(start_offset: u64, num: u64) -> Vec<Record> {
+        let mut records = Vec::new();
+        for i in start_offset..start_offset + num {
+            let mut record = Record::build(
+                Bytes::from(format!("k{}", i)),
+                Bytes::from(format!("v{}", i)),
+                Vec::new(),
+            );
+            record.offset = i;
+            record.timestamp = 1000 + i;
+            records.push(record);
+        }
+        return records;
+    }
+
+    #[test]
+    fn fsync_policy_build() {
+        assert_eq!(
+            FsyncPolicy::build("every_write", 10),
+            FsyncPolicy::EveryWrite
+        );
+        assert_eq!(FsyncPolicy::build("os", 10), FsyncPolicy::Os);
+        assert_eq!(
+            FsyncPolicy::build("interval", 10),
+            FsyncPolicy::Interval(10)
+        );
+    }
+
+    #[test]
+    fn segment_append_read() {
+        let dir = test_dir("segment-append-read");
+        let mut segment = SegmentFile::create(&dir, 0, 100, FsyncPolicy::EveryWrite).unwrap();
+        let positions = segment.append(&build_records(100, 5)).unwrap();
+        assert_eq!(positions.len(), 5);
+        assert_eq!(positions[0], SEGMENT_HEADER_LEN);
+        assert_eq!(segment.next_offset, 105);
+
+        assert!(segment.append(&build_records(104, 1)).is_err());
+
+        let res = segment.read_at(positions[2], 10, 0).unwrap();
+        assert_eq!(res.len(), 3);
+        assert_eq!(res[0].0, positions[2]);
+        assert_eq!(res[0].1.offset, 102);
+        assert_eq!(res[0].1.value, Bytes::from("v102"));
+
+        // Every value has 4 bytes, the first record is returned even beyond max_bytes.
+        assert_eq!(segment.read_at(positions[0], 10, 9).unwrap().len(), 2);
+        assert_eq!(segment.read_at(positions[0], 10, 1).unwrap().len(), 1);
+
+        let record = segment.read_by_offset(104).unwrap().unwrap();
+        assert_eq!(record.key, Bytes::from("k104"));
+        assert_eq!(record.timestamp, 1104);
+        assert!(segment.read_by_offset(105).unwrap().is_none());
+        assert!(segment.read_by_offset(99).unwrap().is_none());
+
+        // A range read of the file decodes the complete frames only.
+        let data = fs::read(&segment.path).unwrap();
+        let end = positions[3] as usize + 3;
+        let frames = decode_frames(&data[positions[1] as usize..end], positions[1]).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1].0, positions[2]);
+        assert_eq!(frames[1].1.offset, 102);
+        let mut corrupted = data[positions[0] as usize..].to_vec();
+        corrupted[10] ^= 0xff;
+        assert!(decode_frames(&corrupted, positions[0]).is_err());
+    }
+
+    #[test]
+    fn segment_recover_truncate_tail() {
+        let dir = test_dir("segment-recover");
+        let path = {
+            let mut segment = SegmentFile::create(&dir, 3, 0, FsyncPolicy::Os).unwrap();
+            segment.append(&build_records(0, 3)).unwrap();
+            segment.sync().unwrap();
+            segment.path.clone()
+        };
+        let valid_len = fs::metadata(&path).unwrap().len();
+
+        // Simulate a write torn by a power loss.
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[0, 0, 0, 40, 1, 2, 3, 4, 5, 6]).unwrap();
+        drop(file);
+
+        let mut segment = SegmentFile::open(&path, 3, FsyncPolicy::Os).unwrap();
+        assert_eq!(segment.start_offset, 0);
+        segment.recover().unwrap();
+        assert_eq!(segment.next_offset, 3);
+        assert_eq!(segment.size, valid_len);
+        assert_eq!(fs::metadata(&path).unwrap().len(), valid_len);
+
+        segment.append(&build_records(3, 1)).unwrap();
+        assert_eq!(segment.read_by_offset(3).unwrap().unwrap().offset, 3);
+    }
+
+    #[test]
+    fn segment_offset_gaps() {
+        let dir = test_dir("segment-gaps");
+        let path = compacting_file_path(&dir.join(segment_file_name(0)));
+        assert!(path.to_string_lossy().ends_with(".log.compacting"));
+        assert_eq!(
+            parse_segment_file_name(&path.file_name().unwrap().to_string_lossy()),
+            None
+        );
+
+        let mut segment = SegmentFile::create_at(&path, 0, 0, FsyncPolicy::Os).unwrap();
+        segment.append(&build_records(0, 2)).unwrap();
+        let mut records = build_records(5, 1);
+        records.extend(build_records(8, 2));
+        let positions = segment.append(&records).unwrap();
+        assert_eq!(segment.next_offset, 10);
+        assert!(segment.append(&build_records(9, 1)).is_err());
+
+        // A missing offset is served by the record after it.
+        assert_eq!(segment.position_of(2).unwrap(), Some(positions[0]));
+        assert_eq!(segment.position_of(6).unwrap(), Some(positions[1]));
+        assert!(segment.read_by_offset(6).unwrap().is_none());
+        assert_eq!(segment.read_by_offset(8).unwrap().unwrap().offset, 8);
+        segment.sync().unwrap();
+        drop(segment);
+
+        let mut segment = SegmentFile::open(&path, 0, FsyncPolicy::Os).unwrap();
+        segment.recover().unwrap();
+        assert_eq!(segment.next_offset, 10);
+        assert_eq!(segment.size, fs::metadata(&path).unwrap().len());
+    }
+}
@@ -0,0 +1,1005 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{
+    producer_state::{AbortedTxn, AppendCheck, ProducerAppend, ProducerState},
+    segment::{
+        parse_segment_file_name, FsyncPolicy, SegmentFile, COMPACTING_FILE_SUFFIX, FRAME_HEADER_LEN,
+    },
+};
+use crate::{
+    index::{engine::IndexEngine, SegmentIndexEntries, SegmentIndexMeta, ShardIndex},
+    record::record::{Record, RecordControl},
+};
+use common_base::{error::common::CommonError, tools::now_mills};
+use log::info;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+const SCAN_BATCH_RECORD_NUM: usize = 100;
+
+// A segment that is no longer appended to and whose records are all below the high
+// watermark, so it can be copied to the remote tier as is.
+#[derive(Debug, Clone)]
+pub struct SealedSegment {
+    pub segment_no: u64,
+    pub path: PathBuf,
+    pub start_offset: u64,
+    pub next_offset: u64,
+    pub size: u64,
+    pub max_timestamp: u64,
+}
+
+// The segments of a shard stored in one folder, records are only appended to the last
+// segment.
+pub struct ShardLog {
+    pub shard_name: String,
+    pub dir: PathBuf,
+    segments: BTreeMap<u64, SegmentFile>,
+    index: ShardIndex,
+    // Index state of every segment, keyed by the start offset of the segment.
+    index_metas: BTreeMap<u64, SegmentIndexMeta>,
+    // Records below the high watermark are on every in-sync replica and can be read by
+    // consumers.
+    high_watermark: u64,
+    fsync_policy: FsyncPolicy,
+    producer_state: ProducerState,
+}
+
+impl ShardLog {
+    pub fn open(
+        dir: &Path,
+        shard_name: String,
+        fsync_policy: FsyncPolicy,
+        index_engine: Arc<IndexEngine>,
+    ) -> Result<Self, CommonError> {
+        fs::create_dir_all(dir)?;
+        let mut segment_files = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if let Some(segment_no) = parse_segment_file_name(&file_name) {
+                segment_files.push((segment_no, entry.path()));
+            } else if file_name.ends_with(COMPACTING_FILE_SUFFIX) {
+                // A compaction that did not finish, the segment itself is untouched.
+                fs::remove_file(entry.path())?;
+            }
+        }
+        segment_files.sort_by_key(|(segment_no, _)| *segment_no);
+
+        let mut segments: Vec<SegmentFile> = Vec::new();
+        for (segment_no, path) in segment_files {
+            segments.push(SegmentFile::open(&path, segment_no, fsync_policy)?);
+        }
+        // Only the active segment can have a torn tail, the other segments end where the
+        // following segment starts.
+        for i in 1..segments.len() {
+            segments[i - 1].next_offset = segments[i].start_offset;
+        }
+        if let Some(active) = segments.last_mut() {
+            active.recover()?;
+        } else {
+            segments.push(SegmentFile::create(dir, 0, 0, fsync_policy)?);
+        }
+
+        // The indexes are written after the segment, so they can lag behind it after a
+        // crash, or run ahead of it when the torn tail of the active segment was cut off.
+        let index = ShardIndex::new(index_engine, shard_name.clone());
+        let mut index_metas = BTreeMap::new();
+        let segment_num = segments.len();
+        for (i, segment) in segments.iter().enumerate() {
+            let is_active = i + 1 == segment_num;
+            let meta = match index.get_meta(segment.segment_no)? {
+                Some(meta) if meta.next_offset == segment.next_offset => meta,
+                Some(mut meta) if is_active && meta.next_offset > segment.next_offset => {
+                    index.truncate(segment, &mut meta)?;
+                    meta
+                }
+                _ => {
+                    info!(
+                        "Rebuilding the indexes of segment {} of shard {}",
+                        segment.segment_no, shard_name
+                    );
+                    let end_offset = if is_active {
+                        u64::MAX
+                    } else {
+                        segment.next_offset
+                    };
+                    index.rebuild(segment, end_offset)?
+                }
+            };
+            index_metas.insert(segment.start_offset, meta);
+        }
+
+        // The high watermark is checkpointed after the records are written, it never runs
+        // ahead of the log unless the tail was cut off.
+        let next_offset = segments[segment_num - 1].next_offset;
+        let high_watermark = match index.get_high_watermark()? {
+            Some(high_watermark) => high_watermark.min(next_offset),
+            None => next_offset,
+        };
+
+        let mut log = ShardLog {
+            shard_name,
+            dir: dir.to_path_buf(),
+            segments: segments
+                .into_iter()
+                .map(|segment| (segment.start_offset, segment))
+                .collect(),
+            index,
+            index_metas,
+            high_watermark,
+            fsync_policy,
+            producer_state: ProducerState::new(),
+        };
+        log.load_producer_state()?;
+        return Ok(log);
+    }
+
+    pub fn start_offset(&self) -> u64 {
+        if let Some((_, segment)) = self.segments.first_key_value() {
+            return segment.start_offset;
+        }
+        return 0;
+    }
+
+    pub fn next_offset(&self) -> u64 {
+        return self.active_segment().next_offset;
+    }
+
+    pub fn high_watermark(&self) -> u64 {
+        return self.high_watermark;
+    }
+
+    // Records below the last stable offset are not part of an ongoing transaction, so
+    // consumers reading committed records can read up to it.
+    pub fn last_stable_offset(&self) -> u64 {
+        match self.producer_state.first_unstable_offset() {
+            Some(offset) => return offset.min(self.high_watermark),
+            None => return self.high_watermark,
+        }
+    }
+
+    pub fn aborted_transactions(&self, start_offset: u64, end_offset: u64) -> Vec<AbortedTxn> {
+        return self
+            .producer_state
+            .aborted_transactions(start_offset, end_offset);
+    }
+
+    // Bytes of all segment files of the shard.
+    pub fn size(&self) -> u64 {
+        return self.segments.values().map(|segment| segment.size).sum();
+    }
+
+    // Moves the high watermark forward, it never goes back or past the end of the log.
+    // Returns whether it moved.
+    pub fn set_high_watermark(&mut self, high_watermark: u64) -> Result<bool, CommonError> {
+        let high_watermark = high_watermark.min(self.next_offset());
+        if high_watermark <= self.high_watermark {
+            return Ok(false);
+        }
+        self.index.save_high_watermark(high_watermark)?;
+        self.high_watermark = high_watermark;
+        return Ok(true);
+    }
+
+    // Removes the records from offset on, used by a follower to drop the records that
+    // the leader does not have.
+    pub fn truncate_to(&mut self, offset: u64) -> Result<(), CommonError> {
+        let offset = offset.max(self.start_offset());
+        if offset >= self.next_offset() {
+            return Ok(());
+        }
+
+        let removed: Vec<u64> = self
+            .segments
+            .range(offset + 1..)
+            .map(|(start_offset, _)| *start_offset)
+            .collect();
+        for start_offset in removed {
+            if let Some(segment) = self.segments.remove(&start_offset) {
+                info!(
+                    "Removing segment {} of shard {} when truncating to offset {}",
+                    segment.segment_no, self.shard_name, offset
+                );
+                let segment_no = segment.segment_no;
+                let path = segment.path.clone();
+                drop(segment);
+                fs::remove_file(&path)?;
+                self.index.delete_segment(segment_no)?;
+            }
+            self.index_metas.remove(&start_offset);
+        }
+
+        let start_offset = self.active_segment().start_offset;
+        let position = match self.position_of(self.active_segment(), offset)? {
+            Some(position) => position,
+            None => return Ok(()),
+        };
+        let active = self.segments.values_mut().next_back().unwrap();
+        active.truncate(position, offset)?;
+        let mut meta = self
+            .index_metas
+            .get(&start_offset)
+            .copied()
+            .unwrap_or_default();
+        self.index.truncate(active, &mut meta)?;
+        self.index_metas.insert(start_offset, meta);
+
+        if self.high_watermark > offset {
+            self.index.save_high_watermark(offset)?;
+            self.high_watermark = offset;
+        }
+
+        if let Some((snapshot_offset, _)) = self.index.get_producer_snapshot()? {
+            if snapshot_offset > offset {
+                self.index.delete_producer_snapshot()?;
+            }
+        }
+        return self.load_producer_state();
+    }
+
+    // Closes the active segment and starts a new one at the end of the log. Nothing is
+    // done while the active segment is empty.
+    pub fn roll(&mut self) -> Result<(), CommonError> {
+        let active = self.segments.values_mut().next_back().unwrap();
+        if active.next_offset == active.start_offset {
+            return Ok(());
+        }
+        active.sync()?;
+        let segment = SegmentFile::create(
+            &self.dir,
+            active.segment_no + 1,
+            active.next_offset,
+            self.fsync_policy,
+        )?;
+        info!(
+            "Rolled shard {} to segment {} at offset {}",
+            self.shard_name, segment.segment_no, segment.start_offset
+        );
+        self.index_metas.insert(
+            segment.start_offset,
+            SegmentIndexMeta {
+                next_offset: segment.start_offset,
+                ..Default::default()
+            },
+        );
+        self.segments.insert(segment.start_offset, segment);
+        return self
+            .index
+            .save_producer_snapshot(self.next_offset(), &self.producer_state);
+    }
+
+    // Returns the sealed segments in offset order.
+    pub fn sealed_segments(&self) -> Vec<SealedSegment> {
+        let active_start_offset = self.active_segment().start_offset;
+        let mut results = Vec::new();
+        for (start_offset, segment) in self.segments.iter() {
+            if *start_offset == active_start_offset || segment.next_offset > self.high_watermark {
+                break;
+            }
+            let max_timestamp = match self.index_metas.get(start_offset) {
+                Some(meta) => meta.max_timestamp,
+                None => 0,
+            };
+            results.push(SealedSegment {
+                segment_no: segment.segment_no,
+                path: segment.path.clone(),
+                start_offset: segment.start_offset,
+                next_offset: segment.next_offset,
+                size: segment.size,
+                max_timestamp,
+            });
+        }
+        return results;
+    }
+
+    pub fn segment_index(&self, segment_no: u64) -> Result<SegmentIndexEntries, CommonError> {
+        return self.index.export(segment_no);
+    }
+
+    // Removes the oldest segment of the log, once its records are kept elsewhere. Only
+    // the first segment can be removed so that the local records stay contiguous, and
+    // never the active one. Returns whether the segment was removed.
+    pub fn remove_oldest_segment(&mut self, start_offset: u64) -> Result<bool, CommonError> {
+        if self.segments.len() < 2 || self.start_offset() != start_offset {
+            return Ok(false);
+        }
+        if let Some(segment) = self.segments.remove(&start_offset) {
+            info!(
+                "Removing local segment {} of shard {}, offsets [{}, {})",
+                segment.segment_no, self.shard_name, segment.start_offset, segment.next_offset
+            );
+            let segment_no = segment.segment_no;
+            let path = segment.path.clone();
+            drop(segment);
+            fs::remove_file(&path)?;
+            self.index.delete_segment(segment_no)?;
+        }
+        self.index_metas.remove(&start_offset);
+        return Ok(true);
+    }
+
+    pub fn active_segment(&self) -> &SegmentFile {
+        // A shard log always holds at least one segment.
+        return self.segments.values().next_back().unwrap();
+    }
+
+    // Assigns consecutive offsets to the records and appends them to the active segment.
+    pub fn append(&mut self, records: Vec<Record>) -> Result<Vec<u64>, CommonError> {
+        let mut next_offset = self.next_offset();
+        let mut records = records;
+        let mut offsets = Vec::new();
+        for record in records.iter_mut() {
+            record.offset = next_offset;
+            if record.timestamp == 0 {
+                record.timestamp = now_mills() as u64;
+            }
+            offsets.push(next_offset);
+            next_offset += 1;
+        }
+        self.append_to_active(&records)?;
+        return Ok(offsets);
+    }
+
+    // Appends the records of an idempotent producer, unless they are a retry of records
+    // that were already written, in which case the offsets of those are returned.
+    pub fn append_idempotent(
+        &mut self,
+        producer: &ProducerAppend,
+        records: Vec<Record>,
+    ) -> Result<Vec<u64>, CommonError> {
+        let check =
+            self.producer_state
+                .check_append(&self.shard_name, producer, records.len() as u32)?;
+        if let AppendCheck::Duplicate(offsets) = check {
+            return Ok(offsets);
+        }
+        let mut records = records;
+        for (i, record) in records.iter_mut().enumerate() {
+            record.producer_id = producer.producer_id;
+            record.producer_epoch = producer.producer_epoch;
+            record.sequence = producer.base_sequence.wrapping_add(i as u32);
+            record.is_transactional = producer.transactional;
+            record.control = RecordControl::None;
+        }
+        return self.append(records);
+    }
+
+    // Appends the marker ending the transaction of the producer, and returns its offset.
+    // Nothing is written when the producer has no ongoing transaction on the shard.
+    pub fn end_transaction(
+        &mut self,
+        producer_id: u64,
+        producer_epoch: u32,
+        control: RecordControl,
+    ) -> Result<Option<u64>, CommonError> {
+        if !self
+            .producer_state
+            .check_end_txn(&self.shard_name, producer_id, producer_epoch)?
+        {
+            return Ok(None);
+        }
+        let marker = Record::build_control(producer_id, producer_epoch, control);
+        let offsets = self.append(vec![marker])?;
+        return Ok(offsets.first().copied());
+    }
+
+    // Aborts the transactions ongoing for timeout_ms or more and returns the offsets of
+    // their markers. The markers take the next epoch of the producer, which fences it, so
+    // that its later commit fails instead of committing nothing.
+    pub fn abort_expired_transactions(&mut self, timeout_ms: u64) -> Result<Vec<u64>, CommonError> {
+        let now_ms = now_mills() as u64;
+        let mut offsets = Vec::new();
+        for (producer_id, producer_epoch) in
+            self.producer_state.expired_transactions(now_ms, timeout_ms)
+        {
+            if let Some(offset) =
+                self.end_transaction(producer_id, producer_epoch + 1, RecordControl::Abort)?
+            {
+                offsets.push(offset);
+            }
+        }
+        return Ok(offsets);
+    }
+
+    // Appends records fetched from the leader with the offsets the leader assigned. They
+    // are not contiguous when the leader read them from a compacted segment.
+    pub fn append_replicated(&mut self, records: Vec<Record>) -> Result<(), CommonError> {
+        return self.append_to_active(&records);
+    }
+
+    fn append_to_active(&mut self, records: &[Record]) -> Result<(), CommonError> {
+        let active = self.segments.values_mut().next_back().unwrap();
+        let positions = active.append(records)?;
+
+        let index_meta = self.index_metas.entry(active.start_offset).or_default();
+        let mut meta = *index_meta;
+        self.index
+            .append(active.segment_no, &mut meta, records, &positions)?;
+        *index_meta = meta;
+
+        for record in records.iter() {
+            if let Some(aborted) = self.producer_state.apply(record) {
+                self.index.save_aborted_txn(&aborted)?;
+            }
+        }
+        return Ok(());
+    }
+
+    // Restores the producer state from its last snapshot and the records written after
+    // it, the whole log is read when there is no usable snapshot. The transactions
+    // aborted after the snapshot are found again while reading.
+    fn load_producer_state(&mut self) -> Result<(), CommonError> {
+        let next_offset = self.next_offset();
+        let (snapshot_offset, mut state) = match self.index.get_producer_snapshot()? {
+            Some((offset, state)) if offset <= next_offset => (offset, state),
+            _ => (0, ProducerState::new()),
+        };
+        let aborted = self
+            .index
+            .list_aborted_txns()?
+            .into_iter()
+            .filter(|txn| txn.last_offset < snapshot_offset)
+            .collect();
+        state.set_aborted(aborted);
+        self.index.truncate_aborted_txns(snapshot_offset)?;
+
+        let mut offset = snapshot_offset.max(self.start_offset());
+        while offset < next_offset {
+            let records = self.read(offset, SCAN_BATCH_RECORD_NUM, 0)?;
+            if records.is_empty() {
+                break;
+            }
+            for record in records.iter() {
+                offset = record.offset + 1;
+                if let Some(aborted) = state.apply(record) {
+                    self.index.save_aborted_txn(&aborted)?;
+                }
+            }
+        }
+        self.index.save_producer_snapshot(next_offset, &state)?;
+        self.producer_state = state;
+        return Ok(());
+    }
+
+    // Replaces a sealed segment with its compacted copy at compacted_path, which holds the
+    // same offsets with the replaced records removed. The copy is renamed over the
+    // segment so a crash leaves either of them complete, and the indexes of the segment
+    // are dropped first so that they are rebuilt if the shard is opened after the rename.
+    // Returns false and removes the copy if the segment changed since it was read.
+    pub fn replace_segment(
+        &mut self,
+        sealed: &SealedSegment,
+        compacted_path: &Path,
+    ) -> Result<bool, CommonError> {
+        let unchanged = match self.segments.get(&sealed.start_offset) {
+            Some(segment) => {
+                segment.segment_no == sealed.segment_no
+                    && segment.size == sealed.size
+                    && segment.next_offset == sealed.next_offset
+                    && segment.start_offset != self.active_segment().start_offset
+            }
+            None => false,
+        };
+        if !unchanged {
+            fs::remove_file(compacted_path)?;
+            return Ok(false);
+        }
+
+        self.index.delete_segment(sealed.segment_no)?;
+        self.segments.remove(&sealed.start_offset);
+        self.index_metas.remove(&sealed.start_offset);
+        fs::rename(compacted_path, &sealed.path)?;
+
+        let mut segment = SegmentFile::open(&sealed.path, sealed.segment_no, self.fsync_policy)?;
+        segment.next_offset = sealed.next_offset;
+        let meta = self.index.rebuild(&segment, segment.next_offset)?;
+        self.index_metas.insert(segment.start_offset, meta);
+        self.segments.insert(segment.start_offset, segment);
+        return Ok(true);
+    }
+
+    // Reads records from offset on, continuing into the following segments until
+    // max_record records or max_bytes of values have been read.
+    pub fn read(
+        &self,
+        offset: u64,
+        max_record: usize,
+        max_bytes: u64,
+    ) -> Result<Vec<Record>, CommonError> {
+        let mut results = Vec::new();
+        let mut offset = offset.max(self.start_offset());
+        let mut total_bytes = 0;
+        while results.len() < max_record && offset < self.next_offset() {
+            let segment = match self.segments.range(..=offset).next_back() {
+                Some((_, segment)) => segment,
+                None => break,
+            };
+            let position = match self.position_of(segment, offset)? {
+                Some(position) => position,
+                // The rest of the segment was removed by compaction.
+                None if segment.next_offset > offset => {
+                    offset = segment.next_offset;
+                    continue;
+                }
+                None => break,
+            };
+            let remaining_bytes = if max_bytes > 0 {
+                max_bytes - total_bytes
+            } else {
+                0
+            };
+            let records = segment.read_at(position, max_record - results.len(), remaining_bytes)?;
+            if records.is_empty() {
+                break;
+            }
+            for (_, record) in records {
+                let size = record.value.len() as u64;
+                if max_bytes > 0 && total_bytes + size > max_bytes && !results.is_empty() {
+                    return Ok(results);
+                }
+                total_bytes += size;
+                offset = record.offset + 1;
+                results.push(record);
+            }
+            if max_bytes > 0 && total_bytes >= max_bytes {
+                break;
+            }
+        }
+        return Ok(results);
+    }
+
+    pub fn read_by_offset(&self, offset: u64) -> Result<Option<Record>, CommonError> {
+        if let Some((_, segment)) = self.segments.range(..=offset).next_back() {
+            if let Some(position) = self.position_of(segment, offset)? {
+                if let Some((_, record)) = segment.read_at(position, 1, 0)?.into_iter().next() {
+                    if record.offset == offset {
+                        return Ok(Some(record));
+                    }
+                }
+            }
+        }
+        return Ok(None);
+    }
+
+    // Returns the offset of the first record whose timestamp is not less than timestamp.
+    pub fn offset_by_timestamp(&self, timestamp: u64) -> Result<Option<u64>, CommonError> {
+        for (start_offset, segment) in self.segments.iter() {
+            if let Some(meta) = self.index_metas.get(start_offset) {
+                if meta.max_timestamp < timestamp {
+                    continue;
+                }
+            }
+            let mut position = self
+                .index
+                .timestamp_position(segment.segment_no, timestamp)?;
+            loop {
+                let data = segment.read_at(position, SCAN_BATCH_RECORD_NUM, 0)?;
+                if data.is_empty() {
+                    break;
+                }
+                for (record_position, record) in data {
+                    if record.timestamp >= timestamp {
+                        return Ok(Some(record.offset));
+                    }
+                    position = record_position + FRAME_HEADER_LEN + record.encode_len() as u64;
+                }
+            }
+        }
+        return Ok(None);
+    }
+
+    // Reads the records whose timestamp is within [start_timestamp, end_timestamp].
+    pub fn read_by_timestamp(
+        &self,
+        start_timestamp: u64,
+        end_timestamp: u64,
+        max_record: usize,
+        max_bytes: u64,
+    ) -> Result<Vec<Record>, CommonError> {
+        let mut results = Vec::new();
+        let mut offset = match self.offset_by_timestamp(start_timestamp)? {
+            Some(offset) => offset,
+            None => return Ok(results),
+        };
+        let mut total_bytes = 0;
+        while results.len() < max_record {
+            let records = self.read(offset, SCAN_BATCH_RECORD_NUM, 0)?;
+            if records.is_empty() {
+                break;
+            }
+            for record in records {
+                offset = record.offset + 1;
+                if record.timestamp < start_timestamp || record.timestamp > end_timestamp {
+                    continue;
+                }
+                let size = record.value.len() as u64;
+                if max_bytes > 0 && total_bytes + size > max_bytes && !results.is_empty() {
+                    return Ok(results);
+                }
+                total_bytes += size;
+                results.push(record);
+                if results.len() >= max_record {
+                    break;
+                }
+            }
+        }
+        return Ok(results);
+    }
+
+    // Returns the latest record with the key.
+    pub fn read_by_key(&self, key: &[u8]) -> Result<Option<Record>, CommonError> {
+        if let Some(offset) = self.index.latest_offset_by_key(key)? {
+            return self.read_by_offset(offset);
+        }
+        return Ok(None);
+    }
+
+    pub fn sync(&mut self) -> Result<(), CommonError> {
+        for segment in self.segments.values_mut() {
+            segment.sync()?;
+        }
+        return Ok(());
+    }
+
+    pub fn fsync_policy(&self) -> FsyncPolicy {
+        return self.fsync_policy;
+    }
+
+    fn position_of(&self, segment: &SegmentFile, offset: u64) -> Result<Option<u64>, CommonError> {
+        let position = self.index.offset_position(segment.segment_no, offset)?;
+        return segment.position_from(position, offset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ShardLog;
+    use crate::{
+        index::engine::IndexEngine,
+        record::record::{Record, RecordControl},
+        shard::{
+            producer_state::{AbortedTxn, ProducerAppend},
+            segment::{FsyncPolicy, SegmentFile},
+        },
+    };
+    use bytes::Bytes;
+    use std::{fs, path::PathBuf, sync::Arc};
+
+    fn test_dir(name: &str) -> (PathBuf, Arc<IndexEngine>) {
+        let dir = std::env::temp_dir().join(format!("robustmq-journal-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let engine = IndexEngine::new(&dir.join("_index").display().to_string(), None).unwrap();
+        return (dir.join("s1"), Arc::new(engine));
+    }
+
+    fn build_records(num: u64) -> Vec<Record> {
+        let mut records = Vec::new();
+        for i in 0..num {
+            records.push(Record::build(
+                Bytes::new(),
+                Bytes::from(format!("v{}", i)),
+                Vec::new(),
+            ));
+        }
+        return records;
+    }
+
+    #[test]
+    fn shard_log_append_read_reopen() {
+        let (dir, engine) = test_dir("shard-log");
+        let shard_name = "s1".to_string();
+        {
+            let mut log = ShardLog::open(
+                &dir,
+                shard_name.clone(),
+                FsyncPolicy::EveryWrite,
+                engine.clone(),
+            )
+            .unwrap();
+            assert_eq!(log.append(build_records(3)).unwrap(), vec![0, 1, 2]);
+            assert_eq!(log.append(build_records(2)).unwrap(), vec![3, 4]);
+            assert!(log.read_by_offset(4).unwrap().unwrap().timestamp > 0);
+        }
+
+        // A second segment as created by a roll, its indexes are built when the shard is
+        // opened.
+        let mut segment = SegmentFile::create(&dir, 1, 5, FsyncPolicy::EveryWrite).unwrap();
+        let mut records = build_records(2);
+        records[0].offset = 5;
+        records[1].offset = 6;
+        segment.append(&records).unwrap();
+        drop(segment);
+
+        let mut log = ShardLog::open(&dir, shard_name, FsyncPolicy::EveryWrite, engine).unwrap();
+        assert_eq!(log.start_offset(), 0);
+        assert_eq!(log.next_offset(), 7);
+
+        let res = log.read(3, 10, 0).unwrap();
+        let offsets: Vec<u64> = res.iter().map(|r| r.offset).collect();
+        assert_eq!(offsets, vec![3, 4, 5, 6]);
+        assert_eq!(log.read(3, 3, 0).unwrap().len(), 3);
+        assert_eq!(log.read(0, 10, 4).unwrap().len(), 2);
+        assert!(log.read(7, 10, 0).unwrap().is_empty());
+
+        assert_eq!(log.append(build_records(1)).unwrap(), vec![7]);
+        assert_eq!(log.read_by_offset(7).unwrap().unwrap().offset, 7);
+        assert_eq!(log.read_by_offset(5).unwrap().unwrap().offset, 5);
+    }
+
+    #[test]
+    fn shard_log_read_by_timestamp_and_key() {
+        let (dir, engine) = test_dir("shard-log-index");
+        let shard_name = "s1".to_string();
+        let mut records = Vec::new();
+        for i in 0..200u64 {
+            let mut record = Record::build(
+                Bytes::from(format!("k{}", i % 5)),
+                Bytes::from(vec![0u8; 100]),
+                Vec::new(),
+            );
+            record.timestamp = 1000 + i * 10;
+            records.push(record);
+        }
+        let path = {
+            let mut log = ShardLog::open(
+                &dir,
+                shard_name.clone(),
+                FsyncPolicy::EveryWrite,
+                engine.clone(),
+            )
+            .unwrap();
+            log.append(records).unwrap();
+
+            assert_eq!(log.offset_by_timestamp(0).unwrap(), Some(0));
+            assert_eq!(log.offset_by_timestamp(1500).unwrap(), Some(50));
+            assert_eq!(log.offset_by_timestamp(1505).unwrap(), Some(51));
+            assert_eq!(log.offset_by_timestamp(100000).unwrap(), None);
+
+            let res = log.read_by_timestamp(1500, 1530, 10, 0).unwrap();
+            let offsets: Vec<u64> = res.iter().map(|r| r.offset).collect();
+            assert_eq!(offsets, vec![50, 51, 52, 53]);
+            assert_eq!(log.read_by_timestamp(1500, 1530, 2, 0).unwrap().len(), 2);
+
+            assert_eq!(log.read_by_key(b"k3").unwrap().unwrap().offset, 198);
+            assert!(log.read_by_key(b"k9").unwrap().is_none());
+            log.active_segment().path.clone()
+        };
+
+        // Cut the last record off as a torn write would, the indexes must follow.
+        let len = fs::metadata(&path).unwrap().len();
+        let file = fs::OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(len - 10).unwrap();
+        drop(file);
+
+        let log = ShardLog::open(&dir, shard_name, FsyncPolicy::EveryWrite, engine).unwrap();
+        assert_eq!(log.next_offset(), 199);
+        assert_eq!(log.read_by_key(b"k4").unwrap().unwrap().offset, 194);
+        assert_eq!(log.read_by_key(b"k3").unwrap().unwrap().offset, 198);
+        assert_eq!(log.offset_by_timestamp(2990).unwrap(), None);
+        assert!(log.read_by_offset(199).unwrap().is_none());
+    }
+
+    #[test]
+    fn shard_log_high_watermark_truncate() {
+        let (dir, engine) = test_dir("shard-log-truncate");
+        let shard_name = "s1".to_string();
+        {
+            let mut log = ShardLog::open(
+                &dir,
+                shard_name.clone(),
+                FsyncPolicy::EveryWrite,
+                engine.clone(),
+            )
+            .unwrap();
+            log.append(build_records(5)).unwrap();
+            assert_eq!(log.high_watermark(), 0);
+            assert!(log.set_high_watermark(3).unwrap());
+            assert!(!log.set_high_watermark(2).unwrap());
+            assert_eq!(log.high_watermark(), 3);
+        }
+
+        let mut segment = SegmentFile::create(&dir, 1, 5, FsyncPolicy::EveryWrite).unwrap();
+        let mut records = build_records(2);
+        records[0].offset = 5;
+        records[1].offset = 6;
+        segment.append(&records).unwrap();
+        drop(segment);
+
+        let mut log = ShardLog::open(
+            &dir,
+            shard_name.clone(),
+            FsyncPolicy::EveryWrite,
+            engine.clone(),
+        )
+        .unwrap();
+        assert_eq!(log.high_watermark(), 3);
+        assert!(log.set_high_watermark(100).unwrap());
+        assert_eq!(log.high_watermark(), 7);
+
+        // Truncating into the first segment removes the second one.
+        log.truncate_to(2).unwrap();
+        assert_eq!(log.next_offset(), 2);
+        assert_eq!(log.high_watermark(), 2);
+        assert!(log.read_by_offset(2).unwrap().is_none());
+        assert_eq!(log.append(build_records(2)).unwrap(), vec![2, 3]);
+        drop(log);
+
+        let log = ShardLog::open(&dir, shard_name, FsyncPolicy::EveryWrite, engine).unwrap();
+        assert_eq!(log.next_offset(), 4);
+        assert_eq!(log.high_watermark(), 2);
+        let offsets: Vec<u64> = log
+            .read(0, 10, 0)
+            .unwrap()
+            .iter()
+            .map(|r| r.offset)
+            .collect();
+        assert_eq!(offsets, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn shard_log_roll_and_remove_segment() {
+        let (dir, engine) = test_dir("shard-log-roll");
+        let shard_name = "s1".to_string();
+        let mut log = ShardLog::open(
+            &dir,
+            shard_name.clone(),
+            FsyncPolicy::EveryWrite,
+            engine.clone(),
+        )
+        .unwrap();
+        // An empty active segment is not rolled.
+        log.roll().unwrap();
+        assert_eq!(log.active_segment().segment_no, 0);
+
+        log.append(build_records(3)).unwrap();
+        log.roll().unwrap();
+        log.append(build_records(2)).unwrap();
+        log.roll().unwrap();
+        log.append(build_records(1)).unwrap();
+        assert_eq!(log.active_segment().segment_no, 2);
+        assert_eq!(log.active_segment().start_offset, 5);
+
+        // Only the segments below the high watermark are sealed.
+        log.set_high_watermark(4).unwrap();
+        let sealed = log.sealed_segments();
+        assert_eq!(sealed.len(), 1);
+        assert_eq!((sealed[0].start_offset, sealed[0].next_offset), (0, 3));
+        log.set_high_watermark(6).unwrap();
+        assert_eq!(log.sealed_segments().len(), 2);
+        assert_eq!(log.segment_index(1).unwrap().offset_entries[0].0, 3);
+
+        // Only the oldest segment can be removed.
+        assert!(!log.remove_oldest_segment(3).unwrap());
+        assert!(log.remove_oldest_segment(0).unwrap());
+        assert_eq!(log.start_offset(), 3);
+        assert!(log.read_by_offset(1).unwrap().is_none());
+        let offsets: Vec<u64> = log
+            .read(0, 10, 0)
+            .unwrap()
+            .iter()
+            .map(|r| r.offset)
+            .collect();
+        assert_eq!(offsets, vec![3, 4, 5]);
+        drop(log);
+
+        let log = ShardLog::open(&dir, shard_name, FsyncPolicy::EveryWrite, engine).unwrap();
+        assert_eq!(log.start_offset(), 3);
+        assert_eq!(log.next_offset(), 6);
+    }
+
+    #[test]
+    fn shard_log_producer_state_recovery() {
+        let (dir, engine) = test_dir("shard-log-producer");
+        let shard_name = "s1".to_string();
+        let producer = ProducerAppend {
+            producer_id: 1,
+            transactional: true,
+            ..Default::default()
+        };
+        let retry = ProducerAppend {
+            base_sequence: 2,
+            ..producer
+        };
+        {
+            let mut log = ShardLog::open(
+                &dir,
+                shard_name.clone(),
+                FsyncPolicy::EveryWrite,
+                engine.clone(),
+            )
+            .unwrap();
+            log.append_idempotent(&producer, build_records(2)).unwrap();
+            assert_eq!(
+                log.end_transaction(1, 0, RecordControl::Abort).unwrap(),
+                Some(2)
+            );
+            log.roll().unwrap();
+            log.append_idempotent(&retry, build_records(2)).unwrap();
+            log.set_high_watermark(5).unwrap();
+            assert_eq!(log.last_stable_offset(), 3);
+        }
+
+        // The state is restored from the snapshot taken by the roll and the records after
+        // it, a retry after the restart is still recognised.
+        let mut log = ShardLog::open(
+            &dir,
+            shard_name.clone(),
+            FsyncPolicy::EveryWrite,
+            engine.clone(),
+        )
+        .unwrap();
+        assert_eq!(log.last_stable_offset(), 3);
+        let aborted = AbortedTxn {
+            producer_id: 1,
+            first_offset: 0,
+            last_offset: 2,
+        };
+        assert_eq!(log.aborted_transactions(0, 5), vec![aborted]);
+        assert_eq!(
+            log.append_idempotent(&retry, build_records(2)).unwrap(),
+            vec![3, 4]
+        );
+        assert_eq!(log.next_offset(), 5);
+
+        // Truncating the records of a transaction away drops it.
+        log.truncate_to(3).unwrap();
+        assert_eq!(
+            log.end_transaction(1, 0, RecordControl::Commit).unwrap(),
+            None
+        );
+        log.truncate_to(1).unwrap();
+        assert!(log.aborted_transactions(0, 5).is_empty());
+        assert_eq!(log.last_stable_offset(), 0);
+        assert_eq!(
+            log.end_transaction(1, 0, RecordControl::Abort).unwrap(),
+            Some(1)
+        );
+        drop(log);
+
+        let log = ShardLog::open(&dir, shard_name, FsyncPolicy::EveryWrite, engine).unwrap();
+        assert_eq!(log.aborted_transactions(0, 5).len(), 1);
+    }
+
+    #[test]
+    fn shard_log_abort_expired_transactions() {
+        let (dir, engine) = test_dir("shard-log-txn-timeout");
+        let mut log =
+            ShardLog::open(&dir, "s1".to_string(), FsyncPolicy::EveryWrite, engine).unwrap();
+        let producer = ProducerAppend {
+            producer_id: 1,
+            transactional: true,
+            ..Default::default()
+        };
+        log.append_idempotent(&producer, build_records(2)).unwrap();
+        log.set_high_watermark(2).unwrap();
+        assert!(log
+            .abort_expired_transactions(3600 * 1000)
+            .unwrap()
+            .is_empty());
+        assert_eq!(log.last_stable_offset(), 0);
+
+        assert_eq!(log.abort_expired_transactions(0).unwrap(), vec![2]);
+        log.set_high_watermark(3).unwrap();
+        assert_eq!(log.last_stable_offset(), 3);
+        assert_eq!(log.aborted_transactions(0, 3).len(), 1);
+        // The producer is fenced, it cannot commit the aborted transaction.
+        assert!(log.end_transaction(1, 0, RecordControl::Commit).is_err());
+    }
+}
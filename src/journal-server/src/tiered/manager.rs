@@ -0,0 +1,432 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::remote_segment::{
+    key_remote_index, key_remote_segment, key_remote_shard_prefix, parse_remote_index_key,
+    RemoteSegment,
+};
+use crate::{
+    record::record::Record,
+    replica::manager::ReplicaManager,
+    shard::{
+        manager::ShardManager,
+        segment::{decode_frames, FRAME_HEADER_LEN},
+    },
+};
+use bytes::Bytes;
+use common_base::{
+    config::journal_server::TieredStorage,
+    error::{common::CommonError, journal_server::JournalServerError},
+    tools::now_mills,
+};
+use dashmap::DashMap;
+use journal_remote::storage::RemoteStorage;
+use log::{error, info};
+use std::{collections::BTreeMap, sync::Arc};
+
+// Bytes read from a remote segment at a time, doubled while a record does not fit.
+const REMOTE_READ_CHUNK_BYTES: u64 = 1024 * 1024;
+
+// Moves the sealed segments of the shards to the remote tier and serves the reads of the
+// records that are only kept there. The leader of a shard uploads its segments, while
+// every replica removes its local copy of the uploaded segments by the local retention.
+pub struct RemoteLogManager {
+    cluster_name: String,
+    config: TieredStorage,
+    storage: Arc<dyn RemoteStorage>,
+    shard_manager: Arc<ShardManager>,
+    replica_manager: Arc<ReplicaManager>,
+    // The remote segments of every shard, keyed by their start offset.
+    segments: DashMap<String, BTreeMap<u64, Arc<RemoteSegment>>>,
+}
+
+impl RemoteLogManager {
+    pub fn new(
+        cluster_name: String,
+        config: TieredStorage,
+        storage: Arc<dyn RemoteStorage>,
+        shard_manager: Arc<ShardManager>,
+        replica_manager: Arc<ReplicaManager>,
+    ) -> Self {
+        return RemoteLogManager {
+            cluster_name,
+            config,
+            storage,
+            shard_manager,
+            replica_manager,
+            segments: DashMap::with_capacity(8),
+        };
+    }
+
+    pub fn offload_interval_ms(&self) -> u64 {
+        return self.config.offload_interval_ms;
+    }
+
+    // Runs one offload round over every shard on this node.
+    pub async fn offload(&self) {
+        for shard_name in self.shard_manager.shard_names() {
+            if let Err(e) = self.offload_shard(&shard_name).await {
+                error!(
+                    "Failed to offload the segments of shard {} to the remote tier, {}",
+                    shard_name, e
+                );
+            }
+        }
+    }
+
+    async fn offload_shard(&self, shard_name: &String) -> Result<(), CommonError> {
+        self.refresh_segments(shard_name).await?;
+        if self.replica_manager.is_leader(shard_name) {
+            self.upload_segments(shard_name).await?;
+        }
+        return self.apply_local_retention(shard_name);
+    }
+
+    // Loads the remote segments of the shard that are not known yet, including the ones
+    // uploaded by a previous leader.
+    async fn refresh_segments(&self, shard_name: &String) -> Result<(), CommonError> {
+        let prefix = key_remote_shard_prefix(&self.cluster_name, shard_name);
+        for key in self.storage.list(&prefix).await? {
+            let start_offset = match parse_remote_index_key(&key) {
+                Some(start_offset) => start_offset,
+                None => continue,
+            };
+            if let Some(segments) = self.segments.get(shard_name) {
+                if segments.contains_key(&start_offset) {
+                    continue;
+                }
+            }
+            let segment = RemoteSegment::decode(&self.storage.get(&key).await?)?;
+            self.add_segment(shard_name, segment);
+        }
+        return Ok(());
+    }
+
+    // Uploads the sealed segments holding records past the end of the remote log. The
+    // segment file goes first, the index object marks the upload as complete.
+    async fn upload_segments(&self, shard_name: &String) -> Result<(), CommonError> {
+        for sealed in self.shard_manager.sealed_segments(shard_name)? {
+            if sealed.next_offset <= self.remote_next_offset(shard_name) {
+                continue;
+            }
+            let index = self
+                .shard_manager
+                .segment_index(shard_name, sealed.segment_no)?;
+            let segment = RemoteSegment {
+                start_offset: sealed.start_offset,
+                next_offset: sealed.next_offset,
+                size: sealed.size,
+                max_timestamp: sealed.max_timestamp,
+                offset_index: index.offset_entries,
+                time_index: index.time_entries,
+            };
+            self.storage
+                .put_file(
+                    &key_remote_segment(&self.cluster_name, shard_name, segment.start_offset),
+                    &sealed.path,
+                )
+                .await?;
+            self.storage
+                .put(
+                    &key_remote_index(&self.cluster_name, shard_name, segment.start_offset),
+                    Bytes::from(segment.encode()),
+                )
+                .await?;
+            info!(
+                "Segment {} of shard {} is offloaded, offsets [{}, {})",
+                sealed.segment_no, shard_name, segment.start_offset, segment.next_offset
+            );
+            self.add_segment(shard_name, segment);
+        }
+        return Ok(());
+    }
+
+    // Removes the oldest local segments once their records are on the remote tier and
+    // they are older than local_retention_ms, or while the shard takes more than
+    // local_retention_bytes on this node.
+    fn apply_local_retention(&self, shard_name: &String) -> Result<(), CommonError> {
+        let now = now_mills() as u64;
+        let mut local_bytes = self.shard_manager.local_size(shard_name)?;
+        for sealed in self.shard_manager.sealed_segments(shard_name)? {
+            if !self.is_offloaded(shard_name, sealed.start_offset, sealed.next_offset) {
+                break;
+            }
+            let expired =
+                now.saturating_sub(sealed.max_timestamp) >= self.config.local_retention_ms;
+            let oversized = self.config.local_retention_bytes > 0
+                && local_bytes > self.config.local_retention_bytes;
+            if !expired && !oversized {
+                break;
+            }
+            if !self
+                .shard_manager
+                .remove_oldest_segment(shard_name, sealed.start_offset)?
+            {
+                break;
+            }
+            local_bytes = local_bytes.saturating_sub(sealed.size);
+        }
+        return Ok(());
+    }
+
+    // Returns whether the records [start_offset, next_offset) are all on the remote tier.
+    pub fn is_offloaded(&self, shard_name: &str, start_offset: u64, next_offset: u64) -> bool {
+        let segments = match self.segments.get(shard_name) {
+            Some(segments) => segments,
+            None => return false,
+        };
+        let mut offset = start_offset;
+        while offset < next_offset {
+            match segments.range(..=offset).next_back() {
+                Some((_, segment)) if segment.contains(offset) => offset = segment.next_offset,
+                _ => return false,
+            }
+        }
+        return true;
+    }
+
+    // The first offset kept on the remote tier.
+    pub fn start_offset(&self, shard_name: &str) -> Option<u64> {
+        if let Some(segments) = self.segments.get(shard_name) {
+            if let Some((start_offset, _)) = segments.first_key_value() {
+                return Some(*start_offset);
+            }
+        }
+        return None;
+    }
+
+    // The offset following the last record kept on the remote tier, 0 if there is none.
+    pub fn remote_next_offset(&self, shard_name: &str) -> u64 {
+        if let Some(segments) = self.segments.get(shard_name) {
+            if let Some((_, segment)) = segments.last_key_value() {
+                return segment.next_offset;
+            }
+        }
+        return 0;
+    }
+
+    // Returns the remote segment holding the record at offset.
+    pub fn find_segment(&self, shard_name: &str, offset: u64) -> Option<Arc<RemoteSegment>> {
+        if let Some(segments) = self.segments.get(shard_name) {
+            if let Some((_, segment)) = segments.range(..=offset).next_back() {
+                if segment.contains(offset) {
+                    return Some(segment.clone());
+                }
+            }
+        }
+        return None;
+    }
+
+    // Reads records of a remote segment from offset on, until max_record records or
+    // max_bytes of values have been read or the segment ends. The first record is always
+    // returned even if it is larger than max_bytes.
+    pub async fn read(
+        &self,
+        shard_name: &str,
+        segment: &RemoteSegment,
+        offset: u64,
+        max_record: usize,
+        max_bytes: u64,
+    ) -> Result<Vec<Record>, CommonError> {
+        let key = key_remote_segment(&self.cluster_name, shard_name, segment.start_offset);
+        let mut results = Vec::new();
+        let mut total_bytes = 0;
+        let mut position = segment.offset_position(offset);
+        let mut chunk_bytes = REMOTE_READ_CHUNK_BYTES.max(max_bytes);
+        while results.len() < max_record && position < segment.size {
+            let data = self.storage.get_range(&key, position, chunk_bytes).await?;
+            if data.is_empty() {
+                break;
+            }
+            let frames = decode_frames(&data, position)?;
+            if frames.is_empty() {
+                if data.len() as u64 >= segment.size - position {
+                    return Err(JournalServerError::InvalidSegmentFile(format!(
+                        "{}, the frame at position {} is incomplete",
+                        key, position
+                    ))
+                    .into());
+                }
+                chunk_bytes *= 2;
+                continue;
+            }
+            for (frame_position, record) in frames {
+                position = frame_position + FRAME_HEADER_LEN + record.encode_len() as u64;
+                if record.offset < offset {
+                    continue;
+                }
+                let size = record.value.len() as u64;
+                if max_bytes > 0 && total_bytes + size > max_bytes && !results.is_empty() {
+                    return Ok(results);
+                }
+                total_bytes += size;
+                results.push(record);
+                if results.len() >= max_record {
+                    break;
+                }
+            }
+        }
+        return Ok(results);
+    }
+
+    fn add_segment(&self, shard_name: &str, segment: RemoteSegment) {
+        self.segments
+            .entry(shard_name.to_string())
+            .or_default()
+            .insert(segment.start_offset, Arc::new(segment));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RemoteLogManager;
+    use crate::{
+        index::engine::IndexEngine,
+        record::record::Record,
+        replica::manager::{ReplicaManager, ShardAssignment},
+        shard::{manager::ShardManager, segment::FsyncPolicy},
+    };
+    use bytes::Bytes;
+    use common_base::config::journal_server::TieredStorage;
+    use journal_remote::{local::LocalStorage, storage::RemoteStorage};
+    use std::{fs, sync::Arc, time::Duration};
+
+    fn build_manager(
+        name: &str,
+        node_id: u64,
+        storage: Arc<dyn RemoteStorage>,
+        local_retention_ms: u64,
+    ) -> Arc<RemoteLogManager> {
+        let dir = std::env::temp_dir().join(format!("robustmq-journal-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let engine =
+            IndexEngine::new(&dir.join("_index_rocksdb").display().to_string(), None).unwrap();
+        // Every append of the tests fills a segment.
+        let shard_manager = Arc::new(ShardManager::new(
+            vec![dir.display().to_string()],
+            FsyncPolicy::Os,
+            100,
+            Arc::new(engine),
+        ));
+        let replica_manager = Arc::new(ReplicaManager::new(
+            node_id,
+            Duration::from_secs(60),
+            shard_manager.clone(),
+        ));
+        let config = TieredStorage {
+            enable: true,
+            local_retention_ms,
+            ..Default::default()
+        };
+        return Arc::new(RemoteLogManager::new(
+            "c1".to_string(),
+            config,
+            storage,
+            shard_manager,
+            replica_manager,
+        ));
+    }
+
+    fn append(manager: &RemoteLogManager, shard_name: &str, start: u64, num: u64) {
+        let records = (start..start + num)
+            .map(|i| Record::build(Bytes::new(), Bytes::from(format!("v{:03}", i)), Vec::new()))
+            .collect();
+        manager
+            .shard_manager
+            .append(&shard_name.to_string(), records)
+            .unwrap();
+        manager
+            .shard_manager
+            .set_high_watermark(&shard_name.to_string(), u64::MAX)
+            .unwrap();
+    }
+
+    fn build_storage(name: &str) -> Arc<dyn RemoteStorage> {
+        let dir = std::env::temp_dir().join(format!("robustmq-journal-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        return Arc::new(LocalStorage::new(&dir.display().to_string()).unwrap());
+    }
+
+    #[tokio::test]
+    async fn offload_and_read_remote() {
+        let storage = build_storage("tiered-remote");
+        let leader = build_manager("tiered-leader", 1, storage.clone(), 0);
+        let shard_name = "s1".to_string();
+        for i in 0..4 {
+            append(&leader, &shard_name, i * 10, 10);
+        }
+        assert_eq!(
+            leader
+                .shard_manager
+                .sealed_segments(&shard_name)
+                .unwrap()
+                .len(),
+            3
+        );
+
+        leader.offload().await;
+        assert_eq!(leader.start_offset(&shard_name), Some(0));
+        assert_eq!(leader.remote_next_offset(&shard_name), 30);
+        assert!(leader.is_offloaded(&shard_name, 5, 30));
+        assert!(!leader.is_offloaded(&shard_name, 25, 31));
+        // Without a local retention time the local copies are removed right away.
+        assert_eq!(leader.shard_manager.start_offset(&shard_name).unwrap(), 30);
+
+        let segment = leader.find_segment(&shard_name, 13).unwrap();
+        assert_eq!(segment.start_offset, 10);
+        let records = leader
+            .read(&shard_name, &segment, 13, 100, 0)
+            .await
+            .unwrap();
+        let offsets: Vec<u64> = records.iter().map(|r| r.offset).collect();
+        assert_eq!(offsets, (13..20).collect::<Vec<u64>>());
+        assert_eq!(records[0].value, Bytes::from("v013"));
+        assert_eq!(
+            leader
+                .read(&shard_name, &segment, 13, 2, 0)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            leader
+                .read(&shard_name, &segment, 13, 100, 9)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!(leader.find_segment(&shard_name, 30).is_none());
+
+        // A follower learns about the segments uploaded by the leader and keeps its local
+        // copies while they are within the local retention time.
+        let follower = build_manager("tiered-follower", 2, storage, 3600 * 1000);
+        follower.replica_manager.set_assignment(
+            &shard_name,
+            ShardAssignment {
+                leader_id: 1,
+                leader_addr: "127.0.0.1:2228".to_string(),
+                replicas: vec![1, 2],
+                next_replicas: Vec::new(),
+            },
+        );
+        for i in 0..3 {
+            append(&follower, &shard_name, i * 10, 10);
+        }
+        follower.offload().await;
+        assert_eq!(follower.remote_next_offset(&shard_name), 30);
+        assert_eq!(follower.shard_manager.start_offset(&shard_name).unwrap(), 0);
+    }
+}
@@ -0,0 +1,44 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use log::info;
+use manager::RemoteLogManager;
+use std::{sync::Arc, time::Duration};
+use tokio::{select, sync::broadcast, time::sleep};
+
+pub mod manager;
+pub mod remote_segment;
+
+// Offloads the sealed segments of all shards every offload_interval_ms.
+pub async fn start_offload_thread(
+    remote_log: Arc<RemoteLogManager>,
+    stop_send: broadcast::Sender<bool>,
+) {
+    let interval_ms = remote_log.offload_interval_ms();
+    let mut stop_rx = stop_send.subscribe();
+    loop {
+        select! {
+            val = stop_rx.recv() => {
+                if let Ok(flag) = val {
+                    if flag {
+                        info!("{}", "Segment offload thread stopped successfully.");
+                        break;
+                    }
+                }
+            }
+            _ = sleep(Duration::from_millis(interval_ms)) => {
+                remote_log.offload().await;
+            }
+        }
+    }
+}
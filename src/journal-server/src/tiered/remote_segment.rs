@@ -0,0 +1,203 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::shard::segment::SEGMENT_HEADER_LEN;
+use bytes::{Buf, BufMut};
+use common_base::error::{common::CommonError, journal_server::JournalServerError};
+
+const REMOTE_SEGMENT_VERSION: u32 = 1;
+
+pub const REMOTE_SEGMENT_SUFFIX: &str = "log";
+pub const REMOTE_INDEX_SUFFIX: &str = "index";
+
+pub fn key_remote_shard_prefix(cluster_name: &str, shard_name: &str) -> String {
+    return format!("{}/{}/", cluster_name, shard_name);
+}
+
+pub fn key_remote_segment(cluster_name: &str, shard_name: &str, start_offset: u64) -> String {
+    return format!(
+        "{}{:020}.{}",
+        key_remote_shard_prefix(cluster_name, shard_name),
+        start_offset,
+        REMOTE_SEGMENT_SUFFIX
+    );
+}
+
+pub fn key_remote_index(cluster_name: &str, shard_name: &str, start_offset: u64) -> String {
+    return format!(
+        "{}{:020}.{}",
+        key_remote_shard_prefix(cluster_name, shard_name),
+        start_offset,
+        REMOTE_INDEX_SUFFIX
+    );
+}
+
+// Returns the start offset of the segment described by an index object.
+pub fn parse_remote_index_key(key: &str) -> Option<u64> {
+    let file_name = key.rsplit('/').next()?;
+    if let Some(offset) = file_name.strip_suffix(&format!(".{}", REMOTE_INDEX_SUFFIX)) {
+        return offset.parse::<u64>().ok();
+    }
+    return None;
+}
+
+// A segment copied to the remote tier. The segment file is uploaded unchanged, and this
+// description is uploaded after it together with the offset and time indexes of the
+// segment, so a segment whose index object exists is complete.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RemoteSegment {
+    pub start_offset: u64,
+    pub next_offset: u64,
+    pub size: u64,
+    pub max_timestamp: u64,
+    // (offset, position) entries of the sparse offset index.
+    pub offset_index: Vec<(u64, u64)>,
+    // (max timestamp, offset, position) entries of the sparse time index.
+    pub time_index: Vec<(u64, u64, u64)>,
+}
+
+impl RemoteSegment {
+    // | version(u32) | start offset | next offset | size | max timestamp |
+    // | offset entry num(u32) | (offset, position)... |
+    // | time entry num(u32) | (timestamp, offset, position)... |
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf =
+            Vec::with_capacity(48 + self.offset_index.len() * 16 + self.time_index.len() * 24);
+        buf.put_u32(REMOTE_SEGMENT_VERSION);
+        buf.put_u64(self.start_offset);
+        buf.put_u64(self.next_offset);
+        buf.put_u64(self.size);
+        buf.put_u64(self.max_timestamp);
+        buf.put_u32(self.offset_index.len() as u32);
+        for (offset, position) in self.offset_index.iter() {
+            buf.put_u64(*offset);
+            buf.put_u64(*position);
+        }
+        buf.put_u32(self.time_index.len() as u32);
+        for (timestamp, offset, position) in self.time_index.iter() {
+            buf.put_u64(*timestamp);
+            buf.put_u64(*offset);
+            buf.put_u64(*position);
+        }
+        return buf;
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, CommonError> {
+        let invalid = || -> CommonError {
+            return JournalServerError::IndexDecodeError(format!(
+                "invalid remote segment index of {} bytes",
+                data.len()
+            ))
+            .into();
+        };
+        let mut buf = data;
+        if buf.remaining() < 40 || buf.get_u32() != REMOTE_SEGMENT_VERSION {
+            return Err(invalid());
+        }
+        let mut segment = RemoteSegment {
+            start_offset: buf.get_u64(),
+            next_offset: buf.get_u64(),
+            size: buf.get_u64(),
+            max_timestamp: buf.get_u64(),
+            ..Default::default()
+        };
+
+        if buf.remaining() < 4 {
+            return Err(invalid());
+        }
+        let num = buf.get_u32() as usize;
+        if buf.remaining() < num * 16 {
+            return Err(invalid());
+        }
+        for _ in 0..num {
+            segment.offset_index.push((buf.get_u64(), buf.get_u64()));
+        }
+
+        if buf.remaining() < 4 {
+            return Err(invalid());
+        }
+        let num = buf.get_u32() as usize;
+        if buf.remaining() != num * 24 {
+            return Err(invalid());
+        }
+        for _ in 0..num {
+            segment
+                .time_index
+                .push((buf.get_u64(), buf.get_u64(), buf.get_u64()));
+        }
+        return Ok(segment);
+    }
+
+    pub fn contains(&self, offset: u64) -> bool {
+        return offset >= self.start_offset && offset < self.next_offset;
+    }
+
+    // Returns the position to scan from for the record at offset.
+    pub fn offset_position(&self, offset: u64) -> u64 {
+        let index = self
+            .offset_index
+            .partition_point(|(indexed_offset, _)| *indexed_offset <= offset);
+        if index == 0 {
+            return SEGMENT_HEADER_LEN;
+        }
+        return self.offset_index[index - 1].1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{key_remote_index, key_remote_segment, parse_remote_index_key, RemoteSegment};
+    use crate::shard::segment::SEGMENT_HEADER_LEN;
+
+    #[test]
+    fn remote_segment_encode_decode() {
+        let segment = RemoteSegment {
+            start_offset: 100,
+            next_offset: 300,
+            size: 20000,
+            max_timestamp: 1700000000000,
+            offset_index: vec![(100, 16), (150, 4200), (230, 8400)],
+            time_index: vec![(1000, 100, 16), (1500, 150, 4200)],
+        };
+        let data = segment.encode();
+        assert_eq!(RemoteSegment::decode(&data).unwrap(), segment);
+        assert!(RemoteSegment::decode(&data[..data.len() - 1]).is_err());
+        assert!(RemoteSegment::decode(&[]).is_err());
+
+        assert_eq!(segment.offset_position(100), 16);
+        assert_eq!(segment.offset_position(149), 16);
+        assert_eq!(segment.offset_position(150), 4200);
+        assert_eq!(segment.offset_position(299), 8400);
+        assert!(segment.contains(299));
+        assert!(!segment.contains(300));
+        assert_eq!(
+            RemoteSegment::default().offset_position(5),
+            SEGMENT_HEADER_LEN
+        );
+    }
+
+    #[test]
+    fn remote_segment_keys() {
+        assert_eq!(
+            key_remote_segment("c1", "s1", 100),
+            "c1/s1/00000000000000000100.log"
+        );
+        let key = key_remote_index("c1", "s1", 100);
+        assert_eq!(key, "c1/s1/00000000000000000100.index");
+        assert_eq!(parse_remote_index_key(&key), Some(100));
+        assert_eq!(
+            parse_remote_index_key(&key_remote_segment("c1", "s1", 100)),
+            None
+        );
+    }
+}
@@ -0,0 +1,179 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::cache::CacheManager;
+use common_base::error::{common::CommonError, mqtt_broker::MQTTBrokerError};
+use metadata_struct::mqtt::topic::MQTTTopic;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use storage_adapter::storage::StorageAdapter;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GroupOffset {
+    pub group_id: String,
+    // Offset of the last record consumed by the group, None if nothing has been committed.
+    pub committed_offset: Option<u128>,
+    pub earliest_offset: u128,
+    pub latest_offset: u128,
+    // Number of records that have not been consumed by the group.
+    pub lag: u128,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ResetOffsetStrategy {
+    Earliest,
+    Latest,
+    // Seconds since the epoch, the group resumes at the first record created at or after it.
+    Timestamp(u128),
+    Offset(u128),
+}
+
+impl ResetOffsetStrategy {
+    pub fn build(strategy: &str, value: u128) -> Result<Self, CommonError> {
+        match strategy.to_lowercase().as_str() {
+            "earliest" => return Ok(ResetOffsetStrategy::Earliest),
+            "latest" => return Ok(ResetOffsetStrategy::Latest),
+            "timestamp" => return Ok(ResetOffsetStrategy::Timestamp(value)),
+            "offset" => return Ok(ResetOffsetStrategy::Offset(value)),
+            _ => {
+                return Err(CommonError::CommmonError(format!(
+                    "Unsupported reset offset strategy [{}], optional: earliest, latest, timestamp, offset",
+                    strategy
+                )));
+            }
+        }
+    }
+}
+
+fn get_topic(
+    metadata_cache: &Arc<CacheManager>,
+    topic_name: &String,
+) -> Result<MQTTTopic, CommonError> {
+    if let Some(topic) = metadata_cache.get_topic_by_name(topic_name) {
+        return Ok(topic);
+    }
+    return Err(MQTTBrokerError::TopicDoesNotExist(topic_name.clone()).into());
+}
+
+pub async fn list_group_offset<S>(
+    metadata_cache: &Arc<CacheManager>,
+    message_storage_adapter: &Arc<S>,
+    topic_name: &String,
+) -> Result<Vec<GroupOffset>, CommonError>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    let topic = get_topic(metadata_cache, topic_name)?;
+    let shard_name = topic.topic_id;
+    let shard_offset = message_storage_adapter
+        .stream_shard_offset(shard_name.clone())
+        .await?;
+
+    let mut results = Vec::new();
+    for group_id in message_storage_adapter
+        .stream_list_group(shard_name.clone())
+        .await?
+    {
+        let committed_offset = message_storage_adapter
+            .stream_group_offset(shard_name.clone(), group_id.clone())
+            .await?;
+        let next_offset = if let Some(offset) = committed_offset {
+            offset + 1
+        } else {
+            shard_offset.earliest_offset
+        };
+        results.push(GroupOffset {
+            group_id,
+            committed_offset,
+            earliest_offset: shard_offset.earliest_offset,
+            latest_offset: shard_offset.latest_offset,
+            lag: shard_offset.latest_offset.saturating_sub(next_offset),
+        });
+    }
+    return Ok(results);
+}
+
+// Returns the offset from which the group will read next.
+pub async fn reset_group_offset<S>(
+    metadata_cache: &Arc<CacheManager>,
+    message_storage_adapter: &Arc<S>,
+    topic_name: &String,
+    group_id: &String,
+    strategy: ResetOffsetStrategy,
+) -> Result<u128, CommonError>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    if group_id.is_empty() {
+        return Err(CommonError::ParameterCannotBeNull("group_id".to_string()));
+    }
+    let topic = get_topic(metadata_cache, topic_name)?;
+    let shard_name = topic.topic_id;
+    let shard_offset = message_storage_adapter
+        .stream_shard_offset(shard_name.clone())
+        .await?;
+
+    let offset = match strategy {
+        ResetOffsetStrategy::Earliest => shard_offset.earliest_offset,
+        ResetOffsetStrategy::Latest => shard_offset.latest_offset,
+        ResetOffsetStrategy::Timestamp(timestamp) => {
+            match message_storage_adapter
+                .stream_offset_by_timestamp(shard_name.clone(), timestamp)
+                .await?
+            {
+                Some(offset) => offset,
+                None => shard_offset.latest_offset,
+            }
+        }
+        ResetOffsetStrategy::Offset(offset) => {
+            if offset < shard_offset.earliest_offset || offset > shard_offset.latest_offset {
+                return Err(CommonError::CommmonError(format!(
+                    "Offset {} is out of range [{}, {}]",
+                    offset, shard_offset.earliest_offset, shard_offset.latest_offset
+                )));
+            }
+            offset
+        }
+    };
+
+    message_storage_adapter
+        .stream_reset_group_offset(shard_name, group_id.clone(), offset)
+        .await?;
+    return Ok(offset);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ResetOffsetStrategy;
+
+    #[test]
+    fn reset_offset_strategy_build() {
+        assert_eq!(
+            ResetOffsetStrategy::build("earliest", 0).unwrap(),
+            ResetOffsetStrategy::Earliest
+        );
+        assert_eq!(
+            ResetOffsetStrategy::build("LATEST", 0).unwrap(),
+            ResetOffsetStrategy::Latest
+        );
+        assert_eq!(
+            ResetOffsetStrategy::build("timestamp", 1700000000).unwrap(),
+            ResetOffsetStrategy::Timestamp(1700000000)
+        );
+        assert_eq!(
+            ResetOffsetStrategy::build("offset", 5).unwrap(),
+            ResetOffsetStrategy::Offset(5)
+        );
+        assert!(ResetOffsetStrategy::build("unknown", 0).is_err());
+    }
+}
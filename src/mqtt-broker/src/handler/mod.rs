@@ -20,6 +20,7 @@ pub mod lastwill;
 pub mod retain;
 pub mod session;
 pub mod topic;
+pub mod group_offset;
 pub mod validator;
 pub mod response;
 pub mod heartbreat;
@@ -15,6 +15,7 @@ use crate::handler::cache::CacheManager;
 use crate::storage::topic::TopicStorage;
 use bytes::Bytes;
 use clients::poll::ClientPool;
+use common_base::config::broker_mqtt::broker_mqtt_conf;
 use common_base::error::common::CommonError;
 use common_base::error::mqtt_broker::MQTTBrokerError;
 use common_base::tools::unique_id;
@@ -22,7 +23,8 @@ use metadata_struct::mqtt::topic::MQTTTopic;
 use protocol::mqtt::common::{Publish, PublishProperties};
 use regex::Regex;
 use std::sync::Arc;
-use storage_adapter::storage::{ShardConfig, StorageAdapter};
+use storage_adapter::compression::CompressionType;
+use storage_adapter::storage::{ShardCleanupPolicy, ShardConfig, StorageAdapter};
 
 pub const SYSTEM_TOPIC_BROKERS: &str = "$SYS/brokers";
 pub const SYSTEM_TOPIC_BROKERS_VERSION: &str = "$SYS/brokers/${node}/version";
@@ -124,7 +126,7 @@ where
 
         // Create the resource object of the storage layer
         let shard_name = topic.topic_id.clone();
-        let shard_config = ShardConfig::default();
+        let shard_config = build_topic_shard_config(&topic_name);
         message_storage_adapter
             .create_shard(shard_name, shard_config)
             .await?;
@@ -133,6 +135,27 @@ where
     return Ok(topic);
 }
 
+// The retention and compression of the topic shard are taken from the [topic_retention]
+// and [topic_compression] configuration.
+pub fn build_topic_shard_config(topic_name: &String) -> ShardConfig {
+    let conf = broker_mqtt_conf();
+    let retention = &conf.topic_retention;
+    let compression = &conf.topic_compression;
+    let algorithm = if let Some(algorithm) = compression.topics.get(topic_name) {
+        algorithm
+    } else {
+        &compression.algorithm
+    };
+    return ShardConfig {
+        retention_sec: retention.retention_sec,
+        retention_bytes: retention.retention_bytes,
+        retention_record_num: retention.retention_record_num,
+        cleanup_policy: ShardCleanupPolicy::from_name(&retention.cleanup_policy),
+        compression: CompressionType::from_name(algorithm),
+        compression_min_size: compression.min_size,
+    };
+}
+
 #[cfg(test)]
 mod test {
 
@@ -28,13 +28,19 @@ use std::sync::Arc;
 use storage::cluster::ClusterStorage;
 use storage_adapter::memory::MemoryStorageAdapter;
 use storage_adapter::mysql::MySQLStorageAdapter;
+use storage_adapter::placement::PlacementStorageAdapter;
+use storage_adapter::retention::start_retention_thread;
+use storage_adapter::sqlite::SQLiteStorageAdapter;
 use storage_adapter::storage::StorageAdapter;
-use storage_adapter::{storage_is_memory, storage_is_mysql};
+use storage_adapter::{
+    storage_is_memory, storage_is_mysql, storage_is_placement, storage_is_sqlite,
+};
 use subscribe::{
     sub_exclusive::SubscribeExclusive, sub_share_follower::SubscribeShareFollower,
     sub_share_leader::SubscribeShareLeader, subscribe_manager::SubscribeManager,
 };
 use third_driver::mysql::build_mysql_conn_pool;
+use third_driver::sqlite::build_sqlite_conn;
 use tokio::{
     runtime::Runtime,
     signal,
@@ -68,8 +74,37 @@ pub fn start_mqtt_broker_server(stop_send: broadcast::Sender<bool>) {
         let message_storage_adapter = Arc::new(MySQLStorageAdapter::new(pool.clone()));
         let server = MqttBroker::new(client_poll, message_storage_adapter, metadata_cache);
         server.start(stop_send);
+    } else if storage_is_sqlite(&storage_type) {
+        if conf.storage.sqlite_path.is_empty() {
+            panic!("storaget type is [sqlite],[storage.sqlite_path] cannot be empty");
+        }
+        let conn = match build_sqlite_conn(&conf.storage.sqlite_path) {
+            Ok(conn) => conn,
+            Err(e) => {
+                panic!(
+                    "Failed to open the sqlite database [{}], {}",
+                    conf.storage.sqlite_path, e
+                );
+            }
+        };
+        let message_storage_adapter = Arc::new(SQLiteStorageAdapter::new(conn));
+        let server = MqttBroker::new(client_poll, message_storage_adapter, metadata_cache);
+        server.start(stop_send);
+    } else if storage_is_placement(&storage_type) {
+        if conf.placement_center.is_empty() {
+            panic!("storaget type is [placement],[placement_center] cannot be empty");
+        }
+        let message_storage_adapter = Arc::new(PlacementStorageAdapter::new(
+            client_poll.clone(),
+            conf.placement_center.clone(),
+            conf.cluster_name.clone(),
+        ));
+        let server = MqttBroker::new(client_poll, message_storage_adapter, metadata_cache);
+        server.start(stop_send);
     } else {
-        panic!("Message data storage type configuration error, optional :mysql, memory");
+        panic!(
+            "Message data storage type configuration error, optional :mysql, memory, sqlite, placement"
+        );
     };
 }
 
@@ -125,6 +160,7 @@ where
         self.start_websocket_server(stop_send.clone());
         self.start_keep_alive_thread(stop_send.clone());
         self.start_cluster_heartbeat_report(stop_send.clone());
+        self.start_retention_thread(stop_send.clone());
         self.start_push_server();
         self.awaiting_stop(stop_send);
     }
@@ -166,8 +202,11 @@ where
     }
 
     fn start_http_server(&self) {
-        let http_state =
-            HttpServerState::new(self.cache_manager.clone(), self.subscribe_manager.clone());
+        let http_state = HttpServerState::new(
+            self.cache_manager.clone(),
+            self.subscribe_manager.clone(),
+            self.message_storage_adapter.clone(),
+        );
         self.runtime
             .spawn(async move { start_http_server(http_state).await });
     }
@@ -206,6 +245,15 @@ where
         });
     }
 
+    fn start_retention_thread(&self, stop_send: broadcast::Sender<bool>) {
+        let conf = broker_mqtt_conf();
+        let message_storage_adapter = self.message_storage_adapter.clone();
+        let interval_sec = conf.topic_retention.check_interval_sec;
+        self.runtime.spawn(async move {
+            start_retention_thread(message_storage_adapter, interval_sec, stop_send).await;
+        });
+    }
+
     fn start_push_server(&self) {
         let subscribe_manager = self.subscribe_manager.clone();
         self.runtime.spawn(async move {
@@ -12,15 +12,22 @@
 // limitations under the License.
 
 use crate::handler::cache::CacheManager;
+use crate::handler::group_offset::{list_group_offset, reset_group_offset, ResetOffsetStrategy};
 use crate::handler::lastwill::send_last_will_message;
 use crate::subscribe::subscribe_manager::SubscribeManager;
 use clients::poll::ClientPool;
-use log::debug;
+use log::{debug, error};
 use metadata_struct::mqtt::lastwill::LastWillData;
+use metadata_struct::placement::broker_node::BrokerNode;
 use protocol::broker_server::generate::mqtt::{
-    mqtt_broker_service_server::MqttBrokerService, CommonReply, UpdateCacheRequest,
+    mqtt_broker_service_server::MqttBrokerService, CommonReply, UpdateCacheActionType,
+    UpdateCacheRequest, UpdateCacheResourceType,
 };
 use protocol::broker_server::generate::mqtt::{DeleteSessionRequest, SendLastWillMessageRequest};
+use protocol::broker_server::generate::mqtt::{
+    GroupOffset, ListGroupOffsetReply, ListGroupOffsetRequest, ResetGroupOffsetReply,
+    ResetGroupOffsetRequest,
+};
 use std::sync::Arc;
 use storage_adapter::storage::StorageAdapter;
 use tonic::{Request, Response, Status};
@@ -55,8 +62,28 @@ where
 {
     async fn update_cache(
         &self,
-        _: Request<UpdateCacheRequest>,
+        request: Request<UpdateCacheRequest>,
     ) -> Result<Response<CommonReply>, Status> {
+        let req = request.into_inner();
+        if self.cache_manager.cluster_name != req.cluster_name {
+            return Err(Status::cancelled("Cluster name does not match".to_string()));
+        }
+
+        match req.resource_type() {
+            UpdateCacheResourceType::Node => {
+                // The groups led by a removed broker have new leaders
+                if req.action_type() == UpdateCacheActionType::Delete {
+                    debug!("Broker {} was removed from the cluster.", req.data);
+                    match serde_json::from_str::<BrokerNode>(&req.data) {
+                        Ok(node) => {
+                            self.subscribe_manager
+                                .restart_share_follower_resub(node.node_id);
+                        }
+                        Err(e) => error!("{}", e),
+                    }
+                }
+            }
+        }
         return Ok(Response::new(CommonReply::default()));
     }
 
@@ -115,4 +142,66 @@ where
             }
         }
     }
+    async fn list_group_offset(
+        &self,
+        request: Request<ListGroupOffsetRequest>,
+    ) -> Result<Response<ListGroupOffsetReply>, Status> {
+        let req = request.into_inner();
+        match list_group_offset(
+            &self.cache_manager,
+            &self.message_storage_adapter,
+            &req.topic_name,
+        )
+        .await
+        {
+            Ok(data) => {
+                let groups = data
+                    .into_iter()
+                    .map(|raw| GroupOffset {
+                        group_id: raw.group_id,
+                        committed: raw.committed_offset.is_some(),
+                        committed_offset: raw.committed_offset.unwrap_or_default() as u64,
+                        earliest_offset: raw.earliest_offset as u64,
+                        latest_offset: raw.latest_offset as u64,
+                        lag: raw.lag as u64,
+                    })
+                    .collect();
+                return Ok(Response::new(ListGroupOffsetReply { groups }));
+            }
+            Err(e) => {
+                return Err(Status::cancelled(e.to_string()));
+            }
+        }
+    }
+
+    async fn reset_group_offset(
+        &self,
+        request: Request<ResetGroupOffsetRequest>,
+    ) -> Result<Response<ResetGroupOffsetReply>, Status> {
+        let req = request.into_inner();
+        let strategy = match ResetOffsetStrategy::build(&req.strategy, req.value as u128) {
+            Ok(strategy) => strategy,
+            Err(e) => {
+                return Err(Status::cancelled(e.to_string()));
+            }
+        };
+        match reset_group_offset(
+            &self.cache_manager,
+            &self.message_storage_adapter,
+            &req.topic_name,
+            &req.group_id,
+            strategy,
+        )
+        .await
+        {
+            Ok(offset) => {
+                return Ok(Response::new(ResetGroupOffsetReply {
+                    offset: offset as u64,
+                }));
+            }
+            Err(e) => {
+                return Err(Status::cancelled(e.to_string()));
+            }
+        }
+    }
 }
@@ -40,7 +40,7 @@ pub async fn metrics() -> String {
     return dump_metrics();
 }
 
-pub async fn cache_info(State(state): State<HttpServerState>) -> String {
+pub async fn cache_info<S>(State(state): State<HttpServerState<S>>) -> String {
     let connection_info = DashMap::with_capacity(8);
     for raw in state.cache_metadata.connection_info.clone() {
         let data = format!("{:?}", raw.1);
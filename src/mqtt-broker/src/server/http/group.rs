@@ -0,0 +1,92 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::server::HttpServerState;
+use crate::handler::group_offset::{list_group_offset, reset_group_offset, ResetOffsetStrategy};
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use common_base::http_response::{error_response_with_msg, success_response};
+use serde::{Deserialize, Serialize};
+use storage_adapter::storage::StorageAdapter;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GroupOffsetListRequest {
+    pub topic_name: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GroupOffsetResetRequest {
+    pub topic_name: String,
+    pub group_id: String,
+    // earliest, latest, timestamp, offset
+    pub strategy: String,
+    // The timestamp in seconds or the offset, depending on the strategy.
+    #[serde(default)]
+    pub value: u128,
+}
+
+pub async fn group_offset_list<S>(
+    State(state): State<HttpServerState<S>>,
+    Query(params): Query<GroupOffsetListRequest>,
+) -> String
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    match list_group_offset(
+        &state.cache_metadata,
+        &state.message_storage_adapter,
+        &params.topic_name,
+    )
+    .await
+    {
+        Ok(data) => {
+            return success_response(data);
+        }
+        Err(e) => {
+            return error_response_with_msg(e.to_string());
+        }
+    }
+}
+
+pub async fn group_offset_reset<S>(
+    State(state): State<HttpServerState<S>>,
+    Json(params): Json<GroupOffsetResetRequest>,
+) -> String
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    let strategy = match ResetOffsetStrategy::build(&params.strategy, params.value) {
+        Ok(strategy) => strategy,
+        Err(e) => {
+            return error_response_with_msg(e.to_string());
+        }
+    };
+    match reset_group_offset(
+        &state.cache_metadata,
+        &state.message_storage_adapter,
+        &params.topic_name,
+        &params.group_id,
+        strategy,
+    )
+    .await
+    {
+        Ok(offset) => {
+            return success_response(offset);
+        }
+        Err(e) => {
+            return error_response_with_msg(e.to_string());
+        }
+    }
+}
@@ -13,4 +13,5 @@
 
 
 mod cache;
+mod group;
 pub mod server;
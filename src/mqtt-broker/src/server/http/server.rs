@@ -12,34 +12,47 @@
 // limitations under the License.
 
 use super::cache::{cache_info, index, metrics};
+use super::group::{group_offset_list, group_offset_reset};
 use crate::handler::cache::CacheManager;
 use crate::subscribe::subscribe_manager::SubscribeManager;
-use axum::routing::get;
+use axum::routing::{get, post};
 use axum::Router;
 use common_base::config::broker_mqtt::broker_mqtt_conf;
 use log::info;
 use std::{net::SocketAddr, sync::Arc};
+use storage_adapter::storage::StorageAdapter;
 
 pub const ROUTE_ROOT: &str = "/";
 pub const ROUTE_CACHE: &str = "/caches";
 pub const ROUTE_METRICS: &str = "/metrics";
+pub const ROUTE_GROUP_OFFSET_LIST: &str = "/group/offset/list";
+pub const ROUTE_GROUP_OFFSET_RESET: &str = "/group/offset/reset";
 
 #[derive(Clone)]
-pub struct HttpServerState {
+pub struct HttpServerState<S> {
     pub cache_metadata: Arc<CacheManager>,
     pub subscribe_cache: Arc<SubscribeManager>,
+    pub message_storage_adapter: Arc<S>,
 }
 
-impl HttpServerState {
-    pub fn new(cache_metadata: Arc<CacheManager>, subscribe_cache: Arc<SubscribeManager>) -> Self {
+impl<S> HttpServerState<S> {
+    pub fn new(
+        cache_metadata: Arc<CacheManager>,
+        subscribe_cache: Arc<SubscribeManager>,
+        message_storage_adapter: Arc<S>,
+    ) -> Self {
         return Self {
             cache_metadata,
             subscribe_cache,
+            message_storage_adapter,
         };
     }
 }
 
-pub async fn start_http_server(state: HttpServerState) {
+pub async fn start_http_server<S>(state: HttpServerState<S>)
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
     let config = broker_mqtt_conf();
     let ip: SocketAddr = format!("0.0.0.0:{}", config.http_port).parse().unwrap();
     let app = routes_v1(state);
@@ -51,11 +64,16 @@ pub async fn start_http_server(state: HttpServerState) {
     axum::serve(listener, app).await.unwrap();
 }
 
-fn routes_v1(state: HttpServerState) -> Router {
+fn routes_v1<S>(state: HttpServerState<S>) -> Router
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
     let meta = Router::new()
         .route(ROUTE_ROOT, get(index))
-        .route(ROUTE_CACHE, get(cache_info))
-        .route(ROUTE_METRICS, get(metrics));
+        .route(ROUTE_CACHE, get(cache_info::<S>))
+        .route(ROUTE_METRICS, get(metrics))
+        .route(ROUTE_GROUP_OFFSET_LIST, get(group_offset_list::<S>))
+        .route(ROUTE_GROUP_OFFSET_RESET, post(group_offset_reset::<S>));
 
     let app = Router::new().merge(meta);
     return app.with_state(state);
@@ -17,6 +17,7 @@ impl AclStorage {
         let config = broker_mqtt_conf();
         let request = ListAclRequest {
             cluster_name: config.cluster_name.clone(),
+            stale_read: false,
         };
         match list_acl(
             self.client_poll.clone(),
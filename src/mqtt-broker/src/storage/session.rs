@@ -77,6 +77,9 @@ impl SessionStorage {
             broker_id,
             reconnect_time,
             distinct_time,
+            expected_broker_id: 0,
+            expected_connection_id: 0,
+            expected_reconnect_time: 0,
         };
         match placement_update_session(
             self.client_poll.clone(),
@@ -117,6 +120,7 @@ impl SessionStorage {
         let request = ListSessionRequest {
             cluster_name: config.cluster_name.clone(),
             client_id,
+            stale_read: false,
         };
         match placement_list_session(
             self.client_poll.clone(),
@@ -148,6 +152,7 @@ impl SessionStorage {
         let request = ListSessionRequest {
             cluster_name: config.cluster_name.clone(),
             client_id: "".to_string(),
+            stale_read: false,
         };
         match placement_list_session(
             self.client_poll.clone(),
@@ -84,6 +84,7 @@ impl TopicStorage {
         let request = ListTopicRequest {
             cluster_name: config.cluster_name.clone(),
             topic_name: "".to_string(),
+            stale_read: false,
         };
         match placement_list_topic(
             self.client_poll.clone(),
@@ -117,6 +118,7 @@ impl TopicStorage {
         let request = ListTopicRequest {
             cluster_name: config.cluster_name.clone(),
             topic_name,
+            stale_read: false,
         };
         match placement_list_topic(
             self.client_poll.clone(),
@@ -211,12 +213,7 @@ impl TopicStorage {
             if retain_message.len() == 0 {
                 return Ok(None);
             }
-            let message = match serde_json::from_slice::<MQTTMessage>(retain_message.as_slice()) {
-                Ok(data) => data,
-                Err(e) => {
-                    return Err(CommonError::CommmonError(e.to_string()));
-                }
-            };
+            let message = MQTTMessage::decode(retain_message.as_slice())?;
             return Ok(Some(message));
         }
 
@@ -77,6 +77,7 @@ impl UserStorage {
         let request = ListUserRequest {
             cluster_name: config.cluster_name.clone(),
             username,
+            stale_read: false,
         };
         match placement_list_user(
             self.client_poll.clone(),
@@ -110,6 +111,7 @@ impl UserStorage {
         let request = ListUserRequest {
             cluster_name: config.cluster_name.clone(),
             username: "".to_string(),
+            stale_read: false,
         };
         match placement_list_user(
             self.client_poll.clone(),
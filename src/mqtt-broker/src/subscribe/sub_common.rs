@@ -135,6 +135,7 @@ pub async fn get_share_sub_leader(
     let req = GetShareSubLeaderRequest {
         cluster_name: conf.cluster_name.clone(),
         group_name,
+        stale_read: false,
     };
     match placement_get_share_sub_leader(client_poll, conf.placement_center.clone(), req).await {
         Ok(reply) => {
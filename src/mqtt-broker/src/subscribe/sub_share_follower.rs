@@ -16,7 +16,7 @@ use super::{
         get_share_sub_leader, publish_message_qos0, publish_message_to_client, qos2_send_publish,
         qos2_send_pubrel, wait_packet_ack,
     },
-    subscribe_manager::SubscribeManager,
+    subscribe_manager::{ShareFollowerResubThread, SubscribeManager},
 };
 use crate::{
     handler::cache::{
@@ -145,9 +145,13 @@ impl SubscribeShareFollower {
                                     &reply.extend_info
                                 ) {
                                     Ok(da) => {
-                                        subscribe_manager
-                                            .share_follower_resub_thread
-                                            .insert(follower_resub_key.clone(), stop_sx.clone());
+                                        subscribe_manager.share_follower_resub_thread.insert(
+                                            follower_resub_key.clone(),
+                                            ShareFollowerResubThread {
+                                                leader_broker_id: reply.broker_id,
+                                                stop_sx: stop_sx.clone(),
+                                            },
+                                        );
                                         da
                                     }
                                     Err(e) => {
@@ -180,13 +184,15 @@ impl SubscribeShareFollower {
     }
 
     fn try_thread_gc(&self) {
-        for (share_fllower_key, sx) in self.subscribe_manager.share_follower_resub_thread.clone() {
+        for (share_fllower_key, thread) in
+            self.subscribe_manager.share_follower_resub_thread.clone()
+        {
             if !self
                 .subscribe_manager
                 .share_follower_subscribe
                 .contains_key(&share_fllower_key)
             {
-                match sx.send(true) {
+                match thread.stop_sx.send(true) {
                     Ok(_) => {
                         self.subscribe_manager
                             .share_follower_subscribe
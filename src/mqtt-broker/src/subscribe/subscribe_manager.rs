@@ -43,6 +43,13 @@ pub struct ShareLeaderSubscribeData {
     pub sub_list: DashMap<String, Subscriber>,
 }
 
+#[derive(Clone)]
+pub struct ShareFollowerResubThread {
+    // The broker leading the group that the thread resubscribes to
+    pub leader_broker_id: u64,
+    pub stop_sx: Sender<bool>,
+}
+
 #[derive(Clone)]
 pub struct SubscribeManager {
     client_poll: Arc<ClientPool>,
@@ -63,8 +70,8 @@ pub struct SubscribeManager {
     // (client_id_group_name_sub_name,ShareSubShareSub)
     pub share_follower_subscribe: DashMap<String, ShareSubShareSub>,
 
-    // (client_id_group_name_sub_name, Sender<bool>)
-    pub share_follower_resub_thread: DashMap<String, Sender<bool>>,
+    // (client_id_group_name_sub_name, ShareFollowerResubThread)
+    pub share_follower_resub_thread: DashMap<String, ShareFollowerResubThread>,
 
     // (identifier_id，client_id)
     pub share_follower_identifier_id: DashMap<usize, String>,
@@ -160,6 +167,25 @@ impl SubscribeManager {
         }
     }
 
+    // Stops the resubscription threads of the shared subscriptions led by the broker, so
+    // that they resolve the leaders of their groups again.
+    pub fn restart_share_follower_resub(&self, leader_broker_id: u64) {
+        for (key, thread) in self.share_follower_resub_thread.clone() {
+            if thread.leader_broker_id != leader_broker_id {
+                continue;
+            }
+            // The thread may have stopped already
+            match thread.stop_sx.send(true) {
+                Ok(_) => {}
+                Err(e) => error!(
+                    "Failed to stop the resubscription thread {} of broker {}, {}",
+                    key, leader_broker_id, e
+                ),
+            }
+            self.share_follower_resub_thread.remove(&key);
+        }
+    }
+
     pub fn remove_subscribe(&self, client_id: &String, filter_path: &Vec<String>) {
         for (topic_name, _) in self.metadata_cache.topic_info.clone() {
             for path in filter_path.clone() {
@@ -205,8 +231,8 @@ impl SubscribeManager {
                 for (key, data) in self.share_follower_subscribe.clone() {
                     if data.client_id == *client_id && data.filter.path == path {
                         self.share_follower_subscribe.remove(&key);
-                        if let Some(sx) = self.share_follower_resub_thread.get(&key) {
-                            match sx.send(true) {
+                        if let Some(thread) = self.share_follower_resub_thread.get(&key) {
+                            match thread.stop_sx.send(true) {
                                 Ok(_) => {}
                                 Err(e) => error!("{}", e),
                             }
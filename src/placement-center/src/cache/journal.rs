@@ -12,14 +12,34 @@
 // limitations under the License.
 
 use dashmap::DashMap;
+use log::error;
+use protocol::placement_center::generate::common::ClusterType;
 use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
 
-use crate::storage::journal::{segment::SegmentInfo, shard::ShardInfo};
+use crate::storage::{
+    journal::{
+        segment::{SegmentInfo, SegmentStatus, SegmentStorage},
+        shard::{ShardInfo, ShardStorage},
+    },
+    placement::cluster::ClusterStorage,
+    rocksdb::RocksDBEngine,
+};
+
+// Progress of a shard on one journal node, as last reported by the node.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ShardProgress {
+    pub next_offset: u64,
+    pub high_watermark: u64,
+    pub log_bytes: u64,
+    pub report_time: u64,
+}
 
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct JournalCacheManager {
     pub shard_list: DashMap<String, ShardInfo>,
     pub segment_list: DashMap<String, SegmentInfo>,
+    pub shard_progress: DashMap<String, DashMap<u64, ShardProgress>>,
 }
 
 impl JournalCacheManager {
@@ -27,6 +47,7 @@ impl JournalCacheManager {
         return JournalCacheManager {
             shard_list: DashMap::with_capacity(8),
             segment_list: DashMap::with_capacity(256),
+            shard_progress: DashMap::with_capacity(8),
         };
     }
 
@@ -38,8 +59,9 @@ impl JournalCacheManager {
     }
 
     pub fn remove_shard(&self, cluster_name: String, shard_name: String) {
-        self.shard_list
-            .remove(&self.shard_key(cluster_name, shard_name));
+        let key = self.shard_key(cluster_name, shard_name);
+        self.shard_list.remove(&key);
+        self.shard_progress.remove(&key);
     }
 
     pub fn next_segment_seq(&self, cluster_name: &String, shard_name: &String) -> u64 {
@@ -50,7 +72,6 @@ impl JournalCacheManager {
         return 1;
     }
     
-    #[allow(dead_code)]
     pub fn get_shard(&self, cluster_name: String, shard_name: String) -> Option<ShardInfo> {
         let key = self.shard_key(cluster_name, shard_name);
         if let Some(shard) = self.shard_list.get(&key) {
@@ -59,6 +80,15 @@ impl JournalCacheManager {
         return None;
     }
 
+    pub fn list_shard(&self, cluster_name: &String) -> Vec<ShardInfo> {
+        return self
+            .shard_list
+            .iter()
+            .filter(|shard| shard.cluster_name.eq(cluster_name))
+            .map(|shard| shard.clone())
+            .collect();
+    }
+
     pub fn add_segment(&self, segment: SegmentInfo) {
         let key = self.segment_key(
             segment.cluster_name.clone(),
@@ -69,6 +99,102 @@ impl JournalCacheManager {
         self.segment_list.insert(key.clone(), segment.clone());
     }
 
+    // The segments of the shard in sequence order.
+    pub fn list_shard_segment(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+    ) -> Vec<SegmentInfo> {
+        let mut results: Vec<SegmentInfo> = self
+            .segment_list
+            .iter()
+            .filter(|segment| {
+                segment.cluster_name.eq(cluster_name) && segment.shard_name.eq(shard_name)
+            })
+            .map(|segment| segment.clone())
+            .collect();
+        results.sort_by_key(|segment| segment.segment_seq);
+        return results;
+    }
+
+    // The segment taking the records of the shard.
+    pub fn get_active_segment(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+    ) -> Option<SegmentInfo> {
+        return self
+            .list_shard_segment(cluster_name, shard_name)
+            .into_iter()
+            .find(|segment| segment.status == SegmentStatus::Write);
+    }
+
+    // The pre-created segment that takes over when the active segment is rolled.
+    pub fn get_next_segment(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+    ) -> Option<SegmentInfo> {
+        return self
+            .list_shard_segment(cluster_name, shard_name)
+            .into_iter()
+            .find(|segment| segment.status == SegmentStatus::Idle);
+    }
+
+    pub fn get_segment(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+        segment_seq: u64,
+    ) -> Option<SegmentInfo> {
+        let key = self.segment_key(cluster_name.clone(), shard_name.clone(), segment_seq);
+        if let Some(segment) = self.segment_list.get(&key) {
+            return Some(segment.clone());
+        }
+        return None;
+    }
+
+    pub fn report_shard_progress(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+        node_id: u64,
+        progress: ShardProgress,
+    ) {
+        let key = self.shard_key(cluster_name.clone(), shard_name.clone());
+        if let Some(data) = self.shard_progress.get_mut(&key) {
+            data.insert(node_id, progress);
+        } else {
+            let data = DashMap::with_capacity(2);
+            data.insert(node_id, progress);
+            self.shard_progress.insert(key, data);
+        }
+    }
+
+    pub fn get_shard_progress(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+    ) -> HashMap<u64, ShardProgress> {
+        let key = self.shard_key(cluster_name.clone(), shard_name.clone());
+        if let Some(data) = self.shard_progress.get(&key) {
+            return data
+                .iter()
+                .map(|raw| (*raw.key(), raw.value().clone()))
+                .collect();
+        }
+        return HashMap::new();
+    }
+
+    pub fn list_segment(&self, cluster_name: &String) -> Vec<SegmentInfo> {
+        return self
+            .segment_list
+            .iter()
+            .filter(|segment| segment.cluster_name.eq(cluster_name))
+            .map(|segment| segment.clone())
+            .collect();
+    }
+
     pub fn remove_segment(&self, cluster_name: String, shard_name: String, segment_seq: u64) {
         let key = self.segment_key(cluster_name.clone(), shard_name.clone(), segment_seq);
         self.segment_list.remove(&key);
@@ -81,4 +207,54 @@ impl JournalCacheManager {
     fn segment_key(&self, cluster_name: String, shard_name: String, segment_num: u64) -> String {
         return format!("{}_{}_{}", cluster_name, shard_name, segment_num);
     }
+
+    // Loads the shards and segments of every journal cluster.
+    pub fn load_cache(&self, rocksdb_engine_handler: Arc<RocksDBEngine>) {
+        let cluster_handler = ClusterStorage::new(rocksdb_engine_handler.clone());
+        let cluster_list = match cluster_handler
+            .list(Some(ClusterType::JournalServer.as_str_name().to_string()))
+        {
+            Ok(data) => data,
+            Err(e) => {
+                error!("Failed to load the journal clusters, {}", e);
+                return;
+            }
+        };
+
+        let shard_handler = ShardStorage::new(rocksdb_engine_handler.clone());
+        let segment_handler = SegmentStorage::new(rocksdb_engine_handler.clone());
+        for cluster in cluster_list {
+            match shard_handler.list_by_shard(&cluster.cluster_name) {
+                Ok(shards) => {
+                    for shard in shards {
+                        self.add_shard(shard);
+                    }
+                }
+                Err(e) => {
+                    error!(
+                        "Failed to load the shards of cluster {}, {}",
+                        cluster.cluster_name, e
+                    );
+                }
+            }
+            match segment_handler.list_by_cluster(&cluster.cluster_name) {
+                Ok(segments) => {
+                    for segment in segments {
+                        self.add_segment(segment);
+                    }
+                }
+                Err(e) => {
+                    error!(
+                        "Failed to load the segments of cluster {}, {}",
+                        cluster.cluster_name, e
+                    );
+                }
+            }
+        }
+    }
+
+    pub fn clear(&self) {
+        self.shard_list.clear();
+        self.segment_list.clear();
+    }
 }
@@ -0,0 +1,54 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::storage::placement::kv::KvEvent;
+use dashmap::DashMap;
+use tokio::sync::broadcast;
+
+pub struct KvCacheManager {
+    // Time of the last keepalive of every lease
+    pub lease_keepalive: DashMap<u64, u64>,
+    // Events applied on this node, for the watchers connected to it
+    event_sender: broadcast::Sender<KvEvent>,
+}
+
+impl KvCacheManager {
+    pub fn new() -> KvCacheManager {
+        let (event_sender, _) = broadcast::channel(1000);
+        return KvCacheManager {
+            lease_keepalive: DashMap::with_capacity(8),
+            event_sender,
+        };
+    }
+
+    pub fn keepalive(&self, lease_id: u64, time: u64) {
+        self.lease_keepalive.insert(lease_id, time);
+    }
+
+    pub fn remove_lease(&self, lease_id: u64) {
+        self.lease_keepalive.remove(&lease_id);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<KvEvent> {
+        return self.event_sender.subscribe();
+    }
+
+    pub fn notify(&self, event: KvEvent) {
+        // Fails only when no watcher is connected
+        let _ = self.event_sender.send(event);
+    }
+
+    pub fn clear(&self) {
+        self.lease_keepalive.clear();
+    }
+}
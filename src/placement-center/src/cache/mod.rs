@@ -14,4 +14,5 @@
 
 pub mod placement;
 pub mod journal;
-pub mod mqtt;
\ No newline at end of file
+pub mod mqtt;
+pub mod kv;
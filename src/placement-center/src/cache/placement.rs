@@ -22,11 +22,29 @@ use crate::storage::{
     rocksdb::RocksDBEngine,
 };
 
+// Resource usage last reported by a node through ReportMonitor.
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct NodeMonitor {
+    pub cpu_rate: f32,
+    pub memory_rate: f32,
+    pub disk_rate: f32,
+    pub folds: Vec<FoldMonitor>,
+    pub report_time: u64,
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct FoldMonitor {
+    pub fold: String,
+    pub used_bytes: u64,
+    pub segment_num: u64,
+}
+
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct PlacementCacheManager {
     pub cluster_list: DashMap<String, ClusterInfo>,
     pub node_list: DashMap<String, DashMap<u64, BrokerNode>>,
     pub node_heartbeat: DashMap<String, DashMap<u64, u64>>,
+    pub node_monitor: DashMap<String, DashMap<u64, NodeMonitor>>,
 }
 
 impl PlacementCacheManager {
@@ -35,6 +53,7 @@ impl PlacementCacheManager {
             cluster_list: DashMap::with_capacity(2),
             node_heartbeat: DashMap::with_capacity(2),
             node_list: DashMap::with_capacity(2),
+            node_monitor: DashMap::with_capacity(2),
         };
         cache.load_cache(rocksdb_engine_handler);
         return cache;
@@ -64,6 +83,16 @@ impl PlacementCacheManager {
         if let Some(data) = self.node_heartbeat.get_mut(cluster_name) {
             data.remove(&node_id);
         }
+        if let Some(data) = self.node_monitor.get_mut(cluster_name) {
+            data.remove(&node_id);
+        }
+    }
+
+    pub fn get_cluster_nodes(&self, cluster_name: &String) -> Vec<BrokerNode> {
+        if let Some(data) = self.node_list.get(cluster_name) {
+            return data.iter().map(|raw| raw.value().clone()).collect();
+        }
+        return Vec::new();
     }
 
     pub fn get_node_addr(&self, cluster_name: &String, node_id: u64) -> Option<BrokerNode> {
@@ -97,6 +126,32 @@ impl PlacementCacheManager {
         }
     }
 
+    pub fn report_monitor(&self, cluster_name: &String, node_id: u64, monitor: NodeMonitor) {
+        if let Some(data) = self.node_monitor.get_mut(cluster_name) {
+            data.insert(node_id, monitor);
+        } else {
+            let data = DashMap::with_capacity(2);
+            data.insert(node_id, monitor);
+            self.node_monitor.insert(cluster_name.clone(), data);
+        }
+    }
+
+    pub fn get_node_monitor(&self, cluster_name: &String, node_id: u64) -> Option<NodeMonitor> {
+        if let Some(data) = self.node_monitor.get(cluster_name) {
+            if let Some(value) = data.get(&node_id) {
+                return Some(value.clone());
+            }
+        }
+        return None;
+    }
+
+    // Drops the cached metadata, node monitors are kept as they are reported by the nodes.
+    pub fn clear(&self) {
+        self.cluster_list.clear();
+        self.node_list.clear();
+        self.node_heartbeat.clear();
+    }
+
     pub fn load_cache(&self, rocksdb_engine_handler: Arc<RocksDBEngine>) {
         let cluster = ClusterStorage::new(rocksdb_engine_handler.clone());
         match cluster.list(None) {
@@ -11,14 +11,46 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::preferred_election::PreferredElection;
+use super::{preferred_election::PreferredElection, segment_lifecycle::SegmentLifecycle};
+use crate::{
+    cache::{journal::JournalCacheManager, placement::PlacementCacheManager},
+    raft::{apply::RaftMachineApply, metadata::RaftGroupMetadata},
+    storage::rocksdb::RocksDBEngine,
+};
+use common_base::config::placement_center::placement_center_conf;
 use log::info;
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use tokio::{select, sync::broadcast, time::sleep};
 
-pub struct StorageEngineController {}
+pub struct StorageEngineController {
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+    placement_cache: Arc<RwLock<RaftGroupMetadata>>,
+    placement_center_storage: Arc<RaftMachineApply>,
+    cluster_cache: Arc<PlacementCacheManager>,
+    engine_cache: Arc<JournalCacheManager>,
+    stop_send: broadcast::Sender<bool>,
+}
 
 impl StorageEngineController {
-    pub fn new() -> StorageEngineController {
-        let controller = StorageEngineController {};
+    pub fn new(
+        rocksdb_engine_handler: Arc<RocksDBEngine>,
+        placement_cache: Arc<RwLock<RaftGroupMetadata>>,
+        placement_center_storage: Arc<RaftMachineApply>,
+        cluster_cache: Arc<PlacementCacheManager>,
+        engine_cache: Arc<JournalCacheManager>,
+        stop_send: broadcast::Sender<bool>,
+    ) -> StorageEngineController {
+        let controller = StorageEngineController {
+            rocksdb_engine_handler,
+            placement_cache,
+            placement_center_storage,
+            cluster_cache,
+            engine_cache,
+            stop_send,
+        };
         controller.load_cache();
         return controller;
     }
@@ -30,38 +62,71 @@ impl StorageEngineController {
     }
 
     pub fn load_cache(&self) {
-        // let cluster_handler = ClusterStorage::new(self.rocksdb_engine_handler.clone());
-        // let cluster_list =
-        //     cluster_handler.list(Some(ClusterType::JournalServer.as_str_name().to_string()));
-
-        // let mut engine_cache = self.engine_cache.write().unwrap();
-        // let node_handler = NodeStorage::new(self.rocksdb_engine_handler.clone());
-        // let shard_handler = ShardStorage::new(self.rocksdb_engine_handler.clone());
-
-        // for cluster in cluster_list {
-        //     let cluster_name = cluster.cluster_name.clone();
-
-        //     // load shard cache
-        //     let shard_list = shard_handler.shard_list(cluster_name.clone());
-        //     for shard in shard_list {
-        //         engine_cache.add_shard(shard.clone());
-        //         let segment_list =
-        //             shard_handler.segment_list(cluster_name.clone(), shard.shard_name);
-        //         for segment in segment_list {
-        //             engine_cache.add_segment(segment);
-        //         }
-        //     }
-        // }
+        self.engine_cache
+            .load_cache(self.rocksdb_engine_handler.clone());
     }
 
+    // Rolls, seals and pre-creates segments, and re-elects the leaders of segments whose
+    // leader node left the cluster. Only the Raft leader acts.
     pub fn resource_manager_thread(&self) {
-        tokio::spawn(async move {});
+        let config = placement_center_conf().journal_controller.clone();
+        let interval = Duration::from_millis(config.segment_check_interval_ms);
+        let lifecycle = SegmentLifecycle::new(
+            self.cluster_cache.clone(),
+            self.engine_cache.clone(),
+            self.placement_center_storage.clone(),
+            config,
+        );
+        let placement_cache = self.placement_cache.clone();
+        let mut stop_recv = self.stop_send.subscribe();
+        tokio::spawn(async move {
+            loop {
+                select! {
+                    val = stop_recv.recv() => {
+                        if let Ok(flag) = val {
+                            if flag {
+                                break;
+                            }
+                        }
+                    }
+                    _ = sleep(interval) => {
+                        if placement_cache.read().unwrap().is_leader() {
+                            lifecycle.check_segments().await;
+                        }
+                    }
+                }
+            }
+        });
     }
 
     pub fn preferred_replica_election(&self) {
-        let election = PreferredElection::new();
+        let config = placement_center_conf();
+        let interval =
+            Duration::from_millis(config.journal_controller.preferred_election_interval_ms);
+        let election = PreferredElection::new(
+            self.cluster_cache.clone(),
+            self.engine_cache.clone(),
+            self.placement_center_storage.clone(),
+        );
+        let placement_cache = self.placement_cache.clone();
+        let mut stop_recv = self.stop_send.subscribe();
         tokio::spawn(async move {
-            election.start().await;
+            loop {
+                select! {
+                    val = stop_recv.recv() => {
+                        if let Ok(flag) = val {
+                            if flag {
+                                break;
+                            }
+                        }
+                    }
+                    _ = sleep(interval) => {
+                        if placement_cache.read().unwrap().is_leader() {
+                            election.start().await;
+                        }
+                    }
+                }
+            }
         });
     }
 }
@@ -12,6 +12,7 @@
 // limitations under the License.
 
 
+pub mod segment_lifecycle;
 pub mod segment_replica;
 pub mod preferred_election;
 pub mod controller;
\ No newline at end of file
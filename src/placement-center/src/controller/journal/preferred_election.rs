@@ -11,12 +11,207 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-pub struct PreferredElection {}
+use super::segment_lifecycle::propose_segment_leader;
+use crate::{
+    cache::{
+        journal::{JournalCacheManager, ShardProgress},
+        placement::PlacementCacheManager,
+    },
+    raft::apply::RaftMachineApply,
+    storage::journal::segment::{SegmentInfo, SegmentStatus},
+};
+use log::{info, warn};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+// The first replica of a segment is its preferred leader, placement spreads the preferred
+// leaders over the nodes. Leadership that moved away after a failure is handed back once
+// the preferred replica is alive and in sync again.
+pub struct PreferredElection {
+    cluster_cache: Arc<PlacementCacheManager>,
+    engine_cache: Arc<JournalCacheManager>,
+    placement_center_storage: Arc<RaftMachineApply>,
+}
 
 impl PreferredElection {
-    pub fn new() -> Self {
-        return PreferredElection {};
+    pub fn new(
+        cluster_cache: Arc<PlacementCacheManager>,
+        engine_cache: Arc<JournalCacheManager>,
+        placement_center_storage: Arc<RaftMachineApply>,
+    ) -> Self {
+        return PreferredElection {
+            cluster_cache,
+            engine_cache,
+            placement_center_storage,
+        };
+    }
+
+    pub async fn start(&self) {
+        let segments: Vec<SegmentInfo> = self
+            .engine_cache
+            .segment_list
+            .iter()
+            .filter(|raw| raw.status == SegmentStatus::Write && raw.replica_leader != 0)
+            .map(|raw| raw.value().clone())
+            .collect();
+        for segment in segments {
+            let alive: HashSet<u64> = self
+                .cluster_cache
+                .get_cluster_nodes(&segment.cluster_name)
+                .iter()
+                .map(|node| node.node_id)
+                .collect();
+            let progress = self
+                .engine_cache
+                .get_shard_progress(&segment.cluster_name, &segment.shard_name);
+            if !preferred_leader_ready(&segment, &alive, &progress) {
+                continue;
+            }
+            match propose_segment_leader(&self.placement_center_storage, &segment, 0).await {
+                Ok(()) => {
+                    info!(
+                        "Leadership of segment {} of shard {} moves back to its preferred replica",
+                        segment.segment_seq, segment.shard_name
+                    );
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to move segment {} of shard {} back to its preferred replica, {}",
+                        segment.segment_seq, segment.shard_name, e
+                    );
+                }
+            }
+        }
+    }
+}
+
+// The preferred replica takes the lead back when its node is alive and it holds every
+// record below the high watermark of the current leader.
+pub fn preferred_leader_ready(
+    segment: &SegmentInfo,
+    alive: &HashSet<u64>,
+    progress: &HashMap<u64, ShardProgress>,
+) -> bool {
+    let (preferred, leader) = match (segment.replicas.first(), segment.leader()) {
+        (Some(preferred), Some(leader)) => (preferred, leader),
+        _ => return false,
+    };
+    if segment.replica_leader == 0 || !alive.contains(&preferred.node_id) {
+        return false;
+    }
+    match (
+        progress.get(&preferred.node_id),
+        progress.get(&leader.node_id),
+    ) {
+        (Some(preferred), Some(leader)) => return preferred.next_offset >= leader.high_watermark,
+        _ => return false,
+    }
+}
+
+// Picks the new leader of a segment whose leader is gone, the alive replica holding the
+// most records. Replicas that never reported come last, ties go to the earlier replica.
+pub fn select_failover_leader(
+    segment: &SegmentInfo,
+    alive: &HashSet<u64>,
+    progress: &HashMap<u64, ShardProgress>,
+) -> Option<u32> {
+    let mut selected: Option<(u32, Option<u64>)> = None;
+    for (i, replica) in segment.replicas.iter().enumerate() {
+        if i as u32 == segment.replica_leader || !alive.contains(&replica.node_id) {
+            continue;
+        }
+        let next_offset = progress.get(&replica.node_id).map(|node| node.next_offset);
+        match selected {
+            Some((_, best)) if best >= next_offset => {}
+            _ => selected = Some((i as u32, next_offset)),
+        }
+    }
+    return selected.map(|(index, _)| index);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{preferred_leader_ready, select_failover_leader};
+    use crate::{
+        cache::journal::ShardProgress,
+        storage::journal::segment::{Replica, SegmentInfo, SegmentStatus},
+    };
+    use std::collections::{HashMap, HashSet};
+
+    fn build_segment(nodes: &[u64], replica_leader: u32) -> SegmentInfo {
+        return SegmentInfo {
+            segment_seq: 1,
+            shard_name: "s1".to_string(),
+            replicas: nodes
+                .iter()
+                .enumerate()
+                .map(|(i, node_id)| Replica {
+                    replica_seq: i as u64,
+                    node_id: *node_id,
+                    fold: "".to_string(),
+                })
+                .collect(),
+            replica_leader,
+            status: SegmentStatus::Write,
+            ..Default::default()
+        };
+    }
+
+    fn progress(next_offset: u64, high_watermark: u64) -> ShardProgress {
+        return ShardProgress {
+            next_offset,
+            high_watermark,
+            ..Default::default()
+        };
     }
 
-    pub async fn start(&self) {}
+    #[test]
+    fn failover_picks_the_most_caught_up_alive_replica() {
+        let segment = build_segment(&[1, 2, 3, 4], 0);
+        let mut progress_map = HashMap::new();
+        let alive: HashSet<u64> = [2, 3, 4].into_iter().collect();
+
+        // Nobody reported, the first alive replica is taken.
+        assert_eq!(
+            select_failover_leader(&segment, &alive, &progress_map),
+            Some(1)
+        );
+
+        progress_map.insert(1, progress(500, 400));
+        progress_map.insert(2, progress(300, 300));
+        progress_map.insert(3, progress(450, 400));
+        progress_map.insert(4, progress(450, 400));
+        assert_eq!(
+            select_failover_leader(&segment, &alive, &progress_map),
+            Some(2)
+        );
+
+        let alive: HashSet<u64> = [1].into_iter().collect();
+        assert_eq!(
+            select_failover_leader(&segment, &alive, &progress_map),
+            None
+        );
+    }
+
+    #[test]
+    fn preferred_replica_takes_back_the_lead_once_in_sync() {
+        let segment = build_segment(&[1, 2, 3], 1);
+        let mut progress_map = HashMap::new();
+        let mut alive: HashSet<u64> = [2, 3].into_iter().collect();
+        progress_map.insert(2, progress(500, 480));
+        progress_map.insert(1, progress(480, 470));
+        assert!(!preferred_leader_ready(&segment, &alive, &progress_map));
+
+        alive.insert(1);
+        assert!(preferred_leader_ready(&segment, &alive, &progress_map));
+
+        progress_map.insert(1, progress(479, 470));
+        assert!(!preferred_leader_ready(&segment, &alive, &progress_map));
+
+        // A segment already led by its preferred replica is left alone.
+        let segment = build_segment(&[1, 2, 3], 0);
+        assert!(!preferred_leader_ready(&segment, &alive, &progress_map));
+    }
 }
@@ -0,0 +1,447 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{preferred_election::select_failover_leader, segment_replica::SegmentReplicaAlgorithm};
+use crate::{
+    cache::{
+        journal::{JournalCacheManager, ShardProgress},
+        placement::PlacementCacheManager,
+    },
+    raft::apply::{RaftMachineApply, StorageData, StorageDataType},
+    storage::journal::{
+        segment::{SegmentInfo, SegmentStatus},
+        shard::ShardInfo,
+    },
+};
+use common_base::{
+    config::placement_center::JournalController, error::common::CommonError, tools::now_mills,
+};
+use log::{info, warn};
+use prost::Message;
+use protocol::placement_center::generate::journal::{
+    CreateSegmentRequest, RollSegmentRequest, UpdateSegmentLeaderRequest,
+    UpdateSegmentStatusRequest,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+// Moves the segments of every journal shard through their states. The segment being
+// written is rolled by size or age into the pre-created next segment, rolled segments are
+// sealed once their records are on the in-sync replicas, and a new next segment is
+// pre-created. Segments led by a node that left the cluster get a new leader.
+pub struct SegmentLifecycle {
+    cluster_cache: Arc<PlacementCacheManager>,
+    engine_cache: Arc<JournalCacheManager>,
+    placement_center_storage: Arc<RaftMachineApply>,
+    config: JournalController,
+}
+
+impl SegmentLifecycle {
+    pub fn new(
+        cluster_cache: Arc<PlacementCacheManager>,
+        engine_cache: Arc<JournalCacheManager>,
+        placement_center_storage: Arc<RaftMachineApply>,
+        config: JournalController,
+    ) -> Self {
+        return SegmentLifecycle {
+            cluster_cache,
+            engine_cache,
+            placement_center_storage,
+            config,
+        };
+    }
+
+    pub async fn check_segments(&self) {
+        let shards: Vec<ShardInfo> = self
+            .engine_cache
+            .shard_list
+            .iter()
+            .map(|raw| raw.value().clone())
+            .collect();
+        for shard in shards {
+            if let Err(e) = self.check_shard(&shard).await {
+                warn!(
+                    "Failed to check the segments of shard {}, {}",
+                    shard.shard_name, e
+                );
+            }
+        }
+    }
+
+    async fn check_shard(&self, shard: &ShardInfo) -> Result<(), CommonError> {
+        let cluster_name = &shard.cluster_name;
+        let shard_name = &shard.shard_name;
+        let progress = self
+            .engine_cache
+            .get_shard_progress(cluster_name, shard_name);
+        let segments = self
+            .engine_cache
+            .list_shard_segment(cluster_name, shard_name);
+
+        let active = segments
+            .iter()
+            .find(|segment| segment.status == SegmentStatus::Write);
+        let next = segments
+            .iter()
+            .find(|segment| segment.status == SegmentStatus::Idle);
+
+        if let Some(active) = active {
+            if !self.leader_alive(active) {
+                self.fail_over(active, &progress).await?;
+                return Ok(());
+            }
+        }
+
+        match (active, next) {
+            (Some(active), Some(next)) => {
+                if let Some((end_offset, end_bytes)) =
+                    roll_point(active, &progress, now_mills(), &self.config)
+                {
+                    self.roll(active, next, end_offset, end_bytes, &progress)
+                        .await?;
+                }
+            }
+            (_, None) => {
+                propose_create_segment(
+                    &self.placement_center_storage,
+                    &self.cluster_cache,
+                    &self.engine_cache,
+                    cluster_name,
+                    shard_name,
+                )
+                .await?;
+            }
+            (None, Some(_)) => {}
+        }
+
+        for segment in segments
+            .iter()
+            .filter(|segment| segment.status == SegmentStatus::PrepareSealUp)
+        {
+            if can_seal_up(segment, &progress) {
+                self.seal_up(segment).await?;
+            }
+        }
+        return Ok(());
+    }
+
+    async fn roll(
+        &self,
+        active: &SegmentInfo,
+        next: &SegmentInfo,
+        end_offset: u64,
+        end_bytes: u64,
+        progress: &HashMap<u64, ShardProgress>,
+    ) -> Result<(), CommonError> {
+        let replica_leader = match select_roll_leader(next, progress, end_offset) {
+            Some(index) => index,
+            None => {
+                // The replicas of the next segment copy the shard before the roll, it is
+                // tried again once one of them caught up.
+                info!(
+                    "Segment {} of shard {} is due to roll, but no replica of segment {} holds offset {} yet",
+                    active.segment_seq, active.shard_name, next.segment_seq, end_offset
+                );
+                return Ok(());
+            }
+        };
+        let req = RollSegmentRequest {
+            cluster_name: active.cluster_name.clone(),
+            shard_name: active.shard_name.clone(),
+            segment_seq: active.segment_seq,
+            end_offset,
+            end_bytes,
+            replica_leader,
+        };
+        let data = StorageData::new(
+            StorageDataType::JournalRollSegment,
+            RollSegmentRequest::encode_to_vec(&req),
+        );
+        self.placement_center_storage
+            .apply_propose_message(data, "roll_segment".to_string())
+            .await?;
+        info!(
+            "Segment {} of shard {} is rolled at offset {}, segment {} takes over",
+            active.segment_seq, active.shard_name, end_offset, next.segment_seq
+        );
+        return Ok(());
+    }
+
+    async fn seal_up(&self, segment: &SegmentInfo) -> Result<(), CommonError> {
+        let req = UpdateSegmentStatusRequest {
+            cluster_name: segment.cluster_name.clone(),
+            shard_name: segment.shard_name.clone(),
+            segment_seq: segment.segment_seq,
+            cur_status: format!("{:?}", SegmentStatus::PrepareSealUp),
+            next_status: format!("{:?}", SegmentStatus::SealUp),
+        };
+        let data = StorageData::new(
+            StorageDataType::JournalUpdateSegmentStatus,
+            UpdateSegmentStatusRequest::encode_to_vec(&req),
+        );
+        self.placement_center_storage
+            .apply_propose_message(data, "seal_up_segment".to_string())
+            .await?;
+        info!(
+            "Segment {} of shard {} is sealed up",
+            segment.segment_seq, segment.shard_name
+        );
+        return Ok(());
+    }
+
+    async fn fail_over(
+        &self,
+        segment: &SegmentInfo,
+        progress: &HashMap<u64, ShardProgress>,
+    ) -> Result<(), CommonError> {
+        let alive = self.alive_nodes(&segment.cluster_name);
+        let replica_leader = match select_failover_leader(segment, &alive, progress) {
+            Some(index) => index,
+            None => {
+                warn!(
+                    "The leader of segment {} of shard {} is gone and no other replica is alive",
+                    segment.segment_seq, segment.shard_name
+                );
+                return Ok(());
+            }
+        };
+        propose_segment_leader(&self.placement_center_storage, segment, replica_leader).await?;
+        info!(
+            "The leader of segment {} of shard {} is gone, replica {} takes over",
+            segment.segment_seq, segment.shard_name, replica_leader
+        );
+        return Ok(());
+    }
+
+    fn leader_alive(&self, segment: &SegmentInfo) -> bool {
+        match segment.leader() {
+            Some(replica) => {
+                return self
+                    .cluster_cache
+                    .get_node_addr(&segment.cluster_name, replica.node_id)
+                    .is_some()
+            }
+            None => return false,
+        }
+    }
+
+    fn alive_nodes(&self, cluster_name: &String) -> HashSet<u64> {
+        return self
+            .cluster_cache
+            .get_cluster_nodes(cluster_name)
+            .iter()
+            .map(|node| node.node_id)
+            .collect();
+    }
+}
+
+// Chooses the replicas of a new segment of the shard and proposes it. The replicas are
+// chosen before the proposal rather than when the log entry is applied, the monitor data
+// they are based on is only held in the memory of the leader.
+pub async fn propose_create_segment(
+    placement_center_storage: &Arc<RaftMachineApply>,
+    cluster_cache: &Arc<PlacementCacheManager>,
+    engine_cache: &Arc<JournalCacheManager>,
+    cluster_name: &String,
+    shard_name: &String,
+) -> Result<(), CommonError> {
+    let algorithm = SegmentReplicaAlgorithm::new(cluster_cache.clone(), engine_cache.clone());
+    let replicas = algorithm.calc_replica_distribution(cluster_name, shard_name)?;
+    let req = CreateSegmentRequest {
+        cluster_name: cluster_name.clone(),
+        shard_name: shard_name.clone(),
+        replicas: serde_json::to_vec(&replicas)?,
+    };
+    let data = StorageData::new(
+        StorageDataType::JournalCreateSegment,
+        CreateSegmentRequest::encode_to_vec(&req),
+    );
+    return placement_center_storage
+        .apply_propose_message(data, "create_segment".to_string())
+        .await;
+}
+
+pub async fn propose_segment_leader(
+    placement_center_storage: &Arc<RaftMachineApply>,
+    segment: &SegmentInfo,
+    replica_leader: u32,
+) -> Result<(), CommonError> {
+    let req = UpdateSegmentLeaderRequest {
+        cluster_name: segment.cluster_name.clone(),
+        shard_name: segment.shard_name.clone(),
+        segment_seq: segment.segment_seq,
+        replica_leader,
+    };
+    let data = StorageData::new(
+        StorageDataType::JournalUpdateSegmentLeader,
+        UpdateSegmentLeaderRequest::encode_to_vec(&req),
+    );
+    return placement_center_storage
+        .apply_propose_message(data, "update_segment_leader".to_string())
+        .await;
+}
+
+// Returns the end offset and the end log bytes of the active segment when it holds
+// records and is too large or too old. Both come from the last report of its leader, a
+// segment whose leader did not report is left alone.
+pub fn roll_point(
+    segment: &SegmentInfo,
+    progress: &HashMap<u64, ShardProgress>,
+    now: u128,
+    config: &JournalController,
+) -> Option<(u64, u64)> {
+    let leader = match segment.leader() {
+        Some(replica) => progress.get(&replica.node_id)?,
+        None => return None,
+    };
+    if leader.next_offset <= segment.start_offset {
+        return None;
+    }
+    let bytes = leader.log_bytes.saturating_sub(segment.start_bytes);
+    let age = now.saturating_sub(segment.start_time);
+    if bytes >= config.segment_max_bytes || age >= config.segment_max_time_ms as u128 {
+        return Some((leader.next_offset, leader.log_bytes));
+    }
+    return None;
+}
+
+// The replica that leads the next segment after the roll. It has to hold every record of
+// the rolled segment, the preferred replica is taken when it does.
+pub fn select_roll_leader(
+    next: &SegmentInfo,
+    progress: &HashMap<u64, ShardProgress>,
+    end_offset: u64,
+) -> Option<u32> {
+    return next
+        .replicas
+        .iter()
+        .position(|replica| match progress.get(&replica.node_id) {
+            Some(node) => node.next_offset >= end_offset,
+            None => false,
+        })
+        .map(|index| index as u32);
+}
+
+// A rolled segment is sealed once the high watermark of the shard passed its end, its
+// records are then on every in-sync replica.
+pub fn can_seal_up(segment: &SegmentInfo, progress: &HashMap<u64, ShardProgress>) -> bool {
+    return progress
+        .values()
+        .any(|node| node.high_watermark >= segment.end_offset);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{can_seal_up, roll_point, select_roll_leader};
+    use crate::{
+        cache::journal::ShardProgress,
+        storage::journal::segment::{Replica, SegmentInfo, SegmentStatus},
+    };
+    use common_base::config::placement_center::JournalController;
+    use std::collections::HashMap;
+
+    fn build_segment(nodes: &[u64], replica_leader: u32) -> SegmentInfo {
+        return SegmentInfo {
+            cluster_name: "c1".to_string(),
+            shard_name: "s1".to_string(),
+            segment_seq: 1,
+            replicas: nodes
+                .iter()
+                .enumerate()
+                .map(|(i, node_id)| Replica {
+                    replica_seq: i as u64,
+                    node_id: *node_id,
+                    fold: "/data".to_string(),
+                })
+                .collect(),
+            replica_leader,
+            status: SegmentStatus::Write,
+            start_offset: 100,
+            start_bytes: 1000,
+            start_time: 10_000,
+            ..Default::default()
+        };
+    }
+
+    fn progress(next_offset: u64, high_watermark: u64, log_bytes: u64) -> ShardProgress {
+        return ShardProgress {
+            next_offset,
+            high_watermark,
+            log_bytes,
+            report_time: 0,
+        };
+    }
+
+    #[test]
+    fn roll_by_size_or_time() {
+        let config = JournalController {
+            segment_max_bytes: 500,
+            segment_max_time_ms: 1000,
+            segment_check_interval_ms: 1000,
+            preferred_election_interval_ms: 1000,
+        };
+        let segment = build_segment(&[1, 2, 3], 1);
+        let mut nodes = HashMap::new();
+
+        // The leader did not report yet.
+        assert_eq!(roll_point(&segment, &nodes, 10_100, &config), None);
+
+        nodes.insert(2, progress(150, 140, 1400));
+        nodes.insert(1, progress(150, 140, 9000));
+        assert_eq!(roll_point(&segment, &nodes, 10_100, &config), None);
+        assert_eq!(
+            roll_point(&segment, &nodes, 11_000, &config),
+            Some((150, 1400))
+        );
+
+        nodes.insert(2, progress(180, 170, 1500));
+        assert_eq!(
+            roll_point(&segment, &nodes, 10_100, &config),
+            Some((180, 1500))
+        );
+
+        // An empty segment is not rolled, however old it is.
+        nodes.insert(2, progress(100, 100, 1000));
+        assert_eq!(roll_point(&segment, &nodes, 99_000, &config), None);
+    }
+
+    #[test]
+    fn roll_leader_holds_the_rolled_records() {
+        let next = build_segment(&[4, 5, 6], 0);
+        let mut nodes = HashMap::new();
+        assert_eq!(select_roll_leader(&next, &nodes, 150), None);
+
+        nodes.insert(6, progress(150, 150, 0));
+        nodes.insert(5, progress(120, 120, 0));
+        assert_eq!(select_roll_leader(&next, &nodes, 150), Some(2));
+
+        nodes.insert(4, progress(160, 150, 0));
+        assert_eq!(select_roll_leader(&next, &nodes, 150), Some(0));
+    }
+
+    #[test]
+    fn seal_up_after_high_watermark_passes_the_end() {
+        let mut segment = build_segment(&[1, 2], 0);
+        segment.status = SegmentStatus::PrepareSealUp;
+        segment.end_offset = 150;
+
+        let mut nodes = HashMap::new();
+        assert!(!can_seal_up(&segment, &nodes));
+        nodes.insert(1, progress(200, 149, 0));
+        assert!(!can_seal_up(&segment, &nodes));
+        nodes.insert(2, progress(200, 150, 0));
+        assert!(can_seal_up(&segment, &nodes));
+    }
+}
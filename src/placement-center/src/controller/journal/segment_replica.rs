@@ -13,14 +13,47 @@
 
 
 use crate::{
-    cache::{placement::PlacementCacheManager, journal::JournalCacheManager}, storage::journal::segment::Replica,
+    cache::{journal::JournalCacheManager, placement::PlacementCacheManager},
+    storage::journal::segment::Replica,
 };
-use std::sync::Arc;
+use common_base::error::{common::CommonError, placement_center::PlacementCenterError};
+use metadata_struct::journal::node_extend::JournalNodeExtend;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+// Nodes whose disks are fuller than this get no new replicas.
+const MAX_DISK_RATE: f32 = 0.9;
+
+#[derive(Debug, Clone, Default)]
+pub struct FoldLoad {
+    pub fold: String,
+    pub segment_num: u64,
+    pub used_bytes: u64,
+}
+
+// A journal node that can hold a replica of a new segment.
+#[derive(Debug, Clone, Default)]
+pub struct NodeCandidate {
+    pub node_id: u64,
+    pub rack: String,
+    pub folds: Vec<FoldLoad>,
+    pub leader_num: u64,
+}
+
+impl NodeCandidate {
+    pub fn segment_num(&self) -> u64 {
+        return self.folds.iter().map(|fold| fold.segment_num).sum();
+    }
+
+    pub fn used_bytes(&self) -> u64 {
+        return self.folds.iter().map(|fold| fold.used_bytes).sum();
+    }
+}
 
 pub struct SegmentReplicaAlgorithm {
-    #[allow(dead_code)]
     cluster_cache: Arc<PlacementCacheManager>,
-    #[allow(dead_code)]
     engine_cache: Arc<JournalCacheManager>,
 }
 
@@ -35,14 +68,291 @@ impl SegmentReplicaAlgorithm {
         };
     }
 
-    pub fn calc_replica_distribution(&self, replica_seq: u64) -> Vec<Replica> {
-        let node_id = 1;
-        let fold = "/data/robustmq".to_string();
-        let rep = Replica {
-            replica_seq,
-            node_id,
-            fold,
+    // Places the replicas of a new segment of the shard on the live journal nodes of the
+    // cluster. The first replica is the preferred leader.
+    pub fn calc_replica_distribution(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+    ) -> Result<Vec<Replica>, CommonError> {
+        let replica_num = match self
+            .engine_cache
+            .get_shard(cluster_name.clone(), shard_name.clone())
+        {
+            Some(shard) => shard.replica,
+            None => {
+                return Err(PlacementCenterError::ShardDoesNotExist(shard_name.clone()).into());
+            }
         };
-        return vec![rep];
+        let candidates = self.build_candidates(cluster_name);
+        return select_replicas(shard_name, &candidates, replica_num);
+    }
+
+    fn build_candidates(&self, cluster_name: &String) -> Vec<NodeCandidate> {
+        // Segments are counted from the placement state as well, the reports of the nodes
+        // lag behind the segments placed since.
+        let mut placed: HashMap<(u64, String), u64> = HashMap::new();
+        let mut leaders: HashMap<u64, u64> = HashMap::new();
+        for segment in self.engine_cache.list_segment(cluster_name) {
+            for replica in segment.replicas.iter() {
+                *placed
+                    .entry((replica.node_id, replica.fold.clone()))
+                    .or_default() += 1;
+            }
+            if let Some(replica) = segment.replicas.get(segment.replica_leader as usize) {
+                *leaders.entry(replica.node_id).or_default() += 1;
+            }
+        }
+
+        let mut candidates = Vec::new();
+        for node in self.cluster_cache.get_cluster_nodes(cluster_name) {
+            let extend =
+                serde_json::from_str::<JournalNodeExtend>(&node.extend).unwrap_or_default();
+            let monitor = self
+                .cluster_cache
+                .get_node_monitor(cluster_name, node.node_id)
+                .unwrap_or_default();
+            if monitor.disk_rate >= MAX_DISK_RATE {
+                continue;
+            }
+
+            let mut fold_names = extend.data_fold.clone();
+            for fold in monitor.folds.iter() {
+                if !fold_names.contains(&fold.fold) {
+                    fold_names.push(fold.fold.clone());
+                }
+            }
+            // A node that did not tell its folders picks one itself.
+            if fold_names.is_empty() {
+                fold_names.push("".to_string());
+            }
+
+            let folds = fold_names
+                .into_iter()
+                .map(|fold| {
+                    let placed_num = placed
+                        .get(&(node.node_id, fold.clone()))
+                        .copied()
+                        .unwrap_or_default();
+                    let mut load = FoldLoad {
+                        fold,
+                        segment_num: placed_num,
+                        used_bytes: 0,
+                    };
+                    if let Some(report) = monitor.folds.iter().find(|raw| raw.fold == load.fold) {
+                        load.segment_num = load.segment_num.max(report.segment_num);
+                        load.used_bytes = report.used_bytes;
+                    }
+                    return load;
+                })
+                .collect();
+            candidates.push(NodeCandidate {
+                node_id: node.node_id,
+                rack: extend.rack,
+                folds,
+                leader_num: leaders.get(&node.node_id).copied().unwrap_or_default(),
+            });
+        }
+        return candidates;
+    }
+}
+
+// Picks replica_num distinct nodes. Replicas are spread over as many racks as possible,
+// and within that the nodes holding the fewest segments and bytes are preferred. On every
+// node the least loaded folder is used. The chosen node leading the fewest segments
+// becomes the first replica, the preferred leader.
+pub fn select_replicas(
+    shard_name: &String,
+    candidates: &[NodeCandidate],
+    replica_num: u32,
+) -> Result<Vec<Replica>, CommonError> {
+    let replica_num = replica_num.max(1);
+    if candidates.len() < replica_num as usize {
+        return Err(PlacementCenterError::NotEnoughJournalNodes(
+            shard_name.clone(),
+            replica_num,
+            candidates.len(),
+        )
+        .into());
+    }
+
+    let mut remaining: Vec<&NodeCandidate> = candidates.iter().collect();
+    remaining.sort_by_key(|node| (node.segment_num(), node.used_bytes(), node.node_id));
+    let mut chosen: Vec<&NodeCandidate> = Vec::new();
+    let mut used_racks = HashSet::new();
+    while chosen.len() < replica_num as usize {
+        // Nodes without a rack label can go anywhere.
+        let index = remaining
+            .iter()
+            .position(|node| node.rack.is_empty() || !used_racks.contains(&node.rack))
+            .unwrap_or(0);
+        let node = remaining.remove(index);
+        if !node.rack.is_empty() {
+            used_racks.insert(node.rack.clone());
+        }
+        chosen.push(node);
+    }
+    chosen.sort_by_key(|node| (node.leader_num, node.segment_num(), node.node_id));
+
+    let replicas = chosen
+        .iter()
+        .enumerate()
+        .map(|(i, node)| {
+            let fold = match node
+                .folds
+                .iter()
+                .min_by_key(|fold| (fold.segment_num, fold.used_bytes, fold.fold.clone()))
+            {
+                Some(fold) => fold.fold.clone(),
+                None => "".to_string(),
+            };
+            return Replica {
+                replica_seq: i as u64,
+                node_id: node.node_id,
+                fold,
+            };
+        })
+        .collect();
+    return Ok(replicas);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{select_replicas, FoldLoad, NodeCandidate};
+    use std::collections::HashSet;
+
+    // A xorshift generator, so that every run checks the same generated clusters.
+    struct TestRng(u64);
+
+    impl TestRng {
+        fn next(&mut self, max: u64) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            return self.0 % max;
+        }
+    }
+
+    fn build_cluster(rng: &mut TestRng, node_num: u64, rack_num: u64) -> Vec<NodeCandidate> {
+        return (1..=node_num)
+            .map(|node_id| NodeCandidate {
+                node_id,
+                rack: if rack_num == 0 {
+                    "".to_string()
+                } else {
+                    format!("rack-{}", rng.next(rack_num))
+                },
+                folds: (0..rng.next(3) + 1)
+                    .map(|i| FoldLoad {
+                        fold: format!("/data/{}", i),
+                        ..Default::default()
+                    })
+                    .collect(),
+                leader_num: 0,
+            })
+            .collect();
+    }
+
+    #[test]
+    fn replicas_are_spread_over_nodes_and_racks() {
+        let mut rng = TestRng(0x9e3779b97f4a7c15);
+        let shard_name = "s1".to_string();
+        for _ in 0..500 {
+            let node_num = rng.next(8) + 1;
+            let rack_num = rng.next(4);
+            let mut cluster = build_cluster(&mut rng, node_num, rack_num);
+            for node in cluster.iter_mut() {
+                for fold in node.folds.iter_mut() {
+                    fold.segment_num = rng.next(20);
+                    fold.used_bytes = rng.next(1 << 30);
+                }
+            }
+            let replica_num = (rng.next(node_num) + 1) as u32;
+            let replicas = select_replicas(&shard_name, &cluster, replica_num).unwrap();
+
+            assert_eq!(replicas.len(), replica_num as usize);
+            let node_ids: HashSet<u64> = replicas.iter().map(|rep| rep.node_id).collect();
+            assert_eq!(node_ids.len(), replicas.len());
+            for (i, replica) in replicas.iter().enumerate() {
+                assert_eq!(replica.replica_seq, i as u64);
+                let node = cluster
+                    .iter()
+                    .find(|n| n.node_id == replica.node_id)
+                    .unwrap();
+                assert!(node.folds.iter().any(|fold| fold.fold == replica.fold));
+            }
+
+            let racks: HashSet<&String> = cluster.iter().map(|n| &n.rack).collect();
+            let replica_racks: HashSet<&String> = replicas
+                .iter()
+                .map(|rep| {
+                    &cluster
+                        .iter()
+                        .find(|n| n.node_id == rep.node_id)
+                        .unwrap()
+                        .rack
+                })
+                .collect();
+            assert_eq!(replica_racks.len(), racks.len().min(replica_num as usize));
+
+            // Without racks the least loaded nodes are chosen.
+            if rack_num == 0 {
+                let max_chosen = replicas
+                    .iter()
+                    .map(|rep| cluster.iter().find(|n| n.node_id == rep.node_id).unwrap())
+                    .map(|n| n.segment_num())
+                    .max()
+                    .unwrap();
+                for node in cluster.iter().filter(|n| !node_ids.contains(&n.node_id)) {
+                    assert!(node.segment_num() >= max_chosen);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn placement_keeps_segments_and_leaders_balanced() {
+        let mut rng = TestRng(0x2545f4914f6cdd1d);
+        let shard_name = "s1".to_string();
+        for _ in 0..200 {
+            let node_num = rng.next(8) + 1;
+            let replica_num = (rng.next(node_num) + 1) as u32;
+            let mut cluster = build_cluster(&mut rng, node_num, 0);
+            for _ in 0..rng.next(200) + 1 {
+                let replicas = select_replicas(&shard_name, &cluster, replica_num).unwrap();
+                for (i, replica) in replicas.iter().enumerate() {
+                    let node = cluster
+                        .iter_mut()
+                        .find(|n| n.node_id == replica.node_id)
+                        .unwrap();
+                    if i == 0 {
+                        node.leader_num += 1;
+                    }
+                    let fold = node
+                        .folds
+                        .iter_mut()
+                        .find(|fold| fold.fold == replica.fold)
+                        .unwrap();
+                    fold.segment_num += 1;
+                }
+            }
+
+            let segments: Vec<u64> = cluster.iter().map(|n| n.segment_num()).collect();
+            let leaders: Vec<u64> = cluster.iter().map(|n| n.leader_num).collect();
+            assert!(segments.iter().max().unwrap() - segments.iter().min().unwrap() <= 1);
+            assert!(leaders.iter().max().unwrap() - leaders.iter().min().unwrap() <= 1);
+            for node in cluster.iter() {
+                let folds: Vec<u64> = node.folds.iter().map(|f| f.segment_num).collect();
+                assert!(folds.iter().max().unwrap() - folds.iter().min().unwrap() <= 1);
+            }
+        }
+    }
+
+    #[test]
+    fn not_enough_nodes() {
+        let mut rng = TestRng(7);
+        let cluster = build_cluster(&mut rng, 2, 0);
+        assert!(select_replicas(&"s1".to_string(), &cluster, 3).is_err());
+        assert!(select_replicas(&"s1".to_string(), &[], 1).is_err());
     }
 }
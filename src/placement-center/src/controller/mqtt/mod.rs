@@ -25,6 +25,7 @@ use tokio::{select, sync::broadcast, time::sleep};
 
 pub mod call_broker;
 pub mod message_expire;
+pub mod node_failover;
 pub mod session_expire;
 
 pub struct MQTTController {
@@ -0,0 +1,328 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    cache::placement::PlacementCacheManager,
+    raft::{
+        apply::{RaftMachineApply, StorageData, StorageDataType},
+        metadata::RaftGroupMetadata,
+    },
+    storage::{mqtt::session::MQTTSessionStorage, rocksdb::RocksDBEngine},
+};
+use clients::{broker_mqtt::call::broker_mqtt_update_cache, poll::ClientPool};
+use common_base::{error::common::CommonError, tools::now_second};
+use log::{error, info, warn};
+use metadata_struct::{mqtt::session::MQTTSession, placement::broker_node::BrokerNode};
+use prost::Message as _;
+use protocol::{
+    broker_server::generate::mqtt::{
+        UpdateCacheActionType, UpdateCacheRequest, UpdateCacheResourceType,
+    },
+    placement_center::generate::{common::ClusterType, mqtt::UpdateSessionRequest},
+};
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use tokio::time::sleep;
+
+// Cleans up after an MQTT broker that was removed from the cluster because its
+// heartbeat timed out. Its shared subscription groups were already moved to the
+// remaining brokers when the node was removed.
+pub struct MQTTNodeFailover {
+    cluster_cache: Arc<PlacementCacheManager>,
+    placement_center_storage: Arc<RaftMachineApply>,
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+    client_poll: Arc<ClientPool>,
+}
+
+impl MQTTNodeFailover {
+    pub fn new(
+        cluster_cache: Arc<PlacementCacheManager>,
+        placement_center_storage: Arc<RaftMachineApply>,
+        rocksdb_engine_handler: Arc<RocksDBEngine>,
+        client_poll: Arc<ClientPool>,
+    ) -> Self {
+        return MQTTNodeFailover {
+            cluster_cache,
+            placement_center_storage,
+            rocksdb_engine_handler,
+            client_poll,
+        };
+    }
+
+    pub async fn start(&self, node: &BrokerNode) {
+        match self.release_sessions(node).await {
+            Ok(num) => {
+                info!(
+                    "Released {} sessions of the removed MQTT broker {} in cluster {}.",
+                    num, node.node_id, node.cluster_name
+                );
+            }
+            Err(e) => {
+                error!("{}", e);
+            }
+        }
+        self.notify_brokers(node).await;
+    }
+
+    // The sessions connected to the removed broker are marked as disconnected, so that
+    // they expire and send their will messages like after a normal disconnect. A release
+    // that fails is retried by the OrphanedSessionCheck.
+    async fn release_sessions(&self, node: &BrokerNode) -> Result<usize, CommonError> {
+        let sessions = self.orphaned_sessions(&node.cluster_name, node.node_id)?;
+        let mut released = 0;
+        for session in sessions.iter() {
+            match release_session(&self.placement_center_storage, &node.cluster_name, session).await
+            {
+                Ok(_) => released += 1,
+                Err(e) => {
+                    error!(
+                        "Failed to release session {} of the removed MQTT broker {}, {}",
+                        session.client_id, node.node_id, e
+                    );
+                }
+            }
+        }
+        return Ok(released);
+    }
+
+    fn orphaned_sessions(
+        &self,
+        cluster_name: &String,
+        broker_id: u64,
+    ) -> Result<Vec<MQTTSession>, CommonError> {
+        return list_sessions(&self.rocksdb_engine_handler, cluster_name, |session| {
+            session.broker_id == Some(broker_id)
+        });
+    }
+
+    // The remaining brokers resolve the leaders of their shared subscriptions again.
+    async fn notify_brokers(&self, node: &BrokerNode) {
+        let data = match serde_json::to_string(node) {
+            Ok(data) => data,
+            Err(e) => {
+                error!("{}", e);
+                return;
+            }
+        };
+        let request = UpdateCacheRequest {
+            cluster_name: node.cluster_name.clone(),
+            action_type: UpdateCacheActionType::Delete.into(),
+            resource_type: UpdateCacheResourceType::Node.into(),
+            data,
+        };
+        for addr in self.cluster_cache.get_cluster_node_addr(&node.cluster_name) {
+            match broker_mqtt_update_cache(self.client_poll.clone(), vec![addr], request.clone())
+                .await
+            {
+                Ok(_) => {}
+                Err(e) => {
+                    warn!("{}", e);
+                }
+            }
+        }
+    }
+}
+
+// Retries the release of the sessions that are still connected to a broker which is no
+// longer a node of its cluster.
+pub struct OrphanedSessionCheck {
+    check_time_ms: u64,
+    cluster_cache: Arc<PlacementCacheManager>,
+    placement_cache: Arc<RwLock<RaftGroupMetadata>>,
+    placement_center_storage: Arc<RaftMachineApply>,
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+}
+
+impl OrphanedSessionCheck {
+    pub fn new(
+        check_time_ms: u64,
+        cluster_cache: Arc<PlacementCacheManager>,
+        placement_cache: Arc<RwLock<RaftGroupMetadata>>,
+        placement_center_storage: Arc<RaftMachineApply>,
+        rocksdb_engine_handler: Arc<RocksDBEngine>,
+    ) -> Self {
+        return OrphanedSessionCheck {
+            check_time_ms,
+            cluster_cache,
+            placement_cache,
+            placement_center_storage,
+            rocksdb_engine_handler,
+        };
+    }
+
+    pub async fn start(&self) {
+        if self.placement_cache.read().unwrap().is_leader() {
+            for (cluster_name, cluster) in self.cluster_cache.cluster_list.clone() {
+                if cluster.cluster_type != ClusterType::MqttBrokerServer.as_str_name() {
+                    continue;
+                }
+                self.release_sessions(&cluster_name).await;
+            }
+        }
+        sleep(Duration::from_millis(self.check_time_ms)).await;
+    }
+
+    async fn release_sessions(&self, cluster_name: &String) {
+        let sessions = match self.orphaned_sessions(cluster_name) {
+            Ok(sessions) => sessions,
+            Err(e) => {
+                error!("{}", e);
+                return;
+            }
+        };
+        for session in sessions {
+            let broker_id = session.broker_id.unwrap_or_default();
+            match release_session(&self.placement_center_storage, cluster_name, &session).await {
+                Ok(_) => {
+                    info!(
+                        "Released session {} of the MQTT broker {} that is no longer in cluster {}.",
+                        session.client_id, broker_id, cluster_name
+                    );
+                }
+                Err(e) => {
+                    error!("{}", e);
+                }
+            }
+        }
+    }
+
+    fn orphaned_sessions(&self, cluster_name: &String) -> Result<Vec<MQTTSession>, CommonError> {
+        return list_sessions(
+            &self.rocksdb_engine_handler,
+            cluster_name,
+            |session| match session.broker_id {
+                Some(broker_id) => self
+                    .cluster_cache
+                    .get_node_addr(cluster_name, broker_id)
+                    .is_none(),
+                None => false,
+            },
+        );
+    }
+}
+
+fn list_sessions(
+    rocksdb_engine_handler: &Arc<RocksDBEngine>,
+    cluster_name: &String,
+    filter: impl Fn(&MQTTSession) -> bool,
+) -> Result<Vec<MQTTSession>, CommonError> {
+    let storage = MQTTSessionStorage::new(rocksdb_engine_handler.clone());
+    let mut results = Vec::new();
+    for raw in storage.list(cluster_name)? {
+        let session = serde_json::from_slice::<MQTTSession>(&raw.data)?;
+        if filter(&session) {
+            results.push(session);
+        }
+    }
+    return Ok(results);
+}
+
+// Marks the session as disconnected, unless the client connected again since the session
+// was read, to another broker or to the same broker after it restarted
+async fn release_session(
+    placement_center_storage: &Arc<RaftMachineApply>,
+    cluster_name: &String,
+    session: &MQTTSession,
+) -> Result<(), CommonError> {
+    let req = UpdateSessionRequest {
+        cluster_name: cluster_name.clone(),
+        client_id: session.client_id.clone(),
+        connection_id: 0,
+        broker_id: 0,
+        reconnect_time: 0,
+        distinct_time: now_second(),
+        expected_broker_id: session.broker_id.unwrap_or_default(),
+        expected_connection_id: session.connection_id.unwrap_or_default(),
+        expected_reconnect_time: session.reconnect_time.unwrap_or_default(),
+    };
+    let data = StorageData::new(
+        StorageDataType::MQTTUpdateSession,
+        UpdateSessionRequest::encode_to_vec(&req),
+    );
+    placement_center_storage
+        .apply_propose_message(data, "release_orphaned_session".to_string())
+        .await?;
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MQTTNodeFailover, OrphanedSessionCheck};
+    use crate::{
+        cache::placement::PlacementCacheManager,
+        raft::{
+            apply::{RaftMachineApply, RaftMessage},
+            metadata::RaftGroupMetadata,
+        },
+        storage::{mqtt::session::MQTTSessionStorage, rocksdb::RocksDBEngine},
+    };
+    use clients::poll::ClientPool;
+    use common_base::{config::placement_center::PlacementCenterConfig, tools::unique_id};
+    use metadata_struct::{mqtt::session::MQTTSession, placement::broker_node::BrokerNode};
+    use std::{
+        fs::remove_dir_all,
+        sync::{Arc, RwLock},
+    };
+
+    #[tokio::test]
+    async fn orphaned_sessions_test() {
+        let mut config = PlacementCenterConfig::default();
+        config.data_path = format!("/tmp/tmp_test/{}", unique_id());
+        config.rocksdb.max_open_files = Some(100);
+        let rocksdb_engine_handler = Arc::new(RocksDBEngine::new(&config));
+        let cluster_cache = Arc::new(PlacementCacheManager::new(rocksdb_engine_handler.clone()));
+        let (raft_message_send, _) = tokio::sync::mpsc::channel::<RaftMessage>(1);
+        let placement_center_storage = Arc::new(RaftMachineApply::new(raft_message_send));
+        let failover = MQTTNodeFailover::new(
+            cluster_cache.clone(),
+            placement_center_storage.clone(),
+            rocksdb_engine_handler.clone(),
+            Arc::new(ClientPool::new(1)),
+        );
+        let session_check = OrphanedSessionCheck::new(
+            1000,
+            cluster_cache.clone(),
+            Arc::new(RwLock::new(RaftGroupMetadata::default())),
+            placement_center_storage,
+            rocksdb_engine_handler.clone(),
+        );
+
+        let cluster_name = "test_cluster".to_string();
+        let storage = MQTTSessionStorage::new(rocksdb_engine_handler);
+        for (client_id, broker_id) in [("c1", Some(1)), ("c2", Some(2)), ("c3", None)] {
+            let mut session = MQTTSession::default();
+            session.client_id = client_id.to_string();
+            session.broker_id = broker_id;
+            storage
+                .save(&cluster_name, &client_id.to_string(), session)
+                .unwrap();
+        }
+
+        let sessions = failover.orphaned_sessions(&cluster_name, 1).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].client_id, "c1");
+
+        // Only broker 2 is still a node of the cluster
+        let mut node = BrokerNode::default();
+        node.cluster_name = cluster_name.clone();
+        node.node_id = 2;
+        cluster_cache.add_node(node);
+        let sessions = session_check.orphaned_sessions(&cluster_name).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].client_id, "c1");
+
+        remove_dir_all(config.data_path).unwrap();
+    }
+}
@@ -11,27 +11,48 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::heartbeat::BrokerHeartbeat;
-use crate::{cache::placement::PlacementCacheManager, raft::apply::RaftMachineApply};
+use super::{
+    heartbeat::BrokerHeartbeat, lease::KvLeaseExpire, lock::LockExpire,
+    stream_retention::StreamRetention,
+};
+use crate::{
+    cache::{kv::KvCacheManager, placement::PlacementCacheManager},
+    controller::mqtt::node_failover::OrphanedSessionCheck,
+    raft::{apply::RaftMachineApply, metadata::RaftGroupMetadata},
+    storage::rocksdb::RocksDBEngine,
+};
+use clients::poll::ClientPool;
 use common_base::config::placement_center::placement_center_conf;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use tokio::{select, sync::broadcast};
 
 pub struct ClusterController {
     cluster_cache: Arc<PlacementCacheManager>,
+    kv_cache: Arc<KvCacheManager>,
+    placement_cache: Arc<RwLock<RaftGroupMetadata>>,
     placement_center_storage: Arc<RaftMachineApply>,
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+    client_poll: Arc<ClientPool>,
     stop_send: broadcast::Sender<bool>,
 }
 
 impl ClusterController {
     pub fn new(
         cluster_cache: Arc<PlacementCacheManager>,
+        kv_cache: Arc<KvCacheManager>,
+        placement_cache: Arc<RwLock<RaftGroupMetadata>>,
         placement_center_storage: Arc<RaftMachineApply>,
+        rocksdb_engine_handler: Arc<RocksDBEngine>,
+        client_poll: Arc<ClientPool>,
         stop_send: broadcast::Sender<bool>,
     ) -> ClusterController {
         let controller = ClusterController {
             cluster_cache,
+            kv_cache,
+            placement_cache,
             placement_center_storage,
+            rocksdb_engine_handler,
+            client_poll,
             stop_send,
         };
         return controller;
@@ -45,7 +66,10 @@ impl ClusterController {
             config.heartbeat_timeout_ms.into(),
             config.heartbeat_check_time_ms,
             self.cluster_cache.clone(),
+            self.placement_cache.clone(),
             self.placement_center_storage.clone(),
+            self.rocksdb_engine_handler.clone(),
+            self.client_poll.clone(),
         );
         loop {
             select! {
@@ -66,4 +90,123 @@ impl ClusterController {
             }
         }
     }
+
+    // Start the expiry check of the kv leases
+    pub async fn start_kv_lease_check(&self) {
+        let mut stop_recv = self.stop_send.subscribe();
+        let config = placement_center_conf();
+        let lease_expire = KvLeaseExpire::new(
+            config.heartbeat_check_time_ms,
+            self.kv_cache.clone(),
+            self.placement_cache.clone(),
+            self.placement_center_storage.clone(),
+            self.rocksdb_engine_handler.clone(),
+        );
+        loop {
+            select! {
+                val = stop_recv.recv() =>{
+                    match val{
+                        Ok(flag) => {
+                            if flag {
+                                break;
+                            }
+                        }
+                        Err(_) => {}
+                    }
+                }
+                _ = lease_expire.start()=>{
+
+                }
+            }
+        }
+    }
+
+    // Start the expiry check of the locks and elections held by the nodes
+    pub async fn start_lock_check(&self) {
+        let mut stop_recv = self.stop_send.subscribe();
+        let config = placement_center_conf();
+        let lock_expire = LockExpire::new(
+            config.heartbeat_check_time_ms,
+            self.cluster_cache.clone(),
+            self.placement_cache.clone(),
+            self.placement_center_storage.clone(),
+            self.rocksdb_engine_handler.clone(),
+        );
+        loop {
+            select! {
+                val = stop_recv.recv() =>{
+                    match val{
+                        Ok(flag) => {
+                            if flag {
+                                break;
+                            }
+                        }
+                        Err(_) => {}
+                    }
+                }
+                _ = lock_expire.start()=>{
+
+                }
+            }
+        }
+    }
+
+    // Start the release of the MQTT sessions left on brokers that were removed
+    pub async fn start_orphaned_session_check(&self) {
+        let mut stop_recv = self.stop_send.subscribe();
+        let config = placement_center_conf();
+        let session_check = OrphanedSessionCheck::new(
+            config.heartbeat_timeout_ms,
+            self.cluster_cache.clone(),
+            self.placement_cache.clone(),
+            self.placement_center_storage.clone(),
+            self.rocksdb_engine_handler.clone(),
+        );
+        loop {
+            select! {
+                val = stop_recv.recv() =>{
+                    match val{
+                        Ok(flag) => {
+                            if flag {
+                                break;
+                            }
+                        }
+                        Err(_) => {}
+                    }
+                }
+                _ = session_check.start()=>{
+
+                }
+            }
+        }
+    }
+
+    // Start the removal of the stream records beyond the retention config of their shard
+    pub async fn start_stream_retention_check(&self) {
+        let mut stop_recv = self.stop_send.subscribe();
+        let config = placement_center_conf();
+        let stream_retention = StreamRetention::new(
+            config.stream_storage.retention_check_interval_ms,
+            self.placement_cache.clone(),
+            self.placement_center_storage.clone(),
+            self.rocksdb_engine_handler.clone(),
+        );
+        loop {
+            select! {
+                val = stop_recv.recv() =>{
+                    match val{
+                        Ok(flag) => {
+                            if flag {
+                                break;
+                            }
+                        }
+                        Err(_) => {}
+                    }
+                }
+                _ = stream_retention.start()=>{
+
+                }
+            }
+        }
+    }
 }
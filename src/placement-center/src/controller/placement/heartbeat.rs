@@ -13,19 +13,33 @@
 
 use crate::{
     cache::placement::PlacementCacheManager,
-    raft::apply::{RaftMachineApply, StorageData, StorageDataType},
+    controller::mqtt::node_failover::MQTTNodeFailover,
+    raft::{
+        apply::{RaftMachineApply, StorageData, StorageDataType},
+        metadata::RaftGroupMetadata,
+    },
+    storage::rocksdb::RocksDBEngine,
 };
+use clients::poll::ClientPool;
 use common_base::tools::now_second;
 use log::{error, info};
+use metadata_struct::placement::broker_node::BrokerNode;
 use prost::Message;
 use protocol::placement_center::generate::{common::ClusterType, placement::UnRegisterNodeRequest};
-use std::{sync::Arc, thread::sleep, time::Duration};
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use tokio::time::sleep;
 
 pub struct BrokerHeartbeat {
     timeout_ms: u64,
     check_time_ms: u64,
     cluster_cache: Arc<PlacementCacheManager>,
+    placement_cache: Arc<RwLock<RaftGroupMetadata>>,
     placement_center_storage: Arc<RaftMachineApply>,
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+    client_poll: Arc<ClientPool>,
 }
 
 impl BrokerHeartbeat {
@@ -33,17 +47,37 @@ impl BrokerHeartbeat {
         timeout_ms: u64,
         check_time_ms: u64,
         cluster_cache: Arc<PlacementCacheManager>,
+        placement_cache: Arc<RwLock<RaftGroupMetadata>>,
         placement_center_storage: Arc<RaftMachineApply>,
+        rocksdb_engine_handler: Arc<RocksDBEngine>,
+        client_poll: Arc<ClientPool>,
     ) -> Self {
         return BrokerHeartbeat {
             timeout_ms,
             check_time_ms,
             cluster_cache,
+            placement_cache,
             placement_center_storage,
+            rocksdb_engine_handler,
+            client_poll,
         };
     }
 
     pub async fn start(&mut self) {
+        // Heartbeats are only sent to the leader. The other nodes keep the heartbeat time
+        // of every node fresh, so that a new leader gives the nodes a full timeout to
+        // reach it.
+        if !self.placement_cache.read().unwrap().is_leader() {
+            for (cluster_name, node_list) in self.cluster_cache.node_list.clone() {
+                for (node_id, _) in node_list {
+                    self.cluster_cache
+                        .heart_time(&cluster_name, node_id, now_second());
+                }
+            }
+            sleep(Duration::from_millis(self.check_time_ms)).await;
+            return;
+        }
+
         for (cluster_name, node_list) in self.cluster_cache.node_list.clone() {
             for (node_id, node) in node_list.clone() {
                 if !self
@@ -57,37 +91,22 @@ impl BrokerHeartbeat {
                     self.cluster_cache.node_heartbeat.get(&cluster_name)
                 {
                     if let Some(time) = cluster_heartbeat.get(&node_id) {
-                        if now_second() - time.clone() >= self.timeout_ms {
+                        if (now_second() - time.clone()) * 1000 >= self.timeout_ms {
                             let cluster_name = node.cluster_name.clone();
-                            if let Some(_) = self.cluster_cache.cluster_list.get(&cluster_name) {
-                                let mut req = UnRegisterNodeRequest::default();
-                                req.node_id = node.node_id;
-                                req.cluster_name = node.cluster_name.clone();
-                                req.cluster_type = ClusterType::JournalServer.into();
-                                let pcs = self.placement_center_storage.clone();
-                                let data = StorageData::new(
-                                    StorageDataType::ClusterUngisterNode,
-                                    UnRegisterNodeRequest::encode_to_vec(&req),
-                                );
-                                tokio::spawn(async move {
-                                    match pcs
-                                        .apply_propose_message(
-                                            data,
-                                            "heartbeat_remove_node".to_string(),
-                                        )
-                                        .await
-                                    {
-                                        Ok(_) => {
-                                            info!(
-                                                   "The heartbeat of the Storage Engine node times out and is deleted from the cluster. Node ID: {}, node IP: {}.",
-                                                    node.node_id,
-                                                    node.node_ip);
-                                        }
-                                        Err(e) => {
-                                            error!("{}", e);
-                                        }
+                            if let Some(cluster) =
+                                self.cluster_cache.cluster_list.get(&cluster_name)
+                            {
+                                match ClusterType::from_str_name(&cluster.cluster_type) {
+                                    Some(cluster_type) => {
+                                        self.remove_node(cluster_type, node);
+                                    }
+                                    None => {
+                                        error!(
+                                            "Cluster {} has an unknown cluster type {}.",
+                                            cluster_name, cluster.cluster_type
+                                        );
                                     }
-                                });
+                                }
                             }
                         }
                     } else {
@@ -100,6 +119,50 @@ impl BrokerHeartbeat {
                 }
             }
         }
-        sleep(Duration::from_millis(self.check_time_ms));
+        sleep(Duration::from_millis(self.check_time_ms)).await;
+    }
+
+    // Removes the node from its cluster, then handles the failure the way its cluster
+    // type needs.
+    fn remove_node(&self, cluster_type: ClusterType, node: BrokerNode) {
+        let mut req = UnRegisterNodeRequest::default();
+        req.node_id = node.node_id;
+        req.cluster_name = node.cluster_name.clone();
+        req.cluster_type = cluster_type.into();
+        let data = StorageData::new(
+            StorageDataType::ClusterUngisterNode,
+            UnRegisterNodeRequest::encode_to_vec(&req),
+        );
+
+        let pcs = self.placement_center_storage.clone();
+        let mqtt_failover = MQTTNodeFailover::new(
+            self.cluster_cache.clone(),
+            self.placement_center_storage.clone(),
+            self.rocksdb_engine_handler.clone(),
+            self.client_poll.clone(),
+        );
+        tokio::spawn(async move {
+            match pcs
+                .apply_propose_message(data, "heartbeat_remove_node".to_string())
+                .await
+            {
+                Ok(_) => {
+                    info!(
+                        "The heartbeat of the {} node times out and is deleted from the cluster. Node ID: {}, node IP: {}.",
+                        cluster_type.as_str_name(),
+                        node.node_id,
+                        node.node_ip
+                    );
+                }
+                Err(e) => {
+                    error!("{}", e);
+                    return;
+                }
+            }
+
+            if cluster_type == ClusterType::MqttBrokerServer {
+                mqtt_failover.start(&node).await;
+            }
+        });
     }
 }
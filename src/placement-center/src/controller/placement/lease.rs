@@ -0,0 +1,118 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    cache::kv::KvCacheManager,
+    raft::{
+        apply::{RaftMachineApply, StorageData, StorageDataType},
+        metadata::RaftGroupMetadata,
+    },
+    storage::{keys::key_kv_lease, placement::kv::KvStorage, rocksdb::RocksDBEngine},
+};
+use common_base::tools::now_second;
+use log::{error, info};
+use prost::Message;
+use protocol::placement_center::generate::kv::DeleteRequest;
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use tokio::time::sleep;
+
+pub struct KvLeaseExpire {
+    check_time_ms: u64,
+    kv_cache: Arc<KvCacheManager>,
+    placement_cache: Arc<RwLock<RaftGroupMetadata>>,
+    placement_center_storage: Arc<RaftMachineApply>,
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+}
+
+impl KvLeaseExpire {
+    pub fn new(
+        check_time_ms: u64,
+        kv_cache: Arc<KvCacheManager>,
+        placement_cache: Arc<RwLock<RaftGroupMetadata>>,
+        placement_center_storage: Arc<RaftMachineApply>,
+        rocksdb_engine_handler: Arc<RocksDBEngine>,
+    ) -> Self {
+        return KvLeaseExpire {
+            check_time_ms,
+            kv_cache,
+            placement_cache,
+            placement_center_storage,
+            rocksdb_engine_handler,
+        };
+    }
+
+    pub async fn start(&self) {
+        let kv_storage = KvStorage::new(self.rocksdb_engine_handler.clone());
+        let leases = match kv_storage.lease_list() {
+            Ok(leases) => leases,
+            Err(e) => {
+                error!("{}", e);
+                sleep(Duration::from_millis(self.check_time_ms)).await;
+                return;
+            }
+        };
+
+        // Keepalives are only sent to the leader. Like the broker heartbeats, the other
+        // nodes keep the keepalive time of every lease fresh, so that a new leader gives
+        // the leases a full ttl.
+        if !self.placement_cache.read().unwrap().is_leader() {
+            for lease in leases {
+                self.kv_cache.keepalive(lease.lease_id, now_second());
+            }
+            sleep(Duration::from_millis(self.check_time_ms)).await;
+            return;
+        }
+
+        for lease in leases {
+            let time = match self.kv_cache.lease_keepalive.get(&lease.lease_id) {
+                Some(time) => *time,
+                None => {
+                    self.kv_cache.keepalive(lease.lease_id, now_second());
+                    continue;
+                }
+            };
+            if now_second() - time < lease.ttl {
+                continue;
+            }
+
+            // Deleting the lease record revokes it with its keys
+            let req = DeleteRequest {
+                key: key_kv_lease(lease.lease_id),
+            };
+            let data = StorageData::new(
+                StorageDataType::KvDelete,
+                DeleteRequest::encode_to_vec(&req),
+            );
+            match self
+                .placement_center_storage
+                .apply_propose_message(data, "lease_expire".to_string())
+                .await
+            {
+                Ok(_) => {
+                    info!(
+                        "Lease {} expired, it was revoked with its {} keys",
+                        lease.lease_id,
+                        lease.keys.len()
+                    );
+                }
+                Err(e) => {
+                    error!("{}", e);
+                }
+            }
+        }
+        sleep(Duration::from_millis(self.check_time_ms)).await;
+    }
+}
@@ -0,0 +1,142 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    cache::placement::PlacementCacheManager,
+    raft::{
+        apply::{RaftMachineApply, StorageData, StorageDataType},
+        metadata::RaftGroupMetadata,
+    },
+    storage::{
+        placement::lock::{LockInfo, LockKind, LockStorage},
+        rocksdb::RocksDBEngine,
+    },
+};
+use common_base::tools::now_second;
+use log::{error, info};
+use prost::Message;
+use protocol::placement_center::generate::placement::{ReleaseLockRequest, ResignRequest};
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use tokio::time::sleep;
+
+// Releases the locks and elections held by nodes that are gone, or whose heartbeat is
+// older than the ttl of the lock.
+pub struct LockExpire {
+    check_time_ms: u64,
+    cluster_cache: Arc<PlacementCacheManager>,
+    placement_cache: Arc<RwLock<RaftGroupMetadata>>,
+    placement_center_storage: Arc<RaftMachineApply>,
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+}
+
+impl LockExpire {
+    pub fn new(
+        check_time_ms: u64,
+        cluster_cache: Arc<PlacementCacheManager>,
+        placement_cache: Arc<RwLock<RaftGroupMetadata>>,
+        placement_center_storage: Arc<RaftMachineApply>,
+        rocksdb_engine_handler: Arc<RocksDBEngine>,
+    ) -> Self {
+        return LockExpire {
+            check_time_ms,
+            cluster_cache,
+            placement_cache,
+            placement_center_storage,
+            rocksdb_engine_handler,
+        };
+    }
+
+    pub async fn start(&self) {
+        // The heartbeats are only kept fresh on the leader
+        if !self.placement_cache.read().unwrap().is_leader() {
+            sleep(Duration::from_millis(self.check_time_ms)).await;
+            return;
+        }
+
+        let lock_storage = LockStorage::new(self.rocksdb_engine_handler.clone());
+        let locks = match lock_storage.list() {
+            Ok(locks) => locks,
+            Err(e) => {
+                error!("{}", e);
+                sleep(Duration::from_millis(self.check_time_ms)).await;
+                return;
+            }
+        };
+
+        for lock in locks {
+            if !lock.held || !self.is_expired(&lock) {
+                continue;
+            }
+
+            let data = match lock.kind {
+                LockKind::Lock => StorageData::new(
+                    StorageDataType::LockRelease,
+                    ReleaseLockRequest::encode_to_vec(&ReleaseLockRequest {
+                        lock_name: lock.name.clone(),
+                        holder: Some(lock.holder()),
+                        fencing_token: lock.fencing_token,
+                    }),
+                ),
+                LockKind::Election => StorageData::new(
+                    StorageDataType::ElectionResign,
+                    ResignRequest::encode_to_vec(&ResignRequest {
+                        election_name: lock.name.clone(),
+                        leader: Some(lock.holder()),
+                        term: lock.fencing_token,
+                    }),
+                ),
+            };
+            match self
+                .placement_center_storage
+                .apply_propose_message(data, "lock_expire".to_string())
+                .await
+            {
+                Ok(_) => {
+                    info!(
+                        "{:?} [{}] held by node {} of cluster [{}] expired and was released",
+                        lock.kind, lock.name, lock.node_id, lock.cluster_name
+                    );
+                }
+                Err(e) => {
+                    error!("{}", e);
+                }
+            }
+        }
+        sleep(Duration::from_millis(self.check_time_ms)).await;
+    }
+
+    fn is_expired(&self, lock: &LockInfo) -> bool {
+        if self
+            .cluster_cache
+            .get_node_addr(&lock.cluster_name, lock.node_id)
+            .is_none()
+        {
+            return true;
+        }
+
+        // The heartbeat check keeps a time for every registered node, a node without one
+        // was just registered.
+        let last_heartbeat = match self.cluster_cache.node_heartbeat.get(&lock.cluster_name) {
+            Some(heartbeat) => heartbeat.get(&lock.node_id).map(|time| *time),
+            None => None,
+        };
+        let last_time = match last_heartbeat {
+            Some(time) => time.max(lock.acquire_time),
+            None => return false,
+        };
+        return now_second().saturating_sub(last_time) >= lock.ttl;
+    }
+}
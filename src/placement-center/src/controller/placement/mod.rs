@@ -13,4 +13,7 @@
 
 
 pub mod heartbeat;
-pub mod controller;
\ No newline at end of file
+pub mod controller;
+pub mod lease;
+pub mod lock;
+pub mod stream_retention;
@@ -0,0 +1,125 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    raft::{
+        apply::{RaftMachineApply, StorageData, StorageDataType},
+        metadata::RaftGroupMetadata,
+    },
+    storage::{
+        placement::stream::{StreamShardInfo, StreamStorage},
+        rocksdb::RocksDBEngine,
+    },
+};
+use common_base::{error::common::CommonError, tools::now_second};
+use log::{error, info};
+use prost::Message;
+use protocol::placement_center::generate::kv::StreamTrimRequest;
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use tokio::time::sleep;
+
+// Applies the retention config of the stream shards. The leader decides which records
+// are removed and proposes it, so that every replica removes the same records.
+pub struct StreamRetention {
+    check_time_ms: u64,
+    placement_cache: Arc<RwLock<RaftGroupMetadata>>,
+    placement_center_storage: Arc<RaftMachineApply>,
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+}
+
+impl StreamRetention {
+    pub fn new(
+        check_time_ms: u64,
+        placement_cache: Arc<RwLock<RaftGroupMetadata>>,
+        placement_center_storage: Arc<RaftMachineApply>,
+        rocksdb_engine_handler: Arc<RocksDBEngine>,
+    ) -> Self {
+        return StreamRetention {
+            check_time_ms,
+            placement_cache,
+            placement_center_storage,
+            rocksdb_engine_handler,
+        };
+    }
+
+    pub async fn start(&self) {
+        if self.placement_cache.read().unwrap().is_leader() {
+            let stream_storage = StreamStorage::new(self.rocksdb_engine_handler.clone());
+            match stream_storage.list_shards() {
+                Ok(shards) => {
+                    for shard in shards {
+                        if !shard.is_retention_enabled() {
+                            continue;
+                        }
+                        if let Err(e) = self.retention_shard(&stream_storage, &shard).await {
+                            error!(
+                                "Retention of stream shard {} of cluster {} failed, error message: {}",
+                                shard.shard_name, shard.cluster_name, e
+                            );
+                        }
+                    }
+                }
+                Err(e) => {
+                    error!("{}", e);
+                }
+            }
+        }
+        sleep(Duration::from_millis(self.check_time_ms)).await;
+    }
+
+    async fn retention_shard(
+        &self,
+        stream_storage: &StreamStorage,
+        shard: &StreamShardInfo,
+    ) -> Result<(), CommonError> {
+        // Compacted shards only keep the latest record of each key, like the other
+        // storage adapters they are not limited by age or size.
+        let req = if shard.compact {
+            StreamTrimRequest {
+                cluster_name: shard.cluster_name.clone(),
+                shard_name: shard.shard_name.clone(),
+                end_offset: 0,
+                compact: true,
+            }
+        } else {
+            let end_offset = stream_storage.retention_end_offset(shard, now_second())?;
+            if end_offset <= shard.start_offset {
+                return Ok(());
+            }
+            StreamTrimRequest {
+                cluster_name: shard.cluster_name.clone(),
+                shard_name: shard.shard_name.clone(),
+                end_offset,
+                compact: false,
+            }
+        };
+
+        let data = StorageData::new(
+            StorageDataType::StreamTrim,
+            StreamTrimRequest::encode_to_vec(&req),
+        );
+        self.placement_center_storage
+            .apply_propose_message(data, "stream_trim".to_string())
+            .await?;
+        if !req.compact {
+            info!(
+                "Stream shard {} of cluster {} was trimmed to offset {} by its retention config.",
+                shard.shard_name, shard.cluster_name, req.end_offset
+            );
+        }
+        return Ok(());
+    }
+}
@@ -14,7 +14,8 @@
 use crate::{
     cache::placement::PlacementCacheManager,
     storage::{
-        keys::storage_key_mqtt_node_sub_group_leader, placement::kv::KvStorage,
+        engine::{engine_get_by_cluster, engine_save_by_cluster},
+        keys::storage_key_mqtt_node_sub_group_leader,
         rocksdb::RocksDBEngine,
     },
 };
@@ -106,10 +107,13 @@ impl ShareSubLeader {
                 group_list.retain(|x| *x != group_name.to_string());
                 node_sub_info.insert(broker_id, group_list);
 
-                let kv_storage = KvStorage::new(self.rocksdb_engine_handler.clone());
                 let key = storage_key_mqtt_node_sub_group_leader(cluster_name);
                 match serde_json::to_string(&node_sub_info) {
-                    Ok(value) => match kv_storage.set(key, value) {
+                    Ok(value) => match engine_save_by_cluster(
+                        self.rocksdb_engine_handler.clone(),
+                        key,
+                        value,
+                    ) {
                         Ok(()) => {}
                         Err(e) => {
                             return Err(e);
@@ -132,16 +136,17 @@ impl ShareSubLeader {
         };
         if node_sub_info.contains_key(&broker_id) {
             node_sub_info.remove(&broker_id);
-            let kv_storage = KvStorage::new(self.rocksdb_engine_handler.clone());
             let key = storage_key_mqtt_node_sub_group_leader(cluster_name);
 
             match serde_json::to_string(&node_sub_info) {
-                Ok(value) => match kv_storage.set(key, value) {
-                    Ok(()) => {}
-                    Err(e) => {
-                        return Err(e);
+                Ok(value) => {
+                    match engine_save_by_cluster(self.rocksdb_engine_handler.clone(), key, value) {
+                        Ok(()) => {}
+                        Err(e) => {
+                            return Err(e);
+                        }
                     }
-                },
+                }
                 Err(e) => {
                     return Err(CommonError::CommmonError(e.to_string()));
                 }
@@ -150,6 +155,62 @@ impl ShareSubLeader {
         return Ok(());
     }
 
+    // Moves the groups led by a removed broker onto the remaining brokers of the cluster,
+    // each group going to the broker leading the fewest groups. Returns the new leaders.
+    pub fn reassign_node_groups(
+        &self,
+        cluster_name: &String,
+        broker_id: u64,
+    ) -> Result<Vec<(String, u64)>, CommonError> {
+        let mut node_sub_info = self.read_node_sub_info(cluster_name)?;
+        let group_list = match node_sub_info.remove(&broker_id) {
+            Some(data) => data,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut broker_ids = Vec::new();
+        if let Some(cluster) = self.cluster_cache.node_list.get(cluster_name) {
+            for (id, _) in cluster.clone() {
+                if id != broker_id {
+                    broker_ids.push(id);
+                }
+            }
+        }
+        broker_ids.sort();
+
+        // Without a live broker the groups get a leader when they are next requested
+        let mut results = Vec::new();
+        if !broker_ids.is_empty() {
+            for group_name in group_list {
+                let mut target_broker_id = broker_ids[0];
+                let mut cur_len = usize::MAX;
+                for id in broker_ids.iter() {
+                    let size = node_sub_info.get(id).map(|list| list.len()).unwrap_or(0);
+                    if size < cur_len {
+                        target_broker_id = *id;
+                        cur_len = size;
+                    }
+                }
+                node_sub_info
+                    .entry(target_broker_id)
+                    .or_default()
+                    .push(group_name.clone());
+                results.push((group_name, target_broker_id));
+            }
+        }
+
+        let key = storage_key_mqtt_node_sub_group_leader(cluster_name);
+        match serde_json::to_string(&node_sub_info) {
+            Ok(value) => {
+                engine_save_by_cluster(self.rocksdb_engine_handler.clone(), key, value)?;
+            }
+            Err(e) => {
+                return Err(CommonError::CommmonError(e.to_string()));
+            }
+        }
+        return Ok(results);
+    }
+
     fn save_node_sub_info(
         &self,
         cluster_name: &String,
@@ -168,16 +229,17 @@ impl ShareSubLeader {
             node_sub_info.insert(broker_id, vec![group_name.clone()]);
         }
 
-        let kv_storage = KvStorage::new(self.rocksdb_engine_handler.clone());
         let key = storage_key_mqtt_node_sub_group_leader(cluster_name);
 
         match serde_json::to_string(&node_sub_info) {
-            Ok(value) => match kv_storage.set(key, value) {
-                Ok(()) => {}
-                Err(e) => {
-                    return Err(e);
+            Ok(value) => {
+                match engine_save_by_cluster(self.rocksdb_engine_handler.clone(), key, value) {
+                    Ok(()) => {}
+                    Err(e) => {
+                        return Err(e);
+                    }
                 }
-            },
+            }
             Err(e) => {
                 return Err(CommonError::CommmonError(e.to_string()));
             }
@@ -189,10 +251,11 @@ impl ShareSubLeader {
         &self,
         cluster_name: &String,
     ) -> Result<HashMap<u64, Vec<String>>, CommonError> {
-        let kv_storage = KvStorage::new(self.rocksdb_engine_handler.clone());
         let key = storage_key_mqtt_node_sub_group_leader(cluster_name);
-        match kv_storage.get(key) {
-            Ok(Some(data)) => match serde_json::from_str::<HashMap<u64, Vec<String>>>(&data) {
+        match engine_get_by_cluster(self.rocksdb_engine_handler.clone(), key) {
+            Ok(Some(data)) => match serde_json::from_slice::<String>(&data.data)
+                .and_then(|data| serde_json::from_str::<HashMap<u64, Vec<String>>>(&data))
+            {
                 Ok(data) => {
                     return Ok(data);
                 }
@@ -329,4 +392,42 @@ mod tests {
             .unwrap();
         assert_eq!(node, 1);
     }
+    #[test]
+    fn reassign_node_groups_test() {
+        let config = PlacementCenterConfig::default();
+        let cluster_name = unique_id();
+        let rocksdb_engine_handler = Arc::new(RocksDBEngine::new(&config));
+        let cluster_cache = Arc::new(PlacementCacheManager::new(rocksdb_engine_handler.clone()));
+        for node_id in 1..=3 {
+            cluster_cache.add_node(BrokerNode {
+                cluster_name: cluster_name.clone(),
+                cluster_type: ClusterType::MqttBrokerServer.as_str_name().to_string(),
+                node_id,
+                node_ip: "".to_string(),
+                node_inner_addr: "".to_string(),
+                extend: "".to_string(),
+                create_time: now_mills(),
+            });
+        }
+        let share_sub = ShareSubLeader::new(cluster_cache.clone(), rocksdb_engine_handler.clone());
+        for group_name in ["group1", "group2", "group3", "group4"] {
+            share_sub
+                .get_leader_node(&cluster_name, &group_name.to_string())
+                .unwrap();
+        }
+
+        cluster_cache.remove_node(&cluster_name, 1);
+        let result = share_sub.reassign_node_groups(&cluster_name, 1).unwrap();
+        assert_eq!(
+            result,
+            vec![("group1".to_string(), 2), ("group4".to_string(), 3)]
+        );
+        let node = share_sub
+            .get_leader_node(&cluster_name, &"group1".to_string())
+            .unwrap();
+        assert_eq!(node, 2);
+
+        let result = share_sub.reassign_node_groups(&cluster_name, 1).unwrap();
+        assert!(result.is_empty());
+    }
 }
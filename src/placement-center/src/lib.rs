@@ -16,6 +16,7 @@ use self::raft::peer::{PeerMessage, PeersManager};
 use crate::raft::metadata::RaftGroupMetadata;
 use crate::server::http::server::{start_http_server, HttpServerState};
 use cache::journal::JournalCacheManager;
+use cache::kv::KvCacheManager;
 use cache::mqtt::MqttCacheManager;
 use cache::placement::PlacementCacheManager;
 use clients::poll::ClientPool;
@@ -37,7 +38,9 @@ use server::grpc::service_kv::GrpcKvService;
 use server::grpc::service_mqtt::GrpcMqttService;
 use server::grpc::service_placement::GrpcPlacementService;
 use std::sync::{Arc, RwLock};
+use storage::placement::backup::BackupStore;
 use storage::placement::raft::RaftMachineStorage;
+use storage::placement::snapshot::SnapshotStore;
 use storage::rocksdb::RocksDBEngine;
 use tokio::runtime::Runtime;
 use tokio::signal;
@@ -51,6 +54,9 @@ mod raft;
 mod server;
 mod storage;
 
+// Used by the admin cli to restore and import metadata backups
+pub use storage::placement::backup::{read_backup_file, restore_metadata, BackupEntry};
+
 pub struct PlacementCenter {
     server_runtime: Runtime,
     daemon_runtime: Runtime,
@@ -59,10 +65,16 @@ pub struct PlacementCenter {
     // Cache metadata information for the Broker Server cluster
     engine_cache: Arc<JournalCacheManager>,
     mqtt_cache: Arc<MqttCacheManager>,
+    // Leases and watchers of the placement kv
+    kv_cache: Arc<KvCacheManager>,
     // Cache metadata information for the Placement Cluster cluster
     placement_cache: Arc<RwLock<RaftGroupMetadata>>,
     // Global implementation of Raft state machine data storage
     raft_machine_storage: Arc<RwLock<RaftMachineStorage>>,
+    // Files of the Raft snapshots taken locally or received from the leader
+    snapshot_store: Arc<SnapshotStore>,
+    // Metadata backups taken through the admin API
+    backup_store: Arc<BackupStore>,
     // Raft Global read and write pointer
     rocksdb_engine_handler: Arc<RocksDBEngine>,
     // Global GRPC client connection pool
@@ -85,10 +97,14 @@ impl PlacementCenter {
             rocksdb_engine_handler.clone(),
             cluster_cache.clone(),
         ));
+        let kv_cache = Arc::new(KvCacheManager::new());
         let placement_cache = Arc::new(RwLock::new(RaftGroupMetadata::new()));
 
+        let snapshot_store = Arc::new(SnapshotStore::new(&config.data_path));
+        let backup_store = Arc::new(BackupStore::new(&config.data_path));
         let raft_machine_storage = Arc::new(RwLock::new(RaftMachineStorage::new(
             rocksdb_engine_handler.clone(),
+            snapshot_store.clone(),
         )));
 
         return PlacementCenter {
@@ -97,8 +113,11 @@ impl PlacementCenter {
             cluster_cache,
             engine_cache,
             mqtt_cache,
+            kv_cache,
             placement_cache,
             raft_machine_storage,
+            snapshot_store,
+            backup_store,
             rocksdb_engine_handler,
             client_poll,
         };
@@ -111,11 +130,11 @@ impl PlacementCenter {
 
         self.start_controller(placement_center_storage.clone(), stop_send.clone());
 
-        self.start_peers_manager(peer_message_recv);
+        self.start_peers_manager(peer_message_recv, placement_center_storage.clone());
 
         self.start_raft_machine(peer_message_send, raft_message_recv, stop_send.subscribe());
 
-        self.start_http_server();
+        self.start_http_server(placement_center_storage.clone());
 
         self.start_grpc_server(placement_center_storage.clone());
 
@@ -123,12 +142,14 @@ impl PlacementCenter {
     }
 
     // Start HTTP Server
-    pub fn start_http_server(&self) {
+    pub fn start_http_server(&self, placement_center_storage: Arc<RaftMachineApply>) {
         let state: HttpServerState = HttpServerState::new(
             self.placement_cache.clone(),
             self.raft_machine_storage.clone(),
             self.cluster_cache.clone(),
             self.engine_cache.clone(),
+            placement_center_storage,
+            self.rocksdb_engine_handler.clone(),
         );
         self.server_runtime.spawn(async move {
             start_http_server(state).await;
@@ -145,10 +166,14 @@ impl PlacementCenter {
             self.cluster_cache.clone(),
             self.rocksdb_engine_handler.clone(),
             self.client_poll.clone(),
+            self.snapshot_store.clone(),
+            self.backup_store.clone(),
         );
 
         let kv_handler = GrpcKvService::new(
             placement_center_storage.clone(),
+            self.placement_cache.clone(),
+            self.kv_cache.clone(),
             self.rocksdb_engine_handler.clone(),
         );
 
@@ -156,11 +181,14 @@ impl PlacementCenter {
             placement_center_storage.clone(),
             self.placement_cache.clone(),
             self.client_poll.clone(),
+            self.engine_cache.clone(),
+            self.cluster_cache.clone(),
         );
 
         let mqtt_handler = GrpcMqttService::new(
             self.cluster_cache.clone(),
             placement_center_storage.clone(),
+            self.placement_cache.clone(),
             self.rocksdb_engine_handler.clone(),
         );
 
@@ -183,13 +211,33 @@ impl PlacementCenter {
         placement_center_storage: Arc<RaftMachineApply>,
         stop_send: broadcast::Sender<bool>,
     ) {
-        let ctrl = ClusterController::new(
+        let ctrl = Arc::new(ClusterController::new(
             self.cluster_cache.clone(),
+            self.kv_cache.clone(),
+            self.placement_cache.clone(),
             placement_center_storage.clone(),
+            self.rocksdb_engine_handler.clone(),
+            self.client_poll.clone(),
             stop_send.clone(),
-        );
+        ));
+        let heartbeat_ctrl = ctrl.clone();
+        self.daemon_runtime.spawn(async move {
+            heartbeat_ctrl.start_node_heartbeat_check().await;
+        });
+        let lock_ctrl = ctrl.clone();
+        self.daemon_runtime.spawn(async move {
+            lock_ctrl.start_lock_check().await;
+        });
+        let stream_ctrl = ctrl.clone();
         self.daemon_runtime.spawn(async move {
-            ctrl.start_node_heartbeat_check().await;
+            stream_ctrl.start_stream_retention_check().await;
+        });
+        let session_ctrl = ctrl.clone();
+        self.daemon_runtime.spawn(async move {
+            session_ctrl.start_orphaned_session_check().await;
+        });
+        self.daemon_runtime.spawn(async move {
+            ctrl.start_kv_lease_check().await;
         });
 
         let mqtt_controller = MQTTController::new(
@@ -203,7 +251,14 @@ impl PlacementCenter {
             mqtt_controller.start().await;
         });
 
-        let journal_controller = StorageEngineController::new();
+        let journal_controller = StorageEngineController::new(
+            self.rocksdb_engine_handler.clone(),
+            self.placement_cache.clone(),
+            placement_center_storage.clone(),
+            self.cluster_cache.clone(),
+            self.engine_cache.clone(),
+            stop_send.clone(),
+        );
         self.daemon_runtime.spawn(async move {
             journal_controller.start().await;
         });
@@ -220,6 +275,7 @@ impl PlacementCenter {
             self.rocksdb_engine_handler.clone(),
             self.cluster_cache.clone(),
             self.engine_cache.clone(),
+            self.kv_cache.clone(),
         )));
 
         let mut raft: RaftMachine = RaftMachine::new(
@@ -236,8 +292,17 @@ impl PlacementCenter {
     }
 
     // Start Raft Node Peer Manager
-    pub fn start_peers_manager(&self, peer_message_recv: Receiver<PeerMessage>) {
-        let mut peers_manager = PeersManager::new(peer_message_recv, self.client_poll.clone());
+    pub fn start_peers_manager(
+        &self,
+        peer_message_recv: Receiver<PeerMessage>,
+        placement_center_storage: Arc<RaftMachineApply>,
+    ) {
+        let mut peers_manager = PeersManager::new(
+            peer_message_recv,
+            self.client_poll.clone(),
+            self.snapshot_store.clone(),
+            placement_center_storage,
+        );
         self.daemon_runtime.spawn(async move {
             peers_manager.start().await;
         });
@@ -11,9 +11,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use bincode::serialize;
-use common_base::error::placement_center::PlacementCenterError;
+use bincode::{deserialize, serialize};
 use common_base::error::common::CommonError;
+use common_base::error::placement_center::PlacementCenterError;
+use metadata_struct::placement::broker_node::BrokerNode;
+use prost::Message as _;
+use protocol::placement_center::generate::placement::ListRaftMemberReply;
 use raft::eraftpb::ConfChange;
 use raft::eraftpb::Message as raftPreludeMessage;
 use serde::Deserialize;
@@ -27,8 +30,21 @@ use tokio::time::timeout;
 
 pub enum RaftResponseMesage {
     Success,
+    // The committed data was applied and the state machine returned a result
+    Reply(Vec<u8>),
+    // The state machine refused the request, the reason is returned to the caller
+    Reject(String),
     Fail,
 }
+
+// A change of the members of the placement center raft group.
+#[derive(Debug, Clone)]
+pub enum MembershipChange {
+    AddVoter(BrokerNode),
+    AddLearner(BrokerNode),
+    PromoteLearner(u64),
+    Remove(u64),
+}
 pub enum RaftMessage {
     ConfChange {
         change: ConfChange,
@@ -46,6 +62,35 @@ pub enum RaftMessage {
         chan: Sender<RaftResponseMesage>,
     },
 
+    // Validated against the current configuration, then proposed as a ConfChangeV2
+    Membership {
+        change: MembershipChange,
+        chan: Sender<RaftResponseMesage>,
+    },
+
+    ListMember {
+        chan: Sender<RaftResponseMesage>,
+    },
+
+    // Answered once the leader confirmed its leadership and applied the read index
+    ReadIndex {
+        chan: Sender<RaftResponseMesage>,
+    },
+
+    // The transfer of a snapshot to a peer finished
+    ReportSnapshot {
+        node_id: u64,
+        success: bool,
+        chan: Sender<RaftResponseMesage>,
+    },
+
+    // Takes a RocksDB checkpoint in the directory between two applied entries, answered with
+    // the index of the last applied one
+    Checkpoint {
+        path: String,
+        chan: Sender<RaftResponseMesage>,
+    },
+
     // The data sent by the client is received. Procedure
     Propose {
         data: Vec<u8>,
@@ -73,6 +118,12 @@ pub enum StorageDataType {
     KvSet,
     KvDelete,
 
+    // stream
+    StreamCreateShard,
+    StreamDeleteShard,
+    StreamWrite,
+    StreamCommitOffset,
+
     // mqtt
     MQTTCreateUser,
     MQTTDeleteUser,
@@ -85,6 +136,30 @@ pub enum StorageDataType {
     MQTTSaveLastWillMessage,
     MQTTCreateAcl,
     MQTTDeleteAcl,
+
+    // Journal segment lifecycle, appended so that the logged variants keep their index.
+    JournalRollSegment,
+    JournalUpdateSegmentStatus,
+    JournalUpdateSegmentLeader,
+
+    // distributed locks and elections
+    LockAcquire,
+    LockRelease,
+    ElectionCampaign,
+    ElectionResign,
+
+    // metadata imported from a backup
+    MetadataImport,
+
+    // mqtt blacklist
+    MQTTCreateBlacklist,
+    MQTTDeleteBlacklist,
+
+    // stream retention
+    StreamTrim,
+
+    // stream group admin
+    StreamResetGroupOffset,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -120,16 +195,77 @@ impl RaftMachineApply {
 
     pub async fn transfer_leader(&self, node_id: u64) -> Result<(), CommonError> {
         let (sx, rx) = oneshot::channel::<RaftResponseMesage>();
-        return Ok(self
+        self.apply_raft_status_machine_message(
+            RaftMessage::TransferLeader {
+                node_id: node_id,
+                chan: sx,
+            },
+            "transfer_leader".to_string(),
+            rx,
+        )
+        .await?;
+        return Ok(());
+    }
+
+    pub async fn change_membership(&self, change: MembershipChange) -> Result<(), CommonError> {
+        let (sx, rx) = oneshot::channel::<RaftResponseMesage>();
+        self.apply_raft_status_machine_message(
+            RaftMessage::Membership { change, chan: sx },
+            "change_membership".to_string(),
+            rx,
+        )
+        .await?;
+        return Ok(());
+    }
+
+    pub async fn read_index(&self) -> Result<(), CommonError> {
+        let (sx, rx) = oneshot::channel::<RaftResponseMesage>();
+        self.apply_raft_status_machine_message(
+            RaftMessage::ReadIndex { chan: sx },
+            "read_index".to_string(),
+            rx,
+        )
+        .await?;
+        return Ok(());
+    }
+
+    pub async fn list_member(&self) -> Result<ListRaftMemberReply, CommonError> {
+        let (sx, rx) = oneshot::channel::<RaftResponseMesage>();
+        let data = self
             .apply_raft_status_machine_message(
-                RaftMessage::TransferLeader {
-                    node_id: node_id,
-                    chan: sx,
-                },
-                "transfer_leader".to_string(),
+                RaftMessage::ListMember { chan: sx },
+                "list_member".to_string(),
                 rx,
             )
-            .await?);
+            .await?;
+        return Ok(ListRaftMemberReply::decode(data.as_ref())?);
+    }
+
+    pub async fn report_snapshot(&self, node_id: u64, success: bool) -> Result<(), CommonError> {
+        let (sx, rx) = oneshot::channel::<RaftResponseMesage>();
+        self.apply_raft_status_machine_message(
+            RaftMessage::ReportSnapshot {
+                node_id,
+                success,
+                chan: sx,
+            },
+            "report_snapshot".to_string(),
+            rx,
+        )
+        .await?;
+        return Ok(());
+    }
+
+    pub async fn checkpoint(&self, path: String) -> Result<u64, CommonError> {
+        let (sx, rx) = oneshot::channel::<RaftResponseMesage>();
+        let data = self
+            .apply_raft_status_machine_message(
+                RaftMessage::Checkpoint { path, chan: sx },
+                "checkpoint".to_string(),
+                rx,
+            )
+            .await?;
+        return deserialize(data.as_ref()).map_err(|e| CommonError::CommmonError(e.to_string()));
     }
 
     pub async fn apply_propose_message(
@@ -137,6 +273,16 @@ impl RaftMachineApply {
         data: StorageData,
         action: String,
     ) -> Result<(), CommonError> {
+        self.apply_propose_message_with_reply(data, action).await?;
+        return Ok(());
+    }
+
+    // Propose data to the Raft state machine and return the result produced when the data is applied.
+    pub async fn apply_propose_message_with_reply(
+        &self,
+        data: StorageData,
+        action: String,
+    ) -> Result<Vec<u8>, CommonError> {
         let (sx, rx) = oneshot::channel::<RaftResponseMesage>();
         return Ok(self
             .apply_raft_status_machine_message(
@@ -156,16 +302,16 @@ impl RaftMachineApply {
         action: String,
     ) -> Result<(), CommonError> {
         let (sx, rx) = oneshot::channel::<RaftResponseMesage>();
-        return Ok(self
-            .apply_raft_status_machine_message(
-                RaftMessage::Raft {
-                    message: message,
-                    chan: sx,
-                },
-                action,
-                rx,
-            )
-            .await?);
+        self.apply_raft_status_machine_message(
+            RaftMessage::Raft {
+                message: message,
+                chan: sx,
+            },
+            action,
+            rx,
+        )
+        .await?;
+        return Ok(());
     }
 
     pub async fn apply_conf_raft_message(
@@ -174,13 +320,13 @@ impl RaftMachineApply {
         action: String,
     ) -> Result<(), CommonError> {
         let (sx, rx) = oneshot::channel::<RaftResponseMesage>();
-        return Ok(self
-            .apply_raft_status_machine_message(
-                RaftMessage::ConfChange { change, chan: sx },
-                action,
-                rx,
-            )
-            .await?);
+        self.apply_raft_status_machine_message(
+            RaftMessage::ConfChange { change, chan: sx },
+            action,
+            rx,
+        )
+        .await?;
+        return Ok(());
     }
 
     async fn apply_raft_status_machine_message(
@@ -188,15 +334,22 @@ impl RaftMachineApply {
         message: RaftMessage,
         action: String,
         rx: Receiver<RaftResponseMesage>,
-    ) -> Result<(), PlacementCenterError> {
+    ) -> Result<Vec<u8>, PlacementCenterError> {
         let _ = self.raft_status_machine_sender.send(message).await;
-        if !self.wait_recv_chan_resp(rx).await {
-            return Err(PlacementCenterError::RaftLogCommitTimeout(action));
+        match self.wait_recv_chan_resp(rx).await {
+            Some(RaftResponseMesage::Reply(data)) => return Ok(data),
+            Some(RaftResponseMesage::Reject(reason)) => {
+                return Err(PlacementCenterError::RaftRequestRejected(action, reason))
+            }
+            Some(_) => return Ok(Vec::new()),
+            None => return Err(PlacementCenterError::RaftLogCommitTimeout(action)),
         }
-        return Ok(());
     }
 
-    async fn wait_recv_chan_resp(&self, rx: Receiver<RaftResponseMesage>) -> bool {
+    async fn wait_recv_chan_resp(
+        &self,
+        rx: Receiver<RaftResponseMesage>,
+    ) -> Option<RaftResponseMesage> {
         let res = timeout(Duration::from_secs(30), async {
             match rx.await {
                 Ok(val) => {
@@ -208,9 +361,9 @@ impl RaftMachineApply {
             }
         });
         match res.await {
-            Ok(_) => return true,
+            Ok(val) => return Some(val),
             Err(_) => {
-                return false;
+                return None;
             }
         }
     }
@@ -11,22 +11,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::apply::{RaftMessage, RaftResponseMesage};
+use super::apply::{MembershipChange, RaftMessage, RaftResponseMesage};
 use super::route::DataRoute;
 use super::storage::RaftRocksDBStorage;
 use crate::raft::metadata::RaftGroupMetadata;
-use crate::raft::peer::PeerMessage;
+use crate::raft::peer::{PeerMessage, PeerSnapshot};
+use crate::storage::placement::member::RaftMemberStorage;
 use crate::storage::placement::raft::RaftMachineStorage;
 use bincode::{deserialize, serialize};
 use common_base::config::placement_center::placement_center_conf;
 use log::{error, info};
 use metadata_struct::placement::broker_node::BrokerNode;
 use prost::Message as _;
+use protocol::placement_center::generate::placement::{ListRaftMemberReply, RaftMember};
 use raft::eraftpb::{
-    ConfChange, ConfChangeType, Entry, EntryType, Message as raftPreludeMessage, MessageType,
-    Snapshot,
+    ConfChange, ConfChangeSingle, ConfChangeTransition, ConfChangeType, ConfChangeV2, Entry,
+    EntryType, Message as raftPreludeMessage, MessageType, Snapshot,
 };
-use raft::{Config, RawNode};
+use raft::{Config, RawNode, SnapshotStatus, StateRole};
 use slog::o;
 use slog::Drain;
 use std::collections::HashMap;
@@ -44,6 +46,13 @@ pub struct RaftMachine {
     receiver: Receiver<RaftMessage>,
     seqnum: AtomicUsize,
     resp_channel: HashMap<usize, oneshot::Sender<RaftResponseMesage>>,
+    // Reads waiting for the leader to confirm the read index
+    read_channel: HashMap<usize, oneshot::Sender<RaftResponseMesage>>,
+    // Reads whose read index is confirmed, waiting for the index to be applied
+    pending_reads: Vec<(u64, oneshot::Sender<RaftResponseMesage>)>,
+    // Context of the membership change that entered the joint configuration on this leader,
+    // its caller is answered once the joint configuration is left.
+    joint_context: Option<Vec<u8>>,
     data_route: Arc<RwLock<DataRoute>>,
     entry_num: AtomicUsize,
     peer_message_send: Sender<PeerMessage>,
@@ -68,6 +77,9 @@ impl RaftMachine {
             receiver,
             seqnum,
             resp_channel,
+            read_channel: HashMap::new(),
+            pending_reads: Vec::new(),
+            joint_context: None,
             data_route,
             entry_num,
             peer_message_send,
@@ -77,6 +89,7 @@ impl RaftMachine {
     }
 
     pub async fn run(&mut self) {
+        self.load_members();
         let mut raft_node: RawNode<RaftRocksDBStorage> = self.new_node().await;
         let heartbeat = Duration::from_millis(100);
         let mut now = Instant::now();
@@ -127,12 +140,91 @@ impl RaftMachine {
                 Ok(Some(RaftMessage::TransferLeader { node_id, chan })) => {
                     // Step advances the state machine using the given message.
                     info!("transfer_leader {}", node_id);
-                    raft_node.transfer_leader(node_id);
-                    match chan.send(RaftResponseMesage::Success) {
-                        Ok(_) => {}
-                        Err(_) => {
-                            error!("{}","commit entry Fails to return data to chan. chan may have been closed");
+                    match self.check_transfer_leader(&raft_node, node_id) {
+                        Ok(()) => {
+                            raft_node.transfer_leader(node_id);
+                            respond(chan, RaftResponseMesage::Success);
+                        }
+                        Err(reason) => respond(chan, RaftResponseMesage::Reject(reason)),
+                    }
+                }
+
+                Ok(Some(RaftMessage::Membership { change, chan })) => {
+                    info!("membership change {:?}", change);
+                    match self.membership_conf_change(&raft_node, change) {
+                        Ok(cc) => {
+                            let seq = self
+                                .seqnum
+                                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                            match raft_node.propose_conf_change(serialize(&seq).unwrap(), cc) {
+                                Ok(_) => {
+                                    self.resp_channel.insert(seq, chan);
+                                }
+                                Err(e) => {
+                                    respond(chan, RaftResponseMesage::Reject(e.to_string()));
+                                }
+                            }
                         }
+                        Err(reason) => respond(chan, RaftResponseMesage::Reject(reason)),
+                    }
+                }
+
+                Ok(Some(RaftMessage::ReadIndex { chan })) => {
+                    if raft_node.raft.state != StateRole::Leader {
+                        let reason = format!("node {} is not the leader", raft_node.raft.id);
+                        respond(chan, RaftResponseMesage::Reject(reason));
+                    } else {
+                        let seq = self
+                            .seqnum
+                            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                        raft_node.read_index(serialize(&seq).unwrap());
+                        self.read_channel.insert(seq, chan);
+                    }
+                }
+
+                Ok(Some(RaftMessage::ListMember { chan })) => {
+                    let reply = self.list_member(&raft_node);
+                    respond(
+                        chan,
+                        RaftResponseMesage::Reply(ListRaftMemberReply::encode_to_vec(&reply)),
+                    );
+                }
+
+                Ok(Some(RaftMessage::ReportSnapshot {
+                    node_id,
+                    success,
+                    chan,
+                })) => {
+                    // The transfer of a snapshot to a peer finished, replication to the peer
+                    // resumes from the snapshot, or the snapshot is sent again on failure.
+                    let status = if success {
+                        SnapshotStatus::Finish
+                    } else {
+                        SnapshotStatus::Failure
+                    };
+                    raft_node.report_snapshot(node_id, status);
+                    if chan.send(RaftResponseMesage::Success).is_err() {
+                        error!(
+                            "{}",
+                            "commit entry Fails to return data to chan. chan may have been closed"
+                        );
+                    }
+                }
+
+                Ok(Some(RaftMessage::Checkpoint { path, chan })) => {
+                    let applied = raft_node.raft.raft_log.applied;
+                    let rocksdb_engine_handler = self
+                        .raft_storage
+                        .read()
+                        .unwrap()
+                        .rocksdb_engine_handler
+                        .clone();
+                    match rocksdb_engine_handler.checkpoint(&path) {
+                        Ok(()) => respond(
+                            chan,
+                            RaftResponseMesage::Reply(serialize(&applied).unwrap()),
+                        ),
+                        Err(e) => respond(chan, RaftResponseMesage::Reject(e.to_string())),
                     }
                 }
 
@@ -170,10 +262,23 @@ impl RaftMachine {
                 self.placement_cluster
                     .write()
                     .unwrap()
-                    .set_role(raft_node.raft.state)
+                    .set_role(raft_node.raft.state);
+                // Read indexes requested as leader are never confirmed once it stepped down
+                if raft_node.raft.state != StateRole::Leader {
+                    for (_, chan) in self.read_channel.drain() {
+                        let reason = format!("node {} is no longer the leader", raft_node.raft.id);
+                        respond(chan, RaftResponseMesage::Reject(reason));
+                    }
+                }
             }
+            self.placement_cluster
+                .write()
+                .unwrap()
+                .update_leader(raft_node.raft.leader_id);
             // info!(&format!("{:?}",raft_node.raft.state));
             self.on_ready(&mut raft_node).await;
+            self.release_reads(raft_node.raft.raft_log.applied);
+            self.leave_joint(&mut raft_node);
         }
     }
 
@@ -189,6 +294,17 @@ impl RaftMachine {
             self.send_message(ready.take_messages()).await;
         }
 
+        // The leader confirmed the read indexes, the reads wait until they are applied.
+        for read_state in ready.take_read_states() {
+            let seq: usize = match deserialize(&read_state.request_ctx) {
+                Ok(seq) => seq,
+                Err(_) => continue,
+            };
+            if let Some(chan) = self.read_channel.remove(&seq) {
+                self.pending_reads.push((read_state.index, chan));
+            }
+        }
+
         // If the snapshot is not empty, save the snapshot to Storage, and apply
         // the data in the snapshot to the State Machine asynchronously.
         // (Although synchronous apply can also be applied here,
@@ -201,6 +317,9 @@ impl RaftMachine {
                 s.get_metadata().get_index()
             );
             raft_node.mut_store().apply_snapshot(s).unwrap();
+            // The state machine was replaced, the caches built from it are stale.
+            self.data_route.read().unwrap().reload_cache();
+            self.load_members();
         }
 
         // messages need to be stored to Storage before they can be sent.Save entries to Storage.
@@ -246,15 +365,22 @@ impl RaftMachine {
     ) {
         let data_route = self.data_route.write().unwrap();
         for entry in entrys {
-            if !entry.data.is_empty() {
+            let mut reply = RaftResponseMesage::Reply(Vec::new());
+            let mut entered_joint = false;
+            // Leaving a joint configuration is an empty ConfChangeV2, which must be applied too.
+            if !entry.data.is_empty() || entry.get_entry_type() == EntryType::EntryConfChangeV2 {
                 info!("ready entrys entry type:{:?}", entry.get_entry_type());
                 match entry.get_entry_type() {
                     EntryType::EntryNormal => {
                         // Saves the service data sent by the client
+                        // An error of the state machine is returned to the proposer.
                         match data_route.route(entry.get_data().to_vec()) {
-                            Ok(_) => {}
+                            Ok(data) => {
+                                reply = RaftResponseMesage::Reply(data);
+                            }
                             Err(err) => {
                                 error!("{}", err);
+                                reply = RaftResponseMesage::Reject(err.to_string());
                             }
                         }
                     }
@@ -287,27 +413,87 @@ impl RaftMachine {
                             let _ = raft_node.mut_store().set_conf_state(cs);
                         }
                     }
-                    EntryType::EntryConfChangeV2 => {}
+                    EntryType::EntryConfChangeV2 => {
+                        // A malformed entry is skipped, every node skips it the same way.
+                        match ConfChangeV2::decode(entry.get_data()) {
+                            Ok(change) => {
+                                self.apply_membership(&change);
+                                match raft_node.apply_conf_change(&change) {
+                                    Ok(cs) => {
+                                        info!("Raft configuration changes to {:?}", cs);
+                                        entered_joint = !cs.voters_outgoing.is_empty();
+                                        let _ = raft_node.mut_store().set_conf_state(cs);
+                                    }
+                                    Err(e) => {
+                                        error!("Failed to apply the membership change, {}", e);
+                                    }
+                                }
+                            }
+                            Err(e) => {
+                                error!(
+                                    "Failed to decode the membership change at index {}, {}",
+                                    entry.get_index(),
+                                    e
+                                );
+                                reply = RaftResponseMesage::Reject(e.to_string());
+                            }
+                        }
+                    }
                 }
             }
 
             let idx: u64 = entry.get_index();
             let _ = raft_node.mut_store().commmit_index(idx);
 
-            match deserialize(entry.get_context()) {
-                Ok(seq) => match self.resp_channel.remove(&seq) {
-                    Some(chan) => match chan.send(RaftResponseMesage::Success) {
+            if entered_joint {
+                if raft_node.raft.state == StateRole::Leader {
+                    self.joint_context = Some(entry.get_context().to_vec());
+                }
+            } else {
+                self.respond_entry(&entry, reply);
+            }
+
+            self.create_snapshot(raft_node, entry.get_index(), entry.get_term());
+        }
+    }
+
+    fn respond_entry(&mut self, entry: &Entry, reply: RaftResponseMesage) {
+        match deserialize(entry.get_context()) {
+            Ok(seq) => {
+                match self.resp_channel.remove(&seq) {
+                    Some(chan) => match chan.send(reply) {
                         Ok(_) => {}
                         Err(_) => {
                             error!("commit entry Fails to return data to chan. chan may have been closed");
                         }
                     },
                     None => {}
-                },
-                Err(_) => {}
+                }
             }
+            Err(_) => {}
+        }
+    }
 
-            self.create_snapshot(raft_node);
+    // The leader leaves a joint configuration as soon as entering it has been applied, the
+    // empty ConfChangeV2 carries the context of the change that entered it. The joint state
+    // is read from the configuration, so a leader elected while the group is still joint
+    // leaves it too, without the context that only the previous leader had.
+    fn leave_joint(&mut self, raft_node: &mut RawNode<RaftRocksDBStorage>) {
+        if raft_node.raft.state != StateRole::Leader {
+            self.joint_context = None;
+            return;
+        }
+        if raft_node.raft.has_pending_conf() {
+            return;
+        }
+        let cs = raft_node.raft.prs().conf().to_conf_state();
+        if cs.voters_outgoing.is_empty() {
+            self.joint_context = None;
+            return;
+        }
+        let context = self.joint_context.take().unwrap_or_default();
+        if let Err(e) = raft_node.propose_conf_change(context, ConfChangeV2::default()) {
+            error!("Failed to leave the joint configuration, {}", e);
         }
     }
 
@@ -319,8 +505,21 @@ impl RaftMachine {
             {
                 info!("ready message:{:?}", msg);
             }
+            let snapshot = if msg.get_msg_type() == MessageType::MsgSnapshot {
+                // The snapshot data is only the name of the snapshot file, the peers
+                // manager streams the file before delivering the message.
+                match String::from_utf8(msg.get_snapshot().get_data().to_vec()) {
+                    Ok(name) => Some(PeerSnapshot { node_id: to, name }),
+                    Err(e) => {
+                        error!("Snapshot sent to node {} has an invalid name, {}", to, e);
+                        continue;
+                    }
+                }
+            } else {
+                None
+            };
             let data: Vec<u8> = raftPreludeMessage::encode_to_vec(&msg);
-            self.send_peer_message(to, data).await;
+            self.send_peer_message(to, data, snapshot).await;
         }
     }
 
@@ -332,10 +531,13 @@ impl RaftMachine {
         let hs = storage.read_lock().hard_state();
         let conf = self.build_config(hs.commit);
 
-        // init voters && learns
+        // init voters && learns, once the membership was changed the stored configuration
+        // is the one of the cluster.
         let mut cs = storage.read_lock().conf_state();
-        cs.voters = cluster.node_ids();
-        let _ = storage.write_lock().save_conf_state(cs);
+        if cs.voters.is_empty() && cs.learners.is_empty() {
+            cs.voters = cluster.node_ids();
+            let _ = storage.write_lock().save_conf_state(cs);
+        }
 
         let logger = self.build_slog();
         let node = RawNode::new(&conf, storage, &logger).unwrap();
@@ -389,16 +591,263 @@ impl RaftMachine {
         return logger;
     }
 
-    fn create_snapshot(&self, raft_node: &mut RawNode<RaftRocksDBStorage>) {
+    // Takes a snapshot after every snapshot_interval_entries applied entries, or as soon as
+    // a peer needs one. The checkpoint is taken while no entry is being applied, the snapshot
+    // file is then built from it in the background.
+    fn create_snapshot(&self, raft_node: &mut RawNode<RaftRocksDBStorage>, index: u64, term: u64) {
+        let conf = placement_center_conf();
+        let interval = conf.raft.snapshot_interval_entries as usize;
         let num = self
             .entry_num
             .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-        if num % 1000 == 0 {
-            raft_node.mut_store().create_snapshot().unwrap();
+        let due = interval > 0 && num % interval == 0;
+        if !due && !raft_node.store().snapshot_requested() {
+            return;
         }
+
+        let log_tail_entries = conf.raft.snapshot_log_tail_entries;
+        let name = match raft_node
+            .mut_store()
+            .create_snapshot(index, term, log_tail_entries)
+        {
+            Ok(name) => name,
+            Err(e) => {
+                error!("Failed to take a raft snapshot at index {}, {}", index, e);
+                return;
+            }
+        };
+        // A snapshot whose file failed to build cannot be sent, the next applied entry
+        // takes a new one.
+        let raft_storage = self.raft_storage.clone();
+        let snapshot_store = raft_storage.read().unwrap().snapshot_store.clone();
+        tokio::task::spawn_blocking(move || match snapshot_store.build(&name) {
+            Ok(()) => snapshot_store.retain(&name),
+            Err(e) => {
+                error!("Failed to build raft snapshot {}, {}", name, e);
+                raft_storage.write().unwrap().snapshot_requested = true;
+            }
+        });
     }
 
-    pub async fn send_peer_message(&self, id: u64, msg: Vec<u8>) {
+    fn release_reads(&mut self, applied: u64) {
+        if self.pending_reads.is_empty() {
+            return;
+        }
+        let (ready, waiting): (Vec<_>, Vec<_>) = self
+            .pending_reads
+            .drain(..)
+            .partition(|(index, _)| *index <= applied);
+        self.pending_reads = waiting;
+        for (_, chan) in ready {
+            respond(chan, RaftResponseMesage::Success);
+        }
+    }
+
+    // Validates a membership change against the current configuration and turns it into
+    // a ConfChangeV2. Changes are made one member at a time, and only once the previous
+    // change was applied. Each change enters a joint configuration that the leader leaves
+    // explicitly, see leave_joint.
+    fn membership_conf_change(
+        &self,
+        raft_node: &RawNode<RaftRocksDBStorage>,
+        change: MembershipChange,
+    ) -> Result<ConfChangeV2, String> {
+        if raft_node.raft.state != StateRole::Leader {
+            return Err(format!("node {} is not the leader", raft_node.raft.id));
+        }
+        if raft_node.raft.has_pending_conf() {
+            return Err("the previous membership change is not applied yet".to_string());
+        }
+        let cs = raft_node.raft.prs().conf().to_conf_state();
+        if !cs.voters_outgoing.is_empty() {
+            return Err("the cluster is in a joint configuration".to_string());
+        }
+        let is_voter = |id: u64| cs.voters.contains(&id);
+        let is_learner = |id: u64| cs.learners.contains(&id);
+
+        let (change_type, node_id, node) = match change {
+            MembershipChange::AddVoter(node) | MembershipChange::AddLearner(node)
+                if is_voter(node.node_id) || is_learner(node.node_id) =>
+            {
+                return Err(format!("node {} is already a member", node.node_id));
+            }
+            MembershipChange::AddVoter(node) | MembershipChange::AddLearner(node)
+                if node.node_inner_addr.is_empty() =>
+            {
+                return Err(format!("node {} has no address", node.node_id));
+            }
+            MembershipChange::AddVoter(node) => (ConfChangeType::AddNode, node.node_id, Some(node)),
+            MembershipChange::AddLearner(node) => {
+                (ConfChangeType::AddLearnerNode, node.node_id, Some(node))
+            }
+            MembershipChange::PromoteLearner(node_id) => {
+                if !is_learner(node_id) {
+                    return Err(format!("node {} is not a learner", node_id));
+                }
+                // A learner that has not caught up would slow down the commits once voting.
+                let matched = raft_node
+                    .raft
+                    .prs()
+                    .get(node_id)
+                    .map(|pr| pr.matched)
+                    .unwrap_or(0);
+                let committed = raft_node.raft.raft_log.committed;
+                if matched < committed {
+                    return Err(format!(
+                        "learner {} has not caught up, it has index {} of {}",
+                        node_id, matched, committed
+                    ));
+                }
+                let node = self
+                    .placement_cluster
+                    .read()
+                    .unwrap()
+                    .get_node_by_id(node_id)
+                    .cloned();
+                (ConfChangeType::AddNode, node_id, node)
+            }
+            MembershipChange::Remove(node_id) => {
+                if !is_voter(node_id) && !is_learner(node_id) {
+                    return Err(format!("node {} is not a member", node_id));
+                }
+                if node_id == raft_node.raft.id {
+                    return Err(format!(
+                        "node {} is the leader, transfer the leadership first",
+                        node_id
+                    ));
+                }
+                if is_voter(node_id) && cs.voters.len() == 1 {
+                    return Err(format!("node {} is the last voter", node_id));
+                }
+                (ConfChangeType::RemoveNode, node_id, None)
+            }
+        };
+
+        let mut single = ConfChangeSingle::default();
+        single.set_change_type(change_type);
+        single.node_id = node_id;
+        let mut cc = ConfChangeV2::default();
+        cc.set_transition(ConfChangeTransition::Explicit);
+        cc.changes = vec![single];
+        if let Some(node) = node {
+            cc.context = serialize(&node).unwrap().into();
+        }
+        return Ok(cc);
+    }
+
+    // Updates the members known by this node as the membership change is applied, on
+    // every node of the cluster.
+    fn apply_membership(&self, change: &ConfChangeV2) {
+        let member_storage = self.member_storage();
+        let node = if change.get_context().is_empty() {
+            None
+        } else {
+            match deserialize::<BrokerNode>(change.get_context()) {
+                Ok(node) => Some(node),
+                Err(e) => {
+                    error!(
+                        "Failed to parse Node data from context with error message {:?}",
+                        e
+                    );
+                    None
+                }
+            }
+        };
+        for single in change.get_changes() {
+            let id = single.node_id;
+            match single.get_change_type() {
+                ConfChangeType::AddNode | ConfChangeType::AddLearnerNode => {
+                    if let Some(node) = node.clone() {
+                        self.placement_cluster
+                            .write()
+                            .unwrap()
+                            .add_peer(id, node.clone());
+                        if let Err(e) = member_storage.save(node) {
+                            error!("Failed to save placement center member {}, {}", id, e);
+                        }
+                    }
+                }
+                ConfChangeType::RemoveNode => {
+                    self.placement_cluster.write().unwrap().remove_peer(id);
+                    if let Err(e) = member_storage.delete(id) {
+                        error!("Failed to delete placement center member {}, {}", id, e);
+                    }
+                }
+            }
+        }
+    }
+
+    // Adds the members stored by earlier membership changes to the configured ones.
+    fn load_members(&self) {
+        match self.member_storage().list() {
+            Ok(nodes) => {
+                let mut cluster = self.placement_cluster.write().unwrap();
+                for node in nodes {
+                    cluster.add_peer(node.node_id, node);
+                }
+            }
+            Err(e) => error!("Failed to load the placement center members, {}", e),
+        }
+    }
+
+    fn list_member(&self, raft_node: &RawNode<RaftRocksDBStorage>) -> ListRaftMemberReply {
+        let cs = raft_node.raft.prs().conf().to_conf_state();
+        let cluster = self.placement_cluster.read().unwrap();
+        let mut members = Vec::new();
+        for (ids, learner) in [(&cs.voters, false), (&cs.learners, true)] {
+            for id in ids {
+                members.push(RaftMember {
+                    node_id: *id,
+                    node_addr: cluster
+                        .get_node_by_id(*id)
+                        .map(|node| node.node_inner_addr.clone())
+                        .unwrap_or_default(),
+                    learner,
+                    matched: raft_node
+                        .raft
+                        .prs()
+                        .get(*id)
+                        .map(|pr| pr.matched)
+                        .unwrap_or(0),
+                });
+            }
+        }
+        return ListRaftMemberReply {
+            leader_id: raft_node.raft.leader_id,
+            commit_index: raft_node.raft.raft_log.committed,
+            members,
+        };
+    }
+
+    fn check_transfer_leader(
+        &self,
+        raft_node: &RawNode<RaftRocksDBStorage>,
+        node_id: u64,
+    ) -> Result<(), String> {
+        if raft_node.raft.state != StateRole::Leader {
+            return Err(format!("node {} is not the leader", raft_node.raft.id));
+        }
+        if node_id == raft_node.raft.id {
+            return Err(format!("node {} is already the leader", node_id));
+        }
+        let cs = raft_node.raft.prs().conf().to_conf_state();
+        if !cs.voters.contains(&node_id) {
+            return Err(format!("node {} is not a voter", node_id));
+        }
+        return Ok(());
+    }
+
+    fn member_storage(&self) -> RaftMemberStorage {
+        let rocksdb_engine_handler = self
+            .raft_storage
+            .read()
+            .unwrap()
+            .rocksdb_engine_handler
+            .clone();
+        return RaftMemberStorage::new(rocksdb_engine_handler);
+    }
+
+    pub async fn send_peer_message(&self, id: u64, msg: Vec<u8>, snapshot: Option<PeerSnapshot>) {
         if let Some(node) = self.placement_cluster.read().unwrap().get_node_by_id(id) {
             let send = self.peer_message_send.clone();
             let node_c = node.clone();
@@ -407,6 +856,7 @@ impl RaftMachine {
                     .send(PeerMessage {
                         to: node_c.node_inner_addr,
                         data: msg,
+                        snapshot,
                     })
                     .await
                 {
@@ -422,3 +872,9 @@ impl RaftMachine {
         }
     }
 }
+
+fn respond(chan: oneshot::Sender<RaftResponseMesage>, resp: RaftResponseMesage) {
+    if chan.send(resp).is_err() {
+        error!("commit entry Fails to return data to chan. chan may have been closed");
+    }
+}
@@ -91,12 +91,33 @@ impl RaftGroupMetadata {
         self.raft_role = role;
     }
 
-    #[allow(dead_code)]
     pub fn set_leader(&mut self, leader: BrokerNode) {
         self.leader = Some(leader);
     }
 
+    // Follows the leader known by raft, 0 when there is none.
+    pub fn update_leader(&mut self, leader_id: u64) {
+        let current = self.leader.as_ref().map(|node| node.node_id).unwrap_or(0);
+        if current == leader_id {
+            return;
+        }
+        info!(
+            "Placement center leader changes from {} to {}",
+            current, leader_id
+        );
+        match self.get_node_by_id(leader_id).cloned() {
+            Some(node) => self.set_leader(node),
+            None => self.leader = None,
+        }
+    }
+
     pub fn get_node_by_id(&self, id: u64) -> Option<&BrokerNode> {
+        if id == self.local.node_id {
+            if let Some(node) = self.peers.get(&id) {
+                return Some(node);
+            }
+            return Some(&self.local);
+        }
         self.peers.get(&id)
     }
 
@@ -11,9 +11,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use clients::{placement::placement::call::send_raft_message, poll::ClientPool};
+use super::apply::RaftMachineApply;
+use crate::storage::placement::snapshot::SnapshotStore;
+use clients::{
+    placement::placement::call::{send_raft_message, send_raft_snapshot},
+    poll::ClientPool,
+};
+use common_base::{config::placement_center::placement_center_conf, error::common::CommonError};
 use log::{debug, error, info};
-use protocol::placement_center::generate::placement::SendRaftMessageRequest;
+use protocol::placement_center::generate::placement::{
+    SendRaftMessageRequest, SendRaftSnapshotRequest,
+};
 use std::sync::Arc;
 use tokio::sync::mpsc;
 
@@ -21,21 +29,35 @@ use tokio::sync::mpsc;
 pub struct PeerMessage {
     pub to: String,
     pub data: Vec<u8>,
+    // Set for a snapshot message, whose snapshot file is sent before the message
+    pub snapshot: Option<PeerSnapshot>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PeerSnapshot {
+    pub node_id: u64,
+    pub name: String,
 }
 
 pub struct PeersManager {
     peer_message_recv: mpsc::Receiver<PeerMessage>,
     client_poll: Arc<ClientPool>,
+    snapshot_store: Arc<SnapshotStore>,
+    placement_center_storage: Arc<RaftMachineApply>,
 }
 
 impl PeersManager {
     pub fn new(
         peer_message_recv: mpsc::Receiver<PeerMessage>,
         client_poll: Arc<ClientPool>,
+        snapshot_store: Arc<SnapshotStore>,
+        placement_center_storage: Arc<RaftMachineApply>,
     ) -> PeersManager {
         let pm = PeersManager {
             peer_message_recv,
             client_poll,
+            snapshot_store,
+            placement_center_storage,
         };
         return pm;
     }
@@ -48,6 +70,44 @@ impl PeersManager {
         loop {
             if let Some(data) = self.peer_message_recv.recv().await {
                 let addr = data.to;
+                if let Some(snapshot) = data.snapshot {
+                    // Snapshots can be large, they are sent without holding back the other
+                    // messages.
+                    let client_poll = self.client_poll.clone();
+                    let snapshot_store = self.snapshot_store.clone();
+                    let placement_center_storage = self.placement_center_storage.clone();
+                    tokio::spawn(async move {
+                        let success = match transfer_snapshot(
+                            client_poll,
+                            snapshot_store,
+                            &addr,
+                            &snapshot.name,
+                            data.data,
+                        )
+                        .await
+                        {
+                            Ok(()) => {
+                                info!("Raft snapshot {} was sent to node {}", snapshot.name, addr);
+                                true
+                            }
+                            Err(e) => {
+                                error!(
+                                    "Failed to send raft snapshot {} to {}, error message: {}",
+                                    snapshot.name, addr, e
+                                );
+                                false
+                            }
+                        };
+                        if let Err(e) = placement_center_storage
+                            .report_snapshot(snapshot.node_id, success)
+                            .await
+                        {
+                            error!("Failed to report the raft snapshot status, {}", e);
+                        }
+                    });
+                    continue;
+                }
+
                 let request = SendRaftMessageRequest { message: data.data };
                 match send_raft_message(self.client_poll.clone(), vec![addr.clone()], request).await
                 {
@@ -62,3 +122,34 @@ impl PeersManager {
         }
     }
 }
+
+// Streams the snapshot file to the peer in chunks, the last chunk carries the snapshot
+// message which installs it.
+async fn transfer_snapshot(
+    client_poll: Arc<ClientPool>,
+    snapshot_store: Arc<SnapshotStore>,
+    addr: &str,
+    name: &str,
+    message: Vec<u8>,
+) -> Result<(), CommonError> {
+    let chunk_bytes = std::cmp::max(placement_center_conf().raft.snapshot_chunk_bytes, 1);
+    let len = snapshot_store.len(name)?;
+    let mut offset = 0;
+    loop {
+        let data = snapshot_store.read_chunk(name, offset, chunk_bytes)?;
+        let next_offset = offset + data.len() as u64;
+        let done = next_offset >= len;
+        let request = SendRaftSnapshotRequest {
+            snapshot: name.to_string(),
+            offset,
+            data,
+            done,
+            message: if done { message.clone() } else { Vec::new() },
+        };
+        send_raft_snapshot(client_poll.clone(), vec![addr.to_string()], request).await?;
+        if done {
+            return Ok(());
+        }
+        offset = next_offset;
+    }
+}
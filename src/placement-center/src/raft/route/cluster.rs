@@ -13,8 +13,10 @@
 
 use crate::{
     cache::placement::PlacementCacheManager,
+    core::share_sub::ShareSubLeader,
     storage::{
-        mqtt::acl::AclStorage,
+        keys::key_cluster_name,
+        mqtt::{acl::AclStorage, blacklist::MQTTBlackListStorage},
         placement::{
             cluster::ClusterStorage, config::ResourceConfigStorage, idempotent::IdempotentStorage,
             node::NodeStorage,
@@ -27,15 +29,17 @@ use common_base::{
     tools::{now_mills, unique_id},
 };
 use metadata_struct::{
-    acl::mqtt_acl::MQTTAcl,
+    acl::{mqtt_acl::MQTTAcl, mqtt_blacklist::MQTTAclBlackList},
     placement::{broker_node::BrokerNode, cluster::ClusterInfo},
 };
 use prost::Message as _;
 use protocol::placement_center::generate::{
-    mqtt::{CreateAclRequest, DeleteAclRequest},
+    common::ClusterType,
+    mqtt::{CreateAclRequest, CreateBlacklistRequest, DeleteAclRequest, DeleteBlacklistRequest},
     placement::{
-        DeleteIdempotentDataRequest, DeleteResourceConfigRequest, RegisterNodeRequest,
-        SetIdempotentDataRequest, SetResourceConfigRequest, UnRegisterNodeRequest,
+        DeleteIdempotentDataRequest, DeleteResourceConfigRequest, ImportMetadataRequest,
+        RegisterNodeRequest, SetIdempotentDataRequest, SetResourceConfigRequest,
+        UnRegisterNodeRequest,
     },
 };
 use std::sync::Arc;
@@ -92,12 +96,23 @@ impl DataRouteCluster {
 
     pub fn delete_node(&self, value: Vec<u8>) -> Result<(), CommonError> {
         let req: UnRegisterNodeRequest = UnRegisterNodeRequest::decode(value.as_ref())?;
+        let cluster_type = req.cluster_type();
         let cluster_name = req.cluster_name;
         let node_id = req.node_id;
         self.cluster_cache.remove_node(&cluster_name, node_id);
 
         let node_storage = NodeStorage::new(self.rocksdb_engine_handler.clone());
-        return node_storage.delete(&cluster_name, node_id);
+        node_storage.delete(&cluster_name, node_id)?;
+
+        // The shared subscription groups led by the broker move to the remaining brokers
+        if cluster_type == ClusterType::MqttBrokerServer {
+            let share_sub = ShareSubLeader::new(
+                self.cluster_cache.clone(),
+                self.rocksdb_engine_handler.clone(),
+            );
+            share_sub.reassign_node_groups(&cluster_name, node_id)?;
+        }
+        return Ok(());
     }
 
     pub fn set_resource_config(&self, value: Vec<u8>) -> Result<(), CommonError> {
@@ -137,6 +152,38 @@ impl DataRouteCluster {
         let acl = serde_json::from_slice::<MQTTAcl>(&req.acl)?;
         return acl_storage.delete(&req.cluster_name, &acl);
     }
+
+    pub fn create_blacklist(&self, value: Vec<u8>) -> Result<(), CommonError> {
+        let req = CreateBlacklistRequest::decode(value.as_ref())?;
+        let blacklist_storage = MQTTBlackListStorage::new(self.rocksdb_engine_handler.clone());
+        let blacklist = serde_json::from_slice::<MQTTAclBlackList>(&req.blacklist)?;
+        return blacklist_storage.save(&req.cluster_name, blacklist);
+    }
+
+    pub fn delete_blacklist(&self, value: Vec<u8>) -> Result<(), CommonError> {
+        let req = DeleteBlacklistRequest::decode(value.as_ref())?;
+        let blacklist_storage = MQTTBlackListStorage::new(self.rocksdb_engine_handler.clone());
+        return blacklist_storage.delete(
+            &req.cluster_name,
+            &req.blacklist_type,
+            &req.resource_name,
+        );
+    }
+
+    // Writes the keys of the cluster read from a backup, the keys of other clusters are
+    // dropped.
+    pub fn import_metadata(&self, value: Vec<u8>) -> Result<(), CommonError> {
+        let req = ImportMetadataRequest::decode(value.as_ref())?;
+        let data = req
+            .entries
+            .into_iter()
+            .filter(|entry| key_cluster_name(&entry.key) == Some(req.cluster_name.as_str()))
+            .map(|entry| (entry.key.into_bytes(), entry.value))
+            .collect();
+        return self
+            .rocksdb_engine_handler
+            .write_batch(self.rocksdb_engine_handler.cf_cluster(), data);
+    }
 }
 
 #[cfg(test)]
@@ -12,39 +12,39 @@
 // limitations under the License.
 
 use crate::{
-    cache::{journal::JournalCacheManager, placement::PlacementCacheManager},
-    controller::journal::segment_replica::SegmentReplicaAlgorithm,
+    cache::journal::JournalCacheManager,
     storage::{
         journal::{
-            segment::{SegmentInfo, SegmentStatus, SegmentStorage},
+            segment::{Replica, SegmentInfo, SegmentStatus, SegmentStorage},
             shard::{ShardInfo, ShardStorage},
         },
         rocksdb::RocksDBEngine,
     },
 };
-use common_base::{error::common::CommonError, tools::{now_mills, unique_id}};
+use common_base::{
+    error::{common::CommonError, placement_center::PlacementCenterError},
+    tools::{now_mills, unique_id},
+};
 use prost::Message as _;
 use protocol::placement_center::generate::journal::{
-    CreateSegmentRequest, CreateShardRequest, DeleteSegmentRequest,
+    CreateSegmentRequest, CreateShardRequest, DeleteSegmentRequest, RollSegmentRequest,
+    UpdateSegmentLeaderRequest, UpdateSegmentStatusRequest,
 };
 use std::sync::Arc;
 
 pub struct DataRouteJournal {
     rocksdb_engine_handler: Arc<RocksDBEngine>,
     engine_cache: Arc<JournalCacheManager>,
-    cluster_cache: Arc<PlacementCacheManager>,
 }
 
 impl DataRouteJournal {
     pub fn new(
         rocksdb_engine_handler: Arc<RocksDBEngine>,
         engine_cache: Arc<JournalCacheManager>,
-        cluster_cache: Arc<PlacementCacheManager>,
     ) -> Self {
         return DataRouteJournal {
             rocksdb_engine_handler,
             engine_cache,
-            cluster_cache,
         };
     }
     pub fn create_shard(&self, value: Vec<u8>) -> Result<(), CommonError> {
@@ -57,6 +57,10 @@ impl DataRouteJournal {
             replica: req.replica,
             last_segment_seq: 0,
             create_time: now_mills(),
+            cleanup_policy: req.cleanup_policy.clone(),
+            tombstone_retention_ms: req.tombstone_retention_ms,
+            compression: req.compression.clone(),
+            compression_min_size: req.compression_min_size,
         };
 
         let shard_storage = ShardStorage::new(self.rocksdb_engine_handler.clone());
@@ -65,9 +69,7 @@ impl DataRouteJournal {
         // upate cache
         self.engine_cache.add_shard(shard_info);
 
-        // Create segments according to the built-in pre-created segment strategy.
-        // Between 0 and N segments may be created.
-        self.pre_create_segment()?;
+        // The segments are proposed separately, their replicas are chosen by the leader.
 
         // todo maybe update storage engine node cache
         return Ok(());
@@ -95,26 +97,137 @@ impl DataRouteJournal {
         let cluster_name = req.cluster_name;
         let shard_name = req.shard_name;
 
+        let mut shard_info = match self
+            .engine_cache
+            .get_shard(cluster_name.clone(), shard_name.clone())
+        {
+            Some(shard) => shard,
+            None => return Err(PlacementCenterError::ShardDoesNotExist(shard_name).into()),
+        };
         let segment_seq = self
             .engine_cache
             .next_segment_seq(&cluster_name, &shard_name);
 
-        let repcli_algo =
-            SegmentReplicaAlgorithm::new(self.cluster_cache.clone(), self.engine_cache.clone());
-        let segment_info = SegmentInfo {
+        // The replicas were chosen by the leader before the proposal, so that every node
+        // applies the same placement.
+        let replicas: Vec<Replica> = if req.replicas.is_empty() {
+            Vec::new()
+        } else {
+            serde_json::from_slice(&req.replicas)?
+        };
+        if replicas.is_empty() {
+            return Err(
+                PlacementCenterError::SegmentReplicasNotSet(shard_name, segment_seq).into(),
+            );
+        }
+
+        // A shard that has a segment being written gets the new one pre-created, it takes
+        // over when the active segment is rolled.
+        let mut segment_info = SegmentInfo {
             cluster_name: cluster_name.clone(),
             shard_name: shard_name.clone(),
-            replicas: repcli_algo.calc_replica_distribution(segment_seq),
+            replicas,
             replica_leader: 0,
-            segment_seq: segment_seq,
+            segment_seq,
             status: SegmentStatus::Idle,
+            ..Default::default()
         };
-        let segment_storage = SegmentStorage::new(self.rocksdb_engine_handler.clone());
-        segment_storage.save(segment_info.clone())?;
-        self.engine_cache.add_segment(segment_info);
+        if self
+            .engine_cache
+            .get_active_segment(&cluster_name, &shard_name)
+            .is_none()
+        {
+            segment_info.status = SegmentStatus::Write;
+            segment_info.start_time = now_mills();
+        }
+        self.save_segment(segment_info)?;
+
+        shard_info.last_segment_seq = segment_seq;
+        let shard_storage = ShardStorage::new(self.rocksdb_engine_handler.clone());
+        shard_storage.save(shard_info.clone())?;
+        self.engine_cache.add_shard(shard_info);
         return Ok(());
     }
 
+    // The active segment stops taking records and waits to be sealed, the pre-created
+    // segment becomes the active one.
+    pub fn roll_segment(&self, value: Vec<u8>) -> Result<(), CommonError> {
+        let req: RollSegmentRequest = RollSegmentRequest::decode(value.as_ref())?;
+        let cluster_name = req.cluster_name;
+        let shard_name = req.shard_name;
+
+        let mut segment = self.get_segment(&cluster_name, &shard_name, req.segment_seq)?;
+        check_segment_status(&segment, SegmentStatus::Write)?;
+        let mut next_segment = match self
+            .engine_cache
+            .get_next_segment(&cluster_name, &shard_name)
+        {
+            Some(segment) => segment,
+            None => {
+                return Err(PlacementCenterError::SegmentDoesNotExist(
+                    shard_name,
+                    req.segment_seq + 1,
+                )
+                .into())
+            }
+        };
+        if next_segment.replicas.len() <= req.replica_leader as usize {
+            return Err(PlacementCenterError::SegmentReplicaNotFound(
+                shard_name,
+                next_segment.segment_seq,
+                req.replica_leader,
+            )
+            .into());
+        }
+
+        segment.status = SegmentStatus::PrepareSealUp;
+        segment.end_offset = req.end_offset;
+        self.save_segment(segment)?;
+
+        next_segment.status = SegmentStatus::Write;
+        next_segment.start_offset = req.end_offset;
+        next_segment.start_bytes = req.end_bytes;
+        next_segment.start_time = now_mills();
+        next_segment.replica_leader = req.replica_leader;
+        self.save_segment(next_segment)?;
+        return Ok(());
+    }
+
+    pub fn update_segment_status(&self, value: Vec<u8>) -> Result<(), CommonError> {
+        let req: UpdateSegmentStatusRequest = UpdateSegmentStatusRequest::decode(value.as_ref())?;
+        let mut segment = self.get_segment(&req.cluster_name, &req.shard_name, req.segment_seq)?;
+        let (cur_status, next_status) = match (
+            SegmentStatus::from_name(&req.cur_status),
+            SegmentStatus::from_name(&req.next_status),
+        ) {
+            (Some(cur_status), Some(next_status)) => (cur_status, next_status),
+            _ => {
+                return Err(CommonError::CommmonError(format!(
+                    "invalid segment status transition from {} to {}",
+                    req.cur_status, req.next_status
+                )))
+            }
+        };
+        check_segment_status(&segment, cur_status)?;
+        segment.status = next_status;
+        return self.save_segment(segment);
+    }
+
+    pub fn update_segment_leader(&self, value: Vec<u8>) -> Result<(), CommonError> {
+        let req: UpdateSegmentLeaderRequest = UpdateSegmentLeaderRequest::decode(value.as_ref())?;
+        let mut segment = self.get_segment(&req.cluster_name, &req.shard_name, req.segment_seq)?;
+        if segment.replicas.len() <= req.replica_leader as usize {
+            return Err(PlacementCenterError::SegmentReplicaNotFound(
+                req.shard_name,
+                req.segment_seq,
+                req.replica_leader,
+            )
+            .into());
+        }
+        segment.replica_leader = req.replica_leader;
+        return self.save_segment(segment);
+    }
+
     pub fn delete_segment(&self, value: Vec<u8>) -> Result<(), CommonError> {
         let req: DeleteSegmentRequest = DeleteSegmentRequest::decode(value.as_ref())?;
         let cluster_name = req.cluster_name;
@@ -128,7 +241,46 @@ impl DataRouteJournal {
         return Ok(());
     }
 
-    pub fn pre_create_segment(&self) -> Result<(), CommonError> {
+    fn get_segment(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+        segment_seq: u64,
+    ) -> Result<SegmentInfo, CommonError> {
+        match self
+            .engine_cache
+            .get_segment(cluster_name, shard_name, segment_seq)
+        {
+            Some(segment) => return Ok(segment),
+            None => {
+                return Err(PlacementCenterError::SegmentDoesNotExist(
+                    shard_name.clone(),
+                    segment_seq,
+                )
+                .into())
+            }
+        }
+    }
+
+    fn save_segment(&self, segment: SegmentInfo) -> Result<(), CommonError> {
+        let segment_storage = SegmentStorage::new(self.rocksdb_engine_handler.clone());
+        segment_storage.save(segment.clone())?;
+        self.engine_cache.add_segment(segment);
         return Ok(());
     }
 }
+
+// The controller proposes a transition based on what it saw before the proposal, the
+// segment may have moved on since.
+fn check_segment_status(segment: &SegmentInfo, expected: SegmentStatus) -> Result<(), CommonError> {
+    if segment.status != expected {
+        return Err(PlacementCenterError::SegmentStatusMismatch(
+            segment.shard_name.clone(),
+            segment.segment_seq,
+            format!("{:?}", segment.status),
+            format!("{:?}", expected),
+        )
+        .into());
+    }
+    return Ok(());
+}
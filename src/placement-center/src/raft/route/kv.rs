@@ -11,31 +11,156 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::storage::{placement::kv::KvStorage, rocksdb::RocksDBEngine};
+use crate::{
+    cache::kv::KvCacheManager,
+    storage::{
+        placement::{
+            kv::{is_lease_key, lease_id_of_key, KvStorage},
+            stream::StreamStorage,
+        },
+        rocksdb::RocksDBEngine,
+    },
+};
 use common_base::error::common::CommonError;
 use prost::Message as _;
-use protocol::placement_center::generate::kv::{DeleteRequest, SetRequest};
+use protocol::placement_center::generate::kv::{
+    CompareAndSwapReply, DeleteRequest, SetRequest, StreamCommitOffsetRequest,
+    StreamCreateShardRequest, StreamDeleteShardRequest, StreamResetGroupOffsetRequest,
+    StreamTrimRequest, StreamWriteReply, StreamWriteRequest,
+};
 use std::sync::Arc;
 pub struct DataRouteKv {
     pub rocksdb_engine_handler: Arc<RocksDBEngine>,
+    kv_cache: Arc<KvCacheManager>,
     kv_storage: KvStorage,
+    stream_storage: StreamStorage,
 }
 
 impl DataRouteKv {
-    pub fn new(rocksdb_engine_handler: Arc<RocksDBEngine>) -> Self {
+    pub fn new(rocksdb_engine_handler: Arc<RocksDBEngine>, kv_cache: Arc<KvCacheManager>) -> Self {
         let kv_storage = KvStorage::new(rocksdb_engine_handler.clone());
+        let stream_storage = StreamStorage::new(rocksdb_engine_handler.clone());
         return DataRouteKv {
             rocksdb_engine_handler,
+            kv_cache,
             kv_storage,
+            stream_storage,
         };
     }
-    pub fn set(&self, value: Vec<u8>) -> Result<(), CommonError> {
+
+    pub fn set(&self, value: Vec<u8>) -> Result<Vec<u8>, CommonError> {
         let req: SetRequest = SetRequest::decode(value.as_ref())?;
-        return self.kv_storage.set(req.key, req.value);
+        if is_lease_key(&req.key) {
+            return self.lease_grant(req);
+        }
+        let expected_revision = if req.compare_revision {
+            Some(req.expected_revision)
+        } else {
+            None
+        };
+        let revision = self.kv_storage.revision()?;
+        let reply = self
+            .kv_storage
+            .put(&req.key, &req.value, req.lease_id, expected_revision)?;
+        self.notify_from(revision)?;
+        return Ok(CompareAndSwapReply::encode_to_vec(&reply));
     }
 
     pub fn delete(&self, value: Vec<u8>) -> Result<(), CommonError> {
         let req: DeleteRequest = DeleteRequest::decode(value.as_ref())?;
-        return self.kv_storage.delete(req.key);
+        let revision = self.kv_storage.revision()?;
+        if is_lease_key(&req.key) {
+            if let Some(lease_id) = lease_id_of_key(&req.key) {
+                self.kv_storage.lease_revoke(lease_id)?;
+                self.kv_cache.remove_lease(lease_id);
+            }
+        } else {
+            self.kv_storage.delete(&req.key)?;
+        }
+        return self.notify_from(revision);
+    }
+
+    fn lease_grant(&self, req: SetRequest) -> Result<Vec<u8>, CommonError> {
+        let reply = match (lease_id_of_key(&req.key), req.value.parse::<u64>()) {
+            (Some(lease_id), Ok(ttl)) => self.kv_storage.lease_create(lease_id, ttl)?,
+            _ => CompareAndSwapReply::default(),
+        };
+        return Ok(CompareAndSwapReply::encode_to_vec(&reply));
+    }
+
+    // Sends the events written after the revision to the watchers
+    fn notify_from(&self, revision: u64) -> Result<(), CommonError> {
+        let end = self.kv_storage.revision()?;
+        if end == revision {
+            return Ok(());
+        }
+        let limit = (end - revision) as usize;
+        for event in self.kv_storage.list_event(revision + 1, limit)? {
+            self.kv_cache.notify(event);
+        }
+        return Ok(());
+    }
+    pub fn stream_create_shard(&self, value: Vec<u8>) -> Result<(), CommonError> {
+        let req: StreamCreateShardRequest = StreamCreateShardRequest::decode(value.as_ref())?;
+        return self.stream_storage.create_shard(
+            &req.cluster_name,
+            &req.shard_name,
+            req.config.unwrap_or_default(),
+        );
+    }
+
+    pub fn stream_delete_shard(&self, value: Vec<u8>) -> Result<(), CommonError> {
+        let req: StreamDeleteShardRequest = StreamDeleteShardRequest::decode(value.as_ref())?;
+        return self
+            .stream_storage
+            .delete_shard(&req.cluster_name, &req.shard_name);
+    }
+
+    pub fn stream_write(&self, value: Vec<u8>) -> Result<Vec<u8>, CommonError> {
+        let req: StreamWriteRequest = StreamWriteRequest::decode(value.as_ref())?;
+        let offsets = self.stream_storage.write(
+            &req.cluster_name,
+            &req.shard_name,
+            req.records,
+            req.max_shard_record_num,
+            req.max_shard_size,
+        )?;
+        return Ok(StreamWriteReply::encode_to_vec(&StreamWriteReply {
+            offsets,
+        }));
+    }
+
+    pub fn stream_commit_offset(&self, value: Vec<u8>) -> Result<(), CommonError> {
+        let req: StreamCommitOffsetRequest = StreamCommitOffsetRequest::decode(value.as_ref())?;
+        return self.stream_storage.commit_offset(
+            &req.cluster_name,
+            &req.shard_name,
+            &req.group_id,
+            req.offset,
+        );
+    }
+
+    pub fn stream_trim(&self, value: Vec<u8>) -> Result<(), CommonError> {
+        let req: StreamTrimRequest = StreamTrimRequest::decode(value.as_ref())?;
+        if req.compact {
+            self.stream_storage
+                .compact(&req.cluster_name, &req.shard_name)?;
+        }
+        if req.end_offset > 0 {
+            self.stream_storage
+                .trim(&req.cluster_name, &req.shard_name, req.end_offset)?;
+        }
+        return Ok(());
+    }
+
+    pub fn stream_reset_group_offset(&self, value: Vec<u8>) -> Result<(), CommonError> {
+        let req: StreamResetGroupOffsetRequest =
+            StreamResetGroupOffsetRequest::decode(value.as_ref())?;
+        return self.stream_storage.reset_group_offset(
+            &req.cluster_name,
+            &req.shard_name,
+            &req.group_id,
+            req.offset,
+        );
     }
 }
@@ -0,0 +1,94 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::storage::{
+    placement::lock::{LockKind, LockStorage},
+    rocksdb::RocksDBEngine,
+};
+use common_base::error::common::CommonError;
+use prost::Message as _;
+use protocol::placement_center::generate::placement::{
+    AcquireLockReply, AcquireLockRequest, CampaignReply, CampaignRequest, LockHolder,
+    ReleaseLockRequest, ResignRequest,
+};
+use std::sync::Arc;
+
+pub struct DataRouteLock {
+    lock_storage: LockStorage,
+}
+
+impl DataRouteLock {
+    pub fn new(rocksdb_engine_handler: Arc<RocksDBEngine>) -> Self {
+        let lock_storage = LockStorage::new(rocksdb_engine_handler);
+        return DataRouteLock { lock_storage };
+    }
+
+    pub fn acquire(&self, value: Vec<u8>) -> Result<Vec<u8>, CommonError> {
+        let req: AcquireLockRequest = AcquireLockRequest::decode(value.as_ref())?;
+        let holder = req.holder.unwrap_or_default();
+        let (acquired, lock) = self.lock_storage.acquire(
+            LockKind::Lock,
+            &req.lock_name,
+            &holder,
+            &"".to_string(),
+            req.ttl,
+            req.acquire_time,
+        )?;
+        return Ok(AcquireLockReply::encode_to_vec(&AcquireLockReply {
+            acquired,
+            fencing_token: lock.fencing_token,
+            holder: Some(lock.holder()),
+        }));
+    }
+
+    pub fn release(&self, value: Vec<u8>) -> Result<(), CommonError> {
+        let req: ReleaseLockRequest = ReleaseLockRequest::decode(value.as_ref())?;
+        self.lock_storage.release(
+            LockKind::Lock,
+            &req.lock_name,
+            &req.holder.unwrap_or_default(),
+            req.fencing_token,
+        )?;
+        return Ok(());
+    }
+
+    pub fn campaign(&self, value: Vec<u8>) -> Result<Vec<u8>, CommonError> {
+        let req: CampaignRequest = CampaignRequest::decode(value.as_ref())?;
+        let candidate: LockHolder = req.candidate.unwrap_or_default();
+        let (elected, lock) = self.lock_storage.acquire(
+            LockKind::Election,
+            &req.election_name,
+            &candidate,
+            &req.value,
+            req.ttl,
+            req.acquire_time,
+        )?;
+        return Ok(CampaignReply::encode_to_vec(&CampaignReply {
+            elected,
+            term: lock.fencing_token,
+            leader: Some(lock.holder()),
+            value: lock.value,
+        }));
+    }
+
+    pub fn resign(&self, value: Vec<u8>) -> Result<(), CommonError> {
+        let req: ResignRequest = ResignRequest::decode(value.as_ref())?;
+        self.lock_storage.release(
+            LockKind::Election,
+            &req.election_name,
+            &req.leader.unwrap_or_default(),
+            req.term,
+        )?;
+        return Ok(());
+    }
+}
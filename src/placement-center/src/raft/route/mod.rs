@@ -14,16 +14,18 @@
 pub mod cluster;
 pub mod journal;
 pub mod kv;
+pub mod lock;
 pub mod mqtt;
 
 use super::{
     apply::{StorageData, StorageDataType},
     route::{
-        cluster::DataRouteCluster, journal::DataRouteJournal, kv::DataRouteKv, mqtt::DataRouteMQTT,
+        cluster::DataRouteCluster, journal::DataRouteJournal, kv::DataRouteKv, lock::DataRouteLock,
+        mqtt::DataRouteMQTT,
     },
 };
 use crate::{
-    cache::{journal::JournalCacheManager, placement::PlacementCacheManager},
+    cache::{journal::JournalCacheManager, kv::KvCacheManager, placement::PlacementCacheManager},
     storage::rocksdb::RocksDBEngine,
 };
 use bincode::deserialize;
@@ -35,6 +37,11 @@ pub struct DataRoute {
     route_mqtt: DataRouteMQTT,
     route_journal: DataRouteJournal,
     route_cluster: DataRouteCluster,
+    route_lock: DataRouteLock,
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+    cluster_cache: Arc<PlacementCacheManager>,
+    engine_cache: Arc<JournalCacheManager>,
+    kv_cache: Arc<KvCacheManager>,
 }
 
 impl DataRoute {
@@ -42,102 +49,170 @@ impl DataRoute {
         rocksdb_engine_handler: Arc<RocksDBEngine>,
         cluster_cache: Arc<PlacementCacheManager>,
         engine_cache: Arc<JournalCacheManager>,
+        kv_cache: Arc<KvCacheManager>,
     ) -> DataRoute {
-        let route_kv = DataRouteKv::new(rocksdb_engine_handler.clone());
+        let route_kv = DataRouteKv::new(rocksdb_engine_handler.clone(), kv_cache.clone());
         let route_mqtt = DataRouteMQTT::new(rocksdb_engine_handler.clone());
         let route_cluster =
             DataRouteCluster::new(rocksdb_engine_handler.clone(), cluster_cache.clone());
-        let route_journal = DataRouteJournal::new(
-            rocksdb_engine_handler.clone(),
-            engine_cache.clone(),
-            cluster_cache.clone(),
-        );
+        let route_journal =
+            DataRouteJournal::new(rocksdb_engine_handler.clone(), engine_cache.clone());
+        let route_lock = DataRouteLock::new(rocksdb_engine_handler.clone());
         return DataRoute {
             route_kv,
             route_mqtt,
             route_journal,
             route_cluster,
+            route_lock,
+            rocksdb_engine_handler,
+            cluster_cache,
+            engine_cache,
+            kv_cache,
         };
     }
 
+    // Rebuilds the caches kept in sync by the routes, after the data was replaced by an
+    // installed snapshot.
+    pub fn reload_cache(&self) {
+        self.cluster_cache.clear();
+        self.cluster_cache
+            .load_cache(self.rocksdb_engine_handler.clone());
+        self.engine_cache.clear();
+        self.engine_cache
+            .load_cache(self.rocksdb_engine_handler.clone());
+        self.kv_cache.clear();
+    }
+
     //Receive write operations performed by the Raft state machine and write subsequent service data after Raft state machine synchronization is complete.
-    pub fn route(&self, data: Vec<u8>) -> Result<(), CommonError> {
+    pub fn route(&self, data: Vec<u8>) -> Result<Vec<u8>, CommonError> {
         let storage_data: StorageData = deserialize(data.as_ref()).unwrap();
         match storage_data.data_type {
             StorageDataType::ClusterRegisterNode => {
-                return self.route_cluster.add_node(storage_data.value);
+                self.route_cluster.add_node(storage_data.value)?;
             }
             StorageDataType::ClusterUngisterNode => {
-                return self.route_cluster.delete_node(storage_data.value);
+                self.route_cluster.delete_node(storage_data.value)?;
             }
             StorageDataType::ClusterSetResourceConfig => {
-                return self.route_cluster.set_resource_config(storage_data.value);
+                self.route_cluster.set_resource_config(storage_data.value)?;
             }
             StorageDataType::ClusterDeleteResourceConfig => {
-                return self
-                    .route_cluster
-                    .delete_resource_config(storage_data.value);
+                self.route_cluster
+                    .delete_resource_config(storage_data.value)?;
             }
             StorageDataType::ClusterSetIdempotentData => {
-                return self.route_cluster.set_idempotent_data(storage_data.value);
+                self.route_cluster.set_idempotent_data(storage_data.value)?;
             }
             StorageDataType::ClusterDeleteIdempotentData => {
-                return self
-                    .route_cluster
-                    .delete_idempotent_data(storage_data.value);
+                self.route_cluster
+                    .delete_idempotent_data(storage_data.value)?;
             }
             StorageDataType::MQTTCreateAcl => {
-                return self.route_cluster.create_acl(storage_data.value);
+                self.route_cluster.create_acl(storage_data.value)?;
             }
             StorageDataType::MQTTDeleteAcl => {
-                return self.route_cluster.delete_acl(storage_data.value);
+                self.route_cluster.delete_acl(storage_data.value)?;
+            }
+            StorageDataType::MQTTCreateBlacklist => {
+                self.route_cluster.create_blacklist(storage_data.value)?;
+            }
+            StorageDataType::MQTTDeleteBlacklist => {
+                self.route_cluster.delete_blacklist(storage_data.value)?;
             }
 
             StorageDataType::JournalCreateShard => {
-                return self.route_journal.create_shard(storage_data.value);
+                self.route_journal.create_shard(storage_data.value)?;
             }
             StorageDataType::JournalDeleteShard => {
-                return self.route_journal.delete_shard(storage_data.value);
+                self.route_journal.delete_shard(storage_data.value)?;
             }
             StorageDataType::JournalCreateSegment => {
-                return self.route_journal.create_segment(storage_data.value);
+                self.route_journal.create_segment(storage_data.value)?;
             }
             StorageDataType::JournalDeleteSegment => {
-                return self.route_journal.delete_segment(storage_data.value);
+                self.route_journal.delete_segment(storage_data.value)?;
+            }
+            StorageDataType::JournalRollSegment => {
+                self.route_journal.roll_segment(storage_data.value)?;
+            }
+            StorageDataType::JournalUpdateSegmentStatus => {
+                self.route_journal
+                    .update_segment_status(storage_data.value)?;
+            }
+            StorageDataType::JournalUpdateSegmentLeader => {
+                self.route_journal
+                    .update_segment_leader(storage_data.value)?;
             }
             StorageDataType::KvSet => {
                 return self.route_kv.set(storage_data.value);
             }
             StorageDataType::KvDelete => {
-                return self.route_kv.delete(storage_data.value);
+                self.route_kv.delete(storage_data.value)?;
+            }
+            StorageDataType::StreamCreateShard => {
+                self.route_kv.stream_create_shard(storage_data.value)?;
+            }
+            StorageDataType::StreamDeleteShard => {
+                self.route_kv.stream_delete_shard(storage_data.value)?;
+            }
+            StorageDataType::StreamWrite => {
+                return self.route_kv.stream_write(storage_data.value);
+            }
+            StorageDataType::StreamCommitOffset => {
+                self.route_kv.stream_commit_offset(storage_data.value)?;
+            }
+            StorageDataType::StreamTrim => {
+                self.route_kv.stream_trim(storage_data.value)?;
+            }
+            StorageDataType::StreamResetGroupOffset => {
+                self.route_kv
+                    .stream_reset_group_offset(storage_data.value)?;
             }
             StorageDataType::MQTTCreateUser => {
-                return self.route_mqtt.create_user(storage_data.value);
+                self.route_mqtt.create_user(storage_data.value)?;
             }
             StorageDataType::MQTTDeleteUser => {
-                return self.route_mqtt.delete_user(storage_data.value);
+                self.route_mqtt.delete_user(storage_data.value)?;
             }
             StorageDataType::MQTTCreateTopic => {
-                return self.route_mqtt.create_topic(storage_data.value);
+                self.route_mqtt.create_topic(storage_data.value)?;
             }
             StorageDataType::MQTTDeleteTopic => {
-                return self.route_mqtt.delete_topic(storage_data.value);
+                self.route_mqtt.delete_topic(storage_data.value)?;
             }
             StorageDataType::MQTTCreateSession => {
-                return self.route_mqtt.create_session(storage_data.value);
+                self.route_mqtt.create_session(storage_data.value)?;
             }
             StorageDataType::MQTTDeleteSession => {
-                return self.route_mqtt.delete_session(storage_data.value);
+                self.route_mqtt.delete_session(storage_data.value)?;
             }
             StorageDataType::MQTTUpdateSession => {
-                return self.route_mqtt.update_session(storage_data.value);
+                self.route_mqtt.update_session(storage_data.value)?;
             }
             StorageDataType::MQTTSetTopicRetainMessage => {
-                return self.route_mqtt.set_topic_retain_message(storage_data.value);
+                self.route_mqtt
+                    .set_topic_retain_message(storage_data.value)?;
             }
             StorageDataType::MQTTSaveLastWillMessage => {
-                return self.route_mqtt.save_last_will_message(storage_data.value);
+                self.route_mqtt.save_last_will_message(storage_data.value)?;
+            }
+            StorageDataType::LockAcquire => {
+                return self.route_lock.acquire(storage_data.value);
+            }
+            StorageDataType::LockRelease => {
+                self.route_lock.release(storage_data.value)?;
+            }
+            StorageDataType::ElectionCampaign => {
+                return self.route_lock.campaign(storage_data.value);
+            }
+            StorageDataType::ElectionResign => {
+                self.route_lock.resign(storage_data.value)?;
+            }
+            StorageDataType::MetadataImport => {
+                self.route_cluster.import_metadata(storage_data.value)?;
+                self.reload_cache();
             }
         }
+        return Ok(Vec::new());
     }
 }
@@ -100,6 +100,22 @@ impl DataRouteMQTT {
 
         let mut session = result.unwrap();
 
+        // The release of a session of a removed broker is dropped once the client
+        // connected to another broker
+        if req.expected_broker_id > 0 && session.broker_id != Some(req.expected_broker_id) {
+            return Ok(());
+        }
+        if req.expected_connection_id > 0
+            && session.connection_id != Some(req.expected_connection_id)
+        {
+            return Ok(());
+        }
+        if req.expected_reconnect_time > 0
+            && session.reconnect_time != Some(req.expected_reconnect_time)
+        {
+            return Ok(());
+        }
+
         if req.connection_id > 0 {
             session.update_connnction_id(Some(req.connection_id));
         } else {
@@ -131,3 +147,63 @@ impl DataRouteMQTT {
         return storage.delete(&req.cluster_name, &req.client_id);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::DataRouteMQTT;
+    use crate::storage::{mqtt::session::MQTTSessionStorage, rocksdb::RocksDBEngine};
+    use common_base::{config::placement_center::PlacementCenterConfig, tools::unique_id};
+    use metadata_struct::mqtt::session::MQTTSession;
+    use prost::Message as _;
+    use protocol::placement_center::generate::mqtt::UpdateSessionRequest;
+    use std::{fs::remove_dir_all, sync::Arc};
+
+    #[test]
+    fn release_session_after_reconnect() {
+        let mut config = PlacementCenterConfig::default();
+        config.data_path = format!("/tmp/tmp_test/{}", unique_id());
+        config.rocksdb.max_open_files = Some(100);
+        let rocksdb_engine_handler = Arc::new(RocksDBEngine::new(&config));
+        let route = DataRouteMQTT::new(rocksdb_engine_handler.clone());
+        let storage = MQTTSessionStorage::new(rocksdb_engine_handler);
+
+        let cluster_name = "test_cluster".to_string();
+        let client_id = "c1".to_string();
+        let mut session = MQTTSession::default();
+        session.client_id = client_id.clone();
+        session.broker_id = Some(1);
+        session.connection_id = Some(2);
+        session.reconnect_time = Some(100);
+        storage.save(&cluster_name, &client_id, session).unwrap();
+
+        // The client connected again to broker 1 after it restarted
+        let release = UpdateSessionRequest {
+            cluster_name: cluster_name.clone(),
+            client_id: client_id.clone(),
+            distinct_time: 200,
+            expected_broker_id: 1,
+            expected_connection_id: 2,
+            expected_reconnect_time: 90,
+            ..Default::default()
+        };
+        route
+            .update_session(UpdateSessionRequest::encode_to_vec(&release))
+            .unwrap();
+        let session = storage.get(&cluster_name, &client_id).unwrap().unwrap();
+        assert_eq!(session.connection_id, Some(2));
+        assert_eq!(session.broker_id, Some(1));
+
+        let release = UpdateSessionRequest {
+            expected_reconnect_time: 100,
+            ..release
+        };
+        route
+            .update_session(UpdateSessionRequest::encode_to_vec(&release))
+            .unwrap();
+        let session = storage.get(&cluster_name, &client_id).unwrap().unwrap();
+        assert_eq!(session.connection_id, None);
+        assert_eq!(session.broker_id, None);
+
+        remove_dir_all(config.data_path).unwrap();
+    }
+}
@@ -12,6 +12,7 @@
 // limitations under the License.
 
 use crate::storage::placement::raft::RaftMachineStorage;
+use common_base::error::common::CommonError;
 use log::info;
 use raft::eraftpb::HardState;
 use raft::prelude::ConfState;
@@ -70,10 +71,9 @@ impl RaftRocksDBStorage {
 }
 
 impl RaftRocksDBStorage {
-    pub fn apply_snapshot(&mut self, snapshot: Snapshot) -> RaftResult<()> {
+    pub fn apply_snapshot(&mut self, snapshot: Snapshot) -> Result<(), CommonError> {
         let mut store = self.core.write().unwrap();
-        let _ = store.apply_snapshot(snapshot);
-        Ok(())
+        return store.apply_snapshot(snapshot);
     }
 
     pub fn append(&mut self, entrys: &Vec<Entry>) -> RaftResult<()> {
@@ -106,10 +106,18 @@ impl RaftRocksDBStorage {
         return Ok(());
     }
 
-    pub fn create_snapshot(&mut self) -> RaftResult<()> {
+    pub fn create_snapshot(
+        &mut self,
+        index: u64,
+        term: u64,
+        log_tail_entries: u64,
+    ) -> Result<String, CommonError> {
         let mut store = self.core.write().unwrap();
-        let _ = store.create_snapshot();
-        return Ok(());
+        return store.create_snapshot(index, term, log_tail_entries);
+    }
+
+    pub fn snapshot_requested(&self) -> bool {
+        return self.read_lock().snapshot_requested;
     }
 }
 
@@ -159,7 +167,7 @@ impl RaftStorage for RaftRocksDBStorage {
         }
 
         let mut entry_list: Vec<Entry> = Vec::new();
-        for idx in low..=high {
+        for idx in low..high {
             let sret = core.entry_by_idx(idx);
             if sret == None {
                 continue;
@@ -180,7 +188,7 @@ impl RaftStorage for RaftRocksDBStorage {
         let core = self.read_lock();
 
         if idx == core.snapshot_metadata.index {
-            return Ok(core.snapshot_metadata.index);
+            return Ok(core.snapshot_metadata.term);
         }
 
         if idx < core.first_index() {
@@ -226,14 +234,17 @@ impl RaftStorage for RaftRocksDBStorage {
     fn snapshot(&self, request_index: u64, to: u64) -> RaftResult<Snapshot> {
         info!("Node {} requests snapshot data", to);
         let mut core = self.write_lock();
-        if core.trigger_snap_unavailable {
+        let snap = core.snapshot();
+        let index = snap.get_metadata().index;
+        // Either no snapshot was taken yet, the latest one is too old for the peer, or its
+        // file is still being built. The next applied entry takes a new snapshot when asked.
+        if index == 0 || index < request_index {
+            core.snapshot_requested = true;
+            return Err(Error::Store(StorageError::SnapshotTemporarilyUnavailable));
+        }
+        if !core.snapshot_ready(&snap) {
             return Err(Error::Store(StorageError::SnapshotTemporarilyUnavailable));
-        } else {
-            let mut snap = core.snapshot();
-            if snap.get_metadata().index < request_index {
-                snap.mut_metadata().index = request_index;
-            }
-            Ok(snap)
         }
+        Ok(snap)
     }
 }
@@ -12,6 +12,7 @@
 // limitations under the License.
 
 
+pub mod read_index;
 pub mod service_journal;
 pub mod service_kv;
 pub mod service_placement;
@@ -0,0 +1,84 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::raft::{apply::RaftMachineApply, metadata::RaftGroupMetadata};
+use clients::placement::LEADER_ADDR_METADATA_KEY;
+use common_base::error::placement_center::PlacementCenterError;
+use std::sync::{Arc, RwLock};
+use tonic::{metadata::MetadataMap, Code, Status};
+
+// Makes the read that follows linearizable: the leader confirms with a quorum that it still
+// leads, then waits until it applied everything that was committed when the read arrived.
+// Other nodes reject the read with the address of the leader, which clients retry on.
+// Stale reads are served from the local data right away.
+pub async fn wait_read_index(
+    placement_cache: &Arc<RwLock<RaftGroupMetadata>>,
+    placement_center_storage: &Arc<RaftMachineApply>,
+    stale_read: bool,
+) -> Result<(), Status> {
+    if stale_read {
+        return Ok(());
+    }
+
+    let (is_leader, node_id, leader_addr) = {
+        let cache = placement_cache.read().unwrap();
+        (cache.is_leader(), cache.local.node_id, cache.leader_addr())
+    };
+    if !is_leader {
+        return Err(not_leader_status(node_id, leader_addr));
+    }
+
+    match placement_center_storage.read_index().await {
+        Ok(()) => return Ok(()),
+        Err(e) => return Err(Status::unavailable(e.to_string())),
+    }
+}
+
+pub fn not_leader_status(node_id: u64, leader_addr: String) -> Status {
+    let message = PlacementCenterError::NotLeader(node_id, leader_addr.clone()).to_string();
+    // Without a leader the client can only retry later
+    if leader_addr.is_empty() {
+        return Status::unavailable(message);
+    }
+    let mut metadata = MetadataMap::new();
+    if let Ok(value) = leader_addr.parse() {
+        metadata.insert(LEADER_ADDR_METADATA_KEY, value);
+    }
+    return Status::with_metadata(Code::FailedPrecondition, message, metadata);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::not_leader_status;
+    use clients::placement::LEADER_ADDR_METADATA_KEY;
+    use tonic::Code;
+
+    #[test]
+    fn not_leader_status_carries_the_leader() {
+        let status = not_leader_status(2, "127.0.0.1:1228".to_string());
+        assert_eq!(status.code(), Code::FailedPrecondition);
+        assert_eq!(
+            status
+                .metadata()
+                .get(LEADER_ADDR_METADATA_KEY)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "127.0.0.1:1228"
+        );
+
+        let status = not_leader_status(2, "".to_string());
+        assert_eq!(status.code(), Code::Unavailable);
+        assert!(status.metadata().get(LEADER_ADDR_METADATA_KEY).is_none());
+    }
+}
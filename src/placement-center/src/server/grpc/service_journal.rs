@@ -13,18 +13,27 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use crate::cache::journal::{JournalCacheManager, ShardProgress};
+use crate::cache::placement::PlacementCacheManager;
+use crate::controller::journal::segment_lifecycle::propose_create_segment;
 use crate::raft::apply::{RaftMachineApply, StorageData, StorageDataType};
 use crate::raft::metadata::RaftGroupMetadata;
+use crate::server::grpc::read_index::wait_read_index;
+use crate::storage::journal::shard::ShardInfo;
 use clients::{
-    placement::journal::call::{create_segment, create_shard, delete_segment, delete_shard},
+    placement::journal::call::{
+        create_segment, create_shard, delete_segment, delete_shard, report_shard_status,
+    },
     poll::ClientPool,
 };
+use common_base::tools::now_second;
 use prost::Message;
 use protocol::placement_center::generate::{
     common::CommonReply,
     journal::{
         engine_service_server::EngineService, CreateSegmentRequest, CreateShardRequest,
-        DeleteSegmentRequest, DeleteShardRequest, GetShardReply, GetShardRequest,
+        DeleteSegmentRequest, DeleteShardRequest, GetShardReply, GetShardRequest, ListShardReply,
+        ListShardRequest, ReportShardStatusRequest,
     },
 };
 use std::sync::{Arc, RwLock};
@@ -34,6 +43,8 @@ pub struct GrpcEngineService {
     placement_center_storage: Arc<RaftMachineApply>,
     placement_cache: Arc<RwLock<RaftGroupMetadata>>,
     client_poll: Arc<ClientPool>,
+    engine_cache: Arc<JournalCacheManager>,
+    cluster_cache: Arc<PlacementCacheManager>,
 }
 
 impl GrpcEngineService {
@@ -41,17 +52,82 @@ impl GrpcEngineService {
         placement_center_storage: Arc<RaftMachineApply>,
         placement_cache: Arc<RwLock<RaftGroupMetadata>>,
         client_poll: Arc<ClientPool>,
+        engine_cache: Arc<JournalCacheManager>,
+        cluster_cache: Arc<PlacementCacheManager>,
     ) -> Self {
         GrpcEngineService {
             placement_center_storage,
             placement_cache,
             client_poll,
+            engine_cache,
+            cluster_cache,
         }
     }
 
     fn rewrite_leader(&self) -> bool {
         return !self.placement_cache.read().unwrap().is_leader();
     }
+
+    async fn propose_create_segment(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+    ) -> Result<(), Status> {
+        match propose_create_segment(
+            &self.placement_center_storage,
+            &self.cluster_cache,
+            &self.engine_cache,
+            cluster_name,
+            shard_name,
+        )
+        .await
+        {
+            Ok(()) => return Ok(()),
+            Err(e) => return Err(Status::cancelled(e.to_string())),
+        }
+    }
+
+    // The replicas and the leader of a shard are those of the segment being written.
+    fn build_shard_reply(&self, shard: ShardInfo) -> Result<GetShardReply, Status> {
+        let mut result = GetShardReply {
+            cluster_name: shard.cluster_name.clone(),
+            shard_id: shard.shard_uid,
+            shard_name: shard.shard_name.clone(),
+            replica: shard.replica,
+            cleanup_policy: shard.cleanup_policy.clone(),
+            tombstone_retention_ms: shard.tombstone_retention_ms,
+            compression: shard.compression.clone(),
+            compression_min_size: shard.compression_min_size,
+            ..Default::default()
+        };
+        if let Some(segment) = self
+            .engine_cache
+            .get_active_segment(&shard.cluster_name, &shard.shard_name)
+        {
+            result.replicas = match serde_json::to_vec(&segment.replicas) {
+                Ok(data) => data,
+                Err(e) => return Err(Status::internal(e.to_string())),
+            };
+            result.replica_nodes = segment.replicas.iter().map(|rep| rep.node_id).collect();
+            result.status = format!("{:?}", segment.status);
+            if let Some(replica) = segment.leader() {
+                result.leader_id = replica.node_id;
+                if let Some(node) = self
+                    .cluster_cache
+                    .get_node_addr(&shard.cluster_name, replica.node_id)
+                {
+                    result.leader_addr = node.node_inner_addr;
+                }
+            }
+        }
+        if let Some(segment) = self
+            .engine_cache
+            .get_next_segment(&shard.cluster_name, &shard.shard_name)
+        {
+            result.next_replica_nodes = segment.replicas.iter().map(|rep| rep.node_id).collect();
+        }
+        return Ok(result);
+    }
 }
 
 #[tonic::async_trait]
@@ -71,22 +147,36 @@ impl EngineService for GrpcEngineService {
         }
 
         // Params validate
+        if !["", "delete", "compact"].contains(&req.cleanup_policy.as_str()) {
+            return Err(Status::invalid_argument(format!(
+                "unknown cleanup policy {}, expected delete or compact",
+                req.cleanup_policy
+            )));
+        }
+        if !["", "none", "lz4", "zstd", "snappy"].contains(&req.compression.as_str()) {
+            return Err(Status::invalid_argument(format!(
+                "unknown compression {}, expected none, lz4, zstd or snappy",
+                req.compression
+            )));
+        }
 
         // Raft state machine is used to store Node data
         let data = StorageData::new(
             StorageDataType::JournalCreateShard,
             CreateShardRequest::encode_to_vec(&req),
         );
-        match self
+        if let Err(e) = self
             .placement_center_storage
             .apply_propose_message(data, "create_shard".to_string())
             .await
         {
-            Ok(_) => return Ok(Response::new(CommonReply::default())),
-            Err(e) => {
-                return Err(Status::cancelled(e.to_string()));
-            }
+            return Err(Status::cancelled(e.to_string()));
         }
+
+        // The first segment of the shard.
+        self.propose_create_segment(&req.cluster_name, &req.shard_name)
+            .await?;
+        return Ok(Response::new(CommonReply::default()));
     }
 
     async fn delete_shard(
@@ -123,11 +213,50 @@ impl EngineService for GrpcEngineService {
 
     async fn get_shard(
         &self,
-        _: Request<GetShardRequest>,
+        request: Request<GetShardRequest>,
     ) -> Result<Response<GetShardReply>, Status> {
-        let result = GetShardReply::default();
+        let req = request.into_inner();
+        wait_read_index(
+            &self.placement_cache,
+            &self.placement_center_storage,
+            req.stale_read,
+        )
+        .await?;
+        // An unknown shard is answered with an empty reply instead of an error, callers
+        // tell it apart by the empty shard_id.
+        let shard = match self
+            .engine_cache
+            .get_shard(req.cluster_name.clone(), req.shard_name.clone())
+        {
+            Some(shard) => shard,
+            None => return Ok(Response::new(GetShardReply::default())),
+        };
+
+        return Ok(Response::new(self.build_shard_reply(shard)?));
+    }
 
-        return Ok(Response::new(result));
+    async fn list_shard(
+        &self,
+        request: Request<ListShardRequest>,
+    ) -> Result<Response<ListShardReply>, Status> {
+        let req = request.into_inner();
+        wait_read_index(
+            &self.placement_cache,
+            &self.placement_center_storage,
+            req.stale_read,
+        )
+        .await?;
+        let mut shards = Vec::new();
+        for shard in self.engine_cache.list_shard(&req.cluster_name) {
+            let reply = self.build_shard_reply(shard)?;
+            if req.node_id == 0
+                || reply.replica_nodes.contains(&req.node_id)
+                || reply.next_replica_nodes.contains(&req.node_id)
+            {
+                shards.push(reply);
+            }
+        }
+        return Ok(Response::new(ListShardReply { shards }));
     }
 
     async fn create_segment(
@@ -146,20 +275,9 @@ impl EngineService for GrpcEngineService {
         // Params validate
 
         // Raft state machine is used to store Node data
-        let data = StorageData::new(
-            StorageDataType::JournalCreateSegment,
-            CreateSegmentRequest::encode_to_vec(&req),
-        );
-        match self
-            .placement_center_storage
-            .apply_propose_message(data, "create_segment".to_string())
-            .await
-        {
-            Ok(_) => return Ok(Response::new(CommonReply::default())),
-            Err(e) => {
-                return Err(Status::cancelled(e.to_string()));
-            }
-        }
+        self.propose_create_segment(&req.cluster_name, &req.shard_name)
+            .await?;
+        return Ok(Response::new(CommonReply::default()));
     }
 
     async fn delete_segment(
@@ -194,4 +312,35 @@ impl EngineService for GrpcEngineService {
             }
         }
     }
+
+    async fn report_shard_status(
+        &self,
+        request: Request<ReportShardStatusRequest>,
+    ) -> Result<Response<CommonReply>, Status> {
+        let req = request.into_inner();
+
+        // Segments are rolled and sealed by the leader, so it keeps the progress.
+        if self.rewrite_leader() {
+            let leader_addr = self.placement_cache.read().unwrap().leader_addr();
+            match report_shard_status(self.client_poll.clone(), vec![leader_addr], req).await {
+                Ok(resp) => return Ok(Response::new(resp)),
+                Err(e) => return Err(Status::cancelled(e.to_string())),
+            }
+        }
+
+        for shard in req.shards {
+            self.engine_cache.report_shard_progress(
+                &req.cluster_name,
+                &shard.shard_name,
+                req.node_id,
+                ShardProgress {
+                    next_offset: shard.next_offset,
+                    high_watermark: shard.high_watermark,
+                    log_bytes: shard.log_bytes,
+                    report_time: now_second(),
+                },
+            );
+        }
+        return Ok(Response::new(CommonReply::default()));
+    }
 }
@@ -14,40 +14,144 @@
  * limitations under the License.
  */
 use crate::{
-    raft::apply::{RaftMachineApply, StorageData, StorageDataType},
-    storage::{placement::kv::KvStorage, rocksdb::RocksDBEngine},
+    cache::kv::KvCacheManager,
+    raft::{
+        apply::{RaftMachineApply, StorageData, StorageDataType},
+        metadata::RaftGroupMetadata,
+    },
+    server::grpc::read_index::{not_leader_status, wait_read_index},
+    storage::{
+        keys::key_kv_lease,
+        placement::{
+            kv::{is_lease_key, KvEvent, KvStorage},
+            stream::StreamStorage,
+        },
+        rocksdb::RocksDBEngine,
+    },
+};
+use common_base::{
+    config::placement_center::placement_center_conf,
+    error::{common::CommonError, placement_center::PlacementCenterError},
+    tools::now_second,
 };
-use common_base::error::common::CommonError;
+use futures::Stream;
 use prost::Message;
 use protocol::placement_center::generate::{
     common::CommonReply,
     kv::{
-        kv_service_server::KvService, DeleteRequest, ExistsReply, ExistsRequest, GetReply,
-        GetRequest, SetRequest,
+        kv_service_server::KvService, CompareAndSwapReply, CompareAndSwapRequest, DeleteRequest,
+        ExistsReply, ExistsRequest, GetReply, GetRequest, LeaseGrantReply, LeaseGrantRequest,
+        LeaseKeepAliveReply, LeaseKeepAliveRequest, LeaseRevokeRequest, ListReply, ListRequest,
+        SetRequest, StreamCommitOffsetRequest, StreamCreateShardRequest, StreamDeleteShardRequest,
+        StreamGetShardReply, StreamGetShardRequest, StreamGroupOffset, StreamListGroupReply,
+        StreamListGroupRequest, StreamOffsetByTimestampReply, StreamOffsetByTimestampRequest,
+        StreamReadByKeyRequest, StreamReadByOffsetReply, StreamReadByOffsetRequest,
+        StreamReadByTimestampRequest, StreamReadReply, StreamReadRequest,
+        StreamResetGroupOffsetRequest, StreamWriteReply, StreamWriteRequest, WatchReply,
+        WatchRequest,
     },
 };
-use std::sync::Arc;
+use std::{
+    pin::Pin,
+    sync::{Arc, RwLock},
+};
+use tokio::{
+    select,
+    sync::{broadcast::error::RecvError, mpsc},
+};
 use tonic::{Request, Response, Status};
 
+const DEFAULT_LIST_LIMIT: usize = 100;
+const MAX_LIST_LIMIT: usize = 1000;
+const WATCH_BATCH_SIZE: usize = 100;
+const LEASE_GRANT_ATTEMPTS: u64 = 10;
+
 pub struct GrpcKvService {
     placement_center_storage: Arc<RaftMachineApply>,
+    placement_cache: Arc<RwLock<RaftGroupMetadata>>,
+    kv_cache: Arc<KvCacheManager>,
     rocksdb_engine_handler: Arc<RocksDBEngine>,
 }
 
 impl GrpcKvService {
     pub fn new(
         placement_center_storage: Arc<RaftMachineApply>,
+        placement_cache: Arc<RwLock<RaftGroupMetadata>>,
+        kv_cache: Arc<KvCacheManager>,
         rocksdb_engine_handler: Arc<RocksDBEngine>,
     ) -> Self {
         GrpcKvService {
             placement_center_storage,
+            placement_cache,
+            kv_cache,
             rocksdb_engine_handler,
         }
     }
+
+    fn check_key(&self, key: &str) -> Result<(), Status> {
+        if is_lease_key(key) {
+            return Err(Status::cancelled(
+                PlacementCenterError::KvKeyReserved(key.to_string()).to_string(),
+            ));
+        }
+        return Ok(());
+    }
+
+    fn check_lease(&self, lease_id: u64) -> Result<(), Status> {
+        if lease_id == 0 {
+            return Ok(());
+        }
+        let kv_storage = KvStorage::new(self.rocksdb_engine_handler.clone());
+        match kv_storage.lease_get(lease_id) {
+            Ok(Some(_)) => return Ok(()),
+            Ok(None) => {
+                return Err(Status::cancelled(
+                    PlacementCenterError::KvLeaseNotFound(lease_id).to_string(),
+                ))
+            }
+            Err(e) => return Err(Status::cancelled(e.to_string())),
+        }
+    }
+
+    async fn propose_set(&self, req: SetRequest) -> Result<CompareAndSwapReply, Status> {
+        self.check_lease(req.lease_id)?;
+        let data = StorageData::new(StorageDataType::KvSet, SetRequest::encode_to_vec(&req));
+        match self
+            .placement_center_storage
+            .apply_propose_message_with_reply(data, "set".to_string())
+            .await
+        {
+            Ok(data) => match CompareAndSwapReply::decode(data.as_ref()) {
+                Ok(reply) => return Ok(reply),
+                Err(e) => return Err(Status::cancelled(e.to_string())),
+            },
+            Err(e) => {
+                return Err(Status::cancelled(e.to_string()));
+            }
+        }
+    }
+}
+
+async fn send_watch_events(
+    reply_send: &mpsc::Sender<Result<WatchReply, Status>>,
+    req: &WatchRequest,
+    events: &[KvEvent],
+) -> bool {
+    let events: Vec<_> = events
+        .iter()
+        .filter(|event| event.matches(&req.key, req.prefix))
+        .map(|event| event.to_watch_event())
+        .collect();
+    if events.is_empty() {
+        return true;
+    }
+    return reply_send.send(Ok(WatchReply { events })).await.is_ok();
 }
 
 #[tonic::async_trait]
 impl KvService for GrpcKvService {
+    type WatchStream = Pin<Box<dyn Stream<Item = Result<WatchReply, Status>> + Send>>;
+
     async fn set(&self, request: Request<SetRequest>) -> Result<Response<CommonReply>, Status> {
         let req = request.into_inner();
 
@@ -56,19 +160,18 @@ impl KvService for GrpcKvService {
                 CommonError::ParameterCannotBeNull("key or value".to_string()).to_string(),
             ));
         }
+        self.check_key(&req.key)?;
 
         // Raft state machine is used to store Node data
-        let data = StorageData::new(StorageDataType::KvSet, SetRequest::encode_to_vec(&req));
-        match self
-            .placement_center_storage
-            .apply_propose_message(data, "set".to_string())
-            .await
-        {
-            Ok(_) => return Ok(Response::new(CommonReply::default())),
-            Err(e) => {
-                return Err(Status::cancelled(e.to_string()));
-            }
+        let key = req.key.clone();
+        let expected_revision = req.expected_revision;
+        let reply = self.propose_set(req).await?;
+        if !reply.succeeded {
+            return Err(Status::cancelled(
+                PlacementCenterError::KvRevisionMismatch(key, expected_revision).to_string(),
+            ));
         }
+        return Ok(Response::new(CommonReply::default()));
     }
 
     async fn get(&self, request: Request<GetRequest>) -> Result<Response<GetReply>, Status> {
@@ -80,6 +183,13 @@ impl KvService for GrpcKvService {
             ));
         }
 
+        wait_read_index(
+            &self.placement_cache,
+            &self.placement_center_storage,
+            req.stale_read,
+        )
+        .await?;
+
         let kv_storage = KvStorage::new(self.rocksdb_engine_handler.clone());
         let mut reply = GetReply::default();
         match kv_storage.get(req.key) {
@@ -105,6 +215,7 @@ impl KvService for GrpcKvService {
                 CommonError::ParameterCannotBeNull("key".to_string()).to_string(),
             ));
         }
+        self.check_key(&req.key)?;
 
         // Raft state machine is used to store Node data
         let data = StorageData::new(
@@ -135,6 +246,13 @@ impl KvService for GrpcKvService {
             ));
         }
 
+        wait_read_index(
+            &self.placement_cache,
+            &self.placement_center_storage,
+            req.stale_read,
+        )
+        .await?;
+
         let kv_storage = KvStorage::new(self.rocksdb_engine_handler.clone());
         match kv_storage.exists(req.key) {
             Ok(flag) => {
@@ -145,4 +263,696 @@ impl KvService for GrpcKvService {
             }
         }
     }
+    async fn stream_create_shard(
+        &self,
+        request: Request<StreamCreateShardRequest>,
+    ) -> Result<Response<CommonReply>, Status> {
+        let req = request.into_inner();
+
+        if req.cluster_name.is_empty() || req.shard_name.is_empty() {
+            return Err(Status::cancelled(
+                CommonError::ParameterCannotBeNull("cluster_name or shard_name".to_string())
+                    .to_string(),
+            ));
+        }
+
+        let data = StorageData::new(
+            StorageDataType::StreamCreateShard,
+            StreamCreateShardRequest::encode_to_vec(&req),
+        );
+        match self
+            .placement_center_storage
+            .apply_propose_message(data, "stream_create_shard".to_string())
+            .await
+        {
+            Ok(_) => return Ok(Response::new(CommonReply::default())),
+            Err(e) => {
+                return Err(Status::cancelled(e.to_string()));
+            }
+        }
+    }
+
+    async fn stream_delete_shard(
+        &self,
+        request: Request<StreamDeleteShardRequest>,
+    ) -> Result<Response<CommonReply>, Status> {
+        let req = request.into_inner();
+
+        if req.cluster_name.is_empty() || req.shard_name.is_empty() {
+            return Err(Status::cancelled(
+                CommonError::ParameterCannotBeNull("cluster_name or shard_name".to_string())
+                    .to_string(),
+            ));
+        }
+
+        let data = StorageData::new(
+            StorageDataType::StreamDeleteShard,
+            StreamDeleteShardRequest::encode_to_vec(&req),
+        );
+        match self
+            .placement_center_storage
+            .apply_propose_message(data, "stream_delete_shard".to_string())
+            .await
+        {
+            Ok(_) => return Ok(Response::new(CommonReply::default())),
+            Err(e) => {
+                return Err(Status::cancelled(e.to_string()));
+            }
+        }
+    }
+
+    async fn stream_write(
+        &self,
+        request: Request<StreamWriteRequest>,
+    ) -> Result<Response<StreamWriteReply>, Status> {
+        let mut req = request.into_inner();
+
+        if req.cluster_name.is_empty() || req.shard_name.is_empty() {
+            return Err(Status::cancelled(
+                CommonError::ParameterCannotBeNull("cluster_name or shard_name".to_string())
+                    .to_string(),
+            ));
+        }
+
+        if req.records.is_empty() {
+            return Ok(Response::new(StreamWriteReply::default()));
+        }
+
+        // The write time is fixed before proposing so that all replicas store the same record.
+        for record in req.records.iter_mut() {
+            if record.create_time == 0 {
+                record.create_time = now_second();
+            }
+        }
+
+        // The limits are checked when the write is applied.
+        let conf = placement_center_conf();
+        req.max_shard_record_num = conf.stream_storage.max_shard_record_num;
+        req.max_shard_size = conf.stream_storage.max_shard_size;
+
+        let record_num = req.records.len();
+        let data = StorageData::new(
+            StorageDataType::StreamWrite,
+            StreamWriteRequest::encode_to_vec(&req),
+        );
+        match self
+            .placement_center_storage
+            .apply_propose_message_with_reply(data, "stream_write".to_string())
+            .await
+        {
+            Ok(data) => match StreamWriteReply::decode(data.as_ref()) {
+                Ok(reply) => {
+                    if reply.offsets.len() != record_num {
+                        return Err(Status::cancelled(format!(
+                            "Shard [{}] stream write was not applied",
+                            req.shard_name
+                        )));
+                    }
+                    return Ok(Response::new(reply));
+                }
+                Err(e) => {
+                    return Err(Status::cancelled(e.to_string()));
+                }
+            },
+            Err(e) => {
+                return Err(Status::cancelled(e.to_string()));
+            }
+        }
+    }
+
+    async fn stream_read(
+        &self,
+        request: Request<StreamReadRequest>,
+    ) -> Result<Response<StreamReadReply>, Status> {
+        let req = request.into_inner();
+
+        if req.cluster_name.is_empty() || req.shard_name.is_empty() || req.group_id.is_empty() {
+            return Err(Status::cancelled(
+                CommonError::ParameterCannotBeNull(
+                    "cluster_name, shard_name or group_id".to_string(),
+                )
+                .to_string(),
+            ));
+        }
+
+        wait_read_index(
+            &self.placement_cache,
+            &self.placement_center_storage,
+            req.stale_read,
+        )
+        .await?;
+
+        let stream_storage = StreamStorage::new(self.rocksdb_engine_handler.clone());
+        let start_offset = match stream_storage.get_group_offset(
+            &req.cluster_name,
+            &req.shard_name,
+            &req.group_id,
+        ) {
+            Ok(Some(offset)) => offset + 1,
+            Ok(None) => 0,
+            Err(e) => return Err(Status::cancelled(e.to_string())),
+        };
+
+        match stream_storage.read(
+            &req.cluster_name,
+            &req.shard_name,
+            start_offset,
+            req.record_num,
+            req.record_size,
+        ) {
+            Ok(records) => {
+                return Ok(Response::new(StreamReadReply { records }));
+            }
+            Err(e) => {
+                return Err(Status::cancelled(e.to_string()));
+            }
+        }
+    }
+
+    async fn stream_commit_offset(
+        &self,
+        request: Request<StreamCommitOffsetRequest>,
+    ) -> Result<Response<CommonReply>, Status> {
+        let req = request.into_inner();
+
+        if req.cluster_name.is_empty() || req.shard_name.is_empty() || req.group_id.is_empty() {
+            return Err(Status::cancelled(
+                CommonError::ParameterCannotBeNull(
+                    "cluster_name, shard_name or group_id".to_string(),
+                )
+                .to_string(),
+            ));
+        }
+
+        let data = StorageData::new(
+            StorageDataType::StreamCommitOffset,
+            StreamCommitOffsetRequest::encode_to_vec(&req),
+        );
+        match self
+            .placement_center_storage
+            .apply_propose_message(data, "stream_commit_offset".to_string())
+            .await
+        {
+            Ok(_) => return Ok(Response::new(CommonReply::default())),
+            Err(e) => {
+                return Err(Status::cancelled(e.to_string()));
+            }
+        }
+    }
+
+    async fn stream_read_by_offset(
+        &self,
+        request: Request<StreamReadByOffsetRequest>,
+    ) -> Result<Response<StreamReadByOffsetReply>, Status> {
+        let req = request.into_inner();
+
+        if req.cluster_name.is_empty() || req.shard_name.is_empty() {
+            return Err(Status::cancelled(
+                CommonError::ParameterCannotBeNull("cluster_name or shard_name".to_string())
+                    .to_string(),
+            ));
+        }
+
+        wait_read_index(
+            &self.placement_cache,
+            &self.placement_center_storage,
+            req.stale_read,
+        )
+        .await?;
+
+        let stream_storage = StreamStorage::new(self.rocksdb_engine_handler.clone());
+        match stream_storage.read_by_offset(&req.cluster_name, &req.shard_name, req.offset) {
+            Ok(record) => {
+                return Ok(Response::new(StreamReadByOffsetReply { record }));
+            }
+            Err(e) => {
+                return Err(Status::cancelled(e.to_string()));
+            }
+        }
+    }
+
+    async fn stream_read_by_timestamp(
+        &self,
+        request: Request<StreamReadByTimestampRequest>,
+    ) -> Result<Response<StreamReadReply>, Status> {
+        let req = request.into_inner();
+
+        if req.cluster_name.is_empty() || req.shard_name.is_empty() {
+            return Err(Status::cancelled(
+                CommonError::ParameterCannotBeNull("cluster_name or shard_name".to_string())
+                    .to_string(),
+            ));
+        }
+
+        wait_read_index(
+            &self.placement_cache,
+            &self.placement_center_storage,
+            req.stale_read,
+        )
+        .await?;
+
+        let stream_storage = StreamStorage::new(self.rocksdb_engine_handler.clone());
+        match stream_storage.read_by_timestamp(
+            &req.cluster_name,
+            &req.shard_name,
+            req.start_timestamp,
+            req.end_timestamp,
+            req.record_num,
+            req.record_size,
+        ) {
+            Ok(records) => {
+                return Ok(Response::new(StreamReadReply { records }));
+            }
+            Err(e) => {
+                return Err(Status::cancelled(e.to_string()));
+            }
+        }
+    }
+
+    async fn stream_read_by_key(
+        &self,
+        request: Request<StreamReadByKeyRequest>,
+    ) -> Result<Response<StreamReadByOffsetReply>, Status> {
+        let req = request.into_inner();
+
+        if req.cluster_name.is_empty() || req.shard_name.is_empty() || req.key.is_empty() {
+            return Err(Status::cancelled(
+                CommonError::ParameterCannotBeNull("cluster_name, shard_name or key".to_string())
+                    .to_string(),
+            ));
+        }
+
+        wait_read_index(
+            &self.placement_cache,
+            &self.placement_center_storage,
+            req.stale_read,
+        )
+        .await?;
+
+        let stream_storage = StreamStorage::new(self.rocksdb_engine_handler.clone());
+        match stream_storage.read_by_key(&req.cluster_name, &req.shard_name, &req.key) {
+            Ok(record) => {
+                return Ok(Response::new(StreamReadByOffsetReply { record }));
+            }
+            Err(e) => {
+                return Err(Status::cancelled(e.to_string()));
+            }
+        }
+    }
+
+    async fn stream_get_shard(
+        &self,
+        request: Request<StreamGetShardRequest>,
+    ) -> Result<Response<StreamGetShardReply>, Status> {
+        let req = request.into_inner();
+
+        if req.cluster_name.is_empty() || req.shard_name.is_empty() {
+            return Err(Status::cancelled(
+                CommonError::ParameterCannotBeNull("cluster_name or shard_name".to_string())
+                    .to_string(),
+            ));
+        }
+
+        wait_read_index(
+            &self.placement_cache,
+            &self.placement_center_storage,
+            req.stale_read,
+        )
+        .await?;
+
+        let stream_storage = StreamStorage::new(self.rocksdb_engine_handler.clone());
+        match stream_storage.get_shard(&req.cluster_name, &req.shard_name) {
+            Ok(Some(shard)) => {
+                return Ok(Response::new(StreamGetShardReply {
+                    start_offset: shard.start_offset,
+                    next_offset: shard.next_offset,
+                    record_num: shard.record_num,
+                    shard_size: shard.shard_size,
+                    config: Some(shard.config()),
+                }));
+            }
+            Ok(None) => {
+                return Ok(Response::new(StreamGetShardReply::default()));
+            }
+            Err(e) => {
+                return Err(Status::cancelled(e.to_string()));
+            }
+        }
+    }
+
+    async fn stream_list_group(
+        &self,
+        request: Request<StreamListGroupRequest>,
+    ) -> Result<Response<StreamListGroupReply>, Status> {
+        let req = request.into_inner();
+
+        if req.cluster_name.is_empty() || req.shard_name.is_empty() {
+            return Err(Status::cancelled(
+                CommonError::ParameterCannotBeNull("cluster_name or shard_name".to_string())
+                    .to_string(),
+            ));
+        }
+
+        wait_read_index(
+            &self.placement_cache,
+            &self.placement_center_storage,
+            req.stale_read,
+        )
+        .await?;
+
+        let stream_storage = StreamStorage::new(self.rocksdb_engine_handler.clone());
+        match stream_storage.list_groups(&req.cluster_name, &req.shard_name) {
+            Ok(groups) => {
+                let groups = groups
+                    .into_iter()
+                    .map(|(group_id, offset)| StreamGroupOffset { group_id, offset })
+                    .collect();
+                return Ok(Response::new(StreamListGroupReply { groups }));
+            }
+            Err(e) => {
+                return Err(Status::cancelled(e.to_string()));
+            }
+        }
+    }
+
+    async fn stream_offset_by_timestamp(
+        &self,
+        request: Request<StreamOffsetByTimestampRequest>,
+    ) -> Result<Response<StreamOffsetByTimestampReply>, Status> {
+        let req = request.into_inner();
+
+        if req.cluster_name.is_empty() || req.shard_name.is_empty() {
+            return Err(Status::cancelled(
+                CommonError::ParameterCannotBeNull("cluster_name or shard_name".to_string())
+                    .to_string(),
+            ));
+        }
+
+        wait_read_index(
+            &self.placement_cache,
+            &self.placement_center_storage,
+            req.stale_read,
+        )
+        .await?;
+
+        let stream_storage = StreamStorage::new(self.rocksdb_engine_handler.clone());
+        match stream_storage.offset_by_timestamp(&req.cluster_name, &req.shard_name, req.timestamp)
+        {
+            Ok(Some(offset)) => {
+                return Ok(Response::new(StreamOffsetByTimestampReply {
+                    found: true,
+                    offset,
+                }));
+            }
+            Ok(None) => {
+                return Ok(Response::new(StreamOffsetByTimestampReply::default()));
+            }
+            Err(e) => {
+                return Err(Status::cancelled(e.to_string()));
+            }
+        }
+    }
+
+    async fn stream_reset_group_offset(
+        &self,
+        request: Request<StreamResetGroupOffsetRequest>,
+    ) -> Result<Response<CommonReply>, Status> {
+        let req = request.into_inner();
+
+        if req.cluster_name.is_empty() || req.shard_name.is_empty() || req.group_id.is_empty() {
+            return Err(Status::cancelled(
+                CommonError::ParameterCannotBeNull(
+                    "cluster_name, shard_name or group_id".to_string(),
+                )
+                .to_string(),
+            ));
+        }
+
+        let data = StorageData::new(
+            StorageDataType::StreamResetGroupOffset,
+            StreamResetGroupOffsetRequest::encode_to_vec(&req),
+        );
+        match self
+            .placement_center_storage
+            .apply_propose_message(data, "stream_reset_group_offset".to_string())
+            .await
+        {
+            Ok(_) => return Ok(Response::new(CommonReply::default())),
+            Err(e) => {
+                return Err(Status::cancelled(e.to_string()));
+            }
+        }
+    }
+
+    async fn list(&self, request: Request<ListRequest>) -> Result<Response<ListReply>, Status> {
+        let req = request.into_inner();
+
+        wait_read_index(
+            &self.placement_cache,
+            &self.placement_center_storage,
+            req.stale_read,
+        )
+        .await?;
+
+        let limit = if req.limit == 0 {
+            DEFAULT_LIST_LIMIT
+        } else {
+            (req.limit as usize).min(MAX_LIST_LIMIT)
+        };
+        let kv_storage = KvStorage::new(self.rocksdb_engine_handler.clone());
+        let revision = match kv_storage.revision() {
+            Ok(revision) => revision,
+            Err(e) => return Err(Status::cancelled(e.to_string())),
+        };
+        match kv_storage.list(&req.prefix, &req.start_key, &req.end_key, limit) {
+            Ok((records, next_key)) => {
+                return Ok(Response::new(ListReply {
+                    entries: records.iter().map(|r| r.to_entry()).collect(),
+                    next_key,
+                    revision,
+                }));
+            }
+            Err(e) => {
+                return Err(Status::cancelled(e.to_string()));
+            }
+        }
+    }
+
+    async fn compare_and_swap(
+        &self,
+        request: Request<CompareAndSwapRequest>,
+    ) -> Result<Response<CompareAndSwapReply>, Status> {
+        let req = request.into_inner();
+
+        if req.key.is_empty() {
+            return Err(Status::cancelled(
+                CommonError::ParameterCannotBeNull("key".to_string()).to_string(),
+            ));
+        }
+        self.check_key(&req.key)?;
+
+        let reply = self
+            .propose_set(SetRequest {
+                key: req.key,
+                value: req.value,
+                lease_id: req.lease_id,
+                compare_revision: true,
+                expected_revision: req.expected_revision,
+            })
+            .await?;
+        return Ok(Response::new(reply));
+    }
+
+    async fn lease_grant(
+        &self,
+        request: Request<LeaseGrantRequest>,
+    ) -> Result<Response<LeaseGrantReply>, Status> {
+        let req = request.into_inner();
+
+        if req.ttl == 0 {
+            return Err(Status::cancelled(
+                CommonError::ParameterCannotBeNull("ttl".to_string()).to_string(),
+            ));
+        }
+
+        // The lease is created by setting its record. The id is picked here, a grant
+        // that raced for the same id is proposed again with the next one.
+        let kv_storage = KvStorage::new(self.rocksdb_engine_handler.clone());
+        let mut lease_id = 0;
+        for _ in 0..LEASE_GRANT_ATTEMPTS {
+            lease_id = match kv_storage.next_lease_id() {
+                Ok(next_lease_id) => next_lease_id.max(lease_id + 1),
+                Err(e) => return Err(Status::cancelled(e.to_string())),
+            };
+            let reply = self
+                .propose_set(SetRequest {
+                    key: key_kv_lease(lease_id),
+                    value: req.ttl.to_string(),
+                    ..Default::default()
+                })
+                .await?;
+            if reply.succeeded {
+                self.kv_cache.keepalive(lease_id, now_second());
+                return Ok(Response::new(LeaseGrantReply {
+                    lease_id,
+                    ttl: req.ttl,
+                }));
+            }
+        }
+        return Err(Status::unavailable(format!(
+            "No lease id could be granted in {} attempts",
+            LEASE_GRANT_ATTEMPTS
+        )));
+    }
+
+    async fn lease_revoke(
+        &self,
+        request: Request<LeaseRevokeRequest>,
+    ) -> Result<Response<CommonReply>, Status> {
+        let req = request.into_inner();
+
+        let data = StorageData::new(
+            StorageDataType::KvDelete,
+            DeleteRequest::encode_to_vec(&DeleteRequest {
+                key: key_kv_lease(req.lease_id),
+            }),
+        );
+        match self
+            .placement_center_storage
+            .apply_propose_message(data, "lease_revoke".to_string())
+            .await
+        {
+            Ok(_) => return Ok(Response::new(CommonReply::default())),
+            Err(e) => {
+                return Err(Status::cancelled(e.to_string()));
+            }
+        }
+    }
+
+    async fn lease_keep_alive(
+        &self,
+        request: Request<LeaseKeepAliveRequest>,
+    ) -> Result<Response<LeaseKeepAliveReply>, Status> {
+        let req = request.into_inner();
+
+        // The leader expires the leases, so it is the one keeping them alive
+        let (is_leader, node_id, leader_addr) = {
+            let cache = self.placement_cache.read().unwrap();
+            (cache.is_leader(), cache.local.node_id, cache.leader_addr())
+        };
+        if !is_leader {
+            return Err(not_leader_status(node_id, leader_addr));
+        }
+
+        let kv_storage = KvStorage::new(self.rocksdb_engine_handler.clone());
+        match kv_storage.lease_get(req.lease_id) {
+            Ok(Some(lease)) => {
+                self.kv_cache.keepalive(lease.lease_id, now_second());
+                return Ok(Response::new(LeaseKeepAliveReply {
+                    lease_id: lease.lease_id,
+                    ttl: lease.ttl,
+                }));
+            }
+            Ok(None) => {
+                return Err(Status::cancelled(
+                    PlacementCenterError::KvLeaseNotFound(req.lease_id).to_string(),
+                ));
+            }
+            Err(e) => return Err(Status::cancelled(e.to_string())),
+        }
+    }
+
+    async fn watch(
+        &self,
+        request: Request<WatchRequest>,
+    ) -> Result<Response<Self::WatchStream>, Status> {
+        let req = request.into_inner();
+        let kv_storage = KvStorage::new(self.rocksdb_engine_handler.clone());
+
+        // Subscribes before reading the stored events, so that no event is missed in between
+        let mut event_recv = self.kv_cache.subscribe();
+        let (revision, oldest_revision) =
+            match (kv_storage.revision(), kv_storage.oldest_revision()) {
+                (Ok(revision), Ok(oldest_revision)) => (revision, oldest_revision),
+                (Err(e), _) | (_, Err(e)) => return Err(Status::cancelled(e.to_string())),
+            };
+        let mut next_revision = if req.start_revision == 0 {
+            revision + 1
+        } else {
+            req.start_revision
+        };
+        if next_revision < oldest_revision {
+            return Err(Status::out_of_range(
+                PlacementCenterError::KvRevisionCompacted(next_revision, oldest_revision)
+                    .to_string(),
+            ));
+        }
+
+        let (reply_send, reply_recv) = mpsc::channel::<Result<WatchReply, Status>>(100);
+        tokio::spawn(async move {
+            loop {
+                // The stored events are sent first, the applied events then follow them.
+                let events = match kv_storage.list_event(next_revision, WATCH_BATCH_SIZE) {
+                    Ok(events) => events,
+                    Err(e) => {
+                        let _ = reply_send.send(Err(Status::cancelled(e.to_string()))).await;
+                        return;
+                    }
+                };
+                if let Some(first) = events.first() {
+                    // A watcher that fell too far behind cannot catch up anymore
+                    if first.revision > next_revision {
+                        let e = PlacementCenterError::KvRevisionCompacted(
+                            next_revision,
+                            first.revision,
+                        );
+                        let _ = reply_send
+                            .send(Err(Status::out_of_range(e.to_string())))
+                            .await;
+                        return;
+                    }
+                    next_revision = events.last().unwrap().revision + 1;
+                    if !send_watch_events(&reply_send, &req, &events).await {
+                        return;
+                    }
+                    continue;
+                }
+
+                select! {
+                    _ = reply_send.closed() => {
+                        return;
+                    }
+                    val = event_recv.recv() => {
+                        match val {
+                            Ok(event) => {
+                                // Other revisions are already sent or are read from the storage
+                                if event.revision != next_revision {
+                                    continue;
+                                }
+                                next_revision += 1;
+                                if !send_watch_events(&reply_send, &req, &[event]).await {
+                                    return;
+                                }
+                            }
+                            Err(RecvError::Lagged(_)) => {}
+                            Err(RecvError::Closed) => {
+                                return;
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        let stream: Self::WatchStream = Box::pin(futures::stream::unfold(
+            reply_recv,
+            |mut reply_recv| async move {
+                match reply_recv.recv().await {
+                    Some(reply) => Some((reply, reply_recv)),
+                    None => None,
+                }
+            },
+        ));
+        return Ok(Response::new(stream));
+    }
 }
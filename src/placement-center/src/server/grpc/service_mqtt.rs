@@ -14,7 +14,11 @@
 use crate::{
     cache::placement::PlacementCacheManager,
     core::share_sub::ShareSubLeader,
-    raft::apply::{RaftMachineApply, StorageData, StorageDataType},
+    raft::{
+        apply::{RaftMachineApply, StorageData, StorageDataType},
+        metadata::RaftGroupMetadata,
+    },
+    server::grpc::read_index::wait_read_index,
     storage::{
         mqtt::{
             acl::AclStorage, session::MQTTSessionStorage, topic::MQTTTopicStorage,
@@ -35,12 +39,13 @@ use protocol::placement_center::generate::{
         SetTopicRetainMessageRequest, UpdateSessionRequest,
     },
 };
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use tonic::{Request, Response, Status};
 
 pub struct GrpcMqttService {
     cluster_cache: Arc<PlacementCacheManager>,
     placement_center_storage: Arc<RaftMachineApply>,
+    placement_cache: Arc<RwLock<RaftGroupMetadata>>,
     rocksdb_engine_handler: Arc<RocksDBEngine>,
 }
 
@@ -48,11 +53,13 @@ impl GrpcMqttService {
     pub fn new(
         cluster_cache: Arc<PlacementCacheManager>,
         placement_center_storage: Arc<RaftMachineApply>,
+        placement_cache: Arc<RwLock<RaftGroupMetadata>>,
         rocksdb_engine_handler: Arc<RocksDBEngine>,
     ) -> Self {
         GrpcMqttService {
             cluster_cache,
             placement_center_storage,
+            placement_cache,
             rocksdb_engine_handler,
         }
     }
@@ -67,6 +74,13 @@ impl MqttService for GrpcMqttService {
         request: Request<GetShareSubLeaderRequest>,
     ) -> Result<Response<GetShareSubLeaderReply>, Status> {
         let req = request.into_inner();
+        wait_read_index(
+            &self.placement_cache,
+            &self.placement_center_storage,
+            req.stale_read,
+        )
+        .await?;
+
         let cluster_name = req.cluster_name;
         let group_name = req.group_name;
         let mut reply = GetShareSubLeaderReply::default();
@@ -99,6 +113,12 @@ impl MqttService for GrpcMqttService {
         request: Request<ListUserRequest>,
     ) -> Result<Response<ListUserReply>, Status> {
         let req = request.into_inner();
+        wait_read_index(
+            &self.placement_cache,
+            &self.placement_center_storage,
+            req.stale_read,
+        )
+        .await?;
         let storage = MQTTUserStorage::new(self.rocksdb_engine_handler.clone());
 
         if !req.username.is_empty() {
@@ -226,6 +246,12 @@ impl MqttService for GrpcMqttService {
         request: Request<ListTopicRequest>,
     ) -> Result<Response<ListTopicReply>, Status> {
         let req = request.into_inner();
+        wait_read_index(
+            &self.placement_cache,
+            &self.placement_center_storage,
+            req.stale_read,
+        )
+        .await?;
         let storage = MQTTTopicStorage::new(self.rocksdb_engine_handler.clone());
         if !req.topic_name.is_empty() {
             match storage.get(&req.cluster_name, &req.topic_name) {
@@ -262,6 +288,12 @@ impl MqttService for GrpcMqttService {
         request: Request<ListSessionRequest>,
     ) -> Result<Response<ListSessionReply>, Status> {
         let req = request.into_inner();
+        wait_read_index(
+            &self.placement_cache,
+            &self.placement_center_storage,
+            req.stale_read,
+        )
+        .await?;
         let storage = MQTTSessionStorage::new(self.rocksdb_engine_handler.clone());
 
         if !req.client_id.is_empty() {
@@ -409,6 +441,12 @@ impl MqttService for GrpcMqttService {
         request: Request<ListAclRequest>,
     ) -> Result<Response<ListAclReply>, Status> {
         let req = request.into_inner();
+        wait_read_index(
+            &self.placement_cache,
+            &self.placement_center_storage,
+            req.stale_read,
+        )
+        .await?;
         let acl_storage = AclStorage::new(self.rocksdb_engine_handler.clone());
         match acl_storage.list(&req.cluster_name) {
             Ok(list) => {
@@ -13,36 +13,65 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
-use crate::cache::placement::PlacementCacheManager;
-use crate::raft::apply::{RaftMachineApply, StorageData, StorageDataType};
+use crate::cache::placement::{FoldMonitor, NodeMonitor, PlacementCacheManager};
+use crate::raft::apply::{MembershipChange, RaftMachineApply, StorageData, StorageDataType};
 use crate::raft::metadata::RaftGroupMetadata;
+use crate::server::grpc::read_index::wait_read_index;
+use crate::storage::keys::key_cluster_name;
+use crate::storage::placement::backup::{backup_name, BackupStore};
 use crate::storage::placement::config::ResourceConfigStorage;
 use crate::storage::placement::idempotent::IdempotentStorage;
+use crate::storage::placement::lock::{LockInfo, LockKind, LockStorage};
+use crate::storage::placement::snapshot::SnapshotStore;
 use crate::storage::rocksdb::RocksDBEngine;
-use clients::placement::placement::call::{register_node, un_register_node};
+use clients::placement::placement::call::{
+    add_raft_member, heartbeat, list_raft_member, promote_raft_learner, register_node,
+    remove_raft_member, report_monitor, transfer_raft_leader, un_register_node,
+};
 use clients::poll::ClientPool;
 use common_base::error::placement_center::PlacementCenterError;
-use common_base::tools::now_second;
+use common_base::tools::{now_mills, now_second};
+use metadata_struct::placement::broker_node::BrokerNode;
 use prost::Message;
-use protocol::placement_center::generate::common::CommonReply;
+use protocol::placement_center::generate::common::{ClusterType, CommonReply};
 use protocol::placement_center::generate::placement::placement_center_service_server::PlacementCenterService;
 use protocol::placement_center::generate::placement::{
-    DeleteIdempotentDataRequest, DeleteResourceConfigRequest, ExistsIdempotentDataReply,
-    ExistsIdempotentDataRequest, GetResourceConfigReply, GetResourceConfigRequest,
-    HeartbeatRequest, RegisterNodeRequest, ReportMonitorRequest, SendRaftConfChangeReply,
-    SendRaftConfChangeRequest, SendRaftMessageReply, SendRaftMessageRequest,
-    SetIdempotentDataRequest, SetResourceConfigRequest, UnRegisterNodeRequest,
+    AcquireLockReply, AcquireLockRequest, AddRaftMemberRequest, CampaignReply, CampaignRequest,
+    CreateBackupReply, CreateBackupRequest, DeleteIdempotentDataRequest,
+    DeleteResourceConfigRequest, ExistsIdempotentDataReply, ExistsIdempotentDataRequest,
+    GetElectionLeaderReply, GetElectionLeaderRequest, GetLockReply, GetLockRequest,
+    GetResourceConfigReply, GetResourceConfigRequest, HeartbeatRequest, ImportMetadataRequest,
+    ListRaftMemberReply, ListRaftMemberRequest, LockHolder, MetadataEntry,
+    PromoteRaftLearnerRequest, ReadBackupReply, ReadBackupRequest, RegisterNodeRequest,
+    ReleaseLockRequest, RemoveRaftMemberRequest, ReportMonitorRequest, ResignRequest,
+    SendRaftConfChangeReply, SendRaftConfChangeRequest, SendRaftMessageReply,
+    SendRaftMessageRequest, SendRaftSnapshotReply, SendRaftSnapshotRequest,
+    SetIdempotentDataRequest, SetResourceConfigRequest, TransferRaftLeaderRequest,
+    UnRegisterNodeRequest,
 };
 use raft::eraftpb::{ConfChange, Message as raftPreludeMessage};
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::time::sleep;
 use tonic::{Request, Response, Status};
 
+// How long a blocked acquire waits before looking at the lock again
+const LOCK_RETRY_INTERVAL_MS: u64 = 100;
+
+// Largest chunk of a backup returned by one ReadBackup, well below the gRPC message limit
+const BACKUP_READ_MAX_BYTES: u64 = 1024 * 1024;
+
+// Largest batch of imported keys written by one raft entry
+const IMPORT_BATCH_MAX_BYTES: usize = 1024 * 1024;
+
 pub struct GrpcPlacementService {
     placement_center_storage: Arc<RaftMachineApply>,
     placement_cache: Arc<RwLock<RaftGroupMetadata>>,
     cluster_cache: Arc<PlacementCacheManager>,
     rocksdb_engine_handler: Arc<RocksDBEngine>,
     client_poll: Arc<ClientPool>,
+    snapshot_store: Arc<SnapshotStore>,
+    backup_store: Arc<BackupStore>,
 }
 
 impl GrpcPlacementService {
@@ -52,6 +81,8 @@ impl GrpcPlacementService {
         cluster_cache: Arc<PlacementCacheManager>,
         rocksdb_engine_handler: Arc<RocksDBEngine>,
         client_poll: Arc<ClientPool>,
+        snapshot_store: Arc<SnapshotStore>,
+        backup_store: Arc<BackupStore>,
     ) -> Self {
         GrpcPlacementService {
             placement_center_storage,
@@ -59,12 +90,102 @@ impl GrpcPlacementService {
             cluster_cache,
             rocksdb_engine_handler,
             client_poll,
+            snapshot_store,
+            backup_store,
         }
     }
 
     fn rewrite_leader(&self) -> bool {
         return !self.placement_cache.read().unwrap().is_leader();
     }
+
+    async fn change_membership(
+        &self,
+        change: MembershipChange,
+    ) -> Result<Response<CommonReply>, Status> {
+        match self
+            .placement_center_storage
+            .change_membership(change)
+            .await
+        {
+            Ok(_) => return Ok(Response::new(CommonReply::default())),
+            Err(e) => return Err(Status::cancelled(e.to_string())),
+        }
+    }
+
+    // Locks are held by registered nodes, and released by the leader once the node stops
+    // sending heartbeats for ttl seconds.
+    fn check_lock_holder(
+        &self,
+        holder: Option<LockHolder>,
+        ttl: u64,
+    ) -> Result<LockHolder, Status> {
+        let holder = match holder {
+            Some(holder) => holder,
+            None => return Err(Status::invalid_argument("holder cannot be empty")),
+        };
+        if ttl == 0 {
+            return Err(Status::invalid_argument("ttl must be greater than 0"));
+        }
+        if self
+            .cluster_cache
+            .get_node_addr(&holder.cluster_name, holder.node_id)
+            .is_none()
+        {
+            return Err(Status::failed_precondition(
+                PlacementCenterError::LockHolderNotRegistered(
+                    holder.cluster_name.clone(),
+                    holder.node_id,
+                )
+                .to_string(),
+            ));
+        }
+        return Ok(holder);
+    }
+
+    // Returns the lock when someone else holds it. The proposal decides who gets the lock,
+    // this only saves proposing while the lock is known to be taken.
+    fn lock_taken_by_other(
+        &self,
+        kind: &LockKind,
+        name: &String,
+        holder: &LockHolder,
+    ) -> Result<Option<LockInfo>, Status> {
+        let storage = LockStorage::new(self.rocksdb_engine_handler.clone());
+        match storage.get(kind, name) {
+            Ok(Some(lock)) => {
+                if lock.held && !lock.is_held_by(holder) {
+                    return Ok(Some(lock));
+                }
+                return Ok(None);
+            }
+            Ok(None) => return Ok(None),
+            Err(e) => return Err(Status::internal(e.to_string())),
+        }
+    }
+
+    async fn propose_lock(&self, data: StorageData, action: &str) -> Result<Vec<u8>, Status> {
+        match self
+            .placement_center_storage
+            .apply_propose_message_with_reply(data, action.to_string())
+            .await
+        {
+            Ok(reply) => return Ok(reply),
+            Err(e) => return Err(Status::cancelled(e.to_string())),
+        }
+    }
+
+    async fn wait_lock_retry(&self, deadline: u64) -> bool {
+        let now = now_mills();
+        if now >= deadline {
+            return false;
+        }
+        sleep(Duration::from_millis(
+            LOCK_RETRY_INTERVAL_MS.min(deadline - now),
+        ))
+        .await;
+        return true;
+    }
 }
 
 #[tonic::async_trait]
@@ -140,6 +261,16 @@ impl PlacementCenterService for GrpcPlacementService {
         request: Request<HeartbeatRequest>,
     ) -> Result<Response<CommonReply>, Status> {
         let req = request.into_inner();
+
+        // Heartbeats are checked by the leader, which unregisters the nodes that time out.
+        if self.rewrite_leader() {
+            let leader_addr = self.placement_cache.read().unwrap().leader_addr();
+            match heartbeat(self.client_poll.clone(), vec![leader_addr], req).await {
+                Ok(resp) => return Ok(Response::new(resp)),
+                Err(e) => return Err(Status::cancelled(e.to_string())),
+            }
+        }
+
         // Params validate
 
         self.cluster_cache
@@ -150,8 +281,37 @@ impl PlacementCenterService for GrpcPlacementService {
 
     async fn report_monitor(
         &self,
-        _: Request<ReportMonitorRequest>,
+        request: Request<ReportMonitorRequest>,
     ) -> Result<Response<CommonReply>, Status> {
+        let req = request.into_inner();
+
+        // Segment replicas are placed by the leader, so it keeps the monitor data.
+        if self.rewrite_leader() {
+            let leader_addr = self.placement_cache.read().unwrap().leader_addr();
+            match report_monitor(self.client_poll.clone(), vec![leader_addr], req).await {
+                Ok(resp) => return Ok(Response::new(resp)),
+                Err(e) => return Err(Status::cancelled(e.to_string())),
+            }
+        }
+
+        let monitor = NodeMonitor {
+            cpu_rate: req.cpu_rate,
+            memory_rate: req.memory_rate,
+            disk_rate: req.disk_rate,
+            folds: req
+                .folds
+                .into_iter()
+                .map(|fold| FoldMonitor {
+                    fold: fold.fold,
+                    used_bytes: fold.used_bytes,
+                    segment_num: fold.segment_num,
+                })
+                .collect(),
+            report_time: now_second(),
+        };
+        self.cluster_cache
+            .report_monitor(&req.cluster_name, req.node_id, monitor);
+
         return Ok(Response::new(CommonReply::default()));
     }
 
@@ -197,6 +357,166 @@ impl PlacementCenterService for GrpcPlacementService {
         }
     }
 
+    async fn send_raft_snapshot(
+        &self,
+        request: Request<SendRaftSnapshotRequest>,
+    ) -> Result<Response<SendRaftSnapshotReply>, Status> {
+        let req = request.into_inner();
+        self.snapshot_store
+            .write_chunk(&req.snapshot, req.offset, &req.data)
+            .map_err(|e| Status::cancelled(e.to_string()))?;
+        if !req.done {
+            return Ok(Response::new(SendRaftSnapshotReply::default()));
+        }
+
+        // The whole snapshot arrived, the raft message installs it.
+        self.snapshot_store
+            .finish_receive(&req.snapshot, req.offset + req.data.len() as u64)
+            .map_err(|e| Status::cancelled(e.to_string()))?;
+        let message = raftPreludeMessage::decode(req.message.as_ref())
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        match self
+            .placement_center_storage
+            .apply_raft_message(message, "send_raft_snapshot".to_string())
+            .await
+        {
+            Ok(_) => return Ok(Response::new(SendRaftSnapshotReply::default())),
+            Err(e) => {
+                return Err(Status::cancelled(
+                    PlacementCenterError::RaftLogCommitTimeout(e.to_string()).to_string(),
+                ));
+            }
+        }
+    }
+
+    async fn list_raft_member(
+        &self,
+        request: Request<ListRaftMemberRequest>,
+    ) -> Result<Response<ListRaftMemberReply>, Status> {
+        let req = request.into_inner();
+
+        // Only the leader knows how far the members have replicated the log
+        if self.rewrite_leader() {
+            let leader_addr = self.placement_cache.read().unwrap().leader_addr();
+            match list_raft_member(self.client_poll.clone(), vec![leader_addr], req).await {
+                Ok(resp) => return Ok(Response::new(resp)),
+                Err(e) => return Err(Status::cancelled(e.to_string())),
+            }
+        }
+
+        match self.placement_center_storage.list_member().await {
+            Ok(reply) => return Ok(Response::new(reply)),
+            Err(e) => return Err(Status::cancelled(e.to_string())),
+        }
+    }
+
+    async fn add_raft_member(
+        &self,
+        request: Request<AddRaftMemberRequest>,
+    ) -> Result<Response<CommonReply>, Status> {
+        let req = request.into_inner();
+
+        if self.rewrite_leader() {
+            let leader_addr = self.placement_cache.read().unwrap().leader_addr();
+            match add_raft_member(self.client_poll.clone(), vec![leader_addr], req).await {
+                Ok(resp) => return Ok(Response::new(resp)),
+                Err(e) => return Err(Status::cancelled(e.to_string())),
+            }
+        }
+
+        let ip = match req.node_addr.split_once(":") {
+            Some((ip, _)) => ip.to_string(),
+            None => {
+                return Err(Status::invalid_argument(format!(
+                    "node address {} has no port",
+                    req.node_addr
+                )));
+            }
+        };
+        let mut node = BrokerNode::default();
+        node.cluster_type = ClusterType::PlacementCenter.as_str_name().to_string();
+        node.cluster_name = self
+            .placement_cache
+            .read()
+            .unwrap()
+            .local
+            .cluster_name
+            .clone();
+        node.node_ip = ip;
+        node.node_inner_addr = req.node_addr;
+        node.node_id = req.node_id;
+
+        let change = if req.learner {
+            MembershipChange::AddLearner(node)
+        } else {
+            MembershipChange::AddVoter(node)
+        };
+        return self.change_membership(change).await;
+    }
+
+    async fn promote_raft_learner(
+        &self,
+        request: Request<PromoteRaftLearnerRequest>,
+    ) -> Result<Response<CommonReply>, Status> {
+        let req = request.into_inner();
+
+        if self.rewrite_leader() {
+            let leader_addr = self.placement_cache.read().unwrap().leader_addr();
+            match promote_raft_learner(self.client_poll.clone(), vec![leader_addr], req).await {
+                Ok(resp) => return Ok(Response::new(resp)),
+                Err(e) => return Err(Status::cancelled(e.to_string())),
+            }
+        }
+
+        return self
+            .change_membership(MembershipChange::PromoteLearner(req.node_id))
+            .await;
+    }
+
+    async fn remove_raft_member(
+        &self,
+        request: Request<RemoveRaftMemberRequest>,
+    ) -> Result<Response<CommonReply>, Status> {
+        let req = request.into_inner();
+
+        if self.rewrite_leader() {
+            let leader_addr = self.placement_cache.read().unwrap().leader_addr();
+            match remove_raft_member(self.client_poll.clone(), vec![leader_addr], req).await {
+                Ok(resp) => return Ok(Response::new(resp)),
+                Err(e) => return Err(Status::cancelled(e.to_string())),
+            }
+        }
+
+        return self
+            .change_membership(MembershipChange::Remove(req.node_id))
+            .await;
+    }
+
+    async fn transfer_raft_leader(
+        &self,
+        request: Request<TransferRaftLeaderRequest>,
+    ) -> Result<Response<CommonReply>, Status> {
+        let req = request.into_inner();
+
+        if self.rewrite_leader() {
+            let leader_addr = self.placement_cache.read().unwrap().leader_addr();
+            match transfer_raft_leader(self.client_poll.clone(), vec![leader_addr], req).await {
+                Ok(resp) => return Ok(Response::new(resp)),
+                Err(e) => return Err(Status::cancelled(e.to_string())),
+            }
+        }
+
+        match self
+            .placement_center_storage
+            .transfer_leader(req.node_id)
+            .await
+        {
+            Ok(_) => return Ok(Response::new(CommonReply::default())),
+            Err(e) => return Err(Status::cancelled(e.to_string())),
+        }
+    }
+
     async fn set_resource_config(
         &self,
         request: Request<SetResourceConfigRequest>,
@@ -320,4 +640,290 @@ impl PlacementCenterService for GrpcPlacementService {
             }
         }
     }
+
+    async fn acquire_lock(
+        &self,
+        request: Request<AcquireLockRequest>,
+    ) -> Result<Response<AcquireLockReply>, Status> {
+        let mut req = request.into_inner();
+        let holder = self.check_lock_holder(req.holder.take(), req.ttl)?;
+        req.holder = Some(holder.clone());
+
+        let deadline = now_mills() + req.timeout_ms;
+        loop {
+            let taken = self.lock_taken_by_other(&LockKind::Lock, &req.lock_name, &holder)?;
+            let reply = if let Some(lock) = taken {
+                AcquireLockReply {
+                    acquired: false,
+                    fencing_token: lock.fencing_token,
+                    holder: Some(lock.holder()),
+                }
+            } else {
+                req.acquire_time = now_second();
+                let data = StorageData::new(
+                    StorageDataType::LockAcquire,
+                    AcquireLockRequest::encode_to_vec(&req),
+                );
+                let reply = self.propose_lock(data, "acquire_lock").await?;
+                AcquireLockReply::decode(reply.as_ref())
+                    .map_err(|e| Status::internal(e.to_string()))?
+            };
+
+            if reply.acquired || !self.wait_lock_retry(deadline).await {
+                return Ok(Response::new(reply));
+            }
+        }
+    }
+
+    async fn release_lock(
+        &self,
+        request: Request<ReleaseLockRequest>,
+    ) -> Result<Response<CommonReply>, Status> {
+        let req = request.into_inner();
+        if req.holder.is_none() {
+            return Err(Status::invalid_argument("holder cannot be empty"));
+        }
+
+        let data = StorageData::new(
+            StorageDataType::LockRelease,
+            ReleaseLockRequest::encode_to_vec(&req),
+        );
+        self.propose_lock(data, "release_lock").await?;
+        return Ok(Response::new(CommonReply::default()));
+    }
+
+    async fn get_lock(
+        &self,
+        request: Request<GetLockRequest>,
+    ) -> Result<Response<GetLockReply>, Status> {
+        let req = request.into_inner();
+        wait_read_index(&self.placement_cache, &self.placement_center_storage, false).await?;
+
+        let storage = LockStorage::new(self.rocksdb_engine_handler.clone());
+        match storage.get(&LockKind::Lock, &req.lock_name) {
+            Ok(Some(lock)) if lock.held => {
+                return Ok(Response::new(GetLockReply {
+                    held: true,
+                    holder: Some(lock.holder()),
+                    fencing_token: lock.fencing_token,
+                }));
+            }
+            Ok(_) => return Ok(Response::new(GetLockReply::default())),
+            Err(e) => return Err(Status::internal(e.to_string())),
+        }
+    }
+
+    async fn campaign(
+        &self,
+        request: Request<CampaignRequest>,
+    ) -> Result<Response<CampaignReply>, Status> {
+        let mut req = request.into_inner();
+        let candidate = self.check_lock_holder(req.candidate.take(), req.ttl)?;
+        req.candidate = Some(candidate.clone());
+
+        let deadline = now_mills() + req.timeout_ms;
+        loop {
+            let taken =
+                self.lock_taken_by_other(&LockKind::Election, &req.election_name, &candidate)?;
+            let reply = if let Some(lock) = taken {
+                CampaignReply {
+                    elected: false,
+                    term: lock.fencing_token,
+                    leader: Some(lock.holder()),
+                    value: lock.value,
+                }
+            } else {
+                req.acquire_time = now_second();
+                let data = StorageData::new(
+                    StorageDataType::ElectionCampaign,
+                    CampaignRequest::encode_to_vec(&req),
+                );
+                let reply = self.propose_lock(data, "campaign").await?;
+                CampaignReply::decode(reply.as_ref())
+                    .map_err(|e| Status::internal(e.to_string()))?
+            };
+
+            if reply.elected || !self.wait_lock_retry(deadline).await {
+                return Ok(Response::new(reply));
+            }
+        }
+    }
+
+    async fn resign(
+        &self,
+        request: Request<ResignRequest>,
+    ) -> Result<Response<CommonReply>, Status> {
+        let req = request.into_inner();
+        if req.leader.is_none() {
+            return Err(Status::invalid_argument("leader cannot be empty"));
+        }
+
+        let data = StorageData::new(
+            StorageDataType::ElectionResign,
+            ResignRequest::encode_to_vec(&req),
+        );
+        self.propose_lock(data, "resign").await?;
+        return Ok(Response::new(CommonReply::default()));
+    }
+
+    async fn get_election_leader(
+        &self,
+        request: Request<GetElectionLeaderRequest>,
+    ) -> Result<Response<GetElectionLeaderReply>, Status> {
+        let req = request.into_inner();
+        wait_read_index(&self.placement_cache, &self.placement_center_storage, false).await?;
+
+        let storage = LockStorage::new(self.rocksdb_engine_handler.clone());
+        match storage.get(&LockKind::Election, &req.election_name) {
+            Ok(Some(lock)) if lock.held => {
+                return Ok(Response::new(GetElectionLeaderReply {
+                    has_leader: true,
+                    leader: Some(lock.holder()),
+                    value: lock.value,
+                    term: lock.fencing_token,
+                }));
+            }
+            Ok(_) => return Ok(Response::new(GetElectionLeaderReply::default())),
+            Err(e) => return Err(Status::internal(e.to_string())),
+        }
+    }
+
+    async fn create_backup(
+        &self,
+        request: Request<CreateBackupRequest>,
+    ) -> Result<Response<CreateBackupReply>, Status> {
+        let req = request.into_inner();
+        let name = backup_name();
+        let checkpoint_path = self.backup_store.checkpoint_path(&name);
+        let applied_index = match self
+            .placement_center_storage
+            .checkpoint(checkpoint_path)
+            .await
+        {
+            Ok(index) => index,
+            Err(e) => return Err(Status::internal(e.to_string())),
+        };
+
+        // The checkpoint is written out as JSON lines away from the runtime threads
+        let backup_store = self.backup_store.clone();
+        let build_name = name.clone();
+        let key_num = match tokio::task::spawn_blocking(move || {
+            backup_store.build(&build_name, applied_index, &req.cluster_name)
+        })
+        .await
+        {
+            Ok(Ok(num)) => num,
+            Ok(Err(e)) => return Err(Status::internal(e.to_string())),
+            Err(e) => return Err(Status::internal(e.to_string())),
+        };
+        let len = match self.backup_store.len(&name) {
+            Ok(len) => len,
+            Err(e) => return Err(Status::internal(e.to_string())),
+        };
+        self.backup_store.retain();
+
+        return Ok(Response::new(CreateBackupReply {
+            backup_name: name,
+            applied_index,
+            key_num,
+            len,
+        }));
+    }
+
+    async fn read_backup(
+        &self,
+        request: Request<ReadBackupRequest>,
+    ) -> Result<Response<ReadBackupReply>, Status> {
+        let req = request.into_inner();
+        let max_bytes = req.max_bytes.min(BACKUP_READ_MAX_BYTES);
+        match self
+            .backup_store
+            .read_chunk(&req.backup_name, req.offset, max_bytes)
+        {
+            Ok(data) => return Ok(Response::new(ReadBackupReply { data })),
+            Err(e) => return Err(Status::invalid_argument(e.to_string())),
+        }
+    }
+
+    async fn import_metadata(
+        &self,
+        request: Request<ImportMetadataRequest>,
+    ) -> Result<Response<CommonReply>, Status> {
+        let req = request.into_inner();
+        if req.cluster_name.is_empty() {
+            return Err(Status::invalid_argument("cluster_name cannot be empty"));
+        }
+        for entry in req.entries.iter() {
+            if key_cluster_name(&entry.key) != Some(req.cluster_name.as_str()) {
+                return Err(Status::invalid_argument(format!(
+                    "key {} does not belong to cluster [{}]",
+                    entry.key, req.cluster_name
+                )));
+            }
+        }
+
+        // The keys are written in bounded batches, a failed import is sent again as a whole.
+        for entries in import_batches(req.entries, IMPORT_BATCH_MAX_BYTES) {
+            let batch = ImportMetadataRequest {
+                cluster_name: req.cluster_name.clone(),
+                entries,
+            };
+            let data = StorageData::new(
+                StorageDataType::MetadataImport,
+                ImportMetadataRequest::encode_to_vec(&batch),
+            );
+            if let Err(e) = self
+                .placement_center_storage
+                .apply_propose_message(data, "import_metadata".to_string())
+                .await
+            {
+                return Err(Status::cancelled(e.to_string()));
+            }
+        }
+        return Ok(Response::new(CommonReply::default()));
+    }
+}
+
+// Splits the entries into batches of at most max_bytes of keys and values, an entry larger
+// than max_bytes is a batch of its own.
+fn import_batches(entries: Vec<MetadataEntry>, max_bytes: usize) -> Vec<Vec<MetadataEntry>> {
+    let mut batches = Vec::new();
+    let mut batch = Vec::new();
+    let mut batch_bytes = 0;
+    for entry in entries {
+        let entry_bytes = entry.key.len() + entry.value.len();
+        if !batch.is_empty() && batch_bytes + entry_bytes > max_bytes {
+            batches.push(batch);
+            batch = Vec::new();
+            batch_bytes = 0;
+        }
+        batch_bytes += entry_bytes;
+        batch.push(entry);
+    }
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+    return batches;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::import_batches;
+    use protocol::placement_center::generate::placement::MetadataEntry;
+
+    #[test]
+    fn import_batches_test() {
+        let entries: Vec<MetadataEntry> = [4, 4, 10, 2, 2]
+            .iter()
+            .enumerate()
+            .map(|(i, size)| MetadataEntry {
+                key: format!("{}", i),
+                value: vec![0; *size],
+            })
+            .collect();
+        let batches = import_batches(entries, 10);
+        let sizes: Vec<usize> = batches.iter().map(|b| b.len()).collect();
+        assert_eq!(sizes, vec![2, 1, 2]);
+        assert!(import_batches(Vec::new(), 10).is_empty());
+    }
 }
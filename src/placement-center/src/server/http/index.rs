@@ -36,6 +36,8 @@ pub struct RaftInfo {
     pub term: u64,
     pub vote: u64,
     pub commit: u64,
+    pub snapshot_index: u64,
+    pub snapshot_term: u64,
     pub voters: Vec<u64>,
     pub learners: Vec<u64>,
     pub voters_outgoing: Vec<u64>,
@@ -58,6 +60,8 @@ pub async fn index(State(state): State<HttpServerState>) -> String {
         term: hs.term,
         vote: hs.vote,
         commit: hs.commit,
+        snapshot_index: storage.snapshot_metadata.index,
+        snapshot_term: storage.snapshot_metadata.term,
         voters: cs.voters.to_vec(),
         learners: cs.learners.to_vec(),
         voters_outgoing: cs.voters_outgoing.to_vec(),
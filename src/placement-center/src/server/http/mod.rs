@@ -14,6 +14,7 @@
 
 pub mod index;
 pub mod mqtt;
+pub mod page;
 pub mod server;
 pub mod journal;
 
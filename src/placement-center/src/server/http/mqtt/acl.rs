@@ -0,0 +1,117 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{apply_write, wait_read};
+use crate::raft::apply::{StorageData, StorageDataType};
+use crate::server::http::page::{filter_match, paginate};
+use crate::server::http::server::HttpServerState;
+use crate::storage::mqtt::acl::AclStorage;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use common_base::http_response::{error_response_with_msg, success_response};
+use metadata_struct::acl::mqtt_acl::MQTTAcl;
+use prost::Message as _;
+use protocol::placement_center::generate::mqtt::{CreateAclRequest, DeleteAclRequest};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AclListRequest {
+    pub cluster_name: String,
+    // ClientId, User or Ip
+    pub resource_type: Option<String>,
+    // Only returns the acls whose resource name contains it.
+    pub resource_name: Option<String>,
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+    #[serde(default)]
+    pub stale_read: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AclRequest {
+    pub cluster_name: String,
+    pub acl: MQTTAcl,
+}
+
+pub async fn acl_create(
+    State(state): State<HttpServerState>,
+    Json(params): Json<AclRequest>,
+) -> String {
+    let acl = match params.acl.encode() {
+        Ok(acl) => acl,
+        Err(e) => {
+            return error_response_with_msg(e.to_string());
+        }
+    };
+    let req = CreateAclRequest {
+        cluster_name: params.cluster_name,
+        acl,
+    };
+    let data = StorageData::new(
+        StorageDataType::MQTTCreateAcl,
+        CreateAclRequest::encode_to_vec(&req),
+    );
+    return apply_write(&state, data, "create_acl").await;
+}
+
+pub async fn acl_delete(
+    State(state): State<HttpServerState>,
+    Json(params): Json<AclRequest>,
+) -> String {
+    let acl = match params.acl.encode() {
+        Ok(acl) => acl,
+        Err(e) => {
+            return error_response_with_msg(e.to_string());
+        }
+    };
+    let req = DeleteAclRequest {
+        cluster_name: params.cluster_name,
+        acl,
+    };
+    let data = StorageData::new(
+        StorageDataType::MQTTDeleteAcl,
+        DeleteAclRequest::encode_to_vec(&req),
+    );
+    return apply_write(&state, data, "delete_acl").await;
+}
+
+pub async fn acl_list(
+    State(state): State<HttpServerState>,
+    Query(params): Query<AclListRequest>,
+) -> String {
+    if let Err(resp) = wait_read(&state, params.stale_read).await {
+        return resp;
+    }
+    let storage = AclStorage::new(state.rocksdb_engine_handler.clone());
+    match storage.list(&params.cluster_name) {
+        Ok(acls) => {
+            let acls: Vec<MQTTAcl> = acls
+                .into_iter()
+                .filter(|acl| {
+                    if let Some(resource_type) = &params.resource_type {
+                        if acl.resource_type.to_string() != *resource_type {
+                            return false;
+                        }
+                    }
+                    return filter_match(&acl.resource_name, &params.resource_name);
+                })
+                .collect();
+            return success_response(paginate(acls, params.page, params.page_size));
+        }
+        Err(e) => {
+            return error_response_with_msg(e.to_string());
+        }
+    }
+}
@@ -0,0 +1,119 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{apply_write, wait_read};
+use crate::raft::apply::{StorageData, StorageDataType};
+use crate::server::http::page::{filter_match, paginate};
+use crate::server::http::server::HttpServerState;
+use crate::storage::mqtt::blacklist::MQTTBlackListStorage;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use common_base::http_response::{error_response_with_msg, success_response};
+use metadata_struct::acl::mqtt_blacklist::MQTTAclBlackList;
+use prost::Message as _;
+use protocol::placement_center::generate::mqtt::{CreateBlacklistRequest, DeleteBlacklistRequest};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BlacklistListRequest {
+    pub cluster_name: String,
+    // ClientId, User, Ip, ClientIdMatch, UserMatch or IPCIDR
+    pub blacklist_type: Option<String>,
+    // Only returns the blacklists whose resource name contains it.
+    pub resource_name: Option<String>,
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+    #[serde(default)]
+    pub stale_read: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BlacklistCreateRequest {
+    pub cluster_name: String,
+    pub blacklist: MQTTAclBlackList,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BlacklistDeleteRequest {
+    pub cluster_name: String,
+    pub blacklist_type: String,
+    pub resource_name: String,
+}
+
+pub async fn blacklist_create(
+    State(state): State<HttpServerState>,
+    Json(params): Json<BlacklistCreateRequest>,
+) -> String {
+    let blacklist = match serde_json::to_vec(&params.blacklist) {
+        Ok(blacklist) => blacklist,
+        Err(e) => {
+            return error_response_with_msg(e.to_string());
+        }
+    };
+    let req = CreateBlacklistRequest {
+        cluster_name: params.cluster_name,
+        blacklist,
+    };
+    let data = StorageData::new(
+        StorageDataType::MQTTCreateBlacklist,
+        CreateBlacklistRequest::encode_to_vec(&req),
+    );
+    return apply_write(&state, data, "create_blacklist").await;
+}
+
+pub async fn blacklist_delete(
+    State(state): State<HttpServerState>,
+    Json(params): Json<BlacklistDeleteRequest>,
+) -> String {
+    let req = DeleteBlacklistRequest {
+        cluster_name: params.cluster_name,
+        blacklist_type: params.blacklist_type,
+        resource_name: params.resource_name,
+    };
+    let data = StorageData::new(
+        StorageDataType::MQTTDeleteBlacklist,
+        DeleteBlacklistRequest::encode_to_vec(&req),
+    );
+    return apply_write(&state, data, "delete_blacklist").await;
+}
+
+pub async fn blacklist_list(
+    State(state): State<HttpServerState>,
+    Query(params): Query<BlacklistListRequest>,
+) -> String {
+    if let Err(resp) = wait_read(&state, params.stale_read).await {
+        return resp;
+    }
+    let storage = MQTTBlackListStorage::new(state.rocksdb_engine_handler.clone());
+    match storage.list(&params.cluster_name) {
+        Ok(blacklists) => {
+            let blacklists: Vec<MQTTAclBlackList> = blacklists
+                .into_iter()
+                .filter(|blacklist| {
+                    if let Some(blacklist_type) = &params.blacklist_type {
+                        if blacklist.blacklist_type.to_string() != *blacklist_type {
+                            return false;
+                        }
+                    }
+                    return filter_match(&blacklist.resource_name, &params.resource_name);
+                })
+                .collect();
+            return success_response(paginate(blacklists, params.page, params.page_size));
+        }
+        Err(e) => {
+            return error_response_with_msg(e.to_string());
+        }
+    }
+}
@@ -11,16 +11,84 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use super::{apply_write, wait_read};
+use crate::raft::apply::{StorageData, StorageDataType};
 use crate::server::http::server::HttpServerState;
-use axum::extract::State;
-use common_base::http_response::success_response;
+use crate::storage::placement::config::ResourceConfigStorage;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use common_base::http_response::{error_response_with_msg, success_response};
+use metadata_struct::mqtt::cluster::MQTTCluster;
+use prost::Message as _;
+use protocol::placement_center::generate::placement::SetResourceConfigRequest;
+use serde::{Deserialize, Serialize};
 
-pub async fn cluster_get(State(_state): State<HttpServerState>) -> String {
-    return "".to_string();
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ClusterGetRequest {
+    pub cluster_name: String,
+    #[serde(default)]
+    pub stale_read: bool,
 }
 
-pub async fn cluster_set(State(_state): State<HttpServerState>) -> String {
-    return "".to_string();
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ClusterSetRequest {
+    pub cluster_name: String,
+    pub config: MQTTCluster,
+}
+
+// The brokers keep the cluster config under the same resources.
+fn cluster_config_resources(cluster_name: &str) -> Vec<String> {
+    return vec!["cluster".to_string(), cluster_name.to_string()];
+}
+
+pub async fn cluster_get(
+    State(state): State<HttpServerState>,
+    Query(params): Query<ClusterGetRequest>,
+) -> String {
+    if let Err(resp) = wait_read(&state, params.stale_read).await {
+        return resp;
+    }
+    let storage = ResourceConfigStorage::new(state.rocksdb_engine_handler.clone());
+    match storage.get(
+        params.cluster_name.clone(),
+        cluster_config_resources(&params.cluster_name),
+    ) {
+        Ok(Some(data)) => match serde_json::from_slice::<MQTTCluster>(&data) {
+            Ok(cluster) => {
+                return success_response(cluster);
+            }
+            Err(e) => {
+                return error_response_with_msg(e.to_string());
+            }
+        },
+        Ok(None) => {
+            return error_response_with_msg(format!(
+                "the config of cluster {} does not exist",
+                params.cluster_name
+            ));
+        }
+        Err(e) => {
+            return error_response_with_msg(e.to_string());
+        }
+    }
+}
+
+pub async fn cluster_set(
+    State(state): State<HttpServerState>,
+    Json(params): Json<ClusterSetRequest>,
+) -> String {
+    let req = SetResourceConfigRequest {
+        cluster_name: params.cluster_name.clone(),
+        resources: cluster_config_resources(&params.cluster_name),
+        config: params.config.encode(),
+    };
+    let data = StorageData::new(
+        StorageDataType::ClusterSetResourceConfig,
+        SetResourceConfigRequest::encode_to_vec(&req),
+    );
+    return apply_write(&state, data, "set_resource_config").await;
 }
 
 pub async fn cluster_list(State(state): State<HttpServerState>) -> String {
@@ -11,20 +11,36 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-
 use super::{
     path_create, path_delete, path_get, path_list, path_update, server::HttpServerState, v1_path,
 };
+use crate::raft::apply::StorageData;
+use crate::server::grpc::read_index::wait_read_index;
+use acl::{acl_create, acl_delete, acl_list};
 use axum::routing::{delete, get, post, put};
 use axum::Router;
+use blacklist::{blacklist_create, blacklist_delete, blacklist_list};
 use cluster::{cluster_get, cluster_list, cluster_set};
+use common_base::error::placement_center::PlacementCenterError;
+use common_base::http_response::{error_response_with_msg, success_response};
+use session::{session_delete, session_get, session_list};
+use topic::{
+    topic_create, topic_delete, topic_get, topic_list, topic_retain_delete, topic_retain_set,
+};
 use user::{user_create, user_delete, user_get, user_list, user_update};
 
+pub mod acl;
+pub mod blacklist;
 pub mod cluster;
+pub mod session;
+pub mod topic;
 pub mod user;
 
 pub const ROUTE_MQTT_USER: &str = "/mqtt/user";
 pub const ROUTE_MQTT_ACL: &str = "/mqtt/acl";
+pub const ROUTE_MQTT_BLACKLIST: &str = "/mqtt/blacklist";
+pub const ROUTE_MQTT_TOPIC: &str = "/mqtt/topic";
+pub const ROUTE_MQTT_TOPIC_RETAIN: &str = "/mqtt/topic/retain";
 pub const ROUTE_MQTT_SESSION: &str = "/mqtt/session";
 pub const ROUTE_CLUSTER: &str = "/mqtt/cluster";
 
@@ -36,19 +52,93 @@ pub fn mqtt_routes() -> Router<HttpServerState> {
         .route(&v1_path(&path_update(ROUTE_MQTT_USER)), put(user_update))
         .route(&v1_path(&path_delete(ROUTE_MQTT_USER)), delete(user_delete))
         .route(&v1_path(&path_list(ROUTE_MQTT_USER)), get(user_list))
-        
         // acl
-        .route(&v1_path(&path_get(ROUTE_MQTT_ACL)), get(cluster_get))
-        .route(&v1_path(&path_update(ROUTE_MQTT_ACL)), put(cluster_set))
-        .route(&v1_path(&path_list(ROUTE_MQTT_ACL)), get(cluster_list))
-        
+        .route(&v1_path(&path_create(ROUTE_MQTT_ACL)), post(acl_create))
+        .route(&v1_path(&path_delete(ROUTE_MQTT_ACL)), delete(acl_delete))
+        .route(&v1_path(&path_list(ROUTE_MQTT_ACL)), get(acl_list))
+        // blacklist
+        .route(
+            &v1_path(&path_create(ROUTE_MQTT_BLACKLIST)),
+            post(blacklist_create),
+        )
+        .route(
+            &v1_path(&path_delete(ROUTE_MQTT_BLACKLIST)),
+            delete(blacklist_delete),
+        )
+        .route(
+            &v1_path(&path_list(ROUTE_MQTT_BLACKLIST)),
+            get(blacklist_list),
+        )
+        // topic
+        .route(&v1_path(&path_get(ROUTE_MQTT_TOPIC)), get(topic_get))
+        .route(&v1_path(&path_create(ROUTE_MQTT_TOPIC)), post(topic_create))
+        .route(
+            &v1_path(&path_delete(ROUTE_MQTT_TOPIC)),
+            delete(topic_delete),
+        )
+        .route(&v1_path(&path_list(ROUTE_MQTT_TOPIC)), get(topic_list))
+        .route(
+            &v1_path(&path_update(ROUTE_MQTT_TOPIC_RETAIN)),
+            put(topic_retain_set),
+        )
+        .route(
+            &v1_path(&path_delete(ROUTE_MQTT_TOPIC_RETAIN)),
+            delete(topic_retain_delete),
+        )
         // session
-        .route(&v1_path(&path_get(ROUTE_MQTT_SESSION)), get(cluster_get))
-        .route(&v1_path(&path_update(ROUTE_MQTT_SESSION)), put(cluster_set))
-        .route(&v1_path(&path_list(ROUTE_MQTT_SESSION)), get(cluster_list))
-        
+        .route(&v1_path(&path_get(ROUTE_MQTT_SESSION)), get(session_get))
+        .route(
+            &v1_path(&path_delete(ROUTE_MQTT_SESSION)),
+            delete(session_delete),
+        )
+        .route(&v1_path(&path_list(ROUTE_MQTT_SESSION)), get(session_list))
         // cluster
         .route(&v1_path(&path_get(ROUTE_CLUSTER)), get(cluster_get))
         .route(&v1_path(&path_update(ROUTE_CLUSTER)), put(cluster_set))
         .route(&v1_path(&path_list(ROUTE_CLUSTER)), get(cluster_list));
 }
+
+// Reads are linearizable unless the caller accepts stale data, the error is the
+// response to return. Other nodes than the leader answer with the address of the leader.
+pub(crate) async fn wait_read(state: &HttpServerState, stale_read: bool) -> Result<(), String> {
+    match wait_read_index(
+        &state.raft_metadata,
+        &state.placement_center_storage,
+        stale_read,
+    )
+    .await
+    {
+        Ok(()) => return Ok(()),
+        Err(status) => return Err(error_response_with_msg(status.message().to_string())),
+    }
+}
+
+// Only the leader proposes the writes of the HTTP api, the other nodes answer with the
+// address of the leader to send the request to.
+pub(crate) fn check_leader(state: &HttpServerState) -> Result<(), String> {
+    let cache = state.raft_metadata.read().unwrap();
+    if cache.is_leader() {
+        return Ok(());
+    }
+    return Err(error_response_with_msg(
+        PlacementCenterError::NotLeader(cache.local.node_id, cache.leader_addr()).to_string(),
+    ));
+}
+
+pub(crate) async fn apply_write(
+    state: &HttpServerState,
+    data: StorageData,
+    action: &str,
+) -> String {
+    if let Err(e) = check_leader(state) {
+        return e;
+    }
+    match state
+        .placement_center_storage
+        .apply_propose_message(data, action.to_string())
+        .await
+    {
+        Ok(_) => return success_response("success"),
+        Err(e) => return error_response_with_msg(e.to_string()),
+    }
+}
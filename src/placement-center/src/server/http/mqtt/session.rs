@@ -0,0 +1,124 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{apply_write, wait_read};
+use crate::raft::apply::{StorageData, StorageDataType};
+use crate::server::http::page::{filter_match, paginate};
+use crate::server::http::server::HttpServerState;
+use crate::storage::mqtt::session::MQTTSessionStorage;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use common_base::http_response::{error_response_with_msg, success_response};
+use metadata_struct::mqtt::session::MQTTSession;
+use prost::Message as _;
+use protocol::placement_center::generate::mqtt::DeleteSessionRequest;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SessionGetRequest {
+    pub cluster_name: String,
+    pub client_id: String,
+    #[serde(default)]
+    pub stale_read: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SessionListRequest {
+    pub cluster_name: String,
+    // Only returns the sessions whose client id contains it.
+    pub client_id: Option<String>,
+    // Only returns the sessions connected to this broker.
+    pub broker_id: Option<u64>,
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+    #[serde(default)]
+    pub stale_read: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SessionDeleteRequest {
+    pub cluster_name: String,
+    pub client_id: String,
+}
+
+pub async fn session_get(
+    State(state): State<HttpServerState>,
+    Query(params): Query<SessionGetRequest>,
+) -> String {
+    if let Err(resp) = wait_read(&state, params.stale_read).await {
+        return resp;
+    }
+    let storage = MQTTSessionStorage::new(state.rocksdb_engine_handler.clone());
+    match storage.get(&params.cluster_name, &params.client_id) {
+        Ok(Some(session)) => {
+            return success_response(session);
+        }
+        Ok(None) => {
+            return error_response_with_msg(format!("session {} does not exist", params.client_id));
+        }
+        Err(e) => {
+            return error_response_with_msg(e.to_string());
+        }
+    }
+}
+
+pub async fn session_delete(
+    State(state): State<HttpServerState>,
+    Json(params): Json<SessionDeleteRequest>,
+) -> String {
+    let req = DeleteSessionRequest {
+        cluster_name: params.cluster_name,
+        client_id: params.client_id,
+    };
+    let data = StorageData::new(
+        StorageDataType::MQTTDeleteSession,
+        DeleteSessionRequest::encode_to_vec(&req),
+    );
+    return apply_write(&state, data, "delete_session").await;
+}
+
+pub async fn session_list(
+    State(state): State<HttpServerState>,
+    Query(params): Query<SessionListRequest>,
+) -> String {
+    if let Err(resp) = wait_read(&state, params.stale_read).await {
+        return resp;
+    }
+    let storage = MQTTSessionStorage::new(state.rocksdb_engine_handler.clone());
+    let data = match storage.list(&params.cluster_name) {
+        Ok(data) => data,
+        Err(e) => {
+            return error_response_with_msg(e.to_string());
+        }
+    };
+
+    let mut sessions = Vec::new();
+    for raw in data {
+        let session = match serde_json::from_slice::<MQTTSession>(&raw.data) {
+            Ok(session) => session,
+            Err(e) => {
+                return error_response_with_msg(e.to_string());
+            }
+        };
+        if !filter_match(&session.client_id, &params.client_id) {
+            continue;
+        }
+        if params.broker_id.is_some() && session.broker_id != params.broker_id {
+            continue;
+        }
+        sessions.push(session);
+    }
+    return success_response(paginate(sessions, params.page, params.page_size));
+}
@@ -0,0 +1,272 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{apply_write, wait_read};
+use crate::raft::apply::{StorageData, StorageDataType};
+use crate::server::http::page::{filter_match, paginate};
+use crate::server::http::server::HttpServerState;
+use crate::storage::mqtt::topic::MQTTTopicStorage;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use bytes::Bytes;
+use common_base::{
+    http_response::{error_response_with_msg, success_response},
+    tools::{now_second, unique_id},
+};
+use metadata_struct::mqtt::{message::MQTTMessage, topic::MQTTTopic};
+use prost::Message as _;
+use protocol::mqtt::common::QoS;
+use protocol::placement_center::generate::mqtt::{
+    CreateTopicRequest, DeleteTopicRequest, SetTopicRetainMessageRequest,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TopicGetRequest {
+    pub cluster_name: String,
+    pub topic_name: String,
+    #[serde(default)]
+    pub stale_read: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TopicListRequest {
+    pub cluster_name: String,
+    // Only returns the topics whose name contains it.
+    pub topic_name: Option<String>,
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+    #[serde(default)]
+    pub stale_read: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TopicRequest {
+    pub cluster_name: String,
+    pub topic_name: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TopicRetainSetRequest {
+    pub cluster_name: String,
+    pub topic_name: String,
+    pub payload: String,
+    #[serde(default)]
+    pub qos: QoS,
+    // In seconds, as the brokers store it.
+    pub expiry_interval: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TopicInfo {
+    pub topic_id: String,
+    pub topic_name: String,
+    pub retain_message: Option<RetainMessageInfo>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RetainMessageInfo {
+    pub client_id: String,
+    pub qos: QoS,
+    pub payload: String,
+    pub create_time: u64,
+    pub expiry_interval: Option<u64>,
+}
+
+impl TopicInfo {
+    fn build(topic: MQTTTopic) -> Self {
+        let retain_message = match topic.retain_message {
+            Some(data) if !data.is_empty() => match MQTTMessage::decode(&data) {
+                Ok(message) => Some(RetainMessageInfo {
+                    client_id: message.client_id,
+                    qos: message.qos,
+                    payload: String::from_utf8_lossy(&message.payload).to_string(),
+                    create_time: message.create_time,
+                    expiry_interval: topic.retain_message_expired_at,
+                }),
+                Err(_) => None,
+            },
+            _ => None,
+        };
+        return TopicInfo {
+            topic_id: topic.topic_id,
+            topic_name: topic.topic_name,
+            retain_message,
+        };
+    }
+}
+
+pub async fn topic_get(
+    State(state): State<HttpServerState>,
+    Query(params): Query<TopicGetRequest>,
+) -> String {
+    if let Err(resp) = wait_read(&state, params.stale_read).await {
+        return resp;
+    }
+    let storage = MQTTTopicStorage::new(state.rocksdb_engine_handler.clone());
+    match storage.get(&params.cluster_name, &params.topic_name) {
+        Ok(Some(topic)) => {
+            return success_response(TopicInfo::build(topic));
+        }
+        Ok(None) => {
+            return error_response_with_msg(format!("topic {} does not exist", params.topic_name));
+        }
+        Err(e) => {
+            return error_response_with_msg(e.to_string());
+        }
+    }
+}
+
+pub async fn topic_create(
+    State(state): State<HttpServerState>,
+    Json(params): Json<TopicRequest>,
+) -> String {
+    if params.topic_name.is_empty() {
+        return error_response_with_msg("topic name can not be empty".to_string());
+    }
+    if let Err(resp) = wait_read(&state, false).await {
+        return resp;
+    }
+    let storage = MQTTTopicStorage::new(state.rocksdb_engine_handler.clone());
+    match storage.get(&params.cluster_name, &params.topic_name) {
+        Ok(Some(_)) => {
+            return error_response_with_msg(format!("topic {} already exists", params.topic_name));
+        }
+        Ok(None) => {}
+        Err(e) => {
+            return error_response_with_msg(e.to_string());
+        }
+    }
+
+    let topic = MQTTTopic::new(unique_id(), params.topic_name.clone());
+    let req = CreateTopicRequest {
+        cluster_name: params.cluster_name,
+        topic_name: params.topic_name,
+        content: topic.encode(),
+    };
+    let data = StorageData::new(
+        StorageDataType::MQTTCreateTopic,
+        CreateTopicRequest::encode_to_vec(&req),
+    );
+    return apply_write(&state, data, "create_topic").await;
+}
+
+pub async fn topic_delete(
+    State(state): State<HttpServerState>,
+    Json(params): Json<TopicRequest>,
+) -> String {
+    let req = DeleteTopicRequest {
+        cluster_name: params.cluster_name,
+        topic_name: params.topic_name,
+    };
+    let data = StorageData::new(
+        StorageDataType::MQTTDeleteTopic,
+        DeleteTopicRequest::encode_to_vec(&req),
+    );
+    return apply_write(&state, data, "delete_topic").await;
+}
+
+pub async fn topic_list(
+    State(state): State<HttpServerState>,
+    Query(params): Query<TopicListRequest>,
+) -> String {
+    if let Err(resp) = wait_read(&state, params.stale_read).await {
+        return resp;
+    }
+    let storage = MQTTTopicStorage::new(state.rocksdb_engine_handler.clone());
+    match storage.list(&params.cluster_name) {
+        Ok(topics) => {
+            let topics: Vec<TopicInfo> = topics
+                .into_iter()
+                .filter(|topic| filter_match(&topic.topic_name, &params.topic_name))
+                .map(TopicInfo::build)
+                .collect();
+            return success_response(paginate(topics, params.page, params.page_size));
+        }
+        Err(e) => {
+            return error_response_with_msg(e.to_string());
+        }
+    }
+}
+
+pub async fn topic_retain_set(
+    State(state): State<HttpServerState>,
+    Json(params): Json<TopicRetainSetRequest>,
+) -> String {
+    if let Err(resp) = check_topic_exists(&state, &params.cluster_name, &params.topic_name).await {
+        return resp;
+    }
+
+    let mut message = MQTTMessage::default();
+    message.qos = params.qos;
+    message.retain = true;
+    message.topic = Bytes::from(params.topic_name.clone());
+    message.payload = Bytes::from(params.payload);
+    message.expiry_interval = Some(params.expiry_interval as u32);
+    message.create_time = now_second();
+
+    let req = SetTopicRetainMessageRequest {
+        cluster_name: params.cluster_name,
+        topic_name: params.topic_name,
+        retain_message: message.encode(),
+        retain_message_expired_at: params.expiry_interval,
+    };
+    let data = StorageData::new(
+        StorageDataType::MQTTSetTopicRetainMessage,
+        SetTopicRetainMessageRequest::encode_to_vec(&req),
+    );
+    return apply_write(&state, data, "set_topic_retain_message").await;
+}
+
+pub async fn topic_retain_delete(
+    State(state): State<HttpServerState>,
+    Json(params): Json<TopicRequest>,
+) -> String {
+    if let Err(resp) = check_topic_exists(&state, &params.cluster_name, &params.topic_name).await {
+        return resp;
+    }
+
+    // An empty message clears the retained message of the topic.
+    let req = SetTopicRetainMessageRequest {
+        cluster_name: params.cluster_name,
+        topic_name: params.topic_name,
+        retain_message: Vec::new(),
+        retain_message_expired_at: 0,
+    };
+    let data = StorageData::new(
+        StorageDataType::MQTTSetTopicRetainMessage,
+        SetTopicRetainMessageRequest::encode_to_vec(&req),
+    );
+    return apply_write(&state, data, "delete_topic_retain_message").await;
+}
+
+async fn check_topic_exists(
+    state: &HttpServerState,
+    cluster_name: &String,
+    topic_name: &String,
+) -> Result<(), String> {
+    wait_read(state, false).await?;
+    let storage = MQTTTopicStorage::new(state.rocksdb_engine_handler.clone());
+    match storage.get(cluster_name, topic_name) {
+        Ok(Some(_)) => return Ok(()),
+        Ok(None) => {
+            return Err(error_response_with_msg(format!(
+                "topic {} does not exist",
+                topic_name
+            )));
+        }
+        Err(e) => return Err(error_response_with_msg(e.to_string())),
+    }
+}
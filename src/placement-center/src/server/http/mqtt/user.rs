@@ -11,27 +11,212 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-
-use axum::extract::State;
+use super::{apply_write, wait_read};
+use crate::raft::apply::{StorageData, StorageDataType};
+use crate::server::http::page::{filter_match, paginate};
 use crate::server::http::server::HttpServerState;
+use crate::storage::mqtt::user::MQTTUserStorage;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use common_base::http_response::{error_response_with_msg, success_response};
+use metadata_struct::mqtt::user::MQTTUser;
+use prost::Message as _;
+use protocol::placement_center::generate::mqtt::{CreateUserRequest, DeleteUserRequest};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct UserGetRequest {
+    pub cluster_name: String,
+    pub username: String,
+    #[serde(default)]
+    pub stale_read: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct UserListRequest {
+    pub cluster_name: String,
+    // Only returns the users whose name contains it.
+    pub username: Option<String>,
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+    #[serde(default)]
+    pub stale_read: bool,
+}
 
-pub async fn user_get(State(_state): State<HttpServerState>) -> String {
-    return "".to_string();
+#[derive(Clone, Serialize, Deserialize)]
+pub struct UserCreateRequest {
+    pub cluster_name: String,
+    pub username: String,
+    pub password: String,
+    #[serde(default)]
+    pub is_superuser: bool,
 }
 
-pub async fn user_create(State(_state): State<HttpServerState>) -> String {
-    return "".to_string();
+#[derive(Clone, Serialize, Deserialize)]
+pub struct UserUpdateRequest {
+    pub cluster_name: String,
+    pub username: String,
+    pub password: Option<String>,
+    pub is_superuser: Option<bool>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct UserDeleteRequest {
+    pub cluster_name: String,
+    pub username: String,
+}
+
+// The password is never returned.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct UserInfo {
+    pub username: String,
+    pub is_superuser: bool,
+}
+
+impl From<MQTTUser> for UserInfo {
+    fn from(user: MQTTUser) -> Self {
+        return UserInfo {
+            username: user.username,
+            is_superuser: user.is_superuser,
+        };
+    }
+}
+
+pub async fn user_get(
+    State(state): State<HttpServerState>,
+    Query(params): Query<UserGetRequest>,
+) -> String {
+    if let Err(resp) = wait_read(&state, params.stale_read).await {
+        return resp;
+    }
+    let storage = MQTTUserStorage::new(state.rocksdb_engine_handler.clone());
+    match storage.get(&params.cluster_name, &params.username) {
+        Ok(Some(user)) => {
+            return success_response(UserInfo::from(user));
+        }
+        Ok(None) => {
+            return error_response_with_msg(format!("user {} does not exist", params.username));
+        }
+        Err(e) => {
+            return error_response_with_msg(e.to_string());
+        }
+    }
+}
+
+pub async fn user_create(
+    State(state): State<HttpServerState>,
+    Json(params): Json<UserCreateRequest>,
+) -> String {
+    if params.username.is_empty() || params.password.is_empty() {
+        return error_response_with_msg("username and password can not be empty".to_string());
+    }
+    // The existence check needs the latest applied users, not a lagging copy
+    if let Err(resp) = wait_read(&state, false).await {
+        return resp;
+    }
+    let storage = MQTTUserStorage::new(state.rocksdb_engine_handler.clone());
+    match storage.get(&params.cluster_name, &params.username) {
+        Ok(Some(_)) => {
+            return error_response_with_msg(format!("user {} already exists", params.username));
+        }
+        Ok(None) => {}
+        Err(e) => {
+            return error_response_with_msg(e.to_string());
+        }
+    }
+
+    let user = MQTTUser {
+        username: params.username,
+        password: params.password,
+        is_superuser: params.is_superuser,
+    };
+    return save_user(&state, params.cluster_name, user, "create_user").await;
+}
+
+pub async fn user_update(
+    State(state): State<HttpServerState>,
+    Json(params): Json<UserUpdateRequest>,
+) -> String {
+    if let Err(resp) = wait_read(&state, false).await {
+        return resp;
+    }
+    let storage = MQTTUserStorage::new(state.rocksdb_engine_handler.clone());
+    let mut user = match storage.get(&params.cluster_name, &params.username) {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return error_response_with_msg(format!("user {} does not exist", params.username));
+        }
+        Err(e) => {
+            return error_response_with_msg(e.to_string());
+        }
+    };
+
+    if let Some(password) = params.password {
+        if password.is_empty() {
+            return error_response_with_msg("password can not be empty".to_string());
+        }
+        user.password = password;
+    }
+    if let Some(is_superuser) = params.is_superuser {
+        user.is_superuser = is_superuser;
+    }
+    return save_user(&state, params.cluster_name, user, "update_user").await;
 }
 
-pub async fn user_update(State(_state): State<HttpServerState>) -> String {
-    return "".to_string();
+pub async fn user_delete(
+    State(state): State<HttpServerState>,
+    Json(params): Json<UserDeleteRequest>,
+) -> String {
+    let req = DeleteUserRequest {
+        cluster_name: params.cluster_name,
+        user_name: params.username,
+    };
+    let data = StorageData::new(
+        StorageDataType::MQTTDeleteUser,
+        DeleteUserRequest::encode_to_vec(&req),
+    );
+    return apply_write(&state, data, "delete_user").await;
 }
 
-pub async fn user_delete(State(_state): State<HttpServerState>) -> String {
-    return "".to_string();
+pub async fn user_list(
+    State(state): State<HttpServerState>,
+    Query(params): Query<UserListRequest>,
+) -> String {
+    if let Err(resp) = wait_read(&state, params.stale_read).await {
+        return resp;
+    }
+    let storage = MQTTUserStorage::new(state.rocksdb_engine_handler.clone());
+    match storage.list(&params.cluster_name) {
+        Ok(users) => {
+            let users: Vec<UserInfo> = users
+                .into_iter()
+                .filter(|user| filter_match(&user.username, &params.username))
+                .map(UserInfo::from)
+                .collect();
+            return success_response(paginate(users, params.page, params.page_size));
+        }
+        Err(e) => {
+            return error_response_with_msg(e.to_string());
+        }
+    }
 }
 
-pub async fn user_list(State(_state): State<HttpServerState>) -> String {
-    
-    return "".to_string();
+async fn save_user(
+    state: &HttpServerState,
+    cluster_name: String,
+    user: MQTTUser,
+    action: &str,
+) -> String {
+    let req = CreateUserRequest {
+        cluster_name,
+        user_name: user.username.clone(),
+        content: user.encode(),
+    };
+    let data = StorageData::new(
+        StorageDataType::MQTTCreateUser,
+        CreateUserRequest::encode_to_vec(&req),
+    );
+    return apply_write(state, data, action).await;
 }
@@ -0,0 +1,82 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PageReply<T> {
+    pub total: usize,
+    pub page: usize,
+    pub page_size: usize,
+    pub data: Vec<T>,
+}
+
+// Returns the requested page of the list, pages start at 1.
+pub fn paginate<T>(list: Vec<T>, page: Option<usize>, page_size: Option<usize>) -> PageReply<T> {
+    let page = page.unwrap_or(1).max(1);
+    let page_size = page_size
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    let total = list.len();
+    let data = list
+        .into_iter()
+        .skip((page - 1).saturating_mul(page_size))
+        .take(page_size)
+        .collect();
+    return PageReply {
+        total,
+        page,
+        page_size,
+        data,
+    };
+}
+
+// Matches everything when no filter is given.
+pub fn filter_match(value: &str, filter: &Option<String>) -> bool {
+    match filter {
+        Some(filter) => value.contains(filter.as_str()),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{filter_match, paginate, DEFAULT_PAGE_SIZE};
+
+    #[test]
+    fn paginate_test() {
+        let list: Vec<u32> = (0..250).collect();
+
+        let reply = paginate(list.clone(), None, None);
+        assert_eq!(reply.total, 250);
+        assert_eq!(reply.page, 1);
+        assert_eq!(reply.data.len(), DEFAULT_PAGE_SIZE);
+
+        let reply = paginate(list.clone(), Some(3), Some(100));
+        assert_eq!(reply.data, (200..250).collect::<Vec<u32>>());
+
+        let reply = paginate(list.clone(), Some(0), Some(0));
+        assert_eq!(reply.page, 1);
+        assert_eq!(reply.data, vec![0]);
+
+        let reply = paginate(list, Some(10), Some(100));
+        assert!(reply.data.is_empty());
+
+        assert!(filter_match("sensor-1", &Some("sensor".to_string())));
+        assert!(!filter_match("sensor-1", &Some("gateway".to_string())));
+        assert!(filter_match("sensor-1", &None));
+    }
+}
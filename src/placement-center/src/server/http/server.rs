@@ -13,10 +13,10 @@
 
 use super::index::{caches, index, metrics};
 use super::mqtt::mqtt_routes;
-use crate::raft::metadata::RaftGroupMetadata;
+use crate::raft::{apply::RaftMachineApply, metadata::RaftGroupMetadata};
 use crate::{
     cache::{journal::JournalCacheManager, placement::PlacementCacheManager},
-    storage::placement::raft::RaftMachineStorage,
+    storage::{placement::raft::RaftMachineStorage, rocksdb::RocksDBEngine},
 };
 use axum::routing::get;
 use axum::Router;
@@ -37,6 +37,8 @@ pub struct HttpServerState {
     pub raft_storage: Arc<RwLock<RaftMachineStorage>>,
     pub cluster_cache: Arc<PlacementCacheManager>,
     pub engine_cache: Arc<JournalCacheManager>,
+    pub placement_center_storage: Arc<RaftMachineApply>,
+    pub rocksdb_engine_handler: Arc<RocksDBEngine>,
 }
 
 impl HttpServerState {
@@ -45,12 +47,16 @@ impl HttpServerState {
         raft_storage: Arc<RwLock<RaftMachineStorage>>,
         cluster_cache: Arc<PlacementCacheManager>,
         engine_cache: Arc<JournalCacheManager>,
+        placement_center_storage: Arc<RaftMachineApply>,
+        rocksdb_engine_handler: Arc<RocksDBEngine>,
     ) -> Self {
         return Self {
             raft_metadata: placement_cache,
             raft_storage,
             cluster_cache,
             engine_cache,
+            placement_center_storage,
+            rocksdb_engine_handler,
         };
     }
 }
@@ -66,6 +66,90 @@ pub fn engine_prefix_list_by_cluster(
     );
 }
 
+// Same as engine_prefix_list_by_cluster, also returning the key of each value.
+pub fn engine_prefix_list_with_key_by_cluster(
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+    prefix_key_name: String,
+) -> Result<Vec<(String, StorageDataWrap)>, CommonError> {
+    let cf = rocksdb_engine_handler.cf_cluster();
+    let data_list = rocksdb_engine_handler.read_prefix(cf, &prefix_key_name);
+    let mut results = Vec::new();
+    for raw in data_list {
+        for (k, v) in raw {
+            match serde_json::from_slice::<StorageDataWrap>(v.as_ref()) {
+                Ok(v) => results.push((k, v)),
+                Err(_) => {
+                    continue;
+                }
+            }
+        }
+    }
+    return Ok(results);
+}
+
+pub fn engine_prefix_list_from_by_cluster(
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+    prefix_key_name: String,
+    start_key_name: String,
+    limit: usize,
+) -> Result<Vec<StorageDataWrap>, CommonError> {
+    return engine_prefix_list_from(
+        rocksdb_engine_handler,
+        DB_COLUMN_FAMILY_CLUSTER,
+        prefix_key_name,
+        start_key_name,
+        limit,
+    );
+}
+
+pub fn engine_prefix_delete_by_cluster(
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+    prefix_key_name: String,
+) -> Result<(), CommonError> {
+    return engine_prefix_delete(
+        rocksdb_engine_handler,
+        DB_COLUMN_FAMILY_CLUSTER,
+        prefix_key_name,
+    );
+}
+
+// Values saved raw are not wrapped in a StorageDataWrap, they can only be read back with
+// the raw functions.
+pub fn engine_save_raw_by_cluster(
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+    key_name: String,
+    value: Vec<u8>,
+) -> Result<(), CommonError> {
+    let cf = rocksdb_engine_handler.cf_cluster();
+    return rocksdb_engine_handler.write_raw(cf, &key_name, &value);
+}
+
+pub fn engine_get_raw_by_cluster(
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+    key_name: String,
+) -> Result<Option<Vec<u8>>, CommonError> {
+    let cf = rocksdb_engine_handler.cf_cluster();
+    return rocksdb_engine_handler.read_raw(cf, &key_name);
+}
+
+pub fn engine_prefix_list_raw_from_by_cluster(
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+    prefix_key_name: String,
+    start_key_name: String,
+    limit: usize,
+) -> Result<Vec<Vec<u8>>, CommonError> {
+    let cf = rocksdb_engine_handler.cf_cluster();
+    let data_list =
+        rocksdb_engine_handler.read_prefix_from(cf, &prefix_key_name, &start_key_name, limit);
+    let mut results = Vec::new();
+    for raw in data_list {
+        for (_, v) in raw {
+            results.push(v);
+        }
+    }
+    return Ok(results);
+}
+
 fn engine_save<T>(
     rocksdb_engine_handler: Arc<RocksDBEngine>,
     rocksdb_cluster: &str,
@@ -173,3 +257,52 @@ fn engine_prefix_list(
     }
     return Ok(results);
 }
+
+fn engine_prefix_list_from(
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+    rocksdb_cluster: &str,
+    prefix_key_name: String,
+    start_key_name: String,
+    limit: usize,
+) -> Result<Vec<StorageDataWrap>, CommonError> {
+    let cf = if rocksdb_cluster.to_string() == DB_COLUMN_FAMILY_CLUSTER.to_string() {
+        rocksdb_engine_handler.cf_cluster()
+    } else {
+        return Err(CommonError::ClusterNoAvailableNode);
+    };
+
+    let data_list =
+        rocksdb_engine_handler.read_prefix_from(cf, &prefix_key_name, &start_key_name, limit);
+    let mut results = Vec::new();
+    for raw in data_list {
+        for (_, v) in raw {
+            match serde_json::from_slice::<StorageDataWrap>(v.as_ref()) {
+                Ok(v) => results.push(v),
+                Err(_) => {
+                    continue;
+                }
+            }
+        }
+    }
+    return Ok(results);
+}
+
+fn engine_prefix_delete(
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+    rocksdb_cluster: &str,
+    prefix_key_name: String,
+) -> Result<(), CommonError> {
+    let cf = if rocksdb_cluster.to_string() == DB_COLUMN_FAMILY_CLUSTER.to_string() {
+        rocksdb_engine_handler.cf_cluster()
+    } else {
+        return Err(CommonError::ClusterNoAvailableNode);
+    };
+
+    let data_list = rocksdb_engine_handler.read_prefix(cf, &prefix_key_name);
+    for raw in data_list {
+        for (key, _) in raw {
+            rocksdb_engine_handler.delete(cf, &key)?;
+        }
+    }
+    return Ok(());
+}
@@ -31,6 +31,23 @@ pub struct SegmentInfo {
     pub replicas: Vec<Replica>,
     pub replica_leader: u32,
     pub status: SegmentStatus,
+    // The offsets and log bytes of the shard where the segment starts and ends, the end
+    // is only known once the segment is rolled.
+    #[serde(default)]
+    pub start_offset: u64,
+    #[serde(default)]
+    pub end_offset: u64,
+    #[serde(default)]
+    pub start_bytes: u64,
+    // When the segment started to take records.
+    #[serde(default)]
+    pub start_time: u128,
+}
+
+impl SegmentInfo {
+    pub fn leader(&self) -> Option<&Replica> {
+        return self.replicas.get(self.replica_leader as usize);
+    }
 }
 
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
@@ -40,7 +57,7 @@ pub struct Replica {
     pub fold: String,
 }
 
-#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum SegmentStatus {
     #[default]
     Idle,
@@ -49,6 +66,18 @@ pub enum SegmentStatus {
     SealUp,
 }
 
+impl SegmentStatus {
+    pub fn from_name(name: &str) -> Option<SegmentStatus> {
+        match name {
+            "Idle" => return Some(SegmentStatus::Idle),
+            "Write" => return Some(SegmentStatus::Write),
+            "PrepareSealUp" => return Some(SegmentStatus::PrepareSealUp),
+            "SealUp" => return Some(SegmentStatus::SealUp),
+            _ => return None,
+        }
+    }
+}
+
 pub struct SegmentStorage {
     rocksdb_engine_handler: Arc<RocksDBEngine>,
 }
@@ -32,6 +32,14 @@ pub struct ShardInfo {
     pub replica: u32,
     pub last_segment_seq: u64,
     pub create_time: u128,
+    #[serde(default)]
+    pub cleanup_policy: String,
+    #[serde(default)]
+    pub tombstone_retention_ms: u64,
+    #[serde(default)]
+    pub compression: String,
+    #[serde(default)]
+    pub compression_min_size: u64,
 }
 
 pub struct ShardStorage {
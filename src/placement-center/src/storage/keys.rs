@@ -12,6 +12,10 @@
 // limitations under the License.
 
 /** ===========Raft========== */
+pub fn key_name_raft_prefix() -> String {
+    return "/raft/".to_string();
+}
+
 pub fn key_name_by_first_index() -> String {
     return "/raft/first_index".to_string();
 }
@@ -40,6 +44,16 @@ pub fn key_name_snapshot() -> String {
     return "/raft/snapshot".to_string();
 }
 
+// Placement center nodes added to the raft group, kept outside of the raft keys so that
+// snapshots carry them.
+pub fn key_raft_member(node_id: u64) -> String {
+    return format!("/placement/member/{}", node_id);
+}
+
+pub fn key_raft_member_prefix() -> String {
+    return "/placement/member/".to_string();
+}
+
 /** ===========Cluster========== */
 pub fn key_cluster(cluster_type: &String, cluster_name: &String) -> String {
     return format!("/clusters/{}/{}", cluster_type, cluster_name);
@@ -73,6 +87,30 @@ pub fn key_resource_idempotent(cluster_name: &String, produce_id: &String, seq_n
     return format!("/idempotent/{}/{}/{}", cluster_name, produce_id, seq_num);
 }
 
+// The cluster a key of the metadata belongs to, None for the keys shared by all clusters
+// such as the raft log, the kv data and the locks.
+pub fn key_cluster_name(key: &str) -> Option<&str> {
+    let parts: Vec<&str> = key.split('/').collect();
+    let index = match parts.get(1) {
+        Some(&"clusters") | Some(&"journal") | Some(&"mqtt") | Some(&"stream") => 3,
+        Some(&"config") | Some(&"idempotent") => 2,
+        _ => return None,
+    };
+    match parts.get(index) {
+        Some(name) if !name.is_empty() => return Some(name),
+        _ => return None,
+    }
+}
+
+/** ===========Lock========== */
+pub fn key_lock(kind: &str, name: &String) -> String {
+    return format!("/lock/{}/{}", kind, name);
+}
+
+pub fn key_lock_prefix() -> String {
+    return "/lock/".to_string();
+}
+
 /** ===========Journal========== */
 pub fn key_shard(cluster_name: &String, shard_name: &String) -> String {
     return format!("/journal/shard/{}/{}", cluster_name, shard_name);
@@ -100,6 +138,75 @@ pub fn key_segment_shard_prefix(cluster_name: &String, shard_name: &String) -> S
     return format!("/journal/segment/{}/{}", cluster_name, shard_name);
 }
 
+/** ===========Stream========== */
+pub fn key_stream_shard(cluster_name: &String, shard_name: &String) -> String {
+    return format!("/stream/shard/{}/{}", cluster_name, shard_name);
+}
+
+pub fn key_stream_shard_prefix() -> String {
+    return "/stream/shard/".to_string();
+}
+
+pub fn key_stream_record(cluster_name: &String, shard_name: &String, offset: u64) -> String {
+    return format!(
+        "/stream/record/{}/{}/{:020}",
+        cluster_name, shard_name, offset
+    );
+}
+
+pub fn key_stream_record_prefix(cluster_name: &String, shard_name: &String) -> String {
+    return format!("/stream/record/{}/{}/", cluster_name, shard_name);
+}
+
+pub fn key_stream_key_index(cluster_name: &String, shard_name: &String, key: &String) -> String {
+    return format!("/stream/key/{}/{}/{}", cluster_name, shard_name, key);
+}
+
+pub fn key_stream_key_index_prefix(cluster_name: &String, shard_name: &String) -> String {
+    return format!("/stream/key/{}/{}/", cluster_name, shard_name);
+}
+
+pub fn key_stream_group_offset(
+    cluster_name: &String,
+    shard_name: &String,
+    group_id: &String,
+) -> String {
+    return format!("/stream/group/{}/{}/{}", cluster_name, shard_name, group_id);
+}
+
+pub fn key_stream_group_offset_prefix(cluster_name: &String, shard_name: &String) -> String {
+    return format!("/stream/group/{}/{}/", cluster_name, shard_name);
+}
+
+/** ===========KV========== */
+pub fn key_kv_data(key: &String) -> String {
+    return format!("/kv/data/{}", key);
+}
+
+pub fn key_kv_revision() -> String {
+    return "/kv/revision".to_string();
+}
+
+pub fn key_kv_event(revision: u64) -> String {
+    return format!("/kv/event/{:020}", revision);
+}
+
+pub fn key_kv_event_prefix() -> String {
+    return "/kv/event/".to_string();
+}
+
+pub fn key_kv_lease(lease_id: u64) -> String {
+    return format!("/kv/lease/{}", lease_id);
+}
+
+pub fn key_kv_lease_prefix() -> String {
+    return "/kv/lease/".to_string();
+}
+
+pub fn key_kv_lease_seq() -> String {
+    return "/kv/lease_seq".to_string();
+}
+
 /** ===========MQTT========== */
 pub fn storage_key_mqtt_user(cluster_name: &String, user_name: &String) -> String {
     return format!("/mqtt/user/{}/{}", cluster_name, user_name);
@@ -38,9 +38,9 @@ impl MQTTBlackListStorage {
             Ok(data) => {
                 let mut results = Vec::new();
                 for raw in data {
-                    match serde_json::from_slice::<Vec<MQTTAclBlackList>>(&raw.data) {
-                        Ok(acl_list) => {
-                            results.extend(acl_list);
+                    match serde_json::from_slice::<MQTTAclBlackList>(&raw.data) {
+                        Ok(blacklist) => {
+                            results.push(blacklist);
                         }
                         Err(e) => {
                             return Err(e.into());
@@ -65,3 +65,46 @@ impl MQTTBlackListStorage {
         return engine_delete_by_cluster(self.rocksdb_engine_handler.clone(), key);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::MQTTBlackListStorage;
+    use crate::storage::rocksdb::RocksDBEngine;
+    use common_base::{config::placement_center::PlacementCenterConfig, tools::unique_id};
+    use metadata_struct::acl::mqtt_blacklist::{MQTTAclBlackList, MQTTAclBlackListType};
+    use std::{fs::remove_dir_all, sync::Arc};
+
+    #[tokio::test]
+    async fn blacklist_storage_test() {
+        let mut config = PlacementCenterConfig::default();
+        config.data_path = format!("/tmp/tmp_test/{}", unique_id());
+        config.rocksdb.max_open_files = Some(100);
+        let rs = Arc::new(RocksDBEngine::new(&config));
+        let storage = MQTTBlackListStorage::new(rs);
+        let cluster_name = "test_cluster".to_string();
+
+        for resource_name in ["client-1", "client-2"] {
+            let blacklist = MQTTAclBlackList {
+                blacklist_type: MQTTAclBlackListType::ClientId,
+                resource_name: resource_name.to_string(),
+                end_time: 0,
+                desc: "".to_string(),
+            };
+            storage.save(&cluster_name, blacklist).unwrap();
+        }
+        assert_eq!(storage.list(&cluster_name).unwrap().len(), 2);
+
+        storage
+            .delete(
+                &cluster_name,
+                &MQTTAclBlackListType::ClientId.to_string(),
+                &"client-1".to_string(),
+            )
+            .unwrap();
+        let list = storage.list(&cluster_name).unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].resource_name, "client-2");
+
+        remove_dir_all(config.data_path).unwrap();
+    }
+}
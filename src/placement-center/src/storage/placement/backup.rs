@@ -0,0 +1,406 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::storage::{
+    keys::{key_cluster_name, key_name_raft_prefix, key_raft_member_prefix},
+    rocksdb::RocksDBEngine,
+};
+use common_base::{
+    config::placement_center::PlacementCenterConfig,
+    error::common::CommonError,
+    tools::{now_mills, now_second},
+};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+const BACKUP_VERSION: u32 = 1;
+
+// Backups kept on disk, older ones are removed when a new one is created
+const BACKUP_RETAIN_NUM: usize = 3;
+
+// Pairs written to RocksDB in one batch on restore
+const RESTORE_BATCH_NUM: usize = 1000;
+
+pub fn backup_name() -> String {
+    return format!("{:020}", now_mills());
+}
+
+// First line of a backup file, every following line is a BackupEntry.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BackupHeader {
+    pub version: u32,
+    pub applied_index: u64,
+    pub cluster_name: String,
+    pub create_time: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BackupEntry {
+    pub key: String,
+    pub value: String,
+}
+
+// Logical exports of the metadata, taken while the node is running.
+//
+// Like a raft snapshot, a backup is first taken as a RocksDB checkpoint between two applied
+// entries. The metadata of the checkpoint is then written as JSON lines, without the raft
+// log and the raft members, which belong to the cluster the backup was taken on.
+pub struct BackupStore {
+    backup_path: String,
+}
+
+impl BackupStore {
+    pub fn new(data_path: &String) -> Self {
+        let backup_path = format!("{}/{}", data_path, "_backup");
+        if let Err(e) = fs::create_dir_all(&backup_path) {
+            panic!("{}", e);
+        }
+        return BackupStore { backup_path };
+    }
+
+    // The directory the raft machine takes the checkpoint of the backup in
+    pub fn checkpoint_path(&self, name: &str) -> String {
+        return format!("{}/{}.checkpoint", self.backup_path, name);
+    }
+
+    // Writes the backup file from its checkpoint, keeping only the keys of the cluster when
+    // one is given, and returns the number of keys written.
+    pub fn build(
+        &self,
+        name: &str,
+        applied_index: u64,
+        cluster_name: &String,
+    ) -> Result<u64, CommonError> {
+        let checkpoint_path = self.checkpoint_path(name);
+        let temp_path = self.temp_path(name);
+
+        let file = File::create(&temp_path)?;
+        let mut writer = BufWriter::new(file);
+        let header = BackupHeader {
+            version: BACKUP_VERSION,
+            applied_index,
+            cluster_name: cluster_name.clone(),
+            create_time: now_second(),
+        };
+        write_line(&mut writer, &header)?;
+
+        let mut num = 0;
+        RocksDBEngine::read_checkpoint(&checkpoint_path, |key, value| {
+            let key = String::from_utf8(key.to_vec())?;
+            if !is_exported(&key, cluster_name) {
+                return Ok(());
+            }
+            let entry = BackupEntry {
+                key,
+                value: String::from_utf8(value.to_vec())?,
+            };
+            num = num + 1;
+            return write_line(&mut writer, &entry);
+        })?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+
+        fs::rename(&temp_path, self.data_path(name))?;
+        fs::remove_dir_all(&checkpoint_path)?;
+        info!(
+            "Metadata backup {} was created at index {} with {} keys",
+            name, applied_index, num
+        );
+        return Ok(num);
+    }
+
+    pub fn len(&self, name: &str) -> Result<u64, CommonError> {
+        check_name(name)?;
+        return Ok(fs::metadata(self.data_path(name))?.len());
+    }
+
+    pub fn read_chunk(
+        &self,
+        name: &str,
+        offset: u64,
+        max_bytes: u64,
+    ) -> Result<Vec<u8>, CommonError> {
+        check_name(name)?;
+        let path = self.data_path(name);
+        if !Path::new(&path).exists() {
+            return Err(CommonError::CommmonError(format!(
+                "metadata backup {} does not exist",
+                name
+            )));
+        }
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut data = Vec::new();
+        file.take(max_bytes).read_to_end(&mut data)?;
+        return Ok(data);
+    }
+
+    // Removes the files of all backups but the newest ones.
+    pub fn retain(&self) {
+        let dir = match fs::read_dir(&self.backup_path) {
+            Ok(dir) => dir,
+            Err(e) => {
+                error!("Failed to list the metadata backups, {}", e);
+                return;
+            }
+        };
+        let mut names = Vec::new();
+        for entry in dir.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if let Some((name, _)) = file_name.split_once('.') {
+                if !names.contains(&name.to_string()) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        names.sort();
+        if names.len() <= BACKUP_RETAIN_NUM {
+            return;
+        }
+        for name in &names[..names.len() - BACKUP_RETAIN_NUM] {
+            for path in [
+                self.data_path(name),
+                self.temp_path(name),
+                self.checkpoint_path(name),
+            ] {
+                let path = Path::new(&path);
+                let result = if path.is_dir() {
+                    fs::remove_dir_all(path)
+                } else if path.exists() {
+                    fs::remove_file(path)
+                } else {
+                    Ok(())
+                };
+                if let Err(e) = result {
+                    error!("Failed to remove metadata backup {}, {}", name, e);
+                }
+            }
+        }
+    }
+
+    fn data_path(&self, name: &str) -> String {
+        return format!("{}/{}.json", self.backup_path, name);
+    }
+
+    fn temp_path(&self, name: &str) -> String {
+        return format!("{}/{}.tmp", self.backup_path, name);
+    }
+}
+
+// Reads a backup file, keeping only the keys of the cluster when one is given.
+pub fn read_backup_file(
+    path: &str,
+    cluster_name: Option<&String>,
+) -> Result<(BackupHeader, Vec<BackupEntry>), CommonError> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut lines = reader.lines();
+    let header: BackupHeader = match lines.next() {
+        Some(line) => serde_json::from_str(&line?)?,
+        None => {
+            return Err(CommonError::CommmonError(format!(
+                "metadata backup {} is empty",
+                path
+            )))
+        }
+    };
+    if header.version != BACKUP_VERSION {
+        return Err(CommonError::CommmonError(format!(
+            "metadata backup {} has version {}, only version {} is supported",
+            path, header.version, BACKUP_VERSION
+        )));
+    }
+
+    let mut entries = Vec::new();
+    for line in lines {
+        let entry: BackupEntry = serde_json::from_str(&line?)?;
+        if let Some(name) = cluster_name {
+            if key_cluster_name(&entry.key) != Some(name.as_str()) {
+                continue;
+            }
+        }
+        entries.push(entry);
+    }
+    return Ok((header, entries));
+}
+
+// Writes the metadata of a backup into the data directory of a node that never started.
+// Every node of the new cluster is restored from the same backup before its first start,
+// the raft group then starts from the restored metadata.
+pub fn restore_metadata(
+    config: &PlacementCenterConfig,
+    path: &str,
+    cluster_name: Option<&String>,
+) -> Result<u64, CommonError> {
+    let db_path = format!("{}/{}", config.data_path, "_storage_rocksdb");
+    if Path::new(&db_path).exists() {
+        return Err(CommonError::CommmonError(format!(
+            "data directory {} already holds metadata, restore into an empty one",
+            config.data_path
+        )));
+    }
+
+    let (header, entries) = read_backup_file(path, cluster_name)?;
+    let rocksdb_engine_handler = RocksDBEngine::new(config);
+    let num = entries.len() as u64;
+    let mut batch = Vec::new();
+    for entry in entries {
+        batch.push((entry.key.into_bytes(), entry.value.into_bytes()));
+        if batch.len() >= RESTORE_BATCH_NUM {
+            rocksdb_engine_handler.write_batch(rocksdb_engine_handler.cf_cluster(), batch)?;
+            batch = Vec::new();
+        }
+    }
+    rocksdb_engine_handler.write_batch(rocksdb_engine_handler.cf_cluster(), batch)?;
+    info!(
+        "{} keys of the metadata backup taken at index {} were restored",
+        num, header.applied_index
+    );
+    return Ok(num);
+}
+
+fn is_exported(key: &str, cluster_name: &String) -> bool {
+    if key.starts_with(&key_name_raft_prefix()) || key.starts_with(&key_raft_member_prefix()) {
+        return false;
+    }
+    if cluster_name.is_empty() {
+        return true;
+    }
+    return key_cluster_name(key) == Some(cluster_name.as_str());
+}
+
+// Names come from the client, they must not point outside of the backup directory.
+fn check_name(name: &str) -> Result<(), CommonError> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_digit()) {
+        return Err(CommonError::CommmonError(format!(
+            "invalid metadata backup name {}",
+            name
+        )));
+    }
+    return Ok(());
+}
+
+fn write_line<W: Write, T: Serialize>(writer: &mut W, value: &T) -> Result<(), CommonError> {
+    serde_json::to_writer(&mut *writer, value)?;
+    writer.write_all(b"\n")?;
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{backup_name, read_backup_file, restore_metadata, BackupStore};
+    use crate::storage::{
+        engine::{engine_get_by_cluster, engine_save_by_cluster},
+        keys::{
+            key_cluster_name, key_kv_data, key_name_by_last_index, key_stream_record,
+            storage_key_mqtt_user,
+        },
+        rocksdb::RocksDBEngine,
+    };
+    use common_base::{config::placement_center::PlacementCenterConfig, tools::unique_id};
+    use std::{fs::remove_dir_all, sync::Arc};
+
+    fn test_config() -> PlacementCenterConfig {
+        let mut config = PlacementCenterConfig::default();
+        config.data_path = format!("/tmp/tmp_test/{}", unique_id());
+        config.rocksdb.max_open_files = Some(100);
+        return config;
+    }
+
+    #[test]
+    fn cluster_name_of_keys() {
+        let cluster = "tenant-a".to_string();
+        let user = "lobo".to_string();
+        assert_eq!(
+            key_cluster_name(&storage_key_mqtt_user(&cluster, &user)),
+            Some("tenant-a")
+        );
+        assert_eq!(
+            key_cluster_name("/clusters/MQTTBrokerServer/tenant-a"),
+            Some("tenant-a")
+        );
+        assert_eq!(
+            key_cluster_name("/clusters/node/tenant-a/1"),
+            Some("tenant-a")
+        );
+        assert_eq!(key_cluster_name("/config/tenant-a/mqtt"), Some("tenant-a"));
+        assert_eq!(
+            key_cluster_name(&key_stream_record(&cluster, &"shard".to_string(), 1)),
+            Some("tenant-a")
+        );
+        assert_eq!(key_cluster_name(&key_kv_data(&user)), None);
+        assert_eq!(key_cluster_name(&key_name_by_last_index()), None);
+    }
+
+    #[test]
+    fn backup_and_restore() {
+        let config = test_config();
+        let rocksdb_engine_handler = Arc::new(RocksDBEngine::new(&config));
+        let store = BackupStore::new(&config.data_path);
+        let user = "lobo".to_string();
+        for cluster in ["tenant-a", "tenant-b"] {
+            let key = storage_key_mqtt_user(&cluster.to_string(), &user);
+            engine_save_by_cluster(rocksdb_engine_handler.clone(), key, cluster.to_string())
+                .unwrap();
+        }
+        engine_save_by_cluster(rocksdb_engine_handler.clone(), key_kv_data(&user), 1).unwrap();
+        engine_save_by_cluster(rocksdb_engine_handler.clone(), key_name_by_last_index(), 10)
+            .unwrap();
+
+        let name = backup_name();
+        rocksdb_engine_handler
+            .checkpoint(&store.checkpoint_path(&name))
+            .unwrap();
+        // Writes after the checkpoint are not part of the backup
+        engine_save_by_cluster(rocksdb_engine_handler.clone(), key_kv_data(&name), 2).unwrap();
+        assert_eq!(store.build(&name, 10, &"".to_string()).unwrap(), 3);
+        assert!(store.read_chunk("../raft", 0, 10).is_err());
+
+        let path = format!("{}/_backup/{}.json", config.data_path, name);
+        let (header, entries) = read_backup_file(&path, None).unwrap();
+        assert_eq!(header.applied_index, 10);
+        assert_eq!(entries.len(), 3);
+        let data = store
+            .read_chunk(&name, 0, store.len(&name).unwrap())
+            .unwrap();
+        assert_eq!(data.len() as u64, store.len(&name).unwrap());
+
+        // Restoring one cluster into a new node
+        let mut restore_config = test_config();
+        restore_config.data_path = format!("{}/restore", config.data_path);
+        let tenant = "tenant-b".to_string();
+        assert_eq!(
+            restore_metadata(&restore_config, &path, Some(&tenant)).unwrap(),
+            1
+        );
+        assert!(restore_metadata(&restore_config, &path, None).is_err());
+
+        let restored = Arc::new(RocksDBEngine::new(&restore_config));
+        let key = storage_key_mqtt_user(&tenant, &user);
+        let data = engine_get_by_cluster(restored.clone(), key)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            serde_json::from_slice::<String>(&data.data).unwrap(),
+            tenant
+        );
+        let key = storage_key_mqtt_user(&"tenant-a".to_string(), &user);
+        assert!(engine_get_by_cluster(restored, key).unwrap().is_none());
+
+        remove_dir_all(config.data_path).unwrap();
+    }
+}
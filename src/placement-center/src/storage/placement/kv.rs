@@ -14,13 +14,103 @@
 use crate::storage::{
     engine::{
         engine_delete_by_cluster, engine_exists_by_cluster, engine_get_by_cluster,
-        engine_save_by_cluster,
+        engine_prefix_list_by_cluster, engine_prefix_list_from_by_cluster, engine_save_by_cluster,
+    },
+    keys::{
+        key_kv_data, key_kv_event, key_kv_event_prefix, key_kv_lease, key_kv_lease_prefix,
+        key_kv_lease_seq, key_kv_revision,
     },
     rocksdb::RocksDBEngine,
 };
-use common_base::error::common::CommonError;
+use common_base::error::{common::CommonError, placement_center::PlacementCenterError};
+use protocol::placement_center::generate::kv::{
+    CompareAndSwapReply, KvEntry, WatchEvent, WatchEventType,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::sync::Arc;
 
+// Number of the latest events kept for the watchers that start from a past revision
+const KV_EVENT_RETENTION: u64 = 10000;
+
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KvRecord {
+    pub key: String,
+    pub value: String,
+    pub create_revision: u64,
+    pub mod_revision: u64,
+    pub version: u64,
+    pub lease_id: u64,
+}
+
+impl KvRecord {
+    pub fn to_entry(&self) -> KvEntry {
+        return KvEntry {
+            key: self.key.clone(),
+            value: self.value.clone(),
+            create_revision: self.create_revision,
+            mod_revision: self.mod_revision,
+            version: self.version,
+            lease_id: self.lease_id,
+        };
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum KvEventType {
+    Put,
+    Delete,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KvEvent {
+    pub revision: u64,
+    pub event_type: KvEventType,
+    // The record after a put, or as it was before a delete
+    pub record: KvRecord,
+}
+
+impl KvEvent {
+    pub fn matches(&self, key: &String, prefix: bool) -> bool {
+        if prefix {
+            return self.record.key.starts_with(key);
+        }
+        return self.record.key == *key;
+    }
+
+    pub fn to_watch_event(&self) -> WatchEvent {
+        let event_type = match self.event_type {
+            KvEventType::Put => WatchEventType::Put,
+            KvEventType::Delete => WatchEventType::Delete,
+        };
+        return WatchEvent {
+            event_type: event_type.into(),
+            revision: self.revision,
+            entry: Some(self.record.to_entry()),
+        };
+    }
+}
+
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KvLease {
+    pub lease_id: u64,
+    pub ttl: u64,
+    pub keys: Vec<String>,
+}
+
+// Leases are granted and revoked by setting and deleting the key of their record, the
+// value being the ttl. These keys are reserved, the clients cannot set them as data.
+pub fn lease_id_of_key(key: &str) -> Option<u64> {
+    return key
+        .strip_prefix(key_kv_lease_prefix().as_str())
+        .and_then(|id| id.parse::<u64>().ok());
+}
+
+pub fn is_lease_key(key: &str) -> bool {
+    return key.starts_with(key_kv_lease_prefix().as_str());
+}
+
+// Every put and delete is a new revision of the store, numbered from 1. The revisions are
+// assigned while the Raft log is applied, so they are the same on every node.
 pub struct KvStorage {
     rocksdb_engine_handler: Arc<RocksDBEngine>,
 }
@@ -32,32 +122,489 @@ impl KvStorage {
         }
     }
 
-    pub fn set(&self, key: String, value: String) -> Result<(), CommonError> {
-        return engine_save_by_cluster(self.rocksdb_engine_handler.clone(), key, value);
+    pub fn get(&self, key: String) -> Result<Option<String>, CommonError> {
+        match self.get_record(&key)? {
+            Some(record) => return Ok(Some(record.value)),
+            None => return Ok(None),
+        }
     }
 
-    pub fn delete(&self, key: String) -> Result<(), CommonError> {
-        return engine_delete_by_cluster(self.rocksdb_engine_handler.clone(), key);
+    pub fn get_record(&self, key: &String) -> Result<Option<KvRecord>, CommonError> {
+        if let Some(record) = self.read(key_kv_data(key))? {
+            return Ok(Some(record));
+        }
+        return self.legacy_record(key);
     }
 
-    pub fn get(&self, key: String) -> Result<Option<String>, CommonError> {
-        match engine_get_by_cluster(self.rocksdb_engine_handler.clone(), key) {
-            Ok(Some(data)) => match serde_json::from_slice::<String>(&data.data) {
-                Ok(data) => {
-                    return Ok(Some(data));
+    pub fn exists(&self, key: String) -> Result<bool, CommonError> {
+        if engine_exists_by_cluster(self.rocksdb_engine_handler.clone(), key_kv_data(&key))? {
+            return Ok(true);
+        }
+        return Ok(self.legacy_record(&key)?.is_some());
+    }
+
+    // Values set before the keys were namespaced are stored as a JSON string under the raw
+    // key. They are read as a record without revisions, which no compare-and-swap matches,
+    // and are moved under /kv/data/ by the next put or delete of the key. They are not listed.
+    fn legacy_record(&self, key: &String) -> Result<Option<KvRecord>, CommonError> {
+        if key.starts_with("/kv/") {
+            return Ok(None);
+        }
+        let data = match engine_get_by_cluster(self.rocksdb_engine_handler.clone(), key.clone())? {
+            Some(data) => data,
+            None => return Ok(None),
+        };
+        // The raw keys are shared with the other metadata, which is not stored as a string
+        match serde_json::from_slice::<String>(&data.data) {
+            Ok(value) => {
+                return Ok(Some(KvRecord {
+                    key: key.clone(),
+                    value,
+                    version: 1,
+                    ..Default::default()
+                }));
+            }
+            Err(_) => return Ok(None),
+        }
+    }
+
+    // Removes the legacy value of the key once it was moved or deleted
+    fn delete_legacy(&self, record: &KvRecord) -> Result<(), CommonError> {
+        if record.create_revision > 0 {
+            return Ok(());
+        }
+        return engine_delete_by_cluster(self.rocksdb_engine_handler.clone(), record.key.clone());
+    }
+
+    pub fn revision(&self) -> Result<u64, CommonError> {
+        return Ok(self.read::<u64>(key_kv_revision())?.unwrap_or(0));
+    }
+
+    // Sets the key. With an expected revision the key is only set if its mod_revision
+    // still is that revision, 0 expecting the key not to exist.
+    pub fn put(
+        &self,
+        key: &String,
+        value: &String,
+        lease_id: u64,
+        expected_revision: Option<u64>,
+    ) -> Result<CompareAndSwapReply, CommonError> {
+        let current = self.get_record(key)?;
+        if let Some(expected_revision) = expected_revision {
+            let matched = match current.as_ref() {
+                Some(record) => expected_revision > 0 && record.mod_revision == expected_revision,
+                None => expected_revision == 0,
+            };
+            if !matched {
+                return Ok(CompareAndSwapReply {
+                    succeeded: false,
+                    revision: 0,
+                    current: current.map(|r| r.to_entry()),
+                });
+            }
+        }
+        if lease_id > 0 && self.lease_get(lease_id)?.is_none() {
+            return Err(PlacementCenterError::KvLeaseNotFound(lease_id).into());
+        }
+
+        let revision = self.revision()? + 1;
+        let record = match current {
+            Some(current) => {
+                if current.lease_id != lease_id && current.lease_id > 0 {
+                    self.detach_lease(current.lease_id, key)?;
                 }
-                Err(e) => {
-                    return Err(e.into());
+                self.delete_legacy(&current)?;
+                KvRecord {
+                    value: value.clone(),
+                    // A legacy value is created again at this revision
+                    create_revision: if current.create_revision > 0 {
+                        current.create_revision
+                    } else {
+                        revision
+                    },
+                    mod_revision: revision,
+                    version: current.version + 1,
+                    lease_id,
+                    ..current
                 }
+            }
+            None => KvRecord {
+                key: key.clone(),
+                value: value.clone(),
+                create_revision: revision,
+                mod_revision: revision,
+                version: 1,
+                lease_id,
             },
-            Ok(None) => {
-                return Ok(None);
+        };
+        if lease_id > 0 {
+            self.attach_lease(lease_id, key)?;
+        }
+
+        engine_save_by_cluster(
+            self.rocksdb_engine_handler.clone(),
+            key_kv_data(key),
+            record.clone(),
+        )?;
+        self.save_event(KvEvent {
+            revision,
+            event_type: KvEventType::Put,
+            record,
+        })?;
+        return Ok(CompareAndSwapReply {
+            succeeded: true,
+            revision,
+            current: None,
+        });
+    }
+
+    // Returns false when the key does not exist
+    pub fn delete(&self, key: &String) -> Result<bool, CommonError> {
+        let current = match self.get_record(key)? {
+            Some(record) => record,
+            None => return Ok(false),
+        };
+        if current.lease_id > 0 {
+            self.detach_lease(current.lease_id, key)?;
+        }
+        self.delete_legacy(&current)?;
+        engine_delete_by_cluster(self.rocksdb_engine_handler.clone(), key_kv_data(key))?;
+        self.save_event(KvEvent {
+            revision: self.revision()? + 1,
+            event_type: KvEventType::Delete,
+            record: current,
+        })?;
+        return Ok(true);
+    }
+
+    // Lists the keys of the prefix from start_key on and before end_key, when it is set.
+    // Also returns the key the next page starts from, empty when there are no more keys.
+    pub fn list(
+        &self,
+        prefix: &String,
+        start_key: &String,
+        end_key: &String,
+        limit: usize,
+    ) -> Result<(Vec<KvRecord>, String), CommonError> {
+        let start_key = if start_key > prefix {
+            start_key
+        } else {
+            prefix
+        };
+        let data = engine_prefix_list_from_by_cluster(
+            self.rocksdb_engine_handler.clone(),
+            key_kv_data(prefix),
+            key_kv_data(start_key),
+            limit + 1,
+        )?;
+
+        let mut records = Vec::new();
+        for raw in data {
+            let record = serde_json::from_slice::<KvRecord>(&raw.data)?;
+            if !end_key.is_empty() && record.key >= *end_key {
+                break;
+            }
+            if records.len() == limit {
+                return Ok((records, record.key));
             }
-            Err(e) => Err(e),
+            records.push(record);
         }
+        return Ok((records, String::new()));
     }
 
-    pub fn exists(&self, key: String) -> Result<bool, CommonError> {
-        return engine_exists_by_cluster(self.rocksdb_engine_handler.clone(), key);
+    pub fn list_event(
+        &self,
+        start_revision: u64,
+        limit: usize,
+    ) -> Result<Vec<KvEvent>, CommonError> {
+        let data = engine_prefix_list_from_by_cluster(
+            self.rocksdb_engine_handler.clone(),
+            key_kv_event_prefix(),
+            key_kv_event(start_revision),
+            limit,
+        )?;
+        let mut results = Vec::new();
+        for raw in data {
+            results.push(serde_json::from_slice::<KvEvent>(&raw.data)?);
+        }
+        return Ok(results);
+    }
+
+    // The oldest revision whose event is still kept
+    pub fn oldest_revision(&self) -> Result<u64, CommonError> {
+        let revision = self.revision()?;
+        if revision <= KV_EVENT_RETENTION {
+            return Ok(1);
+        }
+        return Ok(revision - KV_EVENT_RETENTION + 1);
+    }
+
+    // The id the leader proposes for the next lease. Ids are not reused after a revoke, so
+    // a late keepalive cannot extend another lease.
+    pub fn next_lease_id(&self) -> Result<u64, CommonError> {
+        return Ok(self.read::<u64>(key_kv_lease_seq())?.unwrap_or(0) + 1);
+    }
+
+    // Creates the lease unless its id is already taken, in which case the reply does not
+    // succeed and the leader proposes the lease again with another id.
+    pub fn lease_create(
+        &self,
+        lease_id: u64,
+        ttl: u64,
+    ) -> Result<CompareAndSwapReply, CommonError> {
+        if lease_id < self.next_lease_id()? {
+            return Ok(CompareAndSwapReply::default());
+        }
+        engine_save_by_cluster(
+            self.rocksdb_engine_handler.clone(),
+            key_kv_lease_seq(),
+            lease_id,
+        )?;
+        self.save_lease(&KvLease {
+            lease_id,
+            ttl,
+            keys: Vec::new(),
+        })?;
+        return Ok(CompareAndSwapReply {
+            succeeded: true,
+            revision: self.revision()?,
+            current: None,
+        });
+    }
+
+    // Deletes the lease and the keys attached to it
+    pub fn lease_revoke(&self, lease_id: u64) -> Result<(), CommonError> {
+        let lease = match self.lease_get(lease_id)? {
+            Some(lease) => lease,
+            None => return Ok(()),
+        };
+        for key in lease.keys.iter() {
+            self.delete(key)?;
+        }
+        return engine_delete_by_cluster(
+            self.rocksdb_engine_handler.clone(),
+            key_kv_lease(lease_id),
+        );
+    }
+
+    pub fn lease_get(&self, lease_id: u64) -> Result<Option<KvLease>, CommonError> {
+        return self.read(key_kv_lease(lease_id));
+    }
+
+    pub fn lease_list(&self) -> Result<Vec<KvLease>, CommonError> {
+        let data = engine_prefix_list_by_cluster(
+            self.rocksdb_engine_handler.clone(),
+            key_kv_lease_prefix(),
+        )?;
+        let mut results = Vec::new();
+        for raw in data {
+            results.push(serde_json::from_slice::<KvLease>(&raw.data)?);
+        }
+        return Ok(results);
+    }
+
+    fn attach_lease(&self, lease_id: u64, key: &String) -> Result<(), CommonError> {
+        if let Some(mut lease) = self.lease_get(lease_id)? {
+            if !lease.keys.contains(key) {
+                lease.keys.push(key.clone());
+                self.save_lease(&lease)?;
+            }
+        }
+        return Ok(());
+    }
+
+    fn detach_lease(&self, lease_id: u64, key: &String) -> Result<(), CommonError> {
+        if let Some(mut lease) = self.lease_get(lease_id)? {
+            lease.keys.retain(|k| k != key);
+            self.save_lease(&lease)?;
+        }
+        return Ok(());
+    }
+
+    fn save_lease(&self, lease: &KvLease) -> Result<(), CommonError> {
+        return engine_save_by_cluster(
+            self.rocksdb_engine_handler.clone(),
+            key_kv_lease(lease.lease_id),
+            lease.clone(),
+        );
+    }
+
+    fn save_event(&self, event: KvEvent) -> Result<(), CommonError> {
+        let revision = event.revision;
+        engine_save_by_cluster(
+            self.rocksdb_engine_handler.clone(),
+            key_kv_event(revision),
+            event,
+        )?;
+        engine_save_by_cluster(
+            self.rocksdb_engine_handler.clone(),
+            key_kv_revision(),
+            revision,
+        )?;
+        if revision > KV_EVENT_RETENTION {
+            engine_delete_by_cluster(
+                self.rocksdb_engine_handler.clone(),
+                key_kv_event(revision - KV_EVENT_RETENTION),
+            )?;
+        }
+        return Ok(());
+    }
+
+    fn read<T: DeserializeOwned>(&self, key: String) -> Result<Option<T>, CommonError> {
+        match engine_get_by_cluster(self.rocksdb_engine_handler.clone(), key)? {
+            Some(data) => return Ok(Some(serde_json::from_slice::<T>(&data.data)?)),
+            None => return Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KvEventType, KvStorage};
+    use crate::storage::{
+        engine::{engine_get_by_cluster, engine_save_by_cluster},
+        rocksdb::RocksDBEngine,
+    };
+    use common_base::{config::placement_center::PlacementCenterConfig, tools::unique_id};
+    use std::{fs::remove_dir_all, sync::Arc};
+
+    fn test_storage() -> (KvStorage, String) {
+        let mut config = PlacementCenterConfig::default();
+        config.data_path = format!("/tmp/tmp_test/{}", unique_id());
+        config.rocksdb.max_open_files = Some(100);
+        let storage = KvStorage::new(Arc::new(RocksDBEngine::new(&config)));
+        return (storage, config.data_path);
+    }
+
+    #[test]
+    fn put_compare_and_delete() {
+        let (storage, data_path) = test_storage();
+        let key = "/lock/a".to_string();
+
+        let reply = storage.put(&key, &"v1".to_string(), 0, Some(0)).unwrap();
+        assert!(reply.succeeded);
+        assert_eq!(reply.revision, 1);
+
+        // The key exists, so it is not created a second time
+        let reply = storage.put(&key, &"v2".to_string(), 0, Some(0)).unwrap();
+        assert!(!reply.succeeded);
+        assert_eq!(reply.current.unwrap().value, "v1");
+
+        let reply = storage.put(&key, &"v2".to_string(), 0, Some(1)).unwrap();
+        assert!(reply.succeeded);
+        let record = storage.get_record(&key).unwrap().unwrap();
+        assert_eq!(record.create_revision, 1);
+        assert_eq!(record.mod_revision, 2);
+        assert_eq!(record.version, 2);
+
+        assert!(storage.delete(&key).unwrap());
+        assert!(!storage.delete(&key).unwrap());
+        assert!(!storage.exists(key.clone()).unwrap());
+
+        let events = storage.list_event(1, 10).unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[2].revision, 3);
+        assert_eq!(events[2].event_type, KvEventType::Delete);
+        assert_eq!(events[2].record.value, "v2");
+
+        remove_dir_all(data_path).unwrap();
+    }
+
+    #[test]
+    fn list_pages_and_range() {
+        let (storage, data_path) = test_storage();
+        for key in ["/a/1", "/a/2", "/a/3", "/b/1"] {
+            storage
+                .put(&key.to_string(), &"v".to_string(), 0, None)
+                .unwrap();
+        }
+
+        let prefix = "/a/".to_string();
+        let (records, next_key) = storage
+            .list(&prefix, &"".to_string(), &"".to_string(), 2)
+            .unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(next_key, "/a/3");
+
+        let (records, next_key) = storage
+            .list(&prefix, &next_key, &"".to_string(), 2)
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].key, "/a/3");
+        assert!(next_key.is_empty());
+
+        let (records, _) = storage
+            .list(&"".to_string(), &"/a/2".to_string(), &"/b/".to_string(), 10)
+            .unwrap();
+        let keys: Vec<String> = records.into_iter().map(|r| r.key).collect();
+        assert_eq!(keys, vec!["/a/2".to_string(), "/a/3".to_string()]);
+
+        remove_dir_all(data_path).unwrap();
+    }
+
+    #[test]
+    fn legacy_value_is_moved_on_put() {
+        let (storage, data_path) = test_storage();
+        let key = "/legacy/a".to_string();
+        engine_save_by_cluster(
+            storage.rocksdb_engine_handler.clone(),
+            key.clone(),
+            "v1".to_string(),
+        )
+        .unwrap();
+
+        assert!(storage.exists(key.clone()).unwrap());
+        assert_eq!(storage.get(key.clone()).unwrap().unwrap(), "v1");
+        // No revision matches a legacy value
+        assert!(
+            !storage
+                .put(&key, &"v2".to_string(), 0, Some(0))
+                .unwrap()
+                .succeeded
+        );
+
+        assert!(
+            storage
+                .put(&key, &"v2".to_string(), 0, None)
+                .unwrap()
+                .succeeded
+        );
+        let record = storage.get_record(&key).unwrap().unwrap();
+        assert_eq!(record.value, "v2");
+        assert_eq!(record.create_revision, 1);
+        assert_eq!(record.version, 2);
+        assert!(
+            engine_get_by_cluster(storage.rocksdb_engine_handler.clone(), key.clone())
+                .unwrap()
+                .is_none()
+        );
+
+        remove_dir_all(data_path).unwrap();
+    }
+
+    #[test]
+    fn lease_revoke_deletes_keys() {
+        let (storage, data_path) = test_storage();
+        assert_eq!(storage.next_lease_id().unwrap(), 1);
+        assert!(storage.lease_create(1, 10).unwrap().succeeded);
+        assert!(!storage.lease_create(1, 10).unwrap().succeeded);
+        let lease = storage.lease_get(1).unwrap().unwrap();
+
+        let key = "/session/a".to_string();
+        storage
+            .put(&key, &"v".to_string(), lease.lease_id, None)
+            .unwrap();
+        assert!(storage.put(&key, &"v".to_string(), 2, None).is_err());
+        assert_eq!(
+            storage.lease_get(lease.lease_id).unwrap().unwrap().keys,
+            vec![key.clone()]
+        );
+
+        storage.lease_revoke(lease.lease_id).unwrap();
+        assert!(storage.get_record(&key).unwrap().is_none());
+        assert!(storage.lease_list().unwrap().is_empty());
+        assert_eq!(storage.revision().unwrap(), 2);
+
+        remove_dir_all(data_path).unwrap();
     }
 }
@@ -0,0 +1,231 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::storage::{
+    engine::{engine_get_by_cluster, engine_prefix_list_by_cluster, engine_save_by_cluster},
+    keys::{key_lock, key_lock_prefix},
+    rocksdb::RocksDBEngine,
+};
+use common_base::error::common::CommonError;
+use protocol::placement_center::generate::placement::LockHolder;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+// An election is a lock whose holder is the leader, the fencing token being its term.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LockKind {
+    Lock,
+    Election,
+}
+
+impl LockKind {
+    fn key_name(&self) -> &str {
+        match self {
+            LockKind::Lock => "lock",
+            LockKind::Election => "election",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LockInfo {
+    pub kind: LockKind,
+    pub name: String,
+    pub held: bool,
+    pub cluster_name: String,
+    pub node_id: u64,
+    pub holder_id: String,
+    pub value: String,
+    pub ttl: u64,
+    // Kept once the lock is released, so that the next holder gets a larger one
+    pub fencing_token: u64,
+    pub acquire_time: u64,
+}
+
+impl LockInfo {
+    pub fn is_held_by(&self, holder: &LockHolder) -> bool {
+        return self.held
+            && self.cluster_name == holder.cluster_name
+            && self.node_id == holder.node_id
+            && self.holder_id == holder.holder_id;
+    }
+
+    pub fn holder(&self) -> LockHolder {
+        return LockHolder {
+            cluster_name: self.cluster_name.clone(),
+            node_id: self.node_id,
+            holder_id: self.holder_id.clone(),
+        };
+    }
+}
+
+pub struct LockStorage {
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+}
+
+impl LockStorage {
+    pub fn new(rocksdb_engine_handler: Arc<RocksDBEngine>) -> Self {
+        LockStorage {
+            rocksdb_engine_handler,
+        }
+    }
+
+    // Returns whether the holder has the lock, and the lock as it is now. Acquiring a lock
+    // the holder already has keeps its fencing token. The acquire time comes with the
+    // request, so that every node applies the same one.
+    pub fn acquire(
+        &self,
+        kind: LockKind,
+        name: &String,
+        holder: &LockHolder,
+        value: &String,
+        ttl: u64,
+        acquire_time: u64,
+    ) -> Result<(bool, LockInfo), CommonError> {
+        let current = self.get(&kind, name)?;
+        let fencing_token = match current {
+            Some(lock) if lock.is_held_by(holder) => lock.fencing_token,
+            Some(lock) if lock.held => return Ok((false, lock)),
+            Some(lock) => lock.fencing_token + 1,
+            None => 1,
+        };
+
+        let lock = LockInfo {
+            kind,
+            name: name.clone(),
+            held: true,
+            cluster_name: holder.cluster_name.clone(),
+            node_id: holder.node_id,
+            holder_id: holder.holder_id.clone(),
+            value: value.clone(),
+            ttl,
+            fencing_token,
+            acquire_time,
+        };
+        self.save(&lock)?;
+        return Ok((true, lock));
+    }
+
+    // Releases the lock if the holder still has it with that fencing token
+    pub fn release(
+        &self,
+        kind: LockKind,
+        name: &String,
+        holder: &LockHolder,
+        fencing_token: u64,
+    ) -> Result<bool, CommonError> {
+        let mut lock = match self.get(&kind, name)? {
+            Some(lock) => lock,
+            None => return Ok(false),
+        };
+        if !lock.is_held_by(holder) || lock.fencing_token != fencing_token {
+            return Ok(false);
+        }
+        lock.held = false;
+        self.save(&lock)?;
+        return Ok(true);
+    }
+
+    pub fn get(&self, kind: &LockKind, name: &String) -> Result<Option<LockInfo>, CommonError> {
+        let key = key_lock(kind.key_name(), name);
+        match engine_get_by_cluster(self.rocksdb_engine_handler.clone(), key)? {
+            Some(data) => return Ok(Some(serde_json::from_slice::<LockInfo>(&data.data)?)),
+            None => return Ok(None),
+        }
+    }
+
+    pub fn list(&self) -> Result<Vec<LockInfo>, CommonError> {
+        let data =
+            engine_prefix_list_by_cluster(self.rocksdb_engine_handler.clone(), key_lock_prefix())?;
+        let mut results = Vec::new();
+        for raw in data {
+            results.push(serde_json::from_slice::<LockInfo>(&raw.data)?);
+        }
+        return Ok(results);
+    }
+
+    fn save(&self, lock: &LockInfo) -> Result<(), CommonError> {
+        return engine_save_by_cluster(
+            self.rocksdb_engine_handler.clone(),
+            key_lock(lock.kind.key_name(), &lock.name),
+            lock.clone(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LockKind, LockStorage};
+    use crate::storage::rocksdb::RocksDBEngine;
+    use common_base::{config::placement_center::PlacementCenterConfig, tools::unique_id};
+    use protocol::placement_center::generate::placement::LockHolder;
+    use std::{fs::remove_dir_all, sync::Arc};
+
+    fn holder(node_id: u64) -> LockHolder {
+        return LockHolder {
+            cluster_name: "cluster".to_string(),
+            node_id,
+            holder_id: "controller".to_string(),
+        };
+    }
+
+    #[test]
+    fn fencing_token_grows_with_every_holder() {
+        let mut config = PlacementCenterConfig::default();
+        config.data_path = format!("/tmp/tmp_test/{}", unique_id());
+        config.rocksdb.max_open_files = Some(100);
+        let storage = LockStorage::new(Arc::new(RocksDBEngine::new(&config)));
+        let name = "journal-controller".to_string();
+        let value = "".to_string();
+
+        let (acquired, lock) = storage
+            .acquire(LockKind::Lock, &name, &holder(1), &value, 10, 100)
+            .unwrap();
+        assert!(acquired);
+        assert_eq!(lock.fencing_token, 1);
+        assert_eq!(lock.acquire_time, 100);
+
+        // Taken by node 1, and acquiring it again keeps the token
+        let (acquired, lock) = storage
+            .acquire(LockKind::Lock, &name, &holder(2), &value, 10, 100)
+            .unwrap();
+        assert!(!acquired);
+        assert_eq!(lock.node_id, 1);
+        let (acquired, lock) = storage
+            .acquire(LockKind::Lock, &name, &holder(1), &value, 10, 100)
+            .unwrap();
+        assert!(acquired);
+        assert_eq!(lock.fencing_token, 1);
+
+        // An election of the same name is another lock
+        let (acquired, _) = storage
+            .acquire(LockKind::Election, &name, &holder(2), &value, 10, 100)
+            .unwrap();
+        assert!(acquired);
+
+        assert!(!storage
+            .release(LockKind::Lock, &name, &holder(2), 1)
+            .unwrap());
+        assert!(storage
+            .release(LockKind::Lock, &name, &holder(1), 1)
+            .unwrap());
+        let (acquired, lock) = storage
+            .acquire(LockKind::Lock, &name, &holder(2), &value, 10, 100)
+            .unwrap();
+        assert!(acquired);
+        assert_eq!(lock.fencing_token, 2);
+        assert_eq!(storage.list().unwrap().len(), 2);
+
+        remove_dir_all(config.data_path).unwrap();
+    }
+}
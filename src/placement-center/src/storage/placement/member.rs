@@ -0,0 +1,91 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::storage::{
+    engine::{engine_delete_by_cluster, engine_prefix_list_by_cluster, engine_save_by_cluster},
+    keys::{key_raft_member, key_raft_member_prefix},
+    rocksdb::RocksDBEngine,
+};
+use common_base::error::common::CommonError;
+use metadata_struct::placement::broker_node::BrokerNode;
+use std::sync::Arc;
+
+// Members of the placement center raft group added through the membership API. Members
+// listed in the configuration are only stored once their membership changes.
+pub struct RaftMemberStorage {
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+}
+
+impl RaftMemberStorage {
+    pub fn new(rocksdb_engine_handler: Arc<RocksDBEngine>) -> Self {
+        RaftMemberStorage {
+            rocksdb_engine_handler,
+        }
+    }
+
+    pub fn save(&self, node: BrokerNode) -> Result<(), CommonError> {
+        let key = key_raft_member(node.node_id);
+        return engine_save_by_cluster(self.rocksdb_engine_handler.clone(), key, node);
+    }
+
+    pub fn delete(&self, node_id: u64) -> Result<(), CommonError> {
+        let key = key_raft_member(node_id);
+        return engine_delete_by_cluster(self.rocksdb_engine_handler.clone(), key);
+    }
+
+    pub fn list(&self) -> Result<Vec<BrokerNode>, CommonError> {
+        let data = engine_prefix_list_by_cluster(
+            self.rocksdb_engine_handler.clone(),
+            key_raft_member_prefix(),
+        )?;
+        let mut results = Vec::new();
+        for raw in data {
+            results.push(serde_json::from_slice::<BrokerNode>(&raw.data)?);
+        }
+        return Ok(results);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RaftMemberStorage;
+    use crate::storage::rocksdb::RocksDBEngine;
+    use common_base::{config::placement_center::PlacementCenterConfig, tools::unique_id};
+    use metadata_struct::placement::broker_node::BrokerNode;
+    use std::{fs::remove_dir_all, sync::Arc};
+
+    #[test]
+    fn member_save_list_delete() {
+        let mut config = PlacementCenterConfig::default();
+        config.data_path = format!("/tmp/tmp_test/{}", unique_id());
+        config.rocksdb.max_open_files = Some(100);
+        let storage = RaftMemberStorage::new(Arc::new(RocksDBEngine::new(&config)));
+
+        for node_id in [1, 2] {
+            let mut node = BrokerNode::default();
+            node.node_id = node_id;
+            node.node_inner_addr = format!("127.0.0.1:{}", 1227 + node_id);
+            storage.save(node).unwrap();
+        }
+        let mut ids: Vec<u64> = storage.list().unwrap().iter().map(|n| n.node_id).collect();
+        ids.sort();
+        assert_eq!(ids, vec![1, 2]);
+
+        storage.delete(1).unwrap();
+        let members = storage.list().unwrap();
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].node_inner_addr, "127.0.0.1:1229");
+
+        remove_dir_all(config.data_path).unwrap();
+    }
+}
@@ -14,6 +14,11 @@
 pub mod cluster;
 pub mod config;
 pub mod kv;
+pub mod member;
 pub mod node;
 pub mod raft;
-pub mod idempotent;
\ No newline at end of file
+pub mod snapshot;
+pub mod idempotent;
+pub mod stream;
+pub mod lock;
+pub mod backup;
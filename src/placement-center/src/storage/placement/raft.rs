@@ -18,8 +18,10 @@ use crate::storage::keys::key_name_by_hard_state;
 use crate::storage::keys::key_name_by_last_index;
 use crate::storage::keys::key_name_snapshot;
 use crate::storage::keys::key_name_uncommit;
+use crate::storage::placement::snapshot::{snapshot_name, SnapshotStore};
 use crate::storage::rocksdb::RocksDBEngine;
 use bincode::{deserialize, serialize};
+use common_base::error::common::CommonError;
 use log::error;
 use log::info;
 use prost::Message as _;
@@ -38,23 +40,41 @@ use std::sync::Arc;
 
 pub struct RaftMachineStorage {
     pub uncommit_index: HashMap<u64, i8>,
-    pub trigger_snap_unavailable: bool,
+    // Set when a follower needs a snapshot newer than the latest one, the next applied
+    // entry then takes a snapshot.
+    pub snapshot_requested: bool,
     pub snapshot_metadata: SnapshotMetadata,
     pub rocksdb_engine_handler: Arc<RocksDBEngine>,
+    pub snapshot_store: Arc<SnapshotStore>,
 }
 
 impl RaftMachineStorage {
-    pub fn new(rocksdb_engine_handler: Arc<RocksDBEngine>) -> Self {
+    pub fn new(
+        rocksdb_engine_handler: Arc<RocksDBEngine>,
+        snapshot_store: Arc<SnapshotStore>,
+    ) -> Self {
         let uncommit_index = HashMap::new();
 
         let mut rc = RaftMachineStorage {
             snapshot_metadata: SnapshotMetadata::default(),
-            trigger_snap_unavailable: false,
+            snapshot_requested: false,
             uncommit_index,
             rocksdb_engine_handler,
+            snapshot_store,
         };
         rc.uncommit_index = rc.uncommit_index();
-        rc.snapshot_metadata = rc.create_snapshot_metadata();
+        let mut snapshot = rc.snapshot();
+        rc.snapshot_metadata = snapshot.take_metadata();
+
+        // A restart may have interrupted the build of the latest snapshot. When its file
+        // cannot be rebuilt, the next applied entry takes a new snapshot.
+        let name = String::from_utf8(snapshot.data.to_vec()).unwrap_or_default();
+        if let Err(e) = rc.snapshot_store.recover(&name) {
+            error!("Failed to recover the raft snapshots, {}", e);
+        }
+        if rc.snapshot_metadata.index > 0 && !rc.snapshot_store.exists(&name) {
+            rc.snapshot_requested = true;
+        }
         return rc;
     }
 
@@ -305,76 +325,60 @@ impl RaftMachineStorage {
         }
         return HashMap::new();
     }
-
-    pub fn write_all(&self, data: &[u8]) {
-        if data.len() == 0 {
-            return;
-        }
-
-        match deserialize::<HashMap<String, Vec<HashMap<String, String>>>>(data) {
-            Ok(data) => {
-                for (_, value) in data {
-                    let cf = self.rocksdb_engine_handler.get_column_family();
-                    for raw in value {
-                        for (key, val) in &raw {
-                            info!("key:{:?},val{:?}", key, val.to_string());
-                            match self
-                                .rocksdb_engine_handler
-                                .write_str(cf, key, val.to_string())
-                            {
-                                Ok(_) => {}
-                                Err(err) => {
-                                    error!(
-                                        "Error occurred during apply snapshot. Error message: {}",
-                                        err
-                                    );
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-            Err(err) => {
-                error!("Failed to parse the snapshot data during snapshot data recovery, error message :{}",err.to_string());
-            }
-        }
-    }
 }
 
 impl RaftMachineStorage {
     /// Overwrites the contents of this Storage object with those of the given snapshot.
     ///
-    /// # Panics
-    ///
-    /// Panics if the snapshot index is less than the storage's first index.
-
-    pub fn apply_snapshot(&mut self, mut snapshot: Snapshot) -> RaftResult<()> {
-        let mut meta = snapshot.take_metadata();
+    /// The application data of the snapshot, which was received from the leader, and the
+    /// raft state matching it replace everything stored in one atomic write.
+    pub fn apply_snapshot(&mut self, snapshot: Snapshot) -> Result<(), CommonError> {
+        let meta = snapshot.get_metadata().clone();
         let index = meta.index;
 
         if self.first_index() > index {
-            return Err(Error::Store(StorageError::SnapshotOutOfDate));
+            return Err(CommonError::CommmonError(
+                Error::Store(StorageError::SnapshotOutOfDate).to_string(),
+            ));
         }
 
-        self.snapshot_metadata = meta.clone();
-
-        // Restore snapshot data to persistent storage
-        self.write_all(snapshot.data.as_ref());
+        let name = String::from_utf8(snapshot.data.to_vec())?;
+        let mut data = self.snapshot_store.read_pairs(&name)?;
 
-        // update HardState
         let mut hs = self.hard_state();
         hs.set_term(cmp::max(hs.term, meta.term));
         hs.set_commit(index);
-        let _ = self.save_hard_state(hs);
+        let uncommit_index: HashMap<u64, i8> = HashMap::new();
+        data.push(raft_value(
+            key_name_by_hard_state(),
+            &HardState::encode_to_vec(&hs),
+        )?);
+        data.push(raft_value(
+            key_name_by_conf_state(),
+            &ConfState::encode_to_vec(meta.get_conf_state()),
+        )?);
+        data.push(raft_value(key_name_by_first_index(), &(index + 1))?);
+        data.push(raft_value(key_name_by_last_index(), &index)?);
+        data.push(raft_value(
+            key_name_snapshot(),
+            &Snapshot::encode_to_vec(&snapshot),
+        )?);
+        data.push(raft_value(
+            key_name_uncommit(),
+            &serialize(&uncommit_index).unwrap(),
+        )?);
 
-        // update ConfState
-        let _ = self.save_conf_state(meta.take_conf_state());
+        self.rocksdb_engine_handler
+            .replace_all(self.rocksdb_engine_handler.cf_cluster(), data)?;
+        self.uncommit_index = uncommit_index;
+        self.snapshot_metadata = meta;
+        self.snapshot_store.retain(&name);
+        info!("Installed raft snapshot {}", name);
         return Ok(());
     }
 
-    // Obtain the Entry based on the index ID
-    pub fn snapshot(&mut self) -> Snapshot {
-        self.create_snapshot();
+    // Returns the latest snapshot
+    pub fn snapshot(&self) -> Snapshot {
         let key = key_name_snapshot();
         let value = self
             .rocksdb_engine_handler
@@ -389,45 +393,86 @@ impl RaftMachineStorage {
         }
     }
 
-    // Example Create a data snapshot for the current system
-    pub fn create_snapshot(&mut self) {
-        let mut sns = Snapshot::default();
+    // Whether the file of the snapshot is ready to be sent to a follower.
+    pub fn snapshot_ready(&self, snapshot: &Snapshot) -> bool {
+        match String::from_utf8(snapshot.data.to_vec()) {
+            Ok(name) => return self.snapshot_store.exists(&name),
+            Err(_) => return false,
+        }
+    }
 
-        // create snapshot metadata
-        let meta = self.create_snapshot_metadata();
-        sns.set_metadata(meta.clone());
+    // Takes a snapshot of the state machine as of the entry at index, which must be the last
+    // applied entry, and removes the raft log up to it except for the last log_tail_entries
+    // entries, so that a follower only a little behind still catches up from the log.
+    // Returns the name of the snapshot, whose file still has to be built from the checkpoint.
+    pub fn create_snapshot(
+        &mut self,
+        index: u64,
+        term: u64,
+        log_tail_entries: u64,
+    ) -> Result<String, CommonError> {
+        let name = snapshot_name(index, term);
+        self.snapshot_store
+            .checkpoint(&self.rocksdb_engine_handler, &name)?;
+
+        let mut meta = SnapshotMetadata::default();
+        meta.set_conf_state(self.conf_state());
+        meta.set_index(index);
+        meta.set_term(term);
 
-        // create snapshot data
+        let mut sns = Snapshot::default();
+        sns.set_metadata(meta.clone());
+        sns.data = name.clone().into_bytes().into();
+        self.save_snapshot_data(sns);
+        self.snapshot_metadata = meta;
+        self.snapshot_requested = false;
 
-        let all_data = self.rocksdb_engine_handler.read_all();
-        sns.set_data(serialize(&all_data).unwrap());
+        if index > log_tail_entries {
+            self.compact(index - log_tail_entries)?;
+        }
+        return Ok(name);
+    }
 
-        // update value
-        let _ = self.save_first_index(meta.get_index());
+    // Removes the entries of the raft log up to and including index.
+    pub fn compact(&mut self, index: u64) -> Result<(), CommonError> {
+        let first_index = self.first_index();
+        if index < first_index {
+            return Ok(());
+        }
 
-        //todo clear < first_index entry log
+        let mut keys = Vec::new();
+        for idx in first_index..=index {
+            keys.push(key_name_by_entry(idx));
+            self.uncommit_index.remove(&idx);
+        }
+        self.rocksdb_engine_handler
+            .delete_batch(self.rocksdb_engine_handler.cf_cluster(), keys)?;
 
-        self.save_snapshot_data(sns);
-        self.snapshot_metadata = meta.clone();
+        self.save_first_index(index + 1)
+            .map_err(CommonError::CommmonError)?;
+        if self.last_index() < index {
+            self.save_last_index(index)
+                .map_err(CommonError::CommmonError)?;
+        }
+        self.save_uncommit_index();
+        info!("Raft log was compacted up to index {}", index);
+        return Ok(());
     }
+}
 
-    pub fn create_snapshot_metadata(&self) -> SnapshotMetadata {
-        let hard_state = self.hard_state();
-        let conf_state = self.conf_state();
-
-        let mut meta: SnapshotMetadata = SnapshotMetadata::default();
-        meta.set_conf_state(conf_state);
-        meta.set_index(hard_state.commit);
-        meta.set_term(hard_state.term);
-        return meta;
-    }
+// Encodes a raft value the way RocksDBEngine::write stores it.
+fn raft_value<T: serde::Serialize>(
+    key: String,
+    value: &T,
+) -> Result<(Vec<u8>, Vec<u8>), CommonError> {
+    return Ok((key.into_bytes(), serde_json::to_vec(value)?));
 }
 
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
 
-    use crate::storage::rocksdb::RocksDBEngine;
+    use crate::storage::{placement::snapshot::SnapshotStore, rocksdb::RocksDBEngine};
 
     use super::RaftMachineStorage;
     use common_base::{
@@ -443,7 +488,8 @@ mod tests {
         init_placement_center_conf_by_config(conf.clone());
         init_placement_center_log();
         let rocksdb_engine_handler: Arc<RocksDBEngine> = Arc::new(RocksDBEngine::new(&conf));
-        let rds = RaftMachineStorage::new(rocksdb_engine_handler);
+        let snapshot_store = Arc::new(SnapshotStore::new(&conf.data_path));
+        let rds = RaftMachineStorage::new(rocksdb_engine_handler, snapshot_store);
 
         let first_index = 1;
         let _ = rds.save_first_index(first_index);
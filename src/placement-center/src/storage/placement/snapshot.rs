@@ -0,0 +1,353 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::storage::{keys::key_name_raft_prefix, rocksdb::RocksDBEngine};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use common_base::error::common::CommonError;
+use log::{error, info};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+// Name of the snapshot taken at a raft index. The index is zero padded so that names sort
+// in the order the snapshots were taken.
+pub fn snapshot_name(index: u64, term: u64) -> String {
+    return format!("{:020}-{}", index, term);
+}
+
+// Files of the snapshots of the raft state machine.
+//
+// A snapshot is first taken as a RocksDB checkpoint, right after the entry at its index was
+// applied. The application data of the checkpoint is then written to a single file of
+// length prefixed key value pairs, which is what is sent to followers in chunks and what a
+// follower installs. The raft log and raft state are not part of the file.
+pub struct SnapshotStore {
+    snapshot_path: String,
+}
+
+impl SnapshotStore {
+    pub fn new(data_path: &String) -> Self {
+        let snapshot_path = format!("{}/{}", data_path, "_raft_snapshot");
+        if let Err(e) = fs::create_dir_all(&snapshot_path) {
+            panic!("{}", e);
+        }
+        return SnapshotStore { snapshot_path };
+    }
+
+    // Takes the checkpoint of the snapshot. It must be called between two applied entries,
+    // the checkpoint then holds the state machine as of the last one.
+    pub fn checkpoint(
+        &self,
+        rocksdb_engine_handler: &RocksDBEngine,
+        name: &str,
+    ) -> Result<(), CommonError> {
+        let path = self.checkpoint_path(name);
+        if Path::new(&path).exists() {
+            fs::remove_dir_all(&path)?;
+        }
+        return rocksdb_engine_handler.checkpoint(&path);
+    }
+
+    // Writes the snapshot file from the checkpoint, then removes the checkpoint. The file
+    // only appears once it is complete.
+    pub fn build(&self, name: &str) -> Result<(), CommonError> {
+        let checkpoint_path = self.checkpoint_path(name);
+        let temp_path = self.temp_path(name);
+        let raft_prefix = key_name_raft_prefix();
+
+        let file = File::create(&temp_path)?;
+        let mut writer = BufWriter::new(file);
+        let mut num = 0;
+        RocksDBEngine::read_checkpoint(&checkpoint_path, |key, value| {
+            if key.starts_with(raft_prefix.as_bytes()) {
+                return Ok(());
+            }
+            num = num + 1;
+            return write_pair(&mut writer, key, value);
+        })?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+
+        fs::rename(&temp_path, self.data_path(name))?;
+        fs::remove_dir_all(&checkpoint_path)?;
+        info!("Raft snapshot {} was built with {} keys", name, num);
+        return Ok(());
+    }
+
+    pub fn exists(&self, name: &str) -> bool {
+        return Path::new(&self.data_path(name)).exists();
+    }
+
+    pub fn len(&self, name: &str) -> Result<u64, CommonError> {
+        return Ok(fs::metadata(self.data_path(name))?.len());
+    }
+
+    pub fn read_chunk(
+        &self,
+        name: &str,
+        offset: u64,
+        max_bytes: u64,
+    ) -> Result<Vec<u8>, CommonError> {
+        let mut file = File::open(self.data_path(name))?;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut data = Vec::new();
+        file.take(max_bytes).read_to_end(&mut data)?;
+        return Ok(data);
+    }
+
+    // Stores a chunk of a snapshot sent by the leader. Chunks arrive in order starting at
+    // offset 0, a chunk that was already stored is accepted again so that it can be retried.
+    pub fn write_chunk(&self, name: &str, offset: u64, data: &[u8]) -> Result<(), CommonError> {
+        check_name(name)?;
+        let path = self.receive_path(name);
+        if offset == 0 {
+            File::create(&path)?;
+        }
+        let stored = match fs::metadata(&path) {
+            Ok(meta) => meta.len(),
+            Err(e) if e.kind() == ErrorKind::NotFound => 0,
+            Err(e) => return Err(e.into()),
+        };
+        if offset > 0 && stored == offset + data.len() as u64 {
+            return Ok(());
+        }
+        if stored != offset {
+            return Err(CommonError::CommmonError(format!(
+                "snapshot {} expects the chunk at offset {}, but the chunk at offset {} was sent",
+                name, stored, offset
+            )));
+        }
+        let mut file = OpenOptions::new().append(true).open(&path)?;
+        file.write_all(data)?;
+        return Ok(());
+    }
+
+    // Makes a snapshot received from the leader available once all its bytes arrived.
+    pub fn finish_receive(&self, name: &str, len: u64) -> Result<(), CommonError> {
+        check_name(name)?;
+        let path = self.receive_path(name);
+        let stored = fs::metadata(&path)?.len();
+        if stored != len {
+            return Err(CommonError::CommmonError(format!(
+                "snapshot {} has {} bytes, but {} bytes were sent",
+                name, stored, len
+            )));
+        }
+        File::open(&path)?.sync_all()?;
+        fs::rename(&path, self.data_path(name))?;
+        return Ok(());
+    }
+
+    pub fn read_pairs(&self, name: &str) -> Result<Vec<(Vec<u8>, Vec<u8>)>, CommonError> {
+        let file = File::open(self.data_path(name))?;
+        let len = file.metadata()?.len();
+        let mut reader = BufReader::new(file);
+        let mut pairs = Vec::new();
+        let mut read = 0;
+        while read < len {
+            let key = read_bytes(&mut reader)?;
+            let value = read_bytes(&mut reader)?;
+            read = read + 8 + key.len() as u64 + value.len() as u64;
+            pairs.push((key, value));
+        }
+        return Ok(pairs);
+    }
+
+    // Removes everything left by the snapshots taken or received before this one.
+    pub fn retain(&self, name: &str) {
+        let dir = match fs::read_dir(&self.snapshot_path) {
+            Ok(dir) => dir,
+            Err(e) => {
+                error!("Failed to list the raft snapshots, {}", e);
+                return;
+            }
+        };
+        for entry in dir.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let snapshot = match file_name.split_once('.') {
+                Some((snapshot, _)) => snapshot.to_string(),
+                None => file_name.clone(),
+            };
+            if snapshot.as_str() >= name {
+                continue;
+            }
+            let path = entry.path();
+            let result = if path.is_dir() {
+                fs::remove_dir_all(&path)
+            } else {
+                fs::remove_file(&path)
+            };
+            if let Err(e) = result {
+                error!("Failed to remove raft snapshot file {}, {}", file_name, e);
+            }
+        }
+    }
+
+    // Finishes what a restart interrupted. The file of the latest snapshot is built from its
+    // checkpoint if it is missing, the other checkpoints and the files of interrupted builds
+    // and receives are removed.
+    pub fn recover(&self, name: &str) -> Result<(), CommonError> {
+        let mut leftovers = Vec::new();
+        for entry in fs::read_dir(&self.snapshot_path)?.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if file_name.ends_with(".checkpoint")
+                || file_name.ends_with(".tmp")
+                || file_name.ends_with(".recv")
+            {
+                leftovers.push(entry.path());
+            }
+        }
+
+        if !name.is_empty() && !self.exists(name) && Path::new(&self.checkpoint_path(name)).exists()
+        {
+            match self.build(name) {
+                Ok(()) => info!("Raft snapshot {} was rebuilt from its checkpoint", name),
+                Err(e) => error!("Failed to rebuild raft snapshot {}, {}", name, e),
+            }
+        }
+
+        for path in leftovers {
+            let result = if path.is_dir() {
+                fs::remove_dir_all(&path)
+            } else if path.exists() {
+                fs::remove_file(&path)
+            } else {
+                continue;
+            };
+            if let Err(e) = result {
+                error!(
+                    "Failed to remove raft snapshot file {}, {}",
+                    path.display(),
+                    e
+                );
+            }
+        }
+        return Ok(());
+    }
+
+    fn checkpoint_path(&self, name: &str) -> String {
+        return format!("{}/{}.checkpoint", self.snapshot_path, name);
+    }
+
+    fn data_path(&self, name: &str) -> String {
+        return format!("{}/{}.snap", self.snapshot_path, name);
+    }
+
+    fn temp_path(&self, name: &str) -> String {
+        return format!("{}/{}.tmp", self.snapshot_path, name);
+    }
+
+    fn receive_path(&self, name: &str) -> String {
+        return format!("{}/{}.recv", self.snapshot_path, name);
+    }
+}
+
+// Names come from the leader, they must not point outside of the snapshot directory.
+fn check_name(name: &str) -> Result<(), CommonError> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_digit() || c == '-') {
+        return Err(CommonError::CommmonError(format!(
+            "invalid raft snapshot name {}",
+            name
+        )));
+    }
+    return Ok(());
+}
+
+fn write_pair<W: Write>(writer: &mut W, key: &[u8], value: &[u8]) -> Result<(), CommonError> {
+    writer.write_u32::<BigEndian>(key.len() as u32)?;
+    writer.write_all(key)?;
+    writer.write_u32::<BigEndian>(value.len() as u32)?;
+    writer.write_all(value)?;
+    return Ok(());
+}
+
+fn read_bytes<R: Read>(reader: &mut R) -> Result<Vec<u8>, CommonError> {
+    let len = reader.read_u32::<BigEndian>()?;
+    let mut data = vec![0; len as usize];
+    reader.read_exact(&mut data)?;
+    return Ok(data);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{snapshot_name, SnapshotStore};
+    use crate::storage::rocksdb::RocksDBEngine;
+    use common_base::{config::placement_center::PlacementCenterConfig, tools::unique_id};
+    use std::{fs::remove_dir_all, path::Path};
+
+    #[test]
+    fn snapshot_build_and_transfer() {
+        let mut config = PlacementCenterConfig::default();
+        config.data_path = format!("/tmp/tmp_test/{}", unique_id());
+        config.rocksdb.max_open_files = Some(100);
+        let rs = RocksDBEngine::new(&config);
+        rs.write_str(rs.cf_cluster(), "/mqtt/user/c1/u1", "u1".to_string())
+            .unwrap();
+        rs.write_str(rs.cf_cluster(), "/journal/shard/c1/s1", "s1".to_string())
+            .unwrap();
+        rs.write_str(rs.cf_cluster(), "/raft/entry/1", "e1".to_string())
+            .unwrap();
+
+        let store = SnapshotStore::new(&config.data_path);
+        let name = snapshot_name(1, 1);
+        store.checkpoint(&rs, &name).unwrap();
+        // Writes after the checkpoint are not part of the snapshot.
+        rs.write_str(rs.cf_cluster(), "/mqtt/user/c1/u2", "u2".to_string())
+            .unwrap();
+        assert!(!store.exists(&name));
+        store.build(&name).unwrap();
+        assert!(store.exists(&name));
+
+        let pairs = store.read_pairs(&name).unwrap();
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(pairs[0].0, b"/journal/shard/c1/s1".to_vec());
+        assert_eq!(pairs[1].0, b"/mqtt/user/c1/u1".to_vec());
+
+        // Sent to a follower in chunks of 7 bytes, with a retried chunk.
+        let receiver = SnapshotStore::new(&format!("{}/follower", config.data_path));
+        let len = store.len(&name).unwrap();
+        let mut offset = 0;
+        while offset < len {
+            let data = store.read_chunk(&name, offset, 7).unwrap();
+            receiver.write_chunk(&name, offset, &data).unwrap();
+            receiver.write_chunk(&name, offset, &data).unwrap();
+            offset = offset + data.len() as u64;
+        }
+        assert!(receiver.write_chunk(&name, offset + 7, b"x").is_err());
+        assert!(receiver.write_chunk("../x", 0, b"x").is_err());
+        receiver.finish_receive(&name, len).unwrap();
+        assert_eq!(receiver.read_pairs(&name).unwrap(), pairs);
+
+        let newer = snapshot_name(10, 1);
+        store.checkpoint(&rs, &newer).unwrap();
+        store.build(&newer).unwrap();
+        store.retain(&newer);
+        assert!(!store.exists(&name));
+        assert!(store.exists(&newer));
+
+        // A build interrupted by a restart is done again, older checkpoints are dropped.
+        let older = snapshot_name(11, 1);
+        let latest = snapshot_name(12, 1);
+        store.checkpoint(&rs, &older).unwrap();
+        store.checkpoint(&rs, &latest).unwrap();
+        let store = SnapshotStore::new(&config.data_path);
+        store.recover(&latest).unwrap();
+        assert!(store.exists(&latest));
+        assert!(!store.exists(&older));
+        assert!(!Path::new(&store.checkpoint_path(&older)).exists());
+
+        remove_dir_all(config.data_path).unwrap();
+    }
+}
@@ -0,0 +1,930 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::storage::{
+    engine::{
+        engine_delete_by_cluster, engine_get_by_cluster, engine_get_raw_by_cluster,
+        engine_prefix_delete_by_cluster, engine_prefix_list_by_cluster,
+        engine_prefix_list_raw_from_by_cluster, engine_prefix_list_with_key_by_cluster,
+        engine_save_by_cluster, engine_save_raw_by_cluster,
+    },
+    keys::{
+        key_stream_group_offset, key_stream_group_offset_prefix, key_stream_key_index,
+        key_stream_key_index_prefix, key_stream_record, key_stream_record_prefix, key_stream_shard,
+        key_stream_shard_prefix,
+    },
+    rocksdb::RocksDBEngine,
+};
+use common_base::{
+    error::{common::CommonError, placement_center::PlacementCenterError},
+    tools::now_second,
+};
+use prost::Message as _;
+use protocol::placement_center::generate::kv::{
+    StreamCleanupPolicy, StreamRecord, StreamShardConfig,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+const DEFAULT_READ_RECORD_NUM: u64 = 10;
+const SCAN_BATCH_SIZE: usize = 100;
+
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StreamShardInfo {
+    pub cluster_name: String,
+    pub shard_name: String,
+    // Offset of the oldest record that has not been trimmed.
+    pub start_offset: u64,
+    pub next_offset: u64,
+    // Number and payload size of the records currently stored in the shard.
+    pub record_num: u64,
+    pub shard_size: u64,
+    pub create_time: u64,
+    // Retention config, a limit of 0 is not enabled.
+    pub retention_sec: u64,
+    pub retention_bytes: u64,
+    pub retention_record_num: u64,
+    pub compact: bool,
+    // The records are compressed by the writers, the config is kept for them.
+    pub compression: String,
+    pub compression_min_size: u64,
+}
+
+impl StreamShardInfo {
+    pub fn set_config(&mut self, config: StreamShardConfig) {
+        self.retention_sec = config.retention_sec;
+        self.retention_bytes = config.retention_bytes;
+        self.retention_record_num = config.retention_record_num;
+        self.compact = config.cleanup_policy == StreamCleanupPolicy::Compact as i32;
+        self.compression = config.compression;
+        self.compression_min_size = config.compression_min_size;
+    }
+
+    pub fn config(&self) -> StreamShardConfig {
+        let cleanup_policy = if self.compact {
+            StreamCleanupPolicy::Compact
+        } else {
+            StreamCleanupPolicy::Delete
+        };
+        return StreamShardConfig {
+            retention_sec: self.retention_sec,
+            retention_bytes: self.retention_bytes,
+            retention_record_num: self.retention_record_num,
+            cleanup_policy: cleanup_policy.into(),
+            compression: self.compression.clone(),
+            compression_min_size: self.compression_min_size,
+        };
+    }
+
+    pub fn is_retention_enabled(&self) -> bool {
+        return self.compact
+            || self.retention_sec > 0
+            || self.retention_bytes > 0
+            || self.retention_record_num > 0;
+    }
+}
+
+pub struct StreamStorage {
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+}
+
+impl StreamStorage {
+    pub fn new(rocksdb_engine_handler: Arc<RocksDBEngine>) -> Self {
+        StreamStorage {
+            rocksdb_engine_handler,
+        }
+    }
+
+    pub fn get_shard(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+    ) -> Result<Option<StreamShardInfo>, CommonError> {
+        let key = key_stream_shard(cluster_name, shard_name);
+        match engine_get_by_cluster(self.rocksdb_engine_handler.clone(), key) {
+            Ok(Some(data)) => match serde_json::from_slice::<StreamShardInfo>(&data.data) {
+                Ok(shard) => {
+                    return Ok(Some(shard));
+                }
+                Err(e) => {
+                    return Err(e.into());
+                }
+            },
+            Ok(None) => {
+                return Ok(None);
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save_shard(&self, shard: StreamShardInfo) -> Result<(), CommonError> {
+        let key = key_stream_shard(&shard.cluster_name, &shard.shard_name);
+        return engine_save_by_cluster(self.rocksdb_engine_handler.clone(), key, shard);
+    }
+
+    pub fn list_shards(&self) -> Result<Vec<StreamShardInfo>, CommonError> {
+        let data = engine_prefix_list_by_cluster(
+            self.rocksdb_engine_handler.clone(),
+            key_stream_shard_prefix(),
+        )?;
+        let mut results = Vec::new();
+        for raw in data {
+            results.push(serde_json::from_slice::<StreamShardInfo>(&raw.data)?);
+        }
+        return Ok(results);
+    }
+
+    // Creating a shard that already exists only updates its config.
+    pub fn create_shard(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+        config: StreamShardConfig,
+    ) -> Result<(), CommonError> {
+        let mut shard = match self.get_shard(cluster_name, shard_name)? {
+            Some(shard) => shard,
+            None => StreamShardInfo {
+                cluster_name: cluster_name.clone(),
+                shard_name: shard_name.clone(),
+                create_time: now_second(),
+                ..Default::default()
+            },
+        };
+        shard.set_config(config);
+        return self.save_shard(shard);
+    }
+
+    pub fn delete_shard(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+    ) -> Result<(), CommonError> {
+        engine_prefix_delete_by_cluster(
+            self.rocksdb_engine_handler.clone(),
+            key_stream_record_prefix(cluster_name, shard_name),
+        )?;
+        engine_prefix_delete_by_cluster(
+            self.rocksdb_engine_handler.clone(),
+            key_stream_key_index_prefix(cluster_name, shard_name),
+        )?;
+        engine_prefix_delete_by_cluster(
+            self.rocksdb_engine_handler.clone(),
+            key_stream_group_offset_prefix(cluster_name, shard_name),
+        )?;
+        return engine_delete_by_cluster(
+            self.rocksdb_engine_handler.clone(),
+            key_stream_shard(cluster_name, shard_name),
+        );
+    }
+
+    // Append records to the shard. Offsets are assigned from the shard's next offset,
+    // so every replica applying the same Raft log assigns the same offsets.
+    // The limits are checked here too, a limit of 0 is not enabled.
+    pub fn write(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+        records: Vec<StreamRecord>,
+        max_record_num: u64,
+        max_size: u64,
+    ) -> Result<Vec<u64>, CommonError> {
+        let mut shard = match self.get_shard(cluster_name, shard_name)? {
+            Some(shard) => shard,
+            None => {
+                return Err(PlacementCenterError::ShardDoesNotExist(shard_name.clone()).into());
+            }
+        };
+
+        let write_size: u64 = records.iter().map(|r| r.data.len() as u64).sum();
+        if max_record_num > 0 && shard.record_num + records.len() as u64 > max_record_num {
+            return Err(PlacementCenterError::ShardExceedsStreamStorageLimit(
+                shard_name.clone(),
+                "record num".to_string(),
+                max_record_num,
+            )
+            .into());
+        }
+        if max_size > 0 && shard.shard_size + write_size > max_size {
+            return Err(PlacementCenterError::ShardExceedsStreamStorageLimit(
+                shard_name.clone(),
+                "shard size".to_string(),
+                max_size,
+            )
+            .into());
+        }
+
+        let mut offsets = Vec::new();
+        for mut record in records {
+            record.offset = shard.next_offset;
+            let key = key_stream_record(cluster_name, shard_name, record.offset);
+            engine_save_raw_by_cluster(
+                self.rocksdb_engine_handler.clone(),
+                key,
+                StreamRecord::encode_to_vec(&record),
+            )?;
+
+            if !record.key.is_empty() {
+                let index_key = key_stream_key_index(cluster_name, shard_name, &record.key);
+                engine_save_by_cluster(
+                    self.rocksdb_engine_handler.clone(),
+                    index_key,
+                    record.offset,
+                )?;
+            }
+
+            offsets.push(record.offset);
+            shard.next_offset += 1;
+            shard.record_num += 1;
+            shard.shard_size += record.data.len() as u64;
+        }
+
+        self.save_shard(shard)?;
+        return Ok(offsets);
+    }
+
+    // Remove the records whose offset is lower than end_offset, the record num and size
+    // of the shard only count the records that are left.
+    pub fn trim(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+        end_offset: u64,
+    ) -> Result<u64, CommonError> {
+        let mut shard = match self.get_shard(cluster_name, shard_name)? {
+            Some(shard) => shard,
+            None => return Ok(0),
+        };
+        let end_offset = end_offset.min(shard.next_offset);
+        if end_offset <= shard.start_offset {
+            return Ok(0);
+        }
+
+        let mut trim_num = 0;
+        let mut start_offset = shard.start_offset;
+        while start_offset < end_offset {
+            let data = engine_prefix_list_raw_from_by_cluster(
+                self.rocksdb_engine_handler.clone(),
+                key_stream_record_prefix(cluster_name, shard_name),
+                key_stream_record(cluster_name, shard_name, start_offset),
+                SCAN_BATCH_SIZE,
+            )?;
+            if data.is_empty() {
+                break;
+            }
+
+            for raw in data {
+                let record = StreamRecord::decode(raw.as_ref())?;
+                if record.offset >= end_offset {
+                    start_offset = end_offset;
+                    break;
+                }
+                self.remove_record(cluster_name, shard_name, &record)?;
+                shard.record_num = shard.record_num.saturating_sub(1);
+                shard.shard_size = shard.shard_size.saturating_sub(record.data.len() as u64);
+                trim_num += 1;
+                start_offset = record.offset + 1;
+            }
+        }
+
+        shard.start_offset = end_offset;
+        self.save_shard(shard)?;
+        return Ok(trim_num);
+    }
+
+    // Remove the records whose key was written again by a later record, the records
+    // without a key are kept.
+    pub fn compact(&self, cluster_name: &String, shard_name: &String) -> Result<u64, CommonError> {
+        let mut shard = match self.get_shard(cluster_name, shard_name)? {
+            Some(shard) => shard,
+            None => return Ok(0),
+        };
+
+        let mut compact_num = 0;
+        let mut start_offset = shard.start_offset;
+        loop {
+            let data = engine_prefix_list_raw_from_by_cluster(
+                self.rocksdb_engine_handler.clone(),
+                key_stream_record_prefix(cluster_name, shard_name),
+                key_stream_record(cluster_name, shard_name, start_offset),
+                SCAN_BATCH_SIZE,
+            )?;
+            if data.is_empty() {
+                break;
+            }
+
+            for raw in data {
+                let record = StreamRecord::decode(raw.as_ref())?;
+                start_offset = record.offset + 1;
+                if record.key.is_empty()
+                    || self.key_offset(cluster_name, shard_name, &record.key)?
+                        == Some(record.offset)
+                {
+                    continue;
+                }
+                self.remove_record(cluster_name, shard_name, &record)?;
+                shard.record_num = shard.record_num.saturating_sub(1);
+                shard.shard_size = shard.shard_size.saturating_sub(record.data.len() as u64);
+                compact_num += 1;
+            }
+        }
+
+        if compact_num > 0 {
+            self.save_shard(shard)?;
+        }
+        return Ok(compact_num);
+    }
+
+    // The offset the shard has to be trimmed to so that its records are within the
+    // retention limits, the oldest records are removed first.
+    pub fn retention_end_offset(
+        &self,
+        shard: &StreamShardInfo,
+        now: u64,
+    ) -> Result<u64, CommonError> {
+        let expire_time = now.saturating_sub(shard.retention_sec);
+        let mut record_num = shard.record_num;
+        let mut shard_size = shard.shard_size;
+        let mut end_offset = shard.start_offset;
+        loop {
+            let data = engine_prefix_list_raw_from_by_cluster(
+                self.rocksdb_engine_handler.clone(),
+                key_stream_record_prefix(&shard.cluster_name, &shard.shard_name),
+                key_stream_record(&shard.cluster_name, &shard.shard_name, end_offset),
+                SCAN_BATCH_SIZE,
+            )?;
+            if data.is_empty() {
+                return Ok(end_offset);
+            }
+
+            for raw in data {
+                let record = StreamRecord::decode(raw.as_ref())?;
+                let expired = shard.retention_sec > 0 && record.create_time < expire_time;
+                let over_num =
+                    shard.retention_record_num > 0 && record_num > shard.retention_record_num;
+                let over_size = shard.retention_bytes > 0 && shard_size > shard.retention_bytes;
+                if !expired && !over_num && !over_size {
+                    return Ok(end_offset);
+                }
+                record_num = record_num.saturating_sub(1);
+                shard_size = shard_size.saturating_sub(record.data.len() as u64);
+                end_offset = record.offset + 1;
+            }
+        }
+    }
+
+    pub fn read(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+        start_offset: u64,
+        record_num: u64,
+        record_size: u64,
+    ) -> Result<Vec<StreamRecord>, CommonError> {
+        let record_num = if record_num == 0 {
+            DEFAULT_READ_RECORD_NUM
+        } else {
+            record_num
+        };
+        let data = engine_prefix_list_raw_from_by_cluster(
+            self.rocksdb_engine_handler.clone(),
+            key_stream_record_prefix(cluster_name, shard_name),
+            key_stream_record(cluster_name, shard_name, start_offset),
+            record_num as usize,
+        )?;
+
+        let mut results = Vec::new();
+        let mut total_size = 0;
+        for raw in data {
+            let record = StreamRecord::decode(raw.as_ref())?;
+            total_size += record.data.len() as u64;
+            // Always return at least one record so that a single large record
+            // cannot block the reader.
+            if record_size > 0 && total_size > record_size && !results.is_empty() {
+                break;
+            }
+            results.push(record);
+        }
+        return Ok(results);
+    }
+
+    pub fn read_by_offset(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+        offset: u64,
+    ) -> Result<Option<StreamRecord>, CommonError> {
+        let key = key_stream_record(cluster_name, shard_name, offset);
+        match engine_get_raw_by_cluster(self.rocksdb_engine_handler.clone(), key)? {
+            Some(data) => {
+                return Ok(Some(StreamRecord::decode(data.as_ref())?));
+            }
+            None => {
+                return Ok(None);
+            }
+        }
+    }
+
+    pub fn read_by_timestamp(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+        start_timestamp: u64,
+        end_timestamp: u64,
+        record_num: u64,
+        record_size: u64,
+    ) -> Result<Vec<StreamRecord>, CommonError> {
+        let record_num = if record_num == 0 {
+            DEFAULT_READ_RECORD_NUM
+        } else {
+            record_num
+        };
+
+        let mut start_offset = match self.get_shard(cluster_name, shard_name)? {
+            Some(shard) => shard.start_offset,
+            None => return Ok(Vec::new()),
+        };
+        let mut results = Vec::new();
+        let mut total_size = 0;
+        loop {
+            let data = engine_prefix_list_raw_from_by_cluster(
+                self.rocksdb_engine_handler.clone(),
+                key_stream_record_prefix(cluster_name, shard_name),
+                key_stream_record(cluster_name, shard_name, start_offset),
+                SCAN_BATCH_SIZE,
+            )?;
+            if data.is_empty() {
+                return Ok(results);
+            }
+
+            for raw in data {
+                let record = StreamRecord::decode(raw.as_ref())?;
+                start_offset = record.offset + 1;
+                if record.create_time < start_timestamp || record.create_time > end_timestamp {
+                    continue;
+                }
+
+                total_size += record.data.len() as u64;
+                if record_size > 0 && total_size > record_size && !results.is_empty() {
+                    return Ok(results);
+                }
+                results.push(record);
+                if results.len() as u64 >= record_num {
+                    return Ok(results);
+                }
+            }
+        }
+    }
+
+    pub fn read_by_key(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+        key: &String,
+    ) -> Result<Option<StreamRecord>, CommonError> {
+        match self.key_offset(cluster_name, shard_name, key)? {
+            Some(offset) => {
+                return self.read_by_offset(cluster_name, shard_name, offset);
+            }
+            None => {
+                return Ok(None);
+            }
+        }
+    }
+
+    pub fn commit_offset(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+        group_id: &String,
+        offset: u64,
+    ) -> Result<(), CommonError> {
+        let key = key_stream_group_offset(cluster_name, shard_name, group_id);
+        return engine_save_by_cluster(self.rocksdb_engine_handler.clone(), key, offset);
+    }
+
+    pub fn get_group_offset(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+        group_id: &String,
+    ) -> Result<Option<u64>, CommonError> {
+        let key = key_stream_group_offset(cluster_name, shard_name, group_id);
+        match engine_get_by_cluster(self.rocksdb_engine_handler.clone(), key) {
+            Ok(Some(data)) => {
+                return Ok(Some(serde_json::from_slice::<u64>(&data.data)?));
+            }
+            Ok(None) => {
+                return Ok(None);
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    // The groups that committed an offset on the shard, with the committed offset.
+    pub fn list_groups(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+    ) -> Result<Vec<(String, u64)>, CommonError> {
+        let prefix = key_stream_group_offset_prefix(cluster_name, shard_name);
+        let data = engine_prefix_list_with_key_by_cluster(
+            self.rocksdb_engine_handler.clone(),
+            prefix.clone(),
+        )?;
+        let mut results = Vec::new();
+        for (key, raw) in data {
+            let group_id = key[prefix.len()..].to_string();
+            results.push((group_id, serde_json::from_slice::<u64>(&raw.data)?));
+        }
+        return Ok(results);
+    }
+
+    // The committed offset is the last record consumed by the group, so the group is
+    // removed to read again from the start of the shard.
+    pub fn reset_group_offset(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+        group_id: &String,
+        offset: u64,
+    ) -> Result<(), CommonError> {
+        if offset == 0 {
+            let key = key_stream_group_offset(cluster_name, shard_name, group_id);
+            return engine_delete_by_cluster(self.rocksdb_engine_handler.clone(), key);
+        }
+        return self.commit_offset(cluster_name, shard_name, group_id, offset - 1);
+    }
+
+    // The offset of the first record created at or after the timestamp.
+    pub fn offset_by_timestamp(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+        timestamp: u64,
+    ) -> Result<Option<u64>, CommonError> {
+        let mut start_offset = match self.get_shard(cluster_name, shard_name)? {
+            Some(shard) => shard.start_offset,
+            None => return Ok(None),
+        };
+        loop {
+            let data = engine_prefix_list_raw_from_by_cluster(
+                self.rocksdb_engine_handler.clone(),
+                key_stream_record_prefix(cluster_name, shard_name),
+                key_stream_record(cluster_name, shard_name, start_offset),
+                SCAN_BATCH_SIZE,
+            )?;
+            if data.is_empty() {
+                return Ok(None);
+            }
+
+            for raw in data {
+                let record = StreamRecord::decode(raw.as_ref())?;
+                if record.create_time >= timestamp {
+                    return Ok(Some(record.offset));
+                }
+                start_offset = record.offset + 1;
+            }
+        }
+    }
+
+    // The offset of the latest record written with the key.
+    fn key_offset(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+        key: &String,
+    ) -> Result<Option<u64>, CommonError> {
+        let index_key = key_stream_key_index(cluster_name, shard_name, key);
+        match engine_get_by_cluster(self.rocksdb_engine_handler.clone(), index_key)? {
+            Some(data) => {
+                return Ok(Some(serde_json::from_slice::<u64>(&data.data)?));
+            }
+            None => {
+                return Ok(None);
+            }
+        }
+    }
+
+    // Delete the record and its key index, unless the index already points to a newer
+    // record of the same key.
+    fn remove_record(
+        &self,
+        cluster_name: &String,
+        shard_name: &String,
+        record: &StreamRecord,
+    ) -> Result<(), CommonError> {
+        if !record.key.is_empty()
+            && self.key_offset(cluster_name, shard_name, &record.key)? == Some(record.offset)
+        {
+            engine_delete_by_cluster(
+                self.rocksdb_engine_handler.clone(),
+                key_stream_key_index(cluster_name, shard_name, &record.key),
+            )?;
+        }
+        return engine_delete_by_cluster(
+            self.rocksdb_engine_handler.clone(),
+            key_stream_record(cluster_name, shard_name, record.offset),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StreamStorage;
+    use crate::storage::rocksdb::RocksDBEngine;
+    use common_base::{config::placement_center::PlacementCenterConfig, tools::unique_id};
+    use protocol::placement_center::generate::kv::{
+        StreamCleanupPolicy, StreamRecord, StreamShardConfig,
+    };
+    use std::{fs::remove_dir_all, sync::Arc};
+
+    fn test_config() -> PlacementCenterConfig {
+        let mut config = PlacementCenterConfig::default();
+        config.data_path = format!("/tmp/tmp_test/{}", unique_id());
+        config.rocksdb.max_open_files = Some(100);
+        return config;
+    }
+
+    #[test]
+    fn stream_trim_test() {
+        let config = test_config();
+        let storage = StreamStorage::new(Arc::new(RocksDBEngine::new(&config)));
+
+        let cluster_name = "test_cluster".to_string();
+        let shard_name = "test_shard".to_string();
+        let records: Vec<StreamRecord> = (0..5)
+            .map(|i| StreamRecord {
+                key: format!("k{}", i % 2),
+                data: vec![0; 10],
+                create_time: 100 + i * 10,
+                ..Default::default()
+            })
+            .collect();
+        storage
+            .create_shard(&cluster_name, &shard_name, StreamShardConfig::default())
+            .unwrap();
+        storage
+            .write(&cluster_name, &shard_name, records, 0, 0)
+            .unwrap();
+
+        // The same shard name in another cluster is a different shard
+        let other_cluster = "other_cluster".to_string();
+        assert!(storage
+            .get_shard(&other_cluster, &shard_name)
+            .unwrap()
+            .is_none());
+
+        assert_eq!(storage.trim(&cluster_name, &shard_name, 3).unwrap(), 3);
+        let shard = storage
+            .get_shard(&cluster_name, &shard_name)
+            .unwrap()
+            .unwrap();
+        assert_eq!(shard.start_offset, 3);
+        assert_eq!(shard.next_offset, 5);
+        assert_eq!(shard.record_num, 2);
+        assert_eq!(shard.shard_size, 20);
+
+        let records = storage.read(&cluster_name, &shard_name, 0, 10, 0).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].offset, 3);
+
+        // The scan by timestamp starts at the first record left in the shard
+        let records = storage
+            .read_by_timestamp(&cluster_name, &shard_name, 0, 1000, 10, 0)
+            .unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].offset, 3);
+
+        // k0 was last written at offset 4, k1 at offset 3
+        let k0 = "k0".to_string();
+        let record = storage
+            .read_by_key(&cluster_name, &shard_name, &k0)
+            .unwrap();
+        assert_eq!(record.unwrap().offset, 4);
+
+        assert_eq!(storage.trim(&cluster_name, &shard_name, 10).unwrap(), 2);
+        let k1 = "k1".to_string();
+        assert!(storage
+            .read_by_key(&cluster_name, &shard_name, &k1)
+            .unwrap()
+            .is_none());
+        let shard = storage
+            .get_shard(&cluster_name, &shard_name)
+            .unwrap()
+            .unwrap();
+        assert_eq!(shard.start_offset, 5);
+        assert_eq!(shard.record_num, 0);
+        assert_eq!(shard.shard_size, 0);
+
+        remove_dir_all(config.data_path).unwrap();
+    }
+
+    #[test]
+    fn stream_retention_test() {
+        let config = test_config();
+        let storage = StreamStorage::new(Arc::new(RocksDBEngine::new(&config)));
+        let cluster_name = "test_cluster".to_string();
+        let shard_name = "test_shard".to_string();
+
+        let shard_config = StreamShardConfig {
+            retention_sec: 60,
+            retention_bytes: 35,
+            retention_record_num: 4,
+            ..Default::default()
+        };
+        storage
+            .create_shard(&cluster_name, &shard_name, shard_config)
+            .unwrap();
+        let records: Vec<StreamRecord> = (0..6)
+            .map(|i| StreamRecord {
+                data: vec![0; 10],
+                create_time: 1000 + i * 10,
+                ..Default::default()
+            })
+            .collect();
+        storage
+            .write(&cluster_name, &shard_name, records, 0, 0)
+            .unwrap();
+
+        // The bytes limit keeps the last 3 records
+        let shard = storage
+            .get_shard(&cluster_name, &shard_name)
+            .unwrap()
+            .unwrap();
+        assert!(shard.is_retention_enabled());
+        assert_eq!(storage.retention_end_offset(&shard, 1000).unwrap(), 3);
+
+        // Records older than 60 seconds at 1105 are the first 5
+        assert_eq!(storage.retention_end_offset(&shard, 1105).unwrap(), 5);
+        assert_eq!(storage.list_shards().unwrap().len(), 1);
+
+        remove_dir_all(config.data_path).unwrap();
+    }
+
+    #[test]
+    fn stream_compact_test() {
+        let config = test_config();
+        let storage = StreamStorage::new(Arc::new(RocksDBEngine::new(&config)));
+        let cluster_name = "test_cluster".to_string();
+        let shard_name = "test_shard".to_string();
+
+        let shard_config = StreamShardConfig {
+            cleanup_policy: StreamCleanupPolicy::Compact.into(),
+            ..Default::default()
+        };
+        storage
+            .create_shard(&cluster_name, &shard_name, shard_config)
+            .unwrap();
+        let records: Vec<StreamRecord> = ["k1", "k2", "k1", "", "k1"]
+            .iter()
+            .map(|key| StreamRecord {
+                key: key.to_string(),
+                data: vec![0; 10],
+                ..Default::default()
+            })
+            .collect();
+        storage
+            .write(&cluster_name, &shard_name, records, 0, 0)
+            .unwrap();
+
+        assert_eq!(storage.compact(&cluster_name, &shard_name).unwrap(), 2);
+        let records = storage.read(&cluster_name, &shard_name, 0, 10, 0).unwrap();
+        let offsets: Vec<u64> = records.iter().map(|r| r.offset).collect();
+        assert_eq!(offsets, vec![1, 3, 4]);
+        let shard = storage
+            .get_shard(&cluster_name, &shard_name)
+            .unwrap()
+            .unwrap();
+        assert_eq!(shard.record_num, 3);
+        assert_eq!(shard.shard_size, 30);
+
+        remove_dir_all(config.data_path).unwrap();
+    }
+
+    #[test]
+    fn stream_group_admin_test() {
+        let config = test_config();
+        let storage = StreamStorage::new(Arc::new(RocksDBEngine::new(&config)));
+        let cluster_name = "test_cluster".to_string();
+        let shard_name = "test_shard".to_string();
+        let other_shard = "test_shard_b".to_string();
+
+        let records: Vec<StreamRecord> = (0..4)
+            .map(|i| StreamRecord {
+                data: vec![0; 10],
+                create_time: 100 + i * 10,
+                ..Default::default()
+            })
+            .collect();
+        storage
+            .create_shard(&cluster_name, &shard_name, StreamShardConfig::default())
+            .unwrap();
+        storage
+            .write(&cluster_name, &shard_name, records, 0, 0)
+            .unwrap();
+        assert_eq!(
+            storage
+                .offset_by_timestamp(&cluster_name, &shard_name, 115)
+                .unwrap(),
+            Some(2)
+        );
+        assert_eq!(
+            storage
+                .offset_by_timestamp(&cluster_name, &shard_name, 200)
+                .unwrap(),
+            None
+        );
+
+        let group_id = "g1".to_string();
+        storage
+            .commit_offset(&cluster_name, &shard_name, &group_id, 1)
+            .unwrap();
+        storage
+            .commit_offset(&cluster_name, &other_shard, &"g2".to_string(), 0)
+            .unwrap();
+        assert_eq!(
+            storage.list_groups(&cluster_name, &shard_name).unwrap(),
+            vec![(group_id.clone(), 1)]
+        );
+
+        storage
+            .reset_group_offset(&cluster_name, &shard_name, &group_id, 3)
+            .unwrap();
+        assert_eq!(
+            storage
+                .get_group_offset(&cluster_name, &shard_name, &group_id)
+                .unwrap(),
+            Some(2)
+        );
+        storage
+            .reset_group_offset(&cluster_name, &shard_name, &group_id, 0)
+            .unwrap();
+        assert!(storage
+            .list_groups(&cluster_name, &shard_name)
+            .unwrap()
+            .is_empty());
+
+        remove_dir_all(config.data_path).unwrap();
+    }
+
+    #[test]
+    fn stream_write_limit_test() {
+        let config = test_config();
+        let storage = StreamStorage::new(Arc::new(RocksDBEngine::new(&config)));
+        let cluster_name = "test_cluster".to_string();
+        let shard_name = "test_shard".to_string();
+        let records = |num: usize| -> Vec<StreamRecord> {
+            (0..num)
+                .map(|_| StreamRecord {
+                    data: vec![0; 10],
+                    ..Default::default()
+                })
+                .collect()
+        };
+
+        // Writing does not create the shard
+        assert!(storage
+            .write(&cluster_name, &shard_name, records(1), 0, 0)
+            .is_err());
+        assert!(storage
+            .get_shard(&cluster_name, &shard_name)
+            .unwrap()
+            .is_none());
+
+        storage
+            .create_shard(&cluster_name, &shard_name, StreamShardConfig::default())
+            .unwrap();
+        storage
+            .write(&cluster_name, &shard_name, records(3), 4, 0)
+            .unwrap();
+        assert!(storage
+            .write(&cluster_name, &shard_name, records(2), 4, 0)
+            .is_err());
+        assert!(storage
+            .write(&cluster_name, &shard_name, records(1), 0, 35)
+            .is_err());
+        storage
+            .write(&cluster_name, &shard_name, records(1), 4, 40)
+            .unwrap();
+
+        let shard = storage
+            .get_shard(&cluster_name, &shard_name)
+            .unwrap()
+            .unwrap();
+        assert_eq!(shard.record_num, 4);
+        assert_eq!(shard.shard_size, 40);
+
+        remove_dir_all(config.data_path).unwrap();
+    }
+}
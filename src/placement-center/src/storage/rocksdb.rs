@@ -15,8 +15,9 @@
 use common_base::config::placement_center::PlacementCenterConfig;
 use common_base::error::common::CommonError;
 use log::error;
+use rocksdb::checkpoint::Checkpoint;
 use rocksdb::SliceTransform;
-use rocksdb::{ColumnFamily, DBCompactionStyle, Options, DB};
+use rocksdb::{ColumnFamily, DBCompactionStyle, Options, ReadOptions, WriteBatch, DB};
 use serde::{de::DeserializeOwned, Serialize};
 use serde_json;
 use std::collections::HashMap;
@@ -88,6 +89,15 @@ impl RocksDBEngine {
             .map_err(|err| format!("Failed to put to ColumnFamily:{:?}", err))
     }
 
+    // Write the value as it is, without serializing it
+    pub fn write_raw(&self, cf: &ColumnFamily, key: &str, value: &[u8]) -> Result<(), CommonError> {
+        return Ok(self.db.put_cf(cf, key, value)?);
+    }
+
+    pub fn read_raw(&self, cf: &ColumnFamily, key: &str) -> Result<Option<Vec<u8>>, CommonError> {
+        return Ok(self.db.get_cf(cf, key)?);
+    }
+
     // Read data from the RocksDB
     pub fn read<T: DeserializeOwned>(
         &self,
@@ -142,6 +152,42 @@ impl RocksDBEngine {
         return result;
     }
 
+    // Search data by prefix, starting from start_key and returning at most limit entries
+    pub fn read_prefix_from(
+        &self,
+        cf: &ColumnFamily,
+        search_key: &str,
+        start_key: &str,
+        limit: usize,
+    ) -> Vec<HashMap<String, Vec<u8>>> {
+        let mut iter = self.db.raw_iterator_cf(cf);
+        iter.seek(start_key);
+
+        let mut result = Vec::new();
+        while iter.valid() && result.len() < limit {
+            let (key, value) = match (iter.key(), iter.value()) {
+                (Some(key), Some(value)) => (key, value),
+                _ => break,
+            };
+            let result_key = match String::from_utf8(key.to_vec()) {
+                Ok(s) => s,
+                Err(_) => {
+                    iter.next();
+                    continue;
+                }
+            };
+
+            if !result_key.starts_with(search_key) {
+                break;
+            }
+            let mut raw = HashMap::new();
+            raw.insert(result_key, value.to_vec());
+            result.push(raw);
+            iter.next();
+        }
+        return result;
+    }
+
     // Read data from all Columnfamiliy
     pub fn read_all(&self) -> HashMap<String, Vec<HashMap<String, String>>> {
         let mut result: HashMap<String, Vec<HashMap<String, String>>> = HashMap::new();
@@ -187,6 +233,87 @@ impl RocksDBEngine {
         return Ok(self.db.delete_cf(cf, key)?);
     }
 
+    // Delete the keys in one atomic write
+    pub fn delete_batch(&self, cf: &ColumnFamily, keys: Vec<String>) -> Result<(), CommonError> {
+        let mut batch = WriteBatch::default();
+        for key in keys {
+            batch.delete_cf(cf, key);
+        }
+        return Ok(self.db.write(batch)?);
+    }
+
+    // Write all pairs in one atomic write
+    pub fn write_batch(
+        &self,
+        cf: &ColumnFamily,
+        data: Vec<(Vec<u8>, Vec<u8>)>,
+    ) -> Result<(), CommonError> {
+        let mut batch = WriteBatch::default();
+        for (key, value) in data {
+            batch.put_cf(cf, key, value);
+        }
+        return Ok(self.db.write(batch)?);
+    }
+
+    // Replace the whole content of a ColumnFamily with the given data in one atomic write,
+    // so that a crash leaves either the old or the new content.
+    pub fn replace_all(
+        &self,
+        cf: &ColumnFamily,
+        data: Vec<(Vec<u8>, Vec<u8>)>,
+    ) -> Result<(), CommonError> {
+        let mut batch = WriteBatch::default();
+        let mut iter = self.db.raw_iterator_cf_opt(cf, total_order_read_opts());
+        iter.seek_to_first();
+        while iter.valid() {
+            if let Some(key) = iter.key() {
+                batch.delete_cf(cf, key);
+            }
+            iter.next();
+        }
+        iter.status()?;
+
+        for (key, value) in data {
+            batch.put_cf(cf, key, value);
+        }
+        return Ok(self.db.write(batch)?);
+    }
+
+    // Create a consistent point-in-time copy of the database in the directory, the files
+    // are hard linked so it is cheap to take.
+    pub fn checkpoint(&self, path: &str) -> Result<(), CommonError> {
+        let checkpoint = Checkpoint::new(&self.db)?;
+        checkpoint.create_checkpoint(path)?;
+        return Ok(());
+    }
+
+    // Read all data of the cluster ColumnFamily of a checkpoint, calling f for every key.
+    pub fn read_checkpoint<F>(path: &str, mut f: F) -> Result<(), CommonError>
+    where
+        F: FnMut(&[u8], &[u8]) -> Result<(), CommonError>,
+    {
+        let db = DB::open_cf_for_read_only(&Options::default(), path, column_family_list(), false)?;
+        let cf = match db.cf_handle(DB_COLUMN_FAMILY_CLUSTER) {
+            Some(cf) => cf,
+            None => {
+                return Err(CommonError::CommmonError(format!(
+                    "checkpoint {} has no column family {}",
+                    path, DB_COLUMN_FAMILY_CLUSTER
+                )))
+            }
+        };
+        let mut iter = db.raw_iterator_cf_opt(cf, total_order_read_opts());
+        iter.seek_to_first();
+        while iter.valid() {
+            if let (Some(key), Some(value)) = (iter.key(), iter.value()) {
+                f(key, value)?;
+            }
+            iter.next();
+        }
+        iter.status()?;
+        return Ok(());
+    }
+
     pub fn exist(&self, cf: &ColumnFamily, key: &str) -> bool {
         self.db.key_may_exist_cf(cf, key)
     }
@@ -225,6 +352,13 @@ impl RocksDBEngine {
     }
 }
 
+// Iterate over every key regardless of the prefix extractor.
+fn total_order_read_opts() -> ReadOptions {
+    let mut opts = ReadOptions::default();
+    opts.set_total_order_seek(true);
+    return opts;
+}
+
 #[cfg(test)]
 mod tests {
     use super::RocksDBEngine;
@@ -28,21 +28,31 @@ mod tests {
         let set_req = SetRequest {
             key: key.clone(),
             value: value.clone(),
+            ..Default::default()
         };
         let _ = client.set(set_req).await;
 
-        let get_req = GetRequest { key: key.clone() };
+        let get_req = GetRequest {
+            key: key.clone(),
+            stale_read: false,
+        };
         let get_rep = client.get(get_req).await.unwrap().into_inner();
         assert_eq!(value, get_rep.value);
 
-        let exists_req = ExistsRequest { key: key.clone() };
+        let exists_req = ExistsRequest {
+            key: key.clone(),
+            stale_read: false,
+        };
         let ex_rep = client.exists(exists_req).await.unwrap().into_inner();
         assert!(ex_rep.flag);
 
         let del_req = DeleteRequest { key: key.clone() };
         let _ = client.delete(del_req).await.unwrap().into_inner();
 
-        let exists_req = ExistsRequest { key: key.clone() };
+        let exists_req = ExistsRequest {
+            key: key.clone(),
+            stale_read: false,
+        };
         let ex_rep = client.exists(exists_req).await.unwrap().into_inner();
         assert!(!ex_rep.flag);
     }
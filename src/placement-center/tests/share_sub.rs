@@ -77,6 +77,7 @@ mod tests {
         let req = GetShareSubLeaderRequest {
             cluster_name: cluster_name.clone(),
             group_name,
+            stale_read: false,
         };
 
         match placement_get_share_sub_leader(client_poll.clone(), addrs.clone(), req).await {
@@ -0,0 +1,143 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#[cfg(test)]
+mod tests {
+    use protocol::placement_center::generate::kv::{
+        kv_service_client::KvServiceClient, StreamCommitOffsetRequest, StreamCreateShardRequest,
+        StreamDeleteShardRequest, StreamReadByKeyRequest, StreamReadByOffsetRequest,
+        StreamReadRequest, StreamRecord, StreamWriteRequest,
+    };
+
+    #[tokio::test]
+    async fn stream_storage() {
+        let mut client = KvServiceClient::connect("http://127.0.0.1:1228")
+            .await
+            .unwrap();
+        let cluster_name = "test_stream_cluster".to_string();
+        let shard_name = "test_stream_shard".to_string();
+        let group_id = "test_group".to_string();
+
+        let _ = client
+            .stream_delete_shard(StreamDeleteShardRequest {
+                cluster_name: cluster_name.clone(),
+                shard_name: shard_name.clone(),
+            })
+            .await;
+
+        client
+            .stream_create_shard(StreamCreateShardRequest {
+                cluster_name: cluster_name.clone(),
+                shard_name: shard_name.clone(),
+                config: None,
+            })
+            .await
+            .unwrap();
+
+        let records = vec![
+            StreamRecord {
+                key: "k1".to_string(),
+                data: "v1".as_bytes().to_vec(),
+                ..Default::default()
+            },
+            StreamRecord {
+                key: "k2".to_string(),
+                data: "v2".as_bytes().to_vec(),
+                ..Default::default()
+            },
+        ];
+        let write_rep = client
+            .stream_write(StreamWriteRequest {
+                cluster_name: cluster_name.clone(),
+                shard_name: shard_name.clone(),
+                records,
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(write_rep.offsets, vec![0, 1]);
+
+        let read_rep = client
+            .stream_read_by_offset(StreamReadByOffsetRequest {
+                cluster_name: cluster_name.clone(),
+                shard_name: shard_name.clone(),
+                offset: 1,
+                stale_read: false,
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(read_rep.record.unwrap().data, "v2".as_bytes().to_vec());
+
+        let read_rep = client
+            .stream_read_by_key(StreamReadByKeyRequest {
+                cluster_name: cluster_name.clone(),
+                shard_name: shard_name.clone(),
+                key: "k1".to_string(),
+                stale_read: false,
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(read_rep.record.unwrap().offset, 0);
+
+        let read_rep = client
+            .stream_read(StreamReadRequest {
+                cluster_name: cluster_name.clone(),
+                shard_name: shard_name.clone(),
+                group_id: group_id.clone(),
+                record_num: 10,
+                record_size: 0,
+                stale_read: false,
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(read_rep.records.len(), 2);
+
+        client
+            .stream_commit_offset(StreamCommitOffsetRequest {
+                cluster_name: cluster_name.clone(),
+                shard_name: shard_name.clone(),
+                group_id: group_id.clone(),
+                offset: 0,
+            })
+            .await
+            .unwrap();
+
+        let read_rep = client
+            .stream_read(StreamReadRequest {
+                cluster_name: cluster_name.clone(),
+                shard_name: shard_name.clone(),
+                group_id: group_id.clone(),
+                record_num: 10,
+                record_size: 0,
+                stale_read: false,
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(read_rep.records.len(), 1);
+        assert_eq!(read_rep.records[0].offset, 1);
+
+        client
+            .stream_delete_shard(StreamDeleteShardRequest {
+                cluster_name: cluster_name.clone(),
+                shard_name: shard_name.clone(),
+            })
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,43 @@
+/// Storage format of an MQTTMessage, the encoded bytes are prefixed with a codec version.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MqttMessage {
+    #[prost(string, tag = "1")]
+    pub client_id: ::prost::alloc::string::String,
+    #[prost(bool, tag = "2")]
+    pub dup: bool,
+    #[prost(uint32, tag = "3")]
+    pub qos: u32,
+    #[prost(uint32, tag = "4")]
+    pub pkid: u32,
+    #[prost(bool, tag = "5")]
+    pub retain: bool,
+    #[prost(bytes = "vec", tag = "6")]
+    pub topic: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "7")]
+    pub payload: ::prost::alloc::vec::Vec<u8>,
+    #[prost(uint32, optional, tag = "8")]
+    pub format_indicator: ::core::option::Option<u32>,
+    #[prost(uint32, optional, tag = "9")]
+    pub expiry_interval: ::core::option::Option<u32>,
+    #[prost(string, optional, tag = "10")]
+    pub response_topic: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(bytes = "vec", optional, tag = "11")]
+    pub correlation_data: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+    #[prost(message, repeated, tag = "12")]
+    pub user_properties: ::prost::alloc::vec::Vec<UserProperty>,
+    #[prost(uint64, repeated, tag = "13")]
+    pub subscription_identifiers: ::prost::alloc::vec::Vec<u64>,
+    #[prost(string, optional, tag = "14")]
+    pub content_type: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint64, tag = "15")]
+    pub create_time: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UserProperty {
+    #[prost(string, tag = "1")]
+    pub key: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub value: ::prost::alloc::string::String,
+}
@@ -12,4 +12,5 @@
 // limitations under the License.
 
 
+pub mod message;
 pub mod mqtt;
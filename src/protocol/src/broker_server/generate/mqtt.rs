@@ -1,6 +1,15 @@
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct UpdateCacheRequest {}
+pub struct UpdateCacheRequest {
+    #[prost(string, tag = "1")]
+    pub cluster_name: ::prost::alloc::string::String,
+    #[prost(enumeration = "UpdateCacheActionType", tag = "2")]
+    pub action_type: i32,
+    #[prost(enumeration = "UpdateCacheResourceType", tag = "3")]
+    pub resource_type: i32,
+    #[prost(string, tag = "4")]
+    pub data: ::prost::alloc::string::String,
+}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DeleteSessionRequest {
@@ -25,6 +34,103 @@ pub struct SendLastWillMessageRequest {
     #[prost(bytes = "vec", tag = "2")]
     pub last_will_message: ::prost::alloc::vec::Vec<u8>,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListGroupOffsetRequest {
+    #[prost(string, tag = "1")]
+    pub topic_name: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GroupOffset {
+    #[prost(string, tag = "1")]
+    pub group_id: ::prost::alloc::string::String,
+    #[prost(bool, tag = "2")]
+    pub committed: bool,
+    #[prost(uint64, tag = "3")]
+    pub committed_offset: u64,
+    #[prost(uint64, tag = "4")]
+    pub earliest_offset: u64,
+    #[prost(uint64, tag = "5")]
+    pub latest_offset: u64,
+    #[prost(uint64, tag = "6")]
+    pub lag: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListGroupOffsetReply {
+    #[prost(message, repeated, tag = "1")]
+    pub groups: ::prost::alloc::vec::Vec<GroupOffset>,
+}
+/// strategy: earliest, latest, timestamp, offset.
+/// value is the timestamp in seconds or the offset, depending on the strategy.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ResetGroupOffsetRequest {
+    #[prost(string, tag = "1")]
+    pub topic_name: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub group_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub strategy: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "4")]
+    pub value: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ResetGroupOffsetReply {
+    #[prost(uint64, tag = "1")]
+    pub offset: u64,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum UpdateCacheActionType {
+    Set = 0,
+    Delete = 1,
+}
+impl UpdateCacheActionType {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            UpdateCacheActionType::Set => "Set",
+            UpdateCacheActionType::Delete => "Delete",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "Set" => Some(Self::Set),
+            "Delete" => Some(Self::Delete),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum UpdateCacheResourceType {
+    Node = 0,
+}
+impl UpdateCacheResourceType {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            UpdateCacheResourceType::Node => "Node",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "Node" => Some(Self::Node),
+            _ => None,
+        }
+    }
+}
 /// Generated client implementations.
 pub mod mqtt_broker_service_client {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
@@ -178,6 +284,56 @@ pub mod mqtt_broker_service_client {
                 );
             self.inner.unary(req, path, codec).await
         }
+        pub async fn list_group_offset(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListGroupOffsetRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListGroupOffsetReply>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/mqtt.MqttBrokerService/listGroupOffset",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("mqtt.MqttBrokerService", "listGroupOffset"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn reset_group_offset(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ResetGroupOffsetRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ResetGroupOffsetReply>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/mqtt.MqttBrokerService/resetGroupOffset",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("mqtt.MqttBrokerService", "resetGroupOffset"));
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -199,6 +355,20 @@ pub mod mqtt_broker_service_server {
             &self,
             request: tonic::Request<super::SendLastWillMessageRequest>,
         ) -> std::result::Result<tonic::Response<super::CommonReply>, tonic::Status>;
+        async fn list_group_offset(
+            &self,
+            request: tonic::Request<super::ListGroupOffsetRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListGroupOffsetReply>,
+            tonic::Status,
+        >;
+        async fn reset_group_offset(
+            &self,
+            request: tonic::Request<super::ResetGroupOffsetRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ResetGroupOffsetReply>,
+            tonic::Status,
+        >;
     }
     #[derive(Debug)]
     pub struct MqttBrokerServiceServer<T: MqttBrokerService> {
@@ -423,6 +593,103 @@ pub mod mqtt_broker_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/mqtt.MqttBrokerService/listGroupOffset" => {
+                    #[allow(non_camel_case_types)]
+                    struct listGroupOffsetSvc<T: MqttBrokerService>(pub Arc<T>);
+                    impl<
+                        T: MqttBrokerService,
+                    > tonic::server::UnaryService<super::ListGroupOffsetRequest>
+                    for listGroupOffsetSvc<T> {
+                        type Response = super::ListGroupOffsetReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListGroupOffsetRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as MqttBrokerService>::list_group_offset(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = listGroupOffsetSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/mqtt.MqttBrokerService/resetGroupOffset" => {
+                    #[allow(non_camel_case_types)]
+                    struct resetGroupOffsetSvc<T: MqttBrokerService>(pub Arc<T>);
+                    impl<
+                        T: MqttBrokerService,
+                    > tonic::server::UnaryService<super::ResetGroupOffsetRequest>
+                    for resetGroupOffsetSvc<T> {
+                        type Response = super::ResetGroupOffsetReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ResetGroupOffsetRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as MqttBrokerService>::reset_group_offset(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = resetGroupOffsetSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
@@ -14,8 +14,10 @@
 
 use super::{
     generate::protocol::{
+        end_txn::{EndTxnReq, EndTxnReqBody, EndTxnResp, EndTxnRespBody},
         fetch::{FetchReq, FetchReqBody, FetchResp, FetchRespBody},
         header::{ApiKey, ApiType, Header},
+        metadata::{MetadataReq, MetadataReqBody, MetadataResp, MetadataRespBody},
         produce::{ProduceReq, ProduceReqBody, ProduceResp, ProduceRespBody},
     },
     Error,
@@ -35,6 +37,10 @@ pub enum StorageEnginePacket {
     ProduceResp(ProduceResp),
     FetchReq(FetchReq),
     FetchResp(FetchResp),
+    MetadataReq(MetadataReq),
+    MetadataResp(MetadataResp),
+    EndTxnReq(EndTxnReq),
+    EndTxnResp(EndTxnResp),
 }
 
 impl StorageEngineCodec {
@@ -68,8 +74,42 @@ impl codec::Encoder<StorageEnginePacket> for StorageEngineCodec {
                 header_byte = Header::encode_to_vec(&header);
                 body_byte = ProduceRespBody::encode_to_vec(&body);
             }
-            StorageEnginePacket::FetchReq(data) => {}
-            StorageEnginePacket::FetchResp(data) => {}
+            StorageEnginePacket::FetchReq(data) => {
+                let header = data.header.unwrap();
+                let body = data.body.unwrap();
+                header_byte = Header::encode_to_vec(&header);
+                body_byte = FetchReqBody::encode_to_vec(&body);
+            }
+            StorageEnginePacket::FetchResp(data) => {
+                let header = data.header.unwrap();
+                let body = data.body.unwrap();
+                header_byte = Header::encode_to_vec(&header);
+                body_byte = FetchRespBody::encode_to_vec(&body);
+            }
+            StorageEnginePacket::MetadataReq(data) => {
+                let header = data.header.unwrap();
+                let body = data.body.unwrap();
+                header_byte = Header::encode_to_vec(&header);
+                body_byte = MetadataReqBody::encode_to_vec(&body);
+            }
+            StorageEnginePacket::MetadataResp(data) => {
+                let header = data.header.unwrap();
+                let body = data.body.unwrap();
+                header_byte = Header::encode_to_vec(&header);
+                body_byte = MetadataRespBody::encode_to_vec(&body);
+            }
+            StorageEnginePacket::EndTxnReq(data) => {
+                let header = data.header.unwrap();
+                let body = data.body.unwrap();
+                header_byte = Header::encode_to_vec(&header);
+                body_byte = EndTxnReqBody::encode_to_vec(&body);
+            }
+            StorageEnginePacket::EndTxnResp(data) => {
+                let header = data.header.unwrap();
+                let body = data.body.unwrap();
+                header_byte = Header::encode_to_vec(&header);
+                body_byte = EndTxnRespBody::encode_to_vec(&body);
+            }
         }
 
         let header_len = header_byte.len();
@@ -178,6 +218,14 @@ impl codec::Decoder for StorageEngineCodec {
                     ApiType::Request => return fetch_req(body_bytes, header),
                     ApiType::Response => return fetch_resp(body_bytes, header),
                 },
+                ApiKey::Metadata => match header.api_type() {
+                    ApiType::Request => return metadata_req(body_bytes, header),
+                    ApiType::Response => return metadata_resp(body_bytes, header),
+                },
+                ApiKey::EndTxn => match header.api_type() {
+                    ApiType::Request => return end_txn_req(body_bytes, header),
+                    ApiType::Response => return end_txn_resp(body_bytes, header),
+                },
             },
             Err(e) => {
                 return Err(Error::DecodeHeaderError(e.to_string()));
@@ -262,11 +310,104 @@ fn fetch_resp(body_bytes: BytesMut, header: Header) -> Result<Option<StorageEngi
     }
 }
 
+fn metadata_req(
+    body_bytes: BytesMut,
+    header: Header,
+) -> Result<Option<StorageEnginePacket>, Error> {
+    match MetadataReqBody::decode(body_bytes.as_ref()) {
+        Ok(body) => {
+            let item = StorageEnginePacket::MetadataReq(MetadataReq {
+                header: Some(header),
+                body: Some(body),
+            });
+            return Ok(Some(item));
+        }
+        Err(e) => {
+            return Err(Error::DecodeBodyError(
+                "metadata_req".to_string(),
+                e.to_string(),
+            ));
+        }
+    }
+}
+
+fn metadata_resp(
+    body_bytes: BytesMut,
+    header: Header,
+) -> Result<Option<StorageEnginePacket>, Error> {
+    match MetadataRespBody::decode(body_bytes.as_ref()) {
+        Ok(body) => {
+            let item = StorageEnginePacket::MetadataResp(MetadataResp {
+                header: Some(header),
+                body: Some(body),
+            });
+            return Ok(Some(item));
+        }
+        Err(e) => {
+            return Err(Error::DecodeBodyError(
+                "metadata_resp".to_string(),
+                e.to_string(),
+            ));
+        }
+    }
+}
+
+fn end_txn_req(body_bytes: BytesMut, header: Header) -> Result<Option<StorageEnginePacket>, Error> {
+    match EndTxnReqBody::decode(body_bytes.as_ref()) {
+        Ok(body) => {
+            let item = StorageEnginePacket::EndTxnReq(EndTxnReq {
+                header: Some(header),
+                body: Some(body),
+            });
+            return Ok(Some(item));
+        }
+        Err(e) => {
+            return Err(Error::DecodeBodyError(
+                "end_txn_req".to_string(),
+                e.to_string(),
+            ));
+        }
+    }
+}
+
+fn end_txn_resp(
+    body_bytes: BytesMut,
+    header: Header,
+) -> Result<Option<StorageEnginePacket>, Error> {
+    match EndTxnRespBody::decode(body_bytes.as_ref()) {
+        Ok(body) => {
+            let item = StorageEnginePacket::EndTxnResp(EndTxnResp {
+                header: Some(header),
+                body: Some(body),
+            });
+            return Ok(Some(item));
+        }
+        Err(e) => {
+            return Err(Error::DecodeBodyError(
+                "end_txn_resp".to_string(),
+                e.to_string(),
+            ));
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::journal_server::generate::protocol::{
-        header::{ApiKey, ApiType, ApiVersion, Header, RequestCommon, ResponseCommon},
-        produce::{ProduceReq, ProduceReqBody, ProduceResp, ProduceRespBody},
+        end_txn::{EndTxnReq, EndTxnReqBody, EndTxnResp, EndTxnRespBody, ShardEndTxnResult},
+        fetch::{
+            FetchReq, FetchReqBody, FetchResp, FetchRespBody, FetchShard, IsolationLevel,
+            ShardFetchResult,
+        },
+        header::{ApiKey, ApiType, ApiVersion, ErrorCode, Header, RequestCommon, ResponseCommon},
+        metadata::{
+            MetadataReq, MetadataReqBody, MetadataResp, MetadataRespBody, NodeInfo, ShardMetadata,
+        },
+        produce::{
+            Acks, ProduceReq, ProduceReqBody, ProduceResp, ProduceRespBody, ShardData,
+            ShardProduceResult,
+        },
+        record::{ControlType, Record, RecordHeader},
     };
     use futures::{SinkExt, StreamExt};
     use tokio::io::{self, AsyncReadExt, AsyncWriteExt};
@@ -285,6 +426,43 @@ mod tests {
         assert_eq!(source, target);
     }
 
+    #[test]
+    fn codec_all_packets_test() {
+        let mut codec = StorageEngineCodec::new();
+        let packets = vec![
+            build_produce_req(),
+            build_produce_resp(),
+            build_fetch_req(),
+            build_fetch_resp(),
+            build_metadata_req(),
+            build_metadata_resp(),
+            build_end_txn_req(),
+            build_end_txn_resp(),
+        ];
+
+        // Several frames in one buffer are decoded one by one.
+        let mut dst = bytes::BytesMut::new();
+        for packet in packets.iter() {
+            codec.encode(packet.clone(), &mut dst).unwrap();
+        }
+        for packet in packets.iter() {
+            let target = codec.decode(&mut dst).unwrap().unwrap();
+            assert_eq!(*packet, target);
+        }
+        assert!(codec.decode(&mut dst).unwrap().is_none());
+    }
+
+    #[test]
+    fn codec_partial_frame_test() {
+        let mut codec = StorageEngineCodec::new();
+        let mut dst = bytes::BytesMut::new();
+        codec.encode(build_fetch_resp(), &mut dst).unwrap();
+        let mut tail = dst.split_off(dst.len() / 2);
+        assert!(codec.decode(&mut dst).unwrap().is_none());
+        dst.unsplit(tail.split());
+        assert_eq!(codec.decode(&mut dst).unwrap().unwrap(), build_fetch_resp());
+    }
+
     #[tokio::test]
     async fn storage_engine_frame_server() {
         let ip = "127.0.0.1:1228";
@@ -337,10 +515,21 @@ mod tests {
             api_type: ApiType::Response.into(),
             api_version: ApiVersion::V0.into(),
             request: None,
-            response: Some(ResponseCommon { correlation_id: 33 }),
+            response: Some(ResponseCommon {
+                correlation_id: 33,
+                error_code: ErrorCode::Success.into(),
+                error_message: "".to_string(),
+            }),
         };
 
-        let body = ProduceRespBody {};
+        let body = ProduceRespBody {
+            shards: vec![ShardProduceResult {
+                shard_name: "s1".to_string(),
+                error_code: ErrorCode::Success.into(),
+                error_message: "".to_string(),
+                offsets: vec![0, 1],
+            }],
+        };
         let req = ProduceResp {
             header: Some(header),
             body: Some(body),
@@ -362,9 +551,15 @@ mod tests {
 
         let body = ProduceReqBody {
             transactional_id: 1,
-            acks: 1,
+            acks: Acks::All.into(),
             timeout_ms: 60000,
-            topic_data: None,
+            shards: vec![ShardData {
+                shard_name: "s1".to_string(),
+                records: vec![build_record(0), build_record(1)],
+                base_sequence: 5,
+            }],
+            producer_id: 100,
+            producer_epoch: 2,
         };
         let req = ProduceReq {
             header: Some(header),
@@ -372,4 +567,157 @@ mod tests {
         };
         return StorageEnginePacket::ProduceReq(req);
     }
+
+    fn build_record(offset: u64) -> Record {
+        return Record {
+            offset,
+            timestamp: 1000 + offset,
+            key: b"k1".to_vec(),
+            value: format!("v{}", offset).into_bytes(),
+            headers: vec![RecordHeader {
+                key: "h1".to_string(),
+                value: b"hv".to_vec(),
+            }],
+            producer_id: 100,
+            producer_epoch: 2,
+            sequence: offset as u32,
+            transactional: true,
+            control: ControlType::None.into(),
+            compressed: false,
+        };
+    }
+
+    fn build_header(api_key: ApiKey, api_type: ApiType) -> Header {
+        let (request, response) = match api_type {
+            ApiType::Request => (
+                Some(RequestCommon {
+                    correlation_id: 7,
+                    client_id: "client-1".to_string(),
+                }),
+                None,
+            ),
+            ApiType::Response => (
+                None,
+                Some(ResponseCommon {
+                    correlation_id: 7,
+                    error_code: ErrorCode::Success.into(),
+                    error_message: "".to_string(),
+                }),
+            ),
+        };
+        return Header {
+            api_key: api_key.into(),
+            api_type: api_type.into(),
+            api_version: ApiVersion::V0.into(),
+            request,
+            response,
+        };
+    }
+
+    fn build_fetch_req() -> StorageEnginePacket {
+        return StorageEnginePacket::FetchReq(FetchReq {
+            header: Some(build_header(ApiKey::Consume, ApiType::Request)),
+            body: Some(FetchReqBody {
+                max_wait_ms: 500,
+                max_bytes: 1024,
+                shards: vec![FetchShard {
+                    shard_name: "s1".to_string(),
+                    offset: 10,
+                    max_record_num: 100,
+                    max_bytes: 512,
+                }],
+                replica_id: 0,
+                isolation_level: IsolationLevel::ReadCommitted.into(),
+            }),
+        });
+    }
+
+    fn build_fetch_resp() -> StorageEnginePacket {
+        return StorageEnginePacket::FetchResp(FetchResp {
+            header: Some(build_header(ApiKey::Consume, ApiType::Response)),
+            body: Some(FetchRespBody {
+                shards: vec![
+                    ShardFetchResult {
+                        shard_name: "s1".to_string(),
+                        error_code: ErrorCode::Success.into(),
+                        error_message: "".to_string(),
+                        start_offset: 0,
+                        next_offset: 12,
+                        records: vec![build_record(10), build_record(11)],
+                        high_watermark: 12,
+                        last_stable_offset: 10,
+                        next_fetch_offset: 12,
+                    },
+                    ShardFetchResult {
+                        shard_name: "s2".to_string(),
+                        error_code: ErrorCode::OffsetOutOfRange.into(),
+                        error_message: "offset 100 is out of range".to_string(),
+                        start_offset: 0,
+                        next_offset: 5,
+                        records: Vec::new(),
+                        high_watermark: 5,
+                        last_stable_offset: 5,
+                        next_fetch_offset: 0,
+                    },
+                ],
+            }),
+        });
+    }
+
+    fn build_metadata_req() -> StorageEnginePacket {
+        return StorageEnginePacket::MetadataReq(MetadataReq {
+            header: Some(build_header(ApiKey::Metadata, ApiType::Request)),
+            body: Some(MetadataReqBody {
+                shards: vec!["s1".to_string()],
+            }),
+        });
+    }
+
+    fn build_metadata_resp() -> StorageEnginePacket {
+        return StorageEnginePacket::MetadataResp(MetadataResp {
+            header: Some(build_header(ApiKey::Metadata, ApiType::Response)),
+            body: Some(MetadataRespBody {
+                nodes: vec![NodeInfo {
+                    node_id: 1,
+                    addr: "127.0.0.1:2228".to_string(),
+                }],
+                shards: vec![ShardMetadata {
+                    shard_name: "s1".to_string(),
+                    error_code: ErrorCode::NotLeader.into(),
+                    leader_id: 1,
+                    leader_addr: "127.0.0.1:2228".to_string(),
+                    start_offset: 0,
+                    next_offset: 12,
+                }],
+            }),
+        });
+    }
+
+    fn build_end_txn_req() -> StorageEnginePacket {
+        return StorageEnginePacket::EndTxnReq(EndTxnReq {
+            header: Some(build_header(ApiKey::EndTxn, ApiType::Request)),
+            body: Some(EndTxnReqBody {
+                producer_id: 100,
+                producer_epoch: 2,
+                transactional_id: 3,
+                commit: true,
+                shards: vec!["s1".to_string(), "s2".to_string()],
+                timeout_ms: 1000,
+            }),
+        });
+    }
+
+    fn build_end_txn_resp() -> StorageEnginePacket {
+        return StorageEnginePacket::EndTxnResp(EndTxnResp {
+            header: Some(build_header(ApiKey::EndTxn, ApiType::Response)),
+            body: Some(EndTxnRespBody {
+                shards: vec![ShardEndTxnResult {
+                    shard_name: "s1".to_string(),
+                    error_code: ErrorCode::Success.into(),
+                    error_message: "".to_string(),
+                    offset: 12,
+                }],
+            }),
+        });
+    }
 }
@@ -0,0 +1,53 @@
+/// Ends the transaction of a producer on the shards it wrote to, by appending a commit or
+/// abort control record to each of them.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EndTxnReqBody {
+    #[prost(uint64, tag = "1")]
+    pub producer_id: u64,
+    #[prost(uint32, tag = "2")]
+    pub producer_epoch: u32,
+    #[prost(uint32, tag = "3")]
+    pub transactional_id: u32,
+    #[prost(bool, tag = "4")]
+    pub commit: bool,
+    #[prost(string, repeated, tag = "5")]
+    pub shards: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(uint32, tag = "6")]
+    pub timeout_ms: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ShardEndTxnResult {
+    #[prost(string, tag = "1")]
+    pub shard_name: ::prost::alloc::string::String,
+    #[prost(enumeration = "super::header::ErrorCode", tag = "2")]
+    pub error_code: i32,
+    #[prost(string, tag = "3")]
+    pub error_message: ::prost::alloc::string::String,
+    /// Offset of the control record.
+    #[prost(uint64, tag = "4")]
+    pub offset: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EndTxnRespBody {
+    #[prost(message, repeated, tag = "1")]
+    pub shards: ::prost::alloc::vec::Vec<ShardEndTxnResult>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EndTxnReq {
+    #[prost(message, optional, tag = "1")]
+    pub header: ::core::option::Option<super::header::Header>,
+    #[prost(message, optional, tag = "2")]
+    pub body: ::core::option::Option<EndTxnReqBody>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EndTxnResp {
+    #[prost(message, optional, tag = "1")]
+    pub header: ::core::option::Option<super::header::Header>,
+    #[prost(message, optional, tag = "2")]
+    pub body: ::core::option::Option<EndTxnRespBody>,
+}
@@ -1,9 +1,63 @@
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct FetchReqBody {}
+pub struct FetchShard {
+    #[prost(string, tag = "1")]
+    pub shard_name: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub offset: u64,
+    #[prost(uint32, tag = "3")]
+    pub max_record_num: u32,
+    #[prost(uint64, tag = "4")]
+    pub max_bytes: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FetchReqBody {
+    /// How long the server holds the request when no record is available, 0 returns at once.
+    #[prost(uint32, tag = "1")]
+    pub max_wait_ms: u32,
+    #[prost(uint64, tag = "2")]
+    pub max_bytes: u64,
+    #[prost(message, repeated, tag = "3")]
+    pub shards: ::prost::alloc::vec::Vec<FetchShard>,
+    /// Node id of the follower replica sending the fetch, 0 for consumers. Consumers only
+    /// read records up to the high watermark.
+    #[prost(uint64, tag = "4")]
+    pub replica_id: u64,
+    #[prost(enumeration = "IsolationLevel", tag = "5")]
+    pub isolation_level: i32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ShardFetchResult {
+    #[prost(string, tag = "1")]
+    pub shard_name: ::prost::alloc::string::String,
+    #[prost(enumeration = "super::header::ErrorCode", tag = "2")]
+    pub error_code: i32,
+    #[prost(string, tag = "3")]
+    pub error_message: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "4")]
+    pub start_offset: u64,
+    #[prost(uint64, tag = "5")]
+    pub next_offset: u64,
+    #[prost(message, repeated, tag = "6")]
+    pub records: ::prost::alloc::vec::Vec<super::record::Record>,
+    /// Offset up to which records have been written by all in-sync replicas.
+    #[prost(uint64, tag = "7")]
+    pub high_watermark: u64,
+    /// Offset below which every transaction has ended.
+    #[prost(uint64, tag = "8")]
+    pub last_stable_offset: u64,
+    /// Offset the next fetch continues from, past the records that were filtered out.
+    #[prost(uint64, tag = "9")]
+    pub next_fetch_offset: u64,
+}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct FetchRespBody {}
+pub struct FetchRespBody {
+    #[prost(message, repeated, tag = "1")]
+    pub shards: ::prost::alloc::vec::Vec<ShardFetchResult>,
+}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FetchReq {
@@ -20,3 +74,32 @@ pub struct FetchResp {
     #[prost(message, optional, tag = "2")]
     pub body: ::core::option::Option<FetchRespBody>,
 }
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum IsolationLevel {
+    /// Every record below the high watermark is returned.
+    ReadUncommitted = 0,
+    /// Only records below the last stable offset are returned, without the records of
+    /// aborted transactions and the control records.
+    ReadCommitted = 1,
+}
+impl IsolationLevel {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            IsolationLevel::ReadUncommitted => "ReadUncommitted",
+            IsolationLevel::ReadCommitted => "ReadCommitted",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "ReadUncommitted" => Some(Self::ReadUncommitted),
+            "ReadCommitted" => Some(Self::ReadCommitted),
+            _ => None,
+        }
+    }
+}
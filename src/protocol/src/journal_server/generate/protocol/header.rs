@@ -11,6 +11,10 @@ pub struct RequestCommon {
 pub struct ResponseCommon {
     #[prost(uint32, tag = "1")]
     pub correlation_id: u32,
+    #[prost(enumeration = "ErrorCode", tag = "2")]
+    pub error_code: i32,
+    #[prost(string, tag = "3")]
+    pub error_message: ::prost::alloc::string::String,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -31,6 +35,8 @@ pub struct Header {
 pub enum ApiKey {
     Produce = 0,
     Consume = 1,
+    Metadata = 2,
+    EndTxn = 3,
 }
 impl ApiKey {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -41,6 +47,8 @@ impl ApiKey {
         match self {
             ApiKey::Produce => "produce",
             ApiKey::Consume => "consume",
+            ApiKey::Metadata => "metadata",
+            ApiKey::EndTxn => "end_txn",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -48,6 +56,8 @@ impl ApiKey {
         match value {
             "produce" => Some(Self::Produce),
             "consume" => Some(Self::Consume),
+            "metadata" => Some(Self::Metadata),
+            "end_txn" => Some(Self::EndTxn),
             _ => None,
         }
     }
@@ -105,6 +115,22 @@ impl ApiType {
 #[repr(i32)]
 pub enum ErrorCode {
     Success = 0,
+    UnknownError = 1,
+    /// The node is not the leader of the shard, the client should look the leader up again.
+    NotLeader = 2,
+    /// The requested offset is before the start or after the end of the shard.
+    OffsetOutOfRange = 3,
+    ShardNotFound = 4,
+    InvalidRequest = 5,
+    /// The records were written by the leader but not replicated to the in-sync replicas
+    /// within the timeout of the request.
+    ReplicationTimeout = 6,
+    /// The sequence number of an idempotent producer does not follow the last one written.
+    OutOfOrderSequence = 7,
+    /// A producer with a newer epoch has written to the shard.
+    ProducerFenced = 8,
+    /// The request does not match the transaction of the producer on the shard.
+    InvalidTransaction = 9,
 }
 impl ErrorCode {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -114,12 +140,30 @@ impl ErrorCode {
     pub fn as_str_name(&self) -> &'static str {
         match self {
             ErrorCode::Success => "Success",
+            ErrorCode::UnknownError => "UnknownError",
+            ErrorCode::NotLeader => "NotLeader",
+            ErrorCode::OffsetOutOfRange => "OffsetOutOfRange",
+            ErrorCode::ShardNotFound => "ShardNotFound",
+            ErrorCode::InvalidRequest => "InvalidRequest",
+            ErrorCode::ReplicationTimeout => "ReplicationTimeout",
+            ErrorCode::OutOfOrderSequence => "OutOfOrderSequence",
+            ErrorCode::ProducerFenced => "ProducerFenced",
+            ErrorCode::InvalidTransaction => "InvalidTransaction",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
     pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
         match value {
             "Success" => Some(Self::Success),
+            "UnknownError" => Some(Self::UnknownError),
+            "NotLeader" => Some(Self::NotLeader),
+            "OffsetOutOfRange" => Some(Self::OffsetOutOfRange),
+            "ShardNotFound" => Some(Self::ShardNotFound),
+            "InvalidRequest" => Some(Self::InvalidRequest),
+            "ReplicationTimeout" => Some(Self::ReplicationTimeout),
+            "OutOfOrderSequence" => Some(Self::OutOfOrderSequence),
+            "ProducerFenced" => Some(Self::ProducerFenced),
+            "InvalidTransaction" => Some(Self::InvalidTransaction),
             _ => None,
         }
     }
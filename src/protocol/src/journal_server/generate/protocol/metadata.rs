@@ -0,0 +1,55 @@
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MetadataReqBody {
+    /// Empty returns the metadata of every shard on the node.
+    #[prost(string, repeated, tag = "1")]
+    pub shards: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NodeInfo {
+    #[prost(uint64, tag = "1")]
+    pub node_id: u64,
+    #[prost(string, tag = "2")]
+    pub addr: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ShardMetadata {
+    #[prost(string, tag = "1")]
+    pub shard_name: ::prost::alloc::string::String,
+    #[prost(enumeration = "super::header::ErrorCode", tag = "2")]
+    pub error_code: i32,
+    #[prost(uint64, tag = "3")]
+    pub leader_id: u64,
+    #[prost(string, tag = "4")]
+    pub leader_addr: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "5")]
+    pub start_offset: u64,
+    #[prost(uint64, tag = "6")]
+    pub next_offset: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MetadataRespBody {
+    #[prost(message, repeated, tag = "1")]
+    pub nodes: ::prost::alloc::vec::Vec<NodeInfo>,
+    #[prost(message, repeated, tag = "2")]
+    pub shards: ::prost::alloc::vec::Vec<ShardMetadata>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MetadataReq {
+    #[prost(message, optional, tag = "1")]
+    pub header: ::core::option::Option<super::header::Header>,
+    #[prost(message, optional, tag = "2")]
+    pub body: ::core::option::Option<MetadataReqBody>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MetadataResp {
+    #[prost(message, optional, tag = "1")]
+    pub header: ::core::option::Option<super::header::Header>,
+    #[prost(message, optional, tag = "2")]
+    pub body: ::core::option::Option<MetadataRespBody>,
+}
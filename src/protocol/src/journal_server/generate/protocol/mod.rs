@@ -12,6 +12,9 @@
 // limitations under the License.
 
 
+pub mod end_txn;
 pub mod fetch;
 pub mod header;
+pub mod metadata;
 pub mod produce;
+pub mod record;
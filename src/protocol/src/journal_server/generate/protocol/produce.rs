@@ -1,29 +1,53 @@
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct TopicData {
+pub struct ShardData {
     #[prost(string, tag = "1")]
-    pub name: ::prost::alloc::string::String,
-    #[prost(message, optional, tag = "2")]
-    pub partition_data: ::core::option::Option<PartitionData>,
+    pub shard_name: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "2")]
+    pub records: ::prost::alloc::vec::Vec<super::record::Record>,
+    /// Sequence number of the first record, counted per shard by an idempotent producer.
+    #[prost(uint32, tag = "3")]
+    pub base_sequence: u32,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct PartitionData {}
-#[allow(clippy::derive_partial_eq_without_eq)]
-#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ProduceReqBody {
+    /// Number of the transaction of the producer the records belong to, 0 when they are
+    /// not part of a transaction.
     #[prost(uint32, tag = "1")]
     pub transactional_id: u32,
-    #[prost(uint32, tag = "2")]
-    pub acks: u32,
+    #[prost(enumeration = "Acks", tag = "2")]
+    pub acks: i32,
     #[prost(uint32, tag = "3")]
     pub timeout_ms: u32,
-    #[prost(message, optional, tag = "4")]
-    pub topic_data: ::core::option::Option<TopicData>,
+    #[prost(message, repeated, tag = "4")]
+    pub shards: ::prost::alloc::vec::Vec<ShardData>,
+    /// Chosen by an idempotent producer, the records of a retried request are only written
+    /// once. 0 disables idempotence.
+    #[prost(uint64, tag = "5")]
+    pub producer_id: u64,
+    #[prost(uint32, tag = "6")]
+    pub producer_epoch: u32,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct ProduceRespBody {}
+pub struct ShardProduceResult {
+    #[prost(string, tag = "1")]
+    pub shard_name: ::prost::alloc::string::String,
+    #[prost(enumeration = "super::header::ErrorCode", tag = "2")]
+    pub error_code: i32,
+    #[prost(string, tag = "3")]
+    pub error_message: ::prost::alloc::string::String,
+    /// Offsets assigned to the records, in the order they were sent.
+    #[prost(uint64, repeated, tag = "4")]
+    pub offsets: ::prost::alloc::vec::Vec<u64>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProduceRespBody {
+    #[prost(message, repeated, tag = "1")]
+    pub shards: ::prost::alloc::vec::Vec<ShardProduceResult>,
+}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ProduceReq {
@@ -40,3 +64,32 @@ pub struct ProduceResp {
     #[prost(message, optional, tag = "2")]
     pub body: ::core::option::Option<ProduceRespBody>,
 }
+/// How many replicas must have the records before a produce is answered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Acks {
+    /// The leader has written the records.
+    Leader = 0,
+    /// Every in-sync replica has written the records.
+    All = 1,
+}
+impl Acks {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Acks::Leader => "Leader",
+            Acks::All => "All",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "Leader" => Some(Self::Leader),
+            "All" => Some(Self::All),
+            _ => None,
+        }
+    }
+}
@@ -1,3 +1,69 @@
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct Record {}
+pub struct RecordHeader {
+    #[prost(string, tag = "1")]
+    pub key: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub value: ::prost::alloc::vec::Vec<u8>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Record {
+    #[prost(uint64, tag = "1")]
+    pub offset: u64,
+    /// Milliseconds since the epoch, 0 means the time the record is stored.
+    #[prost(uint64, tag = "2")]
+    pub timestamp: u64,
+    #[prost(bytes = "vec", tag = "3")]
+    pub key: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "4")]
+    pub value: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, repeated, tag = "5")]
+    pub headers: ::prost::alloc::vec::Vec<RecordHeader>,
+    /// Set by the server from the produce request, 0 when the record was not written by an
+    /// idempotent producer.
+    #[prost(uint64, tag = "6")]
+    pub producer_id: u64,
+    #[prost(uint32, tag = "7")]
+    pub producer_epoch: u32,
+    #[prost(uint32, tag = "8")]
+    pub sequence: u32,
+    #[prost(bool, tag = "9")]
+    pub transactional: bool,
+    #[prost(enumeration = "ControlType", tag = "10")]
+    pub control: i32,
+    /// The value is stored compressed, only set on the records fetched by the followers,
+    /// consumers get the value decompressed.
+    #[prost(bool, tag = "11")]
+    pub compressed: bool,
+}
+/// A control record ends a transaction on a shard, it carries no key or value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ControlType {
+    None = 0,
+    Commit = 1,
+    Abort = 2,
+}
+impl ControlType {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ControlType::None => "None",
+            ControlType::Commit => "Commit",
+            ControlType::Abort => "Abort",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "None" => Some(Self::None),
+            "Commit" => Some(Self::Commit),
+            "Abort" => Some(Self::Abort),
+            _ => None,
+        }
+    }
+}
@@ -19,3 +19,4 @@ pub mod broker_server;
 pub mod mqtt;
 pub mod placement_center;
 pub mod journal_server;
+pub mod storage_adapter;
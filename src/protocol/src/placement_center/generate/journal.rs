@@ -7,6 +7,20 @@ pub struct CreateShardRequest {
     pub shard_name: ::prost::alloc::string::String,
     #[prost(uint32, tag = "3")]
     pub replica: u32,
+    /// delete or compact, empty means delete. A compacted shard only keeps the latest
+    /// record of every key.
+    #[prost(string, tag = "4")]
+    pub cleanup_policy: ::prost::alloc::string::String,
+    /// How long a compacted shard keeps the tombstone of a deleted key, 0 uses the default
+    /// of the journal server.
+    #[prost(uint64, tag = "5")]
+    pub tombstone_retention_ms: u64,
+    /// none, lz4, zstd or snappy, empty means none. The leader compresses the values of at
+    /// least compression_min_size bytes.
+    #[prost(string, tag = "6")]
+    pub compression: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "7")]
+    pub compression_min_size: u64,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -15,6 +29,8 @@ pub struct GetShardRequest {
     pub cluster_name: ::prost::alloc::string::String,
     #[prost(string, tag = "2")]
     pub shard_name: ::prost::alloc::string::String,
+    #[prost(bool, tag = "3")]
+    pub stale_read: bool,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -31,6 +47,39 @@ pub struct GetShardReply {
     pub replicas: ::prost::alloc::vec::Vec<u8>,
     #[prost(string, tag = "6")]
     pub status: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "7")]
+    pub leader_id: u64,
+    #[prost(string, tag = "8")]
+    pub leader_addr: ::prost::alloc::string::String,
+    #[prost(uint64, repeated, tag = "9")]
+    pub replica_nodes: ::prost::alloc::vec::Vec<u64>,
+    /// Replicas of the pre-created next segment, they copy the shard ahead of the roll.
+    #[prost(uint64, repeated, tag = "10")]
+    pub next_replica_nodes: ::prost::alloc::vec::Vec<u64>,
+    #[prost(string, tag = "11")]
+    pub cleanup_policy: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "12")]
+    pub tombstone_retention_ms: u64,
+    #[prost(string, tag = "13")]
+    pub compression: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "14")]
+    pub compression_min_size: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListShardRequest {
+    #[prost(string, tag = "1")]
+    pub cluster_name: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub node_id: u64,
+    #[prost(bool, tag = "3")]
+    pub stale_read: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListShardReply {
+    #[prost(message, repeated, tag = "1")]
+    pub shards: ::prost::alloc::vec::Vec<GetShardReply>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -47,6 +96,9 @@ pub struct CreateSegmentRequest {
     pub cluster_name: ::prost::alloc::string::String,
     #[prost(string, tag = "2")]
     pub shard_name: ::prost::alloc::string::String,
+    /// JSON list of the replicas, chosen by the placement center leader.
+    #[prost(bytes = "vec", tag = "3")]
+    pub replicas: ::prost::alloc::vec::Vec<u8>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -58,6 +110,72 @@ pub struct DeleteSegmentRequest {
     #[prost(uint64, tag = "3")]
     pub segment_seq: u64,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReportShardStatusRequest {
+    #[prost(string, tag = "1")]
+    pub cluster_name: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub node_id: u64,
+    #[prost(message, repeated, tag = "3")]
+    pub shards: ::prost::alloc::vec::Vec<ShardStatus>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ShardStatus {
+    #[prost(string, tag = "1")]
+    pub shard_name: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub next_offset: u64,
+    #[prost(uint64, tag = "3")]
+    pub high_watermark: u64,
+    #[prost(uint64, tag = "4")]
+    pub log_bytes: u64,
+}
+/// The segment being written stops at end_offset and the pre-created next segment takes
+/// over, led by its replica at index replica_leader.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RollSegmentRequest {
+    #[prost(string, tag = "1")]
+    pub cluster_name: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub shard_name: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "3")]
+    pub segment_seq: u64,
+    #[prost(uint64, tag = "4")]
+    pub end_offset: u64,
+    #[prost(uint64, tag = "5")]
+    pub end_bytes: u64,
+    #[prost(uint32, tag = "6")]
+    pub replica_leader: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateSegmentStatusRequest {
+    #[prost(string, tag = "1")]
+    pub cluster_name: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub shard_name: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "3")]
+    pub segment_seq: u64,
+    #[prost(string, tag = "4")]
+    pub cur_status: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub next_status: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateSegmentLeaderRequest {
+    #[prost(string, tag = "1")]
+    pub cluster_name: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub shard_name: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "3")]
+    pub segment_seq: u64,
+    #[prost(uint32, tag = "4")]
+    pub replica_leader: u32,
+}
 /// Generated client implementations.
 pub mod engine_service_client {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
@@ -192,6 +310,29 @@ pub mod engine_service_client {
                 .insert(GrpcMethod::new("journal.EngineService", "GetShard"));
             self.inner.unary(req, path, codec).await
         }
+        /// Lists the shards with a replica on the node, or all shards when node_id is 0.
+        pub async fn list_shard(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListShardRequest>,
+        ) -> std::result::Result<tonic::Response<super::ListShardReply>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/journal.EngineService/ListShard",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("journal.EngineService", "ListShard"));
+            self.inner.unary(req, path, codec).await
+        }
         ///
         pub async fn delete_shard(
             &mut self,
@@ -270,6 +411,32 @@ pub mod engine_service_client {
                 .insert(GrpcMethod::new("journal.EngineService", "DeleteSegment"));
             self.inner.unary(req, path, codec).await
         }
+        /// Journal server nodes report the progress of the shards they hold.
+        pub async fn report_shard_status(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReportShardStatusRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::super::common::CommonReply>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/journal.EngineService/ReportShardStatus",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("journal.EngineService", "ReportShardStatus"));
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -292,6 +459,11 @@ pub mod engine_service_server {
             &self,
             request: tonic::Request<super::GetShardRequest>,
         ) -> std::result::Result<tonic::Response<super::GetShardReply>, tonic::Status>;
+        /// Lists the shards with a replica on the node, or all shards when node_id is 0.
+        async fn list_shard(
+            &self,
+            request: tonic::Request<super::ListShardRequest>,
+        ) -> std::result::Result<tonic::Response<super::ListShardReply>, tonic::Status>;
         ///
         async fn delete_shard(
             &self,
@@ -316,6 +488,14 @@ pub mod engine_service_server {
             tonic::Response<super::super::common::CommonReply>,
             tonic::Status,
         >;
+        /// Journal server nodes report the progress of the shards they hold.
+        async fn report_shard_status(
+            &self,
+            request: tonic::Request<super::ReportShardStatusRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::super::common::CommonReply>,
+            tonic::Status,
+        >;
     }
     #[derive(Debug)]
     pub struct EngineServiceServer<T: EngineService> {
@@ -488,6 +668,52 @@ pub mod engine_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/journal.EngineService/ListShard" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListShardSvc<T: EngineService>(pub Arc<T>);
+                    impl<
+                        T: EngineService,
+                    > tonic::server::UnaryService<super::ListShardRequest>
+                    for ListShardSvc<T> {
+                        type Response = super::ListShardReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListShardRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as EngineService>::list_shard(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ListShardSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/journal.EngineService/DeleteShard" => {
                     #[allow(non_camel_case_types)]
                     struct DeleteShardSvc<T: EngineService>(pub Arc<T>);
@@ -626,6 +852,52 @@ pub mod engine_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/journal.EngineService/ReportShardStatus" => {
+                    #[allow(non_camel_case_types)]
+                    struct ReportShardStatusSvc<T: EngineService>(pub Arc<T>);
+                    impl<
+                        T: EngineService,
+                    > tonic::server::UnaryService<super::ReportShardStatusRequest>
+                    for ReportShardStatusSvc<T> {
+                        type Response = super::super::common::CommonReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReportShardStatusRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as EngineService>::report_shard_status(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ReportShardStatusSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
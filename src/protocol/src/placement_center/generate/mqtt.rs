@@ -5,6 +5,8 @@ pub struct GetShareSubLeaderRequest {
     pub group_name: ::prost::alloc::string::String,
     #[prost(string, tag = "3")]
     pub cluster_name: ::prost::alloc::string::String,
+    #[prost(bool, tag = "4")]
+    pub stale_read: bool,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -23,6 +25,10 @@ pub struct ListUserRequest {
     pub cluster_name: ::prost::alloc::string::String,
     #[prost(string, tag = "2")]
     pub username: ::prost::alloc::string::String,
+    /// Serves the read from the local data of the node, which may lag behind the
+    /// leader, instead of a linearizable read.
+    #[prost(bool, tag = "3")]
+    pub stale_read: bool,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -55,6 +61,8 @@ pub struct ListTopicRequest {
     pub cluster_name: ::prost::alloc::string::String,
     #[prost(string, tag = "2")]
     pub topic_name: ::prost::alloc::string::String,
+    #[prost(bool, tag = "3")]
+    pub stale_read: bool,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -99,6 +107,8 @@ pub struct ListSessionRequest {
     pub cluster_name: ::prost::alloc::string::String,
     #[prost(string, tag = "2")]
     pub client_id: ::prost::alloc::string::String,
+    #[prost(bool, tag = "3")]
+    pub stale_read: bool,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -131,6 +141,15 @@ pub struct UpdateSessionRequest {
     pub reconnect_time: u64,
     #[prost(uint64, tag = "6")]
     pub distinct_time: u64,
+    /// When set, the session is only updated while it is still on this broker
+    #[prost(uint64, tag = "7")]
+    pub expected_broker_id: u64,
+    /// When set, the session is only updated while the client has not connected again,
+    /// a broker that restarted with the same id may already hold a new connection
+    #[prost(uint64, tag = "8")]
+    pub expected_connection_id: u64,
+    #[prost(uint64, tag = "9")]
+    pub expected_reconnect_time: u64,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -155,6 +174,8 @@ pub struct SaveLastWillMessageRequest {
 pub struct ListAclRequest {
     #[prost(string, tag = "1")]
     pub cluster_name: ::prost::alloc::string::String,
+    #[prost(bool, tag = "2")]
+    pub stale_read: bool,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -178,6 +199,24 @@ pub struct CreateAclRequest {
     #[prost(bytes = "vec", tag = "2")]
     pub acl: ::prost::alloc::vec::Vec<u8>,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateBlacklistRequest {
+    #[prost(string, tag = "1")]
+    pub cluster_name: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub blacklist: ::prost::alloc::vec::Vec<u8>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteBlacklistRequest {
+    #[prost(string, tag = "1")]
+    pub cluster_name: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub blacklist_type: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub resource_name: ::prost::alloc::string::String,
+}
 /// Generated client implementations.
 pub mod mqtt_service_client {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
@@ -26,6 +26,82 @@ pub struct SendRaftConfChangeRequest {
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SendRaftConfChangeReply {}
+/// A chunk of a snapshot sent by the leader to a follower that is too far behind to be
+/// sent the raft log. Chunks are sent in order, the last one carries the raft message of
+/// the snapshot, which the follower steps once the whole snapshot is stored.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SendRaftSnapshotRequest {
+    #[prost(string, tag = "1")]
+    pub snapshot: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub offset: u64,
+    #[prost(bytes = "vec", tag = "3")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bool, tag = "4")]
+    pub done: bool,
+    #[prost(bytes = "vec", tag = "5")]
+    pub message: ::prost::alloc::vec::Vec<u8>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SendRaftSnapshotReply {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RaftMember {
+    #[prost(uint64, tag = "1")]
+    pub node_id: u64,
+    #[prost(string, tag = "2")]
+    pub node_addr: ::prost::alloc::string::String,
+    #[prost(bool, tag = "3")]
+    pub learner: bool,
+    /// Index of the last entry known to be replicated to the member, only known by the leader
+    #[prost(uint64, tag = "4")]
+    pub matched: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListRaftMemberRequest {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListRaftMemberReply {
+    #[prost(uint64, tag = "1")]
+    pub leader_id: u64,
+    #[prost(uint64, tag = "2")]
+    pub commit_index: u64,
+    #[prost(message, repeated, tag = "3")]
+    pub members: ::prost::alloc::vec::Vec<RaftMember>,
+}
+/// Adds a node to the placement center cluster as a voter, or as a learner which
+/// receives the raft log without voting until it is promoted.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AddRaftMemberRequest {
+    #[prost(uint64, tag = "1")]
+    pub node_id: u64,
+    #[prost(string, tag = "2")]
+    pub node_addr: ::prost::alloc::string::String,
+    #[prost(bool, tag = "3")]
+    pub learner: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PromoteRaftLearnerRequest {
+    #[prost(uint64, tag = "1")]
+    pub node_id: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RemoveRaftMemberRequest {
+    #[prost(uint64, tag = "1")]
+    pub node_id: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TransferRaftLeaderRequest {
+    #[prost(uint64, tag = "1")]
+    pub node_id: u64,
+}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RegisterNodeRequest {
@@ -67,6 +143,19 @@ pub struct ReportMonitorRequest {
     pub disk_rate: f32,
     #[prost(float, tag = "6")]
     pub network_rate: f32,
+    /// Usage of every data folder of a journal server node.
+    #[prost(message, repeated, tag = "7")]
+    pub folds: ::prost::alloc::vec::Vec<FoldMonitor>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FoldMonitor {
+    #[prost(string, tag = "1")]
+    pub fold: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub used_bytes: u64,
+    #[prost(uint64, tag = "3")]
+    pub segment_num: u64,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -136,6 +225,185 @@ pub struct DeleteIdempotentDataRequest {
     #[prost(uint64, tag = "3")]
     pub seq_num: u64,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LockHolder {
+    #[prost(string, tag = "1")]
+    pub cluster_name: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub node_id: u64,
+    /// Tells apart the holders on the same node
+    #[prost(string, tag = "3")]
+    pub holder_id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AcquireLockRequest {
+    #[prost(string, tag = "1")]
+    pub lock_name: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub holder: ::core::option::Option<LockHolder>,
+    /// Seconds without a heartbeat of the node after which the lock is released
+    #[prost(uint64, tag = "3")]
+    pub ttl: u64,
+    /// Milliseconds to wait for the lock, 0 to try once
+    #[prost(uint64, tag = "4")]
+    pub timeout_ms: u64,
+    /// Seconds, set by the placement center node that proposes the acquisition
+    #[prost(uint64, tag = "5")]
+    pub acquire_time: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AcquireLockReply {
+    #[prost(bool, tag = "1")]
+    pub acquired: bool,
+    /// Grows with every acquisition of the lock, the writes made under the lock carry it
+    #[prost(uint64, tag = "2")]
+    pub fencing_token: u64,
+    /// The holder of the lock
+    #[prost(message, optional, tag = "3")]
+    pub holder: ::core::option::Option<LockHolder>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReleaseLockRequest {
+    #[prost(string, tag = "1")]
+    pub lock_name: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub holder: ::core::option::Option<LockHolder>,
+    #[prost(uint64, tag = "3")]
+    pub fencing_token: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetLockRequest {
+    #[prost(string, tag = "1")]
+    pub lock_name: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetLockReply {
+    #[prost(bool, tag = "1")]
+    pub held: bool,
+    #[prost(message, optional, tag = "2")]
+    pub holder: ::core::option::Option<LockHolder>,
+    #[prost(uint64, tag = "3")]
+    pub fencing_token: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CampaignRequest {
+    #[prost(string, tag = "1")]
+    pub election_name: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub candidate: ::core::option::Option<LockHolder>,
+    /// Published to the observers of the election, such as the address of the candidate
+    #[prost(string, tag = "3")]
+    pub value: ::prost::alloc::string::String,
+    /// Seconds without a heartbeat of the node after which the leader is removed
+    #[prost(uint64, tag = "4")]
+    pub ttl: u64,
+    /// Milliseconds to wait for the election, 0 to try once
+    #[prost(uint64, tag = "5")]
+    pub timeout_ms: u64,
+    /// Seconds, set by the placement center node that proposes the campaign
+    #[prost(uint64, tag = "6")]
+    pub acquire_time: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CampaignReply {
+    #[prost(bool, tag = "1")]
+    pub elected: bool,
+    /// Grows with every new leader, it fences the writes of the previous leaders
+    #[prost(uint64, tag = "2")]
+    pub term: u64,
+    #[prost(message, optional, tag = "3")]
+    pub leader: ::core::option::Option<LockHolder>,
+    #[prost(string, tag = "4")]
+    pub value: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ResignRequest {
+    #[prost(string, tag = "1")]
+    pub election_name: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub leader: ::core::option::Option<LockHolder>,
+    #[prost(uint64, tag = "3")]
+    pub term: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetElectionLeaderRequest {
+    #[prost(string, tag = "1")]
+    pub election_name: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetElectionLeaderReply {
+    #[prost(bool, tag = "1")]
+    pub has_leader: bool,
+    #[prost(message, optional, tag = "2")]
+    pub leader: ::core::option::Option<LockHolder>,
+    #[prost(string, tag = "3")]
+    pub value: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "4")]
+    pub term: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateBackupRequest {
+    /// Only exports the metadata of this cluster, all metadata when empty
+    #[prost(string, tag = "1")]
+    pub cluster_name: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateBackupReply {
+    #[prost(string, tag = "1")]
+    pub backup_name: ::prost::alloc::string::String,
+    /// The raft index the backup was taken at
+    #[prost(uint64, tag = "2")]
+    pub applied_index: u64,
+    #[prost(uint64, tag = "3")]
+    pub key_num: u64,
+    #[prost(uint64, tag = "4")]
+    pub len: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReadBackupRequest {
+    #[prost(string, tag = "1")]
+    pub backup_name: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub offset: u64,
+    #[prost(uint64, tag = "3")]
+    pub max_bytes: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReadBackupReply {
+    #[prost(bytes = "vec", tag = "1")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MetadataEntry {
+    #[prost(string, tag = "1")]
+    pub key: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub value: ::prost::alloc::vec::Vec<u8>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ImportMetadataRequest {
+    #[prost(string, tag = "1")]
+    pub cluster_name: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "2")]
+    pub entries: ::prost::alloc::vec::Vec<MetadataEntry>,
+}
 /// Generated client implementations.
 pub mod placement_center_service_client {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
@@ -571,112 +839,667 @@ pub mod placement_center_service_client {
                 );
             self.inner.unary(req, path, codec).await
         }
-    }
-}
-/// Generated server implementations.
-pub mod placement_center_service_server {
-    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
-    use tonic::codegen::*;
-    /// Generated trait containing gRPC methods that should be implemented for use with PlacementCenterServiceServer.
-    #[async_trait]
-    pub trait PlacementCenterService: Send + Sync + 'static {
-        async fn register_node(
-            &self,
-            request: tonic::Request<super::RegisterNodeRequest>,
+        pub async fn send_raft_snapshot(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SendRaftSnapshotRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::super::common::CommonReply>,
+            tonic::Response<super::SendRaftSnapshotReply>,
             tonic::Status,
-        >;
-        async fn un_register_node(
-            &self,
-            request: tonic::Request<super::UnRegisterNodeRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/placement.PlacementCenterService/SendRaftSnapshot",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "placement.PlacementCenterService",
+                        "SendRaftSnapshot",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_raft_member(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListRaftMemberRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListRaftMemberReply>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/placement.PlacementCenterService/ListRaftMember",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "placement.PlacementCenterService",
+                        "ListRaftMember",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn add_raft_member(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AddRaftMemberRequest>,
         ) -> std::result::Result<
             tonic::Response<super::super::common::CommonReply>,
             tonic::Status,
-        >;
-        async fn heartbeat(
-            &self,
-            request: tonic::Request<super::HeartbeatRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/placement.PlacementCenterService/AddRaftMember",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "placement.PlacementCenterService",
+                        "AddRaftMember",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn promote_raft_learner(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PromoteRaftLearnerRequest>,
         ) -> std::result::Result<
             tonic::Response<super::super::common::CommonReply>,
             tonic::Status,
-        >;
-        async fn report_monitor(
-            &self,
-            request: tonic::Request<super::ReportMonitorRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/placement.PlacementCenterService/PromoteRaftLearner",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "placement.PlacementCenterService",
+                        "PromoteRaftLearner",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn remove_raft_member(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RemoveRaftMemberRequest>,
         ) -> std::result::Result<
             tonic::Response<super::super::common::CommonReply>,
             tonic::Status,
-        >;
-        async fn send_raft_message(
-            &self,
-            request: tonic::Request<super::SendRaftMessageRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/placement.PlacementCenterService/RemoveRaftMember",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "placement.PlacementCenterService",
+                        "RemoveRaftMember",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn transfer_raft_leader(
+            &mut self,
+            request: impl tonic::IntoRequest<super::TransferRaftLeaderRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::SendRaftMessageReply>,
+            tonic::Response<super::super::common::CommonReply>,
             tonic::Status,
-        >;
-        async fn send_raft_conf_change(
-            &self,
-            request: tonic::Request<super::SendRaftConfChangeRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/placement.PlacementCenterService/TransferRaftLeader",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "placement.PlacementCenterService",
+                        "TransferRaftLeader",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn acquire_lock(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AcquireLockRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::SendRaftConfChangeReply>,
+            tonic::Response<super::AcquireLockReply>,
             tonic::Status,
-        >;
-        async fn set_resource_config(
-            &self,
-            request: tonic::Request<super::SetResourceConfigRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/placement.PlacementCenterService/AcquireLock",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "placement.PlacementCenterService",
+                        "AcquireLock",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn release_lock(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReleaseLockRequest>,
         ) -> std::result::Result<
             tonic::Response<super::super::common::CommonReply>,
             tonic::Status,
-        >;
-        async fn get_resource_config(
-            &self,
-            request: tonic::Request<super::GetResourceConfigRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/placement.PlacementCenterService/ReleaseLock",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "placement.PlacementCenterService",
+                        "ReleaseLock",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_lock(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetLockRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetResourceConfigReply>,
+            tonic::Response<super::GetLockReply>,
             tonic::Status,
-        >;
-        async fn delete_resource_config(
-            &self,
-            request: tonic::Request<super::DeleteResourceConfigRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/placement.PlacementCenterService/GetLock",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "placement.PlacementCenterService",
+                        "GetLock",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn campaign(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CampaignRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::super::common::CommonReply>,
+            tonic::Response<super::CampaignReply>,
             tonic::Status,
-        >;
-        async fn set_idempotent_data(
-            &self,
-            request: tonic::Request<super::SetIdempotentDataRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/placement.PlacementCenterService/Campaign",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "placement.PlacementCenterService",
+                        "Campaign",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn resign(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ResignRequest>,
         ) -> std::result::Result<
             tonic::Response<super::super::common::CommonReply>,
             tonic::Status,
-        >;
-        async fn exists_idempotent_data(
-            &self,
-            request: tonic::Request<super::ExistsIdempotentDataRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/placement.PlacementCenterService/Resign",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "placement.PlacementCenterService",
+                        "Resign",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_election_leader(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetElectionLeaderRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::ExistsIdempotentDataReply>,
+            tonic::Response<super::GetElectionLeaderReply>,
             tonic::Status,
-        >;
-        async fn delete_idempotent_data(
-            &self,
-            request: tonic::Request<super::DeleteIdempotentDataRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/placement.PlacementCenterService/GetElectionLeader",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "placement.PlacementCenterService",
+                        "GetElectionLeader",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn create_backup(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateBackupRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::super::common::CommonReply>,
+            tonic::Response<super::CreateBackupReply>,
             tonic::Status,
-        >;
-    }
-    #[derive(Debug)]
-    pub struct PlacementCenterServiceServer<T: PlacementCenterService> {
-        inner: _Inner<T>,
-        accept_compression_encodings: EnabledCompressionEncodings,
-        send_compression_encodings: EnabledCompressionEncodings,
-        max_decoding_message_size: Option<usize>,
-        max_encoding_message_size: Option<usize>,
-    }
-    struct _Inner<T>(Arc<T>);
-    impl<T: PlacementCenterService> PlacementCenterServiceServer<T> {
-        pub fn new(inner: T) -> Self {
-            Self::from_arc(Arc::new(inner))
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/placement.PlacementCenterService/CreateBackup",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "placement.PlacementCenterService",
+                        "CreateBackup",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn read_backup(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReadBackupRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ReadBackupReply>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/placement.PlacementCenterService/ReadBackup",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "placement.PlacementCenterService",
+                        "ReadBackup",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn import_metadata(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ImportMetadataRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::super::common::CommonReply>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/placement.PlacementCenterService/ImportMetadata",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "placement.PlacementCenterService",
+                        "ImportMetadata",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod placement_center_service_server {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with PlacementCenterServiceServer.
+    #[async_trait]
+    pub trait PlacementCenterService: Send + Sync + 'static {
+        async fn register_node(
+            &self,
+            request: tonic::Request<super::RegisterNodeRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::super::common::CommonReply>,
+            tonic::Status,
+        >;
+        async fn un_register_node(
+            &self,
+            request: tonic::Request<super::UnRegisterNodeRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::super::common::CommonReply>,
+            tonic::Status,
+        >;
+        async fn heartbeat(
+            &self,
+            request: tonic::Request<super::HeartbeatRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::super::common::CommonReply>,
+            tonic::Status,
+        >;
+        async fn report_monitor(
+            &self,
+            request: tonic::Request<super::ReportMonitorRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::super::common::CommonReply>,
+            tonic::Status,
+        >;
+        async fn send_raft_message(
+            &self,
+            request: tonic::Request<super::SendRaftMessageRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SendRaftMessageReply>,
+            tonic::Status,
+        >;
+        async fn send_raft_conf_change(
+            &self,
+            request: tonic::Request<super::SendRaftConfChangeRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SendRaftConfChangeReply>,
+            tonic::Status,
+        >;
+        async fn set_resource_config(
+            &self,
+            request: tonic::Request<super::SetResourceConfigRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::super::common::CommonReply>,
+            tonic::Status,
+        >;
+        async fn get_resource_config(
+            &self,
+            request: tonic::Request<super::GetResourceConfigRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetResourceConfigReply>,
+            tonic::Status,
+        >;
+        async fn delete_resource_config(
+            &self,
+            request: tonic::Request<super::DeleteResourceConfigRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::super::common::CommonReply>,
+            tonic::Status,
+        >;
+        async fn set_idempotent_data(
+            &self,
+            request: tonic::Request<super::SetIdempotentDataRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::super::common::CommonReply>,
+            tonic::Status,
+        >;
+        async fn exists_idempotent_data(
+            &self,
+            request: tonic::Request<super::ExistsIdempotentDataRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ExistsIdempotentDataReply>,
+            tonic::Status,
+        >;
+        async fn delete_idempotent_data(
+            &self,
+            request: tonic::Request<super::DeleteIdempotentDataRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::super::common::CommonReply>,
+            tonic::Status,
+        >;
+        async fn send_raft_snapshot(
+            &self,
+            request: tonic::Request<super::SendRaftSnapshotRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SendRaftSnapshotReply>,
+            tonic::Status,
+        >;
+        async fn list_raft_member(
+            &self,
+            request: tonic::Request<super::ListRaftMemberRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListRaftMemberReply>,
+            tonic::Status,
+        >;
+        async fn add_raft_member(
+            &self,
+            request: tonic::Request<super::AddRaftMemberRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::super::common::CommonReply>,
+            tonic::Status,
+        >;
+        async fn promote_raft_learner(
+            &self,
+            request: tonic::Request<super::PromoteRaftLearnerRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::super::common::CommonReply>,
+            tonic::Status,
+        >;
+        async fn remove_raft_member(
+            &self,
+            request: tonic::Request<super::RemoveRaftMemberRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::super::common::CommonReply>,
+            tonic::Status,
+        >;
+        async fn transfer_raft_leader(
+            &self,
+            request: tonic::Request<super::TransferRaftLeaderRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::super::common::CommonReply>,
+            tonic::Status,
+        >;
+        async fn acquire_lock(
+            &self,
+            request: tonic::Request<super::AcquireLockRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::AcquireLockReply>,
+            tonic::Status,
+        >;
+        async fn release_lock(
+            &self,
+            request: tonic::Request<super::ReleaseLockRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::super::common::CommonReply>,
+            tonic::Status,
+        >;
+        async fn get_lock(
+            &self,
+            request: tonic::Request<super::GetLockRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetLockReply>,
+            tonic::Status,
+        >;
+        async fn campaign(
+            &self,
+            request: tonic::Request<super::CampaignRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CampaignReply>,
+            tonic::Status,
+        >;
+        async fn resign(
+            &self,
+            request: tonic::Request<super::ResignRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::super::common::CommonReply>,
+            tonic::Status,
+        >;
+        async fn get_election_leader(
+            &self,
+            request: tonic::Request<super::GetElectionLeaderRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetElectionLeaderReply>,
+            tonic::Status,
+        >;
+        async fn create_backup(
+            &self,
+            request: tonic::Request<super::CreateBackupRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CreateBackupReply>,
+            tonic::Status,
+        >;
+        async fn read_backup(
+            &self,
+            request: tonic::Request<super::ReadBackupRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ReadBackupReply>,
+            tonic::Status,
+        >;
+        async fn import_metadata(
+            &self,
+            request: tonic::Request<super::ImportMetadataRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::super::common::CommonReply>,
+            tonic::Status,
+        >;
+    }
+    #[derive(Debug)]
+    pub struct PlacementCenterServiceServer<T: PlacementCenterService> {
+        inner: _Inner<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
+        max_decoding_message_size: Option<usize>,
+        max_encoding_message_size: Option<usize>,
+    }
+    struct _Inner<T>(Arc<T>);
+    impl<T: PlacementCenterService> PlacementCenterServiceServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
         }
         pub fn from_arc(inner: Arc<T>) -> Self {
             let inner = _Inner(inner);
@@ -747,26 +1570,626 @@ pub mod placement_center_service_server {
             match req.uri().path() {
                 "/placement.PlacementCenterService/RegisterNode" => {
                     #[allow(non_camel_case_types)]
-                    struct RegisterNodeSvc<T: PlacementCenterService>(pub Arc<T>);
+                    struct RegisterNodeSvc<T: PlacementCenterService>(pub Arc<T>);
+                    impl<
+                        T: PlacementCenterService,
+                    > tonic::server::UnaryService<super::RegisterNodeRequest>
+                    for RegisterNodeSvc<T> {
+                        type Response = super::super::common::CommonReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RegisterNodeRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as PlacementCenterService>::register_node(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = RegisterNodeSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/placement.PlacementCenterService/UnRegisterNode" => {
+                    #[allow(non_camel_case_types)]
+                    struct UnRegisterNodeSvc<T: PlacementCenterService>(pub Arc<T>);
+                    impl<
+                        T: PlacementCenterService,
+                    > tonic::server::UnaryService<super::UnRegisterNodeRequest>
+                    for UnRegisterNodeSvc<T> {
+                        type Response = super::super::common::CommonReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UnRegisterNodeRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as PlacementCenterService>::un_register_node(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = UnRegisterNodeSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/placement.PlacementCenterService/Heartbeat" => {
+                    #[allow(non_camel_case_types)]
+                    struct HeartbeatSvc<T: PlacementCenterService>(pub Arc<T>);
+                    impl<
+                        T: PlacementCenterService,
+                    > tonic::server::UnaryService<super::HeartbeatRequest>
+                    for HeartbeatSvc<T> {
+                        type Response = super::super::common::CommonReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::HeartbeatRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as PlacementCenterService>::heartbeat(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = HeartbeatSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/placement.PlacementCenterService/ReportMonitor" => {
+                    #[allow(non_camel_case_types)]
+                    struct ReportMonitorSvc<T: PlacementCenterService>(pub Arc<T>);
+                    impl<
+                        T: PlacementCenterService,
+                    > tonic::server::UnaryService<super::ReportMonitorRequest>
+                    for ReportMonitorSvc<T> {
+                        type Response = super::super::common::CommonReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReportMonitorRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as PlacementCenterService>::report_monitor(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ReportMonitorSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/placement.PlacementCenterService/SendRaftMessage" => {
+                    #[allow(non_camel_case_types)]
+                    struct SendRaftMessageSvc<T: PlacementCenterService>(pub Arc<T>);
+                    impl<
+                        T: PlacementCenterService,
+                    > tonic::server::UnaryService<super::SendRaftMessageRequest>
+                    for SendRaftMessageSvc<T> {
+                        type Response = super::SendRaftMessageReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SendRaftMessageRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as PlacementCenterService>::send_raft_message(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SendRaftMessageSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/placement.PlacementCenterService/SendRaftConfChange" => {
+                    #[allow(non_camel_case_types)]
+                    struct SendRaftConfChangeSvc<T: PlacementCenterService>(pub Arc<T>);
+                    impl<
+                        T: PlacementCenterService,
+                    > tonic::server::UnaryService<super::SendRaftConfChangeRequest>
+                    for SendRaftConfChangeSvc<T> {
+                        type Response = super::SendRaftConfChangeReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SendRaftConfChangeRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as PlacementCenterService>::send_raft_conf_change(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SendRaftConfChangeSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/placement.PlacementCenterService/SetResourceConfig" => {
+                    #[allow(non_camel_case_types)]
+                    struct SetResourceConfigSvc<T: PlacementCenterService>(pub Arc<T>);
+                    impl<
+                        T: PlacementCenterService,
+                    > tonic::server::UnaryService<super::SetResourceConfigRequest>
+                    for SetResourceConfigSvc<T> {
+                        type Response = super::super::common::CommonReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SetResourceConfigRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as PlacementCenterService>::set_resource_config(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SetResourceConfigSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/placement.PlacementCenterService/GetResourceConfig" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetResourceConfigSvc<T: PlacementCenterService>(pub Arc<T>);
+                    impl<
+                        T: PlacementCenterService,
+                    > tonic::server::UnaryService<super::GetResourceConfigRequest>
+                    for GetResourceConfigSvc<T> {
+                        type Response = super::GetResourceConfigReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetResourceConfigRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as PlacementCenterService>::get_resource_config(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetResourceConfigSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/placement.PlacementCenterService/DeleteResourceConfig" => {
+                    #[allow(non_camel_case_types)]
+                    struct DeleteResourceConfigSvc<T: PlacementCenterService>(
+                        pub Arc<T>,
+                    );
+                    impl<
+                        T: PlacementCenterService,
+                    > tonic::server::UnaryService<super::DeleteResourceConfigRequest>
+                    for DeleteResourceConfigSvc<T> {
+                        type Response = super::super::common::CommonReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeleteResourceConfigRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as PlacementCenterService>::delete_resource_config(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = DeleteResourceConfigSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/placement.PlacementCenterService/SetIdempotentData" => {
+                    #[allow(non_camel_case_types)]
+                    struct SetIdempotentDataSvc<T: PlacementCenterService>(pub Arc<T>);
+                    impl<
+                        T: PlacementCenterService,
+                    > tonic::server::UnaryService<super::SetIdempotentDataRequest>
+                    for SetIdempotentDataSvc<T> {
+                        type Response = super::super::common::CommonReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SetIdempotentDataRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as PlacementCenterService>::set_idempotent_data(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SetIdempotentDataSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/placement.PlacementCenterService/ExistsIdempotentData" => {
+                    #[allow(non_camel_case_types)]
+                    struct ExistsIdempotentDataSvc<T: PlacementCenterService>(
+                        pub Arc<T>,
+                    );
+                    impl<
+                        T: PlacementCenterService,
+                    > tonic::server::UnaryService<super::ExistsIdempotentDataRequest>
+                    for ExistsIdempotentDataSvc<T> {
+                        type Response = super::ExistsIdempotentDataReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ExistsIdempotentDataRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as PlacementCenterService>::exists_idempotent_data(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ExistsIdempotentDataSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/placement.PlacementCenterService/DeleteIdempotentData" => {
+                    #[allow(non_camel_case_types)]
+                    struct DeleteIdempotentDataSvc<T: PlacementCenterService>(
+                        pub Arc<T>,
+                    );
+                    impl<
+                        T: PlacementCenterService,
+                    > tonic::server::UnaryService<super::DeleteIdempotentDataRequest>
+                    for DeleteIdempotentDataSvc<T> {
+                        type Response = super::super::common::CommonReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeleteIdempotentDataRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as PlacementCenterService>::delete_idempotent_data(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = DeleteIdempotentDataSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/placement.PlacementCenterService/SendRaftSnapshot" => {
+                    #[allow(non_camel_case_types)]
+                    struct SendRaftSnapshotSvc<T: PlacementCenterService>(pub Arc<T>);
                     impl<
                         T: PlacementCenterService,
-                    > tonic::server::UnaryService<super::RegisterNodeRequest>
-                    for RegisterNodeSvc<T> {
-                        type Response = super::super::common::CommonReply;
+                    > tonic::server::UnaryService<super::SendRaftSnapshotRequest>
+                    for SendRaftSnapshotSvc<T> {
+                        type Response = super::SendRaftSnapshotReply;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::RegisterNodeRequest>,
+                            request: tonic::Request<super::SendRaftSnapshotRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as PlacementCenterService>::register_node(
-                                        &inner,
-                                        request,
-                                    )
+                                <T as PlacementCenterService>::send_raft_snapshot(&inner, request)
                                     .await
                             };
                             Box::pin(fut)
@@ -779,7 +2202,7 @@ pub mod placement_center_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = RegisterNodeSvc(inner);
+                        let method = SendRaftSnapshotSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -795,13 +2218,60 @@ pub mod placement_center_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/placement.PlacementCenterService/UnRegisterNode" => {
+                "/placement.PlacementCenterService/ListRaftMember" => {
                     #[allow(non_camel_case_types)]
-                    struct UnRegisterNodeSvc<T: PlacementCenterService>(pub Arc<T>);
+                    struct ListRaftMemberSvc<T: PlacementCenterService>(pub Arc<T>);
                     impl<
                         T: PlacementCenterService,
-                    > tonic::server::UnaryService<super::UnRegisterNodeRequest>
-                    for UnRegisterNodeSvc<T> {
+                    > tonic::server::UnaryService<super::ListRaftMemberRequest>
+                    for ListRaftMemberSvc<T> {
+                        type Response = super::ListRaftMemberReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListRaftMemberRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as PlacementCenterService>::list_raft_member(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ListRaftMemberSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/placement.PlacementCenterService/AddRaftMember" => {
+                    #[allow(non_camel_case_types)]
+                    struct AddRaftMemberSvc<T: PlacementCenterService>(pub Arc<T>);
+                    impl<
+                        T: PlacementCenterService,
+                    > tonic::server::UnaryService<super::AddRaftMemberRequest>
+                    for AddRaftMemberSvc<T> {
                         type Response = super::super::common::CommonReply;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
@@ -809,14 +2279,11 @@ pub mod placement_center_service_server {
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::UnRegisterNodeRequest>,
+                            request: tonic::Request<super::AddRaftMemberRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as PlacementCenterService>::un_register_node(
-                                        &inner,
-                                        request,
-                                    )
+                                <T as PlacementCenterService>::add_raft_member(&inner, request)
                                     .await
                             };
                             Box::pin(fut)
@@ -829,7 +2296,7 @@ pub mod placement_center_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = UnRegisterNodeSvc(inner);
+                        let method = AddRaftMemberSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -845,13 +2312,13 @@ pub mod placement_center_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/placement.PlacementCenterService/Heartbeat" => {
+                "/placement.PlacementCenterService/PromoteRaftLearner" => {
                     #[allow(non_camel_case_types)]
-                    struct HeartbeatSvc<T: PlacementCenterService>(pub Arc<T>);
+                    struct PromoteRaftLearnerSvc<T: PlacementCenterService>(pub Arc<T>);
                     impl<
                         T: PlacementCenterService,
-                    > tonic::server::UnaryService<super::HeartbeatRequest>
-                    for HeartbeatSvc<T> {
+                    > tonic::server::UnaryService<super::PromoteRaftLearnerRequest>
+                    for PromoteRaftLearnerSvc<T> {
                         type Response = super::super::common::CommonReply;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
@@ -859,11 +2326,11 @@ pub mod placement_center_service_server {
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::HeartbeatRequest>,
+                            request: tonic::Request<super::PromoteRaftLearnerRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as PlacementCenterService>::heartbeat(&inner, request)
+                                <T as PlacementCenterService>::promote_raft_learner(&inner, request)
                                     .await
                             };
                             Box::pin(fut)
@@ -876,7 +2343,7 @@ pub mod placement_center_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = HeartbeatSvc(inner);
+                        let method = PromoteRaftLearnerSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -892,13 +2359,13 @@ pub mod placement_center_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/placement.PlacementCenterService/ReportMonitor" => {
+                "/placement.PlacementCenterService/RemoveRaftMember" => {
                     #[allow(non_camel_case_types)]
-                    struct ReportMonitorSvc<T: PlacementCenterService>(pub Arc<T>);
+                    struct RemoveRaftMemberSvc<T: PlacementCenterService>(pub Arc<T>);
                     impl<
                         T: PlacementCenterService,
-                    > tonic::server::UnaryService<super::ReportMonitorRequest>
-                    for ReportMonitorSvc<T> {
+                    > tonic::server::UnaryService<super::RemoveRaftMemberRequest>
+                    for RemoveRaftMemberSvc<T> {
                         type Response = super::super::common::CommonReply;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
@@ -906,14 +2373,11 @@ pub mod placement_center_service_server {
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::ReportMonitorRequest>,
+                            request: tonic::Request<super::RemoveRaftMemberRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as PlacementCenterService>::report_monitor(
-                                        &inner,
-                                        request,
-                                    )
+                                <T as PlacementCenterService>::remove_raft_member(&inner, request)
                                     .await
                             };
                             Box::pin(fut)
@@ -926,7 +2390,7 @@ pub mod placement_center_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = ReportMonitorSvc(inner);
+                        let method = RemoveRaftMemberSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -942,28 +2406,25 @@ pub mod placement_center_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/placement.PlacementCenterService/SendRaftMessage" => {
+                "/placement.PlacementCenterService/TransferRaftLeader" => {
                     #[allow(non_camel_case_types)]
-                    struct SendRaftMessageSvc<T: PlacementCenterService>(pub Arc<T>);
+                    struct TransferRaftLeaderSvc<T: PlacementCenterService>(pub Arc<T>);
                     impl<
                         T: PlacementCenterService,
-                    > tonic::server::UnaryService<super::SendRaftMessageRequest>
-                    for SendRaftMessageSvc<T> {
-                        type Response = super::SendRaftMessageReply;
+                    > tonic::server::UnaryService<super::TransferRaftLeaderRequest>
+                    for TransferRaftLeaderSvc<T> {
+                        type Response = super::super::common::CommonReply;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::SendRaftMessageRequest>,
+                            request: tonic::Request<super::TransferRaftLeaderRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as PlacementCenterService>::send_raft_message(
-                                        &inner,
-                                        request,
-                                    )
+                                <T as PlacementCenterService>::transfer_raft_leader(&inner, request)
                                     .await
                             };
                             Box::pin(fut)
@@ -976,7 +2437,7 @@ pub mod placement_center_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = SendRaftMessageSvc(inner);
+                        let method = TransferRaftLeaderSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -992,28 +2453,25 @@ pub mod placement_center_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/placement.PlacementCenterService/SendRaftConfChange" => {
+                "/placement.PlacementCenterService/AcquireLock" => {
                     #[allow(non_camel_case_types)]
-                    struct SendRaftConfChangeSvc<T: PlacementCenterService>(pub Arc<T>);
+                    struct AcquireLockSvc<T: PlacementCenterService>(pub Arc<T>);
                     impl<
                         T: PlacementCenterService,
-                    > tonic::server::UnaryService<super::SendRaftConfChangeRequest>
-                    for SendRaftConfChangeSvc<T> {
-                        type Response = super::SendRaftConfChangeReply;
+                    > tonic::server::UnaryService<super::AcquireLockRequest>
+                    for AcquireLockSvc<T> {
+                        type Response = super::AcquireLockReply;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::SendRaftConfChangeRequest>,
+                            request: tonic::Request<super::AcquireLockRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as PlacementCenterService>::send_raft_conf_change(
-                                        &inner,
-                                        request,
-                                    )
+                                <T as PlacementCenterService>::acquire_lock(&inner, request)
                                     .await
                             };
                             Box::pin(fut)
@@ -1026,7 +2484,7 @@ pub mod placement_center_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = SendRaftConfChangeSvc(inner);
+                        let method = AcquireLockSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1042,13 +2500,13 @@ pub mod placement_center_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/placement.PlacementCenterService/SetResourceConfig" => {
+                "/placement.PlacementCenterService/ReleaseLock" => {
                     #[allow(non_camel_case_types)]
-                    struct SetResourceConfigSvc<T: PlacementCenterService>(pub Arc<T>);
+                    struct ReleaseLockSvc<T: PlacementCenterService>(pub Arc<T>);
                     impl<
                         T: PlacementCenterService,
-                    > tonic::server::UnaryService<super::SetResourceConfigRequest>
-                    for SetResourceConfigSvc<T> {
+                    > tonic::server::UnaryService<super::ReleaseLockRequest>
+                    for ReleaseLockSvc<T> {
                         type Response = super::super::common::CommonReply;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
@@ -1056,14 +2514,11 @@ pub mod placement_center_service_server {
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::SetResourceConfigRequest>,
+                            request: tonic::Request<super::ReleaseLockRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as PlacementCenterService>::set_resource_config(
-                                        &inner,
-                                        request,
-                                    )
+                                <T as PlacementCenterService>::release_lock(&inner, request)
                                     .await
                             };
                             Box::pin(fut)
@@ -1076,7 +2531,7 @@ pub mod placement_center_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = SetResourceConfigSvc(inner);
+                        let method = ReleaseLockSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1092,28 +2547,25 @@ pub mod placement_center_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/placement.PlacementCenterService/GetResourceConfig" => {
+                "/placement.PlacementCenterService/GetLock" => {
                     #[allow(non_camel_case_types)]
-                    struct GetResourceConfigSvc<T: PlacementCenterService>(pub Arc<T>);
+                    struct GetLockSvc<T: PlacementCenterService>(pub Arc<T>);
                     impl<
                         T: PlacementCenterService,
-                    > tonic::server::UnaryService<super::GetResourceConfigRequest>
-                    for GetResourceConfigSvc<T> {
-                        type Response = super::GetResourceConfigReply;
+                    > tonic::server::UnaryService<super::GetLockRequest>
+                    for GetLockSvc<T> {
+                        type Response = super::GetLockReply;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::GetResourceConfigRequest>,
+                            request: tonic::Request<super::GetLockRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as PlacementCenterService>::get_resource_config(
-                                        &inner,
-                                        request,
-                                    )
+                                <T as PlacementCenterService>::get_lock(&inner, request)
                                     .await
                             };
                             Box::pin(fut)
@@ -1126,7 +2578,7 @@ pub mod placement_center_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = GetResourceConfigSvc(inner);
+                        let method = GetLockSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1142,30 +2594,25 @@ pub mod placement_center_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/placement.PlacementCenterService/DeleteResourceConfig" => {
+                "/placement.PlacementCenterService/Campaign" => {
                     #[allow(non_camel_case_types)]
-                    struct DeleteResourceConfigSvc<T: PlacementCenterService>(
-                        pub Arc<T>,
-                    );
+                    struct CampaignSvc<T: PlacementCenterService>(pub Arc<T>);
                     impl<
                         T: PlacementCenterService,
-                    > tonic::server::UnaryService<super::DeleteResourceConfigRequest>
-                    for DeleteResourceConfigSvc<T> {
-                        type Response = super::super::common::CommonReply;
+                    > tonic::server::UnaryService<super::CampaignRequest>
+                    for CampaignSvc<T> {
+                        type Response = super::CampaignReply;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::DeleteResourceConfigRequest>,
+                            request: tonic::Request<super::CampaignRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as PlacementCenterService>::delete_resource_config(
-                                        &inner,
-                                        request,
-                                    )
+                                <T as PlacementCenterService>::campaign(&inner, request)
                                     .await
                             };
                             Box::pin(fut)
@@ -1178,7 +2625,7 @@ pub mod placement_center_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = DeleteResourceConfigSvc(inner);
+                        let method = CampaignSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1194,13 +2641,13 @@ pub mod placement_center_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/placement.PlacementCenterService/SetIdempotentData" => {
+                "/placement.PlacementCenterService/Resign" => {
                     #[allow(non_camel_case_types)]
-                    struct SetIdempotentDataSvc<T: PlacementCenterService>(pub Arc<T>);
+                    struct ResignSvc<T: PlacementCenterService>(pub Arc<T>);
                     impl<
                         T: PlacementCenterService,
-                    > tonic::server::UnaryService<super::SetIdempotentDataRequest>
-                    for SetIdempotentDataSvc<T> {
+                    > tonic::server::UnaryService<super::ResignRequest>
+                    for ResignSvc<T> {
                         type Response = super::super::common::CommonReply;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
@@ -1208,14 +2655,11 @@ pub mod placement_center_service_server {
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::SetIdempotentDataRequest>,
+                            request: tonic::Request<super::ResignRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as PlacementCenterService>::set_idempotent_data(
-                                        &inner,
-                                        request,
-                                    )
+                                <T as PlacementCenterService>::resign(&inner, request)
                                     .await
                             };
                             Box::pin(fut)
@@ -1228,7 +2672,7 @@ pub mod placement_center_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = SetIdempotentDataSvc(inner);
+                        let method = ResignSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1244,30 +2688,25 @@ pub mod placement_center_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/placement.PlacementCenterService/ExistsIdempotentData" => {
+                "/placement.PlacementCenterService/GetElectionLeader" => {
                     #[allow(non_camel_case_types)]
-                    struct ExistsIdempotentDataSvc<T: PlacementCenterService>(
-                        pub Arc<T>,
-                    );
+                    struct GetElectionLeaderSvc<T: PlacementCenterService>(pub Arc<T>);
                     impl<
                         T: PlacementCenterService,
-                    > tonic::server::UnaryService<super::ExistsIdempotentDataRequest>
-                    for ExistsIdempotentDataSvc<T> {
-                        type Response = super::ExistsIdempotentDataReply;
+                    > tonic::server::UnaryService<super::GetElectionLeaderRequest>
+                    for GetElectionLeaderSvc<T> {
+                        type Response = super::GetElectionLeaderReply;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::ExistsIdempotentDataRequest>,
+                            request: tonic::Request<super::GetElectionLeaderRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as PlacementCenterService>::exists_idempotent_data(
-                                        &inner,
-                                        request,
-                                    )
+                                <T as PlacementCenterService>::get_election_leader(&inner, request)
                                     .await
                             };
                             Box::pin(fut)
@@ -1280,7 +2719,7 @@ pub mod placement_center_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = ExistsIdempotentDataSvc(inner);
+                        let method = GetElectionLeaderSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1296,15 +2735,107 @@ pub mod placement_center_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/placement.PlacementCenterService/DeleteIdempotentData" => {
+                "/placement.PlacementCenterService/CreateBackup" => {
                     #[allow(non_camel_case_types)]
-                    struct DeleteIdempotentDataSvc<T: PlacementCenterService>(
-                        pub Arc<T>,
-                    );
+                    struct CreateBackupSvc<T: PlacementCenterService>(pub Arc<T>);
                     impl<
                         T: PlacementCenterService,
-                    > tonic::server::UnaryService<super::DeleteIdempotentDataRequest>
-                    for DeleteIdempotentDataSvc<T> {
+                    > tonic::server::UnaryService<super::CreateBackupRequest>
+                    for CreateBackupSvc<T> {
+                        type Response = super::CreateBackupReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateBackupRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as PlacementCenterService>::create_backup(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = CreateBackupSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/placement.PlacementCenterService/ReadBackup" => {
+                    #[allow(non_camel_case_types)]
+                    struct ReadBackupSvc<T: PlacementCenterService>(pub Arc<T>);
+                    impl<
+                        T: PlacementCenterService,
+                    > tonic::server::UnaryService<super::ReadBackupRequest>
+                    for ReadBackupSvc<T> {
+                        type Response = super::ReadBackupReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReadBackupRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as PlacementCenterService>::read_backup(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ReadBackupSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/placement.PlacementCenterService/ImportMetadata" => {
+                    #[allow(non_camel_case_types)]
+                    struct ImportMetadataSvc<T: PlacementCenterService>(pub Arc<T>);
+                    impl<
+                        T: PlacementCenterService,
+                    > tonic::server::UnaryService<super::ImportMetadataRequest>
+                    for ImportMetadataSvc<T> {
                         type Response = super::super::common::CommonReply;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
@@ -1312,14 +2843,11 @@ pub mod placement_center_service_server {
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::DeleteIdempotentDataRequest>,
+                            request: tonic::Request<super::ImportMetadataRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as PlacementCenterService>::delete_idempotent_data(
-                                        &inner,
-                                        request,
-                                    )
+                                <T as PlacementCenterService>::import_metadata(&inner, request)
                                     .await
                             };
                             Box::pin(fut)
@@ -1332,7 +2860,7 @@ pub mod placement_center_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = DeleteIdempotentDataSvc(inner);
+                        let method = ImportMetadataSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -0,0 +1,26 @@
+/// Storage format of storage_adapter::record::Record, the encoded bytes are prefixed with a codec version.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Record {
+    #[prost(uint64, tag = "1")]
+    pub offset: u64,
+    #[prost(message, repeated, tag = "2")]
+    pub header: ::prost::alloc::vec::Vec<Header>,
+    #[prost(string, optional, tag = "3")]
+    pub key: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(bytes = "vec", tag = "4")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
+    #[prost(uint64, optional, tag = "5")]
+    pub create_time: ::core::option::Option<u64>,
+    /// Set when the header is Some, so that an empty header list can be told apart from None.
+    #[prost(bool, tag = "6")]
+    pub has_header: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Header {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub value: ::prost::alloc::string::String,
+}
@@ -22,6 +22,7 @@ mod tests {
             .compile(
                 &[
                     "src/broker_server/proto/mqtt.proto",
+                    "src/broker_server/proto/message.proto",
                     ],
                 &["src/broker_server/proto"], // specify the root location to search proto dependencies
             )
@@ -50,8 +51,13 @@ mod tests {
                     "src/journal_server/proto/protocol/header.proto",
                     "src/journal_server/proto/protocol/fetch.proto",
                     "src/journal_server/proto/protocol/produce.proto",
+                    "src/journal_server/proto/protocol/metadata.proto",
+                    "src/journal_server/proto/protocol/end_txn.proto",
                 ],
-                &["src/journal_server/proto/protocol/"], // specify the root location to search proto dependencies
+                &[
+                    "src/journal_server/proto/protocol/",
+                    "src/journal_server/proto/record/",
+                ], // specify the root location to search proto dependencies
             )
             .unwrap();
 
@@ -65,5 +71,16 @@ mod tests {
                 &["src/journal_server/proto/record"], // specify the root location to search proto dependencies
             )
             .unwrap();
+
+        tonic_build::configure()
+            .build_server(false)
+            .out_dir("src/storage_adapter/generate") // you can change the generated code's location
+            .compile(
+                &[
+                    "src/storage_adapter/proto/record.proto",
+                ],
+                &["src/storage_adapter/proto"], // specify the root location to search proto dependencies
+            )
+            .unwrap();
     }
 }
@@ -0,0 +1,231 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    record::{Header, Record},
+    storage::ShardConfig,
+};
+use common_base::error::common::CommonError;
+use serde::{Deserialize, Serialize};
+
+// Compressed records carry this header, its value is the name of the compression type.
+pub const COMPRESSION_HEADER: &str = "__compression";
+
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompressionType {
+    #[default]
+    None,
+    Lz4,
+    Zstd,
+    Snappy,
+}
+
+impl CompressionType {
+    pub fn from_name(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "lz4" => CompressionType::Lz4,
+            "zstd" => CompressionType::Zstd,
+            "snappy" => CompressionType::Snappy,
+            _ => CompressionType::None,
+        }
+    }
+
+    // The compression of a stored record, a name that is not known cannot be read.
+    pub fn parse(name: &str) -> Result<Self, CommonError> {
+        match name.to_lowercase().as_str() {
+            "none" => return Ok(CompressionType::None),
+            "lz4" => return Ok(CompressionType::Lz4),
+            "zstd" => return Ok(CompressionType::Zstd),
+            "snappy" => return Ok(CompressionType::Snappy),
+            _ => {
+                return Err(CommonError::CommmonError(format!(
+                    "Unsupported record compression {}",
+                    name
+                )))
+            }
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            CompressionType::None => "none".to_string(),
+            CompressionType::Lz4 => "lz4".to_string(),
+            CompressionType::Zstd => "zstd".to_string(),
+            CompressionType::Snappy => "snappy".to_string(),
+        }
+    }
+}
+
+pub fn compress(compression: &CompressionType, data: &[u8]) -> Result<Vec<u8>, CommonError> {
+    match compression {
+        CompressionType::None => return Ok(data.to_vec()),
+        CompressionType::Lz4 => return Ok(lz4_flex::compress_prepend_size(data)),
+        CompressionType::Zstd => match zstd::encode_all(data, 0) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        CompressionType::Snappy => match snap::raw::Encoder::new().compress_vec(data) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+    }
+}
+
+pub fn decompress(compression: &CompressionType, data: &[u8]) -> Result<Vec<u8>, CommonError> {
+    match compression {
+        CompressionType::None => return Ok(data.to_vec()),
+        CompressionType::Lz4 => match lz4_flex::decompress_size_prepended(data) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        CompressionType::Zstd => match zstd::decode_all(data) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+        CompressionType::Snappy => match snap::raw::Decoder::new().decompress_vec(data) {
+            Ok(da) => return Ok(da),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        },
+    }
+}
+
+// Compress the data of the record according to the shard config. Records smaller than the
+// minimum size, or that do not get smaller when compressed, are stored as they are.
+// The compression header is reserved, records that already carry it are rejected.
+pub fn compress_record(record: Record, config: &ShardConfig) -> Result<Record, CommonError> {
+    if let Some(header) = &record.header {
+        if header.iter().any(|h| h.name == COMPRESSION_HEADER) {
+            return Err(CommonError::CommmonError(format!(
+                "Header {} is reserved",
+                COMPRESSION_HEADER
+            )));
+        }
+    }
+
+    if config.compression == CompressionType::None
+        || (record.data.len() as u64) < config.compression_min_size
+    {
+        return Ok(record);
+    }
+
+    let data = compress(&config.compression, &record.data)?;
+    if data.len() >= record.data.len() {
+        return Ok(record);
+    }
+
+    let mut record = record;
+    let mut header = record.header.unwrap_or_default();
+    header.push(Header {
+        name: COMPRESSION_HEADER.to_string(),
+        value: config.compression.name(),
+    });
+    record.header = Some(header);
+    record.data = data;
+    return Ok(record);
+}
+
+// The headers of records that were not compressed are returned as they were written.
+pub fn decompress_record(record: Record) -> Result<Record, CommonError> {
+    let mut record = record;
+    let compression = match &record.header {
+        Some(header) => match header.iter().find(|h| h.name == COMPRESSION_HEADER) {
+            Some(h) => CompressionType::parse(&h.value)?,
+            None => return Ok(record),
+        },
+        None => return Ok(record),
+    };
+
+    let new_header: Vec<Header> = record
+        .header
+        .take()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|h| h.name != COMPRESSION_HEADER)
+        .collect();
+    record.data = decompress(&compression, &record.data)?;
+    record.header = if new_header.is_empty() {
+        None
+    } else {
+        Some(new_header)
+    };
+    return Ok(record);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compress_record, decompress_record, CompressionType, COMPRESSION_HEADER};
+    use crate::{
+        record::{Header, Record},
+        storage::ShardConfig,
+    };
+
+    #[test]
+    fn record_compression() {
+        let data = "robustmq".repeat(100).as_bytes().to_vec();
+        for compression in [
+            CompressionType::Lz4,
+            CompressionType::Zstd,
+            CompressionType::Snappy,
+        ] {
+            let config = ShardConfig {
+                compression: compression.clone(),
+                compression_min_size: 64,
+                ..Default::default()
+            };
+            let record = compress_record(Record::build_b(data.clone()), &config).unwrap();
+            assert!(record.data.len() < data.len());
+            assert_eq!(
+                record.header.clone().unwrap()[0].name,
+                COMPRESSION_HEADER.to_string()
+            );
+
+            let record = decompress_record(record).unwrap();
+            assert_eq!(record.data, data);
+            assert!(record.header.is_none());
+        }
+    }
+
+    #[test]
+    fn record_compression_min_size() {
+        let config = ShardConfig {
+            compression: CompressionType::Zstd,
+            compression_min_size: 1024,
+            ..Default::default()
+        };
+        let data = "robustmq".as_bytes().to_vec();
+        let record = compress_record(Record::build_b(data.clone()), &config).unwrap();
+        assert_eq!(record.data, data);
+        assert!(record.header.is_none());
+    }
+
+    #[test]
+    fn record_compression_header() {
+        let config = ShardConfig::default();
+        let mut record = Record::build_b("robustmq".as_bytes().to_vec());
+        record.header = Some(vec![Header {
+            name: COMPRESSION_HEADER.to_string(),
+            value: "lz4".to_string(),
+        }]);
+        assert!(compress_record(record.clone(), &config).is_err());
+
+        // An unknown compression cannot be read
+        record.header.as_mut().unwrap()[0].value = "gzip".to_string();
+        assert!(decompress_record(record).is_err());
+
+        // Records that were not compressed keep their headers
+        let mut record = Record::build_b("robustmq".as_bytes().to_vec());
+        record.header = Some(Vec::new());
+        let record = decompress_record(record).unwrap();
+        assert!(record.header.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,466 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// The behaviour every StorageAdapter must share, so that the broker does not depend on
+// which storage is configured. Each adapter runs the suite from its own tests, the checks
+// panic on the first divergence.
+use crate::{
+    compression::CompressionType,
+    record::Record,
+    storage::{ShardConfig, StorageAdapter},
+};
+use common_base::tools::now_second;
+use std::{collections::HashSet, sync::Arc};
+
+const CONCURRENT_WRITER_NUM: usize = 8;
+const CONCURRENT_WRITER_RECORD_NUM: usize = 20;
+
+// Shard names are used as table names by some adapters, so the prefix should only
+// contain letters, digits and underscores.
+pub async fn run_conformance_suite<S>(storage_adapter: Arc<S>, prefix: &str)
+where
+    S: StorageAdapter + Sync + Send + 'static,
+{
+    check_offset_monotonic(storage_adapter.clone(), format!("{}_offset", prefix)).await;
+    check_group_isolation(storage_adapter.clone(), format!("{}_group", prefix)).await;
+    check_commit_read(storage_adapter.clone(), format!("{}_commit", prefix)).await;
+    check_read_by_timestamp(storage_adapter.clone(), format!("{}_timestamp", prefix)).await;
+    check_read_by_key(storage_adapter.clone(), format!("{}_key", prefix)).await;
+    check_delete_shard(storage_adapter.clone(), format!("{}_delete", prefix)).await;
+    check_group_list(storage_adapter.clone(), format!("{}_glist", prefix)).await;
+    check_compression(storage_adapter.clone(), format!("{}_compression", prefix)).await;
+    check_concurrent_write(storage_adapter.clone(), format!("{}_concurrent", prefix)).await;
+}
+
+// Offsets are assigned one per record and strictly increase across writes.
+pub async fn check_offset_monotonic<S>(storage_adapter: Arc<S>, shard_name: String)
+where
+    S: StorageAdapter + Sync + Send + 'static,
+{
+    init_shard(&storage_adapter, &shard_name).await;
+
+    let mut offsets = Vec::new();
+    for batch in 0..3 {
+        let data = build_records(&format!("b{}", batch), 3);
+        let res = storage_adapter
+            .stream_write(shard_name.clone(), data)
+            .await
+            .unwrap();
+        assert_eq!(res.len(), 3);
+        offsets.extend(res);
+    }
+    for pair in offsets.windows(2) {
+        assert!(
+            pair[0] < pair[1],
+            "offsets {:?} are not increasing",
+            offsets
+        );
+    }
+
+    for (i, offset) in offsets.iter().enumerate() {
+        let record = storage_adapter
+            .stream_read_by_offset(shard_name.clone(), *offset)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(record.offset, *offset as u128);
+        assert_eq!(record.data, format!("b{}-{}", i / 3, i % 3).into_bytes());
+    }
+
+    storage_adapter.delete_shard(shard_name).await.unwrap();
+}
+
+// The committed offset of a group does not move the read position of another group.
+pub async fn check_group_isolation<S>(storage_adapter: Arc<S>, shard_name: String)
+where
+    S: StorageAdapter + Sync + Send + 'static,
+{
+    init_shard(&storage_adapter, &shard_name).await;
+    let offsets = storage_adapter
+        .stream_write(shard_name.clone(), build_records("m", 4))
+        .await
+        .unwrap();
+
+    let group_a = "group_a".to_string();
+    let group_b = "group_b".to_string();
+    let res = read(&storage_adapter, &shard_name, &group_a, Some(2), None).await;
+    assert_eq!(record_offsets(&res), offsets[0..2].to_vec());
+    storage_adapter
+        .stream_commit_offset(shard_name.clone(), group_a.clone(), res[1].offset)
+        .await
+        .unwrap();
+
+    let res = read(&storage_adapter, &shard_name, &group_b, None, None).await;
+    assert_eq!(record_offsets(&res), offsets);
+
+    let res = read(&storage_adapter, &shard_name, &group_a, None, None).await;
+    assert_eq!(record_offsets(&res), offsets[2..4].to_vec());
+
+    storage_adapter.delete_shard(shard_name).await.unwrap();
+}
+
+// Reading does not move a group, reading resumes after the committed offset, and
+// record_size limits the data read while always returning at least one record.
+pub async fn check_commit_read<S>(storage_adapter: Arc<S>, shard_name: String)
+where
+    S: StorageAdapter + Sync + Send + 'static,
+{
+    init_shard(&storage_adapter, &shard_name).await;
+    let offsets = storage_adapter
+        .stream_write(shard_name.clone(), build_records("m", 5))
+        .await
+        .unwrap();
+    let group_id = "group".to_string();
+
+    let first = read(&storage_adapter, &shard_name, &group_id, Some(2), None).await;
+    let second = read(&storage_adapter, &shard_name, &group_id, Some(2), None).await;
+    assert_eq!(record_offsets(&first), offsets[0..2].to_vec());
+    assert_eq!(record_offsets(&first), record_offsets(&second));
+
+    storage_adapter
+        .stream_commit_offset(shard_name.clone(), group_id.clone(), first[1].offset)
+        .await
+        .unwrap();
+    let res = read(&storage_adapter, &shard_name, &group_id, Some(1), Some(1)).await;
+    assert_eq!(record_offsets(&res), offsets[2..3].to_vec());
+
+    // Each record holds 3 bytes, so a second record would exceed the record size.
+    let res = read(&storage_adapter, &shard_name, &group_id, None, Some(5)).await;
+    assert_eq!(record_offsets(&res), offsets[2..3].to_vec());
+
+    let res = read(&storage_adapter, &shard_name, &group_id, None, None).await;
+    assert_eq!(record_offsets(&res), offsets[2..5].to_vec());
+
+    storage_adapter
+        .stream_commit_offset(shard_name.clone(), group_id.clone(), res[2].offset)
+        .await
+        .unwrap();
+    let res = read(&storage_adapter, &shard_name, &group_id, None, None).await;
+    assert!(res.is_empty());
+
+    storage_adapter.delete_shard(shard_name).await.unwrap();
+}
+
+// Both ends of the time range are inclusive and record_num limits the result.
+pub async fn check_read_by_timestamp<S>(storage_adapter: Arc<S>, shard_name: String)
+where
+    S: StorageAdapter + Sync + Send + 'static,
+{
+    init_shard(&storage_adapter, &shard_name).await;
+    let base = (now_second() - 100) as u128;
+    let mut data = build_records("m", 3);
+    for (i, record) in data.iter_mut().enumerate() {
+        record.create_time = Some(base + i as u128 * 10);
+    }
+    let offsets = storage_adapter
+        .stream_write(shard_name.clone(), data)
+        .await
+        .unwrap();
+
+    let cases = vec![
+        (base, base + 20, None, offsets.clone()),
+        (base + 10, base + 20, None, offsets[1..3].to_vec()),
+        (base, base, None, offsets[0..1].to_vec()),
+        (base + 1, base + 9, None, Vec::new()),
+        (base + 21, base + 100, None, Vec::new()),
+        (base, base + 20, Some(2), offsets[0..2].to_vec()),
+    ];
+    for (start, end, record_num, expect) in cases {
+        let res = storage_adapter
+            .stream_read_by_timestamp(shard_name.clone(), start, end, record_num, None)
+            .await
+            .unwrap()
+            .unwrap_or_default();
+        assert_eq!(
+            record_offsets(&res),
+            expect,
+            "read by timestamp [{}, {}]",
+            start,
+            end
+        );
+    }
+
+    storage_adapter.delete_shard(shard_name).await.unwrap();
+}
+
+// A key resolves to the latest record written with it.
+pub async fn check_read_by_key<S>(storage_adapter: Arc<S>, shard_name: String)
+where
+    S: StorageAdapter + Sync + Send + 'static,
+{
+    init_shard(&storage_adapter, &shard_name).await;
+    let data = vec![
+        Record::build_c("k1".to_string(), "v1".as_bytes().to_vec()),
+        Record::build_c("k2".to_string(), "v2".as_bytes().to_vec()),
+        Record::build_c("k1".to_string(), "v3".as_bytes().to_vec()),
+    ];
+    let offsets = storage_adapter
+        .stream_write(shard_name.clone(), data)
+        .await
+        .unwrap();
+
+    let record = storage_adapter
+        .stream_read_by_key(shard_name.clone(), "k1".to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(record.offset, offsets[2] as u128);
+    assert_eq!(record.key, Some("k1".to_string()));
+    assert_eq!(record.data, "v3".as_bytes().to_vec());
+
+    let record = storage_adapter
+        .stream_read_by_key(shard_name.clone(), "k2".to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(record.offset, offsets[1] as u128);
+
+    let record = storage_adapter
+        .stream_read_by_key(shard_name.clone(), "k3".to_string())
+        .await
+        .unwrap();
+    assert!(record.is_none());
+
+    storage_adapter.delete_shard(shard_name).await.unwrap();
+}
+
+// Deleting a shard removes its records and the offsets committed by its groups, so a
+// shard created again with the same name starts empty.
+pub async fn check_delete_shard<S>(storage_adapter: Arc<S>, shard_name: String)
+where
+    S: StorageAdapter + Sync + Send + 'static,
+{
+    init_shard(&storage_adapter, &shard_name).await;
+    let offsets = storage_adapter
+        .stream_write(shard_name.clone(), build_records("old", 3))
+        .await
+        .unwrap();
+    let group_id = "group".to_string();
+    storage_adapter
+        .stream_commit_offset(shard_name.clone(), group_id.clone(), offsets[2] as u128)
+        .await
+        .unwrap();
+
+    storage_adapter
+        .delete_shard(shard_name.clone())
+        .await
+        .unwrap();
+    let res = storage_adapter
+        .stream_read_by_offset(shard_name.clone(), offsets[0])
+        .await;
+    assert!(!matches!(res, Ok(Some(_))));
+
+    storage_adapter
+        .create_shard(shard_name.clone(), ShardConfig::default())
+        .await
+        .unwrap();
+    storage_adapter
+        .stream_write(shard_name.clone(), build_records("new", 2))
+        .await
+        .unwrap();
+    let res = read(&storage_adapter, &shard_name, &group_id, None, None).await;
+    assert_eq!(res.len(), 2);
+    for record in res {
+        assert!(record.data.starts_with("new".as_bytes()));
+    }
+
+    storage_adapter.delete_shard(shard_name).await.unwrap();
+}
+
+// Groups are listed per shard, even when a group and shard name joined with an
+// underscore equal the names of a group on another shard.
+pub async fn check_group_list<S>(storage_adapter: Arc<S>, shard_name: String)
+where
+    S: StorageAdapter + Sync + Send + 'static,
+{
+    let other_shard = format!("t_{}", shard_name);
+    init_shard(&storage_adapter, &shard_name).await;
+    init_shard(&storage_adapter, &other_shard).await;
+    for shard in [&shard_name, &other_shard] {
+        storage_adapter
+            .stream_write(shard.clone(), build_records("g", 3))
+            .await
+            .unwrap();
+    }
+
+    storage_adapter
+        .stream_commit_offset(shard_name.clone(), "g_t".to_string(), 0)
+        .await
+        .unwrap();
+    storage_adapter
+        .stream_commit_offset(other_shard.clone(), "g".to_string(), 1)
+        .await
+        .unwrap();
+
+    let groups = storage_adapter
+        .stream_list_group(shard_name.clone())
+        .await
+        .unwrap();
+    assert_eq!(groups, vec!["g_t".to_string()]);
+    let offset = storage_adapter
+        .stream_group_offset(other_shard.clone(), "g".to_string())
+        .await
+        .unwrap();
+    assert_eq!(offset, Some(1));
+
+    storage_adapter
+        .delete_shard(shard_name.clone())
+        .await
+        .unwrap();
+    let groups = storage_adapter
+        .stream_list_group(other_shard.clone())
+        .await
+        .unwrap();
+    assert_eq!(groups, vec!["g".to_string()]);
+
+    storage_adapter.delete_shard(other_shard).await.unwrap();
+}
+
+// Records of a shard with compression are read back as they were written.
+pub async fn check_compression<S>(storage_adapter: Arc<S>, shard_name: String)
+where
+    S: StorageAdapter + Sync + Send + 'static,
+{
+    storage_adapter
+        .delete_shard(shard_name.clone())
+        .await
+        .unwrap();
+    let shard_config = ShardConfig {
+        compression: CompressionType::Zstd,
+        ..Default::default()
+    };
+    storage_adapter
+        .create_shard(shard_name.clone(), shard_config)
+        .await
+        .unwrap();
+
+    let data = "robustmq".repeat(64).into_bytes();
+    let offsets = storage_adapter
+        .stream_write(
+            shard_name.clone(),
+            vec![Record::build_c("k1".to_string(), data.clone())],
+        )
+        .await
+        .unwrap();
+    let record = storage_adapter
+        .stream_read_by_offset(shard_name.clone(), offsets[0])
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(record.data, data);
+    let record = storage_adapter
+        .stream_read_by_key(shard_name.clone(), "k1".to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(record.data, data);
+    let group_id = "group".to_string();
+    let res = read(&storage_adapter, &shard_name, &group_id, None, None).await;
+    assert_eq!(res.len(), 1);
+    assert_eq!(res[0].data, data);
+
+    storage_adapter.delete_shard(shard_name).await.unwrap();
+}
+
+// Concurrent writers to the same shard never receive the same offset or lose records.
+pub async fn check_concurrent_write<S>(storage_adapter: Arc<S>, shard_name: String)
+where
+    S: StorageAdapter + Sync + Send + 'static,
+{
+    init_shard(&storage_adapter, &shard_name).await;
+
+    let mut handles = Vec::new();
+    for i in 0..CONCURRENT_WRITER_NUM {
+        let adapter = storage_adapter.clone();
+        let shard = shard_name.clone();
+        handles.push(tokio::spawn(async move {
+            let mut offsets = Vec::new();
+            for j in 0..CONCURRENT_WRITER_RECORD_NUM {
+                let data = build_records(&format!("w{}-{}", i, j), 1);
+                offsets.extend(adapter.stream_write(shard.clone(), data).await.unwrap());
+            }
+            return offsets;
+        }));
+    }
+
+    let mut offsets = HashSet::new();
+    for handle in handles {
+        for offset in handle.await.unwrap() {
+            assert!(offsets.insert(offset), "offset {} is duplicated", offset);
+        }
+    }
+    assert_eq!(
+        offsets.len(),
+        CONCURRENT_WRITER_NUM * CONCURRENT_WRITER_RECORD_NUM
+    );
+    for offset in offsets {
+        let record = storage_adapter
+            .stream_read_by_offset(shard_name.clone(), offset)
+            .await
+            .unwrap();
+        assert!(record.is_some(), "record of offset {} is lost", offset);
+    }
+
+    storage_adapter.delete_shard(shard_name).await.unwrap();
+}
+
+async fn init_shard<S>(storage_adapter: &Arc<S>, shard_name: &String)
+where
+    S: StorageAdapter + Sync + Send + 'static,
+{
+    // Persistent adapters may still hold the shard of an interrupted run.
+    storage_adapter
+        .delete_shard(shard_name.clone())
+        .await
+        .unwrap();
+    storage_adapter
+        .create_shard(shard_name.clone(), ShardConfig::default())
+        .await
+        .unwrap();
+}
+
+async fn read<S>(
+    storage_adapter: &Arc<S>,
+    shard_name: &String,
+    group_id: &String,
+    record_num: Option<u128>,
+    record_size: Option<usize>,
+) -> Vec<Record>
+where
+    S: StorageAdapter + Sync + Send + 'static,
+{
+    return storage_adapter
+        .stream_read(
+            shard_name.clone(),
+            group_id.clone(),
+            record_num,
+            record_size,
+        )
+        .await
+        .unwrap()
+        .unwrap_or_default();
+}
+
+fn build_records(prefix: &str, num: usize) -> Vec<Record> {
+    let mut records = Vec::new();
+    for i in 0..num {
+        records.push(Record::build_b(
+            format!("{}-{}", prefix, i).as_bytes().to_vec(),
+        ));
+    }
+    return records;
+}
+
+fn record_offsets(records: &Vec<Record>) -> Vec<usize> {
+    return records.iter().map(|r| r.offset as usize).collect();
+}
@@ -12,11 +12,15 @@
 // limitations under the License.
 
 
+pub mod compression;
+pub mod conformance;
 pub mod journal;
 pub mod memory;
 pub mod mysql;
 pub mod placement;
 pub mod record;
+pub mod retention;
+pub mod sqlite;
 pub mod storage;
 
 #[derive(Debug)]
@@ -25,6 +29,7 @@ pub enum StorageType {
     Memory,
     Mysql,
     Placement,
+    Sqlite,
 }
 
 pub fn storage_is_journal(storage_type: &String) -> bool {
@@ -47,14 +52,20 @@ pub fn storage_is_mysql(storage_type: &String) -> bool {
     return st == storage_type.clone();
 }
 
+pub fn storage_is_sqlite(storage_type: &String) -> bool {
+    let st = format!("{:?}", StorageType::Sqlite).to_lowercase();
+    return st == storage_type.clone();
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{storage_is_journal, storage_is_memory, storage_is_mysql};
+    use crate::{storage_is_journal, storage_is_memory, storage_is_mysql, storage_is_sqlite};
 
     #[tokio::test]
     async fn storage_type_test() {
         assert!(storage_is_journal(&"journal".to_string()));
         assert!(storage_is_memory(&"memory".to_string()));
         assert!(storage_is_mysql(&"mysql".to_string()));
+        assert!(storage_is_sqlite(&"sqlite".to_string()));
     }
 }
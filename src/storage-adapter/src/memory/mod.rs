@@ -12,18 +12,23 @@
 // limitations under the License.
 
 use crate::{
+    compression::{compress_record, decompress_record},
     record::Record,
-    storage::{ShardConfig, StorageAdapter},
+    storage::{ShardCleanupPolicy, ShardConfig, ShardOffset, StorageAdapter},
 };
 use axum::async_trait;
-use common_base::error::common::CommonError;
+use common_base::{error::common::CommonError, tools::now_second};
 use dashmap::DashMap;
+use std::collections::HashMap;
 
 #[derive(Clone)]
 pub struct MemoryStorageAdapter {
     pub memory_data: DashMap<String, Record>,
     pub shard_data: DashMap<String, Vec<Record>>,
-    pub group_data: DashMap<String, u128>,
+    pub shard_config: DashMap<String, ShardConfig>,
+    pub shard_next_offset: DashMap<String, u128>,
+    // Committed offset of each (group_id, shard_name).
+    pub group_data: DashMap<(String, String), u128>,
     pub key_index: DashMap<String, DashMap<String, u128>>,
 }
 
@@ -32,13 +37,15 @@ impl MemoryStorageAdapter {
         return MemoryStorageAdapter {
             memory_data: DashMap::with_capacity(256),
             shard_data: DashMap::with_capacity(256),
+            shard_config: DashMap::with_capacity(256),
+            shard_next_offset: DashMap::with_capacity(256),
             group_data: DashMap::with_capacity(256),
             key_index: DashMap::with_capacity(256),
         };
     }
 
-    pub fn offset_key(&self, group_id: String, shard_name: String) -> String {
-        return format!("{}_{}", group_id, shard_name);
+    pub fn offset_key(&self, group_id: String, shard_name: String) -> (String, String) {
+        return (group_id, shard_name);
     }
 
     pub fn get_offset(&self, group_id: String, shard_name: String) -> Option<u128> {
@@ -50,17 +57,98 @@ impl MemoryStorageAdapter {
     }
 }
 
-impl MemoryStorageAdapter {}
+impl MemoryStorageAdapter {
+    // Records are kept in offset order, but retention may remove records from the head
+    // of the shard, so the position of a record is looked up by its offset.
+    fn record_position(records: &Vec<Record>, offset: u128) -> usize {
+        return records.partition_point(|r| r.offset < offset);
+    }
+
+    // Decompresses the records until their data exceeds record_size, the first record is
+    // always returned so that a single large record cannot block the reader.
+    fn limit_records<'a>(
+        records: impl Iterator<Item = &'a Record>,
+        record_size: Option<usize>,
+    ) -> Result<Vec<Record>, CommonError> {
+        let mut result = Vec::new();
+        let mut total_size = 0;
+        for record in records {
+            let record = decompress_record(record.clone())?;
+            total_size += record.data.len();
+            if let Some(size) = record_size {
+                if total_size > size && !result.is_empty() {
+                    break;
+                }
+            }
+            result.push(record);
+        }
+        return Ok(result);
+    }
+
+    fn retention_shard(records: Vec<Record>, config: &ShardConfig) -> Vec<Record> {
+        if config.cleanup_policy == ShardCleanupPolicy::Compact {
+            let mut latest_offset = HashMap::new();
+            for record in records.iter() {
+                if let Some(key) = &record.key {
+                    latest_offset.insert(key.clone(), record.offset);
+                }
+            }
+            return records
+                .into_iter()
+                .filter(|r| {
+                    if let Some(key) = &r.key {
+                        return latest_offset.get(key) == Some(&r.offset);
+                    }
+                    return true;
+                })
+                .collect();
+        }
+
+        let mut records = records;
+        if config.retention_sec > 0 {
+            let expire_time = now_second().saturating_sub(config.retention_sec) as u128;
+            records.retain(|r| r.create_time.unwrap_or_default() >= expire_time);
+        }
+
+        if config.retention_record_num > 0 && records.len() as u64 > config.retention_record_num {
+            let remove_num = records.len() - config.retention_record_num as usize;
+            records.drain(0..remove_num);
+        }
+
+        if config.retention_bytes > 0 {
+            let mut total_size: u64 = records.iter().map(|r| r.data.len() as u64).sum();
+            let mut remove_num = 0;
+            for record in records.iter() {
+                if total_size <= config.retention_bytes {
+                    break;
+                }
+                total_size -= record.data.len() as u64;
+                remove_num += 1;
+            }
+            records.drain(0..remove_num);
+        }
+        return records;
+    }
+}
 
 #[async_trait]
 impl StorageAdapter for MemoryStorageAdapter {
-    async fn create_shard(&self, shard_name: String, _: ShardConfig) -> Result<(), CommonError> {
-        self.shard_data.insert(shard_name, Vec::new());
+    async fn create_shard(
+        &self,
+        shard_name: String,
+        shard_config: ShardConfig,
+    ) -> Result<(), CommonError> {
+        self.shard_data.insert(shard_name.clone(), Vec::new());
+        self.shard_config.insert(shard_name, shard_config);
         return Ok(());
     }
 
     async fn delete_shard(&self, shard_name: String) -> Result<(), CommonError> {
         self.shard_data.remove(&shard_name);
+        self.shard_config.remove(&shard_name);
+        self.shard_next_offset.remove(&shard_name);
+        self.key_index.remove(&shard_name);
+        self.group_data.retain(|(_, shard), _| *shard != shard_name);
         return Ok(());
     }
 
@@ -87,25 +175,43 @@ impl StorageAdapter for MemoryStorageAdapter {
         shard_name: String,
         message: Vec<Record>,
     ) -> Result<Vec<usize>, CommonError> {
-        let mut shard = if let Some((_, da)) = self.shard_data.remove(&shard_name) {
-            da
+        let shard_config = if let Some(config) = self.shard_config.get(&shard_name) {
+            config.clone()
+        } else {
+            ShardConfig::default()
+        };
+        let mut records = Vec::new();
+        for msg in message {
+            records.push(compress_record(msg, &shard_config)?);
+        }
+
+        // The entry guard is held until the records are appended, so concurrent writers
+        // of the same shard are serialized and never receive the same offset.
+        let mut shard = self.shard_data.entry(shard_name.clone()).or_default();
+        let mut start_offset = if let Some(offset) = self.shard_next_offset.get(&shard_name) {
+            *offset
         } else {
-            Vec::new()
+            0
         };
-        let mut start_offset = shard.len();
+        let key_index = self.key_index.entry(shard_name.clone()).or_default();
         let mut record_list = Vec::new();
         let mut offset_res = Vec::new();
-        for mut msg in message {
-            offset_res.push(start_offset);
-            msg.offset = start_offset as u128;
+        for mut msg in records {
+            offset_res.push(start_offset as usize);
+            msg.offset = start_offset;
+            if msg.create_time.is_none() {
+                msg.create_time = Some(now_second() as u128);
+            }
+            if let Some(key) = &msg.key {
+                key_index.insert(key.clone(), start_offset);
+            }
             start_offset += 1;
             record_list.push(msg);
-            // todo build timestamp index
-            // todo build key index
         }
 
         shard.append(&mut record_list);
-        self.shard_data.insert(shard_name, shard);
+        self.shard_next_offset
+            .insert(shard_name.clone(), start_offset);
         return Ok(offset_res);
     }
 
@@ -114,7 +220,7 @@ impl StorageAdapter for MemoryStorageAdapter {
         shard_name: String,
         group_id: String,
         record_num: Option<u128>,
-        _: Option<usize>,
+        record_size: Option<usize>,
     ) -> Result<Option<Vec<Record>>, CommonError> {
         let offset = if let Some(da) = self.get_offset(group_id, shard_name.clone()) {
             da + 1
@@ -124,20 +230,12 @@ impl StorageAdapter for MemoryStorageAdapter {
 
         let num = if let Some(num) = record_num { num } else { 10 };
         if let Some(da) = self.shard_data.get(&shard_name) {
-            let mut cur_offset = 0;
-            let mut result = Vec::new();
-            for i in offset..(offset + num) {
-                if let Some(value) = da.get(i as usize) {
-                    result.push(value.clone());
-                    cur_offset += 1;
-                } else {
-                    break;
-                }
-            }
-            if cur_offset > 0 {
-                self.group_data.insert(shard_name, offset + cur_offset);
-            }
-            return Ok(Some(result));
+            let start = MemoryStorageAdapter::record_position(&da, offset);
+            let records = da.iter().skip(start).take(num as usize);
+            return Ok(Some(MemoryStorageAdapter::limit_records(
+                records,
+                record_size,
+            )?));
         }
         return Ok(None);
     }
@@ -159,8 +257,11 @@ impl StorageAdapter for MemoryStorageAdapter {
         offset: usize,
     ) -> Result<Option<Record>, CommonError> {
         if let Some(da) = self.shard_data.get(&shard_name) {
-            if let Some(value) = da.get(offset) {
-                return Ok(Some(value.clone()));
+            let position = MemoryStorageAdapter::record_position(&da, offset as u128);
+            if let Some(value) = da.get(position) {
+                if value.offset == offset as u128 {
+                    return Ok(Some(decompress_record(value.clone())?));
+                }
             }
         }
         return Ok(None);
@@ -168,28 +269,144 @@ impl StorageAdapter for MemoryStorageAdapter {
 
     async fn stream_read_by_timestamp(
         &self,
-        _: String,
-        _: u128,
-        _: u128,
-        _: Option<usize>,
-        _: Option<usize>,
+        shard_name: String,
+        start_timestamp: u128,
+        end_timestamp: u128,
+        record_num: Option<usize>,
+        record_size: Option<usize>,
     ) -> Result<Option<Vec<Record>>, CommonError> {
+        let num = if let Some(num) = record_num { num } else { 10 };
+        if let Some(da) = self.shard_data.get(&shard_name) {
+            let records = da
+                .iter()
+                .filter(|r| {
+                    let create_time = r.create_time.unwrap_or_default();
+                    return create_time >= start_timestamp && create_time <= end_timestamp;
+                })
+                .take(num);
+            return Ok(Some(MemoryStorageAdapter::limit_records(
+                records,
+                record_size,
+            )?));
+        }
         return Ok(None);
     }
 
     async fn stream_read_by_key(
         &self,
-        _: String,
-        _: String,
+        shard_name: String,
+        key: String,
     ) -> Result<Option<Record>, CommonError> {
+        let offset = if let Some(index) = self.key_index.get(&shard_name) {
+            if let Some(offset) = index.get(&key) {
+                *offset
+            } else {
+                return Ok(None);
+            }
+        } else {
+            return Ok(None);
+        };
+        return self
+            .stream_read_by_offset(shard_name, offset as usize)
+            .await;
+    }
+
+    async fn stream_retention(&self) -> Result<(), CommonError> {
+        for raw in self.shard_config.iter() {
+            let (shard_name, config) = (raw.key(), raw.value());
+            if !config.is_retention_enabled() {
+                continue;
+            }
+            if let Some(mut records) = self.shard_data.get_mut(shard_name) {
+                let data = std::mem::take(records.value_mut());
+                *records = MemoryStorageAdapter::retention_shard(data, config);
+            }
+        }
+        return Ok(());
+    }
+
+    async fn stream_list_group(&self, shard_name: String) -> Result<Vec<String>, CommonError> {
+        let mut results = Vec::new();
+        for raw in self.group_data.iter() {
+            let (group_id, shard) = raw.key();
+            if *shard == shard_name {
+                results.push(group_id.clone());
+            }
+        }
+        return Ok(results);
+    }
+
+    async fn stream_group_offset(
+        &self,
+        shard_name: String,
+        group_id: String,
+    ) -> Result<Option<u128>, CommonError> {
+        return Ok(self.get_offset(group_id, shard_name));
+    }
+
+    async fn stream_shard_offset(&self, shard_name: String) -> Result<ShardOffset, CommonError> {
+        let latest_offset = if let Some(offset) = self.shard_next_offset.get(&shard_name) {
+            *offset
+        } else {
+            0
+        };
+        let earliest_offset = if let Some(da) = self.shard_data.get(&shard_name) {
+            if let Some(record) = da.first() {
+                record.offset
+            } else {
+                latest_offset
+            }
+        } else {
+            latest_offset
+        };
+        return Ok(ShardOffset {
+            earliest_offset,
+            latest_offset,
+        });
+    }
+
+    async fn stream_offset_by_timestamp(
+        &self,
+        shard_name: String,
+        timestamp: u128,
+    ) -> Result<Option<u128>, CommonError> {
+        if let Some(da) = self.shard_data.get(&shard_name) {
+            for record in da.iter() {
+                if record.create_time.unwrap_or_default() >= timestamp {
+                    return Ok(Some(record.offset));
+                }
+            }
+        }
         return Ok(None);
     }
+
+    async fn stream_reset_group_offset(
+        &self,
+        shard_name: String,
+        group_id: String,
+        offset: u128,
+    ) -> Result<(), CommonError> {
+        // The committed offset is the last consumed record, reading resumes at the next one.
+        let key = self.offset_key(group_id, shard_name);
+        if offset == 0 {
+            self.group_data.remove(&key);
+        } else {
+            self.group_data.insert(key, offset - 1);
+        }
+        return Ok(());
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::MemoryStorageAdapter;
-    use crate::{record::Record, storage::StorageAdapter};
+    use crate::{
+        compression::CompressionType,
+        conformance::run_conformance_suite,
+        record::Record,
+        storage::{ShardCleanupPolicy, ShardConfig, StorageAdapter},
+    };
+    use std::sync::Arc;
 
     #[tokio::test]
     async fn stream_read_write() {
@@ -328,4 +545,171 @@ mod tests {
             .await
             .unwrap();
     }
+
+    #[tokio::test]
+    async fn stream_retention_by_record_num() {
+        let storage_adapter = MemoryStorageAdapter::new();
+        let shard_name = "test-retention".to_string();
+        let shard_config = ShardConfig {
+            retention_record_num: 2,
+            ..Default::default()
+        };
+        storage_adapter
+            .create_shard(shard_name.clone(), shard_config)
+            .await
+            .unwrap();
+
+        let data = vec![
+            Record::build_b("test1".as_bytes().to_vec()),
+            Record::build_b("test2".as_bytes().to_vec()),
+            Record::build_b("test3".as_bytes().to_vec()),
+        ];
+        storage_adapter
+            .stream_write(shard_name.clone(), data)
+            .await
+            .unwrap();
+        storage_adapter.stream_retention().await.unwrap();
+
+        assert_eq!(
+            storage_adapter.shard_data.get(&shard_name).unwrap().len(),
+            2
+        );
+        assert!(storage_adapter
+            .stream_read_by_offset(shard_name.clone(), 0)
+            .await
+            .unwrap()
+            .is_none());
+        let record = storage_adapter
+            .stream_read_by_offset(shard_name.clone(), 1)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(String::from_utf8(record.data).unwrap(), "test2");
+
+        let result = storage_adapter
+            .stream_write(
+                shard_name.clone(),
+                vec![Record::build_b("test4".as_bytes().to_vec())],
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.get(0).unwrap().clone(), 3);
+    }
+
+    #[tokio::test]
+    async fn stream_retention_compact() {
+        let storage_adapter = MemoryStorageAdapter::new();
+        let shard_name = "test-compact".to_string();
+        let shard_config = ShardConfig {
+            cleanup_policy: ShardCleanupPolicy::Compact,
+            ..Default::default()
+        };
+        storage_adapter
+            .create_shard(shard_name.clone(), shard_config)
+            .await
+            .unwrap();
+
+        let data = vec![
+            Record::build_c("k1".to_string(), "v1".as_bytes().to_vec()),
+            Record::build_c("k2".to_string(), "v2".as_bytes().to_vec()),
+            Record::build_c("k1".to_string(), "v3".as_bytes().to_vec()),
+            Record::build_b("v4".as_bytes().to_vec()),
+        ];
+        storage_adapter
+            .stream_write(shard_name.clone(), data)
+            .await
+            .unwrap();
+        storage_adapter.stream_retention().await.unwrap();
+
+        let records = storage_adapter.shard_data.get(&shard_name).unwrap().clone();
+        let offsets: Vec<u128> = records.iter().map(|r| r.offset).collect();
+        assert_eq!(offsets, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn stream_group_offset_reset() {
+        let storage_adapter = MemoryStorageAdapter::new();
+        let shard_name = "test-group-offset".to_string();
+        let group_id = "test-group".to_string();
+        let data = vec![
+            Record::build_b("test1".as_bytes().to_vec()),
+            Record::build_b("test2".as_bytes().to_vec()),
+            Record::build_b("test3".as_bytes().to_vec()),
+        ];
+        storage_adapter
+            .stream_write(shard_name.clone(), data)
+            .await
+            .unwrap();
+        storage_adapter
+            .stream_commit_offset(shard_name.clone(), group_id.clone(), 1)
+            .await
+            .unwrap();
+
+        let groups = storage_adapter
+            .stream_list_group(shard_name.clone())
+            .await
+            .unwrap();
+        assert_eq!(groups, vec![group_id.clone()]);
+
+        let shard_offset = storage_adapter
+            .stream_shard_offset(shard_name.clone())
+            .await
+            .unwrap();
+        assert_eq!(shard_offset.earliest_offset, 0);
+        assert_eq!(shard_offset.latest_offset, 3);
+
+        storage_adapter
+            .stream_reset_group_offset(shard_name.clone(), group_id.clone(), 0)
+            .await
+            .unwrap();
+        assert!(storage_adapter
+            .stream_group_offset(shard_name.clone(), group_id.clone())
+            .await
+            .unwrap()
+            .is_none());
+        let res = storage_adapter
+            .stream_read(shard_name.clone(), group_id.clone(), Some(1), None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(res.get(0).unwrap().offset, 0);
+    }
+
+    #[tokio::test]
+    async fn stream_write_compression() {
+        let storage_adapter = MemoryStorageAdapter::new();
+        let shard_name = "test-compression".to_string();
+        let shard_config = ShardConfig {
+            compression: CompressionType::Lz4,
+            compression_min_size: 16,
+            ..Default::default()
+        };
+        storage_adapter
+            .create_shard(shard_name.clone(), shard_config)
+            .await
+            .unwrap();
+
+        let data = "robustmq".repeat(64).as_bytes().to_vec();
+        storage_adapter
+            .stream_write(shard_name.clone(), vec![Record::build_b(data.clone())])
+            .await
+            .unwrap();
+        let stored_size = storage_adapter.shard_data.get(&shard_name).unwrap()[0]
+            .data
+            .len();
+        assert!(stored_size < data.len());
+
+        let record = storage_adapter
+            .stream_read_by_offset(shard_name.clone(), 0)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(record.data, data);
+    }
+
+    #[tokio::test]
+    async fn memory_conformance() {
+        let storage_adapter = Arc::new(MemoryStorageAdapter::new());
+        run_conformance_suite(storage_adapter, "memory_conformance").await;
+    }
 }
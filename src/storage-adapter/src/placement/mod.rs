@@ -13,17 +13,31 @@
 
 
 use crate::{
-    record::Record,
-    storage::{ShardConfig, StorageAdapter},
+    compression::{compress_record, decompress_record, CompressionType},
+    record::{Header, Record},
+    storage::{ShardCleanupPolicy, ShardConfig, ShardOffset, StorageAdapter},
 };
 use axum::async_trait;
 use clients::{
-    placement::kv::call::{placement_delete, placement_exists, placement_get, placement_set},
+    placement::kv::call::{
+        placement_delete, placement_exists, placement_get, placement_set,
+        placement_stream_commit_offset, placement_stream_create_shard,
+        placement_stream_delete_shard, placement_stream_get_shard, placement_stream_list_group,
+        placement_stream_offset_by_timestamp, placement_stream_read, placement_stream_read_by_key,
+        placement_stream_read_by_offset, placement_stream_read_by_timestamp,
+        placement_stream_reset_group_offset, placement_stream_write,
+    },
     poll::ClientPool,
 };
 use common_base::error::common::CommonError;
+use dashmap::DashMap;
 use protocol::placement_center::generate::kv::{
-    DeleteRequest, ExistsRequest, GetRequest, SetRequest,
+    DeleteRequest, ExistsRequest, GetRequest, SetRequest, StreamCleanupPolicy,
+    StreamCommitOffsetRequest, StreamCreateShardRequest, StreamDeleteShardRequest,
+    StreamGetShardRequest, StreamHeader, StreamListGroupRequest, StreamOffsetByTimestampRequest,
+    StreamReadByKeyRequest, StreamReadByOffsetRequest, StreamReadByTimestampRequest,
+    StreamReadRequest, StreamRecord, StreamResetGroupOffsetRequest, StreamShardConfig,
+    StreamWriteRequest,
 };
 use std::sync::Arc;
 
@@ -31,32 +45,177 @@ use std::sync::Arc;
 pub struct PlacementStorageAdapter {
     client_poll: Arc<ClientPool>,
     addrs: Vec<String>,
+    // The shards are stored under the cluster, so that clusters sharing the placement
+    // center do not see each other's shards.
+    cluster_name: String,
+    // The records are compressed here before they are sent to the placement center.
+    // A config is dropped when a write of its shard fails, so that it is read again.
+    shard_configs: Arc<DashMap<String, ShardConfig>>,
 }
 
 impl PlacementStorageAdapter {
-    pub fn new(client_poll: Arc<ClientPool>, addrs: Vec<String>) -> Self {
-        return PlacementStorageAdapter { client_poll, addrs };
+    pub fn new(client_poll: Arc<ClientPool>, addrs: Vec<String>, cluster_name: String) -> Self {
+        return PlacementStorageAdapter {
+            client_poll,
+            addrs,
+            cluster_name,
+            shard_configs: Arc::new(DashMap::with_capacity(8)),
+        };
+    }
+
+    async fn get_shard_config(&self, shard_name: &String) -> Result<ShardConfig, CommonError> {
+        if let Some(config) = self.shard_configs.get(shard_name) {
+            return Ok(config.clone());
+        }
+        let request = StreamGetShardRequest {
+            cluster_name: self.cluster_name.clone(),
+            shard_name: shard_name.clone(),
+            stale_read: false,
+        };
+        let reply =
+            placement_stream_get_shard(self.client_poll.clone(), self.addrs.clone(), request)
+                .await?;
+        // A shard that does not exist is not cached, the write reports it.
+        let config = match reply.config {
+            Some(config) => stream_config_to_shard_config(config),
+            None => return Ok(ShardConfig::default()),
+        };
+        self.shard_configs
+            .insert(shard_name.clone(), config.clone());
+        return Ok(config);
+    }
+}
+
+// The placement center applies the retention of the shards itself.
+fn shard_config_to_stream_config(config: &ShardConfig) -> StreamShardConfig {
+    let cleanup_policy = match config.cleanup_policy {
+        ShardCleanupPolicy::Delete => StreamCleanupPolicy::Delete,
+        ShardCleanupPolicy::Compact => StreamCleanupPolicy::Compact,
+    };
+    return StreamShardConfig {
+        retention_sec: config.retention_sec,
+        retention_bytes: config.retention_bytes,
+        retention_record_num: config.retention_record_num,
+        cleanup_policy: cleanup_policy.into(),
+        compression: config.compression.name(),
+        compression_min_size: config.compression_min_size,
+    };
+}
+
+fn stream_config_to_shard_config(config: StreamShardConfig) -> ShardConfig {
+    let cleanup_policy = if config.cleanup_policy == StreamCleanupPolicy::Compact as i32 {
+        ShardCleanupPolicy::Compact
+    } else {
+        ShardCleanupPolicy::Delete
+    };
+    return ShardConfig {
+        retention_sec: config.retention_sec,
+        retention_bytes: config.retention_bytes,
+        retention_record_num: config.retention_record_num,
+        cleanup_policy,
+        compression: CompressionType::from_name(&config.compression),
+        compression_min_size: config.compression_min_size,
+    };
+}
+
+fn record_to_stream_record(record: Record) -> StreamRecord {
+    let header = if let Some(header) = record.header {
+        header
+            .into_iter()
+            .map(|h| StreamHeader {
+                name: h.name,
+                value: h.value,
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    return StreamRecord {
+        offset: record.offset as u64,
+        header,
+        key: record.key.unwrap_or_default(),
+        data: record.data,
+        create_time: record.create_time.unwrap_or_default() as u64,
+    };
+}
+
+// Records are decompressed as they are read, the compression is kept in their header.
+fn stream_records_to_records(records: Vec<StreamRecord>) -> Result<Vec<Record>, CommonError> {
+    let mut results = Vec::new();
+    for record in records {
+        results.push(decompress_record(stream_record_to_record(record))?);
     }
+    return Ok(results);
+}
+
+fn stream_record_to_record(record: StreamRecord) -> Record {
+    let header = record
+        .header
+        .into_iter()
+        .map(|h| Header {
+            name: h.name,
+            value: h.value,
+        })
+        .collect();
+    return Record {
+        offset: record.offset as u128,
+        header: Some(header),
+        key: if record.key.is_empty() {
+            None
+        } else {
+            Some(record.key)
+        },
+        data: record.data,
+        create_time: Some(record.create_time as u128),
+    };
 }
 
 #[async_trait]
 impl StorageAdapter for PlacementStorageAdapter {
     async fn create_shard(
         &self,
-        _: String,
-        _: ShardConfig,
+        shard_name: String,
+        shard_config: ShardConfig,
     ) -> Result<(), CommonError> {
-        return Ok(());
+        let request = StreamCreateShardRequest {
+            cluster_name: self.cluster_name.clone(),
+            shard_name: shard_name.clone(),
+            config: Some(shard_config_to_stream_config(&shard_config)),
+        };
+        match placement_stream_create_shard(self.client_poll.clone(), self.addrs.clone(), request)
+            .await
+        {
+            Ok(_) => {
+                self.shard_configs.insert(shard_name, shard_config);
+                return Ok(());
+            }
+            Err(e) => {
+                return Err(e);
+            }
+        }
     }
 
-    async fn delete_shard(&self, _: String) -> Result<(), CommonError> {
-        return Ok(());
+    async fn delete_shard(&self, shard_name: String) -> Result<(), CommonError> {
+        self.shard_configs.remove(&shard_name);
+        let request = StreamDeleteShardRequest {
+            cluster_name: self.cluster_name.clone(),
+            shard_name,
+        };
+        match placement_stream_delete_shard(self.client_poll.clone(), self.addrs.clone(), request)
+            .await
+        {
+            Ok(_) => return Ok(()),
+            Err(e) => {
+                return Err(e);
+            }
+        }
     }
 
     async fn set(&self, key: String, value: Record) -> Result<(), CommonError> {
         let request = SetRequest {
             key,
             value: String::from_utf8(value.data).unwrap(),
+            ..Default::default()
         };
         match placement_set(self.client_poll.clone(), self.addrs.clone(), request).await {
             Ok(_) => {
@@ -69,7 +228,10 @@ impl StorageAdapter for PlacementStorageAdapter {
     }
 
     async fn get(&self, key: String) -> Result<Option<Record>, CommonError> {
-        let request = GetRequest { key };
+        let request = GetRequest {
+            key,
+            stale_read: false,
+        };
         match placement_get(self.client_poll.clone(), self.addrs.clone(), request).await {
             Ok(reply) => {
                 if reply.value.is_empty() {
@@ -93,7 +255,10 @@ impl StorageAdapter for PlacementStorageAdapter {
         }
     }
     async fn exists(&self, key: String) -> Result<bool, CommonError> {
-        let request = ExistsRequest { key };
+        let request = ExistsRequest {
+            key,
+            stale_read: false,
+        };
         match placement_exists(self.client_poll.clone(), self.addrs.clone(), request).await {
             Ok(reply) => return Ok(reply.flag),
             Err(e) => {
@@ -102,71 +267,312 @@ impl StorageAdapter for PlacementStorageAdapter {
         }
     }
 
-    async fn stream_write(&self, _: String, _: Vec<Record>) -> Result<Vec<usize>, CommonError> {
-        return Err(CommonError::NotSupportFeature(
-            "PlacementStorageAdapter".to_string(),
-            "stream_write".to_string(),
-        ));
+    async fn stream_write(
+        &self,
+        shard_name: String,
+        data: Vec<Record>,
+    ) -> Result<Vec<usize>, CommonError> {
+        let shard_config = self.get_shard_config(&shard_name).await?;
+        let mut records = Vec::new();
+        for record in data {
+            let record = compress_record(record, &shard_config)?;
+            records.push(record_to_stream_record(record));
+        }
+        let request = StreamWriteRequest {
+            cluster_name: self.cluster_name.clone(),
+            shard_name: shard_name.clone(),
+            records,
+            ..Default::default()
+        };
+        match placement_stream_write(self.client_poll.clone(), self.addrs.clone(), request).await {
+            Ok(reply) => {
+                return Ok(reply.offsets.into_iter().map(|o| o as usize).collect());
+            }
+            Err(e) => {
+                // The shard may have been deleted or created again by another broker.
+                self.shard_configs.remove(&shard_name);
+                return Err(e);
+            }
+        }
     }
 
     async fn stream_read(
         &self,
-        _: String,
-        _: String,
-        _: Option<u128>,
-        _: Option<usize>,
+        shard_name: String,
+        group_id: String,
+        record_num: Option<u128>,
+        record_size: Option<usize>,
     ) -> Result<Option<Vec<Record>>, CommonError> {
-        return Err(CommonError::NotSupportFeature(
-            "PlacementStorageAdapter".to_string(),
-            "stream_write".to_string(),
-        ));
+        let request = StreamReadRequest {
+            cluster_name: self.cluster_name.clone(),
+            shard_name,
+            group_id,
+            record_num: record_num.unwrap_or_default() as u64,
+            record_size: record_size.unwrap_or_default() as u64,
+            stale_read: false,
+        };
+        match placement_stream_read(self.client_poll.clone(), self.addrs.clone(), request).await {
+            Ok(reply) => {
+                return Ok(Some(stream_records_to_records(reply.records)?));
+            }
+            Err(e) => {
+                return Err(e);
+            }
+        }
     }
 
     async fn stream_commit_offset(
         &self,
-        _: String,
-        _: String,
-        _: u128,
+        shard_name: String,
+        group_id: String,
+        offset: u128,
     ) -> Result<bool, CommonError> {
-        return Err(CommonError::NotSupportFeature(
-            "PlacementStorageAdapter".to_string(),
-            "stream_write".to_string(),
-        ));
+        let request = StreamCommitOffsetRequest {
+            cluster_name: self.cluster_name.clone(),
+            shard_name,
+            group_id,
+            offset: offset as u64,
+        };
+        match placement_stream_commit_offset(self.client_poll.clone(), self.addrs.clone(), request)
+            .await
+        {
+            Ok(_) => return Ok(true),
+            Err(e) => {
+                return Err(e);
+            }
+        }
     }
 
     async fn stream_read_by_offset(
         &self,
-        _: String,
-        _: usize,
+        shard_name: String,
+        record_id: usize,
     ) -> Result<Option<Record>, CommonError> {
-        return Err(CommonError::NotSupportFeature(
-            "PlacementStorageAdapter".to_string(),
-            "stream_write".to_string(),
-        ));
+        let request = StreamReadByOffsetRequest {
+            cluster_name: self.cluster_name.clone(),
+            shard_name,
+            offset: record_id as u64,
+            stale_read: false,
+        };
+        match placement_stream_read_by_offset(self.client_poll.clone(), self.addrs.clone(), request)
+            .await
+        {
+            Ok(reply) => match reply.record {
+                Some(record) => {
+                    return Ok(Some(decompress_record(stream_record_to_record(record))?));
+                }
+                None => return Ok(None),
+            },
+            Err(e) => {
+                return Err(e);
+            }
+        }
     }
 
     async fn stream_read_by_timestamp(
         &self,
-        _: String,
-        _: u128,
-        _: u128,
-        _: Option<usize>,
-        _: Option<usize>,
+        shard_name: String,
+        start_timestamp: u128,
+        end_timestamp: u128,
+        record_num: Option<usize>,
+        record_size: Option<usize>,
     ) -> Result<Option<Vec<Record>>, CommonError> {
-        return Err(CommonError::NotSupportFeature(
-            "PlacementStorageAdapter".to_string(),
-            "stream_write".to_string(),
-        ));
+        let request = StreamReadByTimestampRequest {
+            cluster_name: self.cluster_name.clone(),
+            shard_name,
+            start_timestamp: start_timestamp as u64,
+            end_timestamp: end_timestamp as u64,
+            record_num: record_num.unwrap_or_default() as u64,
+            record_size: record_size.unwrap_or_default() as u64,
+            stale_read: false,
+        };
+        match placement_stream_read_by_timestamp(
+            self.client_poll.clone(),
+            self.addrs.clone(),
+            request,
+        )
+        .await
+        {
+            Ok(reply) => {
+                return Ok(Some(stream_records_to_records(reply.records)?));
+            }
+            Err(e) => {
+                return Err(e);
+            }
+        }
     }
 
     async fn stream_read_by_key(
         &self,
-        _: String,
-        _: String,
+        shard_name: String,
+        key: String,
     ) -> Result<Option<Record>, CommonError> {
-        return Err(CommonError::NotSupportFeature(
-            "PlacementStorageAdapter".to_string(),
-            "stream_write".to_string(),
+        let request = StreamReadByKeyRequest {
+            cluster_name: self.cluster_name.clone(),
+            shard_name,
+            key,
+            stale_read: false,
+        };
+        match placement_stream_read_by_key(self.client_poll.clone(), self.addrs.clone(), request)
+            .await
+        {
+            Ok(reply) => match reply.record {
+                Some(record) => {
+                    return Ok(Some(decompress_record(stream_record_to_record(record))?));
+                }
+                None => return Ok(None),
+            },
+            Err(e) => {
+                return Err(e);
+            }
+        }
+    }
+
+    async fn stream_list_group(&self, shard_name: String) -> Result<Vec<String>, CommonError> {
+        let request = StreamListGroupRequest {
+            cluster_name: self.cluster_name.clone(),
+            shard_name,
+            stale_read: false,
+        };
+        match placement_stream_list_group(self.client_poll.clone(), self.addrs.clone(), request)
+            .await
+        {
+            Ok(reply) => {
+                return Ok(reply.groups.into_iter().map(|g| g.group_id).collect());
+            }
+            Err(e) => {
+                return Err(e);
+            }
+        }
+    }
+
+    async fn stream_group_offset(
+        &self,
+        shard_name: String,
+        group_id: String,
+    ) -> Result<Option<u128>, CommonError> {
+        let request = StreamListGroupRequest {
+            cluster_name: self.cluster_name.clone(),
+            shard_name,
+            stale_read: false,
+        };
+        match placement_stream_list_group(self.client_poll.clone(), self.addrs.clone(), request)
+            .await
+        {
+            Ok(reply) => {
+                return Ok(reply
+                    .groups
+                    .into_iter()
+                    .find(|g| g.group_id == group_id)
+                    .map(|g| g.offset as u128));
+            }
+            Err(e) => {
+                return Err(e);
+            }
+        }
+    }
+
+    async fn stream_shard_offset(&self, shard_name: String) -> Result<ShardOffset, CommonError> {
+        let request = StreamGetShardRequest {
+            cluster_name: self.cluster_name.clone(),
+            shard_name,
+            stale_read: false,
+        };
+        match placement_stream_get_shard(self.client_poll.clone(), self.addrs.clone(), request)
+            .await
+        {
+            Ok(reply) => {
+                return Ok(ShardOffset {
+                    earliest_offset: reply.start_offset as u128,
+                    latest_offset: reply.next_offset as u128,
+                });
+            }
+            Err(e) => {
+                return Err(e);
+            }
+        }
+    }
+
+    async fn stream_offset_by_timestamp(
+        &self,
+        shard_name: String,
+        timestamp: u128,
+    ) -> Result<Option<u128>, CommonError> {
+        let request = StreamOffsetByTimestampRequest {
+            cluster_name: self.cluster_name.clone(),
+            shard_name,
+            timestamp: timestamp as u64,
+            stale_read: false,
+        };
+        match placement_stream_offset_by_timestamp(
+            self.client_poll.clone(),
+            self.addrs.clone(),
+            request,
+        )
+        .await
+        {
+            Ok(reply) => {
+                if reply.found {
+                    return Ok(Some(reply.offset as u128));
+                }
+                return Ok(None);
+            }
+            Err(e) => {
+                return Err(e);
+            }
+        }
+    }
+
+    async fn stream_reset_group_offset(
+        &self,
+        shard_name: String,
+        group_id: String,
+        offset: u128,
+    ) -> Result<(), CommonError> {
+        let request = StreamResetGroupOffsetRequest {
+            cluster_name: self.cluster_name.clone(),
+            shard_name,
+            group_id,
+            offset: offset as u64,
+        };
+        match placement_stream_reset_group_offset(
+            self.client_poll.clone(),
+            self.addrs.clone(),
+            request,
+        )
+        .await
+        {
+            Ok(_) => return Ok(()),
+            Err(e) => {
+                return Err(e);
+            }
+        }
+    }
+
+    async fn stream_retention(&self) -> Result<(), CommonError> {
+        // The retention config is sent with the shard, the placement center leader removes
+        // the records beyond it.
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PlacementStorageAdapter;
+    use crate::conformance::run_conformance_suite;
+    use clients::poll::ClientPool;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    #[ignore]
+    async fn placement_conformance() {
+        let client_poll = Arc::new(ClientPool::new(10));
+        let addrs = vec!["127.0.0.1:1228".to_string()];
+        let storage_adapter = Arc::new(PlacementStorageAdapter::new(
+            client_poll,
+            addrs,
+            "placement_conformance".to_string(),
         ));
+        run_conformance_suite(storage_adapter, "placement_conformance").await;
     }
 }
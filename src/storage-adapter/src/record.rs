@@ -12,8 +12,33 @@
 // limitations under the License.
 
 
+use common_base::error::common::CommonError;
+use prost::Message as _;
+use protocol::storage_adapter::generate::record::{Header as RecordHeaderPb, Record as RecordPb};
 use serde::{Deserialize, Serialize};
 
+// Binary encoded data starts with the codec magic byte followed by the codec version.
+// Data written by older versions is JSON, which always starts with '{', so the two
+// formats can be told apart when reading.
+pub const CODEC_MAGIC: u8 = 0xFE;
+pub const CODEC_VERSION_V1: u8 = 1;
+
+pub fn codec_encode(version: u8, body: Vec<u8>) -> Vec<u8> {
+    let mut data = Vec::with_capacity(body.len() + 2);
+    data.push(CODEC_MAGIC);
+    data.push(version);
+    data.extend(body);
+    return data;
+}
+
+// Returns the codec version and the body, or None if the data is not binary encoded.
+pub fn codec_decode(data: &[u8]) -> Option<(u8, &[u8])> {
+    if data.len() < 2 || data[0] != CODEC_MAGIC {
+        return None;
+    }
+    return Some((data[1], &data[2..]));
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Header {
     pub name: String,
@@ -84,6 +109,107 @@ impl Record {
             header: None,
         };
     }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let header = if let Some(header) = &self.header {
+            header
+                .iter()
+                .map(|h| RecordHeaderPb {
+                    name: h.name.clone(),
+                    value: h.value.clone(),
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let record = RecordPb {
+            offset: self.offset as u64,
+            header,
+            key: self.key.clone(),
+            data: self.data.clone(),
+            create_time: self.create_time.map(|t| t as u64),
+            has_header: self.header.is_some(),
+        };
+        return codec_encode(CODEC_VERSION_V1, RecordPb::encode_to_vec(&record));
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Record, CommonError> {
+        let (version, body) = match codec_decode(data) {
+            Some(da) => da,
+            None => {
+                return Ok(serde_json::from_slice::<Record>(data)?);
+            }
+        };
+
+        match version {
+            CODEC_VERSION_V1 => {
+                let record = RecordPb::decode(body)?;
+                let header = if record.has_header {
+                    Some(
+                        record
+                            .header
+                            .into_iter()
+                            .map(|h| Header {
+                                name: h.name,
+                                value: h.value,
+                            })
+                            .collect(),
+                    )
+                } else {
+                    None
+                };
+                return Ok(Record {
+                    offset: record.offset as u128,
+                    header,
+                    key: record.key,
+                    data: record.data,
+                    create_time: record.create_time.map(|t| t as u128),
+                });
+            }
+            _ => {
+                return Err(CommonError::CommmonError(format!(
+                    "Unsupported record codec version {}",
+                    version
+                )));
+            }
+        }
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::{Header, Record};
 
+    #[test]
+    fn record_codec() {
+        let record = Record::build_a(
+            Some("k1".to_string()),
+            "test".as_bytes().to_vec(),
+            Some(vec![Header {
+                name: "n1".to_string(),
+                value: "v1".to_string(),
+            }]),
+            Some(1700000000),
+        );
+        let data = record.encode();
+        let res = Record::decode(&data).unwrap();
+        assert_eq!(res.key, record.key);
+        assert_eq!(res.data, record.data);
+        assert_eq!(res.create_time, record.create_time);
+        assert_eq!(res.header.unwrap().len(), 1);
+
+        let record = Record::build_b("test".as_bytes().to_vec());
+        let res = Record::decode(&record.encode()).unwrap();
+        assert!(res.header.is_none());
+        assert!(res.key.is_none());
+    }
+
+    #[test]
+    fn record_codec_json_compatible() {
+        let record = Record::build_c("k1".to_string(), "test".as_bytes().to_vec());
+        let data = serde_json::to_vec(&record).unwrap();
+        let res = Record::decode(&data).unwrap();
+        assert_eq!(res.key, record.key);
+        assert_eq!(res.data, record.data);
+    }
+}
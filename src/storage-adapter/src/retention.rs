@@ -0,0 +1,59 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::storage::StorageAdapter;
+use log::{error, info};
+use std::{sync::Arc, time::Duration};
+use tokio::{select, sync::broadcast, time::sleep};
+
+// Periodically applies the retention config of every shard of the storage adapter
+// until a stop signal is received.
+pub async fn start_retention_thread<S>(
+    storage_adapter: Arc<S>,
+    interval_sec: u64,
+    stop_send: broadcast::Sender<bool>,
+) where
+    S: StorageAdapter + Sync + Send + 'static,
+{
+    let mut stop_rx = stop_send.subscribe();
+    loop {
+        select! {
+            val = stop_rx.recv() => {
+                match val {
+                    Ok(flag) => {
+                        if flag {
+                            info!("{}", "Shard retention thread stopped successfully.");
+                            break;
+                        }
+                    }
+                    Err(_) => {}
+                }
+            }
+            _ = retention_once(&storage_adapter, interval_sec) => {
+            }
+        }
+    }
+}
+
+async fn retention_once<S>(storage_adapter: &Arc<S>, interval_sec: u64)
+where
+    S: StorageAdapter + Sync + Send + 'static,
+{
+    match storage_adapter.stream_retention().await {
+        Ok(()) => {}
+        Err(e) => {
+            error!("Shard retention failed, error message: {}", e);
+        }
+    }
+    sleep(Duration::from_secs(interval_sec)).await;
+}
@@ -0,0 +1,827 @@
+// Copyright 2023 RobustMQ Team
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    compression::{compress_record, decompress_record},
+    record::{Header, Record},
+    storage::{ShardCleanupPolicy, ShardConfig, ShardOffset, StorageAdapter},
+};
+use axum::async_trait;
+use common_base::{error::common::CommonError, tools::now_second};
+use rusqlite::{params, Connection, OptionalExtension, Params, Transaction};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+const INIT_TABLE_SQL: &str = "
+CREATE TABLE IF NOT EXISTS storage_kv (
+    data_key TEXT PRIMARY KEY,
+    data_value BLOB,
+    create_time INTEGER NOT NULL,
+    update_time INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS storage_shard (
+    shard_name TEXT PRIMARY KEY,
+    shard_config TEXT NOT NULL,
+    next_offset INTEGER NOT NULL,
+    create_time INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS storage_record (
+    shard_name TEXT NOT NULL,
+    record_offset INTEGER NOT NULL,
+    header TEXT,
+    msg_key TEXT,
+    payload BLOB,
+    create_time INTEGER NOT NULL,
+    PRIMARY KEY (shard_name, record_offset)
+);
+CREATE INDEX IF NOT EXISTS idx_storage_record_time ON storage_record (shard_name, create_time);
+CREATE INDEX IF NOT EXISTS idx_storage_record_key ON storage_record (shard_name, msg_key);
+CREATE TABLE IF NOT EXISTS storage_group_offset (
+    shard_name TEXT NOT NULL,
+    group_id TEXT NOT NULL,
+    committed_offset INTEGER NOT NULL,
+    update_time INTEGER NOT NULL,
+    PRIMARY KEY (shard_name, group_id)
+);";
+
+const RECORD_COLUMNS: &str = "record_offset,header,msg_key,payload,create_time";
+
+// Stores all data in a single SQLite database file, for single node deployments
+// that cannot run an external database. SQLite allows one writer at a time,
+// so all operations share one connection.
+#[derive(Clone)]
+pub struct SQLiteStorageAdapter {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SQLiteStorageAdapter {
+    pub fn new(conn: Connection) -> Self {
+        let adapter = SQLiteStorageAdapter {
+            conn: Arc::new(Mutex::new(conn)),
+        };
+        match adapter.init_table() {
+            Ok(()) => {}
+            Err(e) => {
+                panic!("{}", e.to_string())
+            }
+        }
+        return adapter;
+    }
+
+    pub fn init_table(&self) -> Result<(), CommonError> {
+        let conn = self.lock_conn()?;
+        match conn.execute_batch(INIT_TABLE_SQL) {
+            Ok(()) => return Ok(()),
+            Err(e) => return Err(sqlite_error(e)),
+        }
+    }
+
+    fn lock_conn(&self) -> Result<MutexGuard<Connection>, CommonError> {
+        match self.conn.lock() {
+            Ok(conn) => return Ok(conn),
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        }
+    }
+
+    // The SQLite calls block, so they run on the blocking threads of the runtime.
+    async fn with_conn<T, F>(&self, f: F) -> Result<T, CommonError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T, CommonError> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+        match tokio::task::spawn_blocking(move || {
+            let mut conn = match conn.lock() {
+                Ok(conn) => conn,
+                Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+            };
+            f(&mut conn)
+        })
+        .await
+        {
+            Ok(result) => return result,
+            Err(e) => return Err(CommonError::CommmonError(e.to_string())),
+        }
+    }
+
+    // Returns the config and the next offset of the shard, a shard that has not been
+    // created is written with the default config.
+    fn get_shard(
+        conn: &Connection,
+        shard_name: &String,
+    ) -> Result<Option<(ShardConfig, u128)>, CommonError> {
+        let data: Option<(String, i64)> = conn
+            .query_row(
+                "SELECT shard_config,next_offset FROM storage_shard WHERE shard_name = ?1",
+                params![shard_name],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(sqlite_error)?;
+        if let Some((config, next_offset)) = data {
+            let config = serde_json::from_str::<ShardConfig>(&config)?;
+            return Ok(Some((config, next_offset as u128)));
+        }
+        return Ok(None);
+    }
+
+    fn query_records<P: Params>(
+        conn: &Connection,
+        sql: &str,
+        params: P,
+        record_size: Option<usize>,
+    ) -> Result<Vec<Record>, CommonError> {
+        let mut stmt = conn.prepare(sql).map_err(sqlite_error)?;
+        let rows = stmt
+            .query_map(params, |row| {
+                let offset: i64 = row.get(0)?;
+                let header: Option<String> = row.get(1)?;
+                let key: Option<String> = row.get(2)?;
+                let data: Vec<u8> = row.get(3)?;
+                let create_time: i64 = row.get(4)?;
+                Ok((offset, header, key, data, create_time))
+            })
+            .map_err(sqlite_error)?;
+
+        // The first record is always returned so that a single large record
+        // cannot block the reader.
+        let mut result = Vec::new();
+        let mut total_size = 0;
+        for row in rows {
+            let (offset, header, key, data, create_time) = row.map_err(sqlite_error)?;
+            // A header that cannot be parsed is an error, not a record without headers
+            let header = match header {
+                Some(h) => Some(serde_json::from_str::<Vec<Header>>(&h)?),
+                None => None,
+            };
+            let record = decompress_record(Record {
+                offset: offset as u128,
+                header,
+                key,
+                data,
+                create_time: Some(create_time as u128),
+            })?;
+            total_size += record.data.len();
+            if let Some(size) = record_size {
+                if total_size > size && !result.is_empty() {
+                    break;
+                }
+            }
+            result.push(record);
+        }
+        return Ok(result);
+    }
+
+    fn save_group_offset(
+        conn: &Connection,
+        shard_name: &String,
+        group_id: &String,
+        offset: u128,
+    ) -> Result<(), CommonError> {
+        conn.execute(
+            "INSERT INTO storage_group_offset(shard_name,group_id,committed_offset,update_time) VALUES (?1,?2,?3,?4)
+            ON CONFLICT(shard_name,group_id) DO UPDATE SET committed_offset = excluded.committed_offset, update_time = excluded.update_time",
+            params![shard_name, group_id, offset as i64, now_second() as i64],
+        )
+        .map_err(sqlite_error)?;
+        return Ok(());
+    }
+
+    fn retention_shard(
+        tx: &Transaction,
+        shard_name: &String,
+        config: &ShardConfig,
+    ) -> Result<(), rusqlite::Error> {
+        if config.cleanup_policy == ShardCleanupPolicy::Compact {
+            tx.execute(
+                "DELETE FROM storage_record WHERE shard_name = ?1 AND msg_key IS NOT NULL AND record_offset < (
+                    SELECT MAX(r.record_offset) FROM storage_record r
+                    WHERE r.shard_name = storage_record.shard_name AND r.msg_key = storage_record.msg_key)",
+                params![shard_name],
+            )?;
+            return Ok(());
+        }
+
+        if config.retention_sec > 0 {
+            let expire_time = now_second().saturating_sub(config.retention_sec);
+            tx.execute(
+                "DELETE FROM storage_record WHERE shard_name = ?1 AND create_time < ?2",
+                params![shard_name, expire_time as i64],
+            )?;
+        }
+
+        if config.retention_record_num > 0 {
+            tx.execute(
+                "DELETE FROM storage_record WHERE shard_name = ?1 AND record_offset <= (
+                    SELECT record_offset FROM storage_record WHERE shard_name = ?1
+                    ORDER BY record_offset DESC LIMIT 1 OFFSET ?2)",
+                params![shard_name, config.retention_record_num as i64],
+            )?;
+        }
+
+        if config.retention_bytes > 0 {
+            let mut stmt = tx.prepare(
+                "SELECT record_offset,LENGTH(payload) FROM storage_record WHERE shard_name = ?1 ORDER BY record_offset DESC",
+            )?;
+            let rows = stmt.query_map(params![shard_name], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?))
+            })?;
+            let mut total_size = 0;
+            let mut expire_offset = None;
+            for row in rows {
+                let (offset, size) = row?;
+                total_size += size as u64;
+                if total_size > config.retention_bytes {
+                    expire_offset = Some(offset);
+                    break;
+                }
+            }
+            if let Some(offset) = expire_offset {
+                tx.execute(
+                    "DELETE FROM storage_record WHERE shard_name = ?1 AND record_offset <= ?2",
+                    params![shard_name, offset],
+                )?;
+            }
+        }
+        return Ok(());
+    }
+}
+
+fn sqlite_error(e: rusqlite::Error) -> CommonError {
+    return CommonError::CommmonError(e.to_string());
+}
+
+#[async_trait]
+impl StorageAdapter for SQLiteStorageAdapter {
+    async fn create_shard(
+        &self,
+        shard_name: String,
+        shard_config: ShardConfig,
+    ) -> Result<(), CommonError> {
+        let config = serde_json::to_string(&shard_config)?;
+        self.with_conn(move |conn| {
+            match conn.execute(
+                "INSERT INTO storage_shard(shard_name,shard_config,next_offset,create_time) VALUES (?1,?2,0,?3)
+                ON CONFLICT(shard_name) DO UPDATE SET shard_config = excluded.shard_config",
+                params![shard_name, config, now_second() as i64],
+            ) {
+                Ok(_) => return Ok(()),
+                Err(e) => return Err(sqlite_error(e)),
+            }
+        })
+        .await
+    }
+
+    async fn delete_shard(&self, shard_name: String) -> Result<(), CommonError> {
+        self.with_conn(move |conn| {
+            let tx = conn.transaction().map_err(sqlite_error)?;
+            for sql in [
+                "DELETE FROM storage_record WHERE shard_name = ?1",
+                "DELETE FROM storage_group_offset WHERE shard_name = ?1",
+                "DELETE FROM storage_shard WHERE shard_name = ?1",
+            ] {
+                tx.execute(sql, params![shard_name]).map_err(sqlite_error)?;
+            }
+            match tx.commit() {
+                Ok(()) => return Ok(()),
+                Err(e) => return Err(sqlite_error(e)),
+            }
+        })
+        .await
+    }
+
+    async fn set(&self, key: String, value: Record) -> Result<(), CommonError> {
+        self.with_conn(move |conn| {
+            match conn.execute(
+                "REPLACE INTO storage_kv(data_key,data_value,create_time,update_time) VALUES (?1,?2,?3,?4)",
+                params![key, value.data, now_second() as i64, now_second() as i64],
+            ) {
+                Ok(_) => return Ok(()),
+                Err(e) => return Err(sqlite_error(e)),
+            }
+        })
+        .await
+    }
+
+    async fn get(&self, key: String) -> Result<Option<Record>, CommonError> {
+        self.with_conn(move |conn| {
+            match conn
+                .query_row(
+                    "SELECT data_value FROM storage_kv WHERE data_key = ?1",
+                    params![key],
+                    |row| row.get::<_, Vec<u8>>(0),
+                )
+                .optional()
+            {
+                Ok(data) => return Ok(data.map(Record::build_b)),
+                Err(e) => return Err(sqlite_error(e)),
+            }
+        })
+        .await
+    }
+
+    async fn delete(&self, key: String) -> Result<(), CommonError> {
+        self.with_conn(move |conn| {
+            match conn.execute("DELETE FROM storage_kv WHERE data_key = ?1", params![key]) {
+                Ok(_) => return Ok(()),
+                Err(e) => return Err(sqlite_error(e)),
+            }
+        })
+        .await
+    }
+
+    async fn exists(&self, key: String) -> Result<bool, CommonError> {
+        self.with_conn(move |conn| {
+            match conn.query_row(
+                "SELECT COUNT(*) FROM storage_kv WHERE data_key = ?1",
+                params![key],
+                |row| row.get::<_, i64>(0),
+            ) {
+                Ok(count) => return Ok(count > 0),
+                Err(e) => return Err(sqlite_error(e)),
+            }
+        })
+        .await
+    }
+
+    async fn stream_write(
+        &self,
+        shard_name: String,
+        data: Vec<Record>,
+    ) -> Result<Vec<usize>, CommonError> {
+        self.with_conn(move |conn| {
+            // All records of a write are committed in one transaction, together with the
+            // next offset of the shard.
+            let tx = conn.transaction().map_err(sqlite_error)?;
+            let shard = SQLiteStorageAdapter::get_shard(&tx, &shard_name)?;
+            let (shard_config, mut next_offset) = match shard {
+                Some(shard) => shard,
+                None => {
+                    let shard_config = ShardConfig::default();
+                    tx.execute(
+                        "INSERT INTO storage_shard(shard_name,shard_config,next_offset,create_time) VALUES (?1,?2,0,?3)",
+                        params![
+                            shard_name,
+                            serde_json::to_string(&shard_config)?,
+                            now_second() as i64
+                        ],
+                    )
+                    .map_err(sqlite_error)?;
+                    (shard_config, 0)
+                }
+            };
+
+            let mut offsets = Vec::new();
+            {
+                let mut stmt = tx
+                    .prepare(&format!(
+                        "INSERT INTO storage_record(shard_name,{}) VALUES (?1,?2,?3,?4,?5,?6)",
+                        RECORD_COLUMNS
+                    ))
+                    .map_err(sqlite_error)?;
+                for raw in data {
+                    let raw = compress_record(raw, &shard_config)?;
+                    let header = match raw.header {
+                        Some(h) => Some(serde_json::to_string(&h)?),
+                        None => None,
+                    };
+                    let create_time = if let Some(t) = raw.create_time {
+                        t as i64
+                    } else {
+                        now_second() as i64
+                    };
+                    stmt.execute(params![
+                        shard_name,
+                        next_offset as i64,
+                        header,
+                        raw.key,
+                        raw.data,
+                        create_time
+                    ])
+                    .map_err(sqlite_error)?;
+                    offsets.push(next_offset as usize);
+                    next_offset += 1;
+                }
+            }
+
+            tx.execute(
+                "UPDATE storage_shard SET next_offset = ?1 WHERE shard_name = ?2",
+                params![next_offset as i64, shard_name],
+            )
+            .map_err(sqlite_error)?;
+            match tx.commit() {
+                Ok(()) => return Ok(offsets),
+                Err(e) => return Err(sqlite_error(e)),
+            }
+        })
+        .await
+    }
+
+    async fn stream_read(
+        &self,
+        shard_name: String,
+        group_id: String,
+        record_num: Option<u128>,
+        record_size: Option<usize>,
+    ) -> Result<Option<Vec<Record>>, CommonError> {
+        self.with_conn(move |conn| {
+            let committed_offset: Option<i64> = conn
+                .query_row(
+                    "SELECT committed_offset FROM storage_group_offset WHERE shard_name = ?1 AND group_id = ?2",
+                    params![shard_name, group_id],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(sqlite_error)?;
+            let offset = if let Some(offset) = committed_offset {
+                offset + 1
+            } else {
+                0
+            };
+            let num = if let Some(num) = record_num { num } else { 10 };
+            let records = SQLiteStorageAdapter::query_records(
+                conn,
+                &format!(
+                    "SELECT {} FROM storage_record WHERE shard_name = ?1 AND record_offset >= ?2 ORDER BY record_offset LIMIT ?3",
+                    RECORD_COLUMNS
+                ),
+                params![shard_name, offset, num as i64],
+                record_size,
+            )?;
+            return Ok(Some(records));
+        })
+        .await
+    }
+
+    async fn stream_commit_offset(
+        &self,
+        shard_name: String,
+        group_id: String,
+        offset: u128,
+    ) -> Result<bool, CommonError> {
+        self.with_conn(move |conn| {
+            SQLiteStorageAdapter::save_group_offset(conn, &shard_name, &group_id, offset)?;
+            return Ok(true);
+        })
+        .await
+    }
+
+    async fn stream_read_by_offset(
+        &self,
+        shard_name: String,
+        offset: usize,
+    ) -> Result<Option<Record>, CommonError> {
+        self.with_conn(move |conn| {
+            let records = SQLiteStorageAdapter::query_records(
+                conn,
+                &format!(
+                    "SELECT {} FROM storage_record WHERE shard_name = ?1 AND record_offset = ?2",
+                    RECORD_COLUMNS
+                ),
+                params![shard_name, offset as i64],
+                None,
+            )?;
+            return Ok(records.into_iter().next());
+        })
+        .await
+    }
+
+    async fn stream_read_by_timestamp(
+        &self,
+        shard_name: String,
+        start_timestamp: u128,
+        end_timestamp: u128,
+        record_num: Option<usize>,
+        record_size: Option<usize>,
+    ) -> Result<Option<Vec<Record>>, CommonError> {
+        self.with_conn(move |conn| {
+            let num = if let Some(num) = record_num { num } else { 10 };
+            let records = SQLiteStorageAdapter::query_records(
+                conn,
+                &format!(
+                    "SELECT {} FROM storage_record WHERE shard_name = ?1 AND create_time >= ?2 AND create_time <= ?3 ORDER BY record_offset LIMIT ?4",
+                    RECORD_COLUMNS
+                ),
+                params![
+                    shard_name,
+                    start_timestamp as i64,
+                    end_timestamp as i64,
+                    num as i64
+                ],
+                record_size,
+            )?;
+            return Ok(Some(records));
+        })
+        .await
+    }
+
+    async fn stream_read_by_key(
+        &self,
+        shard_name: String,
+        key: String,
+    ) -> Result<Option<Record>, CommonError> {
+        self.with_conn(move |conn| {
+            let records = SQLiteStorageAdapter::query_records(
+                conn,
+                &format!(
+                    "SELECT {} FROM storage_record WHERE shard_name = ?1 AND msg_key = ?2 ORDER BY record_offset DESC LIMIT 1",
+                    RECORD_COLUMNS
+                ),
+                params![shard_name, key],
+                None,
+            )?;
+            return Ok(records.into_iter().next());
+        })
+        .await
+    }
+
+    async fn stream_retention(&self) -> Result<(), CommonError> {
+        self.with_conn(move |conn| {
+            let shards: Vec<(String, String)> = {
+                let mut stmt = conn
+                    .prepare("SELECT shard_name,shard_config FROM storage_shard")
+                    .map_err(sqlite_error)?;
+                let rows = stmt
+                    .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                    .map_err(sqlite_error)?;
+                rows.collect::<Result<Vec<(String, String)>, rusqlite::Error>>()
+                    .map_err(sqlite_error)?
+            };
+
+            let tx = conn.transaction().map_err(sqlite_error)?;
+            for (shard_name, config) in shards {
+                let config = serde_json::from_str::<ShardConfig>(&config)?;
+                if !config.is_retention_enabled() {
+                    continue;
+                }
+                SQLiteStorageAdapter::retention_shard(&tx, &shard_name, &config)
+                    .map_err(sqlite_error)?;
+            }
+            match tx.commit() {
+                Ok(()) => return Ok(()),
+                Err(e) => return Err(sqlite_error(e)),
+            }
+        })
+        .await
+    }
+
+    async fn stream_list_group(&self, shard_name: String) -> Result<Vec<String>, CommonError> {
+        self.with_conn(move |conn| {
+            let mut stmt = conn
+                .prepare("SELECT group_id FROM storage_group_offset WHERE shard_name = ?1")
+                .map_err(sqlite_error)?;
+            let rows = stmt
+                .query_map(params![shard_name], |row| row.get::<_, String>(0))
+                .map_err(sqlite_error)?;
+            match rows.collect::<Result<Vec<String>, rusqlite::Error>>() {
+                Ok(groups) => return Ok(groups),
+                Err(e) => return Err(sqlite_error(e)),
+            }
+        })
+        .await
+    }
+
+    async fn stream_group_offset(
+        &self,
+        shard_name: String,
+        group_id: String,
+    ) -> Result<Option<u128>, CommonError> {
+        self.with_conn(move |conn| {
+            match conn
+                .query_row(
+                    "SELECT committed_offset FROM storage_group_offset WHERE shard_name = ?1 AND group_id = ?2",
+                    params![shard_name, group_id],
+                    |row| row.get::<_, i64>(0),
+                )
+                .optional()
+            {
+                Ok(offset) => return Ok(offset.map(|o| o as u128)),
+                Err(e) => return Err(sqlite_error(e)),
+            }
+        })
+        .await
+    }
+
+    async fn stream_shard_offset(&self, shard_name: String) -> Result<ShardOffset, CommonError> {
+        self.with_conn(move |conn| {
+            let latest_offset = match SQLiteStorageAdapter::get_shard(conn, &shard_name)? {
+                Some((_, next_offset)) => next_offset,
+                None => 0,
+            };
+            let earliest_offset: Option<i64> = conn
+                .query_row(
+                    "SELECT MIN(record_offset) FROM storage_record WHERE shard_name = ?1",
+                    params![shard_name],
+                    |row| row.get(0),
+                )
+                .map_err(sqlite_error)?;
+            return Ok(ShardOffset {
+                earliest_offset: earliest_offset.map(|o| o as u128).unwrap_or(latest_offset),
+                latest_offset,
+            });
+        })
+        .await
+    }
+
+    async fn stream_offset_by_timestamp(
+        &self,
+        shard_name: String,
+        timestamp: u128,
+    ) -> Result<Option<u128>, CommonError> {
+        self.with_conn(move |conn| {
+            match conn
+                .query_row(
+                    "SELECT record_offset FROM storage_record WHERE shard_name = ?1 AND create_time >= ?2 ORDER BY record_offset LIMIT 1",
+                    params![shard_name, timestamp as i64],
+                    |row| row.get::<_, i64>(0),
+                )
+                .optional()
+            {
+                Ok(offset) => return Ok(offset.map(|o| o as u128)),
+                Err(e) => return Err(sqlite_error(e)),
+            }
+        })
+        .await
+    }
+
+    async fn stream_reset_group_offset(
+        &self,
+        shard_name: String,
+        group_id: String,
+        offset: u128,
+    ) -> Result<(), CommonError> {
+        self.with_conn(move |conn| {
+            // The committed offset is the last consumed record, reading resumes at the next one.
+            if offset == 0 {
+                match conn.execute(
+                    "DELETE FROM storage_group_offset WHERE shard_name = ?1 AND group_id = ?2",
+                    params![shard_name, group_id],
+                ) {
+                    Ok(_) => return Ok(()),
+                    Err(e) => return Err(sqlite_error(e)),
+                }
+            }
+            return SQLiteStorageAdapter::save_group_offset(
+                conn,
+                &shard_name,
+                &group_id,
+                offset - 1,
+            );
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SQLiteStorageAdapter;
+    use crate::{
+        conformance::run_conformance_suite,
+        record::Record,
+        storage::{ShardConfig, StorageAdapter},
+    };
+    use std::{fs, sync::Arc};
+    use third_driver::sqlite::build_sqlite_conn;
+
+    fn build_adapter(name: &str, clean: bool) -> SQLiteStorageAdapter {
+        let path = std::env::temp_dir().join(format!("robustmq-{}.db", name));
+        if clean {
+            for suffix in ["", "-wal", "-shm"] {
+                let _ = fs::remove_file(format!("{}{}", path.display(), suffix));
+            }
+        }
+        let conn = build_sqlite_conn(&path.display().to_string()).unwrap();
+        return SQLiteStorageAdapter::new(conn);
+    }
+
+    #[tokio::test]
+    async fn sqlite_conformance() {
+        let storage_adapter = Arc::new(build_adapter("sqlite-conformance", true));
+        run_conformance_suite(storage_adapter, "sqlite_conformance").await;
+    }
+
+    #[tokio::test]
+    async fn sqlite_kv() {
+        let storage_adapter = build_adapter("sqlite-kv", true);
+        let key = "name".to_string();
+        storage_adapter
+            .set(key.clone(), Record::build_e("robustmq".to_string()))
+            .await
+            .unwrap();
+        assert!(storage_adapter.exists(key.clone()).await.unwrap());
+        let record = storage_adapter.get(key.clone()).await.unwrap().unwrap();
+        assert_eq!(record.data, "robustmq".as_bytes().to_vec());
+
+        storage_adapter.delete(key.clone()).await.unwrap();
+        assert!(!storage_adapter.exists(key.clone()).await.unwrap());
+        assert!(storage_adapter.get(key).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn sqlite_reopen() {
+        let shard_name = "test".to_string();
+        let group_id = "group".to_string();
+        {
+            let storage_adapter = build_adapter("sqlite-reopen", true);
+            storage_adapter
+                .create_shard(shard_name.clone(), ShardConfig::default())
+                .await
+                .unwrap();
+            let data = vec![
+                Record::build_b("m1".as_bytes().to_vec()),
+                Record::build_b("m2".as_bytes().to_vec()),
+            ];
+            let offsets = storage_adapter
+                .stream_write(shard_name.clone(), data)
+                .await
+                .unwrap();
+            assert_eq!(offsets, vec![0, 1]);
+            storage_adapter
+                .stream_commit_offset(shard_name.clone(), group_id.clone(), 0)
+                .await
+                .unwrap();
+        }
+
+        let storage_adapter = build_adapter("sqlite-reopen", false);
+        let res = storage_adapter
+            .stream_read(shard_name.clone(), group_id.clone(), None, None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].data, "m2".as_bytes().to_vec());
+
+        let offsets = storage_adapter
+            .stream_write(
+                shard_name.clone(),
+                vec![Record::build_b("m3".as_bytes().to_vec())],
+            )
+            .await
+            .unwrap();
+        assert_eq!(offsets, vec![2]);
+    }
+
+    #[tokio::test]
+    async fn sqlite_group_offset_and_retention() {
+        let storage_adapter = build_adapter("sqlite-retention", true);
+        let shard_name = "test".to_string();
+        let group_id = "group".to_string();
+        let shard_config = ShardConfig {
+            retention_record_num: 2,
+            ..Default::default()
+        };
+        storage_adapter
+            .create_shard(shard_name.clone(), shard_config)
+            .await
+            .unwrap();
+        let mut data = Vec::new();
+        for i in 0..5 {
+            data.push(Record::build_b(format!("m{}", i).as_bytes().to_vec()));
+        }
+        storage_adapter
+            .stream_write(shard_name.clone(), data)
+            .await
+            .unwrap();
+
+        storage_adapter
+            .stream_reset_group_offset(shard_name.clone(), group_id.clone(), 3)
+            .await
+            .unwrap();
+        assert_eq!(
+            storage_adapter
+                .stream_group_offset(shard_name.clone(), group_id.clone())
+                .await
+                .unwrap(),
+            Some(2)
+        );
+        assert_eq!(
+            storage_adapter
+                .stream_list_group(shard_name.clone())
+                .await
+                .unwrap(),
+            vec![group_id.clone()]
+        );
+
+        storage_adapter.stream_retention().await.unwrap();
+        let offset = storage_adapter
+            .stream_shard_offset(shard_name.clone())
+            .await
+            .unwrap();
+        assert_eq!(offset.earliest_offset, 3);
+        assert_eq!(offset.latest_offset, 5);
+        assert!(storage_adapter
+            .stream_read_by_offset(shard_name.clone(), 2)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -12,12 +12,63 @@
 // limitations under the License.
 
 
-use crate::record::Record;
+use crate::{compression::CompressionType, record::Record};
 use axum::async_trait;
 use common_base::error::common::CommonError;
+use serde::{Deserialize, Serialize};
 
-#[derive(Default)]
-pub struct ShardConfig {}
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShardCleanupPolicy {
+    // Records beyond the retention limits are deleted.
+    #[default]
+    Delete,
+    // Only the latest record of each key is kept, records without a key are never removed.
+    Compact,
+}
+
+impl ShardCleanupPolicy {
+    pub fn from_name(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "compact" => ShardCleanupPolicy::Compact,
+            _ => ShardCleanupPolicy::Delete,
+        }
+    }
+}
+
+// Retention and compression config of a shard. A retention limit of 0 means it is not enabled.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShardConfig {
+    // Maximum age of a record, in seconds.
+    pub retention_sec: u64,
+    // Maximum total size of the record payloads in the shard, in bytes.
+    pub retention_bytes: u64,
+    // Maximum number of records kept in the shard.
+    pub retention_record_num: u64,
+    pub cleanup_policy: ShardCleanupPolicy,
+    // Compression applied to the data of the records written to the shard.
+    pub compression: CompressionType,
+    // Records whose data is smaller than this size, in bytes, are not compressed.
+    pub compression_min_size: u64,
+}
+
+impl ShardConfig {
+    pub fn is_retention_enabled(&self) -> bool {
+        return self.cleanup_policy == ShardCleanupPolicy::Compact
+            || self.retention_sec > 0
+            || self.retention_bytes > 0
+            || self.retention_record_num > 0;
+    }
+}
+
+// Offset range of the records currently stored in a shard.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShardOffset {
+    // Offset of the oldest record that has not been removed.
+    pub earliest_offset: u128,
+    // Offset that will be assigned to the next written record (high watermark).
+    pub latest_offset: u128,
+}
 
 #[async_trait]
 pub trait StorageAdapter {
@@ -91,4 +142,35 @@ pub trait StorageAdapter {
         shard_name: String,
         key: String,
     ) -> Result<Option<Record>, CommonError>;
+
+    // Streaming storage model: List the groups that have committed an offset on the shard
+    async fn stream_list_group(&self, shard_name: String) -> Result<Vec<String>, CommonError>;
+
+    // Streaming storage model: Get the offset committed by the group, None if the group has not committed yet
+    async fn stream_group_offset(
+        &self,
+        shard_name: String,
+        group_id: String,
+    ) -> Result<Option<u128>, CommonError>;
+
+    // Streaming storage model: Get the earliest and latest offset of the shard
+    async fn stream_shard_offset(&self, shard_name: String) -> Result<ShardOffset, CommonError>;
+
+    // Streaming storage model: Get the offset of the first record created at or after the timestamp (in seconds)
+    async fn stream_offset_by_timestamp(
+        &self,
+        shard_name: String,
+        timestamp: u128,
+    ) -> Result<Option<u128>, CommonError>;
+
+    // Streaming storage model: Reset the group so that its next read starts from the given offset
+    async fn stream_reset_group_offset(
+        &self,
+        shard_name: String,
+        group_id: String,
+        offset: u128,
+    ) -> Result<(), CommonError>;
+
+    // Streaming storage model: Remove the records that exceed the retention config of each shard
+    async fn stream_retention(&self) -> Result<(), CommonError>;
 }